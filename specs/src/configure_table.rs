@@ -1,10 +1,11 @@
+use serde::Deserialize;
 use serde::Serialize;
 
 pub const WASM_PAGE_SIZE: u64 = 65536;
 
 const WASM_32_MAXIMAL_PAGES_DEFAULT: u32 = 65536;
 
-#[derive(Serialize, Debug, Clone, Copy)]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
 pub struct ConfigureTable {
     pub init_memory_pages: u32,
     pub maximal_memory_pages: u32,
@@ -59,24 +59,6 @@ pub fn encode_br_table<T: FromBn>(len: T) -> T {
 }
 
 pub fn encode_conversion<T: FromBn>(op: ConversionOp) -> T {
-    match op {
-        ConversionOp::I32WrapI64 => {
-            T::from_bn(&(BigUint::from(OpcodeClass::Conversion as u64)))
-                * T::from_bn(&OPCODE_CLASS_SHIFT)
-                + T::from_bn(&(BigUint::from(ConversionOp::I32WrapI64 as u64)))
-                    * T::from_bn(&OPCODE_ARG0_SHIFT)
-        }
-        ConversionOp::I64ExtendI32s => {
-            T::from_bn(&(BigUint::from(OpcodeClass::Conversion as u64)))
-                * T::from_bn(&OPCODE_CLASS_SHIFT)
-                + T::from_bn(&(BigUint::from(ConversionOp::I64ExtendI32s as u64)))
-                    * T::from_bn(&OPCODE_ARG0_SHIFT)
-        }
-        ConversionOp::I64ExtendI32u => {
-            T::from_bn(&(BigUint::from(OpcodeClass::Conversion as u64)))
-                * T::from_bn(&OPCODE_CLASS_SHIFT)
-                + T::from_bn(&(BigUint::from(ConversionOp::I64ExtendI32u as u64)))
-                    * T::from_bn(&OPCODE_ARG0_SHIFT)
-        }
-    }
+    T::from_bn(&(BigUint::from(OpcodeClass::Conversion as u64))) * T::from_bn(&OPCODE_CLASS_SHIFT)
+        + T::from_bn(&(BigUint::from(op as u64))) * T::from_bn(&OPCODE_ARG0_SHIFT)
 }
@@ -1,10 +1,17 @@
+use std::collections::HashSet;
+use std::ops::Deref;
+use std::ops::DerefMut;
+
+use serde::Deserialize;
 use serde::Serialize;
 
 use super::itable::InstructionTableEntry;
+use super::itable::OpcodeClass;
+use super::itable::OpcodeClassPlain;
 use crate::host_function::HostPlugin;
 use crate::step::StepInfo;
 
-#[derive(Clone, Debug, Serialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct EventTableEntry {
     pub eid: u32,
     pub sp: u32,
@@ -14,6 +21,17 @@ pub struct EventTableEntry {
     pub step_info: StepInfo,
 }
 
+impl EventTableEntry {
+    /// The opcode class proving the step. Trap steps are proved by the `Unreachable` class
+    /// whatever their instruction is.
+    pub fn opcode_class(&self) -> OpcodeClassPlain {
+        match self.step_info {
+            StepInfo::Trap { .. } => OpcodeClassPlain(OpcodeClass::Unreachable as usize),
+            _ => self.inst.opcode.clone().into(),
+        }
+    }
+}
+
 pub struct RestMops {
     rest_mops: Vec<u64>,
 }
@@ -38,24 +56,110 @@ impl Iterator for RestJops {
     }
 }
 
-#[derive(Debug, Default, Clone, Serialize)]
-pub struct EventTable(Vec<EventTableEntry>);
+/// The step limit of an event table is reached, see `EventTable::set_step_limit`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StepLimitExceeded {
+    pub limit: usize,
+}
+
+/// The entries of an event table, bounded by the step limit of the table.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct EventTableEntries {
+    entries: Vec<EventTableEntry>,
+    #[serde(skip)]
+    step_limit: Option<usize>,
+}
+
+impl EventTableEntries {
+    /// Appends `entry`, or unwinds with a `StepLimitExceeded` payload if the step limit is
+    /// reached.
+    pub fn push(&mut self, entry: EventTableEntry) {
+        if let Some(limit) = self.step_limit {
+            if self.entries.len() >= limit {
+                std::panic::resume_unwind(Box::new(StepLimitExceeded { limit }));
+            }
+        }
+
+        self.entries.push(entry);
+    }
+}
+
+impl Deref for EventTableEntries {
+    type Target = Vec<EventTableEntry>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.entries
+    }
+}
+
+impl DerefMut for EventTableEntries {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.entries
+    }
+}
+
+impl<'a> IntoIterator for &'a mut EventTableEntries {
+    type Item = &'a mut EventTableEntry;
+    type IntoIter = std::slice::IterMut<'a, EventTableEntry>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.iter_mut()
+    }
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct EventTable(EventTableEntries);
 
 impl EventTable {
     pub fn new(entries: Vec<EventTableEntry>) -> Self {
-        Self(entries)
+        Self(EventTableEntries {
+            entries,
+            step_limit: None,
+        })
     }
 
     pub fn entries(&self) -> &Vec<EventTableEntry> {
-        &self.0
+        &self.0.entries
     }
 
-    pub fn entries_mut(&mut self) -> &mut Vec<EventTableEntry> {
+    pub fn entries_mut(&mut self) -> &mut EventTableEntries {
         &mut self.0
     }
 
+    /// Bounds the steps of the table by `limit`. The tracer offers no hook on each step,
+    /// so an execution reaching the limit is aborted by unwinding from the push of its
+    /// step with a `StepLimitExceeded` payload, see `EventTableEntries::push`.
+    pub fn set_step_limit(&mut self, limit: Option<usize>) {
+        self.0.step_limit = limit;
+    }
+
+    /// The frames left by the execution, identified by their frame id and callee fid. A
+    /// frame is left by the last step of a return, or by a step of a trapped execution.
+    pub fn returned_frames(&self) -> HashSet<(u32, u32)> {
+        self.0
+            .entries
+            .iter()
+            .filter_map(|entry| match &entry.step_info {
+                StepInfo::Return {
+                    keep, keep_index, ..
+                } if keep.is_empty() || *keep_index as usize + 1 == keep.len() => {
+                    Some((entry.last_jump_eid, entry.inst.fid))
+                }
+                StepInfo::Trap {
+                    frame_id,
+                    callee_fid,
+                    ..
+                } => Some((*frame_id, *callee_fid)),
+                _ => None,
+            })
+            .collect()
+    }
+
     pub fn filter_foreign_entries(&self, foreign: HostPlugin) -> Vec<EventTableEntry> {
         self.0
+            .entries
             .clone()
             .into_iter()
             .filter(|entry| match entry.step_info {
@@ -1,4 +1,5 @@
 use serde::ser::SerializeStruct;
+use serde::Deserialize;
 use serde::Serialize;
 
 use crate::host_function::Signature;
@@ -7,7 +8,7 @@ use crate::types::ValueType;
 pub mod encode;
 mod table;
 
-#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, PartialOrd, Ord)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize, PartialOrd, Ord)]
 pub enum ExternalHostCallSignature {
     Argument,
     Return,
@@ -24,11 +25,11 @@ impl Into<Signature> for ExternalHostCallSignature {
         match self {
             ExternalHostCallSignature::Argument => Signature {
                 params: vec![ValueType::I64],
-                return_type: None,
+                return_types: vec![],
             },
             ExternalHostCallSignature::Return => Signature {
                 params: vec![],
-                return_type: Some(ValueType::I64),
+                return_types: vec![ValueType::I64],
             },
         }
     }
@@ -1,12 +1,13 @@
+use serde::Deserialize;
 use serde::Serialize;
 
 use crate::external_host_call_table::ExternalHostCallSignature;
 use crate::types::ValueType;
 
-#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Signature {
     pub params: Vec<ValueType>,
-    pub return_type: Option<ValueType>,
+    pub return_types: Vec<ValueType>,
 }
 
 #[derive(Debug)]
@@ -28,9 +29,13 @@ pub enum HostFunctionDesc {
     },
 }
 
-#[derive(Clone, Debug, Serialize, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[derive(Clone, Debug, Serialize, Deserialize, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub enum HostPlugin {
     HostInput = 0,
     Sha256,
     Require,
+    Keccak256,
+    Poseidon,
+    MerkleKv,
+    BabyJubjub,
 }
@@ -1,10 +1,13 @@
 use std::cmp::Ordering;
+use std::collections::BTreeMap;
 
 use crate::mtable::LocationType;
+use crate::mtable::MTable;
 use crate::mtable::VarType;
+use serde::Deserialize;
 use serde::Serialize;
 
-#[derive(Serialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct InitMemoryTableEntry {
     pub ltype: LocationType,
     pub is_mutable: bool,
@@ -15,11 +18,12 @@ pub struct InitMemoryTableEntry {
     pub value: u64,
 }
 
-#[derive(Serialize, Default, Debug, Clone)]
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
 pub struct InitMemoryTable {
     entries: Vec<InitMemoryTableEntry>,
     sorted_global_init_entries: Vec<InitMemoryTableEntry>,
     sorted_heap_init_entries: Vec<InitMemoryTableEntry>,
+    sorted_stack_init_entries: Vec<InitMemoryTableEntry>,
 }
 
 impl InitMemoryTable {
@@ -42,26 +46,95 @@ impl InitMemoryTable {
                 .collect(),
             sorted_global_init_entries: vec![],
             sorted_heap_init_entries: vec![],
+            sorted_stack_init_entries: vec![],
         };
         imtable.sort();
         imtable.merge();
+        imtable.index();
 
-        imtable.sorted_heap_init_entries = imtable
-            .entries
-            .iter()
-            .filter(|entry| entry.ltype == LocationType::Heap)
-            .map(|entry| entry.clone())
-            .collect();
-        imtable.sorted_global_init_entries = imtable
-            .entries
-            .iter()
-            .filter(|entry| entry.ltype == LocationType::Global)
-            .map(|entry| entry.clone())
-            .collect();
+        imtable
+    }
+
+    /// Builds the table of a memory snapshot without merging its entries, the snapshot
+    /// keeps the layout the previous segment has proved.
+    pub fn from_snapshot(entries: Vec<InitMemoryTableEntry>) -> Self {
+        let mut imtable = Self {
+            entries,
+            sorted_global_init_entries: vec![],
+            sorted_heap_init_entries: vec![],
+            sorted_stack_init_entries: vec![],
+        };
+        imtable.sort();
+        imtable.index();
 
         imtable
     }
 
+    /// The memory left behind by the accesses of `mtable`. An accessed location is split
+    /// out of the entry covering it and holds the value of its last access.
+    pub fn update(&self, mtable: &MTable) -> Self {
+        let mut last_accesses = BTreeMap::new();
+        for entry in mtable.entries() {
+            last_accesses.insert((entry.ltype, entry.offset), entry);
+        }
+
+        let mut entries = vec![];
+        for entry in self.entries.iter() {
+            let mut start_offset = entry.start_offset;
+
+            for (&(_, offset), _) in last_accesses
+                .range((entry.ltype, entry.start_offset)..=(entry.ltype, entry.end_offset))
+            {
+                if offset > start_offset {
+                    entries.push(InitMemoryTableEntry {
+                        start_offset,
+                        end_offset: offset - 1,
+                        ..entry.clone()
+                    });
+                }
+                start_offset = offset + 1;
+            }
+
+            if start_offset <= entry.end_offset {
+                entries.push(InitMemoryTableEntry {
+                    start_offset,
+                    ..entry.clone()
+                });
+            }
+        }
+
+        for ((ltype, offset), access) in last_accesses {
+            entries.push(InitMemoryTableEntry {
+                ltype,
+                is_mutable: access.is_mutable,
+                start_offset: offset,
+                end_offset: offset,
+                vtype: access.vtype,
+                value: access.value,
+            });
+        }
+
+        Self::from_snapshot(entries)
+    }
+
+    fn index(&mut self) {
+        self.sorted_heap_init_entries = self
+            .filter(LocationType::Heap)
+            .into_iter()
+            .cloned()
+            .collect();
+        self.sorted_global_init_entries = self
+            .filter(LocationType::Global)
+            .into_iter()
+            .cloned()
+            .collect();
+        self.sorted_stack_init_entries = self
+            .filter(LocationType::Stack)
+            .into_iter()
+            .cloned()
+            .collect();
+    }
+
     pub fn entries(&self) -> &Vec<InitMemoryTableEntry> {
         &self.entries
     }
@@ -99,7 +172,15 @@ impl InitMemoryTable {
                     }
                 }
             }
-            LocationType::Stack => unreachable!(),
+            LocationType::Stack => {
+                // The stack is only initialized by the snapshot of a continuation.
+                if let Ok(idx) = self
+                    .sorted_stack_init_entries
+                    .binary_search_by_key(&offset, |entry| entry.start_offset)
+                {
+                    return Some((offset, offset, self.sorted_stack_init_entries[idx].value));
+                }
+            }
         }
 
         None
@@ -114,7 +195,12 @@ impl InitMemoryTable {
         let mut merged_entries: Vec<_> = self
             .entries()
             .iter()
-            .filter(|entry| entry.ltype == LocationType::Global)
+            .filter(|entry| entry.ltype == LocationType::Stack)
+            .chain(
+                self.entries()
+                    .iter()
+                    .filter(|entry| entry.ltype == LocationType::Global),
+            )
             .map(|entry| entry.clone())
             .collect();
 
@@ -155,6 +241,7 @@ impl InitMemoryTable {
         }
 
         self.entries = merged_entries;
+        self.sort();
     }
 
     pub fn filter(&self, ltype: LocationType) -> Vec<&InitMemoryTableEntry> {
@@ -15,6 +15,7 @@ use crate::mtable::MemoryReadSize;
 use crate::mtable::MemoryStoreSize;
 use crate::types::ValueType;
 use num_bigint::BigUint;
+use serde::Deserialize;
 use serde::Serialize;
 use std::collections::HashSet;
 use strum_macros::EnumIter;
@@ -49,6 +50,8 @@ pub enum OpcodeClass {
     MemorySize,
     MemoryGrow,
     Conversion,
+    MemoryFill,
+    MemoryCopy,
     ForeignPluginStart,
 }
 
@@ -73,7 +76,7 @@ impl OpcodeClass {
             OpcodeClass::BrIf => 1,
             OpcodeClass::BrIfEqz => 1,
             OpcodeClass::BrTable => 1,
-            OpcodeClass::Unreachable => todo!(),
+            OpcodeClass::Unreachable => 0,
             OpcodeClass::Call => 0,
             OpcodeClass::CallHost => 1, // Push or pop
             OpcodeClass::CallIndirect => 1,
@@ -82,6 +85,8 @@ impl OpcodeClass {
             OpcodeClass::MemorySize => 1,
             OpcodeClass::MemoryGrow => 2,
             OpcodeClass::Conversion => 2,
+            OpcodeClass::MemoryFill => 1, // Write one heap block per step
+            OpcodeClass::MemoryCopy => 1, // Write one heap block per step
             OpcodeClass::ForeignPluginStart => 0,
         }
     }
@@ -96,7 +101,7 @@ impl OpcodeClass {
     }
 }
 
-#[derive(Clone, Copy, Debug, Eq, PartialEq, PartialOrd, Ord, Hash)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub struct OpcodeClassPlain(pub usize);
 
 impl OpcodeClassPlain {
@@ -105,14 +110,14 @@ impl OpcodeClassPlain {
     }
 }
 
-#[derive(Copy, Clone, Debug, Serialize, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
 pub enum UnaryOp {
     Ctz,
     Clz,
     Popcnt,
 }
 
-#[derive(Copy, Clone, Debug, Serialize, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
 pub enum BinOp {
     Add,
     Sub,
@@ -123,7 +128,7 @@ pub enum BinOp {
     SignedRem,
 }
 
-#[derive(Copy, Clone, Debug, Serialize, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
 pub enum ShiftOp {
     Shl,
     UnsignedShr,
@@ -132,7 +137,7 @@ pub enum ShiftOp {
     Rotr,
 }
 
-#[derive(Copy, Clone, Debug, Serialize, EnumIter, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, EnumIter, PartialEq, Eq, PartialOrd, Ord)]
 pub enum BitOp {
     And = 0,
     Or = 1,
@@ -149,7 +154,7 @@ impl BitOp {
     }
 }
 
-#[derive(Copy, Clone, Debug, Serialize, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
 pub enum RelOp {
     Eq,
     Ne,
@@ -163,26 +168,31 @@ pub enum RelOp {
     UnsignedLe,
 }
 
-#[derive(Copy, Clone, Debug, Serialize, EnumIter, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, EnumIter, PartialEq, Eq, PartialOrd, Ord)]
 pub enum TestOp {
     Eqz,
 }
 
-#[derive(Copy, Clone, Debug, Serialize, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
 pub enum ConversionOp {
     I32WrapI64,
     I64ExtendI32s,
     I64ExtendI32u,
+    I32Extend8S,
+    I32Extend16S,
+    I64Extend8S,
+    I64Extend16S,
+    I64Extend32S,
 }
 
-#[derive(Clone, Debug, Serialize, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
 pub struct BrTarget {
     pub drop: u32,
     pub keep: Vec<ValueType>,
     pub dst_pc: u32,
 }
 
-#[derive(Clone, Debug, Serialize, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
 pub enum Opcode {
     LocalGet {
         vtype: VarType,
@@ -286,6 +296,8 @@ pub enum Opcode {
     Conversion {
         class: ConversionOp,
     },
+    MemoryFill,
+    MemoryCopy,
 }
 
 impl Opcode {
@@ -299,6 +311,23 @@ impl Opcode {
         opcode_class.jops()
     }
 
+    pub fn may_trap(&self) -> bool {
+        match self {
+            Opcode::Unreachable
+            | Opcode::Load { .. }
+            | Opcode::Store { .. }
+            | Opcode::MemoryFill
+            | Opcode::MemoryCopy => true,
+            Opcode::Bin { class, .. } => match class {
+                BinOp::UnsignedDiv | BinOp::UnsignedRem | BinOp::SignedDiv | BinOp::SignedRem => {
+                    true
+                }
+                _ => false,
+            },
+            _ => false,
+        }
+    }
+
     pub fn vtype(&self) -> Option<VarType> {
         match self {
             Opcode::Const { vtype, .. } => Some(*vtype),
@@ -346,7 +375,6 @@ impl Into<BigUint> for Opcode {
                 (BigUint::from(OpcodeClass::Return as u64) << OPCODE_CLASS_SHIFT)
                     + (BigUint::from(drop as u64) << OPCODE_ARG0_SHIFT)
                     + (BigUint::from(keep.len() as u64) << OPCODE_ARG1_SHIFT)
-                    + keep.first().map_or(0u64, |x| VarType::from(*x) as u64)
             }
             Opcode::Bin { class, vtype } => {
                 (BigUint::from(OpcodeClass::Bin as u64) << OPCODE_CLASS_SHIFT)
@@ -447,6 +475,12 @@ impl Into<BigUint> for Opcode {
                 (BigUint::from(OpcodeClass::Conversion as u64) << OPCODE_CLASS_SHIFT)
                     + (BigUint::from(class as u64) << OPCODE_ARG0_SHIFT)
             }
+            Opcode::MemoryFill => {
+                BigUint::from(OpcodeClass::MemoryFill as u64) << OPCODE_CLASS_SHIFT
+            }
+            Opcode::MemoryCopy => {
+                BigUint::from(OpcodeClass::MemoryCopy as u64) << OPCODE_CLASS_SHIFT
+            }
         };
         assert!(bn < BigUint::from(1u64) << OPCODE_SHIFT);
         bn
@@ -485,6 +519,8 @@ impl Into<OpcodeClass> for Opcode {
             Opcode::MemorySize => OpcodeClass::MemorySize,
             Opcode::MemoryGrow => OpcodeClass::MemoryGrow,
             Opcode::Conversion { .. } => OpcodeClass::Conversion,
+            Opcode::MemoryFill => OpcodeClass::MemoryFill,
+            Opcode::MemoryCopy => OpcodeClass::MemoryCopy,
         }
     }
 }
@@ -501,7 +537,7 @@ impl Into<OpcodeClassPlain> for Opcode {
     }
 }
 
-#[derive(Clone, Debug, Serialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct InstructionTableEntry {
     pub fid: u32,
     pub iid: u32,
@@ -521,7 +557,7 @@ impl InstructionTableEntry {
         bn
     }
 }
-#[derive(Default, Serialize, Debug, Clone)]
+#[derive(Default, Serialize, Deserialize, Debug, Clone)]
 pub struct InstructionTable(Vec<InstructionTableEntry>);
 
 impl InstructionTable {
@@ -562,6 +598,11 @@ impl InstructionTable {
 
         self.entries().iter().for_each(|entry| {
             opcodeclass.insert(entry.opcode.clone().into());
+
+            // Traps are proved by the unreachable circuit whatever the trapping instruction is.
+            if entry.opcode.may_trap() {
+                opcodeclass.insert(OpcodeClassPlain(OpcodeClass::Unreachable as usize));
+            }
         });
 
         opcodeclass
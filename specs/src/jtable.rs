@@ -1,7 +1,15 @@
 use super::itable::InstructionTableEntry;
+use serde::Deserialize;
 use serde::Serialize;
 
-#[derive(Default, Serialize, Debug, Clone)]
+/// The frames of the entry function and the start function, their frame id is 0.
+pub const STATIC_FRAME_ENTRY_NUMBER: usize = 2;
+
+/// The maximal number of frames a segment inherits from the previous segment, the
+/// inherited frames are relabeled to `1..=INHERITED_FRAME_TABLE_ENTRIES`.
+pub const INHERITED_FRAME_TABLE_ENTRIES: usize = 256;
+
+#[derive(Default, Serialize, Deserialize, Debug, Clone)]
 pub struct StaticFrameEntry {
     pub frame_id: u32,
     pub next_frame_id: u32,
@@ -10,7 +18,13 @@ pub struct StaticFrameEntry {
     pub iid: u32,
 }
 
-#[derive(Debug, Serialize, Clone)]
+impl StaticFrameEntry {
+    pub fn is_inherited(&self) -> bool {
+        self.frame_id != 0
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct JumpTableEntry {
     // caller eid (unique)
     pub eid: u32,
@@ -25,7 +39,7 @@ impl JumpTableEntry {
     }
 }
 
-#[derive(Clone, Debug, Default, Serialize)]
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
 pub struct JumpTable(Vec<JumpTableEntry>);
 
 impl JumpTable {
@@ -0,0 +1,44 @@
+use serde::Deserialize;
+use serde::Serialize;
+
+/// The little-endian limbs of 64 bits of a node of the Merkle tree of the KV store.
+pub type Node = [u64; 4];
+
+/// An access of the KV store by `kv_get` or `kv_set`, a `kv_get` leaves the value as it is.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct KvAccess {
+    pub key: u64,
+    /// The value before the access, 0 for an absent key.
+    pub value: u64,
+    pub new_value: u64,
+    /// The siblings of the path of the key, from the leaf up.
+    pub siblings: Vec<Node>,
+    /// The root after the access.
+    pub root: Node,
+}
+
+#[derive(Default, Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct KvTable {
+    /// The root before the first access.
+    pub root: Node,
+    pub accesses: Vec<KvAccess>,
+}
+
+impl KvTable {
+    /// The root after the last access.
+    pub fn final_root(&self) -> Node {
+        self.accesses.last().map_or(self.root, |access| access.root)
+    }
+
+    /// The table of the accesses `start..end`.
+    pub fn slice(&self, start: usize, end: usize) -> KvTable {
+        KvTable {
+            root: if start == 0 {
+                self.root
+            } else {
+                self.accesses[start - 1].root
+            },
+            accesses: self.accesses[start..end].to_vec(),
+        }
+    }
+}
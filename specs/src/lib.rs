@@ -3,6 +3,8 @@
 #![deny(dead_code)]
 
 use std::env;
+use std::io;
+use std::io::Read;
 use std::io::Write;
 use std::path::PathBuf;
 
@@ -13,8 +15,12 @@ use imtable::InitMemoryTable;
 use itable::InstructionTable;
 use jtable::JumpTable;
 use jtable::StaticFrameEntry;
+use kvtable::KvTable;
 use mtable::MTable;
+use serde::Deserialize;
 use serde::Serialize;
+use state::InitializationState;
+use step::StepInfo;
 
 #[macro_use]
 extern crate lazy_static;
@@ -28,34 +34,139 @@ pub mod host_function;
 pub mod imtable;
 pub mod itable;
 pub mod jtable;
+pub mod kvtable;
 pub mod mtable;
+pub mod state;
 pub mod step;
 pub mod types;
 
-#[derive(Default, Serialize, Debug, Clone)]
+#[derive(Default, Serialize, Deserialize, Debug, Clone)]
 pub struct CompilationTable {
     pub itable: InstructionTable,
     pub imtable: InitMemoryTable,
     pub elem_table: ElemTable,
     pub configure_table: ConfigureTable,
     pub static_jtable: Vec<StaticFrameEntry>,
-    pub fid_of_entry: u32,
+    pub initialization_state: InitializationState<u32>,
 }
 
-#[derive(Default, Serialize, Clone)]
+#[derive(Default, Serialize, Deserialize, Clone)]
 pub struct ExecutionTable {
     pub etable: EventTable,
     pub mtable: MTable,
     pub jtable: JumpTable,
+    /// The accesses of the KV store of the host, proven against its roots.
+    pub kvtable: KvTable,
 }
 
-#[derive(Default, Clone)]
+impl ExecutionTable {
+    /// The frames still open when the execution stops, outermost first. The frames of the
+    /// entry and start functions are not included.
+    pub fn live_frames(&self, static_jtable: &Vec<StaticFrameEntry>) -> Vec<StaticFrameEntry> {
+        let returned_frames = self.etable.returned_frames();
+
+        let mut frames = static_jtable
+            .iter()
+            .filter(|entry| entry.is_inherited())
+            .cloned()
+            .chain(self.jtable.entries().iter().map(|entry| StaticFrameEntry {
+                frame_id: entry.eid,
+                next_frame_id: entry.last_jump_eid,
+                callee_fid: entry.callee_fid,
+                fid: entry.inst.fid,
+                iid: entry.inst.iid,
+            }))
+            .filter(|entry| !returned_frames.contains(&(entry.frame_id, entry.callee_fid)))
+            .collect::<Vec<_>>();
+        frames.sort_by_key(|entry| entry.frame_id);
+
+        frames
+    }
+}
+
+/// The magic prefix of the binary trace format.
+const TRACE_MAGIC: &[u8; 4] = b"ZKWT";
+/// Bumped whenever the layout of the tables changes, traces of another version are
+/// rejected instead of being misread.
+pub const TRACE_FORMAT_VERSION: u32 = 2;
+
+#[derive(Default, Serialize, Deserialize, Clone)]
 pub struct Tables {
     pub compilation_tables: CompilationTable,
     pub execution_tables: ExecutionTable,
+    /// The image the execution leaves behind, it is the compilation table of the next
+    /// segment of a continuation.
+    pub post_image_table: CompilationTable,
 }
 
 impl Tables {
+    /// Whether the execution terminates: its last step either traps or returns from the
+    /// entry function to the root frame. A segment followed by another one does not.
+    pub fn is_terminated(&self) -> bool {
+        let state = &self.post_image_table.initialization_state;
+
+        match self
+            .execution_tables
+            .etable
+            .entries()
+            .last()
+            .map(|entry| &entry.step_info)
+        {
+            Some(StepInfo::Trap {
+                frame_index,
+                frame_count,
+                ..
+            }) => *frame_index + 1 == *frame_count,
+            Some(StepInfo::Return {
+                keep, keep_index, ..
+            }) => {
+                (keep.is_empty() || *keep_index as usize + 1 == keep.len())
+                    && state.frame_id == 0
+                    && state.fid == 0
+                    && state.iid == 0
+            }
+            _ => false,
+        }
+    }
+
+    /// Writes the tables in the binary trace format: the magic and the format version,
+    /// followed by the tables encoded by bincode.
+    pub fn write<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writer.write_all(TRACE_MAGIC)?;
+        writer.write_all(&TRACE_FORMAT_VERSION.to_le_bytes())?;
+
+        bincode::serialize_into(writer, self)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+
+    /// Reads tables written by `write`.
+    pub fn read<R: Read>(mut reader: R) -> io::Result<Self> {
+        let mut magic = [0u8; 4];
+        reader.read_exact(&mut magic)?;
+        if &magic != TRACE_MAGIC {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "not a zkwasm trace",
+            ));
+        }
+
+        let mut version = [0u8; 4];
+        reader.read_exact(&mut version)?;
+        let version = u32::from_le_bytes(version);
+        if version != TRACE_FORMAT_VERSION {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "trace format version {} is not supported, expected {}",
+                    version, TRACE_FORMAT_VERSION
+                ),
+            ));
+        }
+
+        bincode::deserialize_from(reader)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+
     pub fn write_json(&self, dir: Option<PathBuf>) {
         fn write_file(folder: &PathBuf, filename: &str, buf: &String) {
             let mut folder = folder.clone();
@@ -1,18 +1,20 @@
+use std::collections::BTreeMap;
 use std::collections::HashSet;
 
+use serde::Deserialize;
 use serde::Serialize;
 use strum_macros::EnumIter;
 
 use crate::imtable::InitMemoryTable;
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Hash)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, Hash)]
 pub enum LocationType {
     Stack = 1,
     Heap = 2,
     Global = 3,
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Serialize, Hash, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize, Hash, Eq)]
 pub enum AccessType {
     Read = 1,
     Write = 2,
@@ -25,7 +27,9 @@ impl AccessType {
     }
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, EnumIter, Serialize, Hash, PartialOrd, Ord)]
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, EnumIter, Serialize, Deserialize, Hash, PartialOrd, Ord,
+)]
 pub enum VarType {
     I64 = 0,
     I32 = 1,
@@ -40,7 +44,9 @@ impl VarType {
     }
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, EnumIter, Serialize, Hash, Eq, PartialOrd, Ord)]
+#[derive(
+    Clone, Copy, Debug, PartialEq, EnumIter, Serialize, Deserialize, Hash, Eq, PartialOrd, Ord,
+)]
 pub enum MemoryReadSize {
     U8 = 1,
     S8,
@@ -51,7 +57,9 @@ pub enum MemoryReadSize {
     I64,
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, EnumIter, Serialize, Hash, Eq, PartialOrd, Ord)]
+#[derive(
+    Clone, Copy, Debug, PartialEq, EnumIter, Serialize, Deserialize, Hash, Eq, PartialOrd, Ord,
+)]
 pub enum MemoryStoreSize {
     Byte8 = 1,
     Byte16,
@@ -113,7 +121,7 @@ impl MemoryReadSize {
     }
 }
 
-#[derive(Clone, Debug, Serialize, Hash, Eq, PartialEq)]
+#[derive(Clone, Debug, Serialize, Deserialize, Hash, Eq, PartialEq)]
 pub struct MemoryTableEntry {
     pub eid: u32,
     /*
@@ -141,7 +149,7 @@ impl MemoryTableEntry {
     }
 }
 
-#[derive(Default, Debug, Serialize, Clone)]
+#[derive(Default, Debug, Serialize, Deserialize, Clone)]
 pub struct MTable(Vec<MemoryTableEntry>);
 
 impl MTable {
@@ -178,6 +186,38 @@ impl MTable {
             }
         });
 
+        // Only the snapshot of a continuation initializes the stack, a stack location is
+        // initialized if the segment reads it before writing it.
+        let mut first_stack_accesses = BTreeMap::<u32, &MemoryTableEntry>::new();
+        self.0
+            .iter()
+            .filter(|entry| entry.ltype == LocationType::Stack)
+            .for_each(|entry| {
+                let first = first_stack_accesses.entry(entry.offset).or_insert(entry);
+                if (entry.eid, entry.emid) < (first.eid, first.emid) {
+                    *first = entry;
+                }
+            });
+
+        for entry in first_stack_accesses.into_values() {
+            if entry.atype != AccessType::Read {
+                continue;
+            }
+
+            if let Some((_, _, value)) = imtable.try_find(entry.ltype, entry.offset) {
+                set.insert(MemoryTableEntry {
+                    eid: 0,
+                    emid: 0,
+                    offset: entry.offset,
+                    ltype: entry.ltype,
+                    atype: AccessType::Init,
+                    vtype: entry.vtype,
+                    is_mutable: true,
+                    value,
+                });
+            }
+        }
+
         let mut entries = set.into_iter().collect();
 
         self.0.append(&mut entries);
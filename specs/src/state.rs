@@ -0,0 +1,58 @@
+use serde::Deserialize;
+use serde::Serialize;
+
+/// The registers of the execution when the event table starts.
+///
+/// A whole run starts at the entry function with an empty stack. A segment of a
+/// continuation starts wherever the previous segment stopped, the frames inherited from
+/// the previous segment are relabeled to `1..=frame_id` so `eid` starts after them.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct InitializationState<T> {
+    pub eid: T,
+    pub fid: T,
+    pub iid: T,
+    pub frame_id: T,
+    pub sp: T,
+
+    pub initial_memory_pages: T,
+}
+
+impl<T> InitializationState<T> {
+    /// The fields in the order they are committed by the image checksum.
+    pub fn plain(&self) -> Vec<&T> {
+        vec![
+            &self.eid,
+            &self.fid,
+            &self.iid,
+            &self.frame_id,
+            &self.sp,
+            &self.initial_memory_pages,
+        ]
+    }
+
+    pub fn map<U>(&self, f: impl Fn(&T) -> U) -> InitializationState<U> {
+        InitializationState {
+            eid: f(&self.eid),
+            fid: f(&self.fid),
+            iid: f(&self.iid),
+            frame_id: f(&self.frame_id),
+            sp: f(&self.sp),
+
+            initial_memory_pages: f(&self.initial_memory_pages),
+        }
+    }
+}
+
+impl<T, E> InitializationState<Result<T, E>> {
+    pub fn transpose(self) -> Result<InitializationState<T>, E> {
+        Ok(InitializationState {
+            eid: self.eid?,
+            fid: self.fid?,
+            iid: self.iid?,
+            frame_id: self.frame_id?,
+            sp: self.sp?,
+
+            initial_memory_pages: self.initial_memory_pages?,
+        })
+    }
+}
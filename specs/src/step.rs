@@ -3,6 +3,7 @@ use crate::host_function::HostPlugin;
 use crate::host_function::Signature;
 use crate::itable::BinOp;
 use crate::itable::BitOp;
+use crate::itable::Opcode;
 use crate::itable::RelOp;
 use crate::itable::ShiftOp;
 use crate::itable::UnaryOp;
@@ -10,15 +11,21 @@ use crate::mtable::MemoryReadSize;
 use crate::mtable::MemoryStoreSize;
 use crate::mtable::VarType;
 use crate::types::ValueType;
+use serde::Deserialize;
 use serde::Serialize;
 
-#[derive(Debug, Clone, Serialize)]
+/// Branches keeping several values are traced as one step per kept value, the
+/// deepest value is moved first. `keep_index` is the index in `keep` of the value
+/// moved by the step, a branch without moved values is traced as a single step
+/// with `keep_index == 0`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum StepInfo {
     Br {
         dst_pc: u32,
         drop: u32,
         keep: Vec<ValueType>,
         keep_values: Vec<u64>,
+        keep_index: u32,
     },
     BrIfEqz {
         condition: i32,
@@ -26,6 +33,7 @@ pub enum StepInfo {
         drop: u32,
         keep: Vec<ValueType>,
         keep_values: Vec<u64>,
+        keep_index: u32,
     },
     BrIfNez {
         condition: i32,
@@ -33,6 +41,7 @@ pub enum StepInfo {
         drop: u32,
         keep: Vec<ValueType>,
         keep_values: Vec<u64>,
+        keep_index: u32,
     },
     BrTable {
         index: i32,
@@ -40,12 +49,14 @@ pub enum StepInfo {
         drop: u32,
         keep: Vec<ValueType>,
         keep_values: Vec<u64>,
+        keep_index: u32,
     },
     Return {
         drop: u32,
         keep: Vec<ValueType>,
         drop_values: Vec<u64>,
         keep_values: Vec<u64>,
+        keep_index: u32,
     },
 
     Drop,
@@ -72,7 +83,7 @@ pub enum StepInfo {
         function_name: String,
         signature: Signature,
         args: Vec<u64>,
-        ret_val: Option<u64>,
+        ret_vals: Vec<u64>,
         op_index_in_plugin: usize,
     },
     ExternalHostCall {
@@ -138,6 +149,32 @@ pub enum StepInfo {
         grow_size: i32,
         result: i32,
     },
+    /// One chunk of `memory.fill`, see `bulk_memory_chunk`.
+    /// `dst` and `size` describe the range remaining before this step, the circuit
+    /// proves a `size` below 2^(K-1) only.
+    MemoryFill {
+        dst: u32,
+        value: u32,
+        size: u32,
+        is_first: bool,
+        pre_block_value: u64,
+        updated_block_value: u64,
+    },
+    /// One chunk of `memory.copy`, see `bulk_memory_chunk`.
+    /// `dst`, `src` and `size` describe the range remaining before this step, the circuit
+    /// proves a `size` and a distance between `dst` and `src` below 2^(K-1) only.
+    /// `src_block_value` is the source block read by the chunk, see
+    /// `bulk_memory_copy_read_address`.
+    MemoryCopy {
+        dst: u32,
+        src: u32,
+        size: u32,
+        is_first: bool,
+        is_backward: bool,
+        src_block_value: u64,
+        pre_block_value: u64,
+        updated_block_value: u64,
+    },
 
     I32Const {
         value: i32,
@@ -218,4 +255,170 @@ pub enum StepInfo {
         result: i64,
         sign: bool,
     },
+    I32SignExtendI8 {
+        value: i32,
+        result: i32,
+    },
+    I32SignExtendI16 {
+        value: i32,
+        result: i32,
+    },
+    I64SignExtendI8 {
+        value: i64,
+        result: i64,
+    },
+    I64SignExtendI16 {
+        value: i64,
+        result: i64,
+    },
+    I64SignExtendI32 {
+        value: i64,
+        result: i64,
+    },
+
+    /// The instruction of the step trapped. The tracer records a single trap step,
+    /// `unwind_trap` of the runtime expands it into one step per open frame, innermost
+    /// first, each of them leaves the frame `frame_id` like a return does.
+    Trap {
+        code: TrapCode,
+        /// The address popped by a trapping load or store, or the address out of bounds
+        /// popped by a trapping `memory.fill` or `memory.copy`.
+        address: u32,
+        /// The size popped by a trapping `memory.fill` or `memory.copy`.
+        size: u32,
+        /// Whether `address` is the source of a trapping `memory.copy`, otherwise it is
+        /// the destination.
+        is_source: bool,
+        frame_index: u32,
+        frame_count: u32,
+        frame_id: u32,
+        callee_fid: u32,
+        caller_frame_id: u32,
+        caller_fid: u32,
+        caller_iid: u32,
+    },
+}
+
+/// The reason of a trapped execution, published as the last public output of the proof.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub enum TrapCode {
+    Unreachable = 1,
+    DivisionByZero,
+    /// A load, a store, a `memory.fill` or a `memory.copy` out of the allocated memory.
+    MemoryAccessOutOfBounds,
+    /// A signed division of the minimal integer by -1.
+    IntegerOverflow,
+}
+
+impl TrapCode {
+    /// Whether the circuit proves that `opcode` traps with the code. A trap of
+    /// `call_indirect`, on an undefined element or an unexpected signature, has no code:
+    /// an execution reaching it can not be proved.
+    pub fn is_proved_for(&self, opcode: &Opcode) -> bool {
+        match (self, opcode) {
+            (TrapCode::Unreachable, Opcode::Unreachable) => true,
+            (TrapCode::DivisionByZero, Opcode::Bin { class, .. }) => matches!(
+                class,
+                BinOp::UnsignedDiv | BinOp::UnsignedRem | BinOp::SignedDiv | BinOp::SignedRem
+            ),
+            (
+                TrapCode::IntegerOverflow,
+                Opcode::Bin {
+                    class: BinOp::SignedDiv,
+                    ..
+                },
+            ) => true,
+            (
+                TrapCode::MemoryAccessOutOfBounds,
+                Opcode::Load { .. }
+                | Opcode::Store { .. }
+                | Opcode::MemoryFill
+                | Opcode::MemoryCopy,
+            ) => true,
+            _ => false,
+        }
+    }
+
+    /// The stack values read by the trapping step to witness the trap of `opcode`, as
+    /// `(depth, vtype, value)` where `depth` counts from the top of the stack. The divisor
+    /// is read first, then the dividend of an overflow, or the address out of bounds
+    /// first, then the size of a `memory.fill` or a `memory.copy`.
+    pub fn witness(
+        &self,
+        opcode: &Opcode,
+        address: u32,
+        size: u32,
+        is_source: bool,
+    ) -> Vec<(u32, VarType, u64)> {
+        match (self, opcode) {
+            (TrapCode::Unreachable, _) => vec![],
+            (TrapCode::DivisionByZero, Opcode::Bin { vtype, .. }) => vec![(1, *vtype, 0)],
+            (TrapCode::IntegerOverflow, Opcode::Bin { vtype, .. }) => {
+                let (minus_one, min) = match vtype {
+                    VarType::I32 => (u32::MAX as u64, i32::MIN as u32 as u64),
+                    VarType::I64 => (u64::MAX, i64::MIN as u64),
+                };
+
+                vec![(1, *vtype, minus_one), (2, *vtype, min)]
+            }
+            (TrapCode::MemoryAccessOutOfBounds, Opcode::Load { .. }) => {
+                vec![(1, VarType::I32, address as u64)]
+            }
+            (TrapCode::MemoryAccessOutOfBounds, Opcode::Store { .. }) => {
+                vec![(2, VarType::I32, address as u64)]
+            }
+            (TrapCode::MemoryAccessOutOfBounds, Opcode::MemoryFill | Opcode::MemoryCopy) => vec![
+                (3 - is_source as u32, VarType::I32, address as u64),
+                (1, VarType::I32, size as u64),
+            ],
+            _ => unreachable!(),
+        }
+    }
+}
+
+/// Splits the remaining range of a bulk memory instruction into the chunk
+/// handled by a single event table step, returns `(dst, src, len)` of the chunk.
+///
+/// A chunk is an aligned 8-byte block of the destination when the range allows it,
+/// otherwise a single byte. The source of a block is aligned on the first step only,
+/// on the following ones it is shifted out of two source blocks, see
+/// `bulk_memory_copy_read_address`. An empty range is handled by one step with
+/// `len == 0`. Overlapping copies with `dst > src` are processed backward from the end.
+pub fn bulk_memory_chunk(
+    dst: u32,
+    src: u32,
+    size: u32,
+    is_first: bool,
+    is_backward: bool,
+) -> (u32, u32, u32) {
+    if size == 0 {
+        return (dst, src, 0);
+    }
+
+    let (block_dst, block_src) = if is_backward {
+        (dst + size - size.min(8), src + size - size.min(8))
+    } else {
+        (dst, src)
+    };
+
+    if size >= 8 && block_dst % 8 == 0 && (block_src % 8 == 0 || !is_first) {
+        (block_dst, block_src, 8)
+    } else if is_backward {
+        (dst + size - 1, src + size - 1, 1)
+    } else {
+        (dst, src, 1)
+    }
+}
+
+/// The address whose source block is read by the chunk `(src, len)` of `memory.copy`.
+///
+/// A forward block reads the block of its last byte, its bytes in the block before were
+/// read by the previous step. A backward block reads the block of its first byte, its
+/// bytes in the block after were read by the previous step. A byte reads its own block.
+pub fn bulk_memory_copy_read_address(src: u32, len: u32, is_backward: bool) -> u32 {
+    if len == 8 && !is_backward {
+        src + 7
+    } else {
+        src
+    }
 }
@@ -23,7 +23,7 @@ use super::rtable::RangeTableConfig;
 mod assign;
 mod configure;
 
-pub(in crate::circuits) const STEP_SIZE: usize = 17;
+pub(crate) const STEP_SIZE: usize = 17;
 
 #[derive(Clone, Copy)]
 pub(crate) enum BitTableOp {
@@ -9,7 +9,6 @@ use halo2_proofs::plonk::Expression;
 use halo2_proofs::plonk::VirtualCells;
 use num_bigint::BigUint;
 
-use crate::circuits::config::zkwasm_k;
 use crate::circuits::utils::bn_to_field;
 use crate::circuits::utils::Context;
 use crate::nextn;
@@ -77,15 +76,6 @@ impl<F: FieldExt> AllocatedU64Cell<F> {
     }
 }
 
-#[derive(Debug, Clone, Copy)]
-pub(crate) struct AllocatedU64CellWithFlagBit<F: FieldExt, const POS: usize> {
-    pub(crate) u16_cells_le: [AllocatedU16Cell<F>; 4],
-    pub(crate) u64_cell: AllocatedUnlimitedCell<F>,
-    pub(crate) flag_bit_cell: AllocatedBitCell<F>,
-    pub(crate) flag_u16_rem_cell: AllocatedCommonRangeCell<F>,
-    pub(crate) flag_u16_rem_diff_cell: AllocatedCommonRangeCell<F>,
-}
-
 #[derive(Debug, Clone, Copy)]
 pub(crate) struct AllocatedU64CellWithFlagBitDyn<F: FieldExt> {
     pub(crate) u16_cells_le: [AllocatedU16Cell<F>; 4],
@@ -119,11 +109,12 @@ macro_rules! define_cell {
                 ctx: &mut Context<'_, F>,
                 value: F,
             ) -> Result<AssignedCell<F, F>, Error> {
+                let limit: F = $limit(ctx.k);
                 debug_assert!(
-                    value <= $limit,
+                    value <= limit,
                     "assigned value {:?} exceeds the limit {:?}",
                     value,
-                    $limit
+                    limit
                 );
 
                 self.0.assign(ctx, value)
@@ -132,14 +123,14 @@ macro_rules! define_cell {
     };
 }
 
-define_cell!(AllocatedBitCell, F::one());
-define_cell!(
-    AllocatedCommonRangeCell,
-    F::from((1u64 << (zkwasm_k() - 1)) - 1)
-);
-define_cell!(AllocatedU8Cell, F::from(u8::MAX as u64));
-define_cell!(AllocatedU16Cell, F::from(u16::MAX as u64));
-define_cell!(AllocatedUnlimitedCell, -F::one());
+// The limit of a cell is a function of K of the circuit.
+define_cell!(AllocatedBitCell, |_| F::one());
+define_cell!(AllocatedCommonRangeCell, |k: u32| F::from(
+    (1u64 << (k - 1)) - 1
+));
+define_cell!(AllocatedU8Cell, |_| F::from(u8::MAX as u64));
+define_cell!(AllocatedU16Cell, |_| F::from(u16::MAX as u64));
+define_cell!(AllocatedUnlimitedCell, |_| -F::one());
 
 impl<F: FieldExt> AllocatedU64Cell<F> {
     pub(crate) fn assign(&self, ctx: &mut Context<'_, F>, value: u64) -> Result<(), Error> {
@@ -151,25 +142,6 @@ impl<F: FieldExt> AllocatedU64Cell<F> {
     }
 }
 
-impl<F: FieldExt, const POS: usize> AllocatedU64CellWithFlagBit<F, POS> {
-    pub(crate) fn assign(&self, ctx: &mut Context<'_, F>, value: u64) -> Result<(), Error> {
-        for i in 0..4 {
-            self.u16_cells_le[i].assign(ctx, ((value >> (i * 16)) & 0xffffu64).into())?;
-        }
-        self.u64_cell.assign(ctx, value.into())?;
-
-        let u16_value = (value >> (POS * 16)) & 0xffff;
-        let u16_flag_bit = u16_value >> 15;
-        let u16_rem = u16_value & 0x7fff;
-        let u16_rem_diff = 0x7fff - u16_rem;
-        self.flag_bit_cell.assign_u32(ctx, u16_flag_bit as u32)?;
-        self.flag_u16_rem_cell.assign_u32(ctx, u16_rem as u32)?;
-        self.flag_u16_rem_diff_cell
-            .assign_u32(ctx, u16_rem_diff as u32)?;
-        Ok(())
-    }
-}
-
 impl<F: FieldExt> AllocatedU64CellWithFlagBitDyn<F> {
     pub(crate) fn assign(
         &self,
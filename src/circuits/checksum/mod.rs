@@ -4,21 +4,20 @@ use halo2_proofs::circuit::Layouter;
 use halo2_proofs::plonk::ConstraintSystem;
 use halo2_proofs::plonk::Error;
 
-use self::poseidon::primitives::p128pow5t9::RATE;
-use self::poseidon::primitives::p128pow5t9::WIDTH;
-use self::poseidon::primitives::ConstantLength;
-use self::poseidon::primitives::P128Pow5T9;
-use self::poseidon::Hash;
-use self::poseidon::Pow5Chip;
-use self::poseidon::Pow5Config;
-
-pub mod poseidon;
+use crate::circuits::poseidon::primitives::p128pow5t9::RATE;
+use crate::circuits::poseidon::primitives::p128pow5t9::WIDTH;
+use crate::circuits::poseidon::primitives::ConstantLength;
+use crate::circuits::poseidon::primitives::P128Pow5T9;
+use crate::circuits::poseidon::Hash;
+use crate::circuits::poseidon::Pow5Chip;
+use crate::circuits::poseidon::Pow5Config;
 
 // image data: 8192
-// frame table: 4
-// event table: 1
+// init memory: 8192
+// event table initialization state: 6
+// frame table: 2 * (2 + 256)
 
-pub const L: usize = 8192 + 4 + 1;
+pub const L: usize = 8192 + 8192 + 6 + 2 * (2 + 256);
 
 #[derive(Clone)]
 pub(crate) struct CheckSumConfig<F: FieldExt> {
@@ -62,11 +62,15 @@ impl_cell!(AllocatedCommonRangeCell);
 impl_cell!(AllocatedU8Cell);
 impl_cell!(AllocatedU16Cell);
 impl_cell!(AllocatedUnlimitedCell);
-impl_cell!(AllocatedJumpTableLookupCell);
 impl_cell!(AllocatedBitTableLookupCell);
 
+/// A frame table entry looked up by a step, and whether the frame is left within the
+/// event table.
 #[derive(Debug, Clone, Copy)]
-pub(crate) struct AllocatedJumpTableLookupCell<F: FieldExt>(pub(crate) AllocatedCell<F>);
+pub(crate) struct AllocatedJumpTableLookupCell<F: FieldExt> {
+    pub(crate) entry_cell: AllocatedCell<F>,
+    pub(crate) returned_cell: AllocatedCell<F>,
+}
 
 #[derive(Debug, Clone, Copy)]
 pub(crate) struct AllocatedBitTableLookupCell<F: FieldExt>(pub(crate) AllocatedCell<F>);
@@ -161,7 +165,7 @@ pub(crate) enum EventTableCellType {
     BitTableLookup,
 }
 
-const BIT_COLUMNS: usize = 12;
+const BIT_COLUMNS: usize = 14;
 const U8_COLUMNS: usize = 1;
 const U16_COLUMNS: usize = 10;
 const COMMON_RANGE_COLUMNS: usize = 9;
@@ -279,7 +283,10 @@ impl<F: FieldExt> EventTableCellAllocator<F> {
                 .map(|_| {
                     let col = cols.next().unwrap();
                     jtable.configure_in_table(meta, "c8c. jtable_lookup in jtable", |meta| {
-                        curr!(meta, col)
+                        (
+                            curr!(meta, col) * fixed_curr!(meta, sel),
+                            nextn!(meta, col, 1) * fixed_curr!(meta, sel),
+                        )
                     });
                     col
                 })
@@ -374,7 +381,16 @@ impl<F: FieldExt> EventTableCellAllocator<F> {
     }
 
     pub(crate) fn alloc_jump_table_lookup_cell(&mut self) -> AllocatedJumpTableLookupCell<F> {
-        AllocatedJumpTableLookupCell(self.alloc(&EventTableCellType::JTableLookup))
+        // The lookup pairs the entry with the next row of the column.
+        let entry_cell = self.alloc(&EventTableCellType::JTableLookup);
+        let returned_cell = self.alloc(&EventTableCellType::JTableLookup);
+        assert_eq!(entry_cell.col, returned_cell.col);
+        assert_eq!(entry_cell.rot + 1, returned_cell.rot);
+
+        AllocatedJumpTableLookupCell {
+            entry_cell,
+            returned_cell,
+        }
     }
 
     pub(crate) fn alloc_bit_table_lookup_cell(&mut self) -> AllocatedBitTableLookupCell<F> {
@@ -474,38 +490,6 @@ impl<F: FieldExt> EventTableCellAllocator<F> {
         self.free_u64_cells.pop().expect("no more free u64 cells")
     }
 
-    pub(crate) fn alloc_u64_with_flag_bit_cell<const POS: usize>(
-        &mut self,
-        constraint_builder: &mut ConstraintBuilder<F>,
-    ) -> AllocatedU64CellWithFlagBit<F, POS> {
-        let value = self.free_u64_cells.pop().expect("no more free u64 cells");
-        let flag_bit_cell = self.alloc_bit_cell();
-        let flag_u16_rem_cell = self.alloc_common_range_cell();
-        let flag_u16_rem_diff_cell = self.alloc_common_range_cell();
-
-        constraint_builder.push(
-            "flag bit",
-            Box::new(move |meta| {
-                let flag_u16 = value.u16_cells_le[POS].expr(meta);
-                vec![
-                    (flag_bit_cell.expr(meta) * constant_from!(1 << 15)
-                        + flag_u16_rem_cell.expr(meta)
-                        - flag_u16),
-                    (flag_u16_rem_cell.expr(meta) + flag_u16_rem_diff_cell.expr(meta)
-                        - constant_from!((1 << 15) - 1)),
-                ]
-            }),
-        );
-
-        AllocatedU64CellWithFlagBit {
-            u16_cells_le: value.u16_cells_le,
-            u64_cell: value.u64_cell,
-            flag_bit_cell,
-            flag_u16_rem_cell,
-            flag_u16_rem_diff_cell,
-        }
-    }
-
     pub(crate) fn alloc_u64_with_flag_bit_cell_dyn(
         &mut self,
         constraint_builder: &mut ConstraintBuilder<F>,
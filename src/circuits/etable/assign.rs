@@ -4,11 +4,13 @@ use halo2_proofs::circuit::Cell;
 use halo2_proofs::plonk::Error;
 use log::debug;
 use specs::configure_table::ConfigureTable;
-use specs::itable::Opcode;
+use specs::host_function::HostPlugin;
 use specs::itable::OpcodeClassPlain;
+use specs::state::InitializationState;
+use specs::step::StepInfo;
 use std::collections::BTreeMap;
+use std::collections::HashSet;
 use std::rc::Rc;
-use wasmi::DEFAULT_VALUE_STACK_LIMIT;
 
 use super::EventTableChip;
 use super::EventTableOpcodeConfig;
@@ -20,10 +22,20 @@ use crate::circuits::utils::step_status::StepStatus;
 use crate::circuits::utils::table_entry::EventTableWithMemoryInfo;
 use crate::circuits::utils::Context;
 
+pub(in crate::circuits) struct EventTableTerminateCells<F: FieldExt> {
+    pub(in crate::circuits) fid: AssignedCell<F, F>,
+    pub(in crate::circuits) iid: AssignedCell<F, F>,
+    pub(in crate::circuits) frame_id: AssignedCell<F, F>,
+    pub(in crate::circuits) sp: AssignedCell<F, F>,
+    pub(in crate::circuits) allocated_memory_pages: AssignedCell<F, F>,
+    pub(in crate::circuits) terminated: AssignedCell<F, F>,
+}
+
 pub(in crate::circuits) struct EventTablePermutationCells<F: FieldExt> {
     pub(in crate::circuits) rest_mops: Option<Cell>,
     pub(in crate::circuits) rest_jops: Option<Cell>,
-    pub(in crate::circuits) fid_of_entry: AssignedCell<F, F>,
+    pub(in crate::circuits) initialization_state: InitializationState<AssignedCell<F, F>>,
+    pub(in crate::circuits) terminate_status: EventTableTerminateCells<F>,
 }
 
 impl<F: FieldExt> EventTableChip<F> {
@@ -39,13 +51,11 @@ impl<F: FieldExt> EventTableChip<F> {
             .iter()
             .rev()
             .fold((0, 0), |(rest_mops_sum, rest_jops_sum), entry| {
-                let op_config = op_configs
-                    .get(&entry.eentry.inst.opcode.clone().into())
-                    .unwrap();
+                let op_config = op_configs.get(&entry.eentry.opcode_class()).unwrap();
 
                 let acc = (
                     rest_mops_sum + op_config.memory_writing_ops(&entry.eentry),
-                    rest_jops_sum + op_config.jops(),
+                    rest_jops_sum + op_config.jops(&entry.eentry),
                 );
 
                 rest_ops.push(acc);
@@ -58,7 +68,11 @@ impl<F: FieldExt> EventTableChip<F> {
         rest_ops
     }
 
-    fn init(&self, ctx: &mut Context<'_, F>) -> Result<(), Error> {
+    fn init(
+        &self,
+        ctx: &mut Context<'_, F>,
+        configure_table: &ConfigureTable,
+    ) -> Result<(), Error> {
         let capability = self.max_available_rows / EVENT_TABLE_ENTRY_ROWS as usize;
 
         for _ in 0..capability {
@@ -69,6 +83,13 @@ impl<F: FieldExt> EventTableChip<F> {
                 || Ok(F::one()),
             )?;
 
+            ctx.region.assign_fixed(
+                || "etable: maximal memory pages",
+                self.config.common_config.maximal_memory_pages,
+                ctx.offset,
+                || Ok(F::from(configure_table.maximal_memory_pages as u64)),
+            )?;
+
             ctx.step(EVENT_TABLE_ENTRY_ROWS as usize);
         }
 
@@ -110,15 +131,66 @@ impl<F: FieldExt> EventTableChip<F> {
         Ok((rest_mops_cell.cell(), rest_mops_jell.cell()))
     }
 
+    /*
+     * The terminate status is kept on the disabled steps, it is read at the end of the
+     * table so that its cells do not depend on the number of steps.
+     */
+    fn assign_terminate_status(
+        &self,
+        ctx: &mut Context<'_, F>,
+        status: &Status,
+        terminated: bool,
+    ) -> Result<EventTableTerminateCells<F>, Error> {
+        let capability = self.max_available_rows / EVENT_TABLE_ENTRY_ROWS as usize;
+
+        let offset = ctx.offset;
+        ctx.offset = capability * EVENT_TABLE_ENTRY_ROWS as usize;
+
+        let common_config = &self.config.common_config;
+        let cells = EventTableTerminateCells {
+            fid: common_config
+                .fid_cell
+                .assign(ctx, F::from(status.fid as u64))?,
+            iid: common_config
+                .iid_cell
+                .assign(ctx, F::from(status.iid as u64))?,
+            frame_id: common_config
+                .frame_id_cell
+                .assign(ctx, F::from(status.last_jump_eid as u64))?,
+            sp: common_config
+                .sp_cell
+                .assign(ctx, F::from(status.sp as u64))?,
+            allocated_memory_pages: common_config
+                .mpages_cell
+                .assign(ctx, F::from(status.allocated_memory_pages as u64))?,
+            terminated: common_config
+                .terminated_cell
+                .assign(ctx, F::from(terminated as u64))?,
+        };
+
+        ctx.offset = offset;
+
+        Ok(cells)
+    }
+
     fn assign_entries(
         &self,
         ctx: &mut Context<'_, F>,
         op_configs: &BTreeMap<OpcodeClassPlain, Rc<Box<dyn EventTableOpcodeConfig<F>>>>,
         event_table: &EventTableWithMemoryInfo,
         configure_table: &ConfigureTable,
-        fid_of_entry: u32,
+        initialization_state: &InitializationState<u32>,
+        terminate_status: &Status,
+        terminated: bool,
+        returned_frames: &HashSet<(u32, u32)>,
         rest_ops: Vec<(u32, u32)>,
-    ) -> Result<AssignedCell<F, F>, Error> {
+    ) -> Result<
+        (
+            InitializationState<AssignedCell<F, F>>,
+            EventTableTerminateCells<F>,
+        ),
+        Error,
+    > {
         macro_rules! assign_advice {
             ($cell:ident, $value:expr) => {
                 self.config.common_config.$cell.assign(ctx, $value)?
@@ -142,26 +214,56 @@ impl<F: FieldExt> EventTableChip<F> {
             };
         }
 
+        // The initialization state is committed by the image checksum.
+        #[cfg(feature = "checksum")]
+        macro_rules! assign_state {
+            ($cell:ident, $value:expr) => {
+                assign_advice!($cell, $value)
+            };
+        }
+
+        #[cfg(not(feature = "checksum"))]
+        macro_rules! assign_state {
+            ($cell:ident, $value:expr) => {
+                assign_constant!($cell, $value)
+            };
+        }
+
         let mut host_public_inputs = 0u32;
         let mut external_host_call_call_index = 1u32;
 
+        let mut foreign_call_indexes: BTreeMap<HostPlugin, u32> = BTreeMap::new();
+
         assign_constant!(input_index_cell, F::from(host_public_inputs as u64));
+        assign_constant!(terminated_cell, F::zero());
         assign_constant!(
             external_host_call_index_cell,
             F::from(external_host_call_call_index as u64)
         );
-        assign_constant!(
-            mpages_cell,
-            F::from(configure_table.init_memory_pages as u64)
-        );
-        assign_constant!(sp_cell, F::from(DEFAULT_VALUE_STACK_LIMIT as u64 - 1));
-        assign_constant!(frame_id_cell, F::zero());
-        assign_constant!(eid_cell, F::one());
-        #[cfg(feature = "checksum")]
-        let fid_of_entry_cell = assign_advice!(fid_cell, F::from(fid_of_entry as u64));
-        #[cfg(not(feature = "checksum"))]
-        let fid_of_entry_cell = assign_constant!(fid_cell, F::from(fid_of_entry as u64));
-        assign_constant!(iid_cell, F::zero());
+        for (plugin, cell) in &self.config.common_config.foreign_call_index_cells {
+            ctx.region.assign_advice_from_constant(
+                || "etable foreign_call_index_cell",
+                cell.0.col,
+                ctx.offset + cell.0.rot as usize,
+                F::zero(),
+            )?;
+
+            foreign_call_indexes.insert(*plugin, 0);
+        }
+
+        let initialization_state_cells = InitializationState {
+            eid: assign_state!(eid_cell, F::from(initialization_state.eid as u64)),
+            fid: assign_state!(fid_cell, F::from(initialization_state.fid as u64)),
+            iid: assign_state!(iid_cell, F::from(initialization_state.iid as u64)),
+            frame_id: assign_state!(frame_id_cell, F::from(initialization_state.frame_id as u64)),
+            sp: assign_state!(sp_cell, F::from(initialization_state.sp as u64)),
+            initial_memory_pages: assign_state!(
+                mpages_cell,
+                F::from(initialization_state.initial_memory_pages as u64)
+            ),
+        };
+
+        let terminate_cells = self.assign_terminate_status(ctx, terminate_status, terminated)?;
 
         /*
          * Skip subsequent advice assignment in the first pass to enhance performance.
@@ -169,17 +271,10 @@ impl<F: FieldExt> EventTableChip<F> {
         {
             let assigned_cell = assign_advice!(enabled_cell, F::zero());
             if assigned_cell.value().is_none() {
-                return Ok(fid_of_entry_cell);
+                return Ok((initialization_state_cells, terminate_cells));
             }
         }
 
-        /*
-         * The length of event_table equals 0: without_witness
-         */
-        if event_table.0.len() == 0 {
-            return Ok(fid_of_entry_cell);
-        }
-
         let status = {
             let mut status = event_table
                 .0
@@ -194,23 +289,7 @@ impl<F: FieldExt> EventTableChip<F> {
                 })
                 .collect::<Vec<_>>();
 
-            let terminate_status = Status {
-                eid: status.last().unwrap().eid + 1,
-                fid: 0,
-                iid: 0,
-                sp: status.last().unwrap().sp
-                    + if let Opcode::Return { drop, .. } =
-                        &event_table.0.last().unwrap().eentry.inst.opcode
-                    {
-                        *drop
-                    } else {
-                        unreachable!()
-                    },
-                last_jump_eid: 0,
-                allocated_memory_pages: status.last().unwrap().allocated_memory_pages,
-            };
-
-            status.push(terminate_status);
+            status.push(terminate_status.clone());
 
             status
         };
@@ -224,10 +303,12 @@ impl<F: FieldExt> EventTableChip<F> {
                 current_external_host_call_index: external_host_call_call_index,
                 configure_table: *configure_table,
                 host_public_inputs,
+                foreign_call_indexes: &foreign_call_indexes,
+                returned_frames,
             };
 
             {
-                let class: OpcodeClassPlain = entry.eentry.inst.opcode.clone().into();
+                let class: OpcodeClassPlain = entry.eentry.opcode_class();
 
                 let op = self.config.common_config.ops[class.index()];
                 assign_advice_cell!(op, F::one());
@@ -241,6 +322,9 @@ impl<F: FieldExt> EventTableChip<F> {
                 external_host_call_index_cell,
                 F::from(external_host_call_call_index as u64)
             );
+            for (plugin, cell) in &self.config.common_config.foreign_call_index_cells {
+                cell.assign(ctx, F::from(foreign_call_indexes[plugin] as u64))?;
+            }
             assign_advice!(sp_cell, F::from(entry.eentry.sp as u64));
             assign_advice!(
                 mpages_cell,
@@ -252,9 +336,7 @@ impl<F: FieldExt> EventTableChip<F> {
             assign_advice!(iid_cell, F::from(entry.eentry.inst.iid as u64));
             assign_advice!(itable_lookup_cell, bn_to_field(&entry.eentry.inst.encode()));
 
-            let op_config = op_configs
-                .get(&entry.eentry.inst.opcode.clone().into())
-                .unwrap();
+            let op_config = op_configs.get(&entry.eentry.opcode_class()).unwrap();
             op_config.assign(ctx, &step_status, &entry)?;
 
             if op_config.is_host_public_input(&entry.eentry) {
@@ -263,45 +345,81 @@ impl<F: FieldExt> EventTableChip<F> {
             if op_config.is_external_host_call(&entry.eentry) {
                 external_host_call_call_index += 1;
             }
+            if let StepInfo::CallHost { plugin, .. } = &entry.eentry.step_info {
+                if let Some(index) = foreign_call_indexes.get_mut(plugin) {
+                    *index += 1;
+                }
+            }
 
             ctx.step(EVENT_TABLE_ENTRY_ROWS as usize);
         }
 
         // Assign terminate status
-        assign_advice!(eid_cell, F::from(status.last().unwrap().eid as u64));
-        assign_advice!(fid_cell, F::from(status.last().unwrap().fid as u64));
-        assign_advice!(iid_cell, F::from(status.last().unwrap().iid as u64));
-        assign_advice!(sp_cell, F::from(status.last().unwrap().sp as u64));
-        assign_advice!(
-            frame_id_cell,
-            F::from(status.last().unwrap().last_jump_eid as u64)
-        );
-        assign_advice!(
-            mpages_cell,
-            F::from(status.last().unwrap().allocated_memory_pages as u64)
-        );
+        assign_advice!(eid_cell, F::from(terminate_status.eid as u64));
         assign_advice!(input_index_cell, F::from(host_public_inputs as u64));
         assign_advice!(
             external_host_call_index_cell,
             F::from(external_host_call_call_index as u64)
         );
+        for (plugin, cell) in &self.config.common_config.foreign_call_index_cells {
+            cell.assign(ctx, F::from(foreign_call_indexes[plugin] as u64))?;
+        }
+
+        let capability = self.max_available_rows / EVENT_TABLE_ENTRY_ROWS as usize;
+        for _ in event_table.0.len()..capability {
+            assign_advice!(fid_cell, F::from(terminate_status.fid as u64));
+            assign_advice!(iid_cell, F::from(terminate_status.iid as u64));
+            assign_advice!(sp_cell, F::from(terminate_status.sp as u64));
+            assign_advice!(
+                frame_id_cell,
+                F::from(terminate_status.last_jump_eid as u64)
+            );
+            assign_advice!(
+                mpages_cell,
+                F::from(terminate_status.allocated_memory_pages as u64)
+            );
+            assign_advice!(terminated_cell, F::from(terminated as u64));
+
+            ctx.step(EVENT_TABLE_ENTRY_ROWS as usize);
+        }
 
-        Ok(fid_of_entry_cell)
+        Ok((initialization_state_cells, terminate_cells))
     }
 
+    /*
+     * The terminate status is the status of the step following the table: the status
+     * after the execution has returned or trapped, or the first step of the next segment.
+     * `terminated` tells the former from the latter.
+     */
     pub(in crate::circuits) fn assign(
         &self,
         ctx: &mut Context<'_, F>,
         event_table: &EventTableWithMemoryInfo,
         configure_table: &ConfigureTable,
-        fid_of_entry: u32,
+        initialization_state: &InitializationState<u32>,
+        post_initialization_state: &InitializationState<u32>,
+        terminate_frame_id: u32,
+        terminated: bool,
+        returned_frames: &HashSet<(u32, u32)>,
     ) -> Result<EventTablePermutationCells<F>, Error> {
         debug!("size of execution table: {}", event_table.0.len());
         assert!(event_table.0.len() * EVENT_TABLE_ENTRY_ROWS as usize <= self.max_available_rows);
 
         let rest_ops = self.compute_rest_mops_and_jops(&self.config.op_configs, event_table);
 
-        self.init(ctx)?;
+        let terminate_status = Status {
+            eid: event_table
+                .0
+                .last()
+                .map_or(initialization_state.eid, |entry| entry.eentry.eid + 1),
+            fid: post_initialization_state.fid,
+            iid: post_initialization_state.iid,
+            sp: post_initialization_state.sp,
+            last_jump_eid: terminate_frame_id,
+            allocated_memory_pages: post_initialization_state.initial_memory_pages,
+        };
+
+        self.init(ctx, configure_table)?;
         ctx.reset();
 
         let (rest_mops_cell, rest_jops_cell) = self.assign_rest_ops_first_step(
@@ -311,12 +429,15 @@ impl<F: FieldExt> EventTableChip<F> {
         )?;
         ctx.reset();
 
-        let fid_of_entry = self.assign_entries(
+        let (initialization_state, terminate_status) = self.assign_entries(
             ctx,
             &self.config.op_configs,
             event_table,
             configure_table,
-            fid_of_entry,
+            initialization_state,
+            &terminate_status,
+            terminated,
+            returned_frames,
             rest_ops,
         )?;
         ctx.reset();
@@ -324,7 +445,8 @@ impl<F: FieldExt> EventTableChip<F> {
         Ok(EventTablePermutationCells {
             rest_mops: Some(rest_mops_cell),
             rest_jops: Some(rest_jops_cell),
-            fid_of_entry,
+            initialization_state,
+            terminate_status,
         })
     }
 }
@@ -78,13 +78,15 @@ impl<'a, 'b, F: FieldExt> ConstraintBuilder<'a, 'b, F> {
         for (id, lookups) in self.lookups {
             let config = self.foreign_table_configs.get(&id).unwrap();
 
+            // A lookup is enabled by its op, so that the ops looking up foreign tables share
+            // their lookup cells.
             for (key, expr) in lookups {
                 config.configure_in_table(self.meta, key, &|meta| {
                     expr(meta)
                         .into_iter()
                         .map(|expr| {
-                            let (step_sel, _op_sel) = selector(meta);
-                            expr * step_sel
+                            let (step_sel, op_sel) = selector(meta);
+                            expr * step_sel * op_sel
                         })
                         .collect()
                 });
@@ -11,7 +11,6 @@ use super::traits::ConfigureLookupTable;
 use super::utils::step_status::StepStatus;
 use super::utils::table_entry::EventTableEntryWithMemoryInfo;
 use super::utils::Context;
-use super::CircuitConfigure;
 use crate::circuits::etable::op_configure::op_bin::BinConfigBuilder;
 use crate::circuits::etable::op_configure::op_bin_bit::BinBitConfigBuilder;
 use crate::circuits::etable::op_configure::op_bin_shift::BinShiftConfigBuilder;
@@ -31,6 +30,8 @@ use crate::circuits::etable::op_configure::op_load::LoadConfigBuilder;
 use crate::circuits::etable::op_configure::op_local_get::LocalGetConfigBuilder;
 use crate::circuits::etable::op_configure::op_local_set::LocalSetConfigBuilder;
 use crate::circuits::etable::op_configure::op_local_tee::LocalTeeConfigBuilder;
+use crate::circuits::etable::op_configure::op_memory_copy::MemoryCopyConfigBuilder;
+use crate::circuits::etable::op_configure::op_memory_fill::MemoryFillConfigBuilder;
 use crate::circuits::etable::op_configure::op_memory_grow::MemoryGrowConfigBuilder;
 use crate::circuits::etable::op_configure::op_memory_size::MemorySizeConfigBuilder;
 use crate::circuits::etable::op_configure::op_rel::RelConfigBuilder;
@@ -39,9 +40,15 @@ use crate::circuits::etable::op_configure::op_select::SelectConfigBuilder;
 use crate::circuits::etable::op_configure::op_store::StoreConfigBuilder;
 use crate::circuits::etable::op_configure::op_test::TestConfigBuilder;
 use crate::circuits::etable::op_configure::op_unary::UnaryConfigBuilder;
+use crate::circuits::etable::op_configure::op_unreachable::UnreachableConfigBuilder;
 use crate::constant_from;
 use crate::fixed_curr;
+use crate::foreign::babyjubjub_helper::etable_op_configure::ETableBabyJubjubHelperTableConfigBuilder;
+use crate::foreign::keccak_helper::etable_op_configure::ETableKeccakHelperTableConfigBuilder;
+use crate::foreign::kv_helper::etable_op_configure::ETableKvHelperTableConfigBuilder;
+use crate::foreign::poseidon_helper::etable_op_configure::ETablePoseidonHelperTableConfigBuilder;
 use crate::foreign::require_helper::etable_op_configure::ETableRequireHelperTableConfigBuilder;
+use crate::foreign::sha256_helper::etable_op_configure::ETableSha256HelperTableConfigBuilder;
 use crate::foreign::wasm_input_helper::etable_op_configure::ETableWasmInputHelperTableConfigBuilder;
 use crate::foreign::EventTableForeignCallConfigBuilder;
 use crate::foreign::ForeignTableConfig;
@@ -56,6 +63,7 @@ use halo2_proofs::plonk::Fixed;
 use halo2_proofs::plonk::VirtualCells;
 use specs::encode::instruction_table::encode_instruction_table_entry;
 use specs::etable::EventTableEntry;
+use specs::host_function::HostPlugin;
 use specs::itable::OpcodeClass;
 use specs::itable::OpcodeClassPlain;
 use std::collections::BTreeMap;
@@ -69,10 +77,22 @@ pub(crate) mod allocator;
 pub(crate) mod constraint_builder;
 
 pub(crate) const EVENT_TABLE_ENTRY_ROWS: i32 = 4;
-pub(crate) const OP_CAPABILITY: usize = 32;
+pub(crate) const OP_CAPABILITY: usize = 40;
 
+/// The lookup cells of the foreign plugins, shared by all of them as a step calls a single
+/// plugin.
 const FOREIGN_LOOKUP_CAPABILITY: usize = 2;
 
+/// The plugins whose calls are numbered, a plugin with a table of its own looks its calls
+/// up there by their numbers, so that the calls of the table are the calls of the steps.
+const INDEXED_FOREIGN_PLUGINS: [HostPlugin; 5] = [
+    HostPlugin::Sha256,
+    HostPlugin::Keccak256,
+    HostPlugin::Poseidon,
+    HostPlugin::MerkleKv,
+    HostPlugin::BabyJubjub,
+];
+
 #[derive(Clone)]
 pub struct EventTableCommonConfig<F: FieldExt> {
     enabled_cell: AllocatedBitCell<F>,
@@ -88,6 +108,8 @@ pub struct EventTableCommonConfig<F: FieldExt> {
     pub(crate) eid_cell: AllocatedCommonRangeCell<F>,
     fid_cell: AllocatedCommonRangeCell<F>,
     iid_cell: AllocatedCommonRangeCell<F>,
+    /// Whether a previous step has terminated the execution.
+    terminated_cell: AllocatedCommonRangeCell<F>,
 
     itable_lookup_cell: AllocatedUnlimitedCell<F>,
     brtable_lookup_cell: AllocatedUnlimitedCell<F>,
@@ -96,7 +118,11 @@ pub struct EventTableCommonConfig<F: FieldExt> {
     bit_table_lookup_cell: AllocatedBitTableLookupCell<F>,
     external_foreign_call_lookup_cell: AllocatedUnlimitedCell<F>,
 
-    circuit_configure: CircuitConfigure,
+    /// The number of the calls of each plugin of `INDEXED_FOREIGN_PLUGINS` preceding the step.
+    pub(crate) foreign_call_index_cells: BTreeMap<HostPlugin, AllocatedCommonRangeCell<F>>,
+
+    /// The maximal memory pages of the configure table, on the rows of the steps.
+    maximal_memory_pages: Column<Fixed>,
 }
 
 pub(in crate::circuits::etable) trait EventTableOpcodeConfigBuilder<F: FieldExt> {
@@ -130,12 +156,15 @@ pub trait EventTableOpcodeConfig<F: FieldExt> {
     fn jops_expr(&self, _meta: &mut VirtualCells<'_, F>) -> Option<Expression<F>> {
         None
     }
-    fn jops(&self) -> u32 {
+    fn jops(&self, _: &EventTableEntry) -> u32 {
         0
     }
     fn mops(&self, _meta: &mut VirtualCells<'_, F>) -> Option<Expression<F>> {
         None
     }
+    fn terminates(&self, _meta: &mut VirtualCells<'_, F>) -> Option<Expression<F>> {
+        None
+    }
     fn next_frame_id(
         &self,
         _meta: &mut VirtualCells<'_, F>,
@@ -190,7 +219,6 @@ impl<F: FieldExt> EventTableConfig<F> {
     pub(crate) fn configure(
         meta: &mut ConstraintSystem<F>,
         cols: &mut (impl Iterator<Item = Column<Advice>> + Clone),
-        circuit_configure: &CircuitConfigure,
         rtable: &RangeTableConfig<F>,
         image_table: &ImageTableConfig<F>,
         mtable: &MemoryTableConfig<F>,
@@ -198,9 +226,10 @@ impl<F: FieldExt> EventTableConfig<F> {
         bit_table: &BitTableConfig<F>,
         external_host_call_table: &ExternalHostCallTableConfig<F>,
         foreign_table_configs: &BTreeMap<&'static str, Box<dyn ForeignTableConfig<F>>>,
-        opcode_set: &HashSet<OpcodeClassPlain>,
+        opcode_selector: &HashSet<OpcodeClassPlain>,
     ) -> EventTableConfig<F> {
         let step_sel = meta.fixed_column();
+        let maximal_memory_pages = meta.fixed_column();
 
         let mut allocator =
             EventTableCellAllocator::new(meta, step_sel, rtable, mtable, jtable, bit_table, cols);
@@ -219,6 +248,7 @@ impl<F: FieldExt> EventTableConfig<F> {
         let eid_cell = allocator.alloc_common_range_cell();
         let fid_cell = allocator.alloc_common_range_cell();
         let iid_cell = allocator.alloc_common_range_cell();
+        let terminated_cell = allocator.alloc_common_range_cell();
 
         let itable_lookup_cell = allocator.alloc_unlimited_cell();
         let brtable_lookup_cell = allocator.alloc_unlimited_cell();
@@ -226,8 +256,12 @@ impl<F: FieldExt> EventTableConfig<F> {
         let pow_table_lookup_cell = allocator.alloc_unlimited_cell();
         let bit_table_lookup_cell = allocator.alloc_bit_table_lookup_cell();
         let external_foreign_call_lookup_cell = allocator.alloc_unlimited_cell();
+        let foreign_call_index_cells = INDEXED_FOREIGN_PLUGINS
+            .iter()
+            .map(|plugin| (*plugin, allocator.alloc_common_range_cell()))
+            .collect::<BTreeMap<_, _>>();
 
-        let mut foreign_table_reserved_lookup_cells = [(); FOREIGN_LOOKUP_CAPABILITY]
+        let foreign_table_reserved_lookup_cells = [(); FOREIGN_LOOKUP_CAPABILITY]
             .map(|_| allocator.alloc_unlimited_cell())
             .into_iter();
 
@@ -244,29 +278,28 @@ impl<F: FieldExt> EventTableConfig<F> {
             eid_cell,
             fid_cell,
             iid_cell,
+            terminated_cell,
             itable_lookup_cell,
             brtable_lookup_cell,
             jtable_lookup_cell,
             pow_table_lookup_cell,
             bit_table_lookup_cell,
             external_foreign_call_lookup_cell,
-            circuit_configure: circuit_configure.clone(),
+            foreign_call_index_cells: foreign_call_index_cells.clone(),
+            maximal_memory_pages,
         };
 
         let mut op_bitmaps: BTreeMap<OpcodeClassPlain, usize> = BTreeMap::new();
         let mut op_configs: BTreeMap<OpcodeClassPlain, Rc<Box<dyn EventTableOpcodeConfig<F>>>> =
             BTreeMap::new();
 
-        #[cfg(feature = "checksum")]
-        const OPTIMIZE_GATES: bool = false;
-        #[cfg(not(feature = "checksum"))]
-        const OPTIMIZE_GATES: bool = true;
-
+        // Only the ops of the image are configured, the instruction of a step is looked up in
+        // its image table.
         macro_rules! configure {
             ($op:expr, $x:ident) => {
                 let op = OpcodeClassPlain($op as usize);
 
-                if !OPTIMIZE_GATES || opcode_set.contains(&op) {
+                if opcode_selector.contains(&op) {
                     let foreign_table_configs = BTreeMap::new();
                     let mut constraint_builder =
                         ConstraintBuilder::new(meta, &foreign_table_configs);
@@ -312,9 +345,31 @@ impl<F: FieldExt> EventTableConfig<F> {
         configure!(OpcodeClass::BinBit, BinBitConfigBuilder);
         configure!(OpcodeClass::MemorySize, MemorySizeConfigBuilder);
         configure!(OpcodeClass::MemoryGrow, MemoryGrowConfigBuilder);
+        configure!(OpcodeClass::MemoryFill, MemoryFillConfigBuilder);
+        configure!(OpcodeClass::MemoryCopy, MemoryCopyConfigBuilder);
         configure!(OpcodeClass::BrTable, BrTableConfigBuilder);
         configure!(OpcodeClass::CallIndirect, CallIndirectConfigBuilder);
 
+        let op = OpcodeClassPlain(OpcodeClass::Unreachable as usize);
+        if opcode_selector.contains(&op) {
+            let mut constraint_builder = ConstraintBuilder::new(meta, foreign_table_configs);
+
+            // The trap code is published through the wasm input table, sharing its reserved
+            // lookup cells with the wasm input plugin.
+            let config = UnreachableConfigBuilder::configure(
+                &common_config,
+                &mut allocator.clone(),
+                &mut constraint_builder,
+                &mut foreign_table_reserved_lookup_cells.clone(),
+            );
+
+            constraint_builder
+                .finalize(|meta| (fixed_curr!(meta, step_sel), ops[op.index()].curr_expr(meta)));
+
+            op_bitmaps.insert(op, op.index());
+            op_configs.insert(op, Rc::new(config));
+        }
+
         let mut plugin_index = 0;
         macro_rules! configure_foreign {
             ($x:ident, $i:expr) => {
@@ -322,7 +377,7 @@ impl<F: FieldExt> EventTableConfig<F> {
                 let op = OpcodeClass::ForeignPluginStart as usize + $i;
                 let op = OpcodeClassPlain(op);
 
-                if !OPTIMIZE_GATES || opcode_set.contains(&op) {
+                if opcode_selector.contains(&op) {
                     let mut constraint_builder =
                         ConstraintBuilder::new(meta, foreign_table_configs);
 
@@ -330,7 +385,7 @@ impl<F: FieldExt> EventTableConfig<F> {
                         &common_config,
                         &mut allocator.clone(),
                         &mut constraint_builder,
-                        &mut foreign_table_reserved_lookup_cells,
+                        &mut foreign_table_reserved_lookup_cells.clone(),
                     );
 
                     constraint_builder.finalize(|meta| {
@@ -345,7 +400,12 @@ impl<F: FieldExt> EventTableConfig<F> {
             };
         }
         configure_foreign!(ETableWasmInputHelperTableConfigBuilder, 0);
+        configure_foreign!(ETableSha256HelperTableConfigBuilder, 1);
         configure_foreign!(ETableRequireHelperTableConfigBuilder, 2);
+        configure_foreign!(ETableKeccakHelperTableConfigBuilder, 3);
+        configure_foreign!(ETablePoseidonHelperTableConfigBuilder, 4);
+        configure_foreign!(ETableKvHelperTableConfigBuilder, 5);
+        configure_foreign!(ETableBabyJubjubHelperTableConfigBuilder, 6);
         drop(plugin_index);
 
         meta.create_gate("c1. enable seq", |meta| {
@@ -450,6 +510,21 @@ impl<F: FieldExt> EventTableConfig<F> {
             )]
         });
 
+        meta.create_gate("c5g. foreign call index change", |meta| {
+            foreign_call_index_cells
+                .iter()
+                .map(|(plugin, cell)| {
+                    let op = OpcodeClassPlain(
+                        OpcodeClass::ForeignPluginStart as usize + *plugin as usize,
+                    );
+
+                    (cell.next_expr(meta) - cell.curr_expr(meta) - ops[op.index()].curr_expr(meta))
+                        * enabled_cell.curr_expr(meta)
+                        * fixed_curr!(meta, step_sel)
+                })
+                .collect::<Vec<_>>()
+        });
+
         meta.create_gate("c5e. sp change", |meta| {
             vec![sum_ops_expr_with_init(
                 sp_cell.curr_expr(meta) - sp_cell.next_expr(meta),
@@ -525,6 +600,48 @@ impl<F: FieldExt> EventTableConfig<F> {
             vec![
                 (encode_instruction_table_entry(fid_cell.expr(meta), iid_cell.expr(meta), opcode)
                     - itable_lookup_cell.curr_expr(meta))
+                    * enabled_cell.curr_expr(meta)
+                    * fixed_curr!(meta, step_sel),
+            ]
+        });
+
+        /*
+         * The status after the last step is kept on the disabled steps, so that the
+         * terminate status can be read at the end of the table whatever the number of steps.
+         */
+        meta.create_gate("c6e. terminate status is kept", |meta| {
+            vec![
+                fid_cell.next_expr(meta) - fid_cell.curr_expr(meta),
+                iid_cell.next_expr(meta) - iid_cell.curr_expr(meta),
+                frame_id_cell.next_expr(meta) - frame_id_cell.curr_expr(meta),
+                sp_cell.next_expr(meta) - sp_cell.curr_expr(meta),
+                mpages_cell.next_expr(meta) - mpages_cell.curr_expr(meta),
+                terminated_cell.next_expr(meta) - terminated_cell.curr_expr(meta),
+            ]
+            .into_iter()
+            .map(|expr| {
+                expr * (constant_from!(1) - enabled_cell.curr_expr(meta))
+                    * fixed_curr!(meta, step_sel)
+            })
+            .collect::<Vec<_>>()
+        });
+
+        /*
+         * The terminated flag starts at 0, it is set by the step returning from the entry
+         * function or trapping, and no step follows it.
+         */
+        meta.create_gate("c6f. terminated change", |meta| {
+            vec![
+                sum_ops_expr_with_init(
+                    terminated_cell.curr_expr(meta) - terminated_cell.next_expr(meta),
+                    meta,
+                    &|meta, config: &Rc<Box<dyn EventTableOpcodeConfig<F>>>| {
+                        config.terminates(meta)
+                    },
+                    Some(&|meta| enabled_cell.curr_expr(meta)),
+                ),
+                terminated_cell.curr_expr(meta)
+                    * enabled_cell.curr_expr(meta)
                     * fixed_curr!(meta, step_sel),
             ]
         });
@@ -17,6 +17,8 @@ pub mod op_load;
 pub mod op_local_get;
 pub mod op_local_set;
 pub mod op_local_tee;
+pub mod op_memory_copy;
+pub mod op_memory_fill;
 pub mod op_memory_grow;
 pub mod op_memory_size;
 pub mod op_rel;
@@ -25,3 +27,4 @@ pub mod op_select;
 pub mod op_store;
 pub mod op_test;
 pub mod op_unary;
+pub mod op_unreachable;
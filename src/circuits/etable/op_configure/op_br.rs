@@ -14,12 +14,17 @@ use halo2_proofs::plonk::Expression;
 use halo2_proofs::plonk::VirtualCells;
 use specs::encode::opcode::encode_br;
 use specs::etable::EventTableEntry;
+use specs::itable::OpcodeClass;
 use specs::mtable::LocationType;
 use specs::mtable::VarType;
 use specs::step::StepInfo;
 
 pub struct BrConfig<F: FieldExt> {
+    is_first_cell: AllocatedBitCell<F>,
+    is_last_cell: AllocatedBitCell<F>,
     keep_cell: AllocatedBitCell<F>,
+    keep_count_cell: AllocatedCommonRangeCell<F>,
+    keep_offset_cell: AllocatedCommonRangeCell<F>,
     is_i32_cell: AllocatedBitCell<F>,
     drop_cell: AllocatedCommonRangeCell<F>,
     dst_pc_cell: AllocatedCommonRangeCell<F>,
@@ -36,12 +41,63 @@ impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for BrConfigBuilder {
         allocator: &mut EventTableCellAllocator<F>,
         constraint_builder: &mut ConstraintBuilder<F>,
     ) -> Box<dyn EventTableOpcodeConfig<F>> {
+        let is_first_cell = allocator.alloc_bit_cell();
+        let is_last_cell = allocator.alloc_bit_cell();
         let keep_cell = allocator.alloc_bit_cell();
+        let keep_count_cell = allocator.alloc_common_range_cell();
+        let keep_offset_cell = allocator.alloc_common_range_cell();
         let is_i32_cell = allocator.alloc_bit_cell();
         let drop_cell = allocator.alloc_common_range_cell();
         let dst_pc_cell = allocator.alloc_common_range_cell();
         let value_cell = allocator.alloc_u64_cell();
 
+        let ops = common_config.ops[OpcodeClass::Br as usize];
+
+        /*
+         * A branch keeping n values is proved in n steps, each of them moves one value,
+         * keep_offset counts the moved value from the stack top and goes from n down to 1.
+         */
+        constraint_builder.push(
+            "op_br keep offset",
+            Box::new(move |meta| {
+                vec![
+                    is_first_cell.expr(meta)
+                        * (keep_offset_cell.expr(meta) - keep_count_cell.expr(meta)),
+                    is_last_cell.expr(meta) * (keep_offset_cell.expr(meta) - keep_cell.expr(meta)),
+                    (constant_from!(1) - keep_cell.expr(meta)) * keep_offset_cell.expr(meta),
+                ]
+            }),
+        );
+
+        constraint_builder.push(
+            "op_br next step",
+            Box::new(move |meta| {
+                vec![
+                    ops.next_expr(meta) - constant_from!(1),
+                    is_first_cell.next_expr(meta),
+                    keep_cell.next_expr(meta) - keep_cell.expr(meta),
+                    keep_offset_cell.next_expr(meta) - keep_offset_cell.expr(meta)
+                        + constant_from!(1),
+                ]
+                .into_iter()
+                .map(|expr| expr * (constant_from!(1) - is_last_cell.expr(meta)))
+                .collect()
+            }),
+        );
+
+        constraint_builder.push(
+            "op_br prev step",
+            Box::new(move |meta| {
+                vec![
+                    ops.prev_expr(meta) - constant_from!(1),
+                    is_last_cell.prev_expr(meta),
+                ]
+                .into_iter()
+                .map(|expr| expr * (constant_from!(1) - is_first_cell.expr(meta)))
+                .collect()
+            }),
+        );
+
         let eid = common_config.eid_cell;
         let sp = common_config.sp_cell;
 
@@ -50,7 +106,7 @@ impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for BrConfigBuilder {
             constraint_builder,
             eid,
             move |____| constant_from!(LocationType::Stack as u64),
-            move |meta| sp.expr(meta) + constant_from!(1),
+            move |meta| sp.expr(meta) + keep_offset_cell.expr(meta),
             move |meta| is_i32_cell.expr(meta),
             move |meta| value_cell.u64_cell.expr(meta),
             move |meta| keep_cell.expr(meta),
@@ -60,14 +116,18 @@ impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for BrConfigBuilder {
             constraint_builder,
             eid,
             move |____| constant_from!(LocationType::Stack as u64),
-            move |meta| sp.expr(meta) + drop_cell.expr(meta) + constant_from!(1),
+            move |meta| sp.expr(meta) + drop_cell.expr(meta) + keep_offset_cell.expr(meta),
             move |meta| is_i32_cell.expr(meta),
             move |meta| value_cell.u64_cell.expr(meta),
             move |meta| keep_cell.expr(meta),
         );
 
         Box::new(BrConfig {
+            is_first_cell,
+            is_last_cell,
             keep_cell,
+            keep_count_cell,
+            keep_offset_cell,
             is_i32_cell,
             drop_cell,
             dst_pc_cell,
@@ -82,7 +142,7 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for BrConfig<F> {
     fn opcode(&self, meta: &mut VirtualCells<'_, F>) -> Expression<F> {
         encode_br(
             self.drop_cell.expr(meta),
-            self.keep_cell.expr(meta),
+            self.keep_count_cell.expr(meta),
             self.dst_pc_cell.expr(meta),
         )
     }
@@ -98,18 +158,27 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for BrConfig<F> {
                 drop,
                 keep,
                 keep_values,
+                keep_index,
                 dst_pc,
                 ..
             } => {
-                assert!(keep.len() <= 1);
+                let is_last = keep.is_empty() || *keep_index as usize + 1 == keep.len();
 
+                self.is_first_cell.assign_bool(ctx, *keep_index == 0)?;
+                self.is_last_cell.assign_bool(ctx, is_last)?;
+                self.keep_count_cell
+                    .assign(ctx, F::from(keep.len() as u64))?;
                 self.drop_cell.assign(ctx, F::from(*drop as u64))?;
 
                 if keep.len() > 0 {
-                    let keep_type: VarType = keep[0].into();
+                    let index = *keep_index as usize;
+                    let keep_offset = (keep.len() - index) as u32;
+                    let keep_type: VarType = keep[index].into();
 
                     self.keep_cell.assign(ctx, F::one())?;
-                    self.value_cell.assign(ctx, keep_values[0])?;
+                    self.keep_offset_cell
+                        .assign(ctx, F::from(keep_offset as u64))?;
+                    self.value_cell.assign(ctx, keep_values[index])?;
                     self.is_i32_cell.assign(ctx, F::from(keep_type as u64))?;
 
                     self.memory_table_lookup_stack_read.assign(
@@ -117,20 +186,20 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for BrConfig<F> {
                         entry.memory_rw_entires[0].start_eid,
                         step.current.eid,
                         entry.memory_rw_entires[0].end_eid,
-                        step.current.sp + 1,
+                        step.current.sp + keep_offset,
                         LocationType::Stack,
-                        VarType::from(keep[0]) == VarType::I32,
-                        keep_values[0],
+                        keep_type == VarType::I32,
+                        keep_values[index],
                     )?;
 
                     self.memory_table_lookup_stack_write.assign(
                         ctx,
                         step.current.eid,
                         entry.memory_rw_entires[1].end_eid,
-                        step.current.sp + *drop + 1,
+                        step.current.sp + *drop + keep_offset,
                         LocationType::Stack,
-                        VarType::from(keep[0]) == VarType::I32,
-                        keep_values[0],
+                        keep_type == VarType::I32,
+                        keep_values[index],
                     )?;
                 }
 
@@ -143,7 +212,7 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for BrConfig<F> {
     }
 
     fn sp_diff(&self, meta: &mut VirtualCells<'_, F>) -> Option<Expression<F>> {
-        Some(self.drop_cell.expr(meta))
+        Some(self.is_last_cell.expr(meta) * self.drop_cell.expr(meta))
     }
 
     fn mops(&self, meta: &mut VirtualCells<'_, F>) -> Option<Expression<F>> {
@@ -152,7 +221,7 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for BrConfig<F> {
 
     fn memory_writing_ops(&self, entry: &EventTableEntry) -> u32 {
         match &entry.step_info {
-            StepInfo::Br { keep, .. } => keep.len() as u32,
+            StepInfo::Br { keep, .. } => (!keep.is_empty()) as u32,
             _ => unreachable!(),
         }
     }
@@ -160,8 +229,12 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for BrConfig<F> {
     fn next_iid(
         &self,
         meta: &mut VirtualCells<'_, F>,
-        _: &EventTableCommonConfig<F>,
+        common_config: &EventTableCommonConfig<F>,
     ) -> Option<Expression<F>> {
-        Some(self.dst_pc_cell.expr(meta))
+        Some(
+            self.is_last_cell.expr(meta) * self.dst_pc_cell.expr(meta)
+                + (constant_from!(1) - self.is_last_cell.expr(meta))
+                    * common_config.iid_cell.curr_expr(meta),
+        )
     }
 }
@@ -30,7 +30,11 @@ pub struct BrIfConfig<F: FieldExt> {
     cond_is_zero_cell: AllocatedBitCell<F>,
     cond_is_not_zero_cell: AllocatedBitCell<F>,
 
+    is_first_cell: AllocatedBitCell<F>,
+    is_last_cell: AllocatedBitCell<F>,
     keep_cell: AllocatedBitCell<F>,
+    keep_count_cell: AllocatedCommonRangeCell<F>,
+    keep_offset_cell: AllocatedCommonRangeCell<F>,
     is_i32_cell: AllocatedBitCell<F>,
     drop_cell: AllocatedCommonRangeCell<F>,
     dst_pc_cell: AllocatedCommonRangeCell<F>,
@@ -67,12 +71,61 @@ impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for BrIfConfigBuilder {
             }),
         ));
 
+        let is_first_cell = allocator.alloc_bit_cell();
+        let is_last_cell = allocator.alloc_bit_cell();
         let keep_cell = allocator.alloc_bit_cell();
+        let keep_count_cell = allocator.alloc_common_range_cell();
+        let keep_offset_cell = allocator.alloc_common_range_cell();
         let is_i32_cell = allocator.alloc_bit_cell();
         let drop_cell = allocator.alloc_common_range_cell();
         let dst_pc_cell = allocator.alloc_common_range_cell();
         let value_cell = allocator.alloc_u64_cell();
 
+        let ops = common_config.ops[OpcodeClass::BrIf as usize];
+
+        constraint_builder.push(
+            "op_br_if keep offset",
+            Box::new(move |meta| {
+                vec![
+                    is_first_cell.expr(meta)
+                        * (keep_offset_cell.expr(meta)
+                            - cond_is_not_zero_cell.expr(meta) * keep_count_cell.expr(meta)),
+                    is_last_cell.expr(meta) * (keep_offset_cell.expr(meta) - keep_cell.expr(meta)),
+                    (constant_from!(1) - keep_cell.expr(meta)) * keep_offset_cell.expr(meta),
+                ]
+            }),
+        );
+
+        constraint_builder.push(
+            "op_br_if next step",
+            Box::new(move |meta| {
+                vec![
+                    ops.next_expr(meta) - constant_from!(1),
+                    is_first_cell.next_expr(meta),
+                    keep_cell.next_expr(meta) - keep_cell.expr(meta),
+                    keep_offset_cell.next_expr(meta) - keep_offset_cell.expr(meta)
+                        + constant_from!(1),
+                    cond_cell.u64_cell.next_expr(meta) - cond_cell.u64_cell.expr(meta),
+                ]
+                .into_iter()
+                .map(|expr| expr * (constant_from!(1) - is_last_cell.expr(meta)))
+                .collect()
+            }),
+        );
+
+        constraint_builder.push(
+            "op_br_if prev step",
+            Box::new(move |meta| {
+                vec![
+                    ops.prev_expr(meta) - constant_from!(1),
+                    is_last_cell.prev_expr(meta),
+                ]
+                .into_iter()
+                .map(|expr| expr * (constant_from!(1) - is_first_cell.expr(meta)))
+                .collect()
+            }),
+        );
+
         let eid = common_config.eid_cell;
         let sp = common_config.sp_cell;
 
@@ -84,7 +137,7 @@ impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for BrIfConfigBuilder {
             move |meta| sp.expr(meta) + constant_from!(1),
             move |____| constant_from!(1),
             move |meta| cond_cell.u64_cell.expr(meta),
-            move |____| constant_from!(1),
+            move |meta| is_first_cell.expr(meta),
         );
         let memory_table_lookup_stack_read_return_value = allocator
             .alloc_memory_table_lookup_read_cell(
@@ -92,10 +145,10 @@ impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for BrIfConfigBuilder {
                 constraint_builder,
                 eid,
                 move |____| constant_from!(LocationType::Stack as u64),
-                move |meta| sp.expr(meta) + constant_from!(2),
+                move |meta| sp.expr(meta) + keep_offset_cell.expr(meta) + constant_from!(1),
                 move |meta| is_i32_cell.expr(meta),
                 move |meta| value_cell.u64_cell.expr(meta),
-                move |meta| keep_cell.expr(meta) * cond_is_not_zero_cell.expr(meta),
+                move |meta| keep_cell.expr(meta),
             );
         let memory_table_lookup_stack_write_return_value = allocator
             .alloc_memory_table_lookup_write_cell(
@@ -103,10 +156,15 @@ impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for BrIfConfigBuilder {
                 constraint_builder,
                 eid,
                 move |____| constant_from!(LocationType::Stack as u64),
-                move |meta| sp.expr(meta) + drop_cell.expr(meta) + constant_from!(2),
+                move |meta| {
+                    sp.expr(meta)
+                        + drop_cell.expr(meta)
+                        + keep_offset_cell.expr(meta)
+                        + constant_from!(1)
+                },
                 move |meta| is_i32_cell.expr(meta),
                 move |meta| value_cell.u64_cell.expr(meta),
-                move |meta| keep_cell.expr(meta) * cond_is_not_zero_cell.expr(meta),
+                move |meta| keep_cell.expr(meta),
             );
 
         Box::new(BrIfConfig {
@@ -114,7 +172,11 @@ impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for BrIfConfigBuilder {
             cond_inv_cell,
             cond_is_zero_cell,
             cond_is_not_zero_cell,
+            is_first_cell,
+            is_last_cell,
             keep_cell,
+            keep_count_cell,
+            keep_offset_cell,
             is_i32_cell,
             drop_cell,
             dst_pc_cell,
@@ -132,7 +194,7 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for BrIfConfig<F> {
             &(BigUint::from(OpcodeClass::BrIf as u64) << OPCODE_CLASS_SHIFT)
         )) + self.drop_cell.expr(meta)
             * constant!(bn_to_field(&(BigUint::from(1u64) << OPCODE_ARG0_SHIFT)))
-            + self.keep_cell.expr(meta)
+            + self.keep_count_cell.expr(meta)
                 * constant!(bn_to_field(&(BigUint::from(1u64) << OPCODE_ARG1_SHIFT)))
             + self.dst_pc_cell.expr(meta)
     }
@@ -150,52 +212,70 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for BrIfConfig<F> {
                 drop,
                 keep,
                 keep_values,
+                keep_index,
             } => {
-                assert!(keep.len() <= 1);
-
                 let cond = *condition as u32 as u64;
+                let is_taken = *condition != 0;
+                let is_last =
+                    !is_taken || keep.is_empty() || *keep_index as usize + 1 == keep.len();
+
+                self.is_first_cell.assign_bool(ctx, *keep_index == 0)?;
+                self.is_last_cell.assign_bool(ctx, is_last)?;
+                self.keep_count_cell
+                    .assign(ctx, F::from(keep.len() as u64))?;
+
+                let mut memory_rw_entries = entry.memory_rw_entires.iter();
+
+                if *keep_index == 0 {
+                    let rw_entry = memory_rw_entries.next().unwrap();
 
-                self.memory_table_lookup_stack_read_cond.assign(
-                    ctx,
-                    entry.memory_rw_entires[0].start_eid,
-                    step.current.eid,
-                    entry.memory_rw_entires[0].end_eid,
-                    step.current.sp + 1,
-                    LocationType::Stack,
-                    true,
-                    cond,
-                )?;
+                    self.memory_table_lookup_stack_read_cond.assign(
+                        ctx,
+                        rw_entry.start_eid,
+                        step.current.eid,
+                        rw_entry.end_eid,
+                        step.current.sp + 1,
+                        LocationType::Stack,
+                        true,
+                        cond,
+                    )?;
+                }
 
                 self.drop_cell.assign(ctx, F::from(*drop as u64))?;
 
-                if keep.len() > 0 {
-                    let keep_type: VarType = keep[0].into();
+                if is_taken && keep.len() > 0 {
+                    let index = *keep_index as usize;
+                    let keep_offset = (keep.len() - index) as u32;
+                    let keep_type: VarType = keep[index].into();
 
                     self.keep_cell.assign(ctx, F::one())?;
-                    self.value_cell.assign(ctx, keep_values[0])?;
+                    self.keep_offset_cell
+                        .assign(ctx, F::from(keep_offset as u64))?;
+                    self.value_cell.assign(ctx, keep_values[index])?;
                     self.is_i32_cell.assign(ctx, F::from(keep_type as u64))?;
-                    if *condition != 0 {
-                        self.memory_table_lookup_stack_read_return_value.assign(
-                            ctx,
-                            entry.memory_rw_entires[1].start_eid,
-                            step.current.eid,
-                            entry.memory_rw_entires[1].end_eid,
-                            step.current.sp + 2,
-                            LocationType::Stack,
-                            VarType::from(keep[0]) == VarType::I32,
-                            keep_values[0],
-                        )?;
-
-                        self.memory_table_lookup_stack_write_return_value.assign(
-                            ctx,
-                            step.current.eid,
-                            entry.memory_rw_entires[2].end_eid,
-                            step.current.sp + *drop + 2,
-                            LocationType::Stack,
-                            VarType::from(keep[0]) == VarType::I32,
-                            keep_values[0],
-                        )?;
-                    }
+
+                    let rw_entry = memory_rw_entries.next().unwrap();
+                    self.memory_table_lookup_stack_read_return_value.assign(
+                        ctx,
+                        rw_entry.start_eid,
+                        step.current.eid,
+                        rw_entry.end_eid,
+                        step.current.sp + keep_offset + 1,
+                        LocationType::Stack,
+                        keep_type == VarType::I32,
+                        keep_values[index],
+                    )?;
+
+                    let rw_entry = memory_rw_entries.next().unwrap();
+                    self.memory_table_lookup_stack_write_return_value.assign(
+                        ctx,
+                        step.current.eid,
+                        rw_entry.end_eid,
+                        step.current.sp + *drop + keep_offset + 1,
+                        LocationType::Stack,
+                        keep_type == VarType::I32,
+                        keep_values[index],
+                    )?;
                 }
 
                 self.cond_cell.assign(ctx, cond)?;
@@ -214,24 +294,22 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for BrIfConfig<F> {
     }
 
     fn sp_diff(&self, meta: &mut VirtualCells<'_, F>) -> Option<Expression<F>> {
-        Some(constant_from!(1) + self.cond_is_not_zero_cell.expr(meta) * self.drop_cell.expr(meta))
+        Some(
+            self.is_last_cell.expr(meta)
+                * (constant_from!(1)
+                    + self.cond_is_not_zero_cell.expr(meta) * self.drop_cell.expr(meta)),
+        )
     }
 
     fn mops(&self, meta: &mut VirtualCells<'_, F>) -> Option<Expression<F>> {
-        Some(self.cond_is_not_zero_cell.expr(meta) * self.keep_cell.expr(meta))
+        Some(self.keep_cell.expr(meta))
     }
 
     fn memory_writing_ops(&self, entry: &EventTableEntry) -> u32 {
         match &entry.step_info {
             StepInfo::BrIfNez {
                 keep, condition, ..
-            } => {
-                if *condition == 0 {
-                    0
-                } else {
-                    keep.len() as u32
-                }
-            }
+            } => (*condition != 0 && !keep.is_empty()) as u32,
             _ => unreachable!(),
         }
     }
@@ -242,9 +320,12 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for BrIfConfig<F> {
         common_config: &EventTableCommonConfig<F>,
     ) -> Option<Expression<F>> {
         Some(
-            self.cond_is_not_zero_cell.expr(meta) * self.dst_pc_cell.expr(meta)
-                + self.cond_is_zero_cell.expr(meta)
-                    * (common_config.iid_cell.curr_expr(meta) + constant_from!(1)),
+            self.is_last_cell.expr(meta)
+                * (self.cond_is_not_zero_cell.expr(meta) * self.dst_pc_cell.expr(meta)
+                    + self.cond_is_zero_cell.expr(meta)
+                        * (common_config.iid_cell.curr_expr(meta) + constant_from!(1)))
+                + (constant_from!(1) - self.is_last_cell.expr(meta))
+                    * common_config.iid_cell.curr_expr(meta),
         )
     }
 }
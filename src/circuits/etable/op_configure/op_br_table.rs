@@ -16,12 +16,17 @@ use num_bigint::BigUint;
 use specs::encode::br_table::encode_br_table_entry;
 use specs::encode::opcode::encode_br_table;
 use specs::etable::EventTableEntry;
+use specs::itable::OpcodeClass;
 use specs::mtable::LocationType;
 use specs::mtable::VarType;
 use specs::step::StepInfo;
 
 pub struct BrTableConfig<F: FieldExt> {
+    is_first: AllocatedBitCell<F>,
+    is_last: AllocatedBitCell<F>,
     keep: AllocatedBitCell<F>,
+    keep_count: AllocatedCommonRangeCell<F>,
+    keep_offset: AllocatedCommonRangeCell<F>,
     keep_is_i32: AllocatedBitCell<F>,
     keep_value: AllocatedU64Cell<F>,
     drop: AllocatedCommonRangeCell<F>,
@@ -49,7 +54,11 @@ impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for BrTableConfigBuilder {
         allocator: &mut EventTableCellAllocator<F>,
         constraint_builder: &mut ConstraintBuilder<F>,
     ) -> Box<dyn EventTableOpcodeConfig<F>> {
+        let is_first = allocator.alloc_bit_cell();
+        let is_last = allocator.alloc_bit_cell();
         let keep = allocator.alloc_bit_cell();
+        let keep_count = allocator.alloc_common_range_cell();
+        let keep_offset = allocator.alloc_common_range_cell();
         let keep_is_i32 = allocator.alloc_bit_cell();
         let keep_value = allocator.alloc_u64_cell();
         let drop = allocator.alloc_common_range_cell();
@@ -90,6 +99,48 @@ impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for BrTableConfigBuilder {
             }),
         );
 
+        let ops = common_config.ops[OpcodeClass::BrTable as usize];
+
+        constraint_builder.push(
+            "op_br_table keep offset",
+            Box::new(move |meta| {
+                vec![
+                    is_first.expr(meta) * (keep_offset.expr(meta) - keep_count.expr(meta)),
+                    is_last.expr(meta) * (keep_offset.expr(meta) - keep.expr(meta)),
+                    (constant_from!(1) - keep.expr(meta)) * keep_offset.expr(meta),
+                ]
+            }),
+        );
+
+        constraint_builder.push(
+            "op_br_table next step",
+            Box::new(move |meta| {
+                vec![
+                    ops.next_expr(meta) - constant_from!(1),
+                    is_first.next_expr(meta),
+                    keep.next_expr(meta) - keep.expr(meta),
+                    keep_offset.next_expr(meta) - keep_offset.expr(meta) + constant_from!(1),
+                    expected_index.u64_cell.next_expr(meta) - expected_index.u64_cell.expr(meta),
+                ]
+                .into_iter()
+                .map(|expr| expr * (constant_from!(1) - is_last.expr(meta)))
+                .collect()
+            }),
+        );
+
+        constraint_builder.push(
+            "op_br_table prev step",
+            Box::new(move |meta| {
+                vec![
+                    ops.prev_expr(meta) - constant_from!(1),
+                    is_last.prev_expr(meta),
+                ]
+                .into_iter()
+                .map(|expr| expr * (constant_from!(1) - is_first.expr(meta)))
+                .collect()
+            }),
+        );
+
         let br_table_lookup = common_config.brtable_lookup_cell;
         let fid = common_config.fid_cell;
         let iid = common_config.iid_cell;
@@ -104,7 +155,7 @@ impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for BrTableConfigBuilder {
                             iid.expr(meta),
                             effective_index.expr(meta),
                             drop.expr(meta),
-                            keep.expr(meta),
+                            keep_count.expr(meta),
                             dst_iid.expr(meta),
                         ),
                 ]
@@ -122,16 +173,16 @@ impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for BrTableConfigBuilder {
             move |meta| sp.expr(meta) + constant_from!(1),
             move |____| constant_from!(1),
             move |meta| expected_index.expr(meta),
-            move |____| constant_from!(1),
+            move |meta| is_first.expr(meta),
         );
 
         let memory_table_lookup_stack_read_return_value = allocator
             .alloc_memory_table_lookup_read_cell(
-                "op_br_table stack read index",
+                "op_br_table stack read return value",
                 constraint_builder,
                 eid,
                 move |____| constant_from!(LocationType::Stack as u64),
-                move |meta| sp.expr(meta) + constant_from!(2),
+                move |meta| sp.expr(meta) + keep_offset.expr(meta) + constant_from!(1),
                 move |meta| keep_is_i32.expr(meta),
                 move |meta| keep_value.expr(meta),
                 move |meta| keep.expr(meta),
@@ -139,18 +190,24 @@ impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for BrTableConfigBuilder {
 
         let memory_table_lookup_stack_write_return_value = allocator
             .alloc_memory_table_lookup_write_cell(
-                "op_br stack write",
+                "op_br_table stack write return value",
                 constraint_builder,
                 eid,
                 move |____| constant_from!(LocationType::Stack as u64),
-                move |meta| sp.expr(meta) + drop.expr(meta) + constant_from!(2),
+                move |meta| {
+                    sp.expr(meta) + drop.expr(meta) + keep_offset.expr(meta) + constant_from!(1)
+                },
                 move |meta| keep_is_i32.expr(meta),
                 move |meta| keep_value.expr(meta),
                 move |meta| keep.expr(meta),
             );
 
         Box::new(BrTableConfig {
+            is_first,
+            is_last,
             keep,
+            keep_count,
+            keep_offset,
             keep_is_i32,
             keep_value,
             drop,
@@ -187,9 +244,8 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for BrTableConfig<F> {
                 drop,
                 keep,
                 keep_values,
+                keep_index,
             } => {
-                assert!(keep.len() <= 1);
-
                 let index = *index as u32 as u64;
                 let targets = match &entry.eentry.inst.opcode {
                     specs::itable::Opcode::BrTable { targets } => targets.clone(),
@@ -197,47 +253,64 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for BrTableConfig<F> {
                 };
                 let targets_len = targets.len() as u64;
 
+                self.is_first.assign_bool(ctx, *keep_index == 0)?;
+                self.is_last.assign_bool(
+                    ctx,
+                    keep.is_empty() || *keep_index as usize + 1 == keep.len(),
+                )?;
+                self.keep_count.assign(ctx, F::from(keep.len() as u64))?;
                 self.drop.assign(ctx, F::from(*drop as u64))?;
                 self.dst_iid.assign(ctx, F::from(*dst_pc as u64))?;
 
-                self.memory_table_lookup_stack_read_index.assign(
-                    ctx,
-                    entry.memory_rw_entires[0].start_eid,
-                    step.current.eid,
-                    entry.memory_rw_entires[0].end_eid,
-                    step.current.sp + 1,
-                    LocationType::Stack,
-                    true,
-                    index,
-                )?;
+                let mut memory_rw_entries = entry.memory_rw_entires.iter();
+
+                if *keep_index == 0 {
+                    let rw_entry = memory_rw_entries.next().unwrap();
+
+                    self.memory_table_lookup_stack_read_index.assign(
+                        ctx,
+                        rw_entry.start_eid,
+                        step.current.eid,
+                        rw_entry.end_eid,
+                        step.current.sp + 1,
+                        LocationType::Stack,
+                        true,
+                        index,
+                    )?;
+                }
 
                 if keep.len() > 0 {
-                    let keep_type: VarType = keep[0].into();
+                    let keep_index = *keep_index as usize;
+                    let keep_offset = (keep.len() - keep_index) as u32;
+                    let keep_type: VarType = keep[keep_index].into();
 
                     self.keep.assign(ctx, F::one())?;
-                    self.keep_value.assign(ctx, keep_values[0])?;
+                    self.keep_offset.assign(ctx, F::from(keep_offset as u64))?;
+                    self.keep_value.assign(ctx, keep_values[keep_index])?;
                     self.keep_is_i32
                         .assign_bool(ctx, keep_type == VarType::I32)?;
 
+                    let rw_entry = memory_rw_entries.next().unwrap();
                     self.memory_table_lookup_stack_read_return_value.assign(
                         ctx,
-                        entry.memory_rw_entires[1].start_eid,
+                        rw_entry.start_eid,
                         step.current.eid,
-                        entry.memory_rw_entires[1].end_eid,
-                        step.current.sp + 2,
+                        rw_entry.end_eid,
+                        step.current.sp + keep_offset + 1,
                         LocationType::Stack,
-                        VarType::from(keep[0]) == VarType::I32,
-                        keep_values[0],
+                        keep_type == VarType::I32,
+                        keep_values[keep_index],
                     )?;
 
+                    let rw_entry = memory_rw_entries.next().unwrap();
                     self.memory_table_lookup_stack_write_return_value.assign(
                         ctx,
                         step.current.eid,
-                        entry.memory_rw_entires[2].end_eid,
-                        step.current.sp + drop + 2,
+                        rw_entry.end_eid,
+                        step.current.sp + drop + keep_offset + 1,
                         LocationType::Stack,
-                        VarType::from(keep[0]) == VarType::I32,
-                        keep_values[0],
+                        keep_type == VarType::I32,
+                        keep_values[keep_index],
                     )?;
                 }
 
@@ -283,15 +356,19 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for BrTableConfig<F> {
     }
 
     fn sp_diff(&self, meta: &mut VirtualCells<'_, F>) -> Option<Expression<F>> {
-        Some(self.drop.expr(meta) + constant_from!(1))
+        Some(self.is_last.expr(meta) * (self.drop.expr(meta) + constant_from!(1)))
     }
 
     fn next_iid(
         &self,
         meta: &mut VirtualCells<'_, F>,
-        _common_config: &EventTableCommonConfig<F>,
+        common_config: &EventTableCommonConfig<F>,
     ) -> Option<Expression<F>> {
-        Some(self.dst_iid.expr(meta))
+        Some(
+            self.is_last.expr(meta) * self.dst_iid.expr(meta)
+                + (constant_from!(1) - self.is_last.expr(meta))
+                    * common_config.iid_cell.curr_expr(meta),
+        )
     }
 
     fn mops(&self, meta: &mut VirtualCells<'_, F>) -> Option<Expression<F>> {
@@ -300,7 +377,7 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for BrTableConfig<F> {
 
     fn memory_writing_ops(&self, entry: &EventTableEntry) -> u32 {
         match &entry.step_info {
-            StepInfo::BrTable { keep, .. } => keep.len() as u32,
+            StepInfo::BrTable { keep, .. } => (!keep.is_empty()) as u32,
             _ => unreachable!(),
         }
     }
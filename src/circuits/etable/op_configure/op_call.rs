@@ -17,6 +17,7 @@ use halo2_proofs::plonk::Expression;
 use halo2_proofs::plonk::VirtualCells;
 use specs::encode::frame_table::encode_frame_table_entry;
 use specs::encode::opcode::encode_call;
+use specs::etable::EventTableEntry;
 use specs::step::StepInfo;
 
 pub struct CallConfig<F: FieldExt> {
@@ -44,7 +45,7 @@ impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for CallConfigBuilder {
             "return frame table lookups",
             Box::new(move |meta| {
                 vec![
-                    frame_table_lookup.0.expr(meta)
+                    frame_table_lookup.entry_cell.expr(meta)
                         - JumpTableConfig::encode_lookup(
                             eid.expr(meta),
                             frame_id_cell.expr(meta),
@@ -77,7 +78,11 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for CallConfig<F> {
         match &entry.eentry.step_info {
             StepInfo::Call { index } => {
                 self.index_cell.assign(ctx, F::from(*index as u64))?;
-                self.frame_table_lookup.0.assign(
+                self.frame_table_lookup.returned_cell.assign_bool(
+                    ctx,
+                    step.returned_frames.contains(&(step.current.eid, *index)),
+                )?;
+                self.frame_table_lookup.entry_cell.assign(
                     ctx,
                     bn_to_field(&encode_frame_table_entry(
                         step.current.eid.into(),
@@ -98,7 +103,7 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for CallConfig<F> {
         Some(constant_from!(1))
     }
 
-    fn jops(&self) -> u32 {
+    fn jops(&self, _: &EventTableEntry) -> u32 {
         1
     }
 
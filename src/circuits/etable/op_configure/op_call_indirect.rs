@@ -19,6 +19,7 @@ use num_bigint::BigUint;
 use specs::encode::br_table::encode_elem_entry;
 use specs::encode::frame_table::encode_frame_table_entry;
 use specs::encode::opcode::encode_call_indirect;
+use specs::etable::EventTableEntry;
 use specs::mtable::LocationType;
 use specs::step::StepInfo;
 
@@ -93,7 +94,7 @@ impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for CallIndirectConfigBuilder
             "return frame table lookups",
             Box::new(move |meta| {
                 vec![
-                    frame_table_lookup.0.expr(meta)
+                    frame_table_lookup.entry_cell.expr(meta)
                         - JumpTableConfig::encode_lookup(
                             eid.expr(meta),
                             frame_id_cell.expr(meta),
@@ -161,7 +162,12 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for CallIndirectConfig<F> {
                     *offset as u64,
                 )?;
 
-                self.frame_table_lookup.0.assign_bn(
+                self.frame_table_lookup.returned_cell.assign_bool(
+                    ctx,
+                    step.returned_frames
+                        .contains(&(step.current.eid, *func_index)),
+                )?;
+                self.frame_table_lookup.entry_cell.assign_bn(
                     ctx,
                     &encode_frame_table_entry(
                         step.current.eid.into(),
@@ -187,7 +193,7 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for CallIndirectConfig<F> {
         Some(constant_from!(1))
     }
 
-    fn jops(&self) -> u32 {
+    fn jops(&self, _: &EventTableEntry) -> u32 {
         1
     }
 
@@ -20,7 +20,7 @@ use specs::mtable::VarType;
 use specs::step::StepInfo;
 
 pub struct ConversionConfig<F: FieldExt> {
-    value: AllocatedU64CellWithFlagBit<F, 1>,
+    value: AllocatedU64Cell<F>,
     value_is_i32: AllocatedBitCell<F>,
     res: AllocatedU64Cell<F>,
     res_is_i32: AllocatedBitCell<F>,
@@ -28,6 +28,20 @@ pub struct ConversionConfig<F: FieldExt> {
     is_i32_wrap_i64: AllocatedBitCell<F>,
     is_i64_extend_i32_u: AllocatedBitCell<F>,
     is_i64_extend_i32_s: AllocatedBitCell<F>,
+    is_i32_extend_8_s: AllocatedBitCell<F>,
+    is_i32_extend_16_s: AllocatedBitCell<F>,
+    is_i64_extend_8_s: AllocatedBitCell<F>,
+    is_i64_extend_16_s: AllocatedBitCell<F>,
+    is_i64_extend_32_s: AllocatedBitCell<F>,
+
+    // The u16 holding the sign bit of the extended part, split into two bytes.
+    value_leading_u16: AllocatedUnlimitedCell<F>,
+    value_leading_u16_u8_high: AllocatedU8Cell<F>,
+    value_leading_u16_u8_low: AllocatedU8Cell<F>,
+
+    flag_bit: AllocatedBitCell<F>,
+    flag_u8_rem: AllocatedCommonRangeCell<F>,
+    flag_u8_rem_diff: AllocatedCommonRangeCell<F>,
 
     memory_table_lookup_stack_read: AllocatedMemoryTableLookupReadCell<F>,
     memory_table_lookup_stack_write: AllocatedMemoryTableLookupWriteCell<F>,
@@ -41,7 +55,7 @@ impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for ConversionConfigBuilder {
         allocator: &mut EventTableCellAllocator<F>,
         constraint_builder: &mut ConstraintBuilder<F>,
     ) -> Box<dyn EventTableOpcodeConfig<F>> {
-        let value = allocator.alloc_u64_with_flag_bit_cell(constraint_builder);
+        let value = allocator.alloc_u64_cell();
         let value_is_i32 = allocator.alloc_bit_cell();
         let res = allocator.alloc_u64_cell();
         let res_is_i32 = allocator.alloc_bit_cell();
@@ -49,6 +63,29 @@ impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for ConversionConfigBuilder {
         let is_i32_wrap_i64 = allocator.alloc_bit_cell();
         let is_i64_extend_i32_u = allocator.alloc_bit_cell();
         let is_i64_extend_i32_s = allocator.alloc_bit_cell();
+        let is_i32_extend_8_s = allocator.alloc_bit_cell();
+        let is_i32_extend_16_s = allocator.alloc_bit_cell();
+        let is_i64_extend_8_s = allocator.alloc_bit_cell();
+        let is_i64_extend_16_s = allocator.alloc_bit_cell();
+        let is_i64_extend_32_s = allocator.alloc_bit_cell();
+
+        let value_leading_u16 = allocator.alloc_unlimited_cell();
+        let value_leading_u16_u8_high = allocator.alloc_u8_cell();
+        let value_leading_u16_u8_low = allocator.alloc_u8_cell();
+
+        let flag_bit = allocator.alloc_bit_cell();
+        let flag_u8_rem = allocator.alloc_common_range_cell();
+        let flag_u8_rem_diff = allocator.alloc_common_range_cell();
+
+        let is_extend_8_s = move |meta: &mut VirtualCells<'_, F>| {
+            is_i32_extend_8_s.expr(meta) + is_i64_extend_8_s.expr(meta)
+        };
+        let is_extend_16_s = move |meta: &mut VirtualCells<'_, F>| {
+            is_i32_extend_16_s.expr(meta) + is_i64_extend_16_s.expr(meta)
+        };
+        let is_extend_32_s = move |meta: &mut VirtualCells<'_, F>| {
+            is_i64_extend_i32_s.expr(meta) + is_i64_extend_32_s.expr(meta)
+        };
 
         constraint_builder.push(
             "op_conversion pick one",
@@ -57,6 +94,11 @@ impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for ConversionConfigBuilder {
                     is_i32_wrap_i64.expr(meta)
                         + is_i64_extend_i32_u.expr(meta)
                         + is_i64_extend_i32_s.expr(meta)
+                        + is_i32_extend_8_s.expr(meta)
+                        + is_i32_extend_16_s.expr(meta)
+                        + is_i64_extend_8_s.expr(meta)
+                        + is_i64_extend_16_s.expr(meta)
+                        + is_i64_extend_32_s.expr(meta)
                         - constant_from!(1),
                 ]
             }),
@@ -66,12 +108,15 @@ impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for ConversionConfigBuilder {
             "op_conversion type matches op",
             Box::new(move |meta| {
                 vec![
-                    is_i32_wrap_i64.expr(meta) * value_is_i32.expr(meta),
-                    is_i32_wrap_i64.expr(meta) * (res_is_i32.expr(meta) - constant_from!(1)),
-                    (is_i64_extend_i32_s.expr(meta) + is_i64_extend_i32_u.expr(meta))
-                        * (value_is_i32.expr(meta) - constant_from!(1)),
-                    (is_i64_extend_i32_s.expr(meta) + is_i64_extend_i32_u.expr(meta))
-                        * res_is_i32.expr(meta),
+                    value_is_i32.expr(meta)
+                        - is_i64_extend_i32_u.expr(meta)
+                        - is_i64_extend_i32_s.expr(meta)
+                        - is_i32_extend_8_s.expr(meta)
+                        - is_i32_extend_16_s.expr(meta),
+                    res_is_i32.expr(meta)
+                        - is_i32_wrap_i64.expr(meta)
+                        - is_i32_extend_8_s.expr(meta)
+                        - is_i32_extend_16_s.expr(meta),
                 ]
             }),
         );
@@ -99,13 +144,52 @@ impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for ConversionConfigBuilder {
         );
 
         constraint_builder.push(
-            "i64_extend_i32_s",
+            "op_conversion leading u16 decompose",
             Box::new(move |meta| {
-                let pad = value.flag_bit_cell.expr(meta) * constant_from!((u32::MAX as u64) << 32);
+                vec![
+                    value_leading_u16.expr(meta)
+                        - (is_extend_8_s(meta) + is_extend_16_s(meta))
+                            * value.u16_cells_le[0].expr(meta)
+                        - is_extend_32_s(meta) * value.u16_cells_le[1].expr(meta),
+                    value_leading_u16_u8_high.expr(meta) * constant_from!(1 << 8)
+                        + value_leading_u16_u8_low.expr(meta)
+                        - value_leading_u16.expr(meta),
+                ]
+            }),
+        );
+
+        constraint_builder.push(
+            "op_conversion flag",
+            Box::new(move |meta| {
+                let leading_u8 = is_extend_8_s(meta) * value_leading_u16_u8_low.expr(meta)
+                    + (constant_from!(1) - is_extend_8_s(meta))
+                        * value_leading_u16_u8_high.expr(meta);
 
                 vec![
-                    is_i64_extend_i32_s.expr(meta)
-                        * (pad + value.u64_cell.expr(meta) - res.u64_cell.expr(meta)),
+                    flag_bit.expr(meta) * constant_from!(128) + flag_u8_rem.expr(meta) - leading_u8,
+                    flag_u8_rem.expr(meta) + flag_u8_rem_diff.expr(meta) - constant_from!(127),
+                ]
+            }),
+        );
+
+        constraint_builder.push(
+            "op_conversion sign extension",
+            Box::new(move |meta| {
+                let picked = is_extend_8_s(meta) * value_leading_u16_u8_low.expr(meta)
+                    + is_extend_16_s(meta) * value.u16_cells_le[0].expr(meta)
+                    + is_extend_32_s(meta)
+                        * (value.u16_cells_le[1].expr(meta) * constant_from!(1 << 16)
+                            + value.u16_cells_le[0].expr(meta));
+
+                let padding = is_i32_extend_8_s.expr(meta) * constant_from!(0xffffff00u64)
+                    + is_i32_extend_16_s.expr(meta) * constant_from!(0xffff0000u64)
+                    + is_i64_extend_8_s.expr(meta) * constant_from!(0xffffffffffffff00u64)
+                    + is_i64_extend_16_s.expr(meta) * constant_from!(0xffffffffffff0000u64)
+                    + is_extend_32_s(meta) * constant_from!(0xffffffff00000000u64);
+
+                vec![
+                    (is_extend_8_s(meta) + is_extend_16_s(meta) + is_extend_32_s(meta))
+                        * (picked + flag_bit.expr(meta) * padding - res.u64_cell.expr(meta)),
                 ]
             }),
         );
@@ -143,6 +227,17 @@ impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for ConversionConfigBuilder {
             is_i32_wrap_i64,
             is_i64_extend_i32_u,
             is_i64_extend_i32_s,
+            is_i32_extend_8_s,
+            is_i32_extend_16_s,
+            is_i64_extend_8_s,
+            is_i64_extend_16_s,
+            is_i64_extend_32_s,
+            value_leading_u16,
+            value_leading_u16_u8_high,
+            value_leading_u16_u8_low,
+            flag_bit,
+            flag_u8_rem,
+            flag_u8_rem_diff,
             memory_table_lookup_stack_read,
             memory_table_lookup_stack_write,
         })
@@ -157,6 +252,16 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for ConversionConfig<F> {
                 * encode_conversion::<Expression<F>>(ConversionOp::I64ExtendI32s)
             + self.is_i64_extend_i32_u.expr(meta)
                 * encode_conversion::<Expression<F>>(ConversionOp::I64ExtendI32u)
+            + self.is_i32_extend_8_s.expr(meta)
+                * encode_conversion::<Expression<F>>(ConversionOp::I32Extend8S)
+            + self.is_i32_extend_16_s.expr(meta)
+                * encode_conversion::<Expression<F>>(ConversionOp::I32Extend16S)
+            + self.is_i64_extend_8_s.expr(meta)
+                * encode_conversion::<Expression<F>>(ConversionOp::I64Extend8S)
+            + self.is_i64_extend_16_s.expr(meta)
+                * encode_conversion::<Expression<F>>(ConversionOp::I64Extend16S)
+            + self.is_i64_extend_32_s.expr(meta)
+                * encode_conversion::<Expression<F>>(ConversionOp::I64Extend32S)
     }
 
     fn assign(
@@ -165,43 +270,128 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for ConversionConfig<F> {
         step: &StepStatus,
         entry: &EventTableEntryWithMemoryInfo,
     ) -> Result<(), Error> {
-        let (value, value_type, result, result_type) = match &entry.eentry.step_info {
-            StepInfo::I32WrapI64 { value, result } => {
-                self.is_i32_wrap_i64.assign(ctx, F::one())?;
-
-                (
-                    *value as u64,
-                    VarType::I64,
-                    *result as u32 as u64,
-                    VarType::I32,
-                )
-            }
-            StepInfo::I64ExtendI32 {
-                value,
-                result,
-                sign,
-            } => {
-                if *sign {
-                    self.is_i64_extend_i32_s.assign(ctx, F::one())?;
-                } else {
-                    self.is_i64_extend_i32_u.assign(ctx, F::one())?;
+        // The bit width of the sign-extended part, None if no sign extension happens.
+        let (value, value_type, result, result_type, sign_extend_bits) =
+            match &entry.eentry.step_info {
+                StepInfo::I32WrapI64 { value, result } => {
+                    self.is_i32_wrap_i64.assign(ctx, F::one())?;
+
+                    (
+                        *value as u64,
+                        VarType::I64,
+                        *result as u32 as u64,
+                        VarType::I32,
+                        None,
+                    )
                 }
+                StepInfo::I64ExtendI32 {
+                    value,
+                    result,
+                    sign,
+                } => {
+                    if *sign {
+                        self.is_i64_extend_i32_s.assign(ctx, F::one())?;
+                    } else {
+                        self.is_i64_extend_i32_u.assign(ctx, F::one())?;
+                    }
 
-                (
-                    *value as u32 as u64,
-                    VarType::I32,
-                    *result as u64,
-                    VarType::I64,
-                )
-            }
-            _ => unreachable!(),
-        };
+                    (
+                        *value as u32 as u64,
+                        VarType::I32,
+                        *result as u64,
+                        VarType::I64,
+                        if *sign { Some(32) } else { None },
+                    )
+                }
+                StepInfo::I32SignExtendI8 { value, result } => {
+                    self.is_i32_extend_8_s.assign(ctx, F::one())?;
+
+                    (
+                        *value as u32 as u64,
+                        VarType::I32,
+                        *result as u32 as u64,
+                        VarType::I32,
+                        Some(8),
+                    )
+                }
+                StepInfo::I32SignExtendI16 { value, result } => {
+                    self.is_i32_extend_16_s.assign(ctx, F::one())?;
+
+                    (
+                        *value as u32 as u64,
+                        VarType::I32,
+                        *result as u32 as u64,
+                        VarType::I32,
+                        Some(16),
+                    )
+                }
+                StepInfo::I64SignExtendI8 { value, result } => {
+                    self.is_i64_extend_8_s.assign(ctx, F::one())?;
+
+                    (
+                        *value as u64,
+                        VarType::I64,
+                        *result as u64,
+                        VarType::I64,
+                        Some(8),
+                    )
+                }
+                StepInfo::I64SignExtendI16 { value, result } => {
+                    self.is_i64_extend_16_s.assign(ctx, F::one())?;
+
+                    (
+                        *value as u64,
+                        VarType::I64,
+                        *result as u64,
+                        VarType::I64,
+                        Some(16),
+                    )
+                }
+                StepInfo::I64SignExtendI32 { value, result } => {
+                    self.is_i64_extend_32_s.assign(ctx, F::one())?;
+
+                    (
+                        *value as u64,
+                        VarType::I64,
+                        *result as u64,
+                        VarType::I64,
+                        Some(32),
+                    )
+                }
+                _ => unreachable!(),
+            };
 
         self.value.assign(ctx, value)?;
         self.res.assign(ctx, result)?;
         self.value_is_i32.assign(ctx, F::from(value_type as u64))?;
         self.res_is_i32.assign(ctx, F::from(result_type as u64))?;
 
+        if let Some(bits) = sign_extend_bits {
+            let leading_u16 = if bits == 32 {
+                (value >> 16) & 0xffff
+            } else {
+                value & 0xffff
+            };
+            let leading_u8 = if bits == 8 {
+                leading_u16 & 0xff
+            } else {
+                leading_u16 >> 8
+            };
+
+            self.value_leading_u16.assign(ctx, leading_u16.into())?;
+            self.value_leading_u16_u8_high
+                .assign(ctx, (leading_u16 >> 8).into())?;
+            self.value_leading_u16_u8_low
+                .assign(ctx, (leading_u16 & 0xff).into())?;
+
+            self.flag_bit.assign(ctx, (leading_u8 >> 7).into())?;
+            self.flag_u8_rem.assign(ctx, (leading_u8 & 0x7f).into())?;
+            self.flag_u8_rem_diff
+                .assign(ctx, (0x7f - (leading_u8 & 0x7f)).into())?;
+        } else {
+            self.flag_u8_rem_diff.assign(ctx, 0x7fu64.into())?;
+        }
+
         self.memory_table_lookup_stack_read.assign(
             ctx,
             entry.memory_rw_entires[0].start_eid,
@@ -0,0 +1,721 @@
+use crate::circuits::cell::*;
+use crate::circuits::etable::allocator::*;
+use crate::circuits::etable::ConstraintBuilder;
+use crate::circuits::etable::EventTableCommonConfig;
+use crate::circuits::etable::EventTableOpcodeConfig;
+use crate::circuits::etable::EventTableOpcodeConfigBuilder;
+use crate::circuits::utils::bn_to_field;
+use crate::circuits::utils::step_status::StepStatus;
+use crate::circuits::utils::table_entry::EventTableEntryWithMemoryInfo;
+use crate::circuits::utils::Context;
+use crate::constant;
+use crate::constant_from;
+use crate::constant_from_bn;
+use halo2_proofs::arithmetic::FieldExt;
+use halo2_proofs::plonk::Error;
+use halo2_proofs::plonk::Expression;
+use halo2_proofs::plonk::VirtualCells;
+use num_bigint::BigUint;
+use specs::configure_table::WASM_PAGE_SIZE;
+use specs::etable::EventTableEntry;
+use specs::itable::OpcodeClass;
+use specs::itable::OPCODE_CLASS_SHIFT;
+use specs::mtable::LocationType;
+use specs::step::bulk_memory_chunk;
+use specs::step::bulk_memory_copy_read_address;
+use specs::step::StepInfo;
+
+/*
+ * memory.copy is proved by a sequence of steps sharing the same iid, each step
+ * copies either an aligned 8-byte block or a single byte. Stack operands are
+ * read by the first step and popped by the last step.
+ *
+ * The source of a block need not be aligned except on the first step: a forward
+ * block reads the source block of its last byte and takes its first bytes from the
+ * leading bytes of the source block read by the previous step, a backward block
+ * reads the source block of its first byte and takes its last bytes from the
+ * tailing bytes of the source block read by the previous step.
+ *
+ * If dst > src, the chunks are copied from the end of the range so that an
+ * overlapping source is never overwritten before being read.
+ */
+pub struct MemoryCopyConfig<F: FieldExt> {
+    is_first: AllocatedBitCell<F>,
+    is_last: AllocatedBitCell<F>,
+    is_block: AllocatedBitCell<F>,
+    is_empty: AllocatedBitCell<F>,
+    is_backward: AllocatedBitCell<F>,
+    // is_block and not is_backward
+    is_forward_block: AllocatedBitCell<F>,
+    // dst - src - 1 if is_backward, otherwise src - dst, below 2^(K-1) as size
+    address_gap: AllocatedCommonRangeCell<F>,
+
+    // bytes remaining before this step, a common range cell: a size of 2^(K-1) or more
+    // can not be proved
+    size: AllocatedCommonRangeCell<F>,
+    // bytes remaining after this step
+    rest: AllocatedUnlimitedCell<F>,
+    len: AllocatedUnlimitedCell<F>,
+
+    src_address: AllocatedUnlimitedCell<F>,
+    // the block index and the position of the source address read by the step, see
+    // bulk_memory_copy_read_address
+    src_block_index: AllocatedCommonRangeCell<F>,
+    src_block_inner_pos_bits: [AllocatedBitCell<F>; 3],
+    src_pos_modulus: AllocatedUnlimitedCell<F>,
+
+    dst_address: AllocatedUnlimitedCell<F>,
+    dst_block_index: AllocatedCommonRangeCell<F>,
+    dst_block_inner_pos_bits: [AllocatedBitCell<F>; 3],
+    dst_pos_modulus: AllocatedUnlimitedCell<F>,
+
+    src_block_value: AllocatedU64Cell<F>,
+    src_block_tailing: AllocatedU64Cell<F>,
+    src_block_tailing_diff: AllocatedU64Cell<F>,
+    src_block_picked: AllocatedU8Cell<F>,
+    src_block_leading: AllocatedU64Cell<F>,
+    // the bytes of a block taken from the source block read by the previous step
+    src_carry: AllocatedU64Cell<F>,
+
+    pre_block_value: AllocatedU64Cell<F>,
+    pre_block_tailing: AllocatedU64Cell<F>,
+    pre_block_tailing_diff: AllocatedU64Cell<F>,
+    pre_block_picked: AllocatedU8Cell<F>,
+    pre_block_leading: AllocatedU64Cell<F>,
+    updated_block_value: AllocatedUnlimitedCell<F>,
+
+    address_within_allocated_pages_helper: AllocatedU64Cell<F>,
+
+    memory_table_lookup_stack_read_size: AllocatedMemoryTableLookupReadCell<F>,
+    memory_table_lookup_stack_read_src: AllocatedMemoryTableLookupReadCell<F>,
+    memory_table_lookup_stack_read_dst: AllocatedMemoryTableLookupReadCell<F>,
+    memory_table_lookup_heap_read_src: AllocatedMemoryTableLookupReadCell<F>,
+    memory_table_lookup_heap_read_dst: AllocatedMemoryTableLookupReadCell<F>,
+    memory_table_lookup_heap_write: AllocatedMemoryTableLookupWriteCell<F>,
+}
+
+pub struct MemoryCopyConfigBuilder;
+
+impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for MemoryCopyConfigBuilder {
+    fn configure(
+        common_config: &EventTableCommonConfig<F>,
+        allocator: &mut EventTableCellAllocator<F>,
+        constraint_builder: &mut ConstraintBuilder<F>,
+    ) -> Box<dyn EventTableOpcodeConfig<F>> {
+        let is_first = allocator.alloc_bit_cell();
+        let is_last = allocator.alloc_bit_cell();
+        let is_block = allocator.alloc_bit_cell();
+        let is_empty = allocator.alloc_bit_cell();
+        let is_backward = allocator.alloc_bit_cell();
+        let is_forward_block = allocator.alloc_bit_cell();
+        let address_gap = allocator.alloc_common_range_cell();
+
+        let size = allocator.alloc_common_range_cell();
+        let rest = allocator.alloc_unlimited_cell();
+        let len = allocator.alloc_unlimited_cell();
+
+        let src_address = allocator.alloc_unlimited_cell();
+        let src_block_index = allocator.alloc_common_range_cell();
+        let src_block_inner_pos_bits = [0; 3].map(|_| allocator.alloc_bit_cell());
+        let src_pos_modulus = allocator.alloc_unlimited_cell();
+
+        let dst_address = allocator.alloc_unlimited_cell();
+        let dst_block_index = allocator.alloc_common_range_cell();
+        let dst_block_inner_pos_bits = [0; 3].map(|_| allocator.alloc_bit_cell());
+        let dst_pos_modulus = allocator.alloc_unlimited_cell();
+
+        let src_block_value = allocator.alloc_u64_cell();
+        let src_block_tailing = allocator.alloc_u64_cell();
+        let src_block_tailing_diff = allocator.alloc_u64_cell();
+        let src_block_picked = allocator.alloc_u8_cell();
+        let src_block_leading = allocator.alloc_u64_cell();
+        let src_carry = allocator.alloc_u64_cell();
+
+        let pre_block_value = allocator.alloc_u64_cell();
+        let pre_block_tailing = allocator.alloc_u64_cell();
+        let pre_block_tailing_diff = allocator.alloc_u64_cell();
+        let pre_block_picked = allocator.alloc_u8_cell();
+        let pre_block_leading = allocator.alloc_u64_cell();
+        let updated_block_value = allocator.alloc_unlimited_cell();
+
+        let address_within_allocated_pages_helper = allocator.alloc_u64_cell();
+
+        let ops = common_config.ops[OpcodeClass::MemoryCopy as usize];
+
+        constraint_builder.push(
+            "op_memory_copy len",
+            Box::new(move |meta| {
+                vec![
+                    len.expr(meta)
+                        - (constant_from!(1) - is_empty.expr(meta))
+                            * (constant_from!(1) + constant_from!(7) * is_block.expr(meta)),
+                    rest.expr(meta) + len.expr(meta) - size.expr(meta),
+                    is_last.expr(meta) * rest.expr(meta),
+                ]
+            }),
+        );
+
+        constraint_builder.push(
+            "op_memory_copy empty",
+            Box::new(move |meta| {
+                vec![
+                    is_empty.expr(meta) * (constant_from!(1) - is_first.expr(meta)),
+                    is_empty.expr(meta) * (constant_from!(1) - is_last.expr(meta)),
+                    is_empty.expr(meta) * is_block.expr(meta),
+                ]
+            }),
+        );
+
+        constraint_builder.push(
+            "op_memory_copy direction",
+            Box::new(move |meta| {
+                vec![
+                    is_forward_block.expr(meta)
+                        - is_block.expr(meta) * (constant_from!(1) - is_backward.expr(meta)),
+                    is_backward.expr(meta)
+                        * (dst_address.expr(meta)
+                            - src_address.expr(meta)
+                            - constant_from!(1)
+                            - address_gap.expr(meta))
+                        + (constant_from!(1) - is_backward.expr(meta))
+                            * (src_address.expr(meta)
+                                - dst_address.expr(meta)
+                                - address_gap.expr(meta)),
+                ]
+            }),
+        );
+
+        constraint_builder.push(
+            "op_memory_copy src address",
+            Box::new(move |meta| {
+                let pos_bits = src_block_inner_pos_bits;
+
+                vec![
+                    // A forward block reads the source block of its last byte.
+                    src_block_index.expr(meta) * constant_from!(8)
+                        + pos_bits[0].expr(meta)
+                        + pos_bits[1].expr(meta) * constant_from!(2)
+                        + pos_bits[2].expr(meta) * constant_from!(4)
+                        - src_address.expr(meta)
+                        - is_forward_block.expr(meta) * constant_from!(7),
+                    src_pos_modulus.expr(meta)
+                        - (constant_from!(1) + pos_bits[0].expr(meta) * constant_from!(0xff))
+                            * (constant_from!(1) + pos_bits[1].expr(meta) * constant_from!(0xffff))
+                            * (constant_from!(1)
+                                + pos_bits[2].expr(meta) * constant_from!(0xffffffffu64)),
+                    // The source of the block of the first step is aligned.
+                    is_first.expr(meta)
+                        * (is_forward_block.expr(meta)
+                            * (src_pos_modulus.expr(meta) - constant_from!(1u64 << 56))
+                            + (is_block.expr(meta) - is_forward_block.expr(meta))
+                                * (src_pos_modulus.expr(meta) - constant_from!(1))),
+                ]
+            }),
+        );
+
+        constraint_builder.push(
+            "op_memory_copy dst address",
+            Box::new(move |meta| {
+                let pos_bits = dst_block_inner_pos_bits;
+
+                vec![
+                    dst_block_index.expr(meta) * constant_from!(8)
+                        + pos_bits[0].expr(meta)
+                        + pos_bits[1].expr(meta) * constant_from!(2)
+                        + pos_bits[2].expr(meta) * constant_from!(4)
+                        - dst_address.expr(meta),
+                    dst_pos_modulus.expr(meta)
+                        - (constant_from!(1) + pos_bits[0].expr(meta) * constant_from!(0xff))
+                            * (constant_from!(1) + pos_bits[1].expr(meta) * constant_from!(0xffff))
+                            * (constant_from!(1)
+                                + pos_bits[2].expr(meta) * constant_from!(0xffffffffu64)),
+                    is_block.expr(meta) * pos_bits[0].expr(meta),
+                    is_block.expr(meta) * pos_bits[1].expr(meta),
+                    is_block.expr(meta) * pos_bits[2].expr(meta),
+                ]
+            }),
+        );
+
+        for (name, block_value, tailing, tailing_diff, picked, leading, pos_modulus) in [
+            (
+                "op_memory_copy pick src byte",
+                src_block_value,
+                src_block_tailing,
+                src_block_tailing_diff,
+                src_block_picked,
+                src_block_leading,
+                src_pos_modulus,
+            ),
+            (
+                "op_memory_copy pick dst byte",
+                pre_block_value,
+                pre_block_tailing,
+                pre_block_tailing_diff,
+                pre_block_picked,
+                pre_block_leading,
+                dst_pos_modulus,
+            ),
+        ] {
+            constraint_builder.push(
+                name,
+                Box::new(move |meta| {
+                    vec![
+                        tailing.expr(meta)
+                            + picked.expr(meta) * pos_modulus.expr(meta)
+                            + leading.expr(meta) * pos_modulus.expr(meta) * constant_from!(1 << 8)
+                            - block_value.expr(meta),
+                        tailing.expr(meta) + tailing_diff.expr(meta) + constant_from!(1)
+                            - pos_modulus.expr(meta),
+                    ]
+                }),
+            );
+        }
+
+        constraint_builder.push(
+            "op_memory_copy src carry",
+            Box::new(move |meta| {
+                /*
+                 * The previous step read the source block before the one of a forward
+                 * block, its leading bytes start the block, or the source block after the
+                 * one of a backward block, its tailing bytes end the block.
+                 */
+                vec![
+                    src_carry.expr(meta)
+                        - (constant_from!(1) - is_first.expr(meta))
+                            * (is_forward_block.expr(meta) * src_block_leading.prev_expr(meta)
+                                + (is_block.expr(meta) - is_forward_block.expr(meta))
+                                    * src_block_tailing.prev_expr(meta)),
+                ]
+            }),
+        );
+
+        constraint_builder.push(
+            "op_memory_copy updated block",
+            Box::new(move |meta| {
+                vec![
+                    // A byte replaces the picked byte of the destination block.
+                    (constant_from!(1) - is_block.expr(meta))
+                        * (updated_block_value.expr(meta)
+                            - pre_block_value.expr(meta)
+                            - (src_block_picked.expr(meta) - pre_block_picked.expr(meta))
+                                * dst_pos_modulus.expr(meta)),
+                    /*
+                     * A forward block read at byte p of the source block is the carry
+                     * followed by the bytes up to p, i.e.
+                     * updated = carry + (tailing + picked * 2^8p) * 2^(56 - 8p).
+                     */
+                    is_forward_block.expr(meta)
+                        * ((updated_block_value.expr(meta) - src_carry.expr(meta))
+                            * src_pos_modulus.expr(meta)
+                            - (src_block_tailing.expr(meta)
+                                + src_block_picked.expr(meta) * src_pos_modulus.expr(meta))
+                                * constant_from!(1u64 << 56)),
+                    /*
+                     * A backward block read at byte p of the source block is the bytes from
+                     * p followed by the carry, i.e.
+                     * updated = picked + leading * 2^8 + carry * 2^(64 - 8p).
+                     */
+                    (is_block.expr(meta) - is_forward_block.expr(meta))
+                        * ((updated_block_value.expr(meta)
+                            - src_block_picked.expr(meta)
+                            - src_block_leading.expr(meta) * constant_from!(1 << 8))
+                            * src_pos_modulus.expr(meta)
+                            - src_carry.expr(meta)
+                                * constant_from_bn!(&(BigUint::from(1u64) << 64usize))),
+                ]
+            }),
+        );
+
+        constraint_builder.push(
+            "op_memory_copy next step",
+            Box::new(move |meta| {
+                let forward = constant_from!(1) - is_backward.expr(meta);
+
+                vec![
+                    ops.next_expr(meta) - constant_from!(1),
+                    is_first.next_expr(meta),
+                    is_backward.next_expr(meta) - is_backward.expr(meta),
+                    size.next_expr(meta) - rest.expr(meta),
+                    dst_address.next_expr(meta) - dst_address.expr(meta)
+                        + is_backward.expr(meta) * len.next_expr(meta)
+                        - forward.clone() * len.expr(meta),
+                    src_address.next_expr(meta) - src_address.expr(meta)
+                        + is_backward.expr(meta) * len.next_expr(meta)
+                        - forward * len.expr(meta),
+                ]
+                .into_iter()
+                .map(|expr| expr * (constant_from!(1) - is_last.expr(meta)))
+                .collect()
+            }),
+        );
+
+        constraint_builder.push(
+            "op_memory_copy prev step",
+            Box::new(move |meta| {
+                vec![
+                    ops.prev_expr(meta) - constant_from!(1),
+                    is_last.prev_expr(meta),
+                ]
+                .into_iter()
+                .map(|expr| expr * (constant_from!(1) - is_first.expr(meta)))
+                .collect()
+            }),
+        );
+
+        let current_memory_page_size = common_config.mpages_cell;
+
+        constraint_builder.push(
+            "op_memory_copy allocated address",
+            Box::new(move |meta| {
+                // The higher one of dst and src, the first chunk is at the end if is_backward.
+                let start = is_backward.expr(meta) * (dst_address.expr(meta) - rest.expr(meta))
+                    + (constant_from!(1) - is_backward.expr(meta)) * src_address.expr(meta);
+
+                vec![
+                    (start + size.expr(meta) + address_within_allocated_pages_helper.expr(meta)
+                        - current_memory_page_size.expr(meta) * constant_from!(WASM_PAGE_SIZE))
+                        * is_first.expr(meta),
+                ]
+            }),
+        );
+
+        let sp = common_config.sp_cell;
+        let eid = common_config.eid_cell;
+
+        let memory_table_lookup_stack_read_size = allocator.alloc_memory_table_lookup_read_cell(
+            "op_memory_copy read size",
+            constraint_builder,
+            eid,
+            move |____| constant_from!(LocationType::Stack as u64),
+            move |meta| sp.expr(meta) + constant_from!(1),
+            move |____| constant_from!(1),
+            move |meta| size.expr(meta),
+            move |meta| is_first.expr(meta),
+        );
+
+        let memory_table_lookup_stack_read_src = allocator.alloc_memory_table_lookup_read_cell(
+            "op_memory_copy read src",
+            constraint_builder,
+            eid,
+            move |____| constant_from!(LocationType::Stack as u64),
+            move |meta| sp.expr(meta) + constant_from!(2),
+            move |____| constant_from!(1),
+            move |meta| src_address.expr(meta) - is_backward.expr(meta) * rest.expr(meta),
+            move |meta| is_first.expr(meta),
+        );
+
+        let memory_table_lookup_stack_read_dst = allocator.alloc_memory_table_lookup_read_cell(
+            "op_memory_copy read dst",
+            constraint_builder,
+            eid,
+            move |____| constant_from!(LocationType::Stack as u64),
+            move |meta| sp.expr(meta) + constant_from!(3),
+            move |____| constant_from!(1),
+            move |meta| dst_address.expr(meta) - is_backward.expr(meta) * rest.expr(meta),
+            move |meta| is_first.expr(meta),
+        );
+
+        let memory_table_lookup_heap_read_src = allocator.alloc_memory_table_lookup_read_cell(
+            "op_memory_copy load src",
+            constraint_builder,
+            eid,
+            move |____| constant_from!(LocationType::Heap as u64),
+            move |meta| src_block_index.expr(meta),
+            move |____| constant_from!(0),
+            move |meta| src_block_value.expr(meta),
+            move |meta| constant_from!(1) - is_empty.expr(meta),
+        );
+
+        let memory_table_lookup_heap_read_dst = allocator.alloc_memory_table_lookup_read_cell(
+            "op_memory_copy load origin",
+            constraint_builder,
+            eid,
+            move |____| constant_from!(LocationType::Heap as u64),
+            move |meta| dst_block_index.expr(meta),
+            move |____| constant_from!(0),
+            move |meta| pre_block_value.expr(meta),
+            move |meta| constant_from!(1) - is_empty.expr(meta),
+        );
+
+        let memory_table_lookup_heap_write = allocator.alloc_memory_table_lookup_write_cell(
+            "op_memory_copy write res",
+            constraint_builder,
+            eid,
+            move |____| constant_from!(LocationType::Heap as u64),
+            move |meta| dst_block_index.expr(meta),
+            move |____| constant_from!(0),
+            move |meta| updated_block_value.expr(meta),
+            move |meta| constant_from!(1) - is_empty.expr(meta),
+        );
+
+        Box::new(MemoryCopyConfig {
+            is_first,
+            is_last,
+            is_block,
+            is_empty,
+            is_backward,
+            is_forward_block,
+            address_gap,
+            size,
+            rest,
+            len,
+            src_address,
+            src_block_index,
+            src_block_inner_pos_bits,
+            src_pos_modulus,
+            dst_address,
+            dst_block_index,
+            dst_block_inner_pos_bits,
+            dst_pos_modulus,
+            src_block_value,
+            src_block_tailing,
+            src_block_tailing_diff,
+            src_block_picked,
+            src_block_leading,
+            src_carry,
+            pre_block_value,
+            pre_block_tailing,
+            pre_block_tailing_diff,
+            pre_block_picked,
+            pre_block_leading,
+            updated_block_value,
+            address_within_allocated_pages_helper,
+            memory_table_lookup_stack_read_size,
+            memory_table_lookup_stack_read_src,
+            memory_table_lookup_stack_read_dst,
+            memory_table_lookup_heap_read_src,
+            memory_table_lookup_heap_read_dst,
+            memory_table_lookup_heap_write,
+        })
+    }
+}
+
+impl<F: FieldExt> MemoryCopyConfig<F> {
+    /// Assigns the block index and the byte position of `address`, and picks the byte of
+    /// `block_value` at the position.
+    fn assign_address(
+        &self,
+        ctx: &mut Context<'_, F>,
+        address: u32,
+        block_value: u64,
+        is_src: bool,
+    ) -> Result<(), Error> {
+        let (
+            block_index,
+            pos_bits,
+            pos_modulus_cell,
+            tailing_cell,
+            tailing_diff_cell,
+            picked_cell,
+            leading_cell,
+        ) = if is_src {
+            (
+                &self.src_block_index,
+                &self.src_block_inner_pos_bits,
+                &self.src_pos_modulus,
+                &self.src_block_tailing,
+                &self.src_block_tailing_diff,
+                &self.src_block_picked,
+                &self.src_block_leading,
+            )
+        } else {
+            (
+                &self.dst_block_index,
+                &self.dst_block_inner_pos_bits,
+                &self.dst_pos_modulus,
+                &self.pre_block_tailing,
+                &self.pre_block_tailing_diff,
+                &self.pre_block_picked,
+                &self.pre_block_leading,
+            )
+        };
+
+        let inner_byte_index = address & 7;
+        block_index.assign_u32(ctx, address >> 3)?;
+        pos_bits[0].assign_bool(ctx, inner_byte_index & 1 != 0)?;
+        pos_bits[1].assign_bool(ctx, inner_byte_index & 2 != 0)?;
+        pos_bits[2].assign_bool(ctx, inner_byte_index & 4 != 0)?;
+
+        let pos_modulus = 1u64 << (inner_byte_index * 8);
+        pos_modulus_cell.assign(ctx, pos_modulus.into())?;
+
+        let tailing = block_value & (pos_modulus - 1);
+        let picked = (block_value >> (inner_byte_index * 8)) & 0xff;
+        let leading = block_value
+            .checked_shr((inner_byte_index + 1) * 8)
+            .unwrap_or(0);
+
+        tailing_cell.assign(ctx, tailing)?;
+        tailing_diff_cell.assign(ctx, pos_modulus - 1 - tailing)?;
+        picked_cell.assign(ctx, picked.into())?;
+        leading_cell.assign(ctx, leading)?;
+
+        Ok(())
+    }
+}
+
+impl<F: FieldExt> EventTableOpcodeConfig<F> for MemoryCopyConfig<F> {
+    fn opcode(&self, _meta: &mut VirtualCells<'_, F>) -> Expression<F> {
+        constant!(bn_to_field(
+            &(BigUint::from(OpcodeClass::MemoryCopy as u64) << OPCODE_CLASS_SHIFT)
+        ))
+    }
+
+    fn assign(
+        &self,
+        ctx: &mut Context<'_, F>,
+        step: &StepStatus,
+        entry: &EventTableEntryWithMemoryInfo,
+    ) -> Result<(), Error> {
+        match entry.eentry.step_info {
+            StepInfo::MemoryCopy {
+                dst,
+                src,
+                size,
+                is_first,
+                is_backward,
+                src_block_value,
+                pre_block_value,
+                updated_block_value,
+            } => {
+                let (chunk_dst, chunk_src, len) =
+                    bulk_memory_chunk(dst, src, size, is_first, is_backward);
+                let read_src = bulk_memory_copy_read_address(chunk_src, len, is_backward);
+                let rest = size - len;
+
+                self.is_first.assign_bool(ctx, is_first)?;
+                self.is_last.assign_bool(ctx, rest == 0)?;
+                self.is_block.assign_bool(ctx, len == 8)?;
+                self.is_empty.assign_bool(ctx, len == 0)?;
+                self.is_backward.assign_bool(ctx, is_backward)?;
+                self.is_forward_block
+                    .assign_bool(ctx, len == 8 && !is_backward)?;
+                self.address_gap.assign_u32(
+                    ctx,
+                    if is_backward {
+                        dst - src - 1
+                    } else {
+                        src - dst
+                    },
+                )?;
+
+                self.size.assign_u32(ctx, size)?;
+                self.rest.assign_u32(ctx, rest)?;
+                self.len.assign_u32(ctx, len)?;
+
+                self.src_address.assign_u32(ctx, chunk_src)?;
+                self.src_block_value.assign(ctx, src_block_value)?;
+                self.assign_address(ctx, read_src, src_block_value, true)?;
+                self.dst_address.assign_u32(ctx, chunk_dst)?;
+                self.pre_block_value.assign(ctx, pre_block_value)?;
+                self.assign_address(ctx, chunk_dst, pre_block_value, false)?;
+
+                // The bytes of a misaligned block read by the previous step.
+                let carry = match chunk_src & 7 {
+                    _ if len < 8 => 0,
+                    0 => 0,
+                    pos if is_backward => updated_block_value >> (64 - pos * 8),
+                    pos => updated_block_value & ((1 << (64 - pos * 8)) - 1),
+                };
+                self.src_carry.assign(ctx, carry)?;
+                self.updated_block_value
+                    .assign(ctx, updated_block_value.into())?;
+
+                let mut memory_rw_entries = entry.memory_rw_entires.iter();
+
+                if is_first {
+                    self.address_within_allocated_pages_helper.assign(
+                        ctx,
+                        step.current.allocated_memory_pages as u64 * WASM_PAGE_SIZE
+                            - (dst.max(src) as u64 + size as u64),
+                    )?;
+
+                    for (lookup, offset, value) in [
+                        (&self.memory_table_lookup_stack_read_size, 1, size),
+                        (&self.memory_table_lookup_stack_read_src, 2, src),
+                        (&self.memory_table_lookup_stack_read_dst, 3, dst),
+                    ] {
+                        let rw_entry = memory_rw_entries.next().unwrap();
+
+                        lookup.assign(
+                            ctx,
+                            rw_entry.start_eid,
+                            step.current.eid,
+                            rw_entry.end_eid,
+                            step.current.sp + offset,
+                            LocationType::Stack,
+                            true,
+                            value as u64,
+                        )?;
+                    }
+                }
+
+                if len > 0 {
+                    for (lookup, address, value) in [
+                        (
+                            &self.memory_table_lookup_heap_read_src,
+                            read_src,
+                            src_block_value,
+                        ),
+                        (
+                            &self.memory_table_lookup_heap_read_dst,
+                            chunk_dst,
+                            pre_block_value,
+                        ),
+                    ] {
+                        let rw_entry = memory_rw_entries.next().unwrap();
+
+                        lookup.assign(
+                            ctx,
+                            rw_entry.start_eid,
+                            step.current.eid,
+                            rw_entry.end_eid,
+                            address >> 3,
+                            LocationType::Heap,
+                            false,
+                            value,
+                        )?;
+                    }
+
+                    let rw_entry = memory_rw_entries.next().unwrap();
+                    self.memory_table_lookup_heap_write.assign(
+                        ctx,
+                        step.current.eid,
+                        rw_entry.end_eid,
+                        chunk_dst >> 3,
+                        LocationType::Heap,
+                        false,
+                        updated_block_value,
+                    )?;
+                }
+
+                Ok(())
+            }
+
+            _ => unreachable!(),
+        }
+    }
+
+    fn sp_diff(&self, meta: &mut VirtualCells<'_, F>) -> Option<Expression<F>> {
+        Some(constant_from!(3) * self.is_last.expr(meta))
+    }
+
+    fn mops(&self, meta: &mut VirtualCells<'_, F>) -> Option<Expression<F>> {
+        Some(constant_from!(1) - self.is_empty.expr(meta))
+    }
+
+    fn memory_writing_ops(&self, entry: &EventTableEntry) -> u32 {
+        match &entry.step_info {
+            StepInfo::MemoryCopy { size, .. } => (*size > 0) as u32,
+            _ => unreachable!(),
+        }
+    }
+
+    fn next_iid(
+        &self,
+        meta: &mut VirtualCells<'_, F>,
+        common_config: &EventTableCommonConfig<F>,
+    ) -> Option<Expression<F>> {
+        Some(common_config.iid_cell.expr(meta) + self.is_last.expr(meta))
+    }
+}
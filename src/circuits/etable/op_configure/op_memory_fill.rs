@@ -0,0 +1,488 @@
+use crate::circuits::cell::*;
+use crate::circuits::etable::allocator::*;
+use crate::circuits::etable::ConstraintBuilder;
+use crate::circuits::etable::EventTableCommonConfig;
+use crate::circuits::etable::EventTableOpcodeConfig;
+use crate::circuits::etable::EventTableOpcodeConfigBuilder;
+use crate::circuits::utils::bn_to_field;
+use crate::circuits::utils::step_status::StepStatus;
+use crate::circuits::utils::table_entry::EventTableEntryWithMemoryInfo;
+use crate::circuits::utils::Context;
+use crate::constant;
+use crate::constant_from;
+use halo2_proofs::arithmetic::FieldExt;
+use halo2_proofs::plonk::Error;
+use halo2_proofs::plonk::Expression;
+use halo2_proofs::plonk::VirtualCells;
+use num_bigint::BigUint;
+use specs::configure_table::WASM_PAGE_SIZE;
+use specs::etable::EventTableEntry;
+use specs::itable::OpcodeClass;
+use specs::itable::OPCODE_CLASS_SHIFT;
+use specs::mtable::LocationType;
+use specs::step::bulk_memory_chunk;
+use specs::step::StepInfo;
+
+/*
+ * memory.fill is proved by a sequence of steps sharing the same iid, each step
+ * writes either an aligned 8-byte block or a single byte. Stack operands are
+ * read by the first step and popped by the last step.
+ */
+pub struct MemoryFillConfig<F: FieldExt> {
+    is_first: AllocatedBitCell<F>,
+    is_last: AllocatedBitCell<F>,
+    is_block: AllocatedBitCell<F>,
+    is_empty: AllocatedBitCell<F>,
+
+    // bytes remaining before this step, a common range cell: a size of 2^(K-1) or more
+    // can not be proved
+    size: AllocatedCommonRangeCell<F>,
+    // bytes remaining after this step
+    rest: AllocatedUnlimitedCell<F>,
+    len: AllocatedUnlimitedCell<F>,
+
+    dst_address: AllocatedUnlimitedCell<F>,
+    dst_block_index: AllocatedCommonRangeCell<F>,
+    dst_block_inner_pos_bits: [AllocatedBitCell<F>; 3],
+    dst_pos_modulus: AllocatedUnlimitedCell<F>,
+
+    value: AllocatedU64Cell<F>,
+    value_tailing_u16_u8_high: AllocatedU8Cell<F>,
+    value_tailing_u16_u8_low: AllocatedU8Cell<F>,
+
+    pre_block_value: AllocatedU64Cell<F>,
+    pre_block_tailing: AllocatedU64Cell<F>,
+    pre_block_tailing_diff: AllocatedU64Cell<F>,
+    pre_block_picked: AllocatedU8Cell<F>,
+    pre_block_leading: AllocatedU64Cell<F>,
+    updated_block_value: AllocatedUnlimitedCell<F>,
+
+    address_within_allocated_pages_helper: AllocatedU64Cell<F>,
+
+    memory_table_lookup_stack_read_size: AllocatedMemoryTableLookupReadCell<F>,
+    memory_table_lookup_stack_read_value: AllocatedMemoryTableLookupReadCell<F>,
+    memory_table_lookup_stack_read_dst: AllocatedMemoryTableLookupReadCell<F>,
+    memory_table_lookup_heap_read: AllocatedMemoryTableLookupReadCell<F>,
+    memory_table_lookup_heap_write: AllocatedMemoryTableLookupWriteCell<F>,
+}
+
+pub struct MemoryFillConfigBuilder;
+
+impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for MemoryFillConfigBuilder {
+    fn configure(
+        common_config: &EventTableCommonConfig<F>,
+        allocator: &mut EventTableCellAllocator<F>,
+        constraint_builder: &mut ConstraintBuilder<F>,
+    ) -> Box<dyn EventTableOpcodeConfig<F>> {
+        let is_first = allocator.alloc_bit_cell();
+        let is_last = allocator.alloc_bit_cell();
+        let is_block = allocator.alloc_bit_cell();
+        let is_empty = allocator.alloc_bit_cell();
+
+        let size = allocator.alloc_common_range_cell();
+        let rest = allocator.alloc_unlimited_cell();
+        let len = allocator.alloc_unlimited_cell();
+
+        let dst_address = allocator.alloc_unlimited_cell();
+        let dst_block_index = allocator.alloc_common_range_cell();
+        let dst_block_inner_pos_bits = [0; 3].map(|_| allocator.alloc_bit_cell());
+        let dst_pos_modulus = allocator.alloc_unlimited_cell();
+
+        let value = allocator.alloc_u64_cell();
+        let value_tailing_u16_u8_high = allocator.alloc_u8_cell();
+        let value_tailing_u16_u8_low = allocator.alloc_u8_cell();
+
+        let pre_block_value = allocator.alloc_u64_cell();
+        let pre_block_tailing = allocator.alloc_u64_cell();
+        let pre_block_tailing_diff = allocator.alloc_u64_cell();
+        let pre_block_picked = allocator.alloc_u8_cell();
+        let pre_block_leading = allocator.alloc_u64_cell();
+        let updated_block_value = allocator.alloc_unlimited_cell();
+
+        let address_within_allocated_pages_helper = allocator.alloc_u64_cell();
+
+        let ops = common_config.ops[OpcodeClass::MemoryFill as usize];
+
+        constraint_builder.push(
+            "op_memory_fill len",
+            Box::new(move |meta| {
+                vec![
+                    len.expr(meta)
+                        - (constant_from!(1) - is_empty.expr(meta))
+                            * (constant_from!(1) + constant_from!(7) * is_block.expr(meta)),
+                    rest.expr(meta) + len.expr(meta) - size.expr(meta),
+                    is_last.expr(meta) * rest.expr(meta),
+                ]
+            }),
+        );
+
+        constraint_builder.push(
+            "op_memory_fill empty",
+            Box::new(move |meta| {
+                vec![
+                    is_empty.expr(meta) * (constant_from!(1) - is_first.expr(meta)),
+                    is_empty.expr(meta) * (constant_from!(1) - is_last.expr(meta)),
+                    is_empty.expr(meta) * is_block.expr(meta),
+                ]
+            }),
+        );
+
+        constraint_builder.push(
+            "op_memory_fill dst address",
+            Box::new(move |meta| {
+                vec![
+                    dst_block_index.expr(meta) * constant_from!(8)
+                        + dst_block_inner_pos_bits[0].expr(meta)
+                        + dst_block_inner_pos_bits[1].expr(meta) * constant_from!(2)
+                        + dst_block_inner_pos_bits[2].expr(meta) * constant_from!(4)
+                        - dst_address.expr(meta),
+                    dst_pos_modulus.expr(meta)
+                        - (constant_from!(1)
+                            + dst_block_inner_pos_bits[0].expr(meta) * constant_from!(0xff))
+                            * (constant_from!(1)
+                                + dst_block_inner_pos_bits[1].expr(meta) * constant_from!(0xffff))
+                            * (constant_from!(1)
+                                + dst_block_inner_pos_bits[2].expr(meta)
+                                    * constant_from!(0xffffffffu64)),
+                ]
+            }),
+        );
+
+        constraint_builder.push(
+            "op_memory_fill block aligned",
+            Box::new(move |meta| {
+                dst_block_inner_pos_bits
+                    .iter()
+                    .map(|bit| is_block.expr(meta) * bit.expr(meta))
+                    .collect()
+            }),
+        );
+
+        constraint_builder.push(
+            "op_memory_fill value tailing u16 decompose",
+            Box::new(move |meta| {
+                vec![
+                    value_tailing_u16_u8_high.expr(meta) * constant_from!(1 << 8)
+                        + value_tailing_u16_u8_low.expr(meta)
+                        - value.u16_cells_le[0].expr(meta),
+                ]
+            }),
+        );
+
+        constraint_builder.push(
+            "op_memory_fill pick byte",
+            Box::new(move |meta| {
+                vec![
+                    pre_block_tailing.expr(meta)
+                        + pre_block_picked.expr(meta) * dst_pos_modulus.expr(meta)
+                        + pre_block_leading.expr(meta)
+                            * dst_pos_modulus.expr(meta)
+                            * constant_from!(1 << 8)
+                        - pre_block_value.expr(meta),
+                    pre_block_tailing.expr(meta)
+                        + pre_block_tailing_diff.expr(meta)
+                        + constant_from!(1)
+                        - dst_pos_modulus.expr(meta),
+                ]
+            }),
+        );
+
+        constraint_builder.push(
+            "op_memory_fill updated block",
+            Box::new(move |meta| {
+                vec![
+                    updated_block_value.expr(meta)
+                        - is_block.expr(meta)
+                            * value_tailing_u16_u8_low.expr(meta)
+                            * constant_from!(0x0101010101010101u64)
+                        - (constant_from!(1) - is_block.expr(meta))
+                            * (pre_block_value.expr(meta)
+                                + (value_tailing_u16_u8_low.expr(meta)
+                                    - pre_block_picked.expr(meta))
+                                    * dst_pos_modulus.expr(meta)),
+                ]
+            }),
+        );
+
+        constraint_builder.push(
+            "op_memory_fill next step",
+            Box::new(move |meta| {
+                vec![
+                    ops.next_expr(meta) - constant_from!(1),
+                    is_first.next_expr(meta),
+                    size.next_expr(meta) - rest.expr(meta),
+                    dst_address.next_expr(meta) - dst_address.expr(meta) - len.expr(meta),
+                    value.u64_cell.next_expr(meta) - value.expr(meta),
+                ]
+                .into_iter()
+                .map(|expr| expr * (constant_from!(1) - is_last.expr(meta)))
+                .collect()
+            }),
+        );
+
+        constraint_builder.push(
+            "op_memory_fill prev step",
+            Box::new(move |meta| {
+                vec![
+                    ops.prev_expr(meta) - constant_from!(1),
+                    is_last.prev_expr(meta),
+                ]
+                .into_iter()
+                .map(|expr| expr * (constant_from!(1) - is_first.expr(meta)))
+                .collect()
+            }),
+        );
+
+        let current_memory_page_size = common_config.mpages_cell;
+
+        constraint_builder.push(
+            "op_memory_fill allocated address",
+            Box::new(move |meta| {
+                vec![
+                    (dst_address.expr(meta)
+                        + size.expr(meta)
+                        + address_within_allocated_pages_helper.expr(meta)
+                        - current_memory_page_size.expr(meta) * constant_from!(WASM_PAGE_SIZE))
+                        * is_first.expr(meta),
+                ]
+            }),
+        );
+
+        let sp = common_config.sp_cell;
+        let eid = common_config.eid_cell;
+
+        let memory_table_lookup_stack_read_size = allocator.alloc_memory_table_lookup_read_cell(
+            "op_memory_fill read size",
+            constraint_builder,
+            eid,
+            move |____| constant_from!(LocationType::Stack as u64),
+            move |meta| sp.expr(meta) + constant_from!(1),
+            move |____| constant_from!(1),
+            move |meta| size.expr(meta),
+            move |meta| is_first.expr(meta),
+        );
+
+        let memory_table_lookup_stack_read_value = allocator.alloc_memory_table_lookup_read_cell(
+            "op_memory_fill read value",
+            constraint_builder,
+            eid,
+            move |____| constant_from!(LocationType::Stack as u64),
+            move |meta| sp.expr(meta) + constant_from!(2),
+            move |____| constant_from!(1),
+            move |meta| value.expr(meta),
+            move |meta| is_first.expr(meta),
+        );
+
+        let memory_table_lookup_stack_read_dst = allocator.alloc_memory_table_lookup_read_cell(
+            "op_memory_fill read dst",
+            constraint_builder,
+            eid,
+            move |____| constant_from!(LocationType::Stack as u64),
+            move |meta| sp.expr(meta) + constant_from!(3),
+            move |____| constant_from!(1),
+            move |meta| dst_address.expr(meta),
+            move |meta| is_first.expr(meta),
+        );
+
+        let memory_table_lookup_heap_read = allocator.alloc_memory_table_lookup_read_cell(
+            "op_memory_fill load origin",
+            constraint_builder,
+            eid,
+            move |____| constant_from!(LocationType::Heap as u64),
+            move |meta| dst_block_index.expr(meta),
+            move |____| constant_from!(0),
+            move |meta| pre_block_value.expr(meta),
+            move |meta| constant_from!(1) - is_empty.expr(meta),
+        );
+
+        let memory_table_lookup_heap_write = allocator.alloc_memory_table_lookup_write_cell(
+            "op_memory_fill write res",
+            constraint_builder,
+            eid,
+            move |____| constant_from!(LocationType::Heap as u64),
+            move |meta| dst_block_index.expr(meta),
+            move |____| constant_from!(0),
+            move |meta| updated_block_value.expr(meta),
+            move |meta| constant_from!(1) - is_empty.expr(meta),
+        );
+
+        Box::new(MemoryFillConfig {
+            is_first,
+            is_last,
+            is_block,
+            is_empty,
+            size,
+            rest,
+            len,
+            dst_address,
+            dst_block_index,
+            dst_block_inner_pos_bits,
+            dst_pos_modulus,
+            value,
+            value_tailing_u16_u8_high,
+            value_tailing_u16_u8_low,
+            pre_block_value,
+            pre_block_tailing,
+            pre_block_tailing_diff,
+            pre_block_picked,
+            pre_block_leading,
+            updated_block_value,
+            address_within_allocated_pages_helper,
+            memory_table_lookup_stack_read_size,
+            memory_table_lookup_stack_read_value,
+            memory_table_lookup_stack_read_dst,
+            memory_table_lookup_heap_read,
+            memory_table_lookup_heap_write,
+        })
+    }
+}
+
+impl<F: FieldExt> EventTableOpcodeConfig<F> for MemoryFillConfig<F> {
+    fn opcode(&self, _meta: &mut VirtualCells<'_, F>) -> Expression<F> {
+        constant!(bn_to_field(
+            &(BigUint::from(OpcodeClass::MemoryFill as u64) << OPCODE_CLASS_SHIFT)
+        ))
+    }
+
+    fn assign(
+        &self,
+        ctx: &mut Context<'_, F>,
+        step: &StepStatus,
+        entry: &EventTableEntryWithMemoryInfo,
+    ) -> Result<(), Error> {
+        match entry.eentry.step_info {
+            StepInfo::MemoryFill {
+                dst,
+                value,
+                size,
+                is_first,
+                pre_block_value,
+                updated_block_value,
+            } => {
+                let (chunk_dst, _, len) = bulk_memory_chunk(dst, dst, size, is_first, false);
+                let rest = size - len;
+
+                self.is_first.assign_bool(ctx, is_first)?;
+                self.is_last.assign_bool(ctx, rest == 0)?;
+                self.is_block.assign_bool(ctx, len == 8)?;
+                self.is_empty.assign_bool(ctx, len == 0)?;
+
+                self.size.assign_u32(ctx, size)?;
+                self.rest.assign_u32(ctx, rest)?;
+                self.len.assign_u32(ctx, len)?;
+
+                let inner_byte_index = chunk_dst & 7;
+                self.dst_address.assign_u32(ctx, chunk_dst)?;
+                self.dst_block_index.assign_u32(ctx, chunk_dst >> 3)?;
+                self.dst_block_inner_pos_bits[0].assign_bool(ctx, inner_byte_index & 1 != 0)?;
+                self.dst_block_inner_pos_bits[1].assign_bool(ctx, inner_byte_index & 2 != 0)?;
+                self.dst_block_inner_pos_bits[2].assign_bool(ctx, inner_byte_index & 4 != 0)?;
+
+                let pos_modulus = 1u64 << (inner_byte_index * 8);
+                self.dst_pos_modulus.assign(ctx, pos_modulus.into())?;
+
+                self.value.assign(ctx, value as u64)?;
+                self.value_tailing_u16_u8_low
+                    .assign(ctx, ((value & 0xff) as u64).into())?;
+                self.value_tailing_u16_u8_high
+                    .assign(ctx, (((value >> 8) & 0xff) as u64).into())?;
+
+                let tailing = pre_block_value & (pos_modulus - 1);
+                let picked = (pre_block_value >> (inner_byte_index * 8)) & 0xff;
+                let leading = pre_block_value
+                    .checked_shr((inner_byte_index + 1) * 8)
+                    .unwrap_or(0);
+
+                self.pre_block_value.assign(ctx, pre_block_value)?;
+                self.pre_block_tailing.assign(ctx, tailing)?;
+                self.pre_block_tailing_diff
+                    .assign(ctx, pos_modulus - 1 - tailing)?;
+                self.pre_block_picked.assign(ctx, picked.into())?;
+                self.pre_block_leading.assign(ctx, leading)?;
+                self.updated_block_value
+                    .assign(ctx, updated_block_value.into())?;
+
+                let mut memory_rw_entries = entry.memory_rw_entires.iter();
+
+                if is_first {
+                    self.address_within_allocated_pages_helper.assign(
+                        ctx,
+                        step.current.allocated_memory_pages as u64 * WASM_PAGE_SIZE
+                            - (dst as u64 + size as u64),
+                    )?;
+
+                    for (lookup, offset, value) in [
+                        (&self.memory_table_lookup_stack_read_size, 1, size),
+                        (&self.memory_table_lookup_stack_read_value, 2, value),
+                        (&self.memory_table_lookup_stack_read_dst, 3, dst),
+                    ] {
+                        let rw_entry = memory_rw_entries.next().unwrap();
+
+                        lookup.assign(
+                            ctx,
+                            rw_entry.start_eid,
+                            step.current.eid,
+                            rw_entry.end_eid,
+                            step.current.sp + offset,
+                            LocationType::Stack,
+                            true,
+                            value as u64,
+                        )?;
+                    }
+                }
+
+                if len > 0 {
+                    let rw_entry = memory_rw_entries.next().unwrap();
+                    self.memory_table_lookup_heap_read.assign(
+                        ctx,
+                        rw_entry.start_eid,
+                        step.current.eid,
+                        rw_entry.end_eid,
+                        chunk_dst >> 3,
+                        LocationType::Heap,
+                        false,
+                        pre_block_value,
+                    )?;
+
+                    let rw_entry = memory_rw_entries.next().unwrap();
+                    self.memory_table_lookup_heap_write.assign(
+                        ctx,
+                        step.current.eid,
+                        rw_entry.end_eid,
+                        chunk_dst >> 3,
+                        LocationType::Heap,
+                        false,
+                        updated_block_value,
+                    )?;
+                }
+
+                Ok(())
+            }
+
+            _ => unreachable!(),
+        }
+    }
+
+    fn sp_diff(&self, meta: &mut VirtualCells<'_, F>) -> Option<Expression<F>> {
+        Some(constant_from!(3) * self.is_last.expr(meta))
+    }
+
+    fn mops(&self, meta: &mut VirtualCells<'_, F>) -> Option<Expression<F>> {
+        Some(constant_from!(1) - self.is_empty.expr(meta))
+    }
+
+    fn memory_writing_ops(&self, entry: &EventTableEntry) -> u32 {
+        match &entry.step_info {
+            StepInfo::MemoryFill { size, .. } => (*size > 0) as u32,
+            _ => unreachable!(),
+        }
+    }
+
+    fn next_iid(
+        &self,
+        meta: &mut VirtualCells<'_, F>,
+        common_config: &EventTableCommonConfig<F>,
+    ) -> Option<Expression<F>> {
+        Some(common_config.iid_cell.expr(meta) + self.is_last.expr(meta))
+    }
+}
@@ -10,6 +10,7 @@ use crate::circuits::utils::table_entry::EventTableEntryWithMemoryInfo;
 use crate::circuits::utils::Context;
 use crate::constant;
 use crate::constant_from;
+use crate::fixed_curr;
 use halo2_proofs::arithmetic::FieldExt;
 use halo2_proofs::plonk::Error;
 use halo2_proofs::plonk::Expression;
@@ -46,7 +47,7 @@ impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for MemoryGrowConfigBuilder {
         let success = allocator.alloc_bit_cell();
 
         let current_memory_size = common_config.mpages_cell;
-        let maximal_memory_pages = common_config.circuit_configure.maximal_memory_pages;
+        let maximal_memory_pages = common_config.maximal_memory_pages;
 
         constraint_builder.push(
             "memory_grow: return value",
@@ -67,7 +68,7 @@ impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for MemoryGrowConfigBuilder {
                     (current_memory_size.expr(meta)
                         + grow_size.expr(meta)
                         + current_maximal_diff.expr(meta)
-                        - constant_from!(maximal_memory_pages))
+                        - fixed_curr!(meta, maximal_memory_pages))
                         * success.expr(meta),
                 ]
             }),
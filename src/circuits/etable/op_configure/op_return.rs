@@ -29,7 +29,13 @@ use specs::mtable::VarType;
 use specs::step::StepInfo;
 
 pub struct ReturnConfig<F: FieldExt> {
+    is_first: AllocatedBitCell<F>,
+    is_last: AllocatedBitCell<F>,
+    /// Whether the step returns from the entry function, which terminates the execution.
+    is_terminate: AllocatedBitCell<F>,
     keep: AllocatedBitCell<F>,
+    keep_count: AllocatedCommonRangeCell<F>,
+    keep_offset: AllocatedCommonRangeCell<F>,
     drop: AllocatedCommonRangeCell<F>,
     is_i32: AllocatedBitCell<F>,
     value: AllocatedU64Cell<F>,
@@ -46,11 +52,60 @@ impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for ReturnConfigBuilder {
         allocator: &mut EventTableCellAllocator<F>,
         constraint_builder: &mut ConstraintBuilder<F>,
     ) -> Box<dyn EventTableOpcodeConfig<F>> {
+        let is_first = allocator.alloc_bit_cell();
+        let is_last = allocator.alloc_bit_cell();
+        let is_terminate = allocator.alloc_bit_cell();
         let keep = allocator.alloc_bit_cell();
+        let keep_count = allocator.alloc_common_range_cell();
+        let keep_offset = allocator.alloc_common_range_cell();
         let drop = allocator.alloc_common_range_cell();
         let is_i32 = allocator.alloc_bit_cell();
         let value = allocator.alloc_u64_cell();
 
+        let ops = common_config.ops[OpcodeClass::Return as usize];
+
+        /*
+         * Returning n values is proved in n steps, the frame is left at the last one.
+         */
+        constraint_builder.push(
+            "op_return keep offset",
+            Box::new(move |meta| {
+                vec![
+                    is_first.expr(meta) * (keep_offset.expr(meta) - keep_count.expr(meta)),
+                    is_last.expr(meta) * (keep_offset.expr(meta) - keep.expr(meta)),
+                    (constant_from!(1) - keep.expr(meta)) * keep_offset.expr(meta),
+                ]
+            }),
+        );
+
+        constraint_builder.push(
+            "op_return next step",
+            Box::new(move |meta| {
+                vec![
+                    ops.next_expr(meta) - constant_from!(1),
+                    is_first.next_expr(meta),
+                    keep.next_expr(meta) - keep.expr(meta),
+                    keep_offset.next_expr(meta) - keep_offset.expr(meta) + constant_from!(1),
+                ]
+                .into_iter()
+                .map(|expr| expr * (constant_from!(1) - is_last.expr(meta)))
+                .collect()
+            }),
+        );
+
+        constraint_builder.push(
+            "op_return prev step",
+            Box::new(move |meta| {
+                vec![
+                    ops.prev_expr(meta) - constant_from!(1),
+                    is_last.prev_expr(meta),
+                ]
+                .into_iter()
+                .map(|expr| expr * (constant_from!(1) - is_first.expr(meta)))
+                .collect()
+            }),
+        );
+
         let frame_table_lookup = common_config.jtable_lookup_cell;
 
         let fid_cell = common_config.fid_cell;
@@ -64,7 +119,7 @@ impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for ReturnConfigBuilder {
             constraint_builder,
             eid,
             move |____| constant_from!(LocationType::Stack as u64),
-            move |meta| sp.expr(meta) + constant_from!(1),
+            move |meta| sp.expr(meta) + keep_offset.expr(meta),
             move |meta| is_i32.expr(meta),
             move |meta| value.u64_cell.expr(meta),
             move |meta| keep.expr(meta),
@@ -74,7 +129,7 @@ impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for ReturnConfigBuilder {
             constraint_builder,
             eid,
             move |____| constant_from!(LocationType::Stack as u64),
-            move |meta| sp.expr(meta) + drop.expr(meta) + constant_from!(1),
+            move |meta| sp.expr(meta) + drop.expr(meta) + keep_offset.expr(meta),
             move |meta| is_i32.expr(meta),
             move |meta| value.u64_cell.expr(meta),
             move |meta| keep.expr(meta),
@@ -84,20 +139,43 @@ impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for ReturnConfigBuilder {
             "return frame table lookups",
             Box::new(move |meta| {
                 vec![
-                    frame_table_lookup.0.expr(meta)
-                        - JumpTableConfig::encode_lookup(
-                            frame_id_cell.expr(meta),
-                            frame_id_cell.next_expr(meta),
-                            fid_cell.expr(meta),
-                            fid_cell.next_expr(meta),
-                            iid_cell.next_expr(meta),
-                        ),
+                    frame_table_lookup.returned_cell.expr(meta) - is_last.expr(meta),
+                    frame_table_lookup.entry_cell.expr(meta)
+                        - is_last.expr(meta)
+                            * JumpTableConfig::encode_lookup(
+                                frame_id_cell.expr(meta),
+                                frame_id_cell.next_expr(meta),
+                                fid_cell.expr(meta),
+                                fid_cell.next_expr(meta),
+                                iid_cell.next_expr(meta),
+                            ),
                 ]
             }),
         ));
 
+        /*
+         * Only the entry function returns to the root frame at the instruction 0 of the
+         * function 0, a call returns to the instruction following it.
+         */
+        constraint_builder.push(
+            "op_return terminate",
+            Box::new(move |meta| {
+                vec![
+                    is_terminate.expr(meta) * (constant_from!(1) - is_last.expr(meta)),
+                    is_terminate.expr(meta) * frame_id_cell.next_expr(meta),
+                    is_terminate.expr(meta) * fid_cell.next_expr(meta),
+                    is_terminate.expr(meta) * iid_cell.next_expr(meta),
+                ]
+            }),
+        );
+
         Box::new(ReturnConfig {
+            is_first,
+            is_last,
+            is_terminate,
             keep,
+            keep_count,
+            keep_offset,
             drop,
             is_i32,
             value,
@@ -114,9 +192,8 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for ReturnConfig<F> {
             &(BigUint::from(OpcodeClass::Return as u64) << OPCODE_CLASS_SHIFT)
         )) + self.drop.expr(meta)
             * constant!(bn_to_field(&(BigUint::from(1u64) << OPCODE_ARG0_SHIFT)))
-            + self.keep.expr(meta)
+            + self.keep_count.expr(meta)
                 * constant!(bn_to_field(&(BigUint::from(1u64) << OPCODE_ARG1_SHIFT)))
-            + self.is_i32.expr(meta)
     }
 
     fn assign(
@@ -130,53 +207,73 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for ReturnConfig<F> {
                 drop,
                 keep,
                 keep_values,
+                keep_index,
                 ..
             } => {
-                assert!(keep.len() <= 1);
                 assert_eq!(keep.len(), keep_values.len());
 
+                let is_last = keep.is_empty() || *keep_index as usize + 1 == keep.len();
+
+                self.is_first.assign_bool(ctx, *keep_index == 0)?;
+                self.is_last.assign_bool(ctx, is_last)?;
+                self.is_terminate.assign_bool(
+                    ctx,
+                    is_last
+                        && step.next.last_jump_eid == 0
+                        && step.next.fid == 0
+                        && step.next.iid == 0,
+                )?;
+                self.keep_count.assign(ctx, F::from(keep.len() as u64))?;
                 self.drop.assign(ctx, F::from(*drop as u64))?;
 
-                if keep_values.len() == 0 {
-                    self.keep.assign(ctx, 0.into())?;
-                } else {
+                if keep.len() > 0 {
+                    let index = *keep_index as usize;
+                    let keep_offset = (keep.len() - index) as u32;
+                    let keep_type = VarType::from(keep[index]);
+
                     self.keep.assign(ctx, 1.into())?;
-                    self.is_i32
-                        .assign(ctx, (VarType::from(keep[0]) as u64).into())?;
-                    self.value.assign(ctx, keep_values[0])?;
+                    self.keep_offset.assign(ctx, (keep_offset as u64).into())?;
+                    self.is_i32.assign(ctx, (keep_type as u64).into())?;
+                    self.value.assign(ctx, keep_values[index])?;
 
                     self.memory_table_lookup_stack_read.assign(
                         ctx,
                         entry.memory_rw_entires[0].start_eid,
                         step.current.eid,
                         entry.memory_rw_entires[0].end_eid,
-                        step.current.sp + 1,
+                        step.current.sp + keep_offset,
                         LocationType::Stack,
-                        VarType::from(keep[0]) == VarType::I32,
-                        keep_values[0],
+                        keep_type == VarType::I32,
+                        keep_values[index],
                     )?;
 
                     self.memory_table_lookup_stack_write.assign(
                         ctx,
                         step.current.eid,
                         entry.memory_rw_entires[1].end_eid,
-                        step.current.sp + drop + 1,
+                        step.current.sp + drop + keep_offset,
                         LocationType::Stack,
-                        VarType::from(keep[0]) == VarType::I32,
-                        keep_values[0],
+                        keep_type == VarType::I32,
+                        keep_values[index],
+                    )?;
+                }
+
+                if is_last {
+                    self.frame_table_lookup
+                        .returned_cell
+                        .assign(ctx, F::one())?;
+                    self.frame_table_lookup.entry_cell.assign_bn(
+                        ctx,
+                        &encode_frame_table_entry(
+                            step.current.last_jump_eid.to_biguint().unwrap(),
+                            step.next.last_jump_eid.to_biguint().unwrap(),
+                            step.current.fid.to_biguint().unwrap(),
+                            step.next.fid.to_biguint().unwrap(),
+                            step.next.iid.to_biguint().unwrap(),
+                        ),
                     )?;
                 }
 
-                self.frame_table_lookup.0.assign_bn(
-                    ctx,
-                    &encode_frame_table_entry(
-                        step.current.last_jump_eid.to_biguint().unwrap(),
-                        step.next.last_jump_eid.to_biguint().unwrap(),
-                        step.current.fid.to_biguint().unwrap(),
-                        step.next.fid.to_biguint().unwrap(),
-                        step.next.iid.to_biguint().unwrap(),
-                    ),
-                )?;
                 Ok(())
             }
             _ => unreachable!(),
@@ -184,7 +281,7 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for ReturnConfig<F> {
     }
 
     fn sp_diff(&self, meta: &mut VirtualCells<'_, F>) -> Option<Expression<F>> {
-        Some(self.drop.expr(meta))
+        Some(self.is_last.expr(meta) * self.drop.expr(meta))
     }
 
     fn mops(&self, meta: &mut VirtualCells<'_, F>) -> Option<Expression<F>> {
@@ -193,24 +290,26 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for ReturnConfig<F> {
 
     fn memory_writing_ops(&self, entry: &EventTableEntry) -> u32 {
         match &entry.step_info {
-            StepInfo::Return { keep, .. } => {
-                if keep.len() > 0 {
-                    assert!(keep.len() == 1);
-                    1
-                } else {
-                    0
-                }
-            }
+            StepInfo::Return { keep, .. } => (!keep.is_empty()) as u32,
             _ => unreachable!(),
         }
     }
 
-    fn jops_expr(&self, _meta: &mut VirtualCells<'_, F>) -> Option<Expression<F>> {
-        Some(constant_from!(self.jops()))
+    fn jops_expr(&self, meta: &mut VirtualCells<'_, F>) -> Option<Expression<F>> {
+        Some(self.is_last.expr(meta))
+    }
+
+    fn jops(&self, entry: &EventTableEntry) -> u32 {
+        match &entry.step_info {
+            StepInfo::Return {
+                keep, keep_index, ..
+            } => (keep.is_empty() || *keep_index as usize + 1 == keep.len()) as u32,
+            _ => unreachable!(),
+        }
     }
 
-    fn jops(&self) -> u32 {
-        1
+    fn terminates(&self, meta: &mut VirtualCells<'_, F>) -> Option<Expression<F>> {
+        Some(self.is_terminate.expr(meta))
     }
 
     fn next_frame_id(
@@ -218,7 +317,11 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for ReturnConfig<F> {
         meta: &mut VirtualCells<'_, F>,
         common_config: &EventTableCommonConfig<F>,
     ) -> Option<Expression<F>> {
-        Some(common_config.frame_id_cell.next_expr(meta))
+        Some(
+            self.is_last.expr(meta) * common_config.frame_id_cell.next_expr(meta)
+                + (constant_from!(1) - self.is_last.expr(meta))
+                    * common_config.frame_id_cell.curr_expr(meta),
+        )
     }
 
     fn next_fid(
@@ -226,7 +329,11 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for ReturnConfig<F> {
         meta: &mut VirtualCells<'_, F>,
         common_config: &EventTableCommonConfig<F>,
     ) -> Option<Expression<F>> {
-        Some(common_config.fid_cell.next_expr(meta))
+        Some(
+            self.is_last.expr(meta) * common_config.fid_cell.next_expr(meta)
+                + (constant_from!(1) - self.is_last.expr(meta))
+                    * common_config.fid_cell.curr_expr(meta),
+        )
     }
 
     fn next_iid(
@@ -234,6 +341,10 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for ReturnConfig<F> {
         meta: &mut VirtualCells<'_, F>,
         common_config: &EventTableCommonConfig<F>,
     ) -> Option<Expression<F>> {
-        Some(common_config.iid_cell.next_expr(meta))
+        Some(
+            self.is_last.expr(meta) * common_config.iid_cell.next_expr(meta)
+                + (constant_from!(1) - self.is_last.expr(meta))
+                    * common_config.iid_cell.curr_expr(meta),
+        )
     }
 }
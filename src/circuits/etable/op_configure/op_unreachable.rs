@@ -0,0 +1,691 @@
+use crate::circuits::cell::*;
+use crate::circuits::etable::allocator::*;
+use crate::circuits::etable::ConstraintBuilder;
+use crate::circuits::etable::EventTableCommonConfig;
+use crate::circuits::etable::EventTableOpcodeConfig;
+use crate::circuits::jtable::expression::JtableLookupEntryEncode;
+use crate::circuits::jtable::JumpTableConfig;
+use crate::circuits::utils::bn_to_field;
+use crate::circuits::utils::step_status::StepStatus;
+use crate::circuits::utils::table_entry::EventTableEntryWithMemoryInfo;
+use crate::circuits::utils::Context;
+use crate::constant;
+use crate::constant_from;
+use crate::foreign::wasm_input_helper::circuits::WASM_INPUT_FOREIGN_TABLE_KEY;
+use halo2_proofs::arithmetic::FieldExt;
+use halo2_proofs::plonk::Error;
+use halo2_proofs::plonk::Expression;
+use halo2_proofs::plonk::VirtualCells;
+use num_bigint::BigUint;
+use specs::configure_table::WASM_PAGE_SIZE;
+use specs::encode::frame_table::encode_frame_table_entry;
+use specs::etable::EventTableEntry;
+use specs::itable::BinOp;
+use specs::itable::Opcode;
+use specs::itable::OpcodeClass;
+use specs::itable::OPCODE_ARG0_SHIFT;
+use specs::itable::OPCODE_ARG1_SHIFT;
+use specs::itable::OPCODE_CLASS_SHIFT;
+use specs::mtable::LocationType;
+use specs::mtable::VarType;
+use specs::step::StepInfo;
+use specs::step::TrapCode;
+
+/*
+ * A trapped execution ends with one step per open frame, innermost first. Each step
+ * leaves its frame through the frame table like a return does, the first one also proves
+ * why the instruction traps and publishes the trap code as the last public output. The
+ * frame register follows the frames being left, so the execution stops at frame 0.
+ */
+pub struct UnreachableConfig<F: FieldExt> {
+    is_first: AllocatedBitCell<F>,
+    is_last: AllocatedBitCell<F>,
+
+    is_unreachable: AllocatedBitCell<F>,
+    is_div: AllocatedBitCell<F>,
+    is_overflow: AllocatedBitCell<F>,
+    is_load: AllocatedBitCell<F>,
+    is_store: AllocatedBitCell<F>,
+    is_fill: AllocatedBitCell<F>,
+    is_copy: AllocatedBitCell<F>,
+
+    is_rem: AllocatedBitCell<F>,
+    is_sign: AllocatedBitCell<F>,
+    is_i32: AllocatedBitCell<F>,
+    // the address out of bounds of a memory.copy is its source
+    is_source: AllocatedBitCell<F>,
+
+    // the log2 of the byte length of a load or a store
+    len_log_bits: [AllocatedBitCell<F>; 2],
+
+    opcode_arg0: AllocatedCommonRangeCell<F>,
+    opcode_arg1: AllocatedCommonRangeCell<F>,
+    opcode_offset: AllocatedCommonRangeCell<F>,
+
+    // the divisor for a division, the address for a memory access
+    operand: AllocatedU64Cell<F>,
+    has_operand: AllocatedUnlimitedCell<F>,
+    // the dividend for an overflow, the size for a memory.fill or a memory.copy
+    second_operand: AllocatedU64Cell<F>,
+    has_second_operand: AllocatedUnlimitedCell<F>,
+    out_of_bounds_diff: AllocatedU64Cell<F>,
+
+    frame_id: AllocatedCommonRangeCell<F>,
+    callee_fid: AllocatedCommonRangeCell<F>,
+    caller_frame_id: AllocatedCommonRangeCell<F>,
+    caller_fid: AllocatedCommonRangeCell<F>,
+    caller_iid: AllocatedCommonRangeCell<F>,
+
+    trap_code: AllocatedUnlimitedCell<F>,
+    public_input_index_for_lookup: AllocatedUnlimitedCell<F>,
+    trap_code_for_lookup: AllocatedUnlimitedCell<F>,
+
+    frame_table_lookup: AllocatedJumpTableLookupCell<F>,
+    memory_table_lookup_stack_read: AllocatedMemoryTableLookupReadCell<F>,
+    memory_table_lookup_stack_read_second: AllocatedMemoryTableLookupReadCell<F>,
+}
+
+pub struct UnreachableConfigBuilder;
+
+impl UnreachableConfigBuilder {
+    pub(in crate::circuits::etable) fn configure<F: FieldExt>(
+        common_config: &EventTableCommonConfig<F>,
+        allocator: &mut EventTableCellAllocator<F>,
+        constraint_builder: &mut ConstraintBuilder<F>,
+        lookup_cells: &mut (impl Iterator<Item = AllocatedUnlimitedCell<F>> + Clone),
+    ) -> Box<dyn EventTableOpcodeConfig<F>> {
+        let is_first = allocator.alloc_bit_cell();
+        let is_last = allocator.alloc_bit_cell();
+
+        let is_unreachable = allocator.alloc_bit_cell();
+        let is_div = allocator.alloc_bit_cell();
+        let is_overflow = allocator.alloc_bit_cell();
+        let is_load = allocator.alloc_bit_cell();
+        let is_store = allocator.alloc_bit_cell();
+        let is_fill = allocator.alloc_bit_cell();
+        let is_copy = allocator.alloc_bit_cell();
+
+        let is_rem = allocator.alloc_bit_cell();
+        let is_sign = allocator.alloc_bit_cell();
+        let is_i32 = allocator.alloc_bit_cell();
+        let is_source = allocator.alloc_bit_cell();
+
+        let len_log_bits = [0; 2].map(|_| allocator.alloc_bit_cell());
+
+        let opcode_arg0 = allocator.alloc_common_range_cell();
+        let opcode_arg1 = allocator.alloc_common_range_cell();
+        let opcode_offset = allocator.alloc_common_range_cell();
+
+        let operand = allocator.alloc_u64_cell();
+        let has_operand = allocator.alloc_unlimited_cell();
+        let second_operand = allocator.alloc_u64_cell();
+        let has_second_operand = allocator.alloc_unlimited_cell();
+        let out_of_bounds_diff = allocator.alloc_u64_cell();
+
+        let frame_id = allocator.alloc_common_range_cell();
+        let callee_fid = allocator.alloc_common_range_cell();
+        let caller_frame_id = allocator.alloc_common_range_cell();
+        let caller_fid = allocator.alloc_common_range_cell();
+        let caller_iid = allocator.alloc_common_range_cell();
+
+        let trap_code = allocator.alloc_unlimited_cell();
+        let public_input_index_for_lookup = lookup_cells.next().unwrap();
+        let trap_code_for_lookup = lookup_cells.next().unwrap();
+
+        let ops = common_config.ops[OpcodeClass::Unreachable as usize];
+        let enabled_cell = common_config.enabled_cell;
+        let eid = common_config.eid_cell;
+        let sp = common_config.sp_cell;
+        let fid_cell = common_config.fid_cell;
+        let frame_id_cell = common_config.frame_id_cell;
+        let mpages = common_config.mpages_cell;
+        let public_input_index = common_config.input_index_cell;
+
+        constraint_builder.push(
+            "op_unreachable trapping instruction",
+            Box::new(move |meta| {
+                let has_no_args =
+                    is_unreachable.expr(meta) + is_fill.expr(meta) + is_copy.expr(meta);
+
+                vec![
+                    is_unreachable.expr(meta)
+                        + is_div.expr(meta)
+                        + is_overflow.expr(meta)
+                        + is_load.expr(meta)
+                        + is_store.expr(meta)
+                        + is_fill.expr(meta)
+                        + is_copy.expr(meta)
+                        - constant_from!(1),
+                    has_no_args.clone() * opcode_arg0.expr(meta),
+                    has_no_args * opcode_arg1.expr(meta),
+                    (constant_from!(1) - is_load.expr(meta) - is_store.expr(meta))
+                        * opcode_offset.expr(meta),
+                    (constant_from!(1) - is_div.expr(meta)) * is_rem.expr(meta),
+                    (constant_from!(1) - is_div.expr(meta) - is_load.expr(meta))
+                        * is_sign.expr(meta),
+                    (constant_from!(1) - is_copy.expr(meta)) * is_source.expr(meta),
+                ]
+            }),
+        );
+
+        constraint_builder.push(
+            "op_unreachable division by zero",
+            Box::new(move |meta| {
+                vec![
+                    is_div.expr(meta)
+                        * (opcode_arg0.expr(meta)
+                            - constant_from!(BinOp::UnsignedDiv as u64)
+                            - is_rem.expr(meta)
+                            - constant_from!(2) * is_sign.expr(meta)),
+                    is_div.expr(meta) * (opcode_arg1.expr(meta) - is_i32.expr(meta)),
+                    is_div.expr(meta) * operand.expr(meta),
+                ]
+            }),
+        );
+
+        constraint_builder.push(
+            "op_unreachable integer overflow",
+            Box::new(move |meta| {
+                // The minimal integer divided by -1.
+                vec![
+                    is_overflow.expr(meta)
+                        * (opcode_arg0.expr(meta) - constant_from!(BinOp::SignedDiv as u64)),
+                    is_overflow.expr(meta) * (opcode_arg1.expr(meta) - is_i32.expr(meta)),
+                    is_overflow.expr(meta)
+                        * (operand.expr(meta) - constant_from!(u64::MAX)
+                            + is_i32.expr(meta) * constant_from!(u64::MAX - u32::MAX as u64)),
+                    is_overflow.expr(meta)
+                        * (second_operand.expr(meta) - constant_from!(1u64 << 63)
+                            + is_i32.expr(meta) * constant_from!((1u64 << 63) - (1u64 << 31))),
+                ]
+            }),
+        );
+
+        constraint_builder.push(
+            "op_unreachable memory access size",
+            Box::new(move |meta| {
+                vec![
+                    (constant_from!(1) - is_load.expr(meta) - is_store.expr(meta))
+                        * len_log_bits[0].expr(meta),
+                    (constant_from!(1) - is_load.expr(meta) - is_store.expr(meta))
+                        * len_log_bits[1].expr(meta),
+                    is_load.expr(meta)
+                        * (opcode_arg1.expr(meta)
+                            - constant_from!(1)
+                            - is_sign.expr(meta)
+                            - constant_from!(2) * len_log_bits[0].expr(meta)
+                            - constant_from!(4) * len_log_bits[1].expr(meta)),
+                    len_log_bits[0].expr(meta) * len_log_bits[1].expr(meta) * is_sign.expr(meta),
+                    is_store.expr(meta)
+                        * (opcode_arg1.expr(meta)
+                            - constant_from!(1)
+                            - len_log_bits[0].expr(meta)
+                            - constant_from!(2) * len_log_bits[1].expr(meta)),
+                    // the vtype of a load or a store, its address is always an i32
+                    (is_load.expr(meta) + is_store.expr(meta))
+                        * opcode_arg0.expr(meta)
+                        * (opcode_arg0.expr(meta) - constant_from!(1)),
+                    (is_load.expr(meta)
+                        + is_store.expr(meta)
+                        + is_fill.expr(meta)
+                        + is_copy.expr(meta))
+                        * (constant_from!(1) - is_i32.expr(meta)),
+                ]
+            }),
+        );
+
+        constraint_builder.push(
+            "op_unreachable memory access out of bounds",
+            Box::new(move |meta| {
+                let len = (constant_from!(1) + len_log_bits[0].expr(meta))
+                    * (constant_from!(1) + constant_from!(3) * len_log_bits[1].expr(meta));
+                let bound = mpages.expr(meta) * constant_from!(WASM_PAGE_SIZE);
+
+                vec![
+                    (is_load.expr(meta) + is_store.expr(meta))
+                        * (operand.expr(meta) + opcode_offset.expr(meta) + len
+                            - constant_from!(1)
+                            - bound.clone()
+                            - out_of_bounds_diff.expr(meta)),
+                    (is_fill.expr(meta) + is_copy.expr(meta))
+                        * (operand.expr(meta) + second_operand.expr(meta)
+                            - constant_from!(1)
+                            - bound
+                            - out_of_bounds_diff.expr(meta)),
+                ]
+            }),
+        );
+
+        constraint_builder.push(
+            "op_unreachable trap code",
+            Box::new(move |meta| {
+                vec![
+                    trap_code.expr(meta)
+                        - is_unreachable.expr(meta) * constant_from!(TrapCode::Unreachable as u64)
+                        - is_div.expr(meta) * constant_from!(TrapCode::DivisionByZero as u64)
+                        - is_overflow.expr(meta) * constant_from!(TrapCode::IntegerOverflow as u64)
+                        - (is_load.expr(meta)
+                            + is_store.expr(meta)
+                            + is_fill.expr(meta)
+                            + is_copy.expr(meta))
+                            * constant_from!(TrapCode::MemoryAccessOutOfBounds as u64),
+                    has_operand.expr(meta)
+                        - is_first.expr(meta) * (constant_from!(1) - is_unreachable.expr(meta)),
+                    has_second_operand.expr(meta)
+                        - is_first.expr(meta)
+                            * (is_overflow.expr(meta) + is_fill.expr(meta) + is_copy.expr(meta)),
+                ]
+            }),
+        );
+
+        let memory_table_lookup_stack_read = allocator.alloc_memory_table_lookup_read_cell(
+            "op_unreachable stack read",
+            constraint_builder,
+            eid,
+            move |____| constant_from!(LocationType::Stack as u64),
+            move |meta| {
+                // The address of a memory.fill or a memory.copy is below its size and the
+                // value or the source, the source is below the destination.
+                sp.expr(meta)
+                    + constant_from!(1)
+                    + is_store.expr(meta)
+                    + constant_from!(2) * (is_fill.expr(meta) + is_copy.expr(meta))
+                    - is_source.expr(meta)
+            },
+            move |meta| is_i32.expr(meta),
+            move |meta| operand.expr(meta),
+            move |meta| has_operand.expr(meta),
+        );
+
+        let memory_table_lookup_stack_read_second = allocator.alloc_memory_table_lookup_read_cell(
+            "op_unreachable stack read second",
+            constraint_builder,
+            eid,
+            move |____| constant_from!(LocationType::Stack as u64),
+            move |meta| sp.expr(meta) + constant_from!(1) + is_overflow.expr(meta),
+            move |meta| is_i32.expr(meta),
+            move |meta| second_operand.expr(meta),
+            move |meta| has_second_operand.expr(meta),
+        );
+
+        constraint_builder.push(
+            "op_unreachable frame register",
+            Box::new(move |meta| vec![frame_id.expr(meta) - frame_id_cell.expr(meta)]),
+        );
+
+        constraint_builder.push(
+            "op_unreachable first step",
+            Box::new(move |meta| {
+                vec![is_first.expr(meta) * (callee_fid.expr(meta) - fid_cell.expr(meta))]
+            }),
+        );
+
+        constraint_builder.push(
+            "op_unreachable next step",
+            Box::new(move |meta| {
+                vec![
+                    ops.next_expr(meta) - constant_from!(1),
+                    is_first.next_expr(meta),
+                    frame_id.next_expr(meta) - caller_frame_id.expr(meta),
+                    callee_fid.next_expr(meta) - caller_fid.expr(meta),
+                ]
+                .into_iter()
+                .map(|expr| expr * (constant_from!(1) - is_last.expr(meta)))
+                .collect()
+            }),
+        );
+
+        constraint_builder.push(
+            "op_unreachable prev step",
+            Box::new(move |meta| {
+                vec![
+                    ops.prev_expr(meta) - constant_from!(1),
+                    is_last.prev_expr(meta),
+                ]
+                .into_iter()
+                .map(|expr| expr * (constant_from!(1) - is_first.expr(meta)))
+                .collect()
+            }),
+        );
+
+        constraint_builder.push(
+            "op_unreachable terminates",
+            Box::new(move |meta| vec![is_last.expr(meta) * enabled_cell.next_expr(meta)]),
+        );
+
+        let frame_table_lookup = common_config.jtable_lookup_cell;
+
+        constraint_builder.push(
+            "op_unreachable frame table lookups",
+            Box::new(move |meta| {
+                vec![
+                    frame_table_lookup.returned_cell.expr(meta) - constant_from!(1),
+                    frame_table_lookup.entry_cell.expr(meta)
+                        - JumpTableConfig::encode_lookup(
+                            frame_id.expr(meta),
+                            caller_frame_id.expr(meta),
+                            callee_fid.expr(meta),
+                            caller_fid.expr(meta),
+                            caller_iid.expr(meta),
+                        ),
+                ]
+            }),
+        );
+
+        constraint_builder.push(
+            "op_unreachable public trap code",
+            Box::new(move |meta| {
+                vec![
+                    is_first.expr(meta) * public_input_index.expr(meta)
+                        - public_input_index_for_lookup.expr(meta),
+                    is_first.expr(meta) * trap_code.expr(meta) - trap_code_for_lookup.expr(meta),
+                ]
+            }),
+        );
+
+        constraint_builder.lookup(
+            WASM_INPUT_FOREIGN_TABLE_KEY,
+            "op_unreachable lookup input table",
+            Box::new(move |meta| {
+                vec![
+                    public_input_index_for_lookup.expr(meta),
+                    trap_code_for_lookup.expr(meta),
+                ]
+            }),
+        );
+
+        Box::new(UnreachableConfig {
+            is_first,
+            is_last,
+            is_unreachable,
+            is_div,
+            is_overflow,
+            is_load,
+            is_store,
+            is_fill,
+            is_copy,
+            is_rem,
+            is_sign,
+            is_i32,
+            is_source,
+            len_log_bits,
+            opcode_arg0,
+            opcode_arg1,
+            opcode_offset,
+            operand,
+            has_operand,
+            second_operand,
+            has_second_operand,
+            out_of_bounds_diff,
+            frame_id,
+            callee_fid,
+            caller_frame_id,
+            caller_fid,
+            caller_iid,
+            trap_code,
+            public_input_index_for_lookup,
+            trap_code_for_lookup,
+            frame_table_lookup,
+            memory_table_lookup_stack_read,
+            memory_table_lookup_stack_read_second,
+        })
+    }
+}
+
+impl<F: FieldExt> EventTableOpcodeConfig<F> for UnreachableConfig<F> {
+    fn opcode(&self, meta: &mut VirtualCells<'_, F>) -> Expression<F> {
+        let class = |class: OpcodeClass| {
+            constant!(bn_to_field(
+                &(BigUint::from(class as u64) << OPCODE_CLASS_SHIFT)
+            ))
+        };
+
+        self.is_unreachable.expr(meta) * class(OpcodeClass::Unreachable)
+            + (self.is_div.expr(meta) + self.is_overflow.expr(meta)) * class(OpcodeClass::Bin)
+            + self.is_load.expr(meta) * class(OpcodeClass::Load)
+            + self.is_store.expr(meta) * class(OpcodeClass::Store)
+            + self.is_fill.expr(meta) * class(OpcodeClass::MemoryFill)
+            + self.is_copy.expr(meta) * class(OpcodeClass::MemoryCopy)
+            + self.opcode_arg0.expr(meta)
+                * constant!(bn_to_field(&(BigUint::from(1u64) << OPCODE_ARG0_SHIFT)))
+            + self.opcode_arg1.expr(meta)
+                * constant!(bn_to_field(&(BigUint::from(1u64) << OPCODE_ARG1_SHIFT)))
+            + self.opcode_offset.expr(meta)
+    }
+
+    fn assign(
+        &self,
+        ctx: &mut Context<'_, F>,
+        step: &StepStatus,
+        entry: &EventTableEntryWithMemoryInfo,
+    ) -> Result<(), Error> {
+        match &entry.eentry.step_info {
+            StepInfo::Trap {
+                code,
+                address,
+                size,
+                is_source,
+                frame_index,
+                frame_count,
+                frame_id,
+                callee_fid,
+                caller_frame_id,
+                caller_fid,
+                caller_iid,
+            } => {
+                let is_first = *frame_index == 0;
+
+                self.is_first.assign_bool(ctx, is_first)?;
+                self.is_last
+                    .assign_bool(ctx, *frame_index + 1 == *frame_count)?;
+
+                assert!(code.is_proved_for(&entry.eentry.inst.opcode));
+
+                match &entry.eentry.inst.opcode {
+                    Opcode::Unreachable => {
+                        self.is_unreachable.assign_bool(ctx, true)?;
+                    }
+                    Opcode::Bin { class, vtype } => {
+                        if *code == TrapCode::IntegerOverflow {
+                            self.is_overflow.assign_bool(ctx, true)?;
+                        } else {
+                            self.is_div.assign_bool(ctx, true)?;
+                            self.is_rem.assign_bool(
+                                ctx,
+                                *class == BinOp::UnsignedRem || *class == BinOp::SignedRem,
+                            )?;
+                            self.is_sign.assign_bool(
+                                ctx,
+                                *class == BinOp::SignedDiv || *class == BinOp::SignedRem,
+                            )?;
+                        }
+                        self.is_i32.assign_bool(ctx, *vtype == VarType::I32)?;
+                        self.opcode_arg0.assign(ctx, F::from(*class as u64))?;
+                        self.opcode_arg1.assign(ctx, F::from(*vtype as u64))?;
+                    }
+                    Opcode::Load {
+                        offset,
+                        vtype,
+                        size,
+                    } => {
+                        let len = size.byte_size();
+
+                        self.is_load.assign_bool(ctx, true)?;
+                        self.is_sign.assign_bool(ctx, size.is_sign())?;
+                        self.opcode_offset.assign(ctx, F::from(*offset as u64))?;
+                        self.opcode_arg0.assign(ctx, F::from(*vtype as u64))?;
+                        self.opcode_arg1.assign(ctx, F::from(*size as u64))?;
+                        self.assign_memory_access(ctx, step, *address, *offset, len)?;
+                    }
+                    Opcode::Store {
+                        offset,
+                        vtype,
+                        size,
+                    } => {
+                        let len = size.byte_size();
+
+                        self.is_store.assign_bool(ctx, true)?;
+                        self.opcode_offset.assign(ctx, F::from(*offset as u64))?;
+                        self.opcode_arg0.assign(ctx, F::from(*vtype as u64))?;
+                        self.opcode_arg1.assign(ctx, F::from(*size as u64))?;
+                        self.assign_memory_access(ctx, step, *address, *offset, len)?;
+                    }
+                    Opcode::MemoryFill | Opcode::MemoryCopy => {
+                        let end = *address as u64 + *size as u64 - 1;
+                        let bound = step.current.allocated_memory_pages as u64 * WASM_PAGE_SIZE;
+
+                        if entry.eentry.inst.opcode == Opcode::MemoryFill {
+                            self.is_fill.assign_bool(ctx, true)?;
+                        } else {
+                            self.is_copy.assign_bool(ctx, true)?;
+                        }
+                        self.is_source.assign_bool(ctx, *is_source)?;
+                        self.is_i32.assign_bool(ctx, true)?;
+                        self.out_of_bounds_diff.assign(ctx, end - bound)?;
+                    }
+                    _ => unreachable!(),
+                }
+
+                self.trap_code.assign(ctx, F::from(*code as u64))?;
+                self.public_input_index_for_lookup.assign(
+                    ctx,
+                    F::from(is_first as u64 * step.host_public_inputs as u64),
+                )?;
+                self.trap_code_for_lookup
+                    .assign(ctx, F::from(is_first as u64 * *code as u64))?;
+
+                let mut witness = code
+                    .witness(&entry.eentry.inst.opcode, *address, *size, *is_source)
+                    .into_iter();
+                let mut memory_rw_entries = entry.memory_rw_entires.iter();
+
+                if let Some((depth, vtype, value)) = witness.next() {
+                    self.operand.assign(ctx, value)?;
+                    self.has_operand.assign_bool(ctx, is_first)?;
+
+                    if is_first {
+                        let rw_entry = memory_rw_entries.next().unwrap();
+
+                        self.memory_table_lookup_stack_read.assign(
+                            ctx,
+                            rw_entry.start_eid,
+                            step.current.eid,
+                            rw_entry.end_eid,
+                            step.current.sp + depth,
+                            LocationType::Stack,
+                            vtype == VarType::I32,
+                            value,
+                        )?;
+                    }
+                }
+
+                if let Some((depth, vtype, value)) = witness.next() {
+                    self.second_operand.assign(ctx, value)?;
+                    self.has_second_operand.assign_bool(ctx, is_first)?;
+
+                    if is_first {
+                        let rw_entry = memory_rw_entries.next().unwrap();
+
+                        self.memory_table_lookup_stack_read_second.assign(
+                            ctx,
+                            rw_entry.start_eid,
+                            step.current.eid,
+                            rw_entry.end_eid,
+                            step.current.sp + depth,
+                            LocationType::Stack,
+                            vtype == VarType::I32,
+                            value,
+                        )?;
+                    }
+                }
+
+                self.frame_id.assign_u32(ctx, *frame_id)?;
+                self.callee_fid.assign_u32(ctx, *callee_fid)?;
+                self.caller_frame_id.assign_u32(ctx, *caller_frame_id)?;
+                self.caller_fid.assign_u32(ctx, *caller_fid)?;
+                self.caller_iid.assign_u32(ctx, *caller_iid)?;
+                self.frame_table_lookup
+                    .returned_cell
+                    .assign(ctx, F::one())?;
+                self.frame_table_lookup.entry_cell.assign_bn(
+                    ctx,
+                    &encode_frame_table_entry(
+                        (*frame_id).into(),
+                        (*caller_frame_id).into(),
+                        (*callee_fid).into(),
+                        (*caller_fid).into(),
+                        (*caller_iid).into(),
+                    ),
+                )?;
+
+                Ok(())
+            }
+
+            _ => unreachable!(),
+        }
+    }
+
+    fn jops_expr(&self, _meta: &mut VirtualCells<'_, F>) -> Option<Expression<F>> {
+        Some(constant_from!(1))
+    }
+
+    fn jops(&self, _: &EventTableEntry) -> u32 {
+        1
+    }
+
+    fn terminates(&self, meta: &mut VirtualCells<'_, F>) -> Option<Expression<F>> {
+        Some(self.is_last.expr(meta))
+    }
+
+    fn next_frame_id(
+        &self,
+        meta: &mut VirtualCells<'_, F>,
+        _common_config: &EventTableCommonConfig<F>,
+    ) -> Option<Expression<F>> {
+        Some(self.caller_frame_id.expr(meta))
+    }
+
+    fn next_iid(
+        &self,
+        meta: &mut VirtualCells<'_, F>,
+        common_config: &EventTableCommonConfig<F>,
+    ) -> Option<Expression<F>> {
+        Some(common_config.iid_cell.curr_expr(meta))
+    }
+
+    fn input_index_increase(
+        &self,
+        meta: &mut VirtualCells<'_, F>,
+        _common_config: &EventTableCommonConfig<F>,
+    ) -> Option<Expression<F>> {
+        Some(self.is_first.expr(meta))
+    }
+
+    fn is_host_public_input(&self, entry: &EventTableEntry) -> bool {
+        match &entry.step_info {
+            StepInfo::Trap { frame_index, .. } => *frame_index == 0,
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl<F: FieldExt> UnreachableConfig<F> {
+    fn assign_memory_access(
+        &self,
+        ctx: &mut Context<'_, F>,
+        step: &StepStatus,
+        address: u32,
+        offset: u32,
+        len: u64,
+    ) -> Result<(), Error> {
+        let end = address as u64 + offset as u64 + len - 1;
+        let bound = step.current.allocated_memory_pages as u64 * WASM_PAGE_SIZE;
+
+        self.is_i32.assign_bool(ctx, true)?;
+        self.len_log_bits[0].assign_bool(ctx, len == 2 || len == 8)?;
+        self.len_log_bits[1].assign_bool(ctx, len == 4 || len == 8)?;
+        self.out_of_bounds_diff.assign(ctx, end - bound)?;
+
+        Ok(())
+    }
+}
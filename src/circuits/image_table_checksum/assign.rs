@@ -8,9 +8,9 @@ use specs::brtable::ElemTable;
 use specs::encode::image_table::ImageTableEncoder;
 use specs::imtable::InitMemoryTable;
 use specs::itable::InstructionTable;
-use specs::mtable::LocationType;
 
 use crate::circuits::config::max_image_table_rows;
+use crate::circuits::config::max_init_memory_table_rows;
 use crate::circuits::utils::bn_to_field;
 
 use super::ImageTableChip;
@@ -111,11 +111,25 @@ impl<F: FieldExt> ImageTableChip<F> {
                 }
 
                 {
-                    let heap_entries = init_memory_table.filter(LocationType::Heap);
-                    let global_entries = init_memory_table.filter(LocationType::Global);
+                    let max_rows = max_image_table_rows() as usize;
+                    assert!(offset <= max_rows);
+
+                    while offset < max_rows {
+                        let cell = table.assign_advice(
+                            || "image table padding",
+                            self.config.col,
+                            offset,
+                            || Ok(F::zero()),
+                        )?;
 
+                        ret.push(cell);
+                        offset += 1;
+                    }
+                }
+
+                {
                     let cell = table.assign_advice(
-                        || "br table init cell",
+                        || "init memory table header",
                         self.config.col,
                         offset,
                         || {
@@ -128,7 +142,7 @@ impl<F: FieldExt> ImageTableChip<F> {
                     ret.push(cell);
                     offset += 1;
 
-                    for v in heap_entries.into_iter().chain(global_entries.into_iter()) {
+                    for v in init_memory_table.entries() {
                         let cell = table.assign_advice(
                             || "init memory table cell",
                             self.config.col,
@@ -146,12 +160,12 @@ impl<F: FieldExt> ImageTableChip<F> {
                 }
 
                 {
-                    let max_rows = max_image_table_rows() as usize;
+                    let max_rows = (max_image_table_rows() + max_init_memory_table_rows()) as usize;
                     assert!(offset < max_rows);
 
                     while offset < max_rows {
                         let cell = table.assign_advice(
-                            || "image table padding",
+                            || "init memory table padding",
                             self.config.col,
                             offset,
                             || Ok(F::zero()),
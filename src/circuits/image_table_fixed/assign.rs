@@ -7,7 +7,6 @@ use specs::brtable::ElemTable;
 use specs::encode::image_table::ImageTableEncoder;
 use specs::imtable::InitMemoryTable;
 use specs::itable::InstructionTable;
-use specs::mtable::LocationType;
 
 use crate::circuits::utils::bn_to_field;
 
@@ -103,9 +102,6 @@ impl<F: FieldExt> ImageTableChip<F> {
                 }
 
                 {
-                    let heap_entries = init_memory_table.filter(LocationType::Heap);
-                    let global_entries = init_memory_table.filter(LocationType::Global);
-
                     table.assign_cell(
                         || "init memory table empty",
                         self.config.col,
@@ -119,7 +115,7 @@ impl<F: FieldExt> ImageTableChip<F> {
 
                     offset += 1;
 
-                    for v in heap_entries.into_iter().chain(global_entries.into_iter()) {
+                    for v in init_memory_table.entries() {
                         table.assign_cell(
                             || "init memory table cell",
                             self.config.col,
@@ -4,6 +4,9 @@ use halo2_proofs::circuit::Cell;
 use halo2_proofs::plonk::Error;
 use specs::jtable::JumpTable;
 use specs::jtable::StaticFrameEntry;
+use specs::jtable::INHERITED_FRAME_TABLE_ENTRIES;
+use specs::jtable::STATIC_FRAME_ENTRY_NUMBER;
+use std::collections::HashSet;
 
 use super::JtableOffset;
 use super::JumpTableChip;
@@ -62,118 +65,130 @@ impl<F: FieldExt> JumpTableChip<F> {
         Ok(())
     }
 
-    fn assign_static_entries(
+    fn assign_static_entry(
         &self,
         ctx: &mut Context<'_, F>,
         rest_jops: &mut u64,
-        static_entries: &Vec<StaticFrameEntry>,
+        entry: Option<&StaticFrameEntry>,
+        returned_frames: &HashSet<(u32, u32)>,
     ) -> Result<Vec<AssignedCell<F, F>>, Error> {
-        assert!(static_entries.len() == 1 || static_entries.len() == 2);
+        let enable = F::from(entry.is_some() as u64);
+        let returned = entry.map_or(false, |entry| {
+            returned_frames.contains(&(entry.frame_id, entry.callee_fid))
+        });
+        // The padding entries encode to zero.
+        let encode = entry.map_or(F::zero(), |entry| bn_to_field(&entry.encode()));
 
         let mut cells = vec![];
 
-        for entry in static_entries {
-            ctx.region.assign_fixed(
-                || "jtable start entries",
-                self.config.static_bit,
-                ctx.offset,
-                || Ok(F::one()),
-            )?;
-
-            let cell = if cfg!(feature = "checksum") {
-                ctx.region.assign_advice(
-                    || "jtable enable",
-                    self.config.data,
-                    ctx.offset,
-                    || Ok(F::one()),
-                )?
-            } else {
-                ctx.region.assign_advice_from_constant(
-                    || "jtable enable",
-                    self.config.data,
-                    ctx.offset,
-                    F::one(),
-                )?
-            };
-            cells.push(cell);
-            ctx.next();
+        ctx.region.assign_fixed(
+            || "jtable start entries",
+            self.config.static_bit,
+            ctx.offset,
+            || Ok(F::one()),
+        )?;
 
+        let cell = if cfg!(feature = "checksum") {
             ctx.region.assign_advice(
-                || "jtable rest",
+                || "jtable enable",
                 self.config.data,
                 ctx.offset,
-                || Ok((*rest_jops).into()),
-            )?;
-            ctx.next();
-
-            let cell = if cfg!(feature = "checksum") {
-                ctx.region.assign_advice(
-                    || "jtable entry",
-                    self.config.data,
-                    ctx.offset,
-                    || Ok(bn_to_field(&entry.encode())),
-                )?
-            } else {
-                // Frame Table Constraint 2. Static entry must be a constant."
-                ctx.region.assign_advice_from_constant(
-                    || "jtable entry",
-                    self.config.data,
-                    ctx.offset,
-                    bn_to_field(&entry.encode()),
-                )?
-            };
-            cells.push(cell);
-            ctx.next();
-
-            *rest_jops -= 1;
-        }
-
-        #[cfg(feature = "checksum")]
-        if static_entries.len() != 2 {
-            let rest_f = (*rest_jops).into();
-            let entry = bn_to_field(
-                &StaticFrameEntry {
-                    frame_id: 0,
-                    next_frame_id: 0,
-                    callee_fid: 0,
-                    fid: 0,
-                    iid: 0,
-                }
-                .encode(),
-            );
-
-            ctx.region.assign_fixed(
-                || "jtable start entries",
-                self.config.static_bit,
-                ctx.offset,
-                || Ok(F::one()),
-            )?;
-
-            let cell = ctx.region.assign_advice(
+                || Ok(enable),
+            )?
+        } else {
+            ctx.region.assign_advice_from_constant(
                 || "jtable enable",
                 self.config.data,
                 ctx.offset,
-                || Ok(F::zero()),
-            )?;
-            cells.push(cell);
-            ctx.next();
+                enable,
+            )?
+        };
+        cells.push(cell);
+        ctx.next();
+
+        ctx.region.assign_advice(
+            || "jtable rest",
+            self.config.data,
+            ctx.offset,
+            || Ok((*rest_jops).into()),
+        )?;
+        ctx.next();
 
+        let cell = if cfg!(feature = "checksum") {
             ctx.region.assign_advice(
-                || "jtable rest",
+                || "jtable entry",
                 self.config.data,
                 ctx.offset,
-                || Ok(rest_f),
-            )?;
-            ctx.next();
-
-            let cell = ctx.region.assign_advice(
+                || Ok(encode),
+            )?
+        } else {
+            // Frame Table Constraint 2. Static entry must be a constant."
+            ctx.region.assign_advice_from_constant(
                 || "jtable entry",
                 self.config.data,
                 ctx.offset,
-                || Ok(entry),
-            )?;
-            cells.push(cell);
-            ctx.next();
+                encode,
+            )?
+        };
+        cells.push(cell);
+        ctx.next();
+
+        ctx.region.assign_advice(
+            || "jtable returned",
+            self.config.data,
+            ctx.offset,
+            || Ok(F::from(returned as u64)),
+        )?;
+        ctx.next();
+
+        *rest_jops -= returned as u64;
+
+        Ok(cells)
+    }
+
+    /*
+     * The static entries are the root frames followed by the frames inherited from the
+     * previous segment. With the checksum, they are padded to a fixed number of entries so
+     * that they can be committed by the image checksum.
+     */
+    fn assign_static_entries(
+        &self,
+        ctx: &mut Context<'_, F>,
+        rest_jops: &mut u64,
+        static_entries: &Vec<StaticFrameEntry>,
+        returned_frames: &HashSet<(u32, u32)>,
+    ) -> Result<Vec<AssignedCell<F, F>>, Error> {
+        let (inherited_entries, root_entries): (Vec<_>, Vec<_>) = static_entries
+            .iter()
+            .partition(|entry| entry.is_inherited());
+
+        assert!(root_entries.len() == 1 || root_entries.len() == STATIC_FRAME_ENTRY_NUMBER);
+        assert!(inherited_entries.len() <= INHERITED_FRAME_TABLE_ENTRIES);
+
+        let mut cells = vec![];
+
+        for (entries, capability) in [
+            (root_entries, STATIC_FRAME_ENTRY_NUMBER),
+            (inherited_entries, INHERITED_FRAME_TABLE_ENTRIES),
+        ] {
+            let padding = if cfg!(feature = "checksum") {
+                capability - entries.len()
+            } else {
+                0
+            };
+
+            for entry in entries
+                .into_iter()
+                .map(Some)
+                .chain(std::iter::repeat(None).take(padding))
+            {
+                cells.append(&mut self.assign_static_entry(
+                    ctx,
+                    rest_jops,
+                    entry,
+                    returned_frames,
+                )?);
+            }
         }
 
         Ok(cells)
@@ -184,10 +199,12 @@ impl<F: FieldExt> JumpTableChip<F> {
         ctx: &mut Context<'_, F>,
         rest_jops: &mut u64,
         jtable: &JumpTable,
+        returned_frames: &HashSet<(u32, u32)>,
     ) -> Result<(), Error> {
         for entry in jtable.entries().iter() {
             let rest_f = (*rest_jops).into();
             let entry_f = bn_to_field(&entry.encode());
+            let returned = returned_frames.contains(&(entry.eid, entry.callee_fid));
 
             ctx.region.assign_advice(
                 || "jtable enable",
@@ -213,7 +230,15 @@ impl<F: FieldExt> JumpTableChip<F> {
             )?;
             ctx.next();
 
-            *rest_jops -= 2;
+            ctx.region.assign_advice(
+                || "jtable returned",
+                self.config.data,
+                ctx.offset,
+                || Ok(F::from(returned as u64)),
+            )?;
+            ctx.next();
+
+            *rest_jops -= 1 + returned as u64;
         }
 
         {
@@ -240,6 +265,14 @@ impl<F: FieldExt> JumpTableChip<F> {
                 || Ok(F::zero()),
             )?;
             ctx.next();
+
+            ctx.region.assign_advice(
+                || "jtable returned",
+                self.config.data,
+                ctx.offset,
+                || Ok(F::zero()),
+            )?;
+            ctx.next();
         }
 
         Ok(())
@@ -249,6 +282,7 @@ impl<F: FieldExt> JumpTableChip<F> {
         &self,
         ctx: &mut Context<'_, F>,
         jtable: &JumpTable,
+        returned_frames: &HashSet<(u32, u32)>,
         etable_rest_jops_cell: Option<Cell>,
         static_entries: &Vec<StaticFrameEntry>,
     ) -> Result<Vec<AssignedCell<F, F>>, Error> {
@@ -259,11 +293,19 @@ impl<F: FieldExt> JumpTableChip<F> {
         self.init(ctx)?;
         ctx.reset();
 
-        let mut rest_jops = jtable.entries().len() as u64 * 2 + static_entries.len() as u64;
+        let mut rest_jops = static_entries
+            .iter()
+            .filter(|entry| returned_frames.contains(&(entry.frame_id, entry.callee_fid)))
+            .count() as u64
+            + jtable
+                .entries()
+                .iter()
+                .map(|entry| 1 + returned_frames.contains(&(entry.eid, entry.callee_fid)) as u64)
+                .sum::<u64>();
 
         let frame_table_start_jump_cells =
-            self.assign_static_entries(ctx, &mut rest_jops, static_entries)?;
-        self.assign_jtable_entries(ctx, &mut rest_jops, jtable)?;
+            self.assign_static_entries(ctx, &mut rest_jops, static_entries, returned_frames)?;
+        self.assign_jtable_entries(ctx, &mut rest_jops, jtable, returned_frames)?;
 
         Ok(frame_table_start_jump_cells)
     }
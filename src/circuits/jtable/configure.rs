@@ -1,5 +1,4 @@
 use super::JumpTableConfig;
-use crate::circuits::Lookup;
 use crate::constant_from;
 use crate::fixed_curr;
 use halo2_proofs::arithmetic::FieldExt;
@@ -12,6 +11,7 @@ use halo2_proofs::plonk::VirtualCells;
 pub trait JTableConstraint<F: FieldExt> {
     fn configure(&self, meta: &mut ConstraintSystem<F>) {
         self.enable_is_bit(meta);
+        self.returned_is_bit(meta);
         self.enable_rest_jops_permutation(meta);
         self.configure_rest_jops_decrease(meta);
         self.disabled_block_should_be_end(meta);
@@ -20,6 +20,7 @@ pub trait JTableConstraint<F: FieldExt> {
 
     fn enable_rest_jops_permutation(&self, meta: &mut ConstraintSystem<F>);
     fn enable_is_bit(&self, meta: &mut ConstraintSystem<F>);
+    fn returned_is_bit(&self, meta: &mut ConstraintSystem<F>);
     fn configure_rest_jops_decrease(&self, meta: &mut ConstraintSystem<F>);
     fn disabled_block_should_be_end(&self, meta: &mut ConstraintSystem<F>);
     fn disabled_block_has_no_entry_value(&self, meta: &mut ConstraintSystem<F>);
@@ -40,11 +41,26 @@ impl<F: FieldExt> JTableConstraint<F> for JumpTableConfig<F> {
         });
     }
 
+    fn returned_is_bit(&self, meta: &mut ConstraintSystem<F>) {
+        meta.create_gate("returned is bit", |meta| {
+            vec![
+                self.returned(meta)
+                    * (self.returned(meta) - constant_from!(1))
+                    * fixed_curr!(meta, self.sel),
+            ]
+        });
+    }
+
+    /*
+     * A dynamic entry is looked up by its call, and by its return if the frame is left
+     * within the table. A static entry is only looked up by its return.
+     */
     fn configure_rest_jops_decrease(&self, meta: &mut ConstraintSystem<F>) {
         meta.create_gate("c3. jtable rest decrease", |meta| {
             vec![
-                (self.rest(meta) - self.next_rest(meta) - constant_from!(2)
-                    + self.static_bit(meta))
+                (self.rest(meta) - self.next_rest(meta) - constant_from!(1)
+                    + self.static_bit(meta)
+                    - self.returned(meta))
                     * self.enable(meta)
                     * fixed_curr!(meta, self.sel),
                 (self.rest(meta) - self.next_rest(meta))
@@ -71,30 +87,33 @@ impl<F: FieldExt> JTableConstraint<F> for JumpTableConfig<F> {
                 (constant_from!(1) - self.enable(meta))
                     * self.entry(meta)
                     * fixed_curr!(meta, self.sel),
+                (constant_from!(1) - self.enable(meta))
+                    * self.returned(meta)
+                    * fixed_curr!(meta, self.sel),
             ]
         });
     }
 }
 
-impl<F: FieldExt> Lookup<F> for JumpTableConfig<F> {
-    /// Frame Table Constraint 4. Etable step's call/return record can be found on jtable_entry
-    fn configure_in_table(
+impl<F: FieldExt> JumpTableConfig<F> {
+    /// Frame Table Constraint 4. Etable step's call/return record can be found on jtable_entry,
+    /// together with whether the frame is left within the table.
+    pub(crate) fn configure_in_table(
         &self,
         meta: &mut ConstraintSystem<F>,
         key: &'static str,
-        expr: impl FnOnce(&mut VirtualCells<'_, F>) -> Expression<F>,
+        expr: impl FnOnce(&mut VirtualCells<'_, F>) -> (Expression<F>, Expression<F>),
     ) {
         meta.lookup_any(key, |meta| {
-            vec![(expr(meta), self.entry(meta) * fixed_curr!(meta, self.sel))]
-        });
-    }
+            let (entry, returned) = expr(meta);
 
-    fn encode(&self, _meta: &mut VirtualCells<'_, F>) -> Expression<F> {
-        unimplemented!()
+            vec![
+                (entry, self.entry(meta) * fixed_curr!(meta, self.sel)),
+                (returned, self.returned(meta) * fixed_curr!(meta, self.sel)),
+            ]
+        });
     }
-}
 
-impl<F: FieldExt> JumpTableConfig<F> {
     pub(super) fn new(
         meta: &mut ConstraintSystem<F>,
         cols: &mut impl Iterator<Item = Column<Advice>>,
@@ -28,6 +28,10 @@ impl<F: FieldExt> JumpTableConfig<F> {
         nextn!(meta, self.data, JtableOffset::JtableOffsetEntry as i32)
     }
 
+    pub(super) fn returned(&self, meta: &mut VirtualCells<F>) -> Expression<F> {
+        nextn!(meta, self.data, JtableOffset::JtableOffsetReturned as i32)
+    }
+
     pub(super) fn static_bit(&self, meta: &mut VirtualCells<F>) -> Expression<F> {
         fixed_curr!(meta, self.static_bit)
     }
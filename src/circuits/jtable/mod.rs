@@ -14,7 +14,8 @@ pub enum JtableOffset {
     JtableOffsetEnable = 0,
     JtableOffsetRest = 1,
     JtableOffsetEntry = 2,
-    JtableOffsetMax = 3,
+    JtableOffsetReturned = 3,
+    JtableOffsetMax = 4,
 }
 
 #[derive(Clone)]
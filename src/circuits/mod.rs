@@ -1,5 +1,9 @@
-use crate::circuits::config::zkwasm_k;
 use crate::circuits::utils::Context;
+use crate::foreign::kv_helper::kv_instances;
+use crate::prover::create_proof_with_transcript;
+use crate::prover::verify_proof_with_transcript;
+use crate::prover::Proof;
+use crate::prover::Transcript;
 
 #[cfg(feature = "checksum")]
 use crate::image_hasher::ImageHasher;
@@ -10,23 +14,16 @@ use halo2_proofs::arithmetic::FieldExt;
 use halo2_proofs::pairing::bn256::Bn256;
 use halo2_proofs::pairing::bn256::Fr;
 use halo2_proofs::pairing::bn256::G1Affine;
-use halo2_proofs::plonk::create_proof;
 use halo2_proofs::plonk::keygen_pk;
 use halo2_proofs::plonk::keygen_vk;
-use halo2_proofs::plonk::verify_proof;
 use halo2_proofs::plonk::ConstraintSystem;
 use halo2_proofs::plonk::Expression;
 use halo2_proofs::plonk::ProvingKey;
-use halo2_proofs::plonk::SingleVerifier;
 use halo2_proofs::plonk::VerifyingKey;
 use halo2_proofs::plonk::VirtualCells;
 use halo2_proofs::poly::commitment::Params;
 use halo2_proofs::poly::commitment::ParamsVerifier;
-use halo2_proofs::transcript::Blake2bRead;
-use halo2_proofs::transcript::Blake2bWrite;
-use halo2_proofs::transcript::Challenge255;
 use num_bigint::BigUint;
-use rand::rngs::OsRng;
 use specs::Tables;
 use std::fs::File;
 use std::io::Cursor;
@@ -37,9 +34,9 @@ use std::path::PathBuf;
 pub(crate) mod cell;
 pub(crate) mod etable;
 
-mod bit_table;
+pub(crate) mod bit_table;
 mod external_host_call_table;
-mod mtable;
+pub(crate) mod mtable;
 mod traits;
 
 #[cfg(feature = "checksum")]
@@ -48,6 +45,8 @@ pub mod checksum;
 pub mod image_table_checksum;
 #[cfg(feature = "checksum")]
 pub use image_table_checksum as image_table;
+#[cfg(feature = "checksum")]
+mod post_image_table;
 
 #[cfg(not(feature = "checksum"))]
 pub mod image_table_fixed;
@@ -58,25 +57,35 @@ use self::config::CircuitConfigure;
 
 pub mod config;
 pub mod jtable;
+pub mod poseidon;
 pub mod rtable;
 pub mod test_circuit;
 pub mod utils;
 
-#[derive(Default, Clone)]
+#[derive(Clone)]
 pub struct TestCircuit<F: FieldExt> {
     pub tables: Tables,
+    circuit_configure: CircuitConfigure,
     _data: PhantomData<F>,
 }
 
 impl<F: FieldExt> TestCircuit<F> {
-    pub fn new(tables: Tables) -> Self {
-        CircuitConfigure::from(&tables.compilation_tables).set_global_CIRCUIT_CONFIGURE();
-
+    pub fn new(k: u32, tables: Tables) -> Self {
         TestCircuit {
+            circuit_configure: CircuitConfigure::new(k, &tables.compilation_tables),
             tables,
             _data: PhantomData,
         }
     }
+
+    pub fn k(&self) -> u32 {
+        self.circuit_configure.k
+    }
+
+    /// The configuration of the circuit, its `Circuit::Params`.
+    pub fn circuit_configure(&self) -> &CircuitConfigure {
+        &self.circuit_configure
+    }
 }
 
 trait Encode {
@@ -97,17 +106,20 @@ pub(self) trait Lookup<F: FieldExt> {
 }
 
 pub struct ZkWasmCircuitBuilder {
+    pub k: u32,
     pub tables: Tables,
     pub public_inputs_and_outputs: Vec<u64>,
 }
 
 impl ZkWasmCircuitBuilder {
     pub fn build_circuit<F: FieldExt>(&self) -> TestCircuit<F> {
-        TestCircuit::new(self.tables.clone())
+        TestCircuit::new(self.k, self.tables.clone())
     }
 
+    /// The builder only serves tests and benches, its params come from an unsafe setup and
+    /// are cached in the working directory.
     fn prepare_param(&self) -> Params<G1Affine> {
-        let path = PathBuf::from(format!("test_param.{}.data", zkwasm_k()));
+        let path = PathBuf::from(format!("test_param.{}.data", self.k));
 
         if path.exists() {
             let mut fd = File::open(path.as_path()).unwrap();
@@ -117,8 +129,8 @@ impl ZkWasmCircuitBuilder {
             Params::<G1Affine>::read(Cursor::new(buf)).unwrap()
         } else {
             // Initialize the polynomial commitment parameters
-            let timer = start_timer!(|| format!("build params with K = {}", zkwasm_k()));
-            let params: Params<G1Affine> = Params::<G1Affine>::unsafe_setup::<Bn256>(zkwasm_k());
+            let timer = start_timer!(|| format!("build params with K = {}", self.k));
+            let params: Params<G1Affine> = Params::<G1Affine>::unsafe_setup::<Bn256>(self.k);
             end_timer!(timer);
 
             let mut fd = File::create(path.as_path()).unwrap();
@@ -158,48 +170,46 @@ impl ZkWasmCircuitBuilder {
         params: &Params<G1Affine>,
         pk: &ProvingKey<G1Affine>,
         instance: &Vec<Fr>,
-    ) -> Vec<u8> {
-        let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
-
-        let timer = start_timer!(|| "create proof");
-        create_proof(params, pk, circuits, &[&[instance]], OsRng, &mut transcript)
+        transcript: Transcript,
+    ) -> Proof {
+        let timer = start_timer!(|| format!("create proof with {} transcript", transcript));
+        let proof = create_proof_with_transcript(transcript, params, pk, circuits, &[&[instance]])
             .expect("proof generation should not fail");
         end_timer!(timer);
 
-        transcript.finalize()
+        proof
     }
 
     fn verify_check(
         &self,
         vk: &VerifyingKey<G1Affine>,
         params: &Params<G1Affine>,
-        proof: &Vec<u8>,
+        proof: &Proof,
         instance: &Vec<Fr>,
     ) {
         let public_inputs_size = instance.len();
 
         let params_verifier: ParamsVerifier<Bn256> = params.verifier(public_inputs_size).unwrap();
 
-        let strategy = SingleVerifier::new(&params_verifier);
-        let mut transcript = Blake2bRead::<_, _, Challenge255<_>>::init(&proof[..]);
-
         let timer = start_timer!(|| "verify proof");
-        verify_proof(
-            &params_verifier,
-            vk,
-            strategy,
-            &[&[instance]],
-            &mut transcript,
-        )
-        .unwrap();
+        verify_proof_with_transcript(&params_verifier, vk, &[&[instance]], proof).unwrap();
         end_timer!(timer);
     }
 
     pub fn bench(&self) {
+        self.bench_with_transcript(Transcript::Blake2b)
+    }
+
+    pub fn bench_with_transcript(&self, transcript: Transcript) {
         let mut instances = vec![];
 
         #[cfg(feature = "checksum")]
         instances.push(self.tables.compilation_tables.hash());
+        #[cfg(feature = "checksum")]
+        instances.push(self.tables.post_image_table.hash());
+
+        instances.append(&mut kv_instances(&self.tables));
+        instances.push(Fr::from(self.tables.is_terminated() as u64));
 
         instances.append(
             &mut self
@@ -216,7 +226,7 @@ impl ZkWasmCircuitBuilder {
         let vk = self.prepare_vk(&circuit, &params);
         let pk = self.prepare_pk(&circuit, &params, vk);
 
-        let proof = self.create_proof(&[circuit], &params, &pk, &instances);
+        let proof = self.create_proof(&[circuit], &params, &pk, &instances, transcript);
 
         self.verify_check(pk.get_vk(), &params, &proof, &instances);
     }
@@ -12,6 +12,8 @@ use halo2_proofs::plonk::ConstraintSystem;
 use halo2_proofs::plonk::Expression;
 use halo2_proofs::plonk::Fixed;
 use halo2_proofs::plonk::VirtualCells;
+#[cfg(feature = "checksum")]
+use specs::encode::image_table::ImageTableEncoder;
 use specs::encode::init_memory_table::encode_init_memory_table_entry;
 use specs::encode::memory_table::encode_memory_table_entry;
 use specs::mtable::LocationType;
@@ -344,6 +346,34 @@ impl<F: FieldExt> MemoryTableConfig<F> {
     }
 }
 
+impl<F: FieldExt> MemoryTableConfig<F> {
+    /// The value each location holds after its last access, in the encoding of the init
+    /// memory table. It is zero on the other rows.
+    #[cfg(feature = "checksum")]
+    pub(in crate::circuits) fn finalized_memory_expr(
+        &self,
+        meta: &mut VirtualCells<'_, F>,
+    ) -> Expression<F> {
+        let offset = self.offset_cell.curr_expr(meta);
+
+        ImageTableEncoder::InitMemory.encode(
+            self.enabled_cell.curr_expr(meta)
+                * (constant_from!(1) - self.is_next_same_offset_cell.curr_expr(meta))
+                * encode_init_memory_table_entry(
+                    self.is_stack_cell.curr_expr(meta) * constant_from!(LocationType::Stack as u64)
+                        + self.is_heap_cell.curr_expr(meta)
+                            * constant_from!(LocationType::Heap as u64)
+                        + self.is_global_cell.curr_expr(meta)
+                            * constant_from!(LocationType::Global as u64),
+                    self.is_mutable.curr_expr(meta),
+                    offset.clone(),
+                    offset,
+                    self.value.u64_cell.curr_expr(meta),
+                ),
+        ) * fixed_curr!(meta, self.entry_sel)
+    }
+}
+
 impl<F: FieldExt> ConfigureLookupTable<F> for MemoryTableConfig<F> {
     fn configure_in_table(
         &self,
@@ -0,0 +1,327 @@
+use std::collections::HashSet;
+
+use halo2_proofs::arithmetic::FieldExt;
+use halo2_proofs::circuit::AssignedCell;
+use halo2_proofs::circuit::Layouter;
+use halo2_proofs::plonk::Error;
+use num_bigint::BigUint;
+use specs::encode::image_table::ImageTableEncoder;
+use specs::imtable::InitMemoryTableEntry;
+use specs::jtable::StaticFrameEntry;
+use specs::jtable::INHERITED_FRAME_TABLE_ENTRIES;
+use specs::mtable::LocationType;
+use specs::CompilationTable;
+use specs::ExecutionTable;
+
+use super::PostImageTableCells;
+use super::PostImageTableChip;
+use crate::circuits::config::max_init_memory_table_rows;
+use crate::circuits::utils::bn_to_field;
+use crate::circuits::utils::Context;
+
+impl<F: FieldExt> PostImageTableChip<F> {
+    fn assign_memory_entry(
+        &self,
+        ctx: &mut Context<'_, F>,
+        pre_image_table: &CompilationTable,
+        touched: &HashSet<(LocationType, u32)>,
+        entry: Option<&InitMemoryTableEntry>,
+        next_entry: Option<&InitMemoryTableEntry>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        self.config.enable.assign(ctx, entry.is_some())?;
+
+        let entry = match entry {
+            Some(entry) => entry,
+            None => {
+                return ctx.region.assign_advice(
+                    || "post memory",
+                    self.config.post,
+                    ctx.offset,
+                    || Ok(F::zero()),
+                )
+            }
+        };
+
+        let is_touched = entry.start_offset == entry.end_offset
+            && touched.contains(&(entry.ltype, entry.start_offset));
+        let len = entry.end_offset - entry.start_offset;
+        let next_in_same_ltype = next_entry.filter(|next| next.ltype == entry.ltype);
+
+        self.config.is_touched.assign(ctx, is_touched)?;
+        self.config.is_mutable.assign(ctx, entry.is_mutable)?;
+        self.config
+            .is_next_same_ltype
+            .assign(ctx, next_in_same_ltype.is_some())?;
+        ctx.region.assign_advice(
+            || "post memory ltype",
+            self.config.ltype,
+            ctx.offset,
+            || Ok(F::from(entry.ltype as u64)),
+        )?;
+        self.config
+            .start_offset
+            .assign(ctx, entry.start_offset as u64)?;
+        self.config.len.assign(ctx, len as u64)?;
+        self.config.order_diff.assign(
+            ctx,
+            next_in_same_ltype.map_or(0, |next| (next.start_offset - entry.end_offset - 1) as u64),
+        )?;
+
+        if !is_touched {
+            let (source_start, source_end, _) = pre_image_table
+                .imtable
+                .try_find(entry.ltype, entry.start_offset)
+                .unwrap();
+
+            self.config
+                .source_left
+                .assign(ctx, (entry.start_offset - source_start) as u64)?;
+            self.config
+                .source_right
+                .assign(ctx, (source_end - entry.end_offset) as u64)?;
+        }
+
+        ctx.region.assign_advice(
+            || "post memory value",
+            self.config.value,
+            ctx.offset,
+            || Ok(F::from(entry.value)),
+        )?;
+        for (i, limb) in self.config.value_u16_le.iter().enumerate() {
+            limb.assign(ctx, (entry.value >> (i * 16)) & 0xffff)?;
+        }
+
+        ctx.region.assign_advice(
+            || "post memory",
+            self.config.post,
+            ctx.offset,
+            || {
+                Ok(bn_to_field(
+                    &ImageTableEncoder::InitMemory.encode(entry.encode()),
+                ))
+            },
+        )
+    }
+
+    fn assign_memory(
+        &self,
+        ctx: &mut Context<'_, F>,
+        pre_image_table: &CompilationTable,
+        post_image_table: &CompilationTable,
+        execution_tables: &ExecutionTable,
+    ) -> Result<Vec<AssignedCell<F, F>>, Error> {
+        let max_rows = max_init_memory_table_rows() as usize;
+        let entries = post_image_table.imtable.entries();
+        assert!(entries.len() < max_rows);
+
+        let touched = execution_tables
+            .mtable
+            .entries()
+            .iter()
+            .map(|entry| (entry.ltype, entry.offset))
+            .collect::<HashSet<_>>();
+
+        let mut cells = vec![];
+
+        cells.push(ctx.region.assign_advice_from_constant(
+            || "post memory header",
+            self.config.post,
+            ctx.offset,
+            bn_to_field(&ImageTableEncoder::InitMemory.encode(BigUint::from(0u64))),
+        )?);
+        ctx.next();
+
+        for index in 0..max_rows - 1 {
+            ctx.region.assign_fixed(
+                || "post memory sel",
+                self.config.memory_sel,
+                ctx.offset,
+                || Ok(F::one()),
+            )?;
+
+            if index < max_rows - 2 {
+                ctx.region.assign_fixed(
+                    || "post memory continue",
+                    self.config.memory_continue,
+                    ctx.offset,
+                    || Ok(F::one()),
+                )?;
+            }
+
+            cells.push(self.assign_memory_entry(
+                ctx,
+                pre_image_table,
+                &touched,
+                entries.get(index),
+                entries.get(index + 1),
+            )?);
+            ctx.next();
+        }
+
+        Ok(cells)
+    }
+
+    fn assign_frames(
+        &self,
+        ctx: &mut Context<'_, F>,
+        pre_image_table: &CompilationTable,
+        execution_tables: &ExecutionTable,
+        terminate_frame_id: &AssignedCell<F, F>,
+    ) -> Result<
+        (
+            Vec<AssignedCell<F, F>>,
+            AssignedCell<F, F>,
+            AssignedCell<F, F>,
+        ),
+        Error,
+    > {
+        let live_frames = execution_tables.live_frames(&pre_image_table.static_jtable);
+        assert!(live_frames.len() <= INHERITED_FRAME_TABLE_ENTRIES);
+
+        let depth = live_frames.len() as u64;
+        let terminal_frame_id = live_frames.last().map_or(0, |frame| frame.frame_id);
+
+        let mut cells = vec![];
+        let mut first_cells = None;
+
+        for index in 0..INHERITED_FRAME_TABLE_ENTRIES {
+            let label = index as u32 + 1;
+            let frame = live_frames.get(index);
+
+            ctx.region.assign_fixed(
+                || "post frame sel",
+                self.config.frame_sel,
+                ctx.offset,
+                || Ok(F::one()),
+            )?;
+            ctx.region.assign_fixed(
+                || "post frame label",
+                self.config.label,
+                ctx.offset,
+                || Ok(F::from(label as u64)),
+            )?;
+            if index == 0 {
+                ctx.region.assign_fixed(
+                    || "post frame first",
+                    self.config.frame_first,
+                    ctx.offset,
+                    || Ok(F::one()),
+                )?;
+            }
+            if index < INHERITED_FRAME_TABLE_ENTRIES - 1 {
+                ctx.region.assign_fixed(
+                    || "post frame continue",
+                    self.config.frame_continue,
+                    ctx.offset,
+                    || Ok(F::one()),
+                )?;
+            }
+
+            let terminal_cell = ctx.region.assign_advice(
+                || "post frame terminal",
+                self.config.terminal_frame_id,
+                ctx.offset,
+                || Ok(F::from(terminal_frame_id as u64)),
+            )?;
+            let depth_cell = ctx.region.assign_advice(
+                || "post frame depth",
+                self.config.depth,
+                ctx.offset,
+                || Ok(F::from(depth)),
+            )?;
+
+            let enable = frame.is_some();
+            let encode = frame.map_or(F::zero(), |frame| {
+                bn_to_field(
+                    &StaticFrameEntry {
+                        frame_id: label,
+                        next_frame_id: label - 1,
+                        ..frame.clone()
+                    }
+                    .encode(),
+                )
+            });
+
+            cells.push(ctx.region.assign_advice(
+                || "post frame enable",
+                self.config.enable.col,
+                ctx.offset,
+                || Ok(F::from(enable as u64)),
+            )?);
+            cells.push(ctx.region.assign_advice(
+                || "post frame",
+                self.config.post,
+                ctx.offset,
+                || Ok(encode),
+            )?);
+
+            if let Some(frame) = frame {
+                self.config.frame_id.assign(ctx, frame.frame_id as u64)?;
+                self.config
+                    .next_frame_id
+                    .assign(ctx, frame.next_frame_id as u64)?;
+                self.config
+                    .callee_fid
+                    .assign(ctx, frame.callee_fid as u64)?;
+                self.config.fid.assign(ctx, frame.fid as u64)?;
+                self.config.iid.assign(ctx, frame.iid as u64)?;
+            }
+
+            if index == 0 {
+                ctx.region
+                    .constrain_equal(terminal_cell.cell(), terminate_frame_id.cell())?;
+
+                let eid_cell = ctx.region.assign_advice(
+                    || "post eid",
+                    self.config.post_eid,
+                    ctx.offset,
+                    || Ok(F::from(depth + 1)),
+                )?;
+
+                first_cells = Some((eid_cell, depth_cell));
+            }
+
+            ctx.next();
+        }
+
+        let (eid_cell, depth_cell) = first_cells.unwrap();
+
+        Ok((cells, eid_cell, depth_cell))
+    }
+
+    pub(crate) fn assign(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        pre_image_table: &CompilationTable,
+        post_image_table: &CompilationTable,
+        execution_tables: &ExecutionTable,
+        terminate_frame_id: &AssignedCell<F, F>,
+    ) -> Result<PostImageTableCells<F>, Error> {
+        layouter.assign_region(
+            || "post image table",
+            |region| {
+                let mut ctx = Context::new(region, self.k);
+
+                let memory = self.assign_memory(
+                    &mut ctx,
+                    pre_image_table,
+                    post_image_table,
+                    execution_tables,
+                )?;
+                let (frames, eid, depth) = self.assign_frames(
+                    &mut ctx,
+                    pre_image_table,
+                    execution_tables,
+                    terminate_frame_id,
+                )?;
+
+                Ok(PostImageTableCells {
+                    memory,
+                    frames,
+                    eid,
+                    depth,
+                })
+            },
+        )
+    }
+}
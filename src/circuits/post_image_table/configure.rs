@@ -0,0 +1,336 @@
+use halo2_proofs::arithmetic::FieldExt;
+use halo2_proofs::plonk::ConstraintSystem;
+use halo2_proofs::plonk::Expression;
+use halo2_proofs::plonk::VirtualCells;
+use specs::encode::frame_table::encode_frame_table_entry;
+use specs::encode::image_table::ImageTableEncoder;
+use specs::encode::init_memory_table::encode_init_memory_table_entry;
+use specs::mtable::LocationType;
+
+use super::PostImageTableConfig;
+use crate::circuits::image_table::ImageTableConfig;
+use crate::circuits::jtable::JumpTableConfig;
+use crate::circuits::mtable::MemoryTableConfig;
+use crate::circuits::rtable::RangeTableConfig;
+use crate::circuits::utils::bit::BitColumn;
+use crate::circuits::utils::common_range::CommonRangeColumn;
+use crate::circuits::utils::u16::U16Column;
+use crate::constant_from;
+use crate::curr;
+use crate::fixed_curr;
+use crate::next;
+
+impl<F: FieldExt> PostImageTableConfig<F> {
+    pub(in crate::circuits) fn configure(
+        meta: &mut ConstraintSystem<F>,
+        rtable: &RangeTableConfig<F>,
+        image_table: &ImageTableConfig<F>,
+        mtable: &MemoryTableConfig<F>,
+        jtable: &JumpTableConfig<F>,
+    ) -> Self {
+        let memory_sel = meta.fixed_column();
+        let memory_continue = meta.fixed_column();
+        let frame_sel = meta.fixed_column();
+        let frame_first = meta.fixed_column();
+        let frame_continue = meta.fixed_column();
+        let label = meta.fixed_column();
+
+        let post = meta.advice_column();
+        let ltype = meta.advice_column();
+        let value = meta.advice_column();
+        let terminal_frame_id = meta.advice_column();
+        let depth = meta.advice_column();
+        let post_eid = meta.advice_column();
+        meta.enable_equality(post);
+        meta.enable_equality(terminal_frame_id);
+        meta.enable_equality(depth);
+        meta.enable_equality(post_eid);
+
+        let mut cols = [(); 18].map(|_| meta.advice_column()).into_iter();
+
+        let enable = BitColumn::configure(meta, &mut cols, |meta| {
+            fixed_curr!(meta, memory_sel) + fixed_curr!(meta, frame_sel)
+        });
+        meta.enable_equality(enable.col);
+
+        let is_touched =
+            BitColumn::configure(meta, &mut cols, |meta| fixed_curr!(meta, memory_sel));
+        let is_mutable =
+            BitColumn::configure(meta, &mut cols, |meta| fixed_curr!(meta, memory_sel));
+        let is_next_same_ltype =
+            BitColumn::configure(meta, &mut cols, |meta| fixed_curr!(meta, memory_sel));
+
+        let start_offset = CommonRangeColumn::configure(meta, &mut cols, rtable, |meta| {
+            fixed_curr!(meta, memory_sel)
+        });
+        let len = CommonRangeColumn::configure(meta, &mut cols, rtable, |meta| {
+            fixed_curr!(meta, memory_sel)
+        });
+        let order_diff = CommonRangeColumn::configure(meta, &mut cols, rtable, |meta| {
+            fixed_curr!(meta, memory_sel)
+        });
+        let source_left = CommonRangeColumn::configure(meta, &mut cols, rtable, |meta| {
+            fixed_curr!(meta, memory_sel)
+        });
+        let source_right = CommonRangeColumn::configure(meta, &mut cols, rtable, |meta| {
+            fixed_curr!(meta, memory_sel)
+        });
+        let value_u16_le = [(); 4].map(|_| {
+            U16Column::configure(meta, &mut cols, rtable, |meta| {
+                fixed_curr!(meta, memory_sel)
+            })
+        });
+
+        let frame_id = CommonRangeColumn::configure(meta, &mut cols, rtable, |meta| {
+            fixed_curr!(meta, frame_sel)
+        });
+        let next_frame_id = CommonRangeColumn::configure(meta, &mut cols, rtable, |meta| {
+            fixed_curr!(meta, frame_sel)
+        });
+        let callee_fid = CommonRangeColumn::configure(meta, &mut cols, rtable, |meta| {
+            fixed_curr!(meta, frame_sel)
+        });
+        let fid = CommonRangeColumn::configure(meta, &mut cols, rtable, |meta| {
+            fixed_curr!(meta, frame_sel)
+        });
+        let iid = CommonRangeColumn::configure(meta, &mut cols, rtable, |meta| {
+            fixed_curr!(meta, frame_sel)
+        });
+
+        let config = Self {
+            memory_sel,
+            memory_continue,
+            frame_sel,
+            frame_first,
+            frame_continue,
+            label,
+            post,
+            enable,
+            is_touched,
+            is_mutable,
+            is_next_same_ltype,
+            ltype,
+            start_offset,
+            len,
+            order_diff,
+            source_left,
+            source_right,
+            value,
+            value_u16_le,
+            frame_id,
+            next_frame_id,
+            callee_fid,
+            fid,
+            iid,
+            terminal_frame_id,
+            depth,
+            post_eid,
+        };
+
+        config.configure_memory(meta, image_table, mtable);
+        config.configure_frames(meta, jtable);
+
+        config
+    }
+
+    fn configure_memory(
+        &self,
+        meta: &mut ConstraintSystem<F>,
+        image_table: &ImageTableConfig<F>,
+        mtable: &MemoryTableConfig<F>,
+    ) {
+        meta.create_gate("post memory enable seq", |meta| {
+            vec![
+                (constant_from!(1) - curr!(meta, self.enable.col))
+                    * next!(meta, self.enable.col)
+                    * fixed_curr!(meta, self.memory_continue),
+            ]
+        });
+
+        meta.create_gate("post memory ltype", |meta| {
+            let ltype = curr!(meta, self.ltype);
+
+            vec![
+                curr!(meta, self.enable.col)
+                    * (ltype.clone() - constant_from!(LocationType::Stack as u64))
+                    * (ltype.clone() - constant_from!(LocationType::Heap as u64))
+                    * (ltype - constant_from!(LocationType::Global as u64))
+                    * fixed_curr!(meta, self.memory_sel),
+            ]
+        });
+
+        /*
+         * The rows are sorted by (ltype, start_offset) without overlapping, so a location is
+         * held by at most one row.
+         */
+        meta.create_gate("post memory sort", |meta| {
+            let ltype_diff = next!(meta, self.ltype) - curr!(meta, self.ltype);
+
+            vec![
+                curr!(meta, self.is_next_same_ltype.col) * ltype_diff.clone(),
+                curr!(meta, self.is_next_same_ltype.col)
+                    * (curr!(meta, self.start_offset.col)
+                        + curr!(meta, self.len.col)
+                        + curr!(meta, self.order_diff.col)
+                        + constant_from!(1)
+                        - next!(meta, self.start_offset.col)),
+                (constant_from!(1) - curr!(meta, self.is_next_same_ltype.col))
+                    * (ltype_diff.clone() - constant_from!(1))
+                    * (ltype_diff - constant_from!(2)),
+            ]
+            .into_iter()
+            .map(|x| x * next!(meta, self.enable.col) * fixed_curr!(meta, self.memory_continue))
+            .collect::<Vec<_>>()
+        });
+
+        meta.create_gate("post memory value", |meta| {
+            vec![
+                curr!(meta, self.value)
+                    - self
+                        .value_u16_le
+                        .iter()
+                        .enumerate()
+                        .map(|(i, limb)| curr!(meta, limb.col) * constant_from!(1u64 << (i * 16)))
+                        .fold(constant_from!(0), |acc, x| acc + x),
+                curr!(meta, self.is_touched.col) * curr!(meta, self.len.col),
+            ]
+            .into_iter()
+            .map(|x| x * fixed_curr!(meta, self.memory_sel))
+            .collect::<Vec<_>>()
+        });
+
+        meta.create_gate("post memory encode", |meta| {
+            vec![
+                (curr!(meta, self.post)
+                    - curr!(meta, self.enable.col)
+                        * ImageTableEncoder::InitMemory.encode(encode_init_memory_table_entry(
+                            curr!(meta, self.ltype),
+                            curr!(meta, self.is_mutable.col),
+                            curr!(meta, self.start_offset.col),
+                            curr!(meta, self.start_offset.col) + curr!(meta, self.len.col),
+                            curr!(meta, self.value),
+                        )))
+                    * fixed_curr!(meta, self.memory_sel),
+            ]
+        });
+
+        // An untouched row is a sub-range of an entry of the init memory table.
+        image_table.init_memory_lookup(meta, "post memory untouched", |meta| {
+            curr!(meta, self.enable.col)
+                * (constant_from!(1) - curr!(meta, self.is_touched.col))
+                * encode_init_memory_table_entry(
+                    curr!(meta, self.ltype),
+                    curr!(meta, self.is_mutable.col),
+                    curr!(meta, self.start_offset.col) - curr!(meta, self.source_left.col),
+                    curr!(meta, self.start_offset.col)
+                        + curr!(meta, self.len.col)
+                        + curr!(meta, self.source_right.col),
+                    curr!(meta, self.value),
+                )
+                * fixed_curr!(meta, self.memory_sel)
+        });
+
+        // A touched row holds the last value of its location in the mtable.
+        meta.lookup_any("post memory touched", |meta| {
+            vec![(
+                curr!(meta, self.is_touched.col)
+                    * curr!(meta, self.post)
+                    * fixed_curr!(meta, self.memory_sel),
+                mtable.finalized_memory_expr(meta),
+            )]
+        });
+
+        // Every location accessed by the execution is kept.
+        meta.lookup_any("post memory finalized", |meta| {
+            vec![(mtable.finalized_memory_expr(meta), curr!(meta, self.post))]
+        });
+    }
+
+    fn frame_expr(&self, meta: &mut VirtualCells<'_, F>) -> Expression<F> {
+        encode_frame_table_entry(
+            curr!(meta, self.frame_id.col),
+            curr!(meta, self.next_frame_id.col),
+            curr!(meta, self.callee_fid.col),
+            curr!(meta, self.fid.col),
+            curr!(meta, self.iid.col),
+        )
+    }
+
+    fn configure_frames(&self, meta: &mut ConstraintSystem<F>, jtable: &JumpTableConfig<F>) {
+        meta.create_gate("post frame chain", |meta| {
+            let enable = curr!(meta, self.enable.col);
+            let continue_to_next =
+                fixed_curr!(meta, self.frame_continue) * next!(meta, self.enable.col);
+            let is_last = enable.clone() - continue_to_next.clone();
+
+            vec![
+                (constant_from!(1) - enable.clone())
+                    * next!(meta, self.enable.col)
+                    * fixed_curr!(meta, self.frame_continue),
+                // The outermost frame is called by a root frame.
+                enable.clone()
+                    * curr!(meta, self.next_frame_id.col)
+                    * fixed_curr!(meta, self.frame_first),
+                continue_to_next
+                    * (next!(meta, self.next_frame_id.col) - curr!(meta, self.frame_id.col)),
+                // The innermost frame is the frame the execution stops in.
+                is_last.clone()
+                    * (curr!(meta, self.frame_id.col) - curr!(meta, self.terminal_frame_id)),
+                is_last * (fixed_curr!(meta, self.label) - curr!(meta, self.depth)),
+                (constant_from!(1) - enable.clone())
+                    * curr!(meta, self.terminal_frame_id)
+                    * fixed_curr!(meta, self.frame_first),
+                (constant_from!(1) - enable)
+                    * curr!(meta, self.depth)
+                    * fixed_curr!(meta, self.frame_first),
+            ]
+            .into_iter()
+            .map(|x| x * fixed_curr!(meta, self.frame_sel))
+            .collect::<Vec<_>>()
+        });
+
+        meta.create_gate("post frame terminal", |meta| {
+            vec![
+                next!(meta, self.terminal_frame_id) - curr!(meta, self.terminal_frame_id),
+                next!(meta, self.depth) - curr!(meta, self.depth),
+            ]
+            .into_iter()
+            .map(|x| x * fixed_curr!(meta, self.frame_continue))
+            .collect::<Vec<_>>()
+        });
+
+        meta.create_gate("post frame eid", |meta| {
+            vec![
+                (curr!(meta, self.post_eid) - curr!(meta, self.depth) - constant_from!(1))
+                    * fixed_curr!(meta, self.frame_first),
+            ]
+        });
+
+        meta.create_gate("post frame encode", |meta| {
+            let label = fixed_curr!(meta, self.label);
+
+            vec![
+                (curr!(meta, self.post)
+                    - curr!(meta, self.enable.col)
+                        * encode_frame_table_entry(
+                            label.clone(),
+                            label - constant_from!(1),
+                            curr!(meta, self.callee_fid.col),
+                            curr!(meta, self.fid.col),
+                            curr!(meta, self.iid.col),
+                        ))
+                    * fixed_curr!(meta, self.frame_sel),
+            ]
+        });
+
+        // A frame left open is a frame of the jtable which has not returned.
+        jtable.configure_in_table(meta, "post frame in jtable", |meta| {
+            (
+                curr!(meta, self.enable.col)
+                    * self.frame_expr(meta)
+                    * fixed_curr!(meta, self.frame_sel),
+                constant_from!(0),
+            )
+        });
+    }
+}
@@ -0,0 +1,76 @@
+use halo2_proofs::arithmetic::FieldExt;
+use halo2_proofs::circuit::AssignedCell;
+use halo2_proofs::plonk::Advice;
+use halo2_proofs::plonk::Column;
+use halo2_proofs::plonk::Fixed;
+
+use super::utils::bit::BitColumn;
+use super::utils::common_range::CommonRangeColumn;
+use super::utils::u16::U16Column;
+
+mod assign;
+mod configure;
+
+/*
+ * The post image table commits the state an execution leaves behind, so that the
+ * next segment of a continuation can start from it.
+ *
+ * The memory rows hold the memory after the execution: a location accessed by the
+ * execution holds the value of its last access in the mtable, the other locations are
+ * sub-ranges of the entries of the init memory table. The rows are sorted and do not
+ * overlap.
+ *
+ * The frame rows hold the frames left open, outermost first. They are relabeled to
+ * 1..=depth as the inherited frames of the next segment.
+ */
+#[derive(Clone)]
+pub struct PostImageTableConfig<F: FieldExt> {
+    memory_sel: Column<Fixed>,
+    memory_continue: Column<Fixed>,
+    frame_sel: Column<Fixed>,
+    frame_first: Column<Fixed>,
+    frame_continue: Column<Fixed>,
+    label: Column<Fixed>,
+
+    post: Column<Advice>,
+    enable: BitColumn<F>,
+
+    is_touched: BitColumn<F>,
+    is_mutable: BitColumn<F>,
+    is_next_same_ltype: BitColumn<F>,
+    ltype: Column<Advice>,
+    start_offset: CommonRangeColumn<F>,
+    len: CommonRangeColumn<F>,
+    order_diff: CommonRangeColumn<F>,
+    source_left: CommonRangeColumn<F>,
+    source_right: CommonRangeColumn<F>,
+    value: Column<Advice>,
+    value_u16_le: [U16Column<F>; 4],
+
+    frame_id: CommonRangeColumn<F>,
+    next_frame_id: CommonRangeColumn<F>,
+    callee_fid: CommonRangeColumn<F>,
+    fid: CommonRangeColumn<F>,
+    iid: CommonRangeColumn<F>,
+    terminal_frame_id: Column<Advice>,
+    depth: Column<Advice>,
+    post_eid: Column<Advice>,
+}
+
+pub struct PostImageTableCells<F: FieldExt> {
+    pub(crate) memory: Vec<AssignedCell<F, F>>,
+    pub(crate) frames: Vec<AssignedCell<F, F>>,
+    pub(crate) eid: AssignedCell<F, F>,
+    pub(crate) depth: AssignedCell<F, F>,
+}
+
+pub struct PostImageTableChip<F: FieldExt> {
+    config: PostImageTableConfig<F>,
+    k: u32,
+}
+
+impl<F: FieldExt> PostImageTableChip<F> {
+    pub fn new(config: PostImageTableConfig<F>, k: u32) -> Self {
+        PostImageTableChip { config, k }
+    }
+}
@@ -1,4 +1,3 @@
-use super::config::zkwasm_k;
 use super::config::POW_TABLE_LIMIT;
 use super::utils::bn_to_field;
 use crate::circuits::bit_table::BitTableOp;
@@ -19,7 +18,7 @@ use strum::IntoEnumIterator;
 
 #[derive(Clone)]
 pub struct RangeTableConfig<F: FieldExt> {
-    // [0 .. 1 << zkwasm_k() - 1)
+    // [0 .. 1 << k - 1)
     common_range_col: TableColumn,
     // [0 .. 65536)
     u16_col: TableColumn,
@@ -207,11 +206,11 @@ impl<F: FieldExt> RangeTableChip<F> {
         RangeTableChip { config }
     }
 
-    pub fn init(&self, layouter: &mut impl Layouter<F>) -> Result<(), Error> {
+    pub fn init(&self, layouter: &mut impl Layouter<F>, k: u32) -> Result<(), Error> {
         layouter.assign_table(
             || "common range table",
             |mut table| {
-                for i in 0..(1 << (zkwasm_k() - 1)) {
+                for i in 0..(1 << (k - 1)) {
                     table.assign_cell(
                         || "range table",
                         self.config.common_range_col,
@@ -5,10 +5,16 @@ use ark_std::start_timer;
 use halo2_proofs::arithmetic::FieldExt;
 use halo2_proofs::circuit::Layouter;
 use halo2_proofs::circuit::SimpleFloorPlanner;
+use halo2_proofs::pairing::bn256::Fr;
+use halo2_proofs::plonk::Advice;
 use halo2_proofs::plonk::Circuit;
+use halo2_proofs::plonk::Column;
 use halo2_proofs::plonk::ConstraintSystem;
 use halo2_proofs::plonk::Error;
 use log::debug;
+use specs::host_function::HostPlugin;
+#[cfg(feature = "checksum")]
+use specs::jtable::STATIC_FRAME_ENTRY_NUMBER;
 use specs::ExecutionTable;
 use specs::Tables;
 
@@ -27,6 +33,10 @@ use crate::circuits::jtable::JumpTableChip;
 use crate::circuits::jtable::JumpTableConfig;
 use crate::circuits::mtable::MemoryTableChip;
 use crate::circuits::mtable::MemoryTableConfig;
+#[cfg(feature = "checksum")]
+use crate::circuits::post_image_table::PostImageTableChip;
+#[cfg(feature = "checksum")]
+use crate::circuits::post_image_table::PostImageTableConfig;
 use crate::circuits::rtable::RangeTableChip;
 use crate::circuits::rtable::RangeTableConfig;
 use crate::circuits::utils::table_entry::EventTableWithMemoryInfo;
@@ -34,12 +44,29 @@ use crate::circuits::utils::table_entry::MemoryWritingTable;
 use crate::circuits::utils::Context;
 use crate::circuits::TestCircuit;
 use crate::exec_with_profile;
+use crate::foreign::babyjubjub_helper::circuits::assign::BabyJubjubHelperTableChip;
+use crate::foreign::babyjubjub_helper::circuits::BabyJubjubHelperTableConfig;
+use crate::foreign::babyjubjub_helper::circuits::BABYJUBJUB_FOREIGN_TABLE_KEY;
+use crate::foreign::keccak_helper::circuits::assign::KeccakHelperTableChip;
+use crate::foreign::keccak_helper::circuits::KeccakHelperTableConfig;
+use crate::foreign::keccak_helper::circuits::KECCAK_FOREIGN_TABLE_KEY;
+use crate::foreign::kv_helper::circuits::assign::KvHelperTableChip;
+use crate::foreign::kv_helper::circuits::KvHelperTableConfig;
+use crate::foreign::kv_helper::circuits::KV_FOREIGN_TABLE_KEY;
+use crate::foreign::poseidon_helper::circuits::assign::PoseidonHelperTableChip;
+use crate::foreign::poseidon_helper::circuits::PoseidonHelperTableConfig;
+use crate::foreign::poseidon_helper::circuits::POSEIDON_FOREIGN_TABLE_KEY;
+use crate::foreign::poseidon_helper::compose;
+use crate::foreign::sha256_helper::circuits::assign::Sha256HelperTableChip;
+use crate::foreign::sha256_helper::circuits::Sha256HelperTableConfig;
+use crate::foreign::sha256_helper::circuits::SHA256_FOREIGN_TABLE_KEY;
 use crate::foreign::wasm_input_helper::circuits::assign::WasmInputHelperTableChip;
 use crate::foreign::wasm_input_helper::circuits::WasmInputHelperTableConfig;
 use crate::foreign::wasm_input_helper::circuits::WASM_INPUT_FOREIGN_TABLE_KEY;
 use crate::foreign::ForeignTableConfig;
 
-use super::config::zkwasm_k;
+#[cfg(feature = "checksum")]
+use super::config::max_image_table_rows;
 use super::config::CircuitConfigure;
 use super::image_table::ImageTableConfig;
 
@@ -49,6 +76,30 @@ pub const VAR_COLUMNS: usize = 52;
 // The maximal step size of all tables is bit_table::STEP_SIZE.
 const RESERVE_ROWS: usize = crate::circuits::bit_table::STEP_SIZE;
 
+pub(crate) fn available_rows(k: u32, blinding_factors: usize) -> usize {
+    (1 << k) - (blinding_factors + 1 + RESERVE_ROWS)
+}
+
+lazy_static! {
+    static ref BLINDING_FACTORS: usize = {
+        let mut meta = ConstraintSystem::<Fr>::default();
+        TestCircuit::<Fr>::configure(&mut meta);
+
+        meta.blinding_factors()
+    };
+}
+
+/// The blinding factors of the circuit of every op and every plugin, computed once. They do
+/// not depend on K and bound the ones of the circuit of any image, whose gates are a subset.
+pub(crate) fn blinding_factors() -> usize {
+    *BLINDING_FACTORS
+}
+
+/// The rows available to the tables of any circuit with `k`.
+pub(crate) fn max_available_rows(k: u32) -> usize {
+    available_rows(k, blinding_factors())
+}
+
 #[derive(Clone)]
 pub struct TestCircuitConfig<F: FieldExt> {
     rtable: RangeTableConfig<F>,
@@ -59,28 +110,62 @@ pub struct TestCircuitConfig<F: FieldExt> {
     bit_table: BitTableConfig<F>,
     external_host_call_table: ExternalHostCallTableConfig<F>,
     wasm_input_helper_table: WasmInputHelperTableConfig<F>,
+    /// The tables of the plugins are configured only if the image calls them.
+    sha256_helper_table: Option<Sha256HelperTableConfig<F>>,
+    keccak_helper_table: Option<KeccakHelperTableConfig<F>>,
+    poseidon_helper_table: Option<PoseidonHelperTableConfig<F>>,
+    kv_roots: KvRootsConfig<F>,
+    babyjubjub_helper_table: Option<BabyJubjubHelperTableConfig<F>>,
 
-    max_available_rows: usize,
+    blinding_factors: usize,
 
+    #[cfg(feature = "checksum")]
+    post_image_table: PostImageTableConfig<F>,
     #[cfg(feature = "checksum")]
     checksum_config: CheckSumConfig<F>,
 }
 
+/// The roots of the KV store, which are instances of every circuit.
+#[derive(Clone)]
+enum KvRootsConfig<F: FieldExt> {
+    /// The roots are the ones of the KV table.
+    Table(KvHelperTableConfig<F>),
+    /// The image does not call the KV plugin, both roots are the root assigned in the
+    /// column, which is unchanged.
+    Unchanged(Column<Advice>),
+}
+
 impl<F: FieldExt> Circuit<F> for TestCircuit<F> {
     type Config = TestCircuitConfig<F>;
 
     type FloorPlanner = SimpleFloorPlanner;
 
+    type Params = CircuitConfigure;
+
     fn without_witnesses(&self) -> Self {
-        TestCircuit::new(Tables {
-            compilation_tables: self.tables.compilation_tables.clone(),
-            execution_tables: ExecutionTable::default(),
-        })
+        TestCircuit::new(
+            self.k(),
+            Tables {
+                compilation_tables: self.tables.compilation_tables.clone(),
+                execution_tables: ExecutionTable::default(),
+                post_image_table: self.tables.post_image_table.clone(),
+            },
+        )
+    }
+
+    fn params(&self) -> Self::Params {
+        self.circuit_configure().clone()
     }
 
+    /// Configures the circuit of any image.
     fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
-        let circuit_configure = CircuitConfigure::get();
+        Self::configure_with_params(meta, CircuitConfigure::default())
+    }
 
+    fn configure_with_params(
+        meta: &mut ConstraintSystem<F>,
+        circuit_configure: Self::Params,
+    ) -> Self::Config {
         /*
          * Allocate a column to enable assign_advice_from_constant.
          */
@@ -108,10 +193,61 @@ impl<F: FieldExt> Circuit<F> for TestCircuit<F> {
             Box::new(wasm_input_helper_table.clone()),
         );
 
+        let sha256_helper_table = circuit_configure
+            .calls_plugin(HostPlugin::Sha256)
+            .then(|| Sha256HelperTableConfig::configure(meta));
+        if let Some(sha256_helper_table) = &sha256_helper_table {
+            foreign_table_configs.insert(
+                SHA256_FOREIGN_TABLE_KEY,
+                Box::new(sha256_helper_table.clone()),
+            );
+        }
+
+        let keccak_helper_table = circuit_configure
+            .calls_plugin(HostPlugin::Keccak256)
+            .then(|| KeccakHelperTableConfig::configure(meta));
+        if let Some(keccak_helper_table) = &keccak_helper_table {
+            foreign_table_configs.insert(
+                KECCAK_FOREIGN_TABLE_KEY,
+                Box::new(keccak_helper_table.clone()),
+            );
+        }
+
+        let poseidon_helper_table = circuit_configure
+            .calls_plugin(HostPlugin::Poseidon)
+            .then(|| PoseidonHelperTableConfig::configure(meta));
+        if let Some(poseidon_helper_table) = &poseidon_helper_table {
+            foreign_table_configs.insert(
+                POSEIDON_FOREIGN_TABLE_KEY,
+                Box::new(poseidon_helper_table.clone()),
+            );
+        }
+
+        let kv_roots = if circuit_configure.calls_plugin(HostPlugin::MerkleKv) {
+            let kv_helper_table = KvHelperTableConfig::configure(meta);
+            foreign_table_configs.insert(KV_FOREIGN_TABLE_KEY, Box::new(kv_helper_table.clone()));
+
+            KvRootsConfig::Table(kv_helper_table)
+        } else {
+            let root = meta.advice_column();
+            meta.enable_equality(root);
+
+            KvRootsConfig::Unchanged(root)
+        };
+
+        let babyjubjub_helper_table = circuit_configure
+            .calls_plugin(HostPlugin::BabyJubjub)
+            .then(|| BabyJubjubHelperTableConfig::configure(meta));
+        if let Some(babyjubjub_helper_table) = &babyjubjub_helper_table {
+            foreign_table_configs.insert(
+                BABYJUBJUB_FOREIGN_TABLE_KEY,
+                Box::new(babyjubjub_helper_table.clone()),
+            );
+        }
+
         let etable = EventTableConfig::configure(
             meta,
             &mut cols,
-            &circuit_configure,
             &rtable,
             &image_table,
             &mtable,
@@ -122,12 +258,12 @@ impl<F: FieldExt> Circuit<F> for TestCircuit<F> {
             &circuit_configure.opcode_selector,
         );
 
+        #[cfg(feature = "checksum")]
+        let post_image_table =
+            PostImageTableConfig::configure(meta, &rtable, &image_table, &mtable, &jtable);
         #[cfg(feature = "checksum")]
         let checksum_config = CheckSumConfig::configure(meta);
 
-        let max_available_rows = (1 << zkwasm_k()) - (meta.blinding_factors() + 1 + RESERVE_ROWS);
-        debug!("max_available_rows: {:?}", max_available_rows);
-
         Self::Config {
             rtable,
             image_table,
@@ -137,9 +273,16 @@ impl<F: FieldExt> Circuit<F> for TestCircuit<F> {
             bit_table,
             external_host_call_table,
             wasm_input_helper_table,
+            sha256_helper_table,
+            keccak_helper_table,
+            poseidon_helper_table,
+            kv_roots,
+            babyjubjub_helper_table,
 
-            max_available_rows,
+            blinding_factors: meta.blinding_factors(),
 
+            #[cfg(feature = "checksum")]
+            post_image_table,
             #[cfg(feature = "checksum")]
             checksum_config,
         }
@@ -152,17 +295,20 @@ impl<F: FieldExt> Circuit<F> for TestCircuit<F> {
     ) -> Result<(), Error> {
         let assign_timer = start_timer!(|| "Assign");
 
+        let max_available_rows = available_rows(self.k(), config.blinding_factors);
+        debug!("max_available_rows: {:?}", max_available_rows);
+
         let rchip = RangeTableChip::new(config.rtable);
         let image_chip = ImageTableChip::new(config.image_table);
-        let mchip = MemoryTableChip::new(config.mtable, config.max_available_rows);
-        let jchip = JumpTableChip::new(config.jtable, config.max_available_rows);
-        let echip = EventTableChip::new(config.etable, config.max_available_rows);
-        let bit_chip = BitTableChip::new(config.bit_table, config.max_available_rows);
+        let mchip = MemoryTableChip::new(config.mtable, max_available_rows);
+        let jchip = JumpTableChip::new(config.jtable, max_available_rows);
+        let echip = EventTableChip::new(config.etable, max_available_rows);
+        let bit_chip = BitTableChip::new(config.bit_table, max_available_rows);
         let external_host_call_chip =
-            ExternalHostCallChip::new(config.external_host_call_table, config.max_available_rows);
+            ExternalHostCallChip::new(config.external_host_call_table, max_available_rows);
         let wasm_input_chip = WasmInputHelperTableChip::new(config.wasm_input_helper_table);
 
-        exec_with_profile!(|| "Init range chip", rchip.init(&mut layouter)?);
+        exec_with_profile!(|| "Init range chip", rchip.init(&mut layouter, self.k())?);
         exec_with_profile!(
             || "Init wasm input chip",
             wasm_input_chip.init(&mut layouter)?
@@ -192,14 +338,109 @@ impl<F: FieldExt> Circuit<F> for TestCircuit<F> {
             )?
         );
 
+        if let Some(sha256_helper_table) = config.sha256_helper_table {
+            exec_with_profile!(
+                || "Assign sha256 helper table",
+                Sha256HelperTableChip::new(sha256_helper_table, max_available_rows).assign(
+                    &mut layouter,
+                    &self
+                        .tables
+                        .execution_tables
+                        .etable
+                        .filter_foreign_entries(HostPlugin::Sha256),
+                )?
+            );
+        }
+
+        if let Some(keccak_helper_table) = config.keccak_helper_table {
+            exec_with_profile!(
+                || "Assign keccak helper table",
+                KeccakHelperTableChip::new(keccak_helper_table, max_available_rows).assign(
+                    &mut layouter,
+                    &self
+                        .tables
+                        .execution_tables
+                        .etable
+                        .filter_foreign_entries(HostPlugin::Keccak256),
+                )?
+            );
+        }
+
+        if let Some(poseidon_helper_table) = config.poseidon_helper_table {
+            exec_with_profile!(
+                || "Assign poseidon helper table",
+                PoseidonHelperTableChip::new(poseidon_helper_table, max_available_rows).assign(
+                    &mut layouter,
+                    &self
+                        .tables
+                        .execution_tables
+                        .etable
+                        .filter_foreign_entries(HostPlugin::Poseidon),
+                )?
+            );
+        }
+
+        let (kv_old_root, kv_new_root) = match config.kv_roots {
+            KvRootsConfig::Table(kv_helper_table) => exec_with_profile!(
+                || "Assign kv helper table",
+                KvHelperTableChip::new(kv_helper_table, max_available_rows).assign(
+                    &mut layouter,
+                    &self
+                        .tables
+                        .execution_tables
+                        .etable
+                        .filter_foreign_entries(HostPlugin::MerkleKv),
+                    &self.tables.execution_tables.kvtable,
+                )?
+            ),
+            KvRootsConfig::Unchanged(column) => {
+                let root = layouter.assign_region(
+                    || "kv root",
+                    |mut region| {
+                        region.assign_advice(
+                            || "kv root",
+                            column,
+                            0,
+                            || Ok(compose(&self.tables.execution_tables.kvtable.root)),
+                        )
+                    },
+                )?;
+
+                (root.clone(), root)
+            }
+        };
+
+        if let Some(babyjubjub_helper_table) = config.babyjubjub_helper_table {
+            exec_with_profile!(
+                || "Assign babyjubjub helper table",
+                BabyJubjubHelperTableChip::new(babyjubjub_helper_table, max_available_rows)
+                    .assign(
+                        &mut layouter,
+                        &self
+                            .tables
+                            .execution_tables
+                            .etable
+                            .filter_foreign_entries(HostPlugin::BabyJubjub),
+                    )?
+            );
+        }
+
+        let returned_frames = self.tables.execution_tables.etable.returned_frames();
+        let terminate_frame_id = self
+            .tables
+            .execution_tables
+            .live_frames(&self.tables.compilation_tables.static_jtable)
+            .last()
+            .map_or(0, |frame| frame.frame_id);
+
         #[allow(unused_variables)]
-        let img_info = layouter.assign_region(
+        let (etable_permutation_cells, static_frame_entries) = layouter.assign_region(
             || "jtable mtable etable",
             |region| {
-                let mut ctx = Context::new(region);
+                let mut ctx = Context::new(region, self.k());
 
-                let memory_writing_table: MemoryWritingTable =
-                    self.tables.execution_tables.mtable.clone().into();
+                let memory_writing_table =
+                    MemoryWritingTable::new(self.k(), self.tables.execution_tables.mtable.clone());
 
                 let etable = exec_with_profile!(
                     || "Prepare memory info for etable",
@@ -215,7 +456,11 @@ impl<F: FieldExt> Circuit<F> for TestCircuit<F> {
                         &mut ctx,
                         &etable,
                         &self.tables.compilation_tables.configure_table,
-                        self.tables.compilation_tables.fid_of_entry,
+                        &self.tables.compilation_tables.initialization_state,
+                        &self.tables.post_image_table.initialization_state,
+                        terminate_frame_id,
+                        self.tables.is_terminated(),
+                        &returned_frames,
                     )?
                 );
 
@@ -232,13 +477,14 @@ impl<F: FieldExt> Circuit<F> for TestCircuit<F> {
                     );
                 }
 
-                let jtable_info = {
+                let static_frame_entries = {
                     ctx.reset();
                     exec_with_profile!(
                         || "Assign frame table",
                         jchip.assign(
                             &mut ctx,
                             &self.tables.execution_tables.jtable,
+                            &returned_frames,
                             etable_permutation_cells.rest_jops,
                             &self.tables.compilation_tables.static_jtable,
                         )?
@@ -250,21 +496,72 @@ impl<F: FieldExt> Circuit<F> for TestCircuit<F> {
                     exec_with_profile!(|| "Assign bit table", bit_chip.assign(&mut ctx, &etable)?);
                 }
 
-                Ok(vec![vec![etable_permutation_cells.fid_of_entry], jtable_info].concat())
+                Ok((etable_permutation_cells, static_frame_entries))
             },
         )?;
 
-        #[cfg(feature = "checksum")]
-        let checksum = exec_with_profile!(
-            || "Assign checksum circuit",
-            CheckSumChip::new(config.checksum_config)
-                .assign(&mut layouter, vec![image_entries, img_info].concat())?
-        );
-
-        #[allow(unused_mut)]
         let mut instances = vec![];
+        let terminated = etable_permutation_cells.terminate_status.terminated.clone();
+
         #[cfg(feature = "checksum")]
-        instances.push(checksum);
+        {
+            let post_image_table = exec_with_profile!(
+                || "Assign post image table",
+                PostImageTableChip::new(config.post_image_table, self.k()).assign(
+                    &mut layouter,
+                    &self.tables.compilation_tables,
+                    &self.tables.post_image_table,
+                    &self.tables.execution_tables,
+                    &etable_permutation_cells.terminate_status.frame_id,
+                )?
+            );
+
+            let initialization_state = etable_permutation_cells.initialization_state;
+            let terminate_status = etable_permutation_cells.terminate_status;
+            let code_entries = image_entries[..max_image_table_rows() as usize].to_vec();
+            let root_frame_entries = static_frame_entries[..2 * STATIC_FRAME_ENTRY_NUMBER].to_vec();
+
+            let pre_image_message = vec![
+                image_entries,
+                initialization_state.plain().into_iter().cloned().collect(),
+                static_frame_entries,
+            ]
+            .concat();
+
+            // The post image is laid out as the pre image of the next segment.
+            let post_image_message = vec![
+                code_entries,
+                post_image_table.memory,
+                vec![
+                    post_image_table.eid,
+                    terminate_status.fid,
+                    terminate_status.iid,
+                    post_image_table.depth,
+                    terminate_status.sp,
+                    terminate_status.allocated_memory_pages,
+                ],
+                root_frame_entries,
+                post_image_table.frames,
+            ]
+            .concat();
+
+            let checksum_chip = CheckSumChip::new(config.checksum_config);
+
+            instances.push(exec_with_profile!(
+                || "Assign pre image checksum",
+                checksum_chip.assign(&mut layouter, pre_image_message)?
+            ));
+            instances.push(exec_with_profile!(
+                || "Assign post image checksum",
+                checksum_chip.assign(&mut layouter, post_image_message)?
+            ));
+        }
+
+        // The roots of the KV store follow the image checksums, then whether the execution
+        // terminates.
+        instances.push(kv_old_root);
+        instances.push(kv_new_root);
+        instances.push(terminated);
 
         exec_with_profile!(
             || "Assign wasm input chip",
@@ -15,14 +15,17 @@ pub mod table_entry;
 pub struct Context<'a, F: FieldExt> {
     pub region: Box<Region<'a, F>>,
     pub offset: usize,
+    /// K of the circuit, it bounds the values of the common range cells.
+    pub(crate) k: u32,
     records: Vec<usize>,
 }
 
 impl<'a, F: FieldExt> Context<'a, F> {
-    pub fn new(region: Region<'a, F>) -> Self {
+    pub fn new(region: Region<'a, F>, k: u32) -> Self {
         Self {
             region: Box::new(region),
             offset: 0usize,
+            k,
             records: vec![],
         }
     }
@@ -1,4 +1,7 @@
 use specs::configure_table::ConfigureTable;
+use specs::host_function::HostPlugin;
+use std::collections::BTreeMap;
+use std::collections::HashSet;
 
 #[derive(Clone)]
 pub struct Status {
@@ -15,5 +18,9 @@ pub struct StepStatus<'a> {
     pub next: &'a Status,
     pub current_external_host_call_index: u32,
     pub host_public_inputs: u32,
+    /// The number of the calls of each indexed plugin preceding the step.
+    pub foreign_call_indexes: &'a BTreeMap<HostPlugin, u32>,
     pub configure_table: ConfigureTable,
+    /// The frames left within the event table, see `EventTable::returned_frames`.
+    pub returned_frames: &'a HashSet<(u32, u32)>,
 }
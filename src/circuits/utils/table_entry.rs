@@ -11,7 +11,6 @@ use std::env;
 use std::io::Write;
 use std::path::PathBuf;
 
-use crate::circuits::config::zkwasm_k;
 use crate::runtime::memory_event_of_step;
 
 #[derive(Clone, Debug, Serialize)]
@@ -30,9 +29,11 @@ impl MemoryWritingEntry {
 #[derive(Debug, Serialize)]
 pub struct MemoryWritingTable(pub(in crate::circuits) Vec<MemoryWritingEntry>);
 
-impl From<MTable> for MemoryWritingTable {
-    fn from(value: MTable) -> Self {
-        let maximal_eid = (1u32 << (zkwasm_k() - 1)) - 1;
+impl MemoryWritingTable {
+    /// The writing entries of `value`, the last entry of an address lives until the maximal
+    /// eid of the circuit of `k`.
+    pub fn new(k: u32, value: MTable) -> Self {
+        let maximal_eid = (1u32 << (k - 1)) - 1;
         let mut index = 0;
 
         let mut entries: Vec<MemoryWritingEntry> = value
@@ -66,9 +67,7 @@ impl From<MTable> for MemoryWritingTable {
 
         MemoryWritingTable(entries)
     }
-}
 
-impl MemoryWritingTable {
     // (location, offset) |-> Vec<(start_eid, end_eid)>
     fn build_lookup_mapping(&self) -> BTreeMap<(LocationType, u32), Vec<(u32, u32)>> {
         let mut mapping = BTreeMap::<_, Vec<(u32, u32)>>::new();
@@ -1,23 +1,38 @@
+use anyhow::ensure;
+use anyhow::Result;
 use clap::App;
 use clap::AppSettings;
+use clap::ArgMatches;
 use log::info;
 use std::fs;
 use std::path::PathBuf;
 
-use crate::circuits::config::init_zkwasm_runtime;
 use crate::circuits::config::MIN_K;
 use crate::cli::exec::exec_dry_run;
+use crate::foreign::kv_helper::store::FileKvStore;
+use crate::prover::ProofBundle;
+use crate::prover::ZkWasmProver;
+use crate::runtime::entry::EntryPoint;
 
 use super::command::CommandBuilder;
-use super::exec::compile_image;
 use super::exec::exec_aggregate_create_proof;
+use super::exec::exec_aggregate_images;
 use super::exec::exec_create_proof;
 #[cfg(feature = "checksum")]
+use super::exec::exec_create_segments_proof;
+#[cfg(feature = "checksum")]
 use super::exec::exec_image_checksum;
 use super::exec::exec_setup;
 use super::exec::exec_solidity_aggregate_proof;
+use super::exec::exec_solidity_single_proof;
 use super::exec::exec_verify_aggregate_proof;
+use super::exec::exec_verify_bundle;
 use super::exec::exec_verify_proof;
+#[cfg(feature = "checksum")]
+use super::exec::exec_verify_segments_proof;
+use super::inputs::read_input_manifest;
+use super::inputs::select_input_set;
+use super::params::ParamsLoader;
 
 fn load_or_generate_output_path(wasm_md5: &String, path: Option<&PathBuf>) -> PathBuf {
     if let Some(path) = path {
@@ -33,7 +48,9 @@ pub trait AppBuilder: CommandBuilder {
     const NAME: &'static str;
     const VERSION: &'static str;
     const AGGREGATE_K: u32;
-    const N_PROOFS: usize;
+    /// Number of proofs verified by an aggregate circuit, more executions are aggregated
+    /// in a tree of aggregates.
+    const MAX_AGGREGATE_PROOFS: usize;
     const MAX_PUBLIC_INPUT_SIZE: usize;
 
     fn app_builder<'a>() -> App<'a> {
@@ -43,92 +60,159 @@ pub trait AppBuilder: CommandBuilder {
             .arg(Self::zkwasm_k_arg())
             .arg(Self::output_path_arg())
             .arg(Self::function_name_arg())
+            .arg(Self::expose_result_arg())
+            .arg(Self::params_arg())
+            .arg(Self::unsafe_params_arg())
+            .arg(Self::kv_store_arg())
             .arg(Self::zkwasm_file_arg());
 
         let app = Self::append_setup_subcommand(app);
         let app = Self::append_dry_run_subcommand(app);
         let app = Self::append_create_single_proof_subcommand(app);
         let app = Self::append_verify_single_proof_subcommand(app);
+        let app = Self::append_verify_bundle_subcommand(app);
         let app = Self::append_create_aggregate_proof_subcommand(app);
+        let app = Self::append_aggregate_images_subcommand(app);
         let app = Self::append_verify_aggregate_verify_subcommand(app);
         let app = Self::append_generate_solidity_verifier(app);
+        let app = Self::append_generate_single_solidity_verifier(app);
 
         #[cfg(feature = "checksum")]
         let app = Self::append_image_checksum_subcommand(app);
+        #[cfg(feature = "checksum")]
+        let app = Self::append_create_segments_proof_subcommand(app);
+        #[cfg(feature = "checksum")]
+        let app = Self::append_verify_segments_proof_subcommand(app);
 
         app
     }
 
-    fn exec(command: App) {
+    /// The arguments of the entry, the public inputs given by flags then the ones of the
+    /// input manifest, and the private inputs given by flags then the ones of the manifest.
+    fn parse_single_inputs(
+        wasm_binary: &Vec<u8>,
+        entry: &EntryPoint,
+        matches: &ArgMatches,
+    ) -> Result<(Vec<u64>, Vec<u64>)> {
+        let mut public_inputs = entry.encode_args(wasm_binary, &Self::parse_entry_args(matches))?;
+        public_inputs.append(&mut Self::parse_single_public_arg(matches));
+        let mut private_inputs = Self::parse_single_private_arg(matches);
+
+        if let Some(path) = Self::parse_inputs_arg(matches) {
+            let mut set = select_input_set(
+                read_input_manifest(&path)?,
+                Self::parse_input_set_arg(matches),
+            )?;
+
+            public_inputs.append(&mut set.public_inputs);
+            private_inputs.append(&mut set.private_inputs);
+        }
+
+        Self::check_public_inputs(&public_inputs)?;
+
+        Ok((public_inputs, private_inputs))
+    }
+
+    fn check_public_inputs(public_inputs: &Vec<u64>) -> Result<()> {
+        ensure!(
+            public_inputs.len() <= Self::MAX_PUBLIC_INPUT_SIZE,
+            "{} public inputs are given but at most {} are supported",
+            public_inputs.len(),
+            Self::MAX_PUBLIC_INPUT_SIZE
+        );
+
+        Ok(())
+    }
+
+    /// Runs the subcommand of `command`, a failure is reported as an error rather than a
+    /// panic.
+    fn exec(command: App) -> Result<()> {
         env_logger::init();
 
         let top_matches = command.get_matches();
 
+        // A bundle describes the image and the circuit of its proof, it is verified without
+        // the image.
+        if let Some(("verify", sub_matches)) = top_matches.subcommand() {
+            let bundle_path: PathBuf = Self::parse_bundle_arg(&sub_matches);
+            let bundle = ProofBundle::read(fs::File::open(&bundle_path)?)?;
+
+            let output_dir = load_or_generate_output_path(
+                &bundle.image_md5,
+                top_matches.get_one::<PathBuf>("output"),
+            );
+            let params = ParamsLoader::new(
+                output_dir.clone(),
+                Self::parse_params_arg(&top_matches),
+                Self::parse_unsafe_params(&top_matches),
+            );
+
+            exec_verify_bundle(
+                Self::NAME,
+                &params,
+                &output_dir,
+                &bundle,
+                Self::parse_vkey_arg(&sub_matches),
+            )?;
+
+            return Ok(());
+        }
+
         let zkwasm_k = Self::parse_zkwasm_k_arg(&top_matches).unwrap_or(MIN_K);
 
         let wasm_file_path = Self::parse_zkwasm_file_arg(&top_matches);
-        let wasm_binary = fs::read(&wasm_file_path).unwrap();
+        let wasm_binary = fs::read(&wasm_file_path)?;
 
-        let function_name = Self::parse_function_name(&top_matches);
+        let entry = EntryPoint::new(
+            &Self::parse_function_name(&top_matches),
+            Self::parse_expose_result(&top_matches),
+        );
 
-        {
-            let module = wasmi::Module::from_buffer(&wasm_binary).expect("failed to load wasm");
-
-            let (_, table) = compile_image(&module, &function_name);
-            init_zkwasm_runtime(zkwasm_k, &table.tables);
+        let mut prover = ZkWasmProver::new(zkwasm_k, wasm_binary.clone(), entry.clone())?;
+        if let Some(path) = Self::parse_kv_store_arg(&top_matches) {
+            prover = prover.with_kv_store(Box::new(FileKvStore::open(path)?));
         }
 
         let md5 = format!("{:X}", md5::compute(&wasm_binary));
 
         let output_dir =
             load_or_generate_output_path(&md5, top_matches.get_one::<PathBuf>("output"));
-        fs::create_dir_all(&output_dir).unwrap();
+        fs::create_dir_all(&output_dir)?;
+
+        let params = ParamsLoader::new(
+            output_dir.clone(),
+            Self::parse_params_arg(&top_matches),
+            Self::parse_unsafe_params(&top_matches),
+        );
 
         match top_matches.subcommand() {
             Some(("setup", _)) => {
-                exec_setup(
-                    zkwasm_k,
-                    Self::AGGREGATE_K,
-                    Self::NAME,
-                    &wasm_binary,
-                    &function_name,
-                    &output_dir,
-                );
+                exec_setup(Self::AGGREGATE_K, Self::NAME, &prover, &params, &output_dir)
             }
             #[cfg(feature = "checksum")]
-            Some(("checksum", _)) => {
-                exec_image_checksum(&wasm_binary, &function_name, &output_dir);
-            }
+            Some(("checksum", _)) => exec_image_checksum(&prover, &output_dir),
             Some(("dry-run", sub_matches)) => {
-                let public_inputs: Vec<u64> = Self::parse_single_public_arg(&sub_matches);
-                let private_inputs: Vec<u64> = Self::parse_single_private_arg(&sub_matches);
-
-                assert!(public_inputs.len() <= Self::MAX_PUBLIC_INPUT_SIZE);
+                let (public_inputs, private_inputs) =
+                    Self::parse_single_inputs(&wasm_binary, &entry, &sub_matches)?;
+                let estimate: bool = Self::parse_estimate(&sub_matches);
 
-                exec_dry_run(
-                    &wasm_binary,
-                    &function_name,
-                    &public_inputs,
-                    &private_inputs,
-                )
-                .unwrap();
+                exec_dry_run(&prover, &public_inputs, &private_inputs, estimate)
             }
             Some(("single-prove", sub_matches)) => {
-                let public_inputs: Vec<u64> = Self::parse_single_public_arg(&sub_matches);
-                let private_inputs: Vec<u64> = Self::parse_single_private_arg(&sub_matches);
+                let (public_inputs, private_inputs) =
+                    Self::parse_single_inputs(&wasm_binary, &entry, &sub_matches)?;
 
-                assert!(public_inputs.len() <= Self::MAX_PUBLIC_INPUT_SIZE);
+                let transcript = Self::parse_transcript_arg(&sub_matches);
 
                 exec_create_proof(
                     Self::NAME,
-                    zkwasm_k,
-                    &wasm_binary,
-                    &function_name,
+                    &prover,
+                    &params,
                     &output_dir,
                     &public_inputs,
                     &private_inputs,
+                    transcript,
                 )
-                .unwrap();
             }
             Some(("single-verify", sub_matches)) => {
                 let proof_path: PathBuf = Self::parse_proof_path_arg(&sub_matches);
@@ -136,37 +220,84 @@ pub trait AppBuilder: CommandBuilder {
 
                 exec_verify_proof(
                     Self::NAME,
-                    Self::MAX_PUBLIC_INPUT_SIZE,
-                    zkwasm_k,
-                    &wasm_binary,
-                    &function_name,
+                    &prover,
+                    &params,
                     &output_dir,
                     &proof_path,
                     &instance_path,
-                );
+                )
+            }
+            #[cfg(feature = "checksum")]
+            Some(("segments-prove", sub_matches)) => {
+                let (public_inputs, private_inputs) =
+                    Self::parse_single_inputs(&wasm_binary, &entry, &sub_matches)?;
+
+                let transcript = Self::parse_transcript_arg(&sub_matches);
+                let segment_size = Self::parse_segment_size_arg(&sub_matches)
+                    .unwrap_or_else(|| prover.segment_size());
+
+                exec_create_segments_proof(
+                    Self::NAME,
+                    &prover,
+                    &params,
+                    &output_dir,
+                    &public_inputs,
+                    &private_inputs,
+                    transcript,
+                    segment_size,
+                )
+            }
+            #[cfg(feature = "checksum")]
+            Some(("segments-verify", _)) => {
+                exec_verify_segments_proof(Self::NAME, &prover, &params, &output_dir)
             }
             Some(("aggregate-prove", sub_matches)) => {
-                let public_inputs: Vec<Vec<u64>> = Self::parse_aggregate_public_args(&sub_matches);
-                let private_inputs: Vec<Vec<u64>> =
-                    Self::parse_aggregate_private_args(&sub_matches);
+                let (public_inputs, private_inputs): (Vec<Vec<u64>>, Vec<Vec<u64>>) =
+                    match Self::parse_inputs_arg(&sub_matches) {
+                        Some(path) => read_input_manifest(&path)?
+                            .into_iter()
+                            .map(|set| (set.public_inputs, set.private_inputs))
+                            .unzip(),
+                        None => (
+                            Self::parse_aggregate_public_args(&sub_matches),
+                            Self::parse_aggregate_private_args(&sub_matches),
+                        ),
+                    };
 
                 for instances in &public_inputs {
-                    assert!(instances.len() <= Self::MAX_PUBLIC_INPUT_SIZE);
+                    Self::check_public_inputs(instances)?;
                 }
 
-                assert_eq!(public_inputs.len(), Self::N_PROOFS);
-                assert_eq!(private_inputs.len(), Self::N_PROOFS);
+                ensure!(
+                    public_inputs.len() == private_inputs.len(),
+                    "{} sets of public inputs are given with {} sets of private inputs",
+                    public_inputs.len(),
+                    private_inputs.len()
+                );
 
                 exec_aggregate_create_proof(
-                    zkwasm_k,
                     Self::AGGREGATE_K,
+                    Self::MAX_AGGREGATE_PROOFS,
                     Self::NAME,
-                    &wasm_binary,
-                    &function_name,
+                    &prover,
+                    &params,
                     &output_dir,
                     &public_inputs,
                     &private_inputs,
-                );
+                )
+            }
+
+            Some(("aggregate-images", sub_matches)) => {
+                let targets_path: PathBuf = Self::parse_targets_arg(&sub_matches);
+
+                exec_aggregate_images(
+                    zkwasm_k,
+                    Self::AGGREGATE_K,
+                    Self::NAME,
+                    &params,
+                    &output_dir,
+                    &targets_path,
+                )
             }
 
             Some(("aggregate-verify", sub_matches)) => {
@@ -175,11 +306,12 @@ pub trait AppBuilder: CommandBuilder {
 
                 exec_verify_aggregate_proof(
                     Self::AGGREGATE_K as u32,
+                    &params,
                     &output_dir,
                     &proof_path,
                     &instances_path,
-                    Self::N_PROOFS,
-                );
+                    Self::MAX_AGGREGATE_PROOFS,
+                )
             }
 
             Some(("solidity-aggregate-verifier", sub_matches)) => {
@@ -192,13 +324,32 @@ pub trait AppBuilder: CommandBuilder {
                     zkwasm_k,
                     Self::AGGREGATE_K,
                     Self::MAX_PUBLIC_INPUT_SIZE,
+                    &params,
                     &output_dir,
                     &proof_path,
                     &sol_path,
                     &instances_path,
-                    Self::N_PROOFS,
+                    Self::MAX_AGGREGATE_PROOFS,
                     aux_only,
-                );
+                )
+            }
+
+            Some(("solidity-single-verifier", sub_matches)) => {
+                let proof_path: PathBuf = Self::parse_proof_path_arg(&sub_matches);
+                let instance_path: PathBuf = Self::parse_single_instance_arg(&sub_matches);
+                let aux_only: bool = Self::parse_auxonly(&sub_matches);
+                let sol_path: PathBuf = Self::parse_sol_dir_arg(&sub_matches);
+
+                exec_solidity_single_proof(
+                    Self::NAME,
+                    &prover,
+                    &params,
+                    &output_dir,
+                    &proof_path,
+                    &instance_path,
+                    &sol_path,
+                    aux_only,
+                )
             }
 
             Some((_, _)) => todo!(),
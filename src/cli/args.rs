@@ -3,7 +3,11 @@ use std::path::PathBuf;
 use clap::arg;
 use clap::value_parser;
 use clap::Arg;
+use clap::ArgAction;
 use clap::ArgMatches;
+use wasmi::RuntimeValue;
+
+use crate::prover::Transcript;
 
 pub trait ArgBuilder {
     fn zkwasm_k_arg<'a>() -> Arg<'a> {
@@ -18,7 +22,7 @@ pub trait ArgBuilder {
 
     fn zkwasm_file_arg<'a>() -> Arg<'a> {
         arg!(
-            -w --wasm <WASM_BINARY> "Path of the Wasm binary file"
+            -w --wasm [WASM_BINARY] "Path of the Wasm binary file, every subcommand but verify requires it."
         )
         .value_parser(value_parser!(PathBuf))
     }
@@ -31,7 +35,7 @@ pub trait ArgBuilder {
 
     fn function_name_arg<'a>() -> Arg<'a> {
         arg!(
-            -f --function <FUNCTION_NAME> "Function you would like to run."
+            -f --function [FUNCTION_NAME] "Function you would like to run, every subcommand but verify requires it."
         )
     }
     fn parse_function_name(matches: &ArgMatches) -> String {
@@ -41,12 +45,68 @@ pub trait ArgBuilder {
             .to_string()
     }
 
+    fn expose_result_arg<'a>() -> Arg<'a> {
+        arg!(
+            --expose_result "Append the results of the function to the public outputs."
+        )
+        .takes_value(false)
+    }
+    fn parse_expose_result(matches: &ArgMatches) -> bool {
+        matches
+            .get_many::<String>("expose_result")
+            .map_or(false, |_| true)
+    }
+
     fn output_path_arg<'a>() -> Arg<'a> {
         arg!(
             -o --output [OUTPUT_PATH] "Path of the output files.\nThe md5 of the wasm binary file is the default path if not supplied."
         ).value_parser(value_parser!(PathBuf))
     }
 
+    fn params_arg<'a>() -> Arg<'a> {
+        arg!(
+            --params [PARAMS_PATH] "Path of the powers-of-tau ceremony file (halo2 params or snarkjs ptau) the missing params are imported from."
+        )
+        .value_parser(value_parser!(PathBuf))
+    }
+    fn parse_params_arg(matches: &ArgMatches) -> Option<PathBuf> {
+        matches.get_one::<PathBuf>("params").cloned()
+    }
+
+    fn unsafe_params_arg<'a>() -> Arg<'a> {
+        arg!(
+            --unsafe_params "Allow params from an unsafe setup, which are only fit for testing."
+        )
+        .takes_value(false)
+    }
+    fn parse_unsafe_params(matches: &ArgMatches) -> bool {
+        matches
+            .get_many::<String>("unsafe_params")
+            .map_or(false, |_| true)
+    }
+
+    fn kv_store_arg<'a>() -> Arg<'a> {
+        arg!(
+            --kv_store [KV_STORE_PATH] "Path of the json file of the KV store the executions start from, the nodes written by a single or segmented proof are committed to it once the proof is created."
+        )
+        .value_parser(value_parser!(PathBuf))
+    }
+    fn parse_kv_store_arg(matches: &ArgMatches) -> Option<PathBuf> {
+        matches.get_one::<PathBuf>("kv_store").cloned()
+    }
+
+    #[cfg(feature = "checksum")]
+    fn segment_size_arg<'a>() -> Arg<'a> {
+        arg!(
+            --segment_size [SEGMENT_SIZE] "Most steps of a segment, the most the circuit can prove by default."
+        )
+        .value_parser(value_parser!(usize))
+    }
+    #[cfg(feature = "checksum")]
+    fn parse_segment_size_arg(matches: &ArgMatches) -> Option<usize> {
+        matches.get_one::<usize>("segment_size").cloned()
+    }
+
     fn proof_path_arg<'a>() -> Arg<'a> {
         arg!(
             -p --proof <PROOF_PATH> "Path of proof."
@@ -81,6 +141,105 @@ pub trait ArgBuilder {
         .takes_value(false)
     }
 
+    fn entry_args_arg<'a>() -> Arg<'a> {
+        Arg::new("arg")
+            .long("arg")
+            .value_parser(value_parser!(String))
+            .action(ArgAction::Append)
+            .help("Arguments of the function of format value:type where type=i32|i64, they precede the public inputs")
+            .min_values(0)
+    }
+    fn parse_entry_args(matches: &ArgMatches) -> Vec<RuntimeValue> {
+        matches
+            .get_many::<String>("arg")
+            .unwrap_or_default()
+            .map(|arg| {
+                let [v, t] = arg.split(":").collect::<Vec<&str>>()[..] else {
+                    panic!("argument {} is not of format value:type", arg)
+                };
+                match t {
+                    "i32" => RuntimeValue::I32(v.parse::<i32>().unwrap()),
+                    "i64" => RuntimeValue::I64(v.parse::<i64>().unwrap()),
+                    _ => panic!("Unsupported argument type: {}", t),
+                }
+            })
+            .collect()
+    }
+
+    fn inputs_arg<'a>() -> Arg<'a> {
+        arg!(
+            --inputs [INPUTS_PATH] "Path of the input manifest (json or toml) listing typed public and private inputs."
+        )
+        .value_parser(value_parser!(PathBuf))
+    }
+    fn parse_inputs_arg(matches: &ArgMatches) -> Option<PathBuf> {
+        matches.get_one::<PathBuf>("inputs").cloned()
+    }
+
+    fn input_set_arg<'a>() -> Arg<'a> {
+        arg!(
+            --input_set [INPUT_SET] "Name of the input set of the manifest to use."
+        )
+    }
+    fn parse_input_set_arg(matches: &ArgMatches) -> Option<&String> {
+        matches.get_one::<String>("input_set")
+    }
+
+    fn transcript_arg<'a>() -> Arg<'a> {
+        arg!(
+            --transcript [TRANSCRIPT] "Hash of the proof transcript: blake2b, poseidon (default, for aggregation) or sha256 (for EVM)."
+        )
+        .value_parser(value_parser!(Transcript))
+    }
+    fn parse_transcript_arg(matches: &ArgMatches) -> Transcript {
+        matches
+            .get_one::<Transcript>("transcript")
+            .map_or(Transcript::Poseidon, |x| *x)
+    }
+
+    fn targets_arg<'a>() -> Arg<'a> {
+        arg!(
+            --targets <TARGETS_PATH> "Path of the manifest (json or toml) listing the image, verifying key, proof and instance of each target."
+        )
+        .value_parser(value_parser!(PathBuf))
+    }
+    fn parse_targets_arg(matches: &ArgMatches) -> PathBuf {
+        matches
+            .get_one::<PathBuf>("targets")
+            .expect("targets is required.")
+            .clone()
+    }
+
+    fn bundle_arg<'a>() -> Arg<'a> {
+        arg!(
+            -b --bundle <BUNDLE_PATH> "Path of the proof bundle."
+        )
+        .value_parser(value_parser!(PathBuf))
+    }
+    fn parse_bundle_arg(matches: &ArgMatches) -> PathBuf {
+        matches
+            .get_one::<PathBuf>("bundle")
+            .expect("bundle is required.")
+            .clone()
+    }
+
+    fn vkey_arg<'a>() -> Arg<'a> {
+        arg!(
+            --vkey [VKEY_PATH] "Path of the verifying key.\nThe verifying key of the output path is the default if not supplied."
+        )
+        .value_parser(value_parser!(PathBuf))
+    }
+    fn parse_vkey_arg(matches: &ArgMatches) -> Option<PathBuf> {
+        matches.get_one::<PathBuf>("vkey").cloned()
+    }
+
+    fn estimate_arg<'a>() -> Arg<'a> {
+        arg!(
+            -e --estimate "Print the smallest K the execution fits in, with the rows of each table."
+        )
+        .takes_value(false)
+    }
+
     fn single_public_arg<'a>() -> Arg<'a>;
     fn parse_single_public_arg(matches: &ArgMatches) -> Vec<u64>;
 
@@ -123,4 +282,9 @@ pub trait ArgBuilder {
             .get_many::<String>("auxonly")
             .map_or(false, |_| true)
     }
+    fn parse_estimate(matches: &ArgMatches) -> bool {
+        matches
+            .get_many::<String>("estimate")
+            .map_or(false, |_| true)
+    }
 }
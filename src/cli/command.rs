@@ -20,7 +20,11 @@ pub trait CommandBuilder: ArgBuilder {
     fn append_dry_run_subcommand(app: App) -> App {
         let command = Command::new("dry-run")
             .arg(Self::single_public_arg())
-            .arg(Self::single_private_arg());
+            .arg(Self::single_private_arg())
+            .arg(Self::entry_args_arg())
+            .arg(Self::inputs_arg())
+            .arg(Self::input_set_arg())
+            .arg(Self::estimate_arg());
 
         app.subcommand(command)
     }
@@ -28,7 +32,11 @@ pub trait CommandBuilder: ArgBuilder {
     fn append_create_single_proof_subcommand(app: App) -> App {
         let command = Command::new("single-prove")
             .arg(Self::single_public_arg())
-            .arg(Self::single_private_arg());
+            .arg(Self::single_private_arg())
+            .arg(Self::entry_args_arg())
+            .arg(Self::inputs_arg())
+            .arg(Self::input_set_arg())
+            .arg(Self::transcript_arg());
         app.subcommand(command)
     }
 
@@ -40,10 +48,46 @@ pub trait CommandBuilder: ArgBuilder {
         app.subcommand(command)
     }
 
+    #[cfg(feature = "checksum")]
+    fn append_create_segments_proof_subcommand(app: App) -> App {
+        let command = Command::new("segments-prove")
+            .arg(Self::single_public_arg())
+            .arg(Self::single_private_arg())
+            .arg(Self::entry_args_arg())
+            .arg(Self::inputs_arg())
+            .arg(Self::input_set_arg())
+            .arg(Self::transcript_arg())
+            .arg(Self::segment_size_arg());
+
+        app.subcommand(command)
+    }
+
+    #[cfg(feature = "checksum")]
+    fn append_verify_segments_proof_subcommand(app: App) -> App {
+        let command = Command::new("segments-verify");
+
+        app.subcommand(command)
+    }
+
+    fn append_verify_bundle_subcommand(app: App) -> App {
+        let command = Command::new("verify")
+            .arg(Self::bundle_arg())
+            .arg(Self::vkey_arg());
+
+        app.subcommand(command)
+    }
+
     fn append_create_aggregate_proof_subcommand(app: App) -> App {
         let command = Command::new("aggregate-prove")
             .arg(Self::aggregate_public_args())
-            .arg(Self::aggregate_private_args());
+            .arg(Self::aggregate_private_args())
+            .arg(Self::inputs_arg());
+
+        app.subcommand(command)
+    }
+
+    fn append_aggregate_images_subcommand(app: App) -> App {
+        let command = Command::new("aggregate-images").arg(Self::targets_arg());
 
         app.subcommand(command)
     }
@@ -56,6 +100,16 @@ pub trait CommandBuilder: ArgBuilder {
         app.subcommand(command)
     }
 
+    fn append_generate_single_solidity_verifier(app: App) -> App {
+        let command = Command::new("solidity-single-verifier")
+            .arg(Self::sol_dir_arg())
+            .arg(Self::proof_path_arg())
+            .arg(Self::auxonly_arg())
+            .arg(Self::single_instance_path_arg());
+
+        app.subcommand(command)
+    }
+
     fn append_generate_solidity_verifier(app: App) -> App {
         let command = Command::new("solidity-aggregate-verifier")
             .arg(Self::sol_dir_arg())
@@ -1,433 +1,548 @@
-#[cfg(feature = "checksum")]
-use crate::image_hasher::ImageHasher;
-
 use crate::profile::Profiler;
-use crate::runtime::wasmi_interpreter::WasmRuntimeIO;
-use crate::runtime::CompiledImage;
+use crate::prover::aggregate_images;
+use crate::prover::AggregateLayout;
+use crate::prover::AggregateTarget;
+use crate::prover::Proof;
+use crate::prover::ProofBundle;
+use crate::prover::ProverError;
+use crate::prover::Transcript;
+use crate::prover::Witness;
+use crate::prover::ZkWasmProver;
+use crate::prover::AGGREGATE_PREFIX;
+use crate::runtime::entry::EntryPoint;
+use anyhow::anyhow;
 use anyhow::Result;
 use halo2_proofs::arithmetic::BaseExt;
-use halo2_proofs::dev::MockProver;
 use halo2_proofs::pairing::bn256::Bn256;
 use halo2_proofs::pairing::bn256::Fr;
 use halo2_proofs::pairing::bn256::G1Affine;
 use halo2_proofs::plonk::verify_proof;
+use halo2_proofs::plonk::ProvingKey;
 use halo2_proofs::plonk::SingleVerifier;
+use halo2_proofs::poly::commitment::Params;
 use halo2_proofs::poly::commitment::ParamsVerifier;
 use halo2aggregator_s::circuit_verifier::circuit::AggregatorCircuit;
 use halo2aggregator_s::circuits::utils::load_instance;
-use halo2aggregator_s::circuits::utils::load_or_build_unsafe_params;
-use halo2aggregator_s::circuits::utils::load_or_build_vkey;
-use halo2aggregator_s::circuits::utils::load_or_create_proof;
 use halo2aggregator_s::circuits::utils::load_proof;
 use halo2aggregator_s::circuits::utils::load_vkey;
-use halo2aggregator_s::circuits::utils::run_circuit_unsafe_full_pass;
 use halo2aggregator_s::circuits::utils::store_instance;
-use halo2aggregator_s::circuits::utils::TranscriptHash;
 use halo2aggregator_s::solidity_verifier::codegen::solidity_aux_gen;
 use halo2aggregator_s::solidity_verifier::solidity_render;
-use halo2aggregator_s::transcript::poseidon::PoseidonRead;
 use halo2aggregator_s::transcript::sha256::ShaRead;
 use log::info;
-use specs::ExecutionTable;
-use specs::Tables;
+use std::fs;
+use std::fs::File;
+use std::io::BufReader;
+use std::io::BufWriter;
 #[cfg(feature = "checksum")]
 use std::io::Write;
 use std::path::PathBuf;
-use wasmi::tracer::Tracer;
-use wasmi::ImportsBuilder;
-use wasmi::Module;
-use wasmi::NotStartedModuleRef;
-
-use crate::circuits::TestCircuit;
-use crate::circuits::ZkWasmCircuitBuilder;
-use crate::foreign::log_helper::register_log_foreign;
-use crate::foreign::require_helper::register_require_foreign;
-use crate::foreign::wasm_input_helper::runtime::register_wasm_input_foreign;
-use crate::runtime::host::host_env::HostEnv;
-use crate::runtime::wasmi_interpreter::Execution;
-use crate::runtime::WasmInterpreter;
-
-const AGGREGATE_PREFIX: &'static str = "aggregate-circuit";
-
-pub fn compile_image<'a>(
-    module: &'a Module,
-    function_name: &str,
-) -> (
-    WasmRuntimeIO,
-    CompiledImage<NotStartedModuleRef<'a>, Tracer>,
-) {
-    let mut env = HostEnv::new();
-    let wasm_runtime_io = register_wasm_input_foreign(&mut env, vec![], vec![]);
-    register_require_foreign(&mut env);
-    register_log_foreign(&mut env);
-    env.finalize();
-    let imports = ImportsBuilder::new().with_resolver("env", &env);
-
-    let compiler = WasmInterpreter::new();
-    (
-        wasm_runtime_io,
-        compiler
-            .compile(
-                &module,
-                &imports,
-                &env.function_description_table(),
-                function_name,
-            )
-            .expect("file cannot be complied"),
-    )
-}
-
-#[cfg(feature = "checksum")]
-fn hash_image(wasm_binary: &Vec<u8>, function_name: &str) -> Fr {
-    let module = wasmi::Module::from_buffer(wasm_binary).expect("failed to load wasm");
-
-    let (_, compiled_image) = compile_image(&module, function_name);
-    compiled_image.tables.hash()
-}
-
-pub fn build_circuit_without_witness(
-    wasm_binary: &Vec<u8>,
-    function_name: &str,
-) -> TestCircuit<Fr> {
-    let module = wasmi::Module::from_buffer(wasm_binary).expect("failed to load wasm");
-
-    let (wasm_runtime_io, compiled_module) = compile_image(&module, function_name);
-
-    let builder = ZkWasmCircuitBuilder {
-        tables: Tables {
-            compilation_tables: compiled_module.tables,
-            execution_tables: ExecutionTable::default(),
-        },
-        public_inputs_and_outputs: wasm_runtime_io.public_inputs_and_outputs.borrow().clone(),
-    };
 
-    builder.build_circuit::<Fr>()
-}
-
-fn build_circuit_with_witness(
-    wasm_binary: &Vec<u8>,
-    function_name: &str,
-    public_inputs: &Vec<u64>,
-    private_inputs: &Vec<u64>,
-) -> Result<(TestCircuit<Fr>, Vec<Fr>)> {
-    let module = wasmi::Module::from_buffer(wasm_binary).expect("failed to load wasm");
-
-    let mut env = HostEnv::new();
-    let wasm_runtime_io =
-        register_wasm_input_foreign(&mut env, public_inputs.clone(), private_inputs.clone());
-    register_require_foreign(&mut env);
-    register_log_foreign(&mut env);
-    env.finalize();
-    let imports = ImportsBuilder::new().with_resolver("env", &env);
-
-    let compiler = WasmInterpreter::new();
-    let compiled_module = compiler
-        .compile(
-            &module,
-            &imports,
-            &env.function_description_table(),
-            function_name,
-        )
-        .expect("file cannot be complied");
-
-    let execution_result = compiled_module.run(&mut env, wasm_runtime_io)?;
-
-    execution_result.tables.profile_tables();
-
-    let instance: Vec<Fr> = execution_result
-        .public_inputs_and_outputs
-        .clone()
-        .iter()
-        .map(|v| (*v).into())
-        .collect();
-
-    let builder = ZkWasmCircuitBuilder {
-        tables: execution_result.tables,
-        public_inputs_and_outputs: execution_result.public_inputs_and_outputs,
-    };
-
-    println!("output:");
-    println!("{:?}", execution_result.outputs);
-
-    Ok((builder.build_circuit(), instance))
-}
+use super::params::ParamsLoader;
+use super::targets::read_target_manifest;
 
 pub fn exec_setup(
-    zkwasm_k: u32,
     aggregate_k: u32,
     prefix: &'static str,
-    wasm_binary: &Vec<u8>,
-    entry: &str,
+    prover: &ZkWasmProver,
+    params_loader: &ParamsLoader,
     output_dir: &PathBuf,
-) {
-    let circuit = build_circuit_without_witness(wasm_binary, entry);
-
-    info!("Setup Params and VerifyingKey");
+) -> Result<()> {
+    info!("Setup Params, VerifyingKey and ProvingKey");
 
     // Setup ZkWasm Params
-    let params = {
-        let params_path = &output_dir.join(format!("K{}.params", zkwasm_k));
-
-        if params_path.exists() {
-            info!("Found Params with K = {} at {:?}", zkwasm_k, params_path);
-        } else {
-            info!("Create Params with K = {} to {:?}", zkwasm_k, params_path);
-        }
-
-        load_or_build_unsafe_params::<Bn256>(zkwasm_k, Some(params_path))
-    };
+    let params = params_loader.load(prover.k())?;
 
     // Setup ZkWasm Vkey
-    {
+    let vkey = {
         let vk_path = &output_dir.join(format!("{}.{}.vkey.data", prefix, 0));
 
         if vk_path.exists() {
             info!("Found Verifying at {:?}", vk_path);
+
+            prover.read_vkey(&params, BufReader::new(File::open(vk_path)?))?
         } else {
             info!("Create Verifying to {:?}", vk_path);
-        }
 
-        load_or_build_vkey::<Bn256, _>(&params, &circuit, Some(vk_path));
-    }
+            let vkey = prover.keygen_vk(&params)?;
+            vkey.write(&mut File::create(vk_path)?)?;
+            vkey
+        }
+    };
 
-    // Setup Aggregate Circuit Params
+    // Setup ZkWasm Pkey, proofs of the image reuse it instead of generating it again.
     {
-        let params_path = &output_dir.join(format!("K{}.params", aggregate_k));
+        let pk_path = &output_dir.join(format!("{}.{}.pkey.data", prefix, 0));
 
-        if params_path.exists() {
-            info!("Found Params with K = {} at {:?}", aggregate_k, params_path);
+        if pk_path.exists() {
+            info!("Found ProvingKey at {:?}", pk_path);
         } else {
-            info!(
-                "Create Params with K = {} to {:?}",
-                aggregate_k, params_path
-            );
+            info!("Create ProvingKey to {:?}", pk_path);
+
+            let pkey = prover.keygen_pk(&params, vkey)?;
+            prover.write_proving_key(&pkey, BufWriter::new(File::create(pk_path)?))?;
         }
+    }
 
-        load_or_build_unsafe_params::<Bn256>(aggregate_k, Some(params_path))
-    };
+    // Setup Aggregate Circuit Params
+    params_loader.load(aggregate_k)?;
+
+    Ok(())
 }
 
 #[cfg(feature = "checksum")]
-pub fn exec_image_checksum(wasm_binary: &Vec<u8>, entry: &str, output_dir: &PathBuf) {
-    let circuit = build_circuit_without_witness(wasm_binary, entry);
-    let hash: Fr = circuit.tables.compilation_tables.hash();
-
-    let mut fd =
-        std::fs::File::create(&output_dir.join(format!("checksum.data",)).as_path()).unwrap();
+pub fn exec_image_checksum(prover: &ZkWasmProver, output_dir: &PathBuf) -> Result<()> {
+    let hash = prover.image_hash().to_string();
 
-    let hash = hash.to_string();
-    write!(fd, "{}", hash).unwrap();
+    let mut fd = File::create(&output_dir.join(format!("checksum.data",)).as_path())?;
+    write!(fd, "{}", hash)?;
     println!("{}", hash);
+
+    Ok(())
 }
 
+fn print_execution(witness: &Witness) {
+    witness.circuit.tables.profile_tables();
+
+    println!("output:");
+    println!("{:?}", witness.outputs);
+    if let Some(result) = witness.result {
+        println!("result:");
+        println!("{:?}", result);
+    }
+}
+
+/// With `estimate`, the execution is not bounded by the circuit with the current K and
+/// the smallest K it fits in is printed instead.
 pub fn exec_dry_run(
-    wasm_binary: &Vec<u8>,
-    function_name: &str,
+    prover: &ZkWasmProver,
     public_inputs: &Vec<u64>,
     private_inputs: &Vec<u64>,
+    estimate: bool,
 ) -> Result<()> {
-    let _ = build_circuit_with_witness(wasm_binary, function_name, public_inputs, private_inputs)?;
+    if estimate {
+        print!(
+            "{}",
+            prover.estimate_k(public_inputs.clone(), private_inputs.clone())?
+        );
+
+        return Ok(());
+    }
+
+    let witness = prover.generate_witness(public_inputs.clone(), private_inputs.clone())?;
+    print_execution(&witness);
 
     info!("Execution passed.");
 
     Ok(())
 }
 
+/// Reads the proving key written by setup, or builds it from the verifying key.
+fn load_proving_key(
+    prefix: &'static str,
+    prover: &ZkWasmProver,
+    params: &Params<G1Affine>,
+    output_dir: &PathBuf,
+) -> Result<ProvingKey<G1Affine>> {
+    let pk_path = &output_dir.join(format!("{}.{}.pkey.data", prefix, 0));
+
+    if pk_path.exists() {
+        info!("Load ProvingKey from {:?}", pk_path);
+
+        Ok(prover.read_proving_key(params, BufReader::new(File::open(pk_path)?))?)
+    } else {
+        info!(
+            "ProvingKey is not found at {:?}, build it from VerifyingKey",
+            pk_path
+        );
+
+        let vkey = prover.read_vkey(
+            params,
+            BufReader::new(File::open(
+                output_dir.join(format!("{}.{}.vkey.data", prefix, 0)),
+            )?),
+        )?;
+
+        Ok(prover.keygen_pk(params, vkey)?)
+    }
+}
+
 pub fn exec_create_proof(
     prefix: &'static str,
-    zkwasm_k: u32,
-    wasm_binary: &Vec<u8>,
-    function_name: &str,
+    prover: &ZkWasmProver,
+    params_loader: &ParamsLoader,
     output_dir: &PathBuf,
     public_inputs: &Vec<u64>,
     private_inputs: &Vec<u64>,
+    transcript: Transcript,
 ) -> Result<()> {
-    let (circuit, mut instance) =
-        build_circuit_with_witness(wasm_binary, function_name, public_inputs, private_inputs)?;
+    let witness = prover.generate_witness(public_inputs.clone(), private_inputs.clone())?;
+    print_execution(&witness);
 
-    {
-        store_instance(
-            &vec![instance.clone()],
-            &output_dir.join(format!("{}.{}.instance.data", prefix, 0)),
-        );
-    }
+    store_instance(
+        &vec![witness.instances.clone()],
+        &output_dir.join(format!("{}.{}.instance.data", prefix, 0)),
+    );
 
-    let mut instances = vec![];
+    witness.circuit.tables.write_json(Some(output_dir.clone()));
 
-    #[cfg(feature = "checksum")]
-    instances.push(circuit.tables.compilation_tables.hash());
+    let params = params_loader.load(prover.k())?;
+    let pkey = load_proving_key(prefix, prover, &params, output_dir)?;
 
-    instances.append(&mut instance);
+    let proof = prover.prove(&params, &pkey, &witness, transcript)?;
+    proof.write(BufWriter::new(File::create(
+        &output_dir.join(format!("{}.{}.transcript.data", prefix, 0)),
+    )?))?;
 
-    circuit.tables.write_json(Some(output_dir.clone()));
+    prover
+        .bundle(pkey.get_vk(), &witness, &proof)?
+        .write(BufWriter::new(File::create(
+            &output_dir.join(format!("{}.{}.bundle.json", prefix, 0)),
+        )?))?;
 
-    if false {
-        info!("Mock test...");
+    prover.commit_kv_store(&witness)?;
 
-        let prover = MockProver::run(zkwasm_k, &circuit, vec![instances.clone()])?;
+    info!("Proof has been created with {} transcript.", transcript);
 
-        assert_eq!(prover.verify(), Ok(()));
+    Ok(())
+}
 
-        info!("Mock test passed");
+fn load_single_instances(instance_path: &PathBuf) -> Result<Vec<Fr>> {
+    let mut instances = vec![];
+    let mut fd = File::open(&instance_path)?;
+    while let Ok(f) = Fr::read(&mut fd) {
+        instances.push(f);
     }
 
-    let params = load_or_build_unsafe_params::<Bn256>(
-        zkwasm_k,
-        Some(&output_dir.join(format!("K{}.params", zkwasm_k))),
-    );
+    Ok(instances)
+}
 
-    let vkey = load_vkey::<Bn256, TestCircuit<_>>(
+pub fn exec_verify_proof(
+    prefix: &'static str,
+    prover: &ZkWasmProver,
+    params_loader: &ParamsLoader,
+    output_dir: &PathBuf,
+    proof_path: &PathBuf,
+    instance_path: &PathBuf,
+) -> Result<()> {
+    let instances = load_single_instances(instance_path)?;
+
+    let params = params_loader.load(prover.k())?;
+
+    let vkey = prover.read_vkey(
         &params,
-        &output_dir.join(format!("{}.{}.vkey.data", prefix, 0)),
-    );
+        BufReader::new(File::open(
+            output_dir.join(format!("{}.{}.vkey.data", prefix, 0)),
+        )?),
+    )?;
 
-    load_or_create_proof::<Bn256, _>(
+    let proof = Proof::read(BufReader::new(File::open(proof_path)?))?;
+    info!("Verify proof with {} transcript", proof.transcript);
+
+    prover.verify(&params, &vkey, &instances, &proof)?;
+
+    info!("Verifing proof passed");
+
+    Ok(())
+}
+
+#[cfg(feature = "checksum")]
+fn segment_path(prefix: &'static str, output_dir: &PathBuf, index: usize, name: &str) -> PathBuf {
+    output_dir.join(format!("{}.segment{}.{}.data", prefix, index, name))
+}
+
+/// Proves the execution as segments of at most `segment_size` steps, the instances and
+/// the proof of segment `i` are written to `{prefix}.segment{i}.instance.data` and
+/// `{prefix}.segment{i}.transcript.data`.
+#[cfg(feature = "checksum")]
+pub fn exec_create_segments_proof(
+    prefix: &'static str,
+    prover: &ZkWasmProver,
+    params_loader: &ParamsLoader,
+    output_dir: &PathBuf,
+    public_inputs: &Vec<u64>,
+    private_inputs: &Vec<u64>,
+    transcript: Transcript,
+    segment_size: usize,
+) -> Result<()> {
+    let witnesses =
+        prover.generate_segments(public_inputs.clone(), private_inputs.clone(), segment_size)?;
+    print_execution(witnesses.last().unwrap());
+
+    info!("The execution is split into {} segments.", witnesses.len());
+
+    let params = params_loader.load(prover.k())?;
+    let pkey = load_proving_key(prefix, prover, &params, output_dir)?;
+
+    let proofs = prover.prove_segments(&params, &pkey, &witnesses, transcript)?;
+
+    for (index, (witness, proof)) in witnesses.iter().zip(proofs.iter()).enumerate() {
+        store_instance(
+            &vec![witness.instances.clone()],
+            &segment_path(prefix, output_dir, index, "instance"),
+        );
+        proof.write(BufWriter::new(File::create(&segment_path(
+            prefix,
+            output_dir,
+            index,
+            "transcript",
+        ))?))?;
+    }
+
+    for witness in &witnesses {
+        prover.commit_kv_store(witness)?;
+    }
+
+    info!("Proofs have been created with {} transcript.", transcript);
+
+    Ok(())
+}
+
+/// Verifies the proofs of the segments written by `exec_create_segments_proof`, from
+/// segment 0 to the last one found.
+#[cfg(feature = "checksum")]
+pub fn exec_verify_segments_proof(
+    prefix: &'static str,
+    prover: &ZkWasmProver,
+    params_loader: &ParamsLoader,
+    output_dir: &PathBuf,
+) -> Result<()> {
+    let mut segments = vec![];
+    while segment_path(prefix, output_dir, segments.len(), "transcript").exists() {
+        let index = segments.len();
+
+        let instances =
+            load_single_instances(&segment_path(prefix, output_dir, index, "instance"))?;
+        let proof = Proof::read(BufReader::new(File::open(segment_path(
+            prefix,
+            output_dir,
+            index,
+            "transcript",
+        ))?))?;
+
+        segments.push((instances, proof));
+    }
+
+    let params = params_loader.load(prover.k())?;
+
+    let vkey = prover.read_vkey(
         &params,
-        vkey,
-        circuit.clone(),
-        &[&instances],
-        Some(&output_dir.join(format!("{}.{}.transcript.data", prefix, 0))),
-        TranscriptHash::Poseidon,
-        false,
-    );
+        BufReader::new(File::open(
+            output_dir.join(format!("{}.{}.vkey.data", prefix, 0)),
+        )?),
+    )?;
 
-    info!("Proof has been created.");
+    prover.verify_segments(&params, &vkey, &segments)?;
+
+    info!("Verifing {} segments passed", segments.len());
 
     Ok(())
 }
 
-#[allow(unused_variables)]
-pub fn exec_verify_proof(
+/// Renders the Solidity verifier of the single proofs of the image to the `contracts`
+/// directory of `sol_path` from its `templates`, then writes the aux data and the calldata
+/// of the proof. The proof must be created with the sha256 transcript.
+pub fn exec_solidity_single_proof(
     prefix: &'static str,
-    public_inputs_size: usize,
-    zkwasm_k: u32,
-    wasm_binary: &Vec<u8>,
-    function_name: &str,
+    prover: &ZkWasmProver,
+    params_loader: &ParamsLoader,
     output_dir: &PathBuf,
     proof_path: &PathBuf,
     instance_path: &PathBuf,
-) {
-    let mut instance = {
-        let mut instance = vec![];
-        //load_instance::<Bn256>(&[public_inputs_size], &instances_path);
-        let mut fd = std::fs::File::open(&instance_path).unwrap();
-        while let Ok(f) = Fr::read(&mut fd) {
-            instance.push(f);
-        }
+    sol_path: &PathBuf,
+    aux_only: bool,
+) -> Result<()> {
+    let instances = load_single_instances(instance_path)?;
+    let proof = Proof::read(BufReader::new(File::open(proof_path)?))?;
 
-        instance
-    };
+    let params = params_loader.load(prover.k())?;
+    let vkey = prover.read_vkey(
+        &params,
+        BufReader::new(File::open(
+            output_dir.join(format!("{}.{}.vkey.data", prefix, 0)),
+        )?),
+    )?;
 
-    let instances = {
-        let mut instances = vec![];
+    if !aux_only {
+        prover.render_solidity_verifier(
+            &params,
+            &vkey,
+            &instances,
+            &proof,
+            &sol_path.join("templates"),
+            &sol_path.join("contracts"),
+        )?;
+    }
 
-        #[cfg(feature = "checksum")]
-        instances.push(hash_image(wasm_binary, function_name));
+    prover.solidity_aux(
+        &params,
+        &vkey,
+        &instances,
+        &proof,
+        &output_dir.join(format!("{}.{}.aux.data", prefix, 0)),
+    )?;
 
-        instances.append(&mut instance);
+    let calldata = prover.solidity_calldata(&instances, &proof)?;
+    serde_json::to_writer_pretty(
+        BufWriter::new(File::create(
+            &output_dir.join(format!("{}.{}.calldata.json", prefix, 0)),
+        )?),
+        &calldata,
+    )?;
 
-        instances
-    };
+    info!("Solidity verifier of single proofs has been rendered.");
 
-    let params = load_or_build_unsafe_params::<Bn256>(
-        zkwasm_k,
-        Some(&output_dir.join(format!("K{}.params", zkwasm_k))),
-    );
+    Ok(())
+}
 
-    let vkey = load_vkey::<Bn256, TestCircuit<_>>(
-        &params,
-        &output_dir.join(format!("{}.{}.vkey.data", prefix, 0)),
-    );
+/// Verifies `bundle` with the params of the output directory and the verifying key at
+/// `vkey_path`, or the one of the output directory.
+pub fn exec_verify_bundle(
+    prefix: &'static str,
+    params_loader: &ParamsLoader,
+    output_dir: &PathBuf,
+    bundle: &ProofBundle,
+    vkey_path: Option<PathBuf>,
+) -> Result<()> {
+    let params = params_loader.load(bundle.k)?;
 
-    let proof = load_proof(proof_path);
+    let vkey_path =
+        vkey_path.unwrap_or_else(|| output_dir.join(format!("{}.{}.vkey.data", prefix, 0)));
+    let vkey = bundle.read_vkey(&params, BufReader::new(File::open(&vkey_path)?))?;
 
-    let params_verifier: ParamsVerifier<Bn256> = params.verifier(public_inputs_size).unwrap();
-    let strategy = SingleVerifier::new(&params_verifier);
+    bundle.verify(&params, &vkey)?;
 
-    verify_proof(
-        &params_verifier,
-        &vkey,
-        strategy,
-        &[&[&instances]],
-        &mut PoseidonRead::init(&proof[..]),
-    )
-    .unwrap();
+    info!(
+        "Verifing proof of {} ({}) with {} transcript passed",
+        bundle.entry, bundle.image_md5, bundle.transcript
+    );
 
-    info!("Verifing proof passed");
+    Ok(())
 }
 
+/// Aggregates the executions by groups of `max_proofs`, then the aggregates in turn until
+/// one is left, and writes the layout of the aggregation next to the root proof.
 pub fn exec_aggregate_create_proof(
-    zkwasm_k: u32,
     aggregate_k: u32,
+    max_proofs: usize,
     prefix: &'static str,
-    wasm_binary: &Vec<u8>,
-    function_name: &str,
+    prover: &ZkWasmProver,
+    params_loader: &ParamsLoader,
     output_dir: &PathBuf,
     public_inputs: &Vec<Vec<u64>>,
     private_inputs: &Vec<Vec<u64>>,
-) {
+) -> Result<()> {
     assert_eq!(public_inputs.len(), private_inputs.len());
 
-    let (circuits, instances) = public_inputs.iter().zip(private_inputs.iter()).fold(
-        (vec![], vec![]),
-        |(mut circuits, mut instances), (public, private)| {
-            let (circuit, public_input_and_wasm_output) =
-                build_circuit_with_witness(&wasm_binary, &function_name, &public, &private)
-                    .unwrap();
-            let mut instance = vec![];
-
-            #[cfg(feature = "checksum")]
-            instance.push(hash_image(wasm_binary, function_name));
-
-            instance.append(
-                &mut public_input_and_wasm_output
-                    .iter()
-                    .map(|v| Fr::from(*v))
-                    .collect(),
-            );
-
-            circuits.push(circuit);
-            instances.push(vec![instance]);
-
-            (circuits, instances)
-        },
+    // The full passes read the params from the output directory, they only build them by
+    // an unsafe setup when they are missing.
+    params_loader.load(prover.k())?;
+    params_loader.load(aggregate_k)?;
+
+    let witnesses = public_inputs
+        .iter()
+        .zip(private_inputs.iter())
+        .map(|(public, private)| prover.generate_witness(public.clone(), private.clone()))
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+
+    let layout = prover.aggregate(output_dir, prefix, aggregate_k, max_proofs, witnesses)?;
+
+    serde_json::to_writer_pretty(
+        BufWriter::new(File::create(
+            &output_dir.join(format!("{}.{}.layout.json", AGGREGATE_PREFIX, 0)),
+        )?),
+        &layout,
+    )?;
+
+    info!(
+        "Aggregate proof of {} executions has been created with layers {:?}.",
+        layout.n_proofs, layout.layers
     );
 
-    let (aggregate_circuit, aggregate_instances) = run_circuit_unsafe_full_pass::<Bn256, _>(
-        &output_dir.as_path(),
-        prefix,
-        zkwasm_k,
-        circuits,
-        instances,
-        TranscriptHash::Poseidon,
-        vec![],
-        false,
-    )
-    .unwrap();
-
-    run_circuit_unsafe_full_pass::<Bn256, _>(
-        &output_dir.as_path(),
-        AGGREGATE_PREFIX,
-        aggregate_k,
-        vec![aggregate_circuit],
-        vec![vec![aggregate_instances]],
-        TranscriptHash::Sha,
-        vec![],
-        true,
+    Ok(())
+}
+
+/// The number of proofs of the aggregate circuit, as recorded by its layout, which
+/// differs from `default` for an aggregate over several images.
+fn aggregate_max_proofs(output_dir: &PathBuf, default: usize) -> usize {
+    fs::read(output_dir.join(format!("{}.{}.layout.json", AGGREGATE_PREFIX, 0)))
+        .ok()
+        .and_then(|layout| serde_json::from_slice::<AggregateLayout>(&layout).ok())
+        .map_or(default, |layout| layout.max_proofs)
+}
+
+/// Aggregates the proofs of the images listed by the target manifest at `targets_path`,
+/// they are all of K `zkwasm_k`.
+pub fn exec_aggregate_images(
+    zkwasm_k: u32,
+    aggregate_k: u32,
+    prefix: &'static str,
+    params_loader: &ParamsLoader,
+    output_dir: &PathBuf,
+    targets_path: &PathBuf,
+) -> Result<()> {
+    let params = params_loader.load(zkwasm_k)?;
+    params_loader.load(aggregate_k)?;
+
+    let mut provers = vec![];
+    let mut vkeys = vec![];
+    let mut proofs = vec![];
+    for files in read_target_manifest(targets_path, prefix)? {
+        let prover = ZkWasmProver::new(
+            zkwasm_k,
+            fs::read(&files.wasm)?,
+            EntryPoint::new(&files.function, files.expose_result),
+        )?;
+
+        info!("Load proof of {:?} from {:?}", files.wasm, files.proof);
+
+        vkeys.push(prover.read_vkey(&params, BufReader::new(File::open(&files.vkey)?))?);
+        proofs.push((
+            Proof::read(BufReader::new(File::open(&files.proof)?))?,
+            load_single_instances(&files.instance)?,
+        ));
+        provers.push(prover);
+    }
+
+    let targets = provers
+        .iter()
+        .zip(vkeys.iter())
+        .zip(proofs.into_iter())
+        .map(|((prover, vkey), (proof, instances))| AggregateTarget {
+            prover,
+            vkey,
+            proof,
+            instances,
+        })
+        .collect::<Vec<_>>();
+
+    let layout = aggregate_images(output_dir, &params, aggregate_k, &targets)?;
+
+    serde_json::to_writer_pretty(
+        BufWriter::new(File::create(
+            &output_dir.join(format!("{}.{}.layout.json", AGGREGATE_PREFIX, 0)),
+        )?),
+        &layout,
+    )?;
+
+    info!(
+        "Aggregate proof of {} images has been created.",
+        layout.images.len()
     );
+
+    Ok(())
 }
 
 pub fn exec_verify_aggregate_proof(
     aggregate_k: u32,
+    params_loader: &ParamsLoader,
     output_dir: &PathBuf,
     proof_path: &PathBuf,
     instances_path: &PathBuf,
-    n_proofs: usize,
-) {
-    let params = load_or_build_unsafe_params::<Bn256>(
-        aggregate_k,
-        Some(&output_dir.join(format!("K{}.params", aggregate_k))),
-    );
+    max_proofs: usize,
+) -> Result<()> {
+    let max_proofs = aggregate_max_proofs(output_dir, max_proofs);
+    let params = params_loader.load(aggregate_k)?;
 
     let proof = load_proof(&proof_path.as_path());
     let vkey = load_vkey::<Bn256, AggregatorCircuit<G1Affine>>(
@@ -435,12 +550,13 @@ pub fn exec_verify_aggregate_proof(
         &output_dir.join(format!("{}.{}.vkey.data", AGGREGATE_PREFIX, 0)),
     );
 
-    let public_inputs_size: u32 = 6 + 3 * n_proofs as u32;
+    let public_inputs_size: u32 = 6 + 3 * max_proofs as u32;
 
     let instances = load_instance::<Bn256>(&[public_inputs_size], &instances_path);
 
-    let params_verifier: ParamsVerifier<Bn256> =
-        params.verifier(public_inputs_size as usize).unwrap();
+    let params_verifier: ParamsVerifier<Bn256> = params
+        .verifier(public_inputs_size as usize)
+        .map_err(|err| anyhow!("failed to load the verifier params: {:?}", err))?;
     let strategy = SingleVerifier::new(&params_verifier);
 
     verify_proof(
@@ -450,40 +566,41 @@ pub fn exec_verify_aggregate_proof(
         &[&instances.iter().map(|x| &x[..]).collect::<Vec<_>>()[..]],
         &mut ShaRead::<_, _, _, sha2::Sha256>::init(&proof[..]),
     )
-    .unwrap();
+    .map_err(ProverError::Verification)?;
 
-    info!("Verifing Aggregate Proof Passed.")
+    info!("Verifing Aggregate Proof Passed.");
+
+    Ok(())
 }
 
 pub fn exec_solidity_aggregate_proof(
     zkwasm_k: u32,
     aggregate_k: u32,
     max_public_inputs_size: usize,
+    params_loader: &ParamsLoader,
     output_dir: &PathBuf,
     proof_path: &PathBuf,
     sol_path: &PathBuf,
     instances_path: &PathBuf,
-    n_proofs: usize,
+    max_proofs: usize,
     aux_only: bool,
-) {
+) -> Result<()> {
     let zkwasm_params_verifier: ParamsVerifier<Bn256> = {
-        let params = load_or_build_unsafe_params::<Bn256>(
-            zkwasm_k,
-            Some(&output_dir.join(format!("K{}.params", zkwasm_k))),
-        );
+        let params = params_loader.load(zkwasm_k)?;
 
-        params.verifier(max_public_inputs_size).unwrap()
+        params
+            .verifier(max_public_inputs_size)
+            .map_err(|err| anyhow!("failed to load the verifier params: {:?}", err))?
     };
 
     let (verifier_params_verifier, vkey, instances, proof) = {
-        let public_inputs_size = 6 + 3 * n_proofs;
+        let public_inputs_size = 6 + 3 * aggregate_max_proofs(output_dir, max_proofs);
 
-        let params = load_or_build_unsafe_params::<Bn256>(
-            aggregate_k,
-            Some(&output_dir.join(format!("K{}.params", aggregate_k))),
-        );
+        let params = params_loader.load(aggregate_k)?;
 
-        let params_verifier = params.verifier(public_inputs_size).unwrap();
+        let params_verifier = params
+            .verifier(public_inputs_size)
+            .map_err(|err| anyhow!("failed to load the verifier params: {:?}", err))?;
 
         let vkey = load_vkey::<Bn256, AggregatorCircuit<G1Affine>>(
             &params,
@@ -532,4 +649,6 @@ pub fn exec_solidity_aggregate_proof(
         proof,
         &output_dir.join(format!("{}.{}.aux.data", AGGREGATE_PREFIX, 0)),
     );
+
+    Ok(())
 }
@@ -0,0 +1,199 @@
+//! Input manifests: the public and private inputs of the guest read from a file.
+//!
+//! A manifest is a JSON file, or a TOML file if its extension is `.toml`. It holds either
+//! a single input set at the top level or several named sets, e.g. one per proof to be
+//! aggregated:
+//!
+//! ```toml
+//! [[sets]]
+//! name = "transfer"
+//! public = [{ type = "u64", value = 42 }, { type = "hex", value = "0x1234" }]
+//! private = [{ type = "file", value = "witness.bin" }]
+//! ```
+//!
+//! Integers become one input each. `bytes` becomes one input per byte. `hex`, `utf8`,
+//! `bytes-packed` and `file` data are packed in little-endian order, 8 bytes per input, the
+//! last input being zero padded. File paths are relative to the manifest.
+
+use std::fs;
+use std::path::Path;
+use std::path::PathBuf;
+
+use anyhow::anyhow;
+use anyhow::Result;
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Literal {
+    Unsigned(u64),
+    Signed(i64),
+    Text(String),
+}
+
+impl Literal {
+    fn integer(&self) -> Result<i128> {
+        match self {
+            Literal::Unsigned(v) => Ok(*v as i128),
+            Literal::Signed(v) => Ok(*v as i128),
+            Literal::Text(v) => match v.strip_prefix("0x") {
+                Some(hex) => Ok(u64::from_str_radix(hex, 16)? as i128),
+                None => Ok(v.parse::<i128>()?),
+            },
+        }
+    }
+
+    fn text(&self) -> Result<&str> {
+        match self {
+            Literal::Text(v) => Ok(v),
+            _ => Err(anyhow!("a string is expected")),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", content = "value", rename_all = "kebab-case")]
+enum Input {
+    I32(Literal),
+    I64(Literal),
+    U8(Literal),
+    U32(Literal),
+    U64(Literal),
+    Bytes(Literal),
+    BytesPacked(Literal),
+    Hex(Literal),
+    Utf8(Literal),
+    File(Literal),
+}
+
+fn hex_bytes(value: &Literal) -> Result<Vec<u8>> {
+    let value = value.text()?;
+    let hex = value
+        .strip_prefix("0x")
+        .ok_or_else(|| anyhow!("hex data {} does not start with 0x", value))?;
+
+    Ok(hex::decode(hex)?)
+}
+
+fn pack(bytes: &[u8]) -> Vec<u64> {
+    bytes
+        .chunks(8)
+        .map(|chunk| {
+            let mut data = [0u8; 8];
+            data[..chunk.len()].copy_from_slice(chunk);
+
+            u64::from_le_bytes(data)
+        })
+        .collect()
+}
+
+impl Input {
+    fn encode(&self, base: &Path) -> Result<Vec<u64>> {
+        let ranged = |value: &Literal, min: i128, max: i128| -> Result<u64> {
+            let v = value.integer()?;
+            if v < min || v > max {
+                return Err(anyhow!("{} is out of range [{}, {}]", v, min, max));
+            }
+            Ok(v as u64)
+        };
+
+        Ok(match self {
+            Input::I32(v) => vec![ranged(v, i32::MIN as i128, i32::MAX as i128)? as u32 as u64],
+            Input::I64(v) => vec![ranged(v, i64::MIN as i128, i64::MAX as i128)?],
+            Input::U8(v) => vec![ranged(v, 0, u8::MAX as i128)?],
+            Input::U32(v) => vec![ranged(v, 0, u32::MAX as i128)?],
+            Input::U64(v) => vec![ranged(v, 0, u64::MAX as i128)?],
+            Input::Bytes(v) => hex_bytes(v)?.into_iter().map(u64::from).collect(),
+            Input::BytesPacked(v) | Input::Hex(v) => pack(&hex_bytes(v)?),
+            Input::Utf8(v) => pack(v.text()?.as_bytes()),
+            Input::File(v) => {
+                let path = base.join(v.text()?);
+                pack(
+                    &fs::read(&path)
+                        .map_err(|err| anyhow!("failed to read {}: {}", path.display(), err))?,
+                )
+            }
+        })
+    }
+}
+
+#[derive(Deserialize)]
+struct InputSetManifest {
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    public: Vec<Input>,
+    #[serde(default)]
+    private: Vec<Input>,
+}
+
+#[derive(Deserialize)]
+struct Manifest {
+    #[serde(flatten)]
+    top_level: InputSetManifest,
+    #[serde(default)]
+    sets: Vec<InputSetManifest>,
+}
+
+pub struct InputSet {
+    pub name: String,
+    pub public_inputs: Vec<u64>,
+    pub private_inputs: Vec<u64>,
+}
+
+/// Reads the input sets of the manifest at `path`, in their order in the manifest.
+pub fn read_input_manifest(path: &PathBuf) -> Result<Vec<InputSet>> {
+    let content = fs::read_to_string(path)
+        .map_err(|err| anyhow!("failed to read {}: {}", path.display(), err))?;
+
+    let manifest: Manifest = if path.extension().map_or(false, |ext| ext == "toml") {
+        toml::from_str(&content)?
+    } else {
+        serde_json::from_str(&content)?
+    };
+
+    let base = path.parent().unwrap_or(Path::new("."));
+    let encode = |inputs: &Vec<Input>| -> Result<Vec<u64>> {
+        Ok(inputs
+            .iter()
+            .map(|input| input.encode(base))
+            .collect::<Result<Vec<_>>>()?
+            .concat())
+    };
+
+    let sets = if manifest.sets.is_empty() {
+        vec![manifest.top_level]
+    } else if manifest.top_level.public.is_empty() && manifest.top_level.private.is_empty() {
+        manifest.sets
+    } else {
+        return Err(anyhow!(
+            "{} mixes top level inputs with named input sets",
+            path.display()
+        ));
+    };
+
+    sets.iter()
+        .map(|set| {
+            Ok(InputSet {
+                name: set.name.clone(),
+                public_inputs: encode(&set.public)?,
+                private_inputs: encode(&set.private)?,
+            })
+        })
+        .collect()
+}
+
+/// The set named `name`, or the only set of the manifest if no name is given.
+pub fn select_input_set(sets: Vec<InputSet>, name: Option<&String>) -> Result<InputSet> {
+    match name {
+        Some(name) => sets
+            .into_iter()
+            .find(|set| &set.name == name)
+            .ok_or_else(|| anyhow!("input set {} is not found", name)),
+        None if sets.len() == 1 => Ok(sets.into_iter().next().unwrap()),
+        None => Err(anyhow!(
+            "the manifest has {} input sets, select one by name",
+            sets.len()
+        )),
+    }
+}
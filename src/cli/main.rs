@@ -125,11 +125,11 @@ impl AppBuilder for SampleApp {
     const AGGREGATE_K: u32 = 22;
     const MAX_PUBLIC_INPUT_SIZE: usize = 64;
 
-    const N_PROOFS: usize = 1;
+    const MAX_AGGREGATE_PROOFS: usize = 1;
 }
 
 /// Simple program to greet a person
-fn main() {
+fn main() -> anyhow::Result<()> {
     let app = SampleApp::app_builder();
 
     SampleApp::exec(app)
@@ -2,3 +2,6 @@ pub mod app_builder;
 pub mod args;
 pub mod command;
 pub mod exec;
+pub mod inputs;
+pub mod params;
+pub mod targets;
@@ -0,0 +1,103 @@
+//! Params of the output directory.
+//!
+//! The params of K are kept at `K{k}.params` in the output directory. When they are
+//! missing, they are imported from the ceremony file given by `--params` and downsized to
+//! K. Params from an unsafe setup are only built, and only used afterwards, when
+//! `--unsafe_params` is given; they are marked by a `K{k}.params.unsafe` file next to them.
+
+use std::fs;
+use std::fs::File;
+use std::io::BufReader;
+use std::io::BufWriter;
+use std::path::PathBuf;
+
+use anyhow::anyhow;
+use anyhow::Result;
+use halo2_proofs::pairing::bn256::Bn256;
+use halo2_proofs::pairing::bn256::G1Affine;
+use halo2_proofs::poly::commitment::Params;
+use log::info;
+use log::warn;
+
+use crate::prover::import_params;
+
+pub struct ParamsLoader {
+    output_dir: PathBuf,
+    /// Powers-of-tau ceremony file, in the halo2 format or a snarkjs `.ptau` file.
+    ceremony: Option<PathBuf>,
+    allow_unsafe: bool,
+}
+
+impl ParamsLoader {
+    pub fn new(output_dir: PathBuf, ceremony: Option<PathBuf>, allow_unsafe: bool) -> Self {
+        ParamsLoader {
+            output_dir,
+            ceremony,
+            allow_unsafe,
+        }
+    }
+
+    pub fn params_path(&self, k: u32) -> PathBuf {
+        self.output_dir.join(format!("K{}.params", k))
+    }
+
+    fn unsafe_marker_path(&self, k: u32) -> PathBuf {
+        self.output_dir.join(format!("K{}.params.unsafe", k))
+    }
+
+    /// Loads the params of K from the output directory, importing or building them first
+    /// if they are missing.
+    pub fn load(&self, k: u32) -> Result<Params<G1Affine>> {
+        let params_path = self.params_path(k);
+
+        if params_path.exists() {
+            if self.unsafe_marker_path(k).exists() && !self.allow_unsafe {
+                return Err(anyhow!(
+                    "Params at {:?} are from an unsafe setup, pass --unsafe_params to use them",
+                    params_path
+                ));
+            }
+
+            info!("Found Params with K = {} at {:?}", k, params_path);
+
+            let params = Params::<G1Affine>::read(BufReader::new(File::open(&params_path)?))?;
+            if params.k != k {
+                return Err(anyhow!(
+                    "Params at {:?} are of K {} but K {} is expected",
+                    params_path,
+                    params.k,
+                    k
+                ));
+            }
+
+            return Ok(params);
+        }
+
+        let params = if let Some(ceremony) = &self.ceremony {
+            info!(
+                "Import Params with K = {} from {:?} to {:?}",
+                k, ceremony, params_path
+            );
+
+            import_params(BufReader::new(File::open(ceremony)?), k)?
+        } else if self.allow_unsafe {
+            warn!(
+                "Create Params with K = {} by an unsafe setup to {:?}, they are only fit for testing",
+                k, params_path
+            );
+
+            fs::write(self.unsafe_marker_path(k), [])?;
+            Params::<G1Affine>::unsafe_setup::<Bn256>(k)
+        } else {
+            return Err(anyhow!(
+                "Params with K = {} are not found at {:?}, import them from a ceremony file with --params, or pass --unsafe_params to build them by an unsafe setup for testing",
+                k,
+                params_path
+            ));
+        };
+
+        params.write(&mut BufWriter::new(File::create(&params_path)?))?;
+
+        Ok(params)
+    }
+}
@@ -0,0 +1,90 @@
+//! Aggregate target manifests: the proofs of different images to be aggregated together.
+//!
+//! A manifest is a JSON file, or a TOML file if its extension is `.toml`, listing the
+//! targets in the order of the aggregate:
+//!
+//! ```toml
+//! [[targets]]
+//! wasm = "matcher.wasm"
+//! function = "zkmain"
+//! output = "matcher"
+//! ```
+//!
+//! The verifying key, the proof and the instances of a target are the ones `setup` and
+//! `single-prove` write to its `output` directory, unless `vkey`, `proof` or `instance` are
+//! given. Paths are relative to the manifest.
+
+use std::fs;
+use std::path::Path;
+use std::path::PathBuf;
+
+use anyhow::anyhow;
+use anyhow::Result;
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+struct TargetManifest {
+    wasm: PathBuf,
+    function: String,
+    #[serde(default)]
+    expose_result: bool,
+    output: PathBuf,
+    vkey: Option<PathBuf>,
+    proof: Option<PathBuf>,
+    instance: Option<PathBuf>,
+}
+
+#[derive(Deserialize)]
+struct Manifest {
+    targets: Vec<TargetManifest>,
+}
+
+pub struct TargetFiles {
+    pub wasm: PathBuf,
+    pub function: String,
+    pub expose_result: bool,
+    pub vkey: PathBuf,
+    pub proof: PathBuf,
+    pub instance: PathBuf,
+}
+
+/// Reads the targets of the manifest at `path`, `prefix` is the one of the files of the
+/// output directories.
+pub fn read_target_manifest(path: &PathBuf, prefix: &str) -> Result<Vec<TargetFiles>> {
+    let content = fs::read_to_string(path)
+        .map_err(|err| anyhow!("failed to read {}: {}", path.display(), err))?;
+
+    let manifest: Manifest = if path.extension().map_or(false, |ext| ext == "toml") {
+        toml::from_str(&content)?
+    } else {
+        serde_json::from_str(&content)?
+    };
+
+    if manifest.targets.is_empty() {
+        return Err(anyhow!("{} lists no target", path.display()));
+    }
+
+    let base = path.parent().unwrap_or(Path::new("."));
+
+    Ok(manifest
+        .targets
+        .into_iter()
+        .map(|target| {
+            let output = base.join(&target.output);
+            let file = |given: Option<PathBuf>, name: &str| {
+                given.map_or(output.join(format!("{}.0.{}", prefix, name)), |path| {
+                    base.join(path)
+                })
+            };
+
+            TargetFiles {
+                wasm: base.join(&target.wasm),
+                function: target.function,
+                expose_result: target.expose_result,
+                vkey: file(target.vkey, "vkey.data"),
+                proof: file(target.proof, "transcript.data"),
+                instance: file(target.instance, "instance.data"),
+            }
+        })
+        .collect())
+}
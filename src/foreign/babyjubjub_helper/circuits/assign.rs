@@ -0,0 +1,451 @@
+use halo2_proofs::arithmetic::FieldExt;
+use halo2_proofs::circuit::Layouter;
+use halo2_proofs::circuit::Region;
+use halo2_proofs::plonk::Error;
+use num_bigint::BigUint;
+use specs::etable::EventTableEntry;
+use specs::step::StepInfo;
+
+use super::encode_call;
+use super::BabyJubjubHelperTableConfig;
+use super::END_CALL_ROW;
+use super::LIMB_BITS;
+use super::POINT_ROW;
+use super::PUSH_ROW;
+use super::SCALAR_ROW;
+use super::SLOT_ROWS;
+use super::SUM_ROW;
+use super::SUM_WORD_ROW;
+use crate::circuits::utils::bn_to_field;
+use crate::foreign::babyjubjub_helper::scalar_bits;
+use crate::foreign::babyjubjub_helper::term_point;
+use crate::foreign::babyjubjub_helper::Op;
+use crate::foreign::babyjubjub_helper::Point;
+use crate::foreign::babyjubjub_helper::PUSH_CALLS;
+use crate::foreign::babyjubjub_helper::SCALAR_BITS;
+use crate::foreign::poseidon_helper::decompose;
+use crate::foreign::poseidon_helper::top_limb_bound;
+use crate::foreign::poseidon_helper::LIMBS;
+
+struct Slot {
+    is_new: bool,
+    is_final: bool,
+    is_complete: bool,
+    term: [u64; PUSH_CALLS],
+}
+
+/// The rows of an entry of the table, a term of a sum.
+pub const ENTRY_ROWS: usize = SLOT_ROWS;
+
+/// The terms the calls of `entries` push.
+pub(crate) fn table_entries(entries: &[EventTableEntry]) -> usize {
+    slots(entries).len()
+}
+
+/// The slots of the calls of `entries`, the steps of the plugin in the order of execution.
+fn slots(entries: &[EventTableEntry]) -> Vec<Slot> {
+    let mut slots: Vec<Slot> = vec![];
+
+    let mut is_new = false;
+    let mut term = vec![];
+
+    for entry in entries {
+        match &entry.step_info {
+            StepInfo::CallHost {
+                op_index_in_plugin,
+                args,
+                ..
+            } => match Op::from_index(*op_index_in_plugin) {
+                Op::New => is_new = true,
+                Op::Push => {
+                    term.push(args[0]);
+
+                    if term.len() == PUSH_CALLS {
+                        slots.push(Slot {
+                            is_new,
+                            is_final: false,
+                            is_complete: true,
+                            term: std::mem::take(&mut term).try_into().unwrap(),
+                        });
+
+                        is_new = false;
+                    }
+                }
+                // The calls of `babyjubjub_finalize` read the sum after the last term.
+                Op::Finalize => slots.last_mut().unwrap().is_final = true,
+            },
+            _ => unreachable!(),
+        }
+    }
+
+    // The calls of the last term may follow the last step, they are left out of the
+    // lookups of the event table.
+    if is_new || !term.is_empty() {
+        term.resize(PUSH_CALLS, 0);
+
+        slots.push(Slot {
+            is_new,
+            is_final: false,
+            is_complete: false,
+            term: term.try_into().unwrap(),
+        });
+    }
+
+    slots
+}
+
+/// The points of a row, `(dx, dy)` is added to `(qx, qy)` into `(ox, oy)`.
+struct RowPoints<F: FieldExt> {
+    acc: Point<F>,
+    double: Point<F>,
+    addend: Point<F>,
+    output: Point<F>,
+}
+
+impl<F: FieldExt> RowPoints<F> {
+    fn new(acc: Point<F>, double: Point<F>, addend: Point<F>) -> Self {
+        let output = double
+            .add(&addend)
+            .expect("babyjubjub helper table: a point is out of the curve");
+
+        RowPoints {
+            acc,
+            double,
+            addend,
+            output,
+        }
+    }
+}
+
+pub struct BabyJubjubHelperTableChip<F: FieldExt> {
+    config: BabyJubjubHelperTableConfig<F>,
+    max_available_rows: usize,
+}
+
+impl<F: FieldExt> BabyJubjubHelperTableChip<F> {
+    pub fn new(config: BabyJubjubHelperTableConfig<F>, max_available_rows: usize) -> Self {
+        Self {
+            config,
+            max_available_rows,
+        }
+    }
+
+    fn assign_fixed(&self, region: &mut Region<'_, F>, slot: usize) -> Result<(), Error> {
+        let mut assign = |name, column, row: usize, value: F| {
+            region.assign_fixed(
+                || format!("babyjubjub helper {}", name),
+                column,
+                slot * SLOT_ROWS + row,
+                || Ok(value),
+            )
+        };
+
+        for row in 0..SLOT_ROWS {
+            assign("sel", self.config.sel, row, F::one())?;
+        }
+
+        assign("sel slot", self.config.sel_slot, 0, F::one())?;
+        if slot > 0 {
+            assign("sel chain", self.config.sel_chain, 0, F::one())?;
+        }
+
+        for i in 0..END_CALL_ROW - PUSH_ROW {
+            let row = PUSH_ROW + i;
+
+            if i < PUSH_CALLS {
+                assign("sel push", self.config.sel_push, row, F::one())?;
+            } else {
+                assign("sel finalize", self.config.sel_finalize, row, F::one())?;
+            }
+
+            if i % LIMBS == 0 {
+                assign("sel first limb", self.config.sel_first_limb, row, F::one())?;
+            }
+            assign(
+                "limb weight",
+                self.config.limb_weight,
+                row,
+                bn_to_field(&(BigUint::from(1u64) << (64 * (i % LIMBS)))),
+            )?;
+        }
+
+        for row in 0..SCALAR_BITS {
+            assign("sel bit", self.config.sel_bit, row, F::one())?;
+
+            if row % LIMB_BITS == 0 {
+                assign("sel limb start", self.config.sel_limb_start, row, F::one())?;
+            }
+        }
+
+        assign("sel sum", self.config.sel_sum, SUM_ROW, F::one())?;
+
+        for row in 0..LIMB_BITS {
+            assign("sel bound", self.config.sel_bound, row, F::one())?;
+            assign(
+                "bit weight",
+                self.config.bit_weight,
+                row,
+                F::from(1u64 << row),
+            )?;
+        }
+        assign(
+            "sel last bit",
+            self.config.sel_last_bit,
+            LIMB_BITS - 1,
+            F::one(),
+        )?;
+
+        Ok(())
+    }
+
+    /// Assigns `slot`, or a disabled slot if `None`, as the slot `index` adding to the sum
+    /// `prev` of the previous slot, returns the sum of the slot.
+    fn assign_slot(
+        &self,
+        region: &mut Region<'_, F>,
+        index: usize,
+        slot: Option<&Slot>,
+        prev: Point<F>,
+        call_index: &mut u64,
+    ) -> Result<Point<F>, Error> {
+        let start = index * SLOT_ROWS;
+
+        let enable = slot.is_some();
+        let is_new = slot.map_or(false, |slot| slot.is_new);
+        let is_final = slot.map_or(false, |slot| slot.is_final);
+        let is_complete = slot.map_or(false, |slot| slot.is_complete);
+        let term = slot.map_or([0; PUSH_CALLS], |slot| slot.term);
+
+        let point = term_point::<F>(&term);
+        let scalar = term[2 * LIMBS..].try_into().unwrap();
+        let bits = scalar_bits(&scalar);
+
+        let mut rows = vec![];
+        let mut acc = Point::identity();
+        for bit in bits.iter() {
+            let double = acc
+                .add(&acc)
+                .expect("babyjubjub helper table: a point is out of the curve");
+            let addend = if *bit { point } else { Point::identity() };
+            let row = RowPoints::new(acc, double, addend);
+
+            acc = row.output;
+            rows.push(row);
+        }
+
+        let chained = index > 0 && !is_new;
+        let sum_row = RowPoints::new(
+            Point {
+                x: F::zero(),
+                y: F::zero(),
+            },
+            if chained { prev } else { Point::identity() },
+            acc,
+        );
+        let sum = sum_row.output;
+        rows.push(sum_row);
+
+        let sum_limbs = [decompose(&sum.x), decompose(&sum.y)].concat();
+        let slack = |limb: u64| {
+            if is_final {
+                top_limb_bound::<F>().checked_sub(limb + 1).expect(
+                    "babyjubjub helper table: the top limb of a sum is the one of the modulus",
+                )
+            } else {
+                0
+            }
+        };
+        let x_slack = slack(sum_limbs[LIMBS - 1]);
+        let y_slack = slack(sum_limbs[2 * LIMBS - 1]);
+
+        let mut word = F::zero();
+        let mut limb_acc = 0u64;
+        let mut x_bit_acc = 0u64;
+        let mut y_bit_acc = 0u64;
+
+        let mut limbs = vec![];
+        let mut words = vec![];
+        let mut limb_accs = vec![];
+        let mut px = None;
+        let mut py = None;
+        let mut ox = None;
+        let mut oy = None;
+
+        for (row, points) in rows.iter().enumerate() {
+            let offset = start + row;
+
+            let mut assign_advice = |name, column, value: F| {
+                region.assign_advice(
+                    || format!("babyjubjub helper {}", name),
+                    column,
+                    offset,
+                    || Ok(value),
+                )
+            };
+
+            assign_advice("enable", self.config.enable, F::from(enable as u64))?;
+            assign_advice("is new", self.config.is_new, F::from(is_new as u64))?;
+            assign_advice("is final", self.config.is_final, F::from(is_final as u64))?;
+            assign_advice(
+                "is complete",
+                self.config.is_complete,
+                F::from(is_complete as u64),
+            )?;
+
+            let px_cell = assign_advice("px", self.config.px, point.x)?;
+            let py_cell = assign_advice("py", self.config.py, point.y)?;
+            if row == 0 {
+                px = Some(px_cell);
+                py = Some(py_cell);
+            }
+
+            if row < SCALAR_BITS {
+                let bit = bits[row] as u64;
+                limb_acc = if row % LIMB_BITS == 0 {
+                    bit
+                } else {
+                    (limb_acc << 1) + bit
+                };
+
+                assign_advice("bit", self.config.bit, F::from(bit))?;
+                let cell = assign_advice("limb acc", self.config.limb_acc, F::from(limb_acc))?;
+                if row % LIMB_BITS == LIMB_BITS - 1 {
+                    limb_accs.push(cell);
+                }
+
+                assign_advice("x1", self.config.x1, points.acc.x)?;
+                assign_advice("y1", self.config.y1, points.acc.y)?;
+                assign_advice("t1", self.config.t1, points.acc.x.square())?;
+                assign_advice("u1", self.config.u1, points.acc.y.square())?;
+            } else {
+                assign_advice("t1", self.config.t1, point.x.square())?;
+                assign_advice("u1", self.config.u1, point.y.square())?;
+            }
+
+            assign_advice("dx", self.config.dx, points.double.x)?;
+            assign_advice("dy", self.config.dy, points.double.y)?;
+            assign_advice("qx", self.config.qx, points.addend.x)?;
+            assign_advice("qy", self.config.qy, points.addend.y)?;
+            assign_advice("t2", self.config.t2, points.double.x * points.addend.x)?;
+            assign_advice("u2", self.config.u2, points.double.y * points.addend.y)?;
+            let ox_cell = assign_advice("ox", self.config.ox, points.output.x)?;
+            let oy_cell = assign_advice("oy", self.config.oy, points.output.y)?;
+            if row == SUM_ROW {
+                ox = Some(ox_cell);
+                oy = Some(oy_cell);
+            }
+
+            if row < LIMB_BITS {
+                let x_bit = (x_slack >> row) & 1;
+                let y_bit = (y_slack >> row) & 1;
+                x_bit_acc += x_bit << row;
+                y_bit_acc += y_bit << row;
+
+                assign_advice("x bit", self.config.x_bit, F::from(x_bit))?;
+                assign_advice("x bit acc", self.config.x_bit_acc, F::from(x_bit_acc))?;
+                assign_advice("y bit", self.config.y_bit, F::from(y_bit))?;
+                assign_advice("y bit acc", self.config.y_bit_acc, F::from(y_bit_acc))?;
+            }
+
+            let call = if row == 0 {
+                assign_advice("limb", self.config.limb, F::zero())?;
+
+                is_new.then(|| (Op::New, 0))
+            } else if (PUSH_ROW..END_CALL_ROW).contains(&row) {
+                let i = row - PUSH_ROW;
+                let (op, limb) = if i < PUSH_CALLS {
+                    (Op::Push, term[i])
+                } else {
+                    (Op::Finalize, sum_limbs[i - PUSH_CALLS])
+                };
+
+                let weighted: F = bn_to_field(&(BigUint::from(limb) << (64 * (i % LIMBS))));
+                word = if i % LIMBS == 0 {
+                    weighted
+                } else {
+                    word + weighted
+                };
+
+                limbs.push(assign_advice("limb", self.config.limb, F::from(limb))?);
+                words.push(assign_advice("word", self.config.word, word)?);
+
+                let is_called = match op {
+                    Op::Push => enable,
+                    _ => is_final,
+                };
+                is_called.then(|| (op, limb))
+            } else {
+                None
+            };
+
+            assign_advice("call index", self.config.call_index, F::from(*call_index))?;
+
+            if let Some((op, value)) = call {
+                assign_advice("call enable", self.config.call_enable, F::one())?;
+                assign_advice(
+                    "lookup index",
+                    self.config.lookup_index,
+                    F::from(*call_index),
+                )?;
+                assign_advice(
+                    "call encoded",
+                    self.config.call_encoded,
+                    encode_call(op, value),
+                )?;
+
+                *call_index += 1;
+            }
+        }
+
+        // The cells of the calls, from `PUSH_ROW`.
+        let word_at = |row: usize| words[row - PUSH_ROW].cell();
+
+        region.constrain_equal(word_at(POINT_ROW), px.unwrap().cell())?;
+        region.constrain_equal(word_at(POINT_ROW + LIMBS), py.unwrap().cell())?;
+        // The limbs of the scalar are little-endian, its bits are from the most significant.
+        for (i, limb_acc) in limb_accs.iter().rev().enumerate() {
+            region.constrain_equal(limbs[SCALAR_ROW + i - PUSH_ROW].cell(), limb_acc.cell())?;
+        }
+        region.constrain_equal(word_at(SUM_WORD_ROW), ox.unwrap().cell())?;
+        region.constrain_equal(word_at(SUM_WORD_ROW + LIMBS), oy.unwrap().cell())?;
+
+        Ok(sum)
+    }
+
+    /// Assigns the calls of `entries`, the steps of the plugin in the event table.
+    pub fn assign(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        entries: &[EventTableEntry],
+    ) -> Result<(), Error> {
+        let capability = self.max_available_rows / ENTRY_ROWS;
+        let slots = slots(entries);
+        if slots.len() > capability {
+            return Err(Error::Synthesis);
+        }
+
+        layouter.assign_region(
+            || "babyjubjub helper table",
+            |mut region| {
+                for slot in 0..capability {
+                    self.assign_fixed(&mut region, slot)?;
+                }
+
+                // A disabled slot adds the identity to the sum of the previous one.
+                let mut call_index = 0;
+                let mut sum = Point::identity();
+                for index in 0..capability {
+                    sum = self.assign_slot(
+                        &mut region,
+                        index,
+                        slots.get(index),
+                        sum,
+                        &mut call_index,
+                    )?;
+                }
+
+                Ok(())
+            },
+        )
+    }
+}
@@ -0,0 +1,364 @@
+use std::marker::PhantomData;
+
+use halo2_proofs::arithmetic::FieldExt;
+use halo2_proofs::plonk::ConstraintSystem;
+use halo2_proofs::plonk::Expression;
+use halo2_proofs::plonk::VirtualCells;
+use num_bigint::BigUint;
+
+use crate::constant_from;
+use crate::constant_from_bn;
+use crate::curr;
+use crate::fixed_curr;
+use crate::fixed_nextn;
+use crate::foreign::babyjubjub_helper::Op;
+use crate::foreign::babyjubjub_helper::A;
+use crate::foreign::babyjubjub_helper::D;
+use crate::foreign::poseidon_helper::top_limb_bound;
+use crate::foreign::poseidon_helper::LIMBS;
+use crate::foreign::ForeignTableConfig;
+use crate::nextn;
+
+use super::BabyJubjubHelperTableConfig;
+use super::LIMB_BITS;
+use super::SLOT_ROWS;
+use super::SUM_ROW;
+use super::SUM_WORD_ROW;
+
+fn tag<F: FieldExt>(op: Op) -> Expression<F> {
+    constant_from_bn!(&(BigUint::from(op.tag()) << 64))
+}
+
+impl<F: FieldExt> BabyJubjubHelperTableConfig<F> {
+    pub fn configure(meta: &mut ConstraintSystem<F>) -> Self {
+        let sel = meta.fixed_column();
+        let sel_slot = meta.fixed_column();
+        let sel_chain = meta.fixed_column();
+        let sel_push = meta.fixed_column();
+        let sel_finalize = meta.fixed_column();
+        let sel_first_limb = meta.fixed_column();
+        let limb_weight = meta.fixed_column();
+        let sel_bit = meta.fixed_column();
+        let sel_limb_start = meta.fixed_column();
+        let sel_sum = meta.fixed_column();
+        let sel_bound = meta.fixed_column();
+        let bit_weight = meta.fixed_column();
+        let sel_last_bit = meta.fixed_column();
+
+        let enable = meta.advice_column();
+        let is_new = meta.advice_column();
+        let is_final = meta.advice_column();
+        let is_complete = meta.advice_column();
+
+        let limb = meta.advice_column();
+        let word = meta.advice_column();
+        let bit = meta.advice_column();
+        let limb_acc = meta.advice_column();
+        let px = meta.advice_column();
+        let py = meta.advice_column();
+
+        let x1 = meta.advice_column();
+        let y1 = meta.advice_column();
+        let t1 = meta.advice_column();
+        let u1 = meta.advice_column();
+        let dx = meta.advice_column();
+        let dy = meta.advice_column();
+        let qx = meta.advice_column();
+        let qy = meta.advice_column();
+        let t2 = meta.advice_column();
+        let u2 = meta.advice_column();
+        let ox = meta.advice_column();
+        let oy = meta.advice_column();
+
+        let x_bit = meta.advice_column();
+        let x_bit_acc = meta.advice_column();
+        let y_bit = meta.advice_column();
+        let y_bit_acc = meta.advice_column();
+
+        let call_enable = meta.advice_column();
+        let call_index = meta.advice_column();
+        let lookup_index = meta.advice_column();
+        let call_encoded = meta.advice_column();
+
+        for column in [limb, word, limb_acc, px, py, ox, oy] {
+            meta.enable_equality(column);
+        }
+
+        meta.create_gate("babyjubjub helper: slot", |meta| {
+            let enable_curr = curr!(meta, enable);
+            let is_new_curr = curr!(meta, is_new);
+            let is_final_curr = curr!(meta, is_final);
+            let is_complete_curr = curr!(meta, is_complete);
+            let sel_chain = fixed_curr!(meta, sel_chain);
+
+            vec![
+                enable_curr.clone() * (constant_from!(1) - enable_curr.clone()),
+                is_new_curr.clone() * (constant_from!(1) - is_new_curr.clone()),
+                is_final_curr.clone() * (constant_from!(1) - is_final_curr.clone()),
+                is_complete_curr.clone() * (constant_from!(1) - is_complete_curr.clone()),
+                (is_new_curr.clone() + is_final_curr.clone() + is_complete_curr.clone())
+                    * (constant_from!(1) - enable_curr.clone()),
+                is_final_curr * (constant_from!(1) - is_complete_curr),
+                // The enabled slots precede the others.
+                sel_chain.clone()
+                    * (constant_from!(1) - nextn!(meta, enable, -(SLOT_ROWS as i32)))
+                    * enable_curr.clone(),
+                // Only the last enabled slot may have a partial term.
+                sel_chain.clone()
+                    * enable_curr.clone()
+                    * (constant_from!(1) - nextn!(meta, is_complete, -(SLOT_ROWS as i32))),
+                // A sum continues after a term that is not its last one.
+                sel_chain.clone()
+                    * enable_curr.clone()
+                    * (constant_from!(1) - is_new_curr.clone())
+                    * nextn!(meta, is_final, -(SLOT_ROWS as i32)),
+                (constant_from!(1) - sel_chain) * enable_curr * (constant_from!(1) - is_new_curr),
+            ]
+            .into_iter()
+            .map(|expr| expr * fixed_curr!(meta, sel_slot))
+            .collect::<Vec<_>>()
+        });
+
+        meta.create_gate("babyjubjub helper: slot flags", |meta| {
+            vec![
+                curr!(meta, enable) - nextn!(meta, enable, -1),
+                curr!(meta, is_new) - nextn!(meta, is_new, -1),
+                curr!(meta, is_final) - nextn!(meta, is_final, -1),
+                curr!(meta, is_complete) - nextn!(meta, is_complete, -1),
+                curr!(meta, px) - nextn!(meta, px, -1),
+                curr!(meta, py) - nextn!(meta, py, -1),
+            ]
+            .into_iter()
+            .map(|expr| expr * (fixed_curr!(meta, sel) - fixed_curr!(meta, sel_slot)))
+            .collect::<Vec<_>>()
+        });
+
+        meta.create_gate("babyjubjub helper: words", |meta| {
+            vec![
+                (curr!(meta, word)
+                    - curr!(meta, limb) * fixed_curr!(meta, limb_weight)
+                    - (constant_from!(1) - fixed_curr!(meta, sel_first_limb))
+                        * nextn!(meta, word, -1))
+                    * (fixed_curr!(meta, sel_push) + fixed_curr!(meta, sel_finalize)),
+            ]
+        });
+
+        meta.create_gate("babyjubjub helper: scalar", |meta| {
+            let bit = curr!(meta, bit);
+
+            vec![
+                bit.clone() * (constant_from!(1) - bit.clone()),
+                curr!(meta, limb_acc)
+                    - bit
+                    - constant_from!(2)
+                        * (constant_from!(1) - fixed_curr!(meta, sel_limb_start))
+                        * nextn!(meta, limb_acc, -1),
+            ]
+            .into_iter()
+            .map(|expr| expr * fixed_curr!(meta, sel_bit))
+            .collect::<Vec<_>>()
+        });
+
+        meta.create_gate("babyjubjub helper: double", |meta| {
+            let x1 = curr!(meta, x1);
+            let y1 = curr!(meta, y1);
+            let t1 = curr!(meta, t1);
+            let u1 = curr!(meta, u1);
+            let dtu = constant_from!(D) * t1.clone() * u1.clone();
+            let bit = curr!(meta, bit);
+            let sel_slot = fixed_curr!(meta, sel_slot);
+
+            vec![
+                t1.clone() - x1.clone() * x1.clone(),
+                u1.clone() - y1.clone() * y1.clone(),
+                curr!(meta, dx) * (constant_from!(1) + dtu.clone())
+                    - constant_from!(2) * x1.clone() * y1.clone(),
+                curr!(meta, dy) * (constant_from!(1) - dtu) - (u1 - constant_from!(A) * t1),
+                curr!(meta, qx) - bit.clone() * curr!(meta, px),
+                curr!(meta, qy) - bit.clone() * curr!(meta, py) - (constant_from!(1) - bit),
+                // The accumulator starts from the identity.
+                x1 - (constant_from!(1) - sel_slot.clone()) * nextn!(meta, ox, -1),
+                y1 - (constant_from!(1) - sel_slot.clone()) * nextn!(meta, oy, -1) - sel_slot,
+            ]
+            .into_iter()
+            .map(|expr| expr * fixed_curr!(meta, sel_bit))
+            .collect::<Vec<_>>()
+        });
+
+        meta.create_gate("babyjubjub helper: add", |meta| {
+            let dx = curr!(meta, dx);
+            let dy = curr!(meta, dy);
+            let qx = curr!(meta, qx);
+            let qy = curr!(meta, qy);
+            let t2 = curr!(meta, t2);
+            let u2 = curr!(meta, u2);
+            let dtu = constant_from!(D) * t2.clone() * u2.clone();
+
+            vec![
+                t2.clone() - dx.clone() * qx.clone(),
+                u2.clone() - dy.clone() * qy.clone(),
+                curr!(meta, ox) * (constant_from!(1) + dtu.clone()) - (dx * qy + dy * qx),
+                curr!(meta, oy) * (constant_from!(1) - dtu) - (u2 - constant_from!(A) * t2),
+            ]
+            .into_iter()
+            .map(|expr| expr * fixed_curr!(meta, sel))
+            .collect::<Vec<_>>()
+        });
+
+        meta.create_gate("babyjubjub helper: sum", |meta| {
+            // Whether the multiple is added to the sum of the previous slot.
+            let chained = fixed_nextn!(meta, sel_chain, -(SUM_ROW as i32))
+                * (constant_from!(1) - curr!(meta, is_new));
+            let px = curr!(meta, px);
+            let py = curr!(meta, py);
+            let t1 = curr!(meta, t1);
+            let u1 = curr!(meta, u1);
+
+            vec![
+                curr!(meta, qx) - nextn!(meta, ox, -1),
+                curr!(meta, qy) - nextn!(meta, oy, -1),
+                curr!(meta, dx) - chained.clone() * nextn!(meta, ox, -(SLOT_ROWS as i32)),
+                curr!(meta, dy)
+                    - chained.clone() * nextn!(meta, oy, -(SLOT_ROWS as i32))
+                    - (constant_from!(1) - chained),
+                t1.clone() - px.clone() * px,
+                u1.clone() - py.clone() * py,
+                curr!(meta, is_complete)
+                    * (constant_from!(A) * t1.clone() + u1.clone()
+                        - constant_from!(1)
+                        - constant_from!(D) * t1 * u1),
+            ]
+            .into_iter()
+            .map(|expr| expr * fixed_curr!(meta, sel_sum))
+            .collect::<Vec<_>>()
+        });
+
+        meta.create_gate("babyjubjub helper: sum bound", |meta| {
+            let x_bit = curr!(meta, x_bit);
+            let y_bit = curr!(meta, y_bit);
+            let chained = constant_from!(1) - fixed_curr!(meta, sel_slot);
+            // The top limbs of the coordinates of the sum, from the last row of the bits.
+            let x_top_limb = nextn!(meta, limb, SUM_WORD_ROW as i32 - (LIMB_BITS as i32 - 1));
+            let y_top_limb = nextn!(
+                meta,
+                limb,
+                (SUM_WORD_ROW + LIMBS) as i32 - (LIMB_BITS as i32 - 1)
+            );
+            let bound = constant_from!(top_limb_bound::<F>() - 1);
+
+            vec![
+                x_bit.clone() * (constant_from!(1) - x_bit.clone()) * fixed_curr!(meta, sel_bound),
+                y_bit.clone() * (constant_from!(1) - y_bit.clone()) * fixed_curr!(meta, sel_bound),
+                (curr!(meta, x_bit_acc)
+                    - x_bit * fixed_curr!(meta, bit_weight)
+                    - chained.clone() * nextn!(meta, x_bit_acc, -1))
+                    * fixed_curr!(meta, sel_bound),
+                (curr!(meta, y_bit_acc)
+                    - y_bit * fixed_curr!(meta, bit_weight)
+                    - chained * nextn!(meta, y_bit_acc, -1))
+                    * fixed_curr!(meta, sel_bound),
+                (curr!(meta, x_bit_acc) - curr!(meta, is_final) * (bound.clone() - x_top_limb))
+                    * fixed_curr!(meta, sel_last_bit),
+                (curr!(meta, y_bit_acc) - curr!(meta, is_final) * (bound - y_top_limb))
+                    * fixed_curr!(meta, sel_last_bit),
+            ]
+        });
+
+        meta.create_gate("babyjubjub helper: calls", |meta| {
+            let limb = curr!(meta, limb);
+
+            let is_new_call = fixed_curr!(meta, sel_slot) * curr!(meta, is_new);
+            let is_push_call = fixed_curr!(meta, sel_push) * curr!(meta, enable);
+            let is_finalize_call = fixed_curr!(meta, sel_finalize) * curr!(meta, is_final);
+
+            let call_enable_expr =
+                is_new_call.clone() + is_push_call.clone() + is_finalize_call.clone();
+            // `babyjubjub_new` takes no argument.
+            let call_encoded_expr = is_new_call * tag(Op::New)
+                + is_push_call * (tag(Op::Push) + limb.clone())
+                + is_finalize_call * (tag(Op::Finalize) + limb);
+
+            let is_first_row =
+                fixed_curr!(meta, sel_slot) * (constant_from!(1) - fixed_curr!(meta, sel_chain));
+
+            vec![
+                curr!(meta, call_enable) - call_enable_expr,
+                curr!(meta, call_encoded) - call_encoded_expr,
+                curr!(meta, lookup_index) - curr!(meta, call_enable) * curr!(meta, call_index),
+                is_first_row.clone() * curr!(meta, call_index),
+                (constant_from!(1) - is_first_row)
+                    * (curr!(meta, call_index)
+                        - nextn!(meta, call_index, -1)
+                        - nextn!(meta, call_enable, -1)),
+            ]
+            .into_iter()
+            .map(|expr| expr * fixed_curr!(meta, sel))
+            .collect::<Vec<_>>()
+        });
+
+        BabyJubjubHelperTableConfig {
+            sel,
+            sel_slot,
+            sel_chain,
+            sel_push,
+            sel_finalize,
+            sel_first_limb,
+            limb_weight,
+            sel_bit,
+            sel_limb_start,
+            sel_sum,
+            sel_bound,
+            bit_weight,
+            sel_last_bit,
+            enable,
+            is_new,
+            is_final,
+            is_complete,
+            limb,
+            word,
+            bit,
+            limb_acc,
+            px,
+            py,
+            x1,
+            y1,
+            t1,
+            u1,
+            dx,
+            dy,
+            qx,
+            qy,
+            t2,
+            u2,
+            ox,
+            oy,
+            x_bit,
+            x_bit_acc,
+            y_bit,
+            y_bit_acc,
+            call_enable,
+            call_index,
+            lookup_index,
+            call_encoded,
+            _mark: PhantomData,
+        }
+    }
+}
+
+impl<F: FieldExt> ForeignTableConfig<F> for BabyJubjubHelperTableConfig<F> {
+    fn configure_in_table(
+        &self,
+        meta: &mut ConstraintSystem<F>,
+        key: &'static str,
+        expr: &dyn Fn(&mut VirtualCells<'_, F>) -> Vec<Expression<F>>,
+    ) {
+        meta.lookup_any(key, |meta| {
+            let mut exprs = expr(meta);
+
+            vec![
+                (exprs.remove(0), curr!(meta, self.lookup_index)),
+                (exprs.remove(0), curr!(meta, self.call_encoded)),
+            ]
+        });
+    }
+}
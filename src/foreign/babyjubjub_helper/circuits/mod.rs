@@ -0,0 +1,130 @@
+//! The Baby Jubjub table.
+//!
+//! The table is a sequence of slots of `SLOT_ROWS` rows, a slot adds a term to a sum.
+//! Calls of the guest are laid out in the slots, a call on a row with its limb in `limb`:
+//! `babyjubjub_new` on the first row of the slot starting a sum, the pushes of the term on
+//! the following rows, and the calls of `babyjubjub_finalize` on the following ones for the
+//! last term of a sum. The limbs of each element are composed in `word`, the point of the
+//! term is `px` and `py` on every row of the slot.
+//!
+//! The multiple of the point is computed by double-and-add on the first `SCALAR_BITS` rows,
+//! a row per bit of the scalar from the most significant one: the accumulator of the
+//! previous row `(x1, y1)` is doubled into `(dx, dy)`, `(qx, qy)` is the point if the bit is
+//! set and the identity otherwise, and their sum is `(ox, oy)`. The bits are composed into
+//! the limbs of the scalar in `limb_acc`. On the last row of the slot, the multiple is added
+//! to the sum of the previous slot, or to the identity for a new sum, the sum of a final
+//! slot is the one read by the calls of `babyjubjub_finalize`. Both additions are by the
+//! complete addition law, whose denominators never vanish for points of the curve.
+//!
+//! The point of a slot is on the curve, but for the last enabled slot if its term is only
+//! partly pushed at the end of the execution, as its sum is never read. The limbs left to
+//! push are then 0, so that a point that is not fully pushed has the scalar 0 and is never
+//! added, and a point that is is on the curve, as checked by the host.
+//!
+//! The limbs of a coordinate of a sum are unique as long as its top limb is below the top
+//! limb of the modulus, the bits of the differences are laid out over the first rows of the
+//! slot in `x_bit` and `y_bit`. A sum with the top limb of the modulus, a chance of 2^-64,
+//! cannot be proven.
+//!
+//! Calls are numbered in the order of the table, the event table looks up each call with
+//! its number among the calls of the plugin, so the calls of the table are exactly the
+//! calls of the execution.
+
+use std::marker::PhantomData;
+
+use halo2_proofs::arithmetic::FieldExt;
+use halo2_proofs::plonk::Advice;
+use halo2_proofs::plonk::Column;
+use halo2_proofs::plonk::Fixed;
+
+use crate::foreign::poseidon_helper::LIMBS;
+
+use super::FINALIZE_CALLS;
+use super::PUSH_CALLS;
+use super::SCALAR_BITS;
+pub(crate) use crate::foreign::stream_helper::encode_call;
+
+pub mod assign;
+pub mod config;
+
+pub const BABYJUBJUB_FOREIGN_TABLE_KEY: &'static str = "babyjubjub-helper-table";
+
+const LIMB_BITS: usize = 64;
+const PUSH_ROW: usize = 1;
+/// The row the point of the term is composed on, its `y` follows `LIMBS` rows after.
+const POINT_ROW: usize = PUSH_ROW + LIMBS - 1;
+/// The row of the first limb of the scalar.
+const SCALAR_ROW: usize = PUSH_ROW + 2 * LIMBS;
+const FINALIZE_ROW: usize = PUSH_ROW + PUSH_CALLS;
+/// The row the `x` of the sum is composed on, its `y` follows `LIMBS` rows after.
+const SUM_WORD_ROW: usize = FINALIZE_ROW + LIMBS - 1;
+const END_CALL_ROW: usize = FINALIZE_ROW + FINALIZE_CALLS;
+/// The row adding the multiple of the point to the sum.
+const SUM_ROW: usize = SCALAR_BITS;
+const SLOT_ROWS: usize = SCALAR_BITS + 1;
+
+#[derive(Clone)]
+pub struct BabyJubjubHelperTableConfig<F: FieldExt> {
+    /// Enables the rows of the slots.
+    sel: Column<Fixed>,
+    /// Enables the first row of each slot.
+    sel_slot: Column<Fixed>,
+    /// Enables the first row of each slot but the first one.
+    sel_chain: Column<Fixed>,
+    sel_push: Column<Fixed>,
+    sel_finalize: Column<Fixed>,
+    /// Enables the rows of the limb 0 of an element.
+    sel_first_limb: Column<Fixed>,
+    limb_weight: Column<Fixed>,
+    /// Enables the rows of the bits of the scalar.
+    sel_bit: Column<Fixed>,
+    /// Enables the rows of the most significant bit of each limb of the scalar.
+    sel_limb_start: Column<Fixed>,
+    sel_sum: Column<Fixed>,
+    /// Enables the rows of the bits of the bounds of a sum.
+    sel_bound: Column<Fixed>,
+    bit_weight: Column<Fixed>,
+    sel_last_bit: Column<Fixed>,
+
+    enable: Column<Advice>,
+    is_new: Column<Advice>,
+    is_final: Column<Advice>,
+    /// Whether the term of the slot is fully pushed.
+    is_complete: Column<Advice>,
+
+    /// The value of the call of the row.
+    limb: Column<Advice>,
+    word: Column<Advice>,
+    bit: Column<Advice>,
+    limb_acc: Column<Advice>,
+    px: Column<Advice>,
+    py: Column<Advice>,
+
+    x1: Column<Advice>,
+    y1: Column<Advice>,
+    /// `x1^2` and `y1^2` on the rows of the bits, `px^2` and `py^2` on the last row.
+    t1: Column<Advice>,
+    u1: Column<Advice>,
+    dx: Column<Advice>,
+    dy: Column<Advice>,
+    qx: Column<Advice>,
+    qy: Column<Advice>,
+    /// `dx * qx` and `dy * qy`.
+    t2: Column<Advice>,
+    u2: Column<Advice>,
+    ox: Column<Advice>,
+    oy: Column<Advice>,
+
+    x_bit: Column<Advice>,
+    x_bit_acc: Column<Advice>,
+    y_bit: Column<Advice>,
+    y_bit_acc: Column<Advice>,
+
+    call_enable: Column<Advice>,
+    /// The number of the calls preceding the row.
+    call_index: Column<Advice>,
+    lookup_index: Column<Advice>,
+    call_encoded: Column<Advice>,
+
+    _mark: PhantomData<F>,
+}
@@ -0,0 +1,24 @@
+use specs::host_function::HostPlugin;
+
+use super::circuits::BABYJUBJUB_FOREIGN_TABLE_KEY;
+
+use crate::foreign::stream_helper::ETableStreamHelperTableConfigBuilder;
+use crate::foreign::stream_helper::StreamPlugin;
+
+pub(crate) struct BabyJubjubPlugin;
+
+impl StreamPlugin for BabyJubjubPlugin {
+    const PLUGIN: HostPlugin = HostPlugin::BabyJubjub;
+    const TABLE_KEY: &'static str = BABYJUBJUB_FOREIGN_TABLE_KEY;
+    const NEW_TAKES_VALUE: bool = false;
+
+    const STACK_READ: &'static str = "babyjubjub stack read";
+    const STACK_WRITE: &'static str = "babyjubjub stack write";
+    const SEL: &'static str = "babyjubjub_helper: sel";
+    const NEW_VALUE: &'static str = "babyjubjub_helper: new takes no value";
+    const LOOKUP_AUX: &'static str = "babyjubjub_helper: lookup aux";
+    const LOOKUP: &'static str = "lookup babyjubjub table";
+}
+
+pub(crate) type ETableBabyJubjubHelperTableConfigBuilder =
+    ETableStreamHelperTableConfigBuilder<BabyJubjubPlugin>;
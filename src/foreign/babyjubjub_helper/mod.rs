@@ -0,0 +1,144 @@
+//! Sums of multiples of Baby Jubjub points computed by the host.
+//!
+//! Baby Jubjub is the twisted Edwards curve `A * x^2 + y^2 = 1 + D * x^2 * y^2` over the
+//! scalar field of BN254, so its arithmetic is native to the circuit. A guest computes
+//! `k_0 * P_0 + ... + k_n * P_n`: it starts a sum with `babyjubjub_new`, pushes each term
+//! with `babyjubjub_push`, the coordinates of the point then the scalar, each one as 4
+//! little-endian limbs of 64 bits, then reads the sum, its coordinates the same way, with
+//! 8 calls of `babyjubjub_finalize`. A point addition is a sum of 2 terms with the scalar
+//! 1, a scalar multiplication is a sum of a single term. The event table looks each call up
+//! in the Baby Jubjub table, which proves the sum, see `circuits`.
+//!
+//! A scalar is any integer below 2^256. The sum of the points of the whole curve is
+//! computed, a guest checking a signature checks that its points are in the subgroup.
+
+use halo2_proofs::arithmetic::FieldExt;
+use specs::etable::EventTableEntry;
+use specs::host_function::HostPlugin;
+use specs::step::StepInfo;
+
+use crate::foreign::poseidon_helper::compose;
+use crate::foreign::poseidon_helper::LIMBS;
+
+pub mod circuits;
+pub mod etable_op_configure;
+pub mod runtime;
+pub mod test;
+
+pub(crate) use crate::foreign::stream_helper::Op;
+
+/// Calls of `babyjubjub_push` pushing a term, the coordinates of its point and its scalar.
+pub(crate) const PUSH_CALLS: usize = 3 * LIMBS;
+/// Calls of `babyjubjub_finalize` reading the coordinates of a sum.
+pub(crate) const FINALIZE_CALLS: usize = 2 * LIMBS;
+pub(crate) const SCALAR_BITS: usize = 64 * LIMBS;
+
+/// The coefficients of the curve.
+pub(crate) const A: u64 = 168700;
+pub(crate) const D: u64 = 168696;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct Point<F: FieldExt> {
+    pub(crate) x: F,
+    pub(crate) y: F,
+}
+
+impl<F: FieldExt> Point<F> {
+    pub(crate) fn identity() -> Self {
+        Point {
+            x: F::zero(),
+            y: F::one(),
+        }
+    }
+
+    pub(crate) fn is_on_curve(&self) -> bool {
+        let x2 = self.x.square();
+        let y2 = self.y.square();
+
+        F::from(A) * x2 + y2 == F::one() + F::from(D) * x2 * y2
+    }
+
+    /// The sum of `self` and `other`, by the complete addition law of the curve. The sum is
+    /// `None` for points out of the curve whose sum is not defined.
+    pub(crate) fn add(&self, other: &Self) -> Option<Self> {
+        let t = self.x * other.x;
+        let u = self.y * other.y;
+        let dtu = F::from(D) * t * u;
+
+        let x =
+            (self.x * other.y + self.y * other.x) * Option::<F>::from((F::one() + dtu).invert())?;
+        let y = (u - F::from(A) * t) * Option::<F>::from((F::one() - dtu).invert())?;
+
+        Some(Point { x, y })
+    }
+
+    /// The accumulators of `scalar * self` by double-and-add from the most significant
+    /// bit of the little-endian limbs `scalar`, after each bit.
+    pub(crate) fn mul_steps(&self, scalar: &[u64; LIMBS]) -> Option<Vec<Self>> {
+        let mut acc = Point::identity();
+        let mut steps = vec![];
+
+        for bit in scalar_bits(scalar) {
+            acc = acc.add(&acc)?;
+            if bit {
+                acc = acc.add(self)?;
+            }
+
+            steps.push(acc);
+        }
+
+        Some(steps)
+    }
+}
+
+/// The point of the limbs `term` pushed by a guest, its coordinates composed from the first
+/// `2 * LIMBS` limbs.
+pub(crate) fn term_point<F: FieldExt>(term: &[u64]) -> Point<F> {
+    Point {
+        x: compose(&term[0..LIMBS]),
+        y: compose(&term[LIMBS..2 * LIMBS]),
+    }
+}
+
+/// The bits of the little-endian limbs `scalar`, the most significant first.
+pub(crate) fn scalar_bits(scalar: &[u64; LIMBS]) -> Vec<bool> {
+    (0..SCALAR_BITS)
+        .rev()
+        .map(|bit| (scalar[bit / 64] >> (bit % 64)) & 1 == 1)
+        .collect()
+}
+
+/// Whether a sum is in progress before each step of `entries` and after the last one.
+///
+/// The table of a circuit proves the sums started by its own steps, so an execution is not
+/// split into segments while a sum is in progress.
+pub(crate) fn sum_in_progress(entries: &[EventTableEntry]) -> Vec<bool> {
+    let mut in_progress = false;
+    let mut finalize_calls = 0;
+
+    let mut status = vec![false];
+    for entry in entries {
+        if let StepInfo::CallHost {
+            plugin: HostPlugin::BabyJubjub,
+            op_index_in_plugin,
+            ..
+        } = &entry.step_info
+        {
+            match Op::from_index(*op_index_in_plugin) {
+                Op::New => in_progress = true,
+                Op::Push => (),
+                Op::Finalize => {
+                    finalize_calls += 1;
+                    if finalize_calls == FINALIZE_CALLS {
+                        finalize_calls = 0;
+                        in_progress = false;
+                    }
+                }
+            }
+        }
+
+        status.push(in_progress);
+    }
+
+    status
+}
@@ -0,0 +1,183 @@
+use std::rc::Rc;
+
+use halo2_proofs::pairing::bn256::Fr;
+use specs::host_function::HostPlugin;
+use specs::types::ValueType;
+
+use crate::foreign::poseidon_helper::decompose;
+use crate::foreign::poseidon_helper::LIMBS;
+use crate::runtime::host::host_env::HostEnv;
+use crate::runtime::host::ForeignContext;
+
+use super::term_point;
+use super::Op;
+use super::Point;
+use super::PUSH_CALLS;
+
+struct Context {
+    /// The sum in progress, until it is finalized.
+    sum: Option<Point<Fr>>,
+    terms: usize,
+    term: Vec<u64>,
+    /// The limbs of the finalized sum left to read.
+    result: Vec<u64>,
+    error: Option<String>,
+}
+
+impl Context {
+    fn new() -> Self {
+        Context {
+            sum: None,
+            terms: 0,
+            term: vec![],
+            result: vec![],
+            error: None,
+        }
+    }
+
+    fn is_in_progress(&self) -> bool {
+        self.sum.is_some() || !self.result.is_empty()
+    }
+
+    fn new_sum(&mut self) {
+        if self.is_in_progress() {
+            self.error =
+                Some("babyjubjub_new is called while the previous sum is in progress".to_owned());
+
+            return;
+        }
+
+        self.sum = Some(Point::identity());
+        self.terms = 0;
+    }
+
+    fn push(&mut self, value: u64) {
+        let sum = match self.sum.as_mut() {
+            Some(sum) => sum,
+            None => {
+                self.error = Some("babyjubjub_push is called without a sum in progress".to_owned());
+
+                return;
+            }
+        };
+
+        self.term.push(value);
+
+        // The point is checked as soon as it is pushed, see `circuits`.
+        if self.term.len() == 2 * LIMBS && !term_point::<Fr>(&self.term).is_on_curve() {
+            self.error = Some("babyjubjub_push is given a point out of the curve".to_owned());
+
+            return;
+        }
+
+        if self.term.len() == PUSH_CALLS {
+            let point = term_point(&self.term);
+            let scalar = self.term[2 * LIMBS..].try_into().unwrap();
+            let multiple = *point.mul_steps(&scalar).unwrap().last().unwrap();
+            *sum = sum.add(&multiple).unwrap();
+
+            self.terms += 1;
+            self.term.clear();
+        }
+    }
+
+    /// The next limb of the sum, or `None` with the error of the context set if there is no
+    /// sum to read.
+    fn finalize(&mut self) -> Option<u64> {
+        if let Some(sum) = self.sum.take() {
+            if self.terms == 0 || !self.term.is_empty() {
+                self.error = Some(
+                    "babyjubjub_finalize is called on a sum that is not made of whole terms"
+                        .to_owned(),
+                );
+
+                return None;
+            }
+
+            self.result = [decompose(&sum.x), decompose(&sum.y)].concat();
+        }
+
+        if self.result.is_empty() {
+            self.error = Some("babyjubjub_finalize is called without a sum in progress".to_owned());
+
+            return None;
+        }
+
+        Some(self.result.remove(0))
+    }
+}
+
+impl ForeignContext for Context {
+    fn take_error(&mut self) -> Option<String> {
+        self.error.take()
+    }
+}
+
+pub fn register_babyjubjub_foreign(env: &mut HostEnv) {
+    let babyjubjub_new = Rc::new(
+        |context: &mut dyn ForeignContext, _args: wasmi::RuntimeArgs| {
+            let context = context.downcast_mut::<Context>().unwrap();
+
+            context.new_sum();
+
+            None
+        },
+    );
+
+    let babyjubjub_push = Rc::new(
+        |context: &mut dyn ForeignContext, args: wasmi::RuntimeArgs| {
+            let context = context.downcast_mut::<Context>().unwrap();
+
+            let value: i64 = args.nth(0);
+            context.push(value as u64);
+
+            None
+        },
+    );
+
+    let babyjubjub_finalize = Rc::new(
+        |context: &mut dyn ForeignContext, _args: wasmi::RuntimeArgs| {
+            let context = context.downcast_mut::<Context>().unwrap();
+
+            context
+                .finalize()
+                .map(|value| wasmi::RuntimeValue::I64(value as i64))
+        },
+    );
+
+    env.internal_env
+        .register_plugin(HostPlugin::BabyJubjub, Box::new(Context::new()));
+
+    env.internal_env.register_function(
+        "babyjubjub_new",
+        specs::host_function::Signature {
+            params: vec![],
+            return_types: vec![],
+        },
+        HostPlugin::BabyJubjub,
+        Op::New as usize,
+        babyjubjub_new,
+    );
+
+    env.internal_env.register_function(
+        "babyjubjub_push",
+        specs::host_function::Signature {
+            params: vec![ValueType::I64],
+            return_types: vec![],
+        },
+        HostPlugin::BabyJubjub,
+        Op::Push as usize,
+        babyjubjub_push,
+    );
+
+    env.internal_env.register_function(
+        "babyjubjub_finalize",
+        specs::host_function::Signature {
+            params: vec![],
+            return_types: vec![ValueType::I64],
+        },
+        HostPlugin::BabyJubjub,
+        Op::Finalize as usize,
+        babyjubjub_finalize,
+    );
+}
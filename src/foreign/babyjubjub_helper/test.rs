@@ -0,0 +1,197 @@
+#[cfg(test)]
+mod tests {
+    use crate::circuits::utils::bn_to_field;
+    use crate::foreign::babyjubjub_helper::runtime::register_babyjubjub_foreign;
+    use crate::foreign::babyjubjub_helper::Point;
+    use crate::foreign::poseidon_helper::decompose;
+    use crate::foreign::require_helper::register_require_foreign;
+    use crate::foreign::wasm_input_helper::runtime::register_wasm_input_foreign;
+    use crate::runtime::host::host_env::HostEnv;
+    use crate::test::test_circuit_with_env;
+    use halo2_proofs::pairing::bn256::Fr;
+    use num_bigint::BigUint;
+
+    /// Computes `sums` sums given as private inputs, the number of terms of each one and the
+    /// limbs of the terms, and requires the coordinates of each sum to be the next 8 public
+    /// inputs.
+    fn guest(sums: usize) -> String {
+        format!(
+            r#"
+        (module
+            (import "env" "wasm_input" (func $wasm_input (param i32) (result i64)))
+            (import "env" "require" (func $require (param i32)))
+            (import "env" "babyjubjub_new" (func $babyjubjub_new))
+            (import "env" "babyjubjub_push" (func $babyjubjub_push (param i64)))
+            (import "env" "babyjubjub_finalize" (func $babyjubjub_finalize (result i64)))
+            (export "main" (func $main))
+            (func $sum (; 5 ;)
+                (local $limbs i64)
+                (call $babyjubjub_new)
+                (local.set $limbs (i64.mul (call $wasm_input (i32.const 0)) (i64.const 12)))
+                (block
+                    (loop
+                        (br_if 1 (i64.eqz (local.get $limbs)))
+                        (call $babyjubjub_push (call $wasm_input (i32.const 0)))
+                        (local.set $limbs (i64.sub (local.get $limbs) (i64.const 1)))
+                        (br 0)
+                    )
+                )
+                (local.set $limbs (i64.const 8))
+                (block
+                    (loop
+                        (br_if 1 (i64.eqz (local.get $limbs)))
+                        (call $require (i64.eq (call $babyjubjub_finalize) (call $wasm_input (i32.const 1))))
+                        (local.set $limbs (i64.sub (local.get $limbs) (i64.const 1)))
+                        (br 0)
+                    )
+                )
+            )
+            (func $main (; 6 ;)
+                (local $sums i32)
+                (local.set $sums (i32.const {}))
+                (block
+                    (loop
+                        (br_if 1 (i32.eqz (local.get $sums)))
+                        (call $sum)
+                        (local.set $sums (i32.sub (local.get $sums) (i32.const 1)))
+                        (br 0)
+                    )
+                )
+            )
+        )
+    "#,
+            sums
+        )
+    }
+
+    /// The base point of the prime order subgroup.
+    fn base_point() -> Point<Fr> {
+        let coordinate =
+            |decimal: &str| bn_to_field(&BigUint::parse_bytes(decimal.as_bytes(), 10).unwrap());
+
+        let point = Point {
+            x: coordinate(
+                "5299619240641551281634865583518297030282874472190772894086521144482721001553",
+            ),
+            y: coordinate(
+                "16950150798460657717958625567821834550301663161624707787222815936182638968203",
+            ),
+        };
+        assert!(point.is_on_curve());
+
+        point
+    }
+
+    fn mul(point: &Point<Fr>, scalar: [u64; 4]) -> Point<Fr> {
+        *point.mul_steps(&scalar).unwrap().last().unwrap()
+    }
+
+    fn sum(terms: &[(Point<Fr>, [u64; 4])]) -> Point<Fr> {
+        terms
+            .iter()
+            .fold(Point::identity(), |sum, (point, scalar)| {
+                sum.add(&mul(point, *scalar)).unwrap()
+            })
+    }
+
+    fn test_babyjubjub_plugin(sums: &[Vec<(Point<Fr>, [u64; 4])>]) {
+        let mut private_inputs = vec![];
+        let mut public_inputs = vec![];
+        for terms in sums {
+            private_inputs.push(terms.len() as u64);
+            for (point, scalar) in terms {
+                private_inputs.extend(decompose(&point.x));
+                private_inputs.extend(decompose(&point.y));
+                private_inputs.extend(scalar);
+            }
+
+            let sum = sum(terms);
+            public_inputs.extend(decompose(&sum.x));
+            public_inputs.extend(decompose(&sum.y));
+        }
+
+        let wasm = wabt::wat2wasm(&guest(sums.len())).expect("failed to parse wat");
+
+        let mut env = HostEnv::new();
+        let wasm_runtime_io = register_wasm_input_foreign(&mut env, public_inputs, private_inputs);
+        register_require_foreign(&mut env);
+        register_babyjubjub_foreign(&mut env);
+        env.finalize();
+
+        test_circuit_with_env(env, wasm_runtime_io, wasm, "main").unwrap();
+    }
+
+    #[test]
+    fn test_babyjubjub_plugin_add() {
+        let base = base_point();
+        let double = base.add(&base).unwrap();
+        assert_eq!(mul(&base, [2, 0, 0, 0]), double);
+
+        test_babyjubjub_plugin(&[vec![(base, [1, 0, 0, 0]), (double, [1, 0, 0, 0])]]);
+    }
+
+    #[test]
+    fn test_babyjubjub_plugin_mul() {
+        let base = base_point();
+
+        test_babyjubjub_plugin(&[
+            vec![(base, [u64::MAX, 3, 0, 1 << 63])],
+            vec![(base, [0, 0, 0, 0])],
+        ]);
+    }
+
+    #[test]
+    fn test_babyjubjub_plugin_msm() {
+        let base = base_point();
+        let points = [base, mul(&base, [7, 0, 0, 0]), mul(&base, [0, 0, 5, 0])];
+
+        test_babyjubjub_plugin(&[
+            vec![
+                (points[0], [11, 1, 0, 0]),
+                (points[1], [u64::MAX, u64::MAX, u64::MAX, u64::MAX]),
+                (points[2], [3, 0, 0, 0]),
+            ],
+            vec![(points[2], [1, 2, 3, 4])],
+        ]);
+    }
+
+    /// Misuses of the plugin trap the execution instead of panicking the host.
+    #[test]
+    fn test_babyjubjub_plugin_misuse() {
+        // (0, 0) is out of the curve.
+        let off_curve = format!(
+            "(call $babyjubjub_new) {}",
+            "(call $babyjubjub_push (i64.const 0)) ".repeat(8)
+        );
+
+        for body in [
+            "(call $babyjubjub_push (i64.const 0))".to_owned(),
+            "(drop (call $babyjubjub_finalize))".to_owned(),
+            "(call $babyjubjub_new) (call $babyjubjub_new)".to_owned(),
+            "(call $babyjubjub_new) (call $babyjubjub_push (i64.const 0)) (drop (call $babyjubjub_finalize))".to_owned(),
+            off_curve,
+        ] {
+            let wasm = wabt::wat2wasm(format!(
+                r#"
+            (module
+                (import "env" "babyjubjub_new" (func $babyjubjub_new))
+                (import "env" "babyjubjub_push" (func $babyjubjub_push (param i64)))
+                (import "env" "babyjubjub_finalize" (func $babyjubjub_finalize (result i64)))
+                (func (export "main")
+                    {}
+                )
+            )
+            "#,
+                body
+            ))
+            .expect("failed to parse wat");
+
+            let mut env = HostEnv::new();
+            let wasm_runtime_io = register_wasm_input_foreign(&mut env, vec![], vec![]);
+            register_babyjubjub_foreign(&mut env);
+            env.finalize();
+
+            assert!(test_circuit_with_env(env, wasm_runtime_io, wasm, "main").is_err());
+        }
+    }
+}
@@ -0,0 +1,86 @@
+//! The lookup of a call of a plugin in the table of the plugin.
+//!
+//! A call is looked up with its number among the calls of its plugin, the foreign call
+//! index of the plugin, and its encoding, which each plugin defines. The table numbers its
+//! calls the same way, so the calls of the table are exactly the calls of the execution.
+
+use halo2_proofs::arithmetic::FieldExt;
+use halo2_proofs::plonk::Error;
+use halo2_proofs::plonk::Expression;
+use halo2_proofs::plonk::VirtualCells;
+use specs::host_function::HostPlugin;
+
+use crate::circuits::cell::AllocatedUnlimitedCell;
+use crate::circuits::cell::CellExpression;
+use crate::circuits::etable::constraint_builder::ConstraintBuilder;
+use crate::circuits::etable::EventTableCommonConfig;
+use crate::circuits::utils::step_status::StepStatus;
+use crate::circuits::utils::Context;
+
+pub(crate) struct ForeignCallLookupConfig<F: FieldExt> {
+    plugin: HostPlugin,
+    call_index_for_lookup: AllocatedUnlimitedCell<F>,
+    call_encoded_for_lookup: AllocatedUnlimitedCell<F>,
+}
+
+impl<F: FieldExt> ForeignCallLookupConfig<F> {
+    /// Looks the calls of `plugin` up in the table of `table_key`, `encoded` is the
+    /// encoding of the call of a step.
+    pub(crate) fn configure(
+        plugin: HostPlugin,
+        table_key: &'static str,
+        names: (&'static str, &'static str),
+        common_config: &EventTableCommonConfig<F>,
+        constraint_builder: &mut ConstraintBuilder<F>,
+        lookup_cells: &mut (impl Iterator<Item = AllocatedUnlimitedCell<F>> + Clone),
+        encoded: impl Fn(&mut VirtualCells<'_, F>) -> Expression<F> + 'static,
+    ) -> Self {
+        let (aux_name, lookup_name) = names;
+        let call_index = common_config.foreign_call_index_cells[&plugin];
+
+        let call_index_for_lookup = lookup_cells.next().unwrap();
+        let call_encoded_for_lookup = lookup_cells.next().unwrap();
+
+        constraint_builder.push(
+            aux_name,
+            Box::new(move |meta| {
+                vec![
+                    call_index_for_lookup.expr(meta) - call_index.expr(meta),
+                    call_encoded_for_lookup.expr(meta) - encoded(meta),
+                ]
+            }),
+        );
+
+        constraint_builder.lookup(
+            table_key,
+            lookup_name,
+            Box::new(move |meta| {
+                vec![
+                    call_index_for_lookup.expr(meta),
+                    call_encoded_for_lookup.expr(meta),
+                ]
+            }),
+        );
+
+        ForeignCallLookupConfig {
+            plugin,
+            call_index_for_lookup,
+            call_encoded_for_lookup,
+        }
+    }
+
+    pub(crate) fn assign(
+        &self,
+        ctx: &mut Context<'_, F>,
+        step: &StepStatus,
+        encoded: F,
+    ) -> Result<(), Error> {
+        let call_index = step.foreign_call_indexes[&self.plugin];
+
+        self.call_index_for_lookup
+            .assign(ctx, F::from(call_index as u64))?;
+        self.call_encoded_for_lookup.assign(ctx, encoded)?;
+
+        Ok(())
+    }
+}
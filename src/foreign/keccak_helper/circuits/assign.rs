@@ -0,0 +1,368 @@
+use halo2_proofs::arithmetic::FieldExt;
+use halo2_proofs::circuit::Layouter;
+use halo2_proofs::circuit::Region;
+use halo2_proofs::plonk::Advice;
+use halo2_proofs::plonk::Column;
+use halo2_proofs::plonk::Error;
+use specs::etable::EventTableEntry;
+use specs::step::StepInfo;
+
+use super::encode_call;
+use super::KeccakHelperTableConfig;
+use super::LANE_BITS;
+use super::OUTPUT_ROW;
+use super::ROUND_ROWS;
+use super::SLOT_ROWS;
+use crate::foreign::keccak_helper::absorb;
+use crate::foreign::keccak_helper::Op;
+use crate::foreign::keccak_helper::Permutation;
+use crate::foreign::keccak_helper::FINALIZE_CALLS;
+use crate::foreign::keccak_helper::LANES;
+use crate::foreign::keccak_helper::RATE_LANES;
+use crate::foreign::keccak_helper::ROUND_CONSTANTS;
+
+struct Slot {
+    is_new: bool,
+    is_final: bool,
+    state: [u64; LANES],
+    block: [u64; RATE_LANES],
+}
+
+/// The rows of an entry of the table, a permuted block.
+pub const ENTRY_ROWS: usize = SLOT_ROWS;
+
+/// The blocks the calls of `entries` absorb.
+pub(crate) fn table_entries(entries: &[EventTableEntry]) -> usize {
+    slots(entries).len()
+}
+
+/// The slots of the calls of `entries`, the steps of the plugin in the order of execution.
+fn slots(entries: &[EventTableEntry]) -> Vec<Slot> {
+    let mut slots: Vec<Slot> = vec![];
+
+    let mut state = [0; LANES];
+    let mut is_new = false;
+    let mut block = vec![];
+
+    for entry in entries {
+        match &entry.step_info {
+            StepInfo::CallHost {
+                op_index_in_plugin,
+                args,
+                ..
+            } => match Op::from_index(*op_index_in_plugin) {
+                Op::New => {
+                    state = [0; LANES];
+                    is_new = true;
+                }
+                Op::Push => {
+                    block.push(args[0]);
+
+                    if block.len() == RATE_LANES {
+                        let block: [u64; RATE_LANES] =
+                            std::mem::take(&mut block).try_into().unwrap();
+
+                        slots.push(Slot {
+                            is_new,
+                            is_final: false,
+                            state,
+                            block,
+                        });
+
+                        state = Permutation::new(&absorb(&state, &block)).output;
+                        is_new = false;
+                    }
+                }
+                // The calls of `keccak_finalize` read the output of the last block.
+                Op::Finalize => slots.last_mut().unwrap().is_final = true,
+            },
+            _ => unreachable!(),
+        }
+    }
+
+    // The calls of the last block may follow the last step, they are left out of the
+    // lookups of the event table.
+    if is_new || !block.is_empty() {
+        block.resize(RATE_LANES, 0);
+
+        slots.push(Slot {
+            is_new,
+            is_final: false,
+            state,
+            block: block.try_into().unwrap(),
+        });
+    }
+
+    slots
+}
+
+pub struct KeccakHelperTableChip<F: FieldExt> {
+    config: KeccakHelperTableConfig<F>,
+    max_available_rows: usize,
+}
+
+impl<F: FieldExt> KeccakHelperTableChip<F> {
+    pub fn new(config: KeccakHelperTableConfig<F>, max_available_rows: usize) -> Self {
+        Self {
+            config,
+            max_available_rows,
+        }
+    }
+
+    fn assign_fixed(&self, region: &mut Region<'_, F>, slot: usize) -> Result<(), Error> {
+        let mut assign = |name, column, row: usize, value: F| {
+            region.assign_fixed(
+                || format!("keccak helper {}", name),
+                column,
+                slot * SLOT_ROWS + row,
+                || Ok(value),
+            )
+        };
+
+        for row in 0..SLOT_ROWS {
+            assign("sel", self.config.sel, row, F::one())?;
+        }
+
+        assign("sel slot", self.config.sel_slot, 0, F::one())?;
+
+        for z in 0..LANE_BITS {
+            let weight = F::from(1u64 << z);
+
+            assign("sel absorb", self.config.sel_absorb, z, F::one())?;
+            assign("bit weight", self.config.bit_weight, z, weight)?;
+            assign(
+                "sel output",
+                self.config.sel_output,
+                OUTPUT_ROW + z,
+                F::one(),
+            )?;
+            assign("bit weight", self.config.bit_weight, OUTPUT_ROW + z, weight)?;
+
+            if slot > 0 {
+                assign("sel chain", self.config.sel_chain, z, F::one())?;
+            }
+        }
+
+        for row in [0, OUTPUT_ROW] {
+            assign("sel first bit", self.config.sel_first_bit, row, F::one())?;
+        }
+
+        for (round, constant) in ROUND_CONSTANTS.iter().enumerate() {
+            let start = LANE_BITS + round * ROUND_ROWS;
+
+            for row in start..start + ROUND_ROWS {
+                assign("sel round", self.config.sel_round, row, F::one())?;
+            }
+
+            for z in 0..LANE_BITS {
+                let row = start + LANE_BITS + z;
+
+                assign("sel step", self.config.sel_step, row, F::one())?;
+                assign(
+                    "round constant",
+                    self.config.round_constant,
+                    row,
+                    F::from((constant >> z) & 1),
+                )?;
+            }
+        }
+
+        for (i, sel) in self.config.sel_push.iter().enumerate() {
+            assign("sel push", *sel, 1 + i, F::one())?;
+        }
+
+        for (i, sel) in self.config.sel_finalize.iter().enumerate() {
+            assign("sel finalize", *sel, OUTPUT_ROW + 1 + i, F::one())?;
+        }
+
+        Ok(())
+    }
+
+    /// Assigns the bits of `lanes` to `columns` over the `LANE_BITS` rows from `start`.
+    fn assign_lanes(
+        &self,
+        region: &mut Region<'_, F>,
+        columns: &[Column<Advice>],
+        lanes: &[u64],
+        start: usize,
+    ) -> Result<(), Error> {
+        for (column, lane) in columns.iter().zip(lanes.iter()) {
+            for z in 0..LANE_BITS {
+                region.assign_advice(
+                    || "keccak helper bit",
+                    *column,
+                    start + z,
+                    || Ok(F::from((lane >> z) & 1)),
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Assigns the composition of `lanes` in `acc` over the `LANE_BITS` rows from `start`.
+    fn assign_acc(
+        &self,
+        region: &mut Region<'_, F>,
+        lanes: &[u64],
+        start: usize,
+    ) -> Result<(), Error> {
+        for (column, lane) in self.config.acc.iter().zip(lanes.iter()) {
+            for z in 0..LANE_BITS {
+                let mask = if z == LANE_BITS - 1 {
+                    u64::MAX
+                } else {
+                    (1 << (z + 1)) - 1
+                };
+
+                region.assign_advice(
+                    || "keccak helper acc",
+                    *column,
+                    start + z,
+                    || Ok(F::from(lane & mask)),
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Assigns `slot` as the slot `index`, returns the number of the calls up to the end
+    /// of the slot.
+    fn assign_slot(
+        &self,
+        region: &mut Region<'_, F>,
+        index: usize,
+        slot: &Slot,
+        mut call_index: u64,
+    ) -> Result<u64, Error> {
+        let start = index * SLOT_ROWS;
+
+        let permutation = Permutation::new(&absorb(&slot.state, &slot.block));
+
+        self.assign_lanes(region, &self.config.a, &slot.state, start)?;
+        self.assign_lanes(region, &self.config.b, &slot.block, start)?;
+        self.assign_acc(region, &slot.block, start)?;
+
+        for (round, step) in permutation.rounds.iter().enumerate() {
+            let round_start = start + LANE_BITS + round * ROUND_ROWS;
+
+            for half in [round_start, round_start + LANE_BITS] {
+                self.assign_lanes(region, &self.config.a, &step.a, half)?;
+                self.assign_lanes(region, &self.config.c, &step.c, half)?;
+                self.assign_lanes(region, &self.config.t, &step.t, half)?;
+            }
+
+            let step_start = round_start + LANE_BITS;
+            self.assign_lanes(region, &self.config.b, &step.b, step_start)?;
+            self.assign_lanes(
+                region,
+                &[self.config.iota],
+                &[ROUND_CONSTANTS[round]],
+                step_start,
+            )?;
+        }
+
+        self.assign_lanes(
+            region,
+            &self.config.a,
+            &permutation.output,
+            start + OUTPUT_ROW,
+        )?;
+        self.assign_acc(
+            region,
+            &permutation.output[..FINALIZE_CALLS],
+            start + OUTPUT_ROW,
+        )?;
+
+        for row in 0..SLOT_ROWS {
+            let offset = start + row;
+
+            let mut assign_advice = |name, column, value: F| {
+                region.assign_advice(
+                    || format!("keccak helper {}", name),
+                    column,
+                    offset,
+                    || Ok(value),
+                )
+            };
+
+            assign_advice("enable", self.config.enable, F::one())?;
+            assign_advice("is new", self.config.is_new, F::from(slot.is_new as u64))?;
+            assign_advice(
+                "is final",
+                self.config.is_final,
+                F::from(slot.is_final as u64),
+            )?;
+
+            let call = if row == 0 && slot.is_new {
+                Some((Op::New, 0))
+            } else if (1..=RATE_LANES).contains(&row) {
+                Some((Op::Push, slot.block[row - 1]))
+            } else if (OUTPUT_ROW + 1..=OUTPUT_ROW + FINALIZE_CALLS).contains(&row) && slot.is_final
+            {
+                Some((Op::Finalize, permutation.output[row - OUTPUT_ROW - 1]))
+            } else {
+                None
+            };
+
+            assign_advice("call index", self.config.call_index, F::from(call_index))?;
+
+            if let Some((op, value)) = call {
+                assign_advice("call enable", self.config.call_enable, F::one())?;
+                assign_advice(
+                    "lookup index",
+                    self.config.lookup_index,
+                    F::from(call_index),
+                )?;
+                assign_advice(
+                    "call encoded",
+                    self.config.call_encoded,
+                    encode_call(op, value),
+                )?;
+
+                call_index += 1;
+            }
+        }
+
+        Ok(call_index)
+    }
+
+    /// Assigns the calls of `entries`, the steps of the plugin in the event table.
+    pub fn assign(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        entries: &[EventTableEntry],
+    ) -> Result<(), Error> {
+        let capability = self.max_available_rows / ENTRY_ROWS;
+        let slots = slots(entries);
+        if slots.len() > capability {
+            return Err(Error::Synthesis);
+        }
+
+        layouter.assign_region(
+            || "keccak helper table",
+            |mut region| {
+                for slot in 0..capability {
+                    self.assign_fixed(&mut region, slot)?;
+                }
+
+                let mut call_index = 0;
+                for (index, slot) in slots.iter().enumerate() {
+                    call_index = self.assign_slot(&mut region, index, slot, call_index)?;
+                }
+
+                for offset in slots.len() * SLOT_ROWS..capability * SLOT_ROWS {
+                    region.assign_advice(
+                        || "keccak helper call index",
+                        self.config.call_index,
+                        offset,
+                        || Ok(F::from(call_index)),
+                    )?;
+                }
+
+                Ok(())
+            },
+        )
+    }
+}
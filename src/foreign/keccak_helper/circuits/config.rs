@@ -0,0 +1,325 @@
+use halo2_proofs::arithmetic::FieldExt;
+use halo2_proofs::plonk::ConstraintSystem;
+use halo2_proofs::plonk::Expression;
+use halo2_proofs::plonk::VirtualCells;
+use num_bigint::BigUint;
+
+use crate::circuits::utils::bn_to_field;
+use crate::constant_from;
+use crate::constant_from_bn;
+use crate::curr;
+use crate::fixed_curr;
+use crate::foreign::keccak_helper::pi;
+use crate::foreign::keccak_helper::Op;
+use crate::foreign::keccak_helper::FINALIZE_CALLS;
+use crate::foreign::keccak_helper::LANES;
+use crate::foreign::keccak_helper::RATE_LANES;
+use crate::foreign::keccak_helper::RHO_OFFSETS;
+use crate::foreign::ForeignTableConfig;
+use crate::nextn;
+
+use super::KeccakHelperTableConfig;
+use super::LANE_BITS;
+use super::SLOT_ROWS;
+
+fn xor<F: FieldExt>(x: Expression<F>, y: Expression<F>) -> Expression<F> {
+    x.clone() + y.clone() - constant_from!(2) * x * y
+}
+
+fn tag<F: FieldExt>(op: Op) -> Expression<F> {
+    constant_from_bn!(&(BigUint::from(op.tag()) << 64))
+}
+
+impl<F: FieldExt> KeccakHelperTableConfig<F> {
+    pub fn configure(meta: &mut ConstraintSystem<F>) -> Self {
+        let sel = meta.fixed_column();
+        let sel_slot = meta.fixed_column();
+        let sel_absorb = meta.fixed_column();
+        let sel_chain = meta.fixed_column();
+        let sel_round = meta.fixed_column();
+        let sel_step = meta.fixed_column();
+        let sel_output = meta.fixed_column();
+        let sel_first_bit = meta.fixed_column();
+        let bit_weight = meta.fixed_column();
+        let round_constant = meta.fixed_column();
+        let sel_push = [(); RATE_LANES].map(|_| meta.fixed_column());
+        let sel_finalize = [(); FINALIZE_CALLS].map(|_| meta.fixed_column());
+
+        let enable = meta.advice_column();
+        let is_new = meta.advice_column();
+        let is_final = meta.advice_column();
+
+        let a = [(); LANES].map(|_| meta.advice_column());
+        let c = [(); 5].map(|_| meta.advice_column());
+        let t = [(); LANES].map(|_| meta.advice_column());
+        let b = [(); LANES].map(|_| meta.advice_column());
+        let iota = meta.advice_column();
+        let acc = [(); RATE_LANES].map(|_| meta.advice_column());
+
+        let call_enable = meta.advice_column();
+        let call_index = meta.advice_column();
+        let lookup_index = meta.advice_column();
+        let call_encoded = meta.advice_column();
+
+        let lane_bits = LANE_BITS as i32;
+
+        meta.create_gate("keccak helper: bits", |meta| {
+            a.iter()
+                .chain(c.iter())
+                .chain(t.iter())
+                .chain(b.iter())
+                .map(|bit| curr!(meta, *bit) * (constant_from!(1) - curr!(meta, *bit)))
+                .map(|expr| expr * fixed_curr!(meta, sel))
+                .collect::<Vec<_>>()
+        });
+
+        meta.create_gate("keccak helper: slot", |meta| {
+            let enable_curr = curr!(meta, enable);
+            let is_new_curr = curr!(meta, is_new);
+            let is_final_curr = curr!(meta, is_final);
+            let sel_chain = fixed_curr!(meta, sel_chain);
+
+            vec![
+                enable_curr.clone() * (constant_from!(1) - enable_curr.clone()),
+                is_new_curr.clone() * (constant_from!(1) - is_new_curr.clone()),
+                is_final_curr.clone() * (constant_from!(1) - is_final_curr.clone()),
+                (is_new_curr.clone() + is_final_curr) * (constant_from!(1) - enable_curr.clone()),
+                // The enabled slots precede the others.
+                sel_chain.clone()
+                    * (constant_from!(1) - nextn!(meta, enable, -(SLOT_ROWS as i32)))
+                    * enable_curr.clone(),
+                // A digest continues after a block that is not its last one.
+                sel_chain.clone()
+                    * enable_curr.clone()
+                    * (constant_from!(1) - is_new_curr.clone())
+                    * nextn!(meta, is_final, -(SLOT_ROWS as i32)),
+                (constant_from!(1) - sel_chain) * enable_curr * (constant_from!(1) - is_new_curr),
+            ]
+            .into_iter()
+            .map(|expr| expr * fixed_curr!(meta, sel_slot))
+            .collect::<Vec<_>>()
+        });
+
+        meta.create_gate("keccak helper: slot flags", |meta| {
+            vec![
+                curr!(meta, enable) - nextn!(meta, enable, -1),
+                curr!(meta, is_new) - nextn!(meta, is_new, -1),
+                curr!(meta, is_final) - nextn!(meta, is_final, -1),
+            ]
+            .into_iter()
+            .map(|expr| expr * (fixed_curr!(meta, sel) - fixed_curr!(meta, sel_slot)))
+            .collect::<Vec<_>>()
+        });
+
+        meta.create_gate("keccak helper: absorb", |meta| {
+            let is_new = curr!(meta, is_new);
+            let is_chained = fixed_curr!(meta, sel_chain)
+                * curr!(meta, enable)
+                * (constant_from!(1) - is_new.clone());
+
+            let mut constraints = vec![];
+            for lane in 0..LANES {
+                let state = curr!(meta, a[lane]);
+                let absorbed = if lane < RATE_LANES {
+                    xor(state.clone(), curr!(meta, b[lane]))
+                } else {
+                    state.clone()
+                };
+
+                constraints.push(is_new.clone() * state.clone());
+                // The previous slot ends with its output.
+                constraints.push(is_chained.clone() * (state - nextn!(meta, a[lane], -lane_bits)));
+                constraints.push(nextn!(meta, a[lane], lane_bits) - absorbed);
+            }
+
+            constraints
+                .into_iter()
+                .map(|expr| expr * fixed_curr!(meta, sel_absorb))
+                .collect::<Vec<_>>()
+        });
+
+        meta.create_gate("keccak helper: parity", |meta| {
+            (0..5)
+                .map(|x| {
+                    let sum =
+                        (0..5).fold(constant_from!(0), |sum, y| sum + curr!(meta, a[x + 5 * y]));
+                    let even = sum - curr!(meta, c[x]);
+
+                    even.clone()
+                        * (even.clone() - constant_from!(2))
+                        * (even - constant_from!(4))
+                        * fixed_curr!(meta, sel_round)
+                })
+                .collect::<Vec<_>>()
+        });
+
+        meta.create_gate("keccak helper: round", |meta| {
+            let mut constraints = vec![];
+
+            for lane in 0..LANES {
+                let x = lane % 5;
+
+                // The first half of the rows repeats the state of the round and its theta.
+                constraints.push(curr!(meta, a[lane]) - nextn!(meta, a[lane], -lane_bits));
+                constraints.push(curr!(meta, t[lane]) - nextn!(meta, t[lane], -lane_bits));
+
+                constraints.push(
+                    curr!(meta, t[lane])
+                        - xor(
+                            xor(curr!(meta, a[lane]), curr!(meta, c[(x + 4) % 5])),
+                            nextn!(meta, c[(x + 1) % 5], -1),
+                        ),
+                );
+
+                constraints.push(
+                    curr!(meta, b[pi(lane)]) - nextn!(meta, t[lane], -(RHO_OFFSETS[lane] as i32)),
+                );
+            }
+
+            for lane in 0..LANES {
+                let (x, y) = (lane % 5, lane / 5);
+
+                let chi = xor(
+                    curr!(meta, b[lane]),
+                    (constant_from!(1) - curr!(meta, b[(x + 1) % 5 + 5 * y]))
+                        * curr!(meta, b[(x + 2) % 5 + 5 * y]),
+                );
+                let chi = if lane == 0 {
+                    xor(chi, curr!(meta, iota))
+                } else {
+                    chi
+                };
+
+                constraints.push(nextn!(meta, a[lane], lane_bits) - chi);
+            }
+
+            constraints
+                .push(curr!(meta, iota) - curr!(meta, enable) * fixed_curr!(meta, round_constant));
+
+            constraints
+                .into_iter()
+                .map(|expr| expr * fixed_curr!(meta, sel_step))
+                .collect::<Vec<_>>()
+        });
+
+        meta.create_gate("keccak helper: lanes", |meta| {
+            let is_first_bit = fixed_curr!(meta, sel_first_bit);
+            let weight = fixed_curr!(meta, bit_weight);
+
+            let mut constraints = vec![];
+            for lane in 0..RATE_LANES {
+                let sum = curr!(meta, acc[lane])
+                    - (constant_from!(1) - is_first_bit.clone()) * nextn!(meta, acc[lane], -1);
+
+                constraints.push(
+                    (sum.clone() - curr!(meta, b[lane]) * weight.clone())
+                        * fixed_curr!(meta, sel_absorb),
+                );
+                constraints.push(
+                    (sum - curr!(meta, a[lane]) * weight.clone()) * fixed_curr!(meta, sel_output),
+                );
+            }
+
+            constraints
+        });
+
+        meta.create_gate("keccak helper: calls", |meta| {
+            let enable = curr!(meta, enable);
+            let is_new = curr!(meta, is_new);
+            let is_final = curr!(meta, is_final);
+
+            // A call on the row `1 + i` of the absorption or of the output reads the lane `i`
+            // composed on the last row.
+            let lane = |meta: &mut VirtualCells<'_, F>, i: usize| {
+                nextn!(meta, acc[i], lane_bits - 2 - i as i32)
+            };
+
+            let is_new_call = fixed_curr!(meta, sel_slot) * is_new;
+            let is_push_call = sel_push
+                .iter()
+                .map(|sel| fixed_curr!(meta, *sel) * enable.clone())
+                .collect::<Vec<_>>();
+            let is_finalize_call = sel_finalize
+                .iter()
+                .map(|sel| fixed_curr!(meta, *sel) * is_final.clone())
+                .collect::<Vec<_>>();
+
+            let mut call_enable_expr = is_new_call.clone();
+            let mut call_encoded_expr = is_new_call * tag(Op::New);
+            for (i, is_push_call) in is_push_call.into_iter().enumerate() {
+                call_enable_expr = call_enable_expr + is_push_call.clone();
+                call_encoded_expr =
+                    call_encoded_expr + is_push_call * (tag(Op::Push) + lane(meta, i));
+            }
+            for (i, is_finalize_call) in is_finalize_call.into_iter().enumerate() {
+                call_enable_expr = call_enable_expr + is_finalize_call.clone();
+                call_encoded_expr =
+                    call_encoded_expr + is_finalize_call * (tag(Op::Finalize) + lane(meta, i));
+            }
+
+            let is_first_row =
+                fixed_curr!(meta, sel_slot) * (constant_from!(1) - fixed_curr!(meta, sel_chain));
+
+            vec![
+                curr!(meta, call_enable) - call_enable_expr,
+                curr!(meta, call_encoded) - call_encoded_expr,
+                curr!(meta, lookup_index) - curr!(meta, call_enable) * curr!(meta, call_index),
+                is_first_row.clone() * curr!(meta, call_index),
+                (constant_from!(1) - is_first_row)
+                    * (curr!(meta, call_index)
+                        - nextn!(meta, call_index, -1)
+                        - nextn!(meta, call_enable, -1)),
+            ]
+            .into_iter()
+            .map(|expr| expr * fixed_curr!(meta, sel))
+            .collect::<Vec<_>>()
+        });
+
+        KeccakHelperTableConfig {
+            sel,
+            sel_slot,
+            sel_absorb,
+            sel_chain,
+            sel_round,
+            sel_step,
+            sel_output,
+            sel_first_bit,
+            bit_weight,
+            round_constant,
+            sel_push,
+            sel_finalize,
+            enable,
+            is_new,
+            is_final,
+            a,
+            c,
+            t,
+            b,
+            iota,
+            acc,
+            call_enable,
+            call_index,
+            lookup_index,
+            call_encoded,
+            _mark: std::marker::PhantomData,
+        }
+    }
+}
+
+impl<F: FieldExt> ForeignTableConfig<F> for KeccakHelperTableConfig<F> {
+    fn configure_in_table(
+        &self,
+        meta: &mut ConstraintSystem<F>,
+        key: &'static str,
+        expr: &dyn Fn(&mut VirtualCells<'_, F>) -> Vec<Expression<F>>,
+    ) {
+        meta.lookup_any(key, |meta| {
+            let mut exprs = expr(meta);
+
+            vec![
+                (exprs.remove(0), curr!(meta, self.lookup_index)),
+                (exprs.remove(0), curr!(meta, self.call_encoded)),
+            ]
+        });
+    }
+}
@@ -0,0 +1,91 @@
+//! The Keccak table.
+//!
+//! The table is a sequence of slots of `SLOT_ROWS` rows, a slot permutes the state after
+//! the absorption of a block. Lanes are laid out bit by bit: a lane occupies a column over
+//! `LANE_BITS` rows, the bit `z` in the row `z`, so that rotating a lane is rotating rows
+//! and the steps of a round are the same constraints on each row.
+//!
+//! A slot starts with the rows of the absorption, where `a` is the state the block starts
+//! from and `b` is the block, followed by the rounds, then by the rows of the output, where
+//! `a` is the permuted state. Each round takes `ROUND_ROWS` rows, its state twice over, so
+//! that the bits a rotation wraps around are the rows of the first half seen from the
+//! second one, where the round is constrained: the parities of the columns `c`, the state
+//! `t` following theta, the state `b` following rho and pi, and the state following chi
+//! and iota as `a` of the next round.
+//!
+//! A slot either starts a digest, from the zero state, or continues the digest of the
+//! previous slot, from its output. The calls of the guest are laid out in the slots:
+//! `keccak_new` on the first row of the slot starting a digest, the pushes of the lanes of
+//! the block on the following rows, and the calls of `keccak_finalize` on the output rows
+//! of the last block of a digest. The lanes the calls read are composed in `acc` from
+//! their bits. Calls are numbered in the order of the table, the event table looks up each
+//! call with its number among the calls of the plugin, so the calls of the table are
+//! exactly the calls of the execution.
+
+use std::marker::PhantomData;
+
+use halo2_proofs::arithmetic::FieldExt;
+use halo2_proofs::plonk::Advice;
+use halo2_proofs::plonk::Column;
+use halo2_proofs::plonk::Fixed;
+
+use super::FINALIZE_CALLS;
+use super::LANES;
+use super::RATE_LANES;
+use super::ROUNDS;
+pub(crate) use crate::foreign::stream_helper::encode_call;
+
+pub mod assign;
+pub mod config;
+
+pub const KECCAK_FOREIGN_TABLE_KEY: &'static str = "keccak-helper-table";
+
+const LANE_BITS: usize = 64;
+const ROUND_ROWS: usize = 2 * LANE_BITS;
+/// The first row of the output of a slot.
+const OUTPUT_ROW: usize = LANE_BITS + ROUNDS * ROUND_ROWS;
+const SLOT_ROWS: usize = OUTPUT_ROW + LANE_BITS;
+
+#[derive(Clone)]
+pub struct KeccakHelperTableConfig<F: FieldExt> {
+    /// Enables the rows of the slots.
+    sel: Column<Fixed>,
+    /// Enables the first row of each slot.
+    sel_slot: Column<Fixed>,
+    /// Enables the rows of the absorption of each slot.
+    sel_absorb: Column<Fixed>,
+    /// Enables the rows of the absorption of each slot but the first one.
+    sel_chain: Column<Fixed>,
+    /// Enables the rows of the rounds.
+    sel_round: Column<Fixed>,
+    /// Enables the second half of the rows of each round.
+    sel_step: Column<Fixed>,
+    sel_output: Column<Fixed>,
+    /// Enables the rows of the bit 0 of the absorption and of the output.
+    sel_first_bit: Column<Fixed>,
+    bit_weight: Column<Fixed>,
+    /// The bits of the round constant of the round.
+    round_constant: Column<Fixed>,
+    sel_push: [Column<Fixed>; RATE_LANES],
+    sel_finalize: [Column<Fixed>; FINALIZE_CALLS],
+
+    enable: Column<Advice>,
+    is_new: Column<Advice>,
+    is_final: Column<Advice>,
+
+    a: [Column<Advice>; LANES],
+    c: [Column<Advice>; 5],
+    t: [Column<Advice>; LANES],
+    b: [Column<Advice>; LANES],
+    /// The bits of the round constant of an enabled slot.
+    iota: Column<Advice>,
+    acc: [Column<Advice>; RATE_LANES],
+
+    call_enable: Column<Advice>,
+    /// The number of the calls preceding the row.
+    call_index: Column<Advice>,
+    lookup_index: Column<Advice>,
+    call_encoded: Column<Advice>,
+
+    _mark: PhantomData<F>,
+}
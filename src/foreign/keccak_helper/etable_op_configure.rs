@@ -0,0 +1,24 @@
+use specs::host_function::HostPlugin;
+
+use super::circuits::KECCAK_FOREIGN_TABLE_KEY;
+
+use crate::foreign::stream_helper::ETableStreamHelperTableConfigBuilder;
+use crate::foreign::stream_helper::StreamPlugin;
+
+pub(crate) struct KeccakPlugin;
+
+impl StreamPlugin for KeccakPlugin {
+    const PLUGIN: HostPlugin = HostPlugin::Keccak256;
+    const TABLE_KEY: &'static str = KECCAK_FOREIGN_TABLE_KEY;
+    const NEW_TAKES_VALUE: bool = false;
+
+    const STACK_READ: &'static str = "keccak stack read";
+    const STACK_WRITE: &'static str = "keccak stack write";
+    const SEL: &'static str = "keccak_helper: sel";
+    const NEW_VALUE: &'static str = "keccak_helper: new takes no value";
+    const LOOKUP_AUX: &'static str = "keccak_helper: lookup aux";
+    const LOOKUP: &'static str = "lookup keccak table";
+}
+
+pub(crate) type ETableKeccakHelperTableConfigBuilder =
+    ETableStreamHelperTableConfigBuilder<KeccakPlugin>;
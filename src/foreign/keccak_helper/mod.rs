@@ -1 +1,178 @@
+//! Keccak-256 digests computed by the host.
+//!
+//! A guest hashes a message padded as Keccak specifies, with the first padding bit `0x01`
+//! and the last one `0x80`: it starts a digest with `keccak_new`, pushes the padded message
+//! 8 bytes at a time, as little-endian lanes, with `keccak_push`, then reads the digest 8
+//! bytes at a time, as little-endian lanes, with 4 calls of `keccak_finalize`. The event
+//! table looks each call up in the Keccak table, which proves the permutations of the
+//! blocks, see `circuits`.
+
+use specs::etable::EventTableEntry;
+use specs::host_function::HostPlugin;
+use specs::step::StepInfo;
+
+pub mod circuits;
+pub mod etable_op_configure;
+pub mod runtime;
 pub mod test;
+
+pub(crate) use crate::foreign::stream_helper::Op;
+
+/// Lanes of the state.
+pub(crate) const LANES: usize = 25;
+/// Lanes of a block of 1088 bits, the rate of Keccak-256.
+pub(crate) const RATE_LANES: usize = 17;
+/// Calls of `keccak_finalize` reading a digest of 256 bits.
+pub(crate) const FINALIZE_CALLS: usize = 4;
+/// Rounds of the permutation.
+pub(crate) const ROUNDS: usize = 24;
+
+pub(crate) const ROUND_CONSTANTS: [u64; ROUNDS] = [
+    0x0000000000000001,
+    0x0000000000008082,
+    0x800000000000808a,
+    0x8000000080008000,
+    0x000000000000808b,
+    0x0000000080000001,
+    0x8000000080008081,
+    0x8000000000008009,
+    0x000000000000008a,
+    0x0000000000000088,
+    0x0000000080008009,
+    0x000000008000000a,
+    0x000000008000808b,
+    0x800000000000008b,
+    0x8000000000008089,
+    0x8000000000008003,
+    0x8000000000008002,
+    0x8000000000000080,
+    0x000000000000800a,
+    0x800000008000000a,
+    0x8000000080008081,
+    0x8000000000008080,
+    0x0000000080000001,
+    0x8000000080008008,
+];
+
+/// The rotation of the lane `x + 5y` in the step rho.
+pub(crate) const RHO_OFFSETS: [u32; LANES] = [
+    0, 1, 62, 28, 27, 36, 44, 6, 55, 20, 3, 10, 43, 25, 39, 41, 45, 15, 21, 8, 18, 2, 61, 56, 14,
+];
+
+/// The lane the step pi moves the lane `x + 5y` to, the lane `y + 5(2x + 3y)`.
+pub(crate) fn pi(lane: usize) -> usize {
+    let (x, y) = (lane % 5, lane / 5);
+
+    y + 5 * ((2 * x + 3 * y) % 5)
+}
+
+/// A round of the permutation step by step, as the table lays it out.
+pub(crate) struct Round {
+    /// The state the round starts from.
+    pub(crate) a: [u64; LANES],
+    /// The parities of the columns of `a`.
+    pub(crate) c: [u64; 5],
+    /// The state following theta.
+    pub(crate) t: [u64; LANES],
+    /// The state following rho and pi.
+    pub(crate) b: [u64; LANES],
+}
+
+impl Round {
+    fn new(a: [u64; LANES]) -> Self {
+        let c = [0, 1, 2, 3, 4].map(|x| (0..5).fold(0, |acc, y| acc ^ a[x + 5 * y]));
+
+        let mut t = [0; LANES];
+        for (lane, t) in t.iter_mut().enumerate() {
+            let x = lane % 5;
+
+            *t = a[lane] ^ c[(x + 4) % 5] ^ c[(x + 1) % 5].rotate_left(1);
+        }
+
+        let mut b = [0; LANES];
+        for lane in 0..LANES {
+            b[pi(lane)] = t[lane].rotate_left(RHO_OFFSETS[lane]);
+        }
+
+        Round { a, c, t, b }
+    }
+
+    /// The state following chi and iota.
+    fn output(&self, round: usize) -> [u64; LANES] {
+        let mut a = [0; LANES];
+        for (lane, a) in a.iter_mut().enumerate() {
+            let (x, y) = (lane % 5, lane / 5);
+
+            *a = self.b[lane] ^ (!self.b[(x + 1) % 5 + 5 * y] & self.b[(x + 2) % 5 + 5 * y]);
+        }
+        a[0] ^= ROUND_CONSTANTS[round];
+
+        a
+    }
+}
+
+/// The permutation of a state round by round.
+pub(crate) struct Permutation {
+    pub(crate) rounds: Vec<Round>,
+    pub(crate) output: [u64; LANES],
+}
+
+impl Permutation {
+    pub(crate) fn new(state: &[u64; LANES]) -> Self {
+        let mut rounds = vec![];
+        let mut a = *state;
+
+        for round in 0..ROUNDS {
+            let step = Round::new(a);
+            a = step.output(round);
+            rounds.push(step);
+        }
+
+        Permutation { rounds, output: a }
+    }
+}
+
+/// The state `block` is absorbed into, the state the permutation of the block starts from.
+pub(crate) fn absorb(state: &[u64; LANES], block: &[u64; RATE_LANES]) -> [u64; LANES] {
+    let mut state = *state;
+    for (lane, value) in block.iter().enumerate() {
+        state[lane] ^= value;
+    }
+
+    state
+}
+
+/// Whether a digest is in progress before each step of `entries` and after the last one.
+///
+/// The table of a circuit proves the digests started by its own steps, so an execution is
+/// not split into segments while a digest is in progress.
+pub(crate) fn digest_in_progress(entries: &[EventTableEntry]) -> Vec<bool> {
+    let mut in_progress = false;
+    let mut finalize_calls = 0;
+
+    let mut status = vec![false];
+    for entry in entries {
+        if let StepInfo::CallHost {
+            plugin: HostPlugin::Keccak256,
+            op_index_in_plugin,
+            ..
+        } = &entry.step_info
+        {
+            match Op::from_index(*op_index_in_plugin) {
+                Op::New => in_progress = true,
+                Op::Push => (),
+                Op::Finalize => {
+                    finalize_calls += 1;
+                    if finalize_calls == FINALIZE_CALLS {
+                        finalize_calls = 0;
+                        in_progress = false;
+                    }
+                }
+            }
+        }
+
+        status.push(in_progress);
+    }
+
+    status
+}
@@ -1,11 +1,13 @@
 #[cfg(test)]
 pub(crate) mod tests {
+    use crate::foreign::keccak_helper::runtime::register_keccak_foreign;
+    use crate::foreign::require_helper::register_require_foreign;
     use crate::foreign::wasm_input_helper::runtime::register_wasm_input_foreign;
     use crate::runtime::host::host_env::HostEnv;
+    use crate::test::compile_then_execute_wasm;
+    use crate::test::test_circuit_mock;
     use crate::test::test_circuit_with_env;
-
-    use crate::circuits::config::set_zkwasm_k;
-    use rusty_fork::rusty_fork_test;
+    use halo2_proofs::pairing::bn256::Fr;
     use std::fs;
 
     pub(crate) fn prepare_inputs() -> (Vec<u64>, Vec<u64>) {
@@ -28,23 +30,185 @@ pub(crate) mod tests {
         (public_inputs, private_inputs)
     }
 
-    /*
-     *  FORK the test since it modifies global variable zkwasm_k.
-     */
-    rusty_fork_test! {
-        #[test]
-        fn test_keccak() {
-            set_zkwasm_k(19);
+    #[test]
+    fn test_keccak() {
+        let k = 19;
+
+        let (public_inputs, private_inputs) = prepare_inputs();
+
+        let wasm = fs::read("wasm/keccak.wasm").unwrap();
+
+        let mut env = HostEnv::new();
+        let wasm_runtime_io =
+            register_wasm_input_foreign(&mut env, public_inputs.clone(), private_inputs.clone());
+        env.finalize();
+
+        let trace =
+            compile_then_execute_wasm(k, env, wasm_runtime_io, wasm, "keccak_digest").unwrap();
+        test_circuit_mock::<Fr>(k, trace).unwrap();
+    }
+
+    /// Hashes `messages` messages given as private inputs, the number of lanes of each one
+    /// followed by the lanes, and requires each digest to be the next 4 public inputs.
+    fn guest(messages: usize) -> String {
+        format!(
+            r#"
+        (module
+            (import "env" "wasm_input" (func $wasm_input (param i32) (result i64)))
+            (import "env" "require" (func $require (param i32)))
+            (import "env" "keccak_new" (func $keccak_new))
+            (import "env" "keccak_push" (func $keccak_push (param i64)))
+            (import "env" "keccak_finalize" (func $keccak_finalize (result i64)))
+            (export "main" (func $main))
+            (func $digest (; 5 ;)
+                (local $lanes i64)
+                (call $keccak_new)
+                (local.set $lanes (call $wasm_input (i32.const 0)))
+                (block
+                    (loop
+                        (br_if 1 (i64.eqz (local.get $lanes)))
+                        (call $keccak_push (call $wasm_input (i32.const 0)))
+                        (local.set $lanes (i64.sub (local.get $lanes) (i64.const 1)))
+                        (br 0)
+                    )
+                )
+                (call $require (i64.eq (call $keccak_finalize) (call $wasm_input (i32.const 1))))
+                (call $require (i64.eq (call $keccak_finalize) (call $wasm_input (i32.const 1))))
+                (call $require (i64.eq (call $keccak_finalize) (call $wasm_input (i32.const 1))))
+                (call $require (i64.eq (call $keccak_finalize) (call $wasm_input (i32.const 1))))
+            )
+            (func $main (; 6 ;)
+                (local $messages i32)
+                (local.set $messages (i32.const {}))
+                (block
+                    (loop
+                        (br_if 1 (i32.eqz (local.get $messages)))
+                        (call $digest)
+                        (local.set $messages (i32.sub (local.get $messages) (i32.const 1)))
+                        (br 0)
+                    )
+                )
+            )
+        )
+    "#,
+            messages
+        )
+    }
+
+    /// The message padded as Keccak specifies, 8 bytes per lane.
+    fn padded_lanes(message: &[u8]) -> Vec<u64> {
+        let mut padded = message.to_vec();
+        padded.push(0x01);
+        padded.resize((padded.len() + 135) / 136 * 136, 0);
+        *padded.last_mut().unwrap() |= 0x80;
+
+        padded
+            .chunks(8)
+            .map(|chunk| u64::from_le_bytes(chunk.try_into().unwrap()))
+            .collect()
+    }
+
+    fn digest_lanes(digest: &[u64]) -> Vec<u64> {
+        digest
+            .chunks(8)
+            .map(|chunk| chunk.iter().rev().fold(0, |lane, byte| (lane << 8) + *byte))
+            .collect()
+    }
+
+    fn test_keccak_plugin(messages: &[(&[u8], Vec<u64>)]) {
+        let mut private_inputs = vec![];
+        let mut public_inputs = vec![];
+        for (message, digest) in messages {
+            let lanes = padded_lanes(message);
+
+            private_inputs.push(lanes.len() as u64);
+            private_inputs.extend(lanes);
+            public_inputs.extend(digest_lanes(digest));
+        }
+
+        let wasm = wabt::wat2wasm(&guest(messages.len())).expect("failed to parse wat");
+
+        let mut env = HostEnv::new();
+        let wasm_runtime_io = register_wasm_input_foreign(&mut env, public_inputs, private_inputs);
+        register_require_foreign(&mut env);
+        register_keccak_foreign(&mut env);
+        env.finalize();
+
+        test_circuit_with_env(env, wasm_runtime_io, wasm, "main").unwrap();
+    }
+
+    fn hex_digest(digest: &str) -> Vec<u64> {
+        hex::decode(digest)
+            .unwrap()
+            .into_iter()
+            .map(|v| v as u64)
+            .collect()
+    }
+
+    #[test]
+    fn test_keccak_plugin_matches_wasm() {
+        // The digest the wasm implementation is checked against is the one of the
+        // message "abcdef".
+        let (digest, _) = prepare_inputs();
+
+        test_keccak_plugin(&[(b"abcdef", digest)]);
+    }
+
+    #[test]
+    fn test_keccak_plugin_two_blocks() {
+        test_keccak_plugin(&[(
+            &b"abcdef".repeat(40),
+            hex_digest("c4ca5ce537fe90c73a663a046d8d45e3241df80290b600be11d8188417cea8ba"),
+        )]);
+    }
+
+    #[test]
+    fn test_keccak_plugin_consecutive_digests() {
+        let (digest, _) = prepare_inputs();
 
-            let (public_inputs, private_inputs) = prepare_inputs();
+        test_keccak_plugin(&[
+            (
+                b"",
+                hex_digest("c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470"),
+            ),
+            (b"abcdef", digest),
+            (
+                b"The quick brown fox jumps over the lazy dog",
+                hex_digest("4d741b6f1eb29cb2a9b9911c82f56fa8d73b04959d3d9d222895df6c0b28aa15"),
+            ),
+        ]);
+    }
 
-            let wasm = fs::read("wasm/keccak.wasm").unwrap();
+    /// Misuses of the plugin trap the execution instead of panicking the host.
+    #[test]
+    fn test_keccak_plugin_misuse() {
+        for body in [
+            "(call $keccak_push (i64.const 0))",
+            "(drop (call $keccak_finalize))",
+            "(call $keccak_new) (call $keccak_new)",
+            "(call $keccak_new) (call $keccak_push (i64.const 0)) (drop (call $keccak_finalize))",
+        ] {
+            let wasm = wabt::wat2wasm(format!(
+                r#"
+            (module
+                (import "env" "keccak_new" (func $keccak_new))
+                (import "env" "keccak_push" (func $keccak_push (param i64)))
+                (import "env" "keccak_finalize" (func $keccak_finalize (result i64)))
+                (func (export "main")
+                    {}
+                )
+            )
+            "#,
+                body
+            ))
+            .expect("failed to parse wat");
 
             let mut env = HostEnv::new();
-            let wasm_runtime_io = register_wasm_input_foreign(&mut env, public_inputs.clone(), private_inputs.clone());
+            let wasm_runtime_io = register_wasm_input_foreign(&mut env, vec![], vec![]);
+            register_keccak_foreign(&mut env);
             env.finalize();
 
-            test_circuit_with_env(env, wasm_runtime_io, wasm, "keccak_digest").unwrap();
+            assert!(test_circuit_with_env(env, wasm_runtime_io, wasm, "main").is_err());
         }
     }
 }
@@ -0,0 +1,352 @@
+use halo2_proofs::arithmetic::FieldExt;
+use halo2_proofs::circuit::AssignedCell;
+use halo2_proofs::circuit::Layouter;
+use halo2_proofs::circuit::Region;
+use halo2_proofs::plonk::Error;
+use specs::etable::EventTableEntry;
+use specs::kvtable::KvTable;
+use specs::step::StepInfo;
+
+use super::encode_call;
+use super::KvHelperTableConfig;
+use super::HASH_ROWS;
+use super::ROOT_ROW;
+use super::SLOT_ROWS;
+use crate::circuits::poseidon::primitives::p128pow5t9::RATE;
+use crate::circuits::poseidon::primitives::p128pow5t9::WIDTH;
+use crate::circuits::poseidon::primitives::ConstantLength;
+use crate::circuits::poseidon::primitives::P128Pow5T9;
+use crate::circuits::poseidon::Hash;
+use crate::circuits::poseidon::Pow5Chip;
+use crate::foreign::kv_helper::hash;
+use crate::foreign::kv_helper::Op;
+use crate::foreign::kv_helper::DEPTH;
+use crate::foreign::poseidon_helper::compose;
+use crate::foreign::poseidon_helper::Permutation;
+
+struct Slot<F: FieldExt> {
+    op: Op,
+    key: u64,
+    /// The value of the call, read by `kv_get` or written by `kv_set`.
+    value: u64,
+    siblings: Vec<F>,
+    /// The nodes of the path before the access, from the leaf to the root.
+    old: Vec<F>,
+    new: Vec<F>,
+}
+
+/// The nodes of the path of `key` from `leaf` to the root.
+fn path<F: FieldExt>(permutation: &Permutation<F>, key: u64, leaf: F, siblings: &[F]) -> Vec<F> {
+    let mut nodes = vec![leaf];
+    for (height, sibling) in siblings.iter().enumerate() {
+        let node = nodes[height];
+
+        nodes.push(if (key >> height) & 1 == 0 {
+            hash(permutation, node, *sibling)
+        } else {
+            hash(permutation, *sibling, node)
+        });
+    }
+
+    nodes
+}
+
+/// The cells of a slot the hashes are copy constrained to.
+struct SlotCells<F: FieldExt> {
+    /// The children of the parents of each height of the path, before and after the access.
+    children: Vec<[[AssignedCell<F, F>; 2]; 2]>,
+    /// The parents of each height, before and after the access.
+    parents: Vec<[AssignedCell<F, F>; 2]>,
+    old_root: AssignedCell<F, F>,
+    new_root: AssignedCell<F, F>,
+}
+
+/// The rows of an entry of the table, an access, which are the rows of the hashes of its
+/// old and new paths by the Pow5 chip.
+pub const ENTRY_ROWS: usize = 2 * DEPTH * HASH_ROWS;
+
+/// The accesses of the calls of `entries`, a call per access.
+pub(crate) fn table_entries(entries: &[EventTableEntry]) -> usize {
+    entries.len()
+}
+
+pub struct KvHelperTableChip<F: FieldExt> {
+    config: KvHelperTableConfig<F>,
+    max_available_rows: usize,
+}
+
+impl<F: FieldExt> KvHelperTableChip<F> {
+    pub fn new(config: KvHelperTableConfig<F>, max_available_rows: usize) -> Self {
+        Self {
+            config,
+            max_available_rows,
+        }
+    }
+
+    fn assign_fixed(&self, region: &mut Region<'_, F>, slot: usize) -> Result<(), Error> {
+        let mut assign = |name, column, row: usize, value: F| {
+            region.assign_fixed(
+                || format!("kv helper {}", name),
+                column,
+                slot * SLOT_ROWS + row,
+                || Ok(value),
+            )
+        };
+
+        for row in 0..SLOT_ROWS {
+            assign("sel", self.config.sel, row, F::one())?;
+        }
+        for row in 0..DEPTH {
+            assign("sel path", self.config.sel_path, row, F::one())?;
+            assign(
+                "bit weight",
+                self.config.bit_weight,
+                row,
+                F::from(1u64 << row),
+            )?;
+        }
+
+        assign("sel slot", self.config.sel_slot, 0, F::one())?;
+        if slot > 0 {
+            assign("sel chain", self.config.sel_chain, 0, F::one())?;
+        }
+        assign("sel root", self.config.sel_root, ROOT_ROW, F::one())?;
+
+        Ok(())
+    }
+
+    /// Assigns `slot` as the slot `index`, disabled if `enable` is false, between the roots
+    /// `old_root` and `new_root`, returns its cells.
+    fn assign_slot(
+        &self,
+        region: &mut Region<'_, F>,
+        index: usize,
+        slot: &Slot<F>,
+        enable: bool,
+        (old_root, new_root): (F, F),
+        call_index: &mut u64,
+    ) -> Result<SlotCells<F>, Error> {
+        let start = index * SLOT_ROWS;
+        let is_set = enable && slot.op == Op::Set;
+
+        let mut children = vec![];
+        let mut parents = vec![];
+        let mut key_acc = 0u64;
+
+        for row in 0..SLOT_ROWS {
+            let offset = start + row;
+
+            let mut assign_advice = |name, column, value: F| {
+                region.assign_advice(
+                    || format!("kv helper {}", name),
+                    column,
+                    offset,
+                    || Ok(value),
+                )
+            };
+
+            assign_advice("enable", self.config.enable, F::from(enable as u64))?;
+            assign_advice("is set", self.config.is_set, F::from(is_set as u64))?;
+            assign_advice("call index", self.config.call_index, F::from(*call_index))?;
+
+            let old = assign_advice("old", self.config.old, slot.old[row])?;
+            let new = assign_advice("new", self.config.new, slot.new[row])?;
+            if row > 0 {
+                parents.push([old, new]);
+            }
+
+            if row < DEPTH {
+                let bit = (slot.key >> row) & 1;
+                key_acc += bit << row;
+
+                let sibling = slot.siblings[row];
+                let ordered = |node: F| {
+                    if bit == 0 {
+                        (node, sibling)
+                    } else {
+                        (sibling, node)
+                    }
+                };
+                let (old_left, old_right) = ordered(slot.old[row]);
+                let (new_left, new_right) = ordered(slot.new[row]);
+
+                assign_advice("key bit", self.config.key_bit, F::from(bit))?;
+                assign_advice("key acc", self.config.key_acc, F::from(key_acc))?;
+                assign_advice("sibling", self.config.sibling, sibling)?;
+
+                children.push([
+                    [
+                        assign_advice("old left", self.config.old_left, old_left)?,
+                        assign_advice("old right", self.config.old_right, old_right)?,
+                    ],
+                    [
+                        assign_advice("new left", self.config.new_left, new_left)?,
+                        assign_advice("new right", self.config.new_right, new_right)?,
+                    ],
+                ]);
+            }
+        }
+
+        let offset = start + ROOT_ROW;
+        let mut assign_advice = |name, column, value: F| {
+            region.assign_advice(
+                || format!("kv helper {}", name),
+                column,
+                offset,
+                || Ok(value),
+            )
+        };
+
+        let old_root = assign_advice("old root", self.config.old_root, old_root)?;
+        let new_root = assign_advice("new root", self.config.new_root, new_root)?;
+
+        if enable {
+            assign_advice("call enable", self.config.call_enable, F::one())?;
+            assign_advice(
+                "lookup index",
+                self.config.lookup_index,
+                F::from(*call_index),
+            )?;
+            assign_advice(
+                "call encoded",
+                self.config.call_encoded,
+                encode_call(slot.op, slot.key, slot.value),
+            )?;
+
+            *call_index += 1;
+        }
+
+        Ok(SlotCells {
+            children,
+            parents,
+            old_root,
+            new_root,
+        })
+    }
+
+    /// Assigns the calls of `entries`, the steps of the plugin in the event table, with
+    /// their accesses in `kvtable`. Returns the roots before and after the execution.
+    pub fn assign(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        entries: &[EventTableEntry],
+        kvtable: &KvTable,
+    ) -> Result<(AssignedCell<F, F>, AssignedCell<F, F>), Error> {
+        let capability = self.max_available_rows / ENTRY_ROWS;
+        if entries.len() > capability {
+            return Err(Error::Synthesis);
+        }
+        assert_eq!(entries.len(), kvtable.accesses.len());
+
+        // Every slot hashes its paths so that the hashes are the same for any execution, a
+        // disabled slot hashes the path of an empty leaf of the key 0.
+        let permutation = Permutation::new();
+        let disabled = {
+            let siblings = vec![F::zero(); DEPTH];
+            let nodes = path(&permutation, 0, F::zero(), &siblings);
+
+            Slot {
+                op: Op::Get,
+                key: 0,
+                value: 0,
+                siblings,
+                old: nodes.clone(),
+                new: nodes,
+            }
+        };
+        let slots = entries
+            .iter()
+            .zip(kvtable.accesses.iter())
+            .map(|(entry, access)| {
+                let op = match &entry.step_info {
+                    StepInfo::CallHost {
+                        op_index_in_plugin, ..
+                    } => Op::from_index(*op_index_in_plugin),
+                    _ => unreachable!(),
+                };
+                let siblings = access
+                    .siblings
+                    .iter()
+                    .map(|sibling| compose(sibling))
+                    .collect::<Vec<_>>();
+
+                Slot {
+                    op,
+                    key: access.key,
+                    value: match op {
+                        Op::Get => access.value,
+                        Op::Set => access.new_value,
+                    },
+                    old: path(&permutation, access.key, F::from(access.value), &siblings),
+                    new: path(
+                        &permutation,
+                        access.key,
+                        F::from(access.new_value),
+                        &siblings,
+                    ),
+                    siblings,
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let cells = layouter.assign_region(
+            || "kv helper table",
+            |mut region| {
+                for slot in 0..capability {
+                    self.assign_fixed(&mut region, slot)?;
+                }
+
+                let mut root = compose::<F>(&kvtable.root);
+                let mut call_index = 0;
+                (0..capability)
+                    .map(|index| {
+                        let (slot, enable) = match slots.get(index) {
+                            Some(slot) => (slot, true),
+                            None => (&disabled, false),
+                        };
+                        let roots = if enable {
+                            (slot.old[DEPTH], slot.new[DEPTH])
+                        } else {
+                            (root, root)
+                        };
+                        root = roots.1;
+
+                        self.assign_slot(&mut region, index, slot, enable, roots, &mut call_index)
+                    })
+                    .collect::<Result<Vec<_>, Error>>()
+            },
+        )?;
+
+        let mut hashes = vec![];
+        for slot in cells.iter() {
+            for (children, parents) in slot.children.iter().zip(slot.parents.iter()) {
+                for (children, parent) in children.iter().zip(parents.iter()) {
+                    let hasher = Hash::<_, _, P128Pow5T9<F>, ConstantLength<2>, WIDTH, RATE>::init(
+                        Pow5Chip::construct(self.config.pow5_config.clone()),
+                        layouter.namespace(|| "kv helper hash init"),
+                    )?;
+                    let output =
+                        hasher.hash(layouter.namespace(|| "kv helper hash"), children.clone())?;
+
+                    hashes.push((output, parent.clone()));
+                }
+            }
+        }
+
+        layouter.assign_region(
+            || "kv helper hashes",
+            |mut region| {
+                for (output, parent) in hashes.iter() {
+                    region.constrain_equal(output.cell(), parent.cell())?;
+                }
+
+                Ok(())
+            },
+        )?;
+
+        Ok((
+            cells.first().unwrap().old_root.clone(),
+            cells.last().unwrap().new_root.clone(),
+        ))
+    }
+}
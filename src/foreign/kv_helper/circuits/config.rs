@@ -0,0 +1,228 @@
+use halo2_proofs::arithmetic::FieldExt;
+use halo2_proofs::plonk::ConstraintSystem;
+use halo2_proofs::plonk::Expression;
+use halo2_proofs::plonk::VirtualCells;
+use num_bigint::BigUint;
+
+use crate::circuits::poseidon::primitives::p128pow5t9::WIDTH;
+use crate::circuits::poseidon::primitives::P128Pow5T9;
+use crate::circuits::poseidon::Pow5Chip;
+use crate::circuits::utils::bn_to_field;
+use crate::constant_from;
+use crate::constant_from_bn;
+use crate::curr;
+use crate::fixed_curr;
+use crate::foreign::kv_helper::Op;
+use crate::foreign::ForeignTableConfig;
+use crate::nextn;
+
+use super::KvHelperTableConfig;
+use super::ROOT_ROW;
+
+fn tag<F: FieldExt>(op: Op) -> Expression<F> {
+    constant_from_bn!(&(BigUint::from(op.tag()) << 128))
+}
+
+impl<F: FieldExt> KvHelperTableConfig<F> {
+    pub fn configure(meta: &mut ConstraintSystem<F>) -> Self {
+        let sel = meta.fixed_column();
+        let sel_slot = meta.fixed_column();
+        let sel_chain = meta.fixed_column();
+        let sel_path = meta.fixed_column();
+        let sel_root = meta.fixed_column();
+        let bit_weight = meta.fixed_column();
+
+        let enable = meta.advice_column();
+        let is_set = meta.advice_column();
+
+        let key_bit = meta.advice_column();
+        let key_acc = meta.advice_column();
+        let sibling = meta.advice_column();
+        let old = meta.advice_column();
+        let new = meta.advice_column();
+        let old_left = meta.advice_column();
+        let old_right = meta.advice_column();
+        let new_left = meta.advice_column();
+        let new_right = meta.advice_column();
+        let old_root = meta.advice_column();
+        let new_root = meta.advice_column();
+
+        let call_enable = meta.advice_column();
+        let call_index = meta.advice_column();
+        let lookup_index = meta.advice_column();
+        let call_encoded = meta.advice_column();
+
+        for column in [
+            old, new, old_left, old_right, new_left, new_right, old_root, new_root,
+        ] {
+            meta.enable_equality(column);
+        }
+
+        let state = [(); WIDTH].map(|_| meta.advice_column());
+        let partial_sbox = meta.advice_column();
+        let mid_0_helper = meta.advice_column();
+        let mid_0_helper_sqr = meta.advice_column();
+        let cur_0_rc_a0 = meta.advice_column();
+        let cur_0_rc_a0_sqr = meta.advice_column();
+        let state_rc_a = [(); WIDTH].map(|_| meta.advice_column());
+        let state_rc_a_sqr = [(); WIDTH].map(|_| meta.advice_column());
+        let rc_a = [(); WIDTH].map(|_| meta.fixed_column());
+        let rc_b = [(); WIDTH].map(|_| meta.fixed_column());
+
+        let pow5_config = Pow5Chip::configure::<P128Pow5T9<F>>(
+            meta,
+            state,
+            state_rc_a,
+            state_rc_a_sqr,
+            partial_sbox,
+            mid_0_helper,
+            mid_0_helper_sqr,
+            cur_0_rc_a0,
+            cur_0_rc_a0_sqr,
+            rc_a,
+            rc_b,
+        );
+
+        meta.create_gate("kv helper: slot", |meta| {
+            let enable_curr = curr!(meta, enable);
+            let is_set_curr = curr!(meta, is_set);
+            let sel_chain = fixed_curr!(meta, sel_chain);
+
+            vec![
+                enable_curr.clone() * (constant_from!(1) - enable_curr.clone()),
+                is_set_curr.clone() * (constant_from!(1) - is_set_curr.clone()),
+                is_set_curr.clone() * (constant_from!(1) - enable_curr.clone()),
+                // The enabled slots precede the others.
+                sel_chain.clone() * (constant_from!(1) - nextn!(meta, enable, -1)) * enable_curr,
+                // A `kv_get` leaves the leaf as it is.
+                (constant_from!(1) - is_set_curr) * (curr!(meta, new) - curr!(meta, old)),
+                // The root before the access is the root after the previous one.
+                sel_chain * (nextn!(meta, old_root, ROOT_ROW as i32) - nextn!(meta, new_root, -1)),
+            ]
+            .into_iter()
+            .map(|expr| expr * fixed_curr!(meta, sel_slot))
+            .collect::<Vec<_>>()
+        });
+
+        meta.create_gate("kv helper: slot flags", |meta| {
+            vec![
+                curr!(meta, enable) - nextn!(meta, enable, -1),
+                curr!(meta, is_set) - nextn!(meta, is_set, -1),
+            ]
+            .into_iter()
+            .map(|expr| expr * (fixed_curr!(meta, sel) - fixed_curr!(meta, sel_slot)))
+            .collect::<Vec<_>>()
+        });
+
+        meta.create_gate("kv helper: path", |meta| {
+            let bit = curr!(meta, key_bit);
+            let sibling = curr!(meta, sibling);
+            let old = curr!(meta, old);
+            let new = curr!(meta, new);
+
+            vec![
+                bit.clone() * (constant_from!(1) - bit.clone()),
+                curr!(meta, key_acc)
+                    - bit.clone() * fixed_curr!(meta, bit_weight)
+                    - (constant_from!(1) - fixed_curr!(meta, sel_slot)) * nextn!(meta, key_acc, -1),
+                curr!(meta, old_left) - old.clone() - bit.clone() * (sibling.clone() - old.clone()),
+                curr!(meta, old_right) - sibling.clone() - bit.clone() * (old - sibling.clone()),
+                curr!(meta, new_left) - new.clone() - bit.clone() * (sibling.clone() - new.clone()),
+                curr!(meta, new_right) - sibling.clone() - bit * (new - sibling),
+            ]
+            .into_iter()
+            .map(|expr| expr * fixed_curr!(meta, sel_path))
+            .collect::<Vec<_>>()
+        });
+
+        meta.create_gate("kv helper: root", |meta| {
+            let enable = curr!(meta, enable);
+
+            vec![
+                enable.clone() * (curr!(meta, old) - curr!(meta, old_root)),
+                enable.clone() * (curr!(meta, new) - curr!(meta, new_root)),
+                (constant_from!(1) - enable) * (curr!(meta, new_root) - curr!(meta, old_root)),
+            ]
+            .into_iter()
+            .map(|expr| expr * fixed_curr!(meta, sel_root))
+            .collect::<Vec<_>>()
+        });
+
+        meta.create_gate("kv helper: calls", |meta| {
+            let is_set = curr!(meta, is_set);
+            // The key is composed on the row of the last bit, the leaves are on the first
+            // row of the slot.
+            let key = nextn!(meta, key_acc, -1);
+            let old_leaf = nextn!(meta, old, -(ROOT_ROW as i32));
+            let new_leaf = nextn!(meta, new, -(ROOT_ROW as i32));
+
+            let call_encoded_expr = tag(Op::Get)
+                + is_set.clone() * (tag(Op::Set) - tag(Op::Get))
+                + key * constant_from_bn!(&(BigUint::from(1u64) << 64))
+                + old_leaf.clone()
+                + is_set * (new_leaf - old_leaf);
+
+            let is_first_row =
+                fixed_curr!(meta, sel_slot) * (constant_from!(1) - fixed_curr!(meta, sel_chain));
+
+            vec![
+                curr!(meta, call_enable) - fixed_curr!(meta, sel_root) * curr!(meta, enable),
+                curr!(meta, call_encoded) - curr!(meta, call_enable) * call_encoded_expr,
+                curr!(meta, lookup_index) - curr!(meta, call_enable) * curr!(meta, call_index),
+                is_first_row.clone() * curr!(meta, call_index),
+                (constant_from!(1) - is_first_row)
+                    * (curr!(meta, call_index)
+                        - nextn!(meta, call_index, -1)
+                        - nextn!(meta, call_enable, -1)),
+            ]
+            .into_iter()
+            .map(|expr| expr * fixed_curr!(meta, sel))
+            .collect::<Vec<_>>()
+        });
+
+        KvHelperTableConfig {
+            sel,
+            sel_slot,
+            sel_chain,
+            sel_path,
+            sel_root,
+            bit_weight,
+            enable,
+            is_set,
+            key_bit,
+            key_acc,
+            sibling,
+            old,
+            new,
+            old_left,
+            old_right,
+            new_left,
+            new_right,
+            old_root,
+            new_root,
+            call_enable,
+            call_index,
+            lookup_index,
+            call_encoded,
+            pow5_config,
+        }
+    }
+}
+
+impl<F: FieldExt> ForeignTableConfig<F> for KvHelperTableConfig<F> {
+    fn configure_in_table(
+        &self,
+        meta: &mut ConstraintSystem<F>,
+        key: &'static str,
+        expr: &dyn Fn(&mut VirtualCells<'_, F>) -> Vec<Expression<F>>,
+    ) {
+        meta.lookup_any(key, |meta| {
+            let mut exprs = expr(meta);
+
+            vec![
+                (exprs.remove(0), curr!(meta, self.lookup_index)),
+                (exprs.remove(0), curr!(meta, self.call_encoded)),
+            ]
+        });
+    }
+}
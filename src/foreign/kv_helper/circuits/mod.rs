@@ -0,0 +1,87 @@
+//! The KV table.
+//!
+//! The table is a sequence of slots of `SLOT_ROWS` rows, a slot proves an access against
+//! the root. The path of the key is laid out on the first `DEPTH` rows, a row per height
+//! from the leaf up: the bit of the key, the sibling, and the nodes of the path before and
+//! after the access in `old` and `new`, ordered with the sibling as the children of their
+//! parents in `*_left` and `*_right`. The parents are hashed by the Pow5 chip, outside of
+//! the table, and copy constrained to the nodes of the next row. A `kv_get` leaves the leaf
+//! as it is, so both paths are the same.
+//!
+//! The last row of a slot has the roots of the paths, and the roots of the store before
+//! and after the access in `old_root` and `new_root`, which are the roots of the paths for
+//! an enabled slot and the same root for a disabled one. The root before an access is the
+//! root after the previous one, so the root before the first slot and the root after the
+//! last one are the roots before and after the execution, which are public instances.
+//!
+//! The call of a slot is on its last row, a call is numbered in the order of the table,
+//! the event table looks up each call with its number among the calls of the plugin, so
+//! the calls of the table are exactly the calls of the execution.
+
+use halo2_proofs::arithmetic::FieldExt;
+use halo2_proofs::plonk::Advice;
+use halo2_proofs::plonk::Column;
+use halo2_proofs::plonk::Fixed;
+use num_bigint::BigUint;
+
+use crate::circuits::poseidon::primitives::p128pow5t9::RATE;
+use crate::circuits::poseidon::primitives::p128pow5t9::WIDTH;
+use crate::circuits::poseidon::Pow5Config;
+use crate::circuits::utils::bn_to_field;
+
+use super::Op;
+use super::DEPTH;
+
+pub mod assign;
+pub mod config;
+
+pub const KV_FOREIGN_TABLE_KEY: &'static str = "kv-helper-table";
+
+/// The row of the roots and the call of a slot.
+const ROOT_ROW: usize = DEPTH;
+const SLOT_ROWS: usize = DEPTH + 1;
+/// The rows of a hash by the Pow5 chip: the initial state, the absorption of the children
+/// and the rounds of the permutation.
+const HASH_ROWS: usize = 45;
+
+/// The encoding of a call in the lookup of the event table.
+pub(crate) fn encode_call<F: FieldExt>(op: Op, key: u64, value: u64) -> F {
+    bn_to_field(&((BigUint::from(op.tag()) << 128) + (BigUint::from(key) << 64) + value))
+}
+
+#[derive(Clone)]
+pub struct KvHelperTableConfig<F: FieldExt> {
+    /// Enables the rows of the slots.
+    sel: Column<Fixed>,
+    /// Enables the first row of each slot.
+    sel_slot: Column<Fixed>,
+    /// Enables the first row of each slot but the first one.
+    sel_chain: Column<Fixed>,
+    /// Enables the rows of the path of each slot.
+    sel_path: Column<Fixed>,
+    sel_root: Column<Fixed>,
+    bit_weight: Column<Fixed>,
+
+    enable: Column<Advice>,
+    is_set: Column<Advice>,
+
+    key_bit: Column<Advice>,
+    key_acc: Column<Advice>,
+    sibling: Column<Advice>,
+    old: Column<Advice>,
+    new: Column<Advice>,
+    old_left: Column<Advice>,
+    old_right: Column<Advice>,
+    new_left: Column<Advice>,
+    new_right: Column<Advice>,
+    old_root: Column<Advice>,
+    new_root: Column<Advice>,
+
+    call_enable: Column<Advice>,
+    /// The number of the calls preceding the row.
+    call_index: Column<Advice>,
+    lookup_index: Column<Advice>,
+    call_encoded: Column<Advice>,
+
+    pow5_config: Pow5Config<F, WIDTH, RATE>,
+}
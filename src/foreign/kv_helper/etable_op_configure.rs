@@ -0,0 +1,255 @@
+use halo2_proofs::arithmetic::FieldExt;
+use halo2_proofs::plonk::Error;
+use halo2_proofs::plonk::Expression;
+use halo2_proofs::plonk::VirtualCells;
+use num_bigint::BigUint;
+use specs::etable::EventTableEntry;
+use specs::host_function::HostPlugin;
+use specs::itable::OpcodeClass;
+use specs::itable::OPCODE_CLASS_SHIFT;
+use specs::mtable::LocationType;
+use specs::step::StepInfo;
+
+use super::circuits::encode_call;
+use super::circuits::KV_FOREIGN_TABLE_KEY;
+use super::Op;
+
+use crate::circuits::cell::AllocatedBitCell;
+use crate::circuits::cell::AllocatedU64Cell;
+use crate::circuits::cell::AllocatedUnlimitedCell;
+use crate::circuits::cell::CellExpression;
+use crate::circuits::etable::allocator::AllocatedMemoryTableLookupReadCell;
+use crate::circuits::etable::allocator::AllocatedMemoryTableLookupWriteCell;
+use crate::circuits::etable::allocator::EventTableCellAllocator;
+use crate::circuits::etable::constraint_builder::ConstraintBuilder;
+use crate::circuits::etable::EventTableCommonConfig;
+use crate::circuits::etable::EventTableOpcodeConfig;
+use crate::circuits::utils::bn_to_field;
+use crate::circuits::utils::step_status::StepStatus;
+use crate::circuits::utils::table_entry::EventTableEntryWithMemoryInfo;
+use crate::circuits::utils::Context;
+use crate::constant_from;
+use crate::constant_from_bn;
+use crate::foreign::etable_op_configure::ForeignCallLookupConfig;
+use crate::foreign::EventTableForeignCallConfigBuilder;
+use crate::foreign::InternalHostPluginBuilder;
+
+pub struct ETableKvHelperTableConfig<F: FieldExt> {
+    plugin_index: usize,
+    is_get_op: AllocatedBitCell<F>,
+    is_set_op: AllocatedBitCell<F>,
+
+    key: AllocatedU64Cell<F>,
+    /// The value read by `kv_get` or written by `kv_set`.
+    value: AllocatedU64Cell<F>,
+
+    lookup_call: ForeignCallLookupConfig<F>,
+
+    lookup_read_key: AllocatedMemoryTableLookupReadCell<F>,
+    lookup_read_value: AllocatedMemoryTableLookupReadCell<F>,
+    lookup_write_value: AllocatedMemoryTableLookupWriteCell<F>,
+}
+
+pub struct ETableKvHelperTableConfigBuilder {
+    index: usize,
+}
+
+impl InternalHostPluginBuilder for ETableKvHelperTableConfigBuilder {
+    fn new(index: usize) -> Self {
+        Self { index }
+    }
+}
+
+impl<F: FieldExt> EventTableForeignCallConfigBuilder<F> for ETableKvHelperTableConfigBuilder {
+    fn configure(
+        self,
+        common_config: &EventTableCommonConfig<F>,
+        allocator: &mut EventTableCellAllocator<F>,
+        constraint_builder: &mut ConstraintBuilder<F>,
+        lookup_cells: &mut (impl Iterator<Item = AllocatedUnlimitedCell<F>> + Clone),
+    ) -> Box<dyn EventTableOpcodeConfig<F>> {
+        let eid = common_config.eid_cell;
+        let sp = common_config.sp_cell;
+
+        let is_get_op = allocator.alloc_bit_cell();
+        let is_set_op = allocator.alloc_bit_cell();
+
+        let key = allocator.alloc_u64_cell();
+        let value = allocator.alloc_u64_cell();
+
+        // The key is the first argument, below the value of `kv_set`.
+        let lookup_read_key = allocator.alloc_memory_table_lookup_read_cell(
+            "kv key read",
+            constraint_builder,
+            eid,
+            move |____| constant_from!(LocationType::Stack as u64),
+            move |meta| sp.expr(meta) + constant_from!(1) + is_set_op.expr(meta),
+            move |____| constant_from!(0),
+            move |meta| key.u64_cell.expr(meta),
+            move |____| constant_from!(1),
+        );
+        let lookup_read_value = allocator.alloc_memory_table_lookup_read_cell(
+            "kv value read",
+            constraint_builder,
+            eid,
+            move |____| constant_from!(LocationType::Stack as u64),
+            move |meta| sp.expr(meta) + constant_from!(1),
+            move |____| constant_from!(0),
+            move |meta| value.u64_cell.expr(meta),
+            move |meta| is_set_op.expr(meta),
+        );
+        let lookup_write_value = allocator.alloc_memory_table_lookup_write_cell(
+            "kv value write",
+            constraint_builder,
+            eid,
+            move |____| constant_from!(LocationType::Stack as u64),
+            move |meta| sp.expr(meta) + constant_from!(1),
+            move |____| constant_from!(0),
+            move |meta| value.u64_cell.expr(meta),
+            move |meta| is_get_op.expr(meta),
+        );
+
+        constraint_builder.push(
+            "kv_helper: sel",
+            Box::new(move |meta| {
+                vec![is_get_op.expr(meta) + is_set_op.expr(meta) - constant_from!(1)]
+            }),
+        );
+
+        let lookup_call = ForeignCallLookupConfig::configure(
+            HostPlugin::MerkleKv,
+            KV_FOREIGN_TABLE_KEY,
+            ("kv_helper: lookup aux", "lookup kv table"),
+            common_config,
+            constraint_builder,
+            lookup_cells,
+            move |meta| {
+                let tag = |op: Op| constant_from_bn!(&(BigUint::from(op.tag()) << 128));
+
+                is_get_op.expr(meta) * tag(Op::Get)
+                    + is_set_op.expr(meta) * tag(Op::Set)
+                    + key.expr(meta) * constant_from_bn!(&(BigUint::from(1u64) << 64))
+                    + value.expr(meta)
+            },
+        );
+
+        Box::new(ETableKvHelperTableConfig {
+            plugin_index: self.index,
+            is_get_op,
+            is_set_op,
+            key,
+            value,
+            lookup_call,
+            lookup_read_key,
+            lookup_read_value,
+            lookup_write_value,
+        })
+    }
+}
+
+impl<F: FieldExt> EventTableOpcodeConfig<F> for ETableKvHelperTableConfig<F> {
+    fn opcode(&self, meta: &mut VirtualCells<'_, F>) -> Expression<F> {
+        constant_from_bn!(
+            &(BigUint::from(OpcodeClass::ForeignPluginStart as u64 + self.plugin_index as u64)
+                << OPCODE_CLASS_SHIFT)
+        ) + self.is_set_op.expr(meta) * constant_from!(Op::Set as u64)
+    }
+
+    fn assign(
+        &self,
+        ctx: &mut Context<'_, F>,
+        step: &StepStatus,
+        entry: &EventTableEntryWithMemoryInfo,
+    ) -> Result<(), Error> {
+        match &entry.eentry.step_info {
+            StepInfo::CallHost {
+                args,
+                ret_vals,
+                op_index_in_plugin,
+                ..
+            } => {
+                let op = Op::from_index(*op_index_in_plugin);
+                let key = args[0];
+
+                self.lookup_read_key.assign(
+                    ctx,
+                    entry.memory_rw_entires[0].start_eid,
+                    step.current.eid,
+                    entry.memory_rw_entires[0].end_eid,
+                    step.current.sp + args.len() as u32,
+                    LocationType::Stack,
+                    false,
+                    key,
+                )?;
+
+                let value = match op {
+                    Op::Get => {
+                        let value = ret_vals[0];
+
+                        self.is_get_op.assign_bool(ctx, true)?;
+                        self.lookup_write_value.assign(
+                            ctx,
+                            step.current.eid,
+                            entry.memory_rw_entires[1].end_eid,
+                            step.current.sp + 1,
+                            LocationType::Stack,
+                            false,
+                            value,
+                        )?;
+
+                        value
+                    }
+                    Op::Set => {
+                        let value = args[1];
+
+                        self.is_set_op.assign_bool(ctx, true)?;
+                        self.lookup_read_value.assign(
+                            ctx,
+                            entry.memory_rw_entires[1].start_eid,
+                            step.current.eid,
+                            entry.memory_rw_entires[1].end_eid,
+                            step.current.sp + 1,
+                            LocationType::Stack,
+                            false,
+                            value,
+                        )?;
+
+                        value
+                    }
+                };
+
+                self.key.assign(ctx, key)?;
+                self.value.assign(ctx, value)?;
+                self.lookup_call
+                    .assign(ctx, step, encode_call(op, key, value))?;
+
+                Ok(())
+            }
+
+            _ => unreachable!(),
+        }
+    }
+
+    fn sp_diff(&self, meta: &mut VirtualCells<'_, F>) -> Option<Expression<F>> {
+        Some(self.is_set_op.expr(meta) * constant_from!(2))
+    }
+
+    fn mops(&self, meta: &mut VirtualCells<'_, F>) -> Option<Expression<F>> {
+        Some(self.is_get_op.expr(meta))
+    }
+
+    fn memory_writing_ops(&self, entry: &EventTableEntry) -> u32 {
+        match &entry.step_info {
+            StepInfo::CallHost {
+                plugin,
+                op_index_in_plugin,
+                ..
+            } => {
+                assert_eq!(*plugin, HostPlugin::MerkleKv);
+
+                (Op::from_index(*op_index_in_plugin) == Op::Get) as u32
+            }
+            _ => unreachable!(),
+        }
+    }
+}
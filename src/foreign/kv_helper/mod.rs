@@ -0,0 +1,166 @@
+//! A key-value store kept by the host, committed to by a sparse Merkle tree.
+//!
+//! A guest reads a value with `kv_get(key)`, 0 for an absent key, and writes one with
+//! `kv_set(key, value)`, keys are below 2^`DEPTH`. The leaves of the tree are the values,
+//! a node is the Poseidon digest of its children, with the P128Pow5T9 spec and the domain
+//! `ConstantLength<2>`. The host records each access with the siblings of the path of its
+//! key, see `specs::kvtable`, and the KV table proves it against the root, see `circuits`.
+//! The roots before and after the execution are public instances, so that the state an
+//! execution starts from is the one the previous execution leaves behind.
+//!
+//! The nodes are kept in a `KvStore`, in memory or in a file, see `store`. The nodes an
+//! execution writes are kept by the host until the execution is proven, they are then
+//! committed to the store with `commit`.
+
+use std::collections::BTreeMap;
+use std::io;
+
+use halo2_proofs::arithmetic::FieldExt;
+use halo2_proofs::pairing::bn256::Fr;
+use specs::etable::EventTableEntry;
+use specs::host_function::HostPlugin;
+use specs::kvtable::KvTable;
+use specs::kvtable::Node;
+use specs::step::StepInfo;
+use specs::Tables;
+
+use crate::foreign::poseidon_helper::compose;
+use crate::foreign::poseidon_helper::decompose;
+use crate::foreign::poseidon_helper::initial_state;
+use crate::foreign::poseidon_helper::Permutation;
+
+pub mod circuits;
+pub mod etable_op_configure;
+pub mod runtime;
+pub mod store;
+pub mod test;
+
+use store::KvStore;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Op {
+    Get = 0,
+    Set = 1,
+}
+
+impl Op {
+    pub(crate) fn from_index(op_index_in_plugin: usize) -> Self {
+        match op_index_in_plugin {
+            0 => Op::Get,
+            1 => Op::Set,
+            _ => unreachable!(),
+        }
+    }
+
+    /// The tag of the op in the lookup of a call, tags start from 1 so that a call never
+    /// looks up the empty rows of the table.
+    pub(crate) fn tag(self) -> u64 {
+        self as u64 + 1
+    }
+}
+
+/// The height of the tree, the bits of a key.
+pub const DEPTH: usize = 32;
+
+/// The parent of `left` and `right`.
+pub(crate) fn hash<F: FieldExt>(permutation: &Permutation<F>, left: F, right: F) -> F {
+    let mut state = initial_state::<F>(2);
+    state[0] += left;
+    state[1] += right;
+
+    permutation.permute(&state)[0]
+}
+
+/// The roots of the empty subtrees of each height, from the leaves up.
+pub(crate) fn empty_nodes<F: FieldExt>(permutation: &Permutation<F>) -> Vec<F> {
+    let mut nodes = vec![F::zero()];
+    for height in 0..DEPTH {
+        nodes.push(hash(permutation, nodes[height], nodes[height]));
+    }
+
+    nodes
+}
+
+/// The nodes of the path of `key` from its leaf `leaf` to the root, by height and index
+/// among the nodes of their height, `siblings` are the siblings of the path from the leaf.
+pub(crate) fn path<F: FieldExt>(
+    permutation: &Permutation<F>,
+    key: u64,
+    leaf: F,
+    siblings: &[F],
+) -> Vec<(usize, u64, F)> {
+    let mut node = leaf;
+    let mut path = vec![(0, key, node)];
+    for (height, sibling) in siblings.iter().enumerate() {
+        node = if (key >> height) & 1 == 0 {
+            hash(permutation, node, *sibling)
+        } else {
+            hash(permutation, *sibling, node)
+        };
+
+        path.push((height + 1, key >> (height + 1), node));
+    }
+
+    path
+}
+
+/// Writes the nodes written by the accesses of `kvtable` to `store`, once the execution
+/// is proven. The store must be in the state the execution started from.
+pub fn commit(store: &mut dyn KvStore, kvtable: &KvTable) -> io::Result<()> {
+    let permutation = Permutation::<Fr>::new();
+
+    let root = store
+        .node(DEPTH, 0)
+        .unwrap_or_else(|| decompose(&empty_nodes(&permutation)[DEPTH]));
+    if root != kvtable.root {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "the kv store has changed since the execution",
+        ));
+    }
+
+    // The nodes of a later access replace the ones of an earlier access.
+    let mut nodes = BTreeMap::new();
+    for access in &kvtable.accesses {
+        if access.new_value != access.value {
+            let siblings = access
+                .siblings
+                .iter()
+                .map(|node| compose(node))
+                .collect::<Vec<Fr>>();
+
+            for (height, index, node) in path(
+                &permutation,
+                access.key,
+                Fr::from(access.new_value),
+                &siblings,
+            ) {
+                nodes.insert((height, index), decompose(&node));
+            }
+        }
+    }
+
+    store.update(
+        nodes
+            .into_iter()
+            .map(|((height, index), node): ((usize, u64), Node)| (height, index, node))
+            .collect(),
+    )
+}
+
+pub(crate) fn is_kv_step(entry: &EventTableEntry) -> bool {
+    matches!(
+        &entry.step_info,
+        StepInfo::CallHost {
+            plugin: HostPlugin::MerkleKv,
+            ..
+        }
+    )
+}
+
+/// The public instances of the KV store, the roots before and after the execution.
+pub(crate) fn kv_instances<F: FieldExt>(tables: &Tables) -> Vec<F> {
+    let kvtable = &tables.execution_tables.kvtable;
+
+    vec![compose(&kvtable.root), compose(&kvtable.final_root())]
+}
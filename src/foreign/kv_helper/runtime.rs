@@ -0,0 +1,170 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use halo2_proofs::pairing::bn256::Fr;
+use specs::host_function::HostPlugin;
+use specs::kvtable::KvAccess;
+use specs::kvtable::KvTable;
+use specs::types::ValueType;
+
+use crate::foreign::poseidon_helper::compose;
+use crate::foreign::poseidon_helper::decompose;
+use crate::foreign::poseidon_helper::Permutation;
+use crate::runtime::host::host_env::HostEnv;
+use crate::runtime::host::ForeignContext;
+use crate::runtime::wasmi_interpreter::WasmRuntimeIO;
+
+use super::empty_nodes;
+use super::path;
+use super::store::SharedKvStore;
+use super::Op;
+use super::DEPTH;
+
+struct Context {
+    permutation: Permutation<Fr>,
+    empty_nodes: Vec<Fr>,
+    store: SharedKvStore,
+    /// The nodes written by the execution, they are committed to the store once the
+    /// execution is proven.
+    pending: HashMap<(usize, u64), Fr>,
+    kvtable: Rc<RefCell<KvTable>>,
+    error: Option<String>,
+}
+
+impl Context {
+    fn new(store: SharedKvStore, kvtable: Rc<RefCell<KvTable>>) -> Self {
+        let permutation = Permutation::new();
+        let empty_nodes = empty_nodes(&permutation);
+
+        let context = Context {
+            permutation,
+            empty_nodes,
+            store,
+            pending: HashMap::new(),
+            kvtable,
+            error: None,
+        };
+
+        let root = decompose(&context.node(DEPTH, 0));
+        *context.kvtable.borrow_mut() = KvTable {
+            root,
+            accesses: vec![],
+        };
+
+        context
+    }
+
+    fn node(&self, height: usize, index: u64) -> Fr {
+        if let Some(node) = self.pending.get(&(height, index)) {
+            return *node;
+        }
+
+        self.store
+            .lock()
+            .unwrap()
+            .node(height, index)
+            .map_or(self.empty_nodes[height], |node| compose(&node))
+    }
+
+    /// Reads the value of `key` and writes `new_value` if it is given, returns the value
+    /// read, or `None` with the error of the context set if the key is out of the tree.
+    fn access(&mut self, key: u64, new_value: Option<u64>) -> Option<u64> {
+        if key >> DEPTH != 0 {
+            self.error = Some(format!("kv key {} is not below 2^{}", key, DEPTH));
+
+            return None;
+        }
+
+        let value = decompose(&self.node(0, key))[0];
+        let new_value = new_value.unwrap_or(value);
+
+        let siblings = (0..DEPTH)
+            .map(|height| self.node(height, (key >> height) ^ 1))
+            .collect::<Vec<_>>();
+
+        let path = path(&self.permutation, key, Fr::from(new_value), &siblings);
+        let root = path.last().unwrap().2;
+
+        if new_value != value {
+            for (height, index, node) in path {
+                self.pending.insert((height, index), node);
+            }
+        }
+
+        self.kvtable.borrow_mut().accesses.push(KvAccess {
+            key,
+            value,
+            new_value,
+            siblings: siblings.iter().map(decompose).collect(),
+            root: decompose(&root),
+        });
+
+        Some(value)
+    }
+}
+
+impl ForeignContext for Context {
+    fn take_error(&mut self) -> Option<String> {
+        self.error.take()
+    }
+}
+
+/// Registers `kv_get` and `kv_set` on the nodes of `store`, the accesses are recorded in
+/// the KV table of `wasm_runtime_io`. The store is left as it is, the nodes written by the
+/// execution are committed with `kv_helper::commit` once it is proven.
+pub fn register_kv_foreign(
+    env: &mut HostEnv,
+    store: SharedKvStore,
+    wasm_runtime_io: &WasmRuntimeIO,
+) {
+    let kv_get = Rc::new(
+        |context: &mut dyn ForeignContext, args: wasmi::RuntimeArgs| {
+            let context = context.downcast_mut::<Context>().unwrap();
+
+            let key: i64 = args.nth(0);
+            context
+                .access(key as u64, None)
+                .map(|value| wasmi::RuntimeValue::I64(value as i64))
+        },
+    );
+
+    let kv_set = Rc::new(
+        |context: &mut dyn ForeignContext, args: wasmi::RuntimeArgs| {
+            let context = context.downcast_mut::<Context>().unwrap();
+
+            let key: i64 = args.nth(0);
+            let value: i64 = args.nth(1);
+            context.access(key as u64, Some(value as u64));
+
+            None
+        },
+    );
+
+    env.internal_env.register_plugin(
+        HostPlugin::MerkleKv,
+        Box::new(Context::new(store, wasm_runtime_io.kvtable.clone())),
+    );
+
+    env.internal_env.register_function(
+        "kv_get",
+        specs::host_function::Signature {
+            params: vec![ValueType::I64],
+            return_types: vec![ValueType::I64],
+        },
+        HostPlugin::MerkleKv,
+        Op::Get as usize,
+        kv_get,
+    );
+
+    env.internal_env.register_function(
+        "kv_set",
+        specs::host_function::Signature {
+            params: vec![ValueType::I64, ValueType::I64],
+            return_types: vec![],
+        },
+        HostPlugin::MerkleKv,
+        Op::Set as usize,
+        kv_set,
+    );
+}
@@ -0,0 +1,95 @@
+//! Backends keeping the nodes of the tree of the KV store.
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io;
+use std::io::BufReader;
+use std::io::BufWriter;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::Mutex;
+
+use specs::kvtable::Node;
+
+/// The nodes of the tree, by height and index among the nodes of their height. A node
+/// missing from the store is the root of an empty subtree.
+pub trait KvStore: Send {
+    fn node(&self, height: usize, index: u64) -> Option<Node>;
+
+    /// Updates the nodes of the paths of the keys written by a proven execution.
+    fn update(&mut self, nodes: Vec<(usize, u64, Node)>) -> io::Result<()>;
+}
+
+/// A store shared by the host functions reading it and the prover committing to it, which
+/// may be held by another thread than the one executing.
+pub type SharedKvStore = Arc<Mutex<Box<dyn KvStore>>>;
+
+/// A store lost with the host, e.g. to test a guest offline.
+#[derive(Default)]
+pub struct MemoryKvStore {
+    nodes: HashMap<(usize, u64), Node>,
+}
+
+impl KvStore for MemoryKvStore {
+    fn node(&self, height: usize, index: u64) -> Option<Node> {
+        self.nodes.get(&(height, index)).cloned()
+    }
+
+    fn update(&mut self, nodes: Vec<(usize, u64, Node)>) -> io::Result<()> {
+        for (height, index, node) in nodes {
+            self.nodes.insert((height, index), node);
+        }
+
+        Ok(())
+    }
+}
+
+/// A store persisted in a JSON file, the file is rewritten on each update, i.e. once per
+/// proven execution, so the state left by an execution is the one the next execution
+/// starts from.
+pub struct FileKvStore {
+    path: PathBuf,
+    nodes: MemoryKvStore,
+}
+
+impl FileKvStore {
+    /// Opens the store of `path`, an empty store if the file does not exist.
+    pub fn open(path: PathBuf) -> io::Result<Self> {
+        let mut nodes = MemoryKvStore::default();
+
+        if path.exists() {
+            let entries: Vec<(usize, u64, Node)> =
+                serde_json::from_reader(BufReader::new(File::open(&path)?))
+                    .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+
+            nodes.update(entries)?;
+        }
+
+        Ok(FileKvStore { path, nodes })
+    }
+}
+
+impl KvStore for FileKvStore {
+    fn node(&self, height: usize, index: u64) -> Option<Node> {
+        self.nodes.node(height, index)
+    }
+
+    fn update(&mut self, nodes: Vec<(usize, u64, Node)>) -> io::Result<()> {
+        self.nodes.update(nodes)?;
+
+        let mut entries = self
+            .nodes
+            .nodes
+            .iter()
+            .map(|((height, index), node)| (*height, *index, *node))
+            .collect::<Vec<_>>();
+        entries.sort();
+
+        let mut writer = BufWriter::new(File::create(&self.path)?);
+        serde_json::to_writer(&mut writer, &entries)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+
+        writer.flush()
+    }
+}
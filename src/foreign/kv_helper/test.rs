@@ -0,0 +1,159 @@
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::sync::Mutex;
+
+    use anyhow::Result;
+    use specs::kvtable::KvTable;
+    use wasmi::RuntimeValue;
+
+    use crate::foreign::kv_helper::commit;
+    use crate::foreign::kv_helper::runtime::register_kv_foreign;
+    use crate::foreign::kv_helper::store::FileKvStore;
+    use crate::foreign::kv_helper::store::KvStore;
+    use crate::foreign::kv_helper::store::MemoryKvStore;
+    use crate::foreign::kv_helper::store::SharedKvStore;
+    use crate::foreign::require_helper::register_require_foreign;
+    use crate::foreign::wasm_input_helper::runtime::register_wasm_input_foreign;
+    use crate::runtime::host::host_env::HostEnv;
+    use crate::runtime::ExecutionResult;
+    use crate::test::test_circuit_with_env;
+
+    /// Writes the pairs of keys and values given as private inputs, preceded by their
+    /// number, then reads the keys given as private inputs, preceded by their number, and
+    /// requires each value read to be the next public input.
+    const GUEST: &str = r#"
+        (module
+            (import "env" "wasm_input" (func $wasm_input (param i32) (result i64)))
+            (import "env" "require" (func $require (param i32)))
+            (import "env" "kv_get" (func $kv_get (param i64) (result i64)))
+            (import "env" "kv_set" (func $kv_set (param i64 i64)))
+            (export "main" (func $main))
+            (func $main (; 4 ;)
+                (local $n i64)
+                (local.set $n (call $wasm_input (i32.const 0)))
+                (block
+                    (loop
+                        (br_if 1 (i64.eqz (local.get $n)))
+                        (call $kv_set (call $wasm_input (i32.const 0)) (call $wasm_input (i32.const 0)))
+                        (local.set $n (i64.sub (local.get $n) (i64.const 1)))
+                        (br 0)
+                    )
+                )
+                (local.set $n (call $wasm_input (i32.const 0)))
+                (block
+                    (loop
+                        (br_if 1 (i64.eqz (local.get $n)))
+                        (call $require (i64.eq (call $kv_get (call $wasm_input (i32.const 0))) (call $wasm_input (i32.const 1))))
+                        (local.set $n (i64.sub (local.get $n) (i64.const 1)))
+                        (br 0)
+                    )
+                )
+            )
+        )
+    "#;
+
+    fn shared(store: impl KvStore + 'static) -> SharedKvStore {
+        Arc::new(Mutex::new(Box::new(store)))
+    }
+
+    /// Runs the guest on `store`, `reads` are the keys read with the values they are
+    /// expected to have.
+    fn run_kv_plugin(
+        store: SharedKvStore,
+        writes: &[(u64, u64)],
+        reads: &[(u64, u64)],
+    ) -> Result<ExecutionResult<RuntimeValue>> {
+        let mut private_inputs = vec![writes.len() as u64];
+        for (key, value) in writes {
+            private_inputs.push(*key);
+            private_inputs.push(*value);
+        }
+        private_inputs.push(reads.len() as u64);
+        private_inputs.extend(reads.iter().map(|(key, _)| *key));
+
+        let public_inputs = reads.iter().map(|(_, value)| *value).collect();
+
+        let wasm = wabt::wat2wasm(GUEST).expect("failed to parse wat");
+
+        let mut env = HostEnv::new();
+        let wasm_runtime_io = register_wasm_input_foreign(&mut env, public_inputs, private_inputs);
+        register_require_foreign(&mut env);
+        register_kv_foreign(&mut env, store, &wasm_runtime_io);
+        env.finalize();
+
+        test_circuit_with_env(env, wasm_runtime_io, wasm, "main")
+    }
+
+    /// Runs the guest on `store` and returns its KV table.
+    fn test_kv_plugin(
+        store: SharedKvStore,
+        writes: &[(u64, u64)],
+        reads: &[(u64, u64)],
+    ) -> KvTable {
+        let execution_result = run_kv_plugin(store, writes, reads).unwrap();
+
+        execution_result.tables.execution_tables.kvtable
+    }
+
+    #[test]
+    fn test_kv_plugin_roundtrip() {
+        let kvtable = test_kv_plugin(
+            shared(MemoryKvStore::default()),
+            &[(1, 10), (7, 70), (1, 11), (u32::MAX as u64, u64::MAX)],
+            &[(1, 11), (7, 70), (u32::MAX as u64, u64::MAX)],
+        );
+
+        assert_eq!(kvtable.accesses.len(), 7);
+        assert_ne!(kvtable.root, kvtable.final_root());
+    }
+
+    #[test]
+    fn test_kv_plugin_absent_key() {
+        let kvtable = test_kv_plugin(
+            shared(MemoryKvStore::default()),
+            &[(3, 30)],
+            &[(4, 0), (3, 30), (0, 0)],
+        );
+        assert_ne!(kvtable.root, kvtable.final_root());
+
+        let kvtable = test_kv_plugin(shared(MemoryKvStore::default()), &[], &[(5, 0)]);
+        assert_eq!(kvtable.root, kvtable.final_root());
+    }
+
+    #[test]
+    fn test_kv_plugin_key_out_of_tree() {
+        let store = shared(MemoryKvStore::default());
+
+        // The execution traps instead of panicking the host.
+        assert!(run_kv_plugin(store.clone(), &[(1 << 32, 1)], &[]).is_err());
+        assert!(run_kv_plugin(store, &[], &[(u64::MAX, 0)]).is_err());
+    }
+
+    #[test]
+    fn test_kv_plugin_file_store() {
+        let path = std::env::temp_dir().join(format!("zkwasm-kv-{}.json", uuid::Uuid::new_v4()));
+
+        let store = shared(FileKvStore::open(path.clone()).unwrap());
+        let first = test_kv_plugin(store.clone(), &[(2, 20), (9, 90)], &[]);
+
+        // The writes are not persisted until they are committed.
+        assert!(!path.exists());
+        let uncommitted = test_kv_plugin(store.clone(), &[], &[(2, 0)]);
+        assert_eq!(uncommitted.root, first.root);
+
+        commit(store.lock().unwrap().as_mut(), &first).unwrap();
+
+        let store = shared(FileKvStore::open(path.clone()).unwrap());
+        let second = test_kv_plugin(store.clone(), &[(9, 91)], &[(2, 20), (9, 91), (3, 0)]);
+
+        // The second execution starts from the state the first one leaves behind.
+        assert_eq!(second.root, first.final_root());
+
+        // The first execution does not start from the state the store is in.
+        assert!(commit(store.lock().unwrap().as_mut(), &first).is_err());
+        commit(store.lock().unwrap().as_mut(), &second).unwrap();
+
+        std::fs::remove_file(path).unwrap();
+    }
+}
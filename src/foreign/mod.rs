@@ -8,9 +8,15 @@ use halo2_proofs::plonk::ConstraintSystem;
 use halo2_proofs::plonk::Expression;
 use halo2_proofs::plonk::VirtualCells;
 
+pub mod babyjubjub_helper;
+mod etable_op_configure;
 pub mod keccak_helper;
+pub mod kv_helper;
 pub mod log_helper;
+pub mod poseidon_helper;
 pub mod require_helper;
+pub mod sha256_helper;
+mod stream_helper;
 pub mod wasm_input_helper;
 
 pub trait ForeignTableConfig<F: FieldExt> {
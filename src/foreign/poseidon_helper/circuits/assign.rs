@@ -0,0 +1,434 @@
+use halo2_proofs::arithmetic::FieldExt;
+use halo2_proofs::circuit::AssignedCell;
+use halo2_proofs::circuit::Layouter;
+use halo2_proofs::circuit::Region;
+use halo2_proofs::plonk::Error;
+use num_bigint::BigUint;
+use specs::etable::EventTableEntry;
+use specs::step::StepInfo;
+
+use super::encode_call;
+use super::PoseidonHelperTableConfig;
+use super::PUSH_ROW;
+use super::SLOT_ROWS;
+use super::STATE_ROW;
+use crate::circuits::poseidon::primitives::p128pow5t9::RATE;
+use crate::circuits::poseidon::primitives::p128pow5t9::WIDTH;
+use crate::circuits::poseidon::primitives::P128Pow5T9;
+use crate::circuits::poseidon::PoseidonInstructions;
+use crate::circuits::poseidon::Pow5Chip;
+use crate::circuits::poseidon::StateWord;
+use crate::circuits::utils::bn_to_field;
+use crate::foreign::poseidon_helper::absorb;
+use crate::foreign::poseidon_helper::decompose;
+use crate::foreign::poseidon_helper::initial_state;
+use crate::foreign::poseidon_helper::top_limb_bound;
+use crate::foreign::poseidon_helper::Op;
+use crate::foreign::poseidon_helper::Permutation;
+use crate::foreign::poseidon_helper::FINALIZE_CALLS;
+use crate::foreign::poseidon_helper::LIMBS;
+use crate::foreign::poseidon_helper::PUSH_CALLS;
+
+struct Slot {
+    is_new: bool,
+    is_final: bool,
+    length: u64,
+    block: [u64; PUSH_CALLS],
+}
+
+/// The rows of an entry of the table, an absorbed block, which also bound the rows of
+/// its permutation by the Pow5 chip.
+pub const ENTRY_ROWS: usize = SLOT_ROWS;
+
+/// The blocks the calls of `entries` absorb.
+pub(crate) fn table_entries(entries: &[EventTableEntry]) -> usize {
+    slots(entries).len()
+}
+
+/// The slots of the calls of `entries`, the steps of the plugin in the order of execution.
+fn slots(entries: &[EventTableEntry]) -> Vec<Slot> {
+    let mut slots: Vec<Slot> = vec![];
+
+    let mut is_new = false;
+    let mut length = 0;
+    let mut block = vec![];
+
+    for entry in entries {
+        match &entry.step_info {
+            StepInfo::CallHost {
+                op_index_in_plugin,
+                args,
+                ..
+            } => match Op::from_index(*op_index_in_plugin) {
+                Op::New => {
+                    is_new = true;
+                    length = args[0];
+                }
+                Op::Push => {
+                    block.push(args[0]);
+
+                    if block.len() == PUSH_CALLS {
+                        slots.push(Slot {
+                            is_new,
+                            is_final: false,
+                            length,
+                            block: std::mem::take(&mut block).try_into().unwrap(),
+                        });
+
+                        is_new = false;
+                    }
+                }
+                // The calls of `poseidon_finalize` read the output of the last block.
+                Op::Finalize => slots.last_mut().unwrap().is_final = true,
+            },
+            _ => unreachable!(),
+        }
+    }
+
+    // The calls of the last block may follow the last step, they are left out of the
+    // lookups of the event table.
+    if is_new || !block.is_empty() {
+        block.resize(PUSH_CALLS, 0);
+
+        slots.push(Slot {
+            is_new,
+            is_final: false,
+            length,
+            block: block.try_into().unwrap(),
+        });
+    }
+
+    slots
+}
+
+/// The states of a slot, `start` is permuted into `output`.
+struct SlotState<F: FieldExt> {
+    prev: [F; WIDTH],
+    start: [F; WIDTH],
+    output: [F; WIDTH],
+}
+
+/// The cells of a slot the permutations are copy constrained to.
+struct SlotCells<F: FieldExt> {
+    prev: Vec<AssignedCell<F, F>>,
+    start: Vec<AssignedCell<F, F>>,
+    digest: AssignedCell<F, F>,
+}
+
+pub struct PoseidonHelperTableChip<F: FieldExt> {
+    config: PoseidonHelperTableConfig<F>,
+    max_available_rows: usize,
+}
+
+impl<F: FieldExt> PoseidonHelperTableChip<F> {
+    pub fn new(config: PoseidonHelperTableConfig<F>, max_available_rows: usize) -> Self {
+        Self {
+            config,
+            max_available_rows,
+        }
+    }
+
+    fn assign_fixed(&self, region: &mut Region<'_, F>, slot: usize) -> Result<(), Error> {
+        let mut assign = |name, column, row: usize, value: F| {
+            region.assign_fixed(
+                || format!("poseidon helper {}", name),
+                column,
+                slot * SLOT_ROWS + row,
+                || Ok(value),
+            )
+        };
+
+        for row in 0..SLOT_ROWS {
+            assign("sel", self.config.sel, row, F::one())?;
+            assign(
+                "bit weight",
+                self.config.bit_weight,
+                row,
+                F::from(1u64 << row),
+            )?;
+        }
+
+        assign("sel slot", self.config.sel_slot, 0, F::one())?;
+        if slot > 0 {
+            assign("sel chain", self.config.sel_chain, 0, F::one())?;
+        }
+
+        for i in 0..PUSH_CALLS + FINALIZE_CALLS {
+            let row = PUSH_ROW + i;
+
+            if i < PUSH_CALLS {
+                assign("sel push", self.config.sel_push, row, F::one())?;
+            } else {
+                assign("sel finalize", self.config.sel_finalize, row, F::one())?;
+            }
+
+            if i % LIMBS == 0 {
+                assign("sel first limb", self.config.sel_first_limb, row, F::one())?;
+            }
+            assign(
+                "limb weight",
+                self.config.limb_weight,
+                row,
+                bn_to_field(&(BigUint::from(1u64) << (64 * (i % LIMBS)))),
+            )?;
+        }
+
+        for i in 0..RATE {
+            assign("sel rate", self.config.sel_rate, STATE_ROW + i, F::one())?;
+        }
+        assign(
+            "sel capacity",
+            self.config.sel_capacity,
+            STATE_ROW + RATE,
+            F::one(),
+        )?;
+
+        assign(
+            "sel last bit",
+            self.config.sel_last_bit,
+            SLOT_ROWS - 1,
+            F::one(),
+        )?;
+
+        Ok(())
+    }
+
+    /// Assigns `slot`, or a disabled slot if `None`, as the slot `index`, returns its cells.
+    fn assign_slot(
+        &self,
+        region: &mut Region<'_, F>,
+        index: usize,
+        slot: Option<&Slot>,
+        state: &SlotState<F>,
+        call_index: &mut u64,
+    ) -> Result<SlotCells<F>, Error> {
+        let start = index * SLOT_ROWS;
+
+        let enable = slot.is_some();
+        let is_new = slot.map_or(false, |slot| slot.is_new);
+        let is_final = slot.map_or(false, |slot| slot.is_final);
+        let length = slot.map_or(0, |slot| slot.length);
+        let block = slot.map_or([0; PUSH_CALLS], |slot| slot.block);
+        let digest = decompose(&state.output[0]);
+
+        let slack = if is_final {
+            top_limb_bound::<F>()
+                .checked_sub(digest[LIMBS - 1] + 1)
+                .expect("poseidon helper table: the top limb of a digest is the one of the modulus")
+        } else {
+            0
+        };
+
+        let mut words = vec![];
+        let mut word = F::zero();
+        let mut bit_acc = 0u64;
+
+        for row in 0..SLOT_ROWS {
+            let offset = start + row;
+
+            let mut assign_advice = |name, column, value: F| {
+                region.assign_advice(
+                    || format!("poseidon helper {}", name),
+                    column,
+                    offset,
+                    || Ok(value),
+                )
+            };
+
+            assign_advice("enable", self.config.enable, F::from(enable as u64))?;
+            assign_advice("is new", self.config.is_new, F::from(is_new as u64))?;
+            assign_advice("is final", self.config.is_final, F::from(is_final as u64))?;
+
+            let bit = (slack >> row) & 1;
+            bit_acc += bit << row;
+            assign_advice("bit", self.config.bit, F::from(bit))?;
+            assign_advice("bit acc", self.config.bit_acc, F::from(bit_acc))?;
+
+            let call = if row == 0 {
+                assign_advice("limb", self.config.limb, F::from(length))?;
+
+                is_new.then(|| (Op::New, length))
+            } else if (PUSH_ROW..STATE_ROW).contains(&row) {
+                let i = row - PUSH_ROW;
+                let (op, limb) = if i < PUSH_CALLS {
+                    (Op::Push, block[i])
+                } else {
+                    (Op::Finalize, digest[i - PUSH_CALLS])
+                };
+
+                let weighted: F = bn_to_field(&(BigUint::from(limb) << (64 * (i % LIMBS))));
+                word = if i % LIMBS == 0 {
+                    weighted
+                } else {
+                    word + weighted
+                };
+
+                assign_advice("limb", self.config.limb, F::from(limb))?;
+                let cell = assign_advice("word", self.config.word, word)?;
+                if i % LIMBS == LIMBS - 1 {
+                    words.push(cell);
+                }
+
+                let is_called = match op {
+                    Op::Push => enable,
+                    _ => is_final,
+                };
+                is_called.then(|| (op, limb))
+            } else {
+                None
+            };
+
+            assign_advice("call index", self.config.call_index, F::from(*call_index))?;
+
+            if let Some((op, value)) = call {
+                assign_advice("call enable", self.config.call_enable, F::one())?;
+                assign_advice(
+                    "lookup index",
+                    self.config.lookup_index,
+                    F::from(*call_index),
+                )?;
+                assign_advice(
+                    "call encoded",
+                    self.config.call_encoded,
+                    encode_call(op, value),
+                )?;
+
+                *call_index += 1;
+            }
+        }
+
+        let mut prev = vec![];
+        let mut start_cells = vec![];
+        for i in 0..WIDTH {
+            let offset = start + STATE_ROW + i;
+
+            if i < RATE {
+                words[i].copy_advice(
+                    || "poseidon helper message",
+                    region,
+                    self.config.message,
+                    offset,
+                )?;
+            }
+
+            prev.push(region.assign_advice(
+                || "poseidon helper prev",
+                self.config.prev,
+                offset,
+                || Ok(state.prev[i]),
+            )?);
+            start_cells.push(region.assign_advice(
+                || "poseidon helper start",
+                self.config.start,
+                offset,
+                || Ok(state.start[i]),
+            )?);
+        }
+
+        Ok(SlotCells {
+            prev,
+            start: start_cells,
+            digest: words.pop().unwrap(),
+        })
+    }
+
+    /// Assigns the calls of `entries`, the steps of the plugin in the event table.
+    pub fn assign(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        entries: &[EventTableEntry],
+    ) -> Result<(), Error> {
+        let capability = self.max_available_rows / ENTRY_ROWS;
+        let slots = slots(entries);
+        if slots.len() > capability {
+            return Err(Error::Synthesis);
+        }
+
+        // Every slot is permuted so that the permutations are the same for any execution,
+        // a disabled slot permutes the output of the previous one.
+        let permutation = Permutation::new();
+        let mut prev = [F::zero(); WIDTH];
+        let states = (0..capability)
+            .map(|index| {
+                let start = match slots.get(index) {
+                    Some(slot) if slot.is_new => absorb(&initial_state(slot.length), &slot.block),
+                    Some(slot) => absorb(&prev, &slot.block),
+                    None => prev,
+                };
+                let output = permutation.permute(&start);
+
+                let state = SlotState {
+                    prev,
+                    start,
+                    output,
+                };
+                prev = output;
+
+                state
+            })
+            .collect::<Vec<_>>();
+
+        let cells = layouter.assign_region(
+            || "poseidon helper table",
+            |mut region| {
+                for slot in 0..capability {
+                    self.assign_fixed(&mut region, slot)?;
+                }
+
+                let mut call_index = 0;
+                states
+                    .iter()
+                    .enumerate()
+                    .map(|(index, state)| {
+                        self.assign_slot(
+                            &mut region,
+                            index,
+                            slots.get(index),
+                            state,
+                            &mut call_index,
+                        )
+                    })
+                    .collect::<Result<Vec<_>, Error>>()
+            },
+        )?;
+
+        let chip = Pow5Chip::construct(self.config.pow5_config.clone());
+        let outputs = cells
+            .iter()
+            .map(|cells| {
+                let start = cells
+                    .start
+                    .iter()
+                    .cloned()
+                    .map(StateWord::from)
+                    .collect::<Vec<_>>()
+                    .try_into()
+                    .unwrap();
+
+                <Pow5Chip<F, WIDTH, RATE> as PoseidonInstructions<
+                    F,
+                    P128Pow5T9<F>,
+                    WIDTH,
+                    RATE,
+                >>::permute(&chip, layouter, &start)
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+
+        layouter.assign_region(
+            || "poseidon helper permutations",
+            |mut region| {
+                for (index, output) in outputs.iter().enumerate() {
+                    region.constrain_equal(output[0].0.cell(), cells[index].digest.cell())?;
+
+                    if let Some(next) = cells.get(index + 1) {
+                        for (word, prev) in output.iter().zip(next.prev.iter()) {
+                            region.constrain_equal(word.0.cell(), prev.cell())?;
+                        }
+                    }
+                }
+
+                Ok(())
+            },
+        )
+    }
+}
@@ -0,0 +1,251 @@
+use halo2_proofs::arithmetic::FieldExt;
+use halo2_proofs::plonk::ConstraintSystem;
+use halo2_proofs::plonk::Expression;
+use halo2_proofs::plonk::VirtualCells;
+use num_bigint::BigUint;
+
+use crate::circuits::poseidon::primitives::p128pow5t9::RATE;
+use crate::circuits::poseidon::primitives::p128pow5t9::WIDTH;
+use crate::circuits::poseidon::primitives::P128Pow5T9;
+use crate::circuits::poseidon::Pow5Chip;
+use crate::circuits::utils::bn_to_field;
+use crate::constant_from;
+use crate::constant_from_bn;
+use crate::curr;
+use crate::fixed_curr;
+use crate::foreign::poseidon_helper::top_limb_bound;
+use crate::foreign::poseidon_helper::Op;
+use crate::foreign::ForeignTableConfig;
+use crate::nextn;
+
+use super::PoseidonHelperTableConfig;
+use super::DIGEST_ROW;
+use super::SLOT_ROWS;
+use super::STATE_ROW;
+
+fn tag<F: FieldExt>(op: Op) -> Expression<F> {
+    constant_from_bn!(&(BigUint::from(op.tag()) << 64))
+}
+
+impl<F: FieldExt> PoseidonHelperTableConfig<F> {
+    pub fn configure(meta: &mut ConstraintSystem<F>) -> Self {
+        let sel = meta.fixed_column();
+        let sel_slot = meta.fixed_column();
+        let sel_chain = meta.fixed_column();
+        let sel_push = meta.fixed_column();
+        let sel_finalize = meta.fixed_column();
+        let sel_first_limb = meta.fixed_column();
+        let limb_weight = meta.fixed_column();
+        let sel_rate = meta.fixed_column();
+        let sel_capacity = meta.fixed_column();
+        let bit_weight = meta.fixed_column();
+        let sel_last_bit = meta.fixed_column();
+
+        let enable = meta.advice_column();
+        let is_new = meta.advice_column();
+        let is_final = meta.advice_column();
+
+        let limb = meta.advice_column();
+        let word = meta.advice_column();
+        let message = meta.advice_column();
+        let prev = meta.advice_column();
+        let start = meta.advice_column();
+        let bit = meta.advice_column();
+        let bit_acc = meta.advice_column();
+
+        let call_enable = meta.advice_column();
+        let call_index = meta.advice_column();
+        let lookup_index = meta.advice_column();
+        let call_encoded = meta.advice_column();
+
+        for column in [word, message, prev, start] {
+            meta.enable_equality(column);
+        }
+
+        let state = [(); WIDTH].map(|_| meta.advice_column());
+        let partial_sbox = meta.advice_column();
+        let mid_0_helper = meta.advice_column();
+        let mid_0_helper_sqr = meta.advice_column();
+        let cur_0_rc_a0 = meta.advice_column();
+        let cur_0_rc_a0_sqr = meta.advice_column();
+        let state_rc_a = [(); WIDTH].map(|_| meta.advice_column());
+        let state_rc_a_sqr = [(); WIDTH].map(|_| meta.advice_column());
+        let rc_a = [(); WIDTH].map(|_| meta.fixed_column());
+        let rc_b = [(); WIDTH].map(|_| meta.fixed_column());
+
+        let pow5_config = Pow5Chip::configure::<P128Pow5T9<F>>(
+            meta,
+            state,
+            state_rc_a,
+            state_rc_a_sqr,
+            partial_sbox,
+            mid_0_helper,
+            mid_0_helper_sqr,
+            cur_0_rc_a0,
+            cur_0_rc_a0_sqr,
+            rc_a,
+            rc_b,
+        );
+
+        meta.create_gate("poseidon helper: slot", |meta| {
+            let enable_curr = curr!(meta, enable);
+            let is_new_curr = curr!(meta, is_new);
+            let is_final_curr = curr!(meta, is_final);
+            let sel_chain = fixed_curr!(meta, sel_chain);
+
+            vec![
+                enable_curr.clone() * (constant_from!(1) - enable_curr.clone()),
+                is_new_curr.clone() * (constant_from!(1) - is_new_curr.clone()),
+                is_final_curr.clone() * (constant_from!(1) - is_final_curr.clone()),
+                (is_new_curr.clone() + is_final_curr) * (constant_from!(1) - enable_curr.clone()),
+                // The enabled slots precede the others.
+                sel_chain.clone()
+                    * (constant_from!(1) - nextn!(meta, enable, -(SLOT_ROWS as i32)))
+                    * enable_curr.clone(),
+                // A digest continues after a block that is not its last one.
+                sel_chain.clone()
+                    * enable_curr.clone()
+                    * (constant_from!(1) - is_new_curr.clone())
+                    * nextn!(meta, is_final, -(SLOT_ROWS as i32)),
+                (constant_from!(1) - sel_chain) * enable_curr * (constant_from!(1) - is_new_curr),
+            ]
+            .into_iter()
+            .map(|expr| expr * fixed_curr!(meta, sel_slot))
+            .collect::<Vec<_>>()
+        });
+
+        meta.create_gate("poseidon helper: slot flags", |meta| {
+            vec![
+                curr!(meta, enable) - nextn!(meta, enable, -1),
+                curr!(meta, is_new) - nextn!(meta, is_new, -1),
+                curr!(meta, is_final) - nextn!(meta, is_final, -1),
+            ]
+            .into_iter()
+            .map(|expr| expr * (fixed_curr!(meta, sel) - fixed_curr!(meta, sel_slot)))
+            .collect::<Vec<_>>()
+        });
+
+        meta.create_gate("poseidon helper: words", |meta| {
+            vec![
+                (curr!(meta, word)
+                    - curr!(meta, limb) * fixed_curr!(meta, limb_weight)
+                    - (constant_from!(1) - fixed_curr!(meta, sel_first_limb))
+                        * nextn!(meta, word, -1))
+                    * (fixed_curr!(meta, sel_push) + fixed_curr!(meta, sel_finalize)),
+            ]
+        });
+
+        meta.create_gate("poseidon helper: state", |meta| {
+            let chained = (constant_from!(1) - curr!(meta, is_new)) * curr!(meta, prev);
+            // The length given to `poseidon_new` on the first row of the slot.
+            let length = nextn!(meta, limb, -((STATE_ROW + RATE) as i32));
+
+            vec![
+                (curr!(meta, start) - chained.clone() - curr!(meta, message))
+                    * fixed_curr!(meta, sel_rate),
+                (curr!(meta, start)
+                    - chained
+                    - curr!(meta, is_new)
+                        * length
+                        * constant_from_bn!(&(BigUint::from(1u64) << 64)))
+                    * fixed_curr!(meta, sel_capacity),
+            ]
+        });
+
+        meta.create_gate("poseidon helper: digest bound", |meta| {
+            let bit = curr!(meta, bit);
+            let top_limb = nextn!(meta, limb, DIGEST_ROW as i32 - (SLOT_ROWS as i32 - 1));
+
+            vec![
+                bit.clone() * (constant_from!(1) - bit.clone()) * fixed_curr!(meta, sel),
+                (curr!(meta, bit_acc)
+                    - bit * fixed_curr!(meta, bit_weight)
+                    - (constant_from!(1) - fixed_curr!(meta, sel_slot))
+                        * nextn!(meta, bit_acc, -1))
+                    * fixed_curr!(meta, sel),
+                (curr!(meta, bit_acc)
+                    - curr!(meta, is_final)
+                        * (constant_from!(top_limb_bound::<F>() - 1) - top_limb))
+                    * fixed_curr!(meta, sel_last_bit),
+            ]
+        });
+
+        meta.create_gate("poseidon helper: calls", |meta| {
+            let limb = curr!(meta, limb);
+
+            let is_new_call = fixed_curr!(meta, sel_slot) * curr!(meta, is_new);
+            let is_push_call = fixed_curr!(meta, sel_push) * curr!(meta, enable);
+            let is_finalize_call = fixed_curr!(meta, sel_finalize) * curr!(meta, is_final);
+
+            let call_enable_expr =
+                is_new_call.clone() + is_push_call.clone() + is_finalize_call.clone();
+            let call_encoded_expr = is_new_call * (tag(Op::New) + limb.clone())
+                + is_push_call * (tag(Op::Push) + limb.clone())
+                + is_finalize_call * (tag(Op::Finalize) + limb);
+
+            let is_first_row =
+                fixed_curr!(meta, sel_slot) * (constant_from!(1) - fixed_curr!(meta, sel_chain));
+
+            vec![
+                curr!(meta, call_enable) - call_enable_expr,
+                curr!(meta, call_encoded) - call_encoded_expr,
+                curr!(meta, lookup_index) - curr!(meta, call_enable) * curr!(meta, call_index),
+                is_first_row.clone() * curr!(meta, call_index),
+                (constant_from!(1) - is_first_row)
+                    * (curr!(meta, call_index)
+                        - nextn!(meta, call_index, -1)
+                        - nextn!(meta, call_enable, -1)),
+            ]
+            .into_iter()
+            .map(|expr| expr * fixed_curr!(meta, sel))
+            .collect::<Vec<_>>()
+        });
+
+        PoseidonHelperTableConfig {
+            sel,
+            sel_slot,
+            sel_chain,
+            sel_push,
+            sel_finalize,
+            sel_first_limb,
+            limb_weight,
+            sel_rate,
+            sel_capacity,
+            bit_weight,
+            sel_last_bit,
+            enable,
+            is_new,
+            is_final,
+            limb,
+            word,
+            message,
+            prev,
+            start,
+            bit,
+            bit_acc,
+            call_enable,
+            call_index,
+            lookup_index,
+            call_encoded,
+            pow5_config,
+        }
+    }
+}
+
+impl<F: FieldExt> ForeignTableConfig<F> for PoseidonHelperTableConfig<F> {
+    fn configure_in_table(
+        &self,
+        meta: &mut ConstraintSystem<F>,
+        key: &'static str,
+        expr: &dyn Fn(&mut VirtualCells<'_, F>) -> Vec<Expression<F>>,
+    ) {
+        meta.lookup_any(key, |meta| {
+            let mut exprs = expr(meta);
+
+            vec![
+                (exprs.remove(0), curr!(meta, self.lookup_index)),
+                (exprs.remove(0), curr!(meta, self.call_encoded)),
+            ]
+        });
+    }
+}
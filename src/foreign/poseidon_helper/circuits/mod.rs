@@ -0,0 +1,93 @@
+//! The Poseidon table.
+//!
+//! The table is a sequence of slots of `SLOT_ROWS` rows, a slot absorbs a block and has its
+//! state permuted by the Pow5 chip, outside of the table. Calls of the guest are laid out
+//! in the slots, a call on a row with its limb in `limb`: `poseidon_new` on the first row
+//! of the slot starting a digest, the pushes of the block on the following rows, and the
+//! calls of `poseidon_finalize` on the following ones for the last block of a digest. The
+//! limbs of each element are composed in `word`.
+//!
+//! The state of a slot is laid out on the rows following the calls, a word per row: `prev`
+//! is the output of the permutation of the previous slot, `start` is the state the
+//! permutation of the slot starts from, either `prev` or the initial state of a new digest,
+//! with the block added to its rate. The permutations are copy constrained to `start` and
+//! to `prev` of the next slot, and their first word to the digest of the slot.
+//!
+//! The limbs of a digest are unique as long as its top limb is below the top limb of the
+//! modulus, the bits of the difference are laid out over the rows of the slot in `bit`.
+//! A digest with the top limb of the modulus, a chance of 2^-64, cannot be proven.
+//!
+//! Calls are numbered in the order of the table, the event table looks up each call with
+//! its number among the calls of the plugin, so the calls of the table are exactly the
+//! calls of the execution.
+
+use halo2_proofs::arithmetic::FieldExt;
+use halo2_proofs::plonk::Advice;
+use halo2_proofs::plonk::Column;
+use halo2_proofs::plonk::Fixed;
+
+use crate::circuits::poseidon::primitives::p128pow5t9::RATE;
+use crate::circuits::poseidon::primitives::p128pow5t9::WIDTH;
+use crate::circuits::poseidon::Pow5Config;
+
+use super::FINALIZE_CALLS;
+use super::PUSH_CALLS;
+pub(crate) use crate::foreign::stream_helper::encode_call;
+
+pub mod assign;
+pub mod config;
+
+pub const POSEIDON_FOREIGN_TABLE_KEY: &'static str = "poseidon-helper-table";
+
+const LIMB_BITS: usize = 64;
+const PUSH_ROW: usize = 1;
+const FINALIZE_ROW: usize = PUSH_ROW + PUSH_CALLS;
+/// The row the digest is composed on.
+const DIGEST_ROW: usize = FINALIZE_ROW + FINALIZE_CALLS - 1;
+/// The row of the first word of the state.
+const STATE_ROW: usize = FINALIZE_ROW + FINALIZE_CALLS;
+/// The rows of the bits of a limb, the calls and the state of a slot take less.
+const SLOT_ROWS: usize = LIMB_BITS;
+
+#[derive(Clone)]
+pub struct PoseidonHelperTableConfig<F: FieldExt> {
+    /// Enables the rows of the slots.
+    sel: Column<Fixed>,
+    /// Enables the first row of each slot.
+    sel_slot: Column<Fixed>,
+    /// Enables the first row of each slot but the first one.
+    sel_chain: Column<Fixed>,
+    sel_push: Column<Fixed>,
+    sel_finalize: Column<Fixed>,
+    /// Enables the rows of the limb 0 of an element.
+    sel_first_limb: Column<Fixed>,
+    limb_weight: Column<Fixed>,
+    /// Enables the rows of the rate of the state.
+    sel_rate: Column<Fixed>,
+    /// Enables the row of the capacity of the state.
+    sel_capacity: Column<Fixed>,
+    bit_weight: Column<Fixed>,
+    sel_last_bit: Column<Fixed>,
+
+    enable: Column<Advice>,
+    is_new: Column<Advice>,
+    is_final: Column<Advice>,
+
+    /// The value of the call of the row.
+    limb: Column<Advice>,
+    word: Column<Advice>,
+    /// The word of the block added to the state word of the row.
+    message: Column<Advice>,
+    prev: Column<Advice>,
+    start: Column<Advice>,
+    bit: Column<Advice>,
+    bit_acc: Column<Advice>,
+
+    call_enable: Column<Advice>,
+    /// The number of the calls preceding the row.
+    call_index: Column<Advice>,
+    lookup_index: Column<Advice>,
+    call_encoded: Column<Advice>,
+
+    pow5_config: Pow5Config<F, WIDTH, RATE>,
+}
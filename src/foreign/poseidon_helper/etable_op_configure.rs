@@ -0,0 +1,23 @@
+use specs::host_function::HostPlugin;
+
+use super::circuits::POSEIDON_FOREIGN_TABLE_KEY;
+
+use crate::foreign::stream_helper::ETableStreamHelperTableConfigBuilder;
+use crate::foreign::stream_helper::StreamPlugin;
+
+pub(crate) struct PoseidonPlugin;
+
+impl StreamPlugin for PoseidonPlugin {
+    const PLUGIN: HostPlugin = HostPlugin::Poseidon;
+    const TABLE_KEY: &'static str = POSEIDON_FOREIGN_TABLE_KEY;
+    const NEW_TAKES_VALUE: bool = true;
+
+    const STACK_READ: &'static str = "poseidon stack read";
+    const STACK_WRITE: &'static str = "poseidon stack write";
+    const SEL: &'static str = "poseidon_helper: sel";
+    const LOOKUP_AUX: &'static str = "poseidon_helper: lookup aux";
+    const LOOKUP: &'static str = "lookup poseidon table";
+}
+
+pub(crate) type ETablePoseidonHelperTableConfigBuilder =
+    ETableStreamHelperTableConfigBuilder<PoseidonPlugin>;
@@ -0,0 +1,144 @@
+//! Poseidon digests computed by the host, with the P128Pow5T9 spec of the image checksum.
+//!
+//! A guest hashes a message of field elements as `primitives::Hash` with the domain
+//! `ConstantLength` does: it starts a digest with `poseidon_new`, given the length of the
+//! message, pushes the message padded with zeros to whole blocks of `RATE` elements, an
+//! element as 4 little-endian limbs of 64 bits, with `poseidon_push`, then reads the
+//! digest, the same way, with 4 calls of `poseidon_finalize`. The event table looks each
+//! call up in the Poseidon table, which proves the permutations of the blocks with the
+//! Pow5 chip, see `circuits`.
+
+use halo2_proofs::arithmetic::FieldExt;
+use num_bigint::BigUint;
+use specs::etable::EventTableEntry;
+use specs::host_function::HostPlugin;
+use specs::step::StepInfo;
+
+use crate::circuits::poseidon::primitives::p128pow5t9::RATE;
+use crate::circuits::poseidon::primitives::p128pow5t9::WIDTH;
+use crate::circuits::poseidon::primitives::permute;
+use crate::circuits::poseidon::primitives::Mds;
+use crate::circuits::poseidon::primitives::P128Pow5T9;
+use crate::circuits::poseidon::primitives::Spec;
+use crate::circuits::utils::bn_to_field;
+use crate::circuits::utils::field_to_bn;
+
+pub mod circuits;
+pub mod etable_op_configure;
+pub mod runtime;
+pub mod test;
+
+pub(crate) use crate::foreign::stream_helper::Op;
+
+/// Limbs of 64 bits of a field element.
+pub(crate) const LIMBS: usize = 4;
+/// Calls of `poseidon_push` pushing a block of `RATE` elements.
+pub(crate) const PUSH_CALLS: usize = RATE * LIMBS;
+/// Calls of `poseidon_finalize` reading a digest.
+pub(crate) const FINALIZE_CALLS: usize = LIMBS;
+
+/// The element of the little-endian `limbs`, reduced by the modulus.
+pub(crate) fn compose<F: FieldExt>(limbs: &[u64]) -> F {
+    let value = limbs
+        .iter()
+        .rev()
+        .fold(BigUint::from(0u64), |acc, limb| (acc << 64) + limb);
+
+    bn_to_field(&(value % modulus::<F>()))
+}
+
+/// The little-endian limbs of `value`.
+pub(crate) fn decompose<F: FieldExt>(value: &F) -> [u64; LIMBS] {
+    let mut limbs = field_to_bn(value).to_u64_digits();
+    limbs.resize(LIMBS, 0);
+
+    limbs.try_into().unwrap()
+}
+
+pub(crate) fn modulus<F: FieldExt>() -> BigUint {
+    field_to_bn(&-F::one()) + 1u64
+}
+
+/// The top limb of the modulus, a digest is read with its top limb below it so that the
+/// limbs of a digest are unique.
+pub(crate) fn top_limb_bound<F: FieldExt>() -> u64 {
+    (modulus::<F>() >> (64 * (LIMBS - 1))).to_u64_digits()[0]
+}
+
+/// The state a digest of a message of `length` elements starts from, as with the domain
+/// `ConstantLength`.
+pub(crate) fn initial_state<F: FieldExt>(length: u64) -> [F; WIDTH] {
+    let mut state = [F::zero(); WIDTH];
+    state[RATE] = F::from_u128((length as u128) << 64);
+
+    state
+}
+
+/// The state `block` is absorbed into, the state the permutation of the block starts from.
+pub(crate) fn absorb<F: FieldExt>(state: &[F; WIDTH], block: &[u64; PUSH_CALLS]) -> [F; WIDTH] {
+    let mut state = *state;
+    for (word, limbs) in state.iter_mut().zip(block.chunks(LIMBS)) {
+        *word += compose::<F>(limbs);
+    }
+
+    state
+}
+
+/// The permutation of the spec, with its constants generated once.
+pub(crate) struct Permutation<F: FieldExt> {
+    round_constants: Vec<[F; WIDTH]>,
+    mds: Mds<F, WIDTH>,
+}
+
+impl<F: FieldExt> Permutation<F> {
+    pub(crate) fn new() -> Self {
+        let (round_constants, mds, _) = P128Pow5T9::<F>::constants();
+
+        Permutation {
+            round_constants,
+            mds,
+        }
+    }
+
+    pub(crate) fn permute(&self, state: &[F; WIDTH]) -> [F; WIDTH] {
+        let mut state = *state;
+        permute::<F, P128Pow5T9<F>, WIDTH, RATE>(&mut state, &self.mds, &self.round_constants);
+
+        state
+    }
+}
+
+/// Whether a digest is in progress before each step of `entries` and after the last one.
+///
+/// The table of a circuit proves the digests started by its own steps, so an execution is
+/// not split into segments while a digest is in progress.
+pub(crate) fn digest_in_progress(entries: &[EventTableEntry]) -> Vec<bool> {
+    let mut in_progress = false;
+    let mut finalize_calls = 0;
+
+    let mut status = vec![false];
+    for entry in entries {
+        if let StepInfo::CallHost {
+            plugin: HostPlugin::Poseidon,
+            op_index_in_plugin,
+            ..
+        } = &entry.step_info
+        {
+            match Op::from_index(*op_index_in_plugin) {
+                Op::New => in_progress = true,
+                Op::Push => (),
+                Op::Finalize => {
+                    finalize_calls += 1;
+                    if finalize_calls == FINALIZE_CALLS {
+                        finalize_calls = 0;
+                        in_progress = false;
+                    }
+                }
+            }
+        }
+
+        status.push(in_progress);
+    }
+
+    status
+}
@@ -0,0 +1,180 @@
+use std::rc::Rc;
+
+use halo2_proofs::pairing::bn256::Fr;
+use specs::host_function::HostPlugin;
+use specs::types::ValueType;
+
+use crate::circuits::poseidon::primitives::p128pow5t9::WIDTH;
+use crate::runtime::host::host_env::HostEnv;
+use crate::runtime::host::ForeignContext;
+
+use super::absorb;
+use super::decompose;
+use super::initial_state;
+use super::Op;
+use super::Permutation;
+use super::PUSH_CALLS;
+
+struct Context {
+    permutation: Permutation<Fr>,
+    /// The state of the digest in progress, until it is finalized.
+    state: Option<[Fr; WIDTH]>,
+    blocks: usize,
+    block: Vec<u64>,
+    /// The limbs of the finalized digest left to read.
+    digest: Vec<u64>,
+    error: Option<String>,
+}
+
+impl Context {
+    fn new() -> Self {
+        Context {
+            permutation: Permutation::new(),
+            state: None,
+            blocks: 0,
+            block: vec![],
+            digest: vec![],
+            error: None,
+        }
+    }
+
+    fn is_in_progress(&self) -> bool {
+        self.state.is_some() || !self.digest.is_empty()
+    }
+
+    fn new_digest(&mut self, length: u64) {
+        if self.is_in_progress() {
+            self.error =
+                Some("poseidon_new is called while the previous digest is in progress".to_owned());
+
+            return;
+        }
+
+        self.state = Some(initial_state(length));
+        self.blocks = 0;
+    }
+
+    fn push(&mut self, value: u64) {
+        let state = match self.state.as_mut() {
+            Some(state) => state,
+            None => {
+                self.error =
+                    Some("poseidon_push is called without a digest in progress".to_owned());
+
+                return;
+            }
+        };
+
+        self.block.push(value);
+
+        if self.block.len() == PUSH_CALLS {
+            let block: [u64; PUSH_CALLS] = self.block.clone().try_into().unwrap();
+            *state = self.permutation.permute(&absorb(state, &block));
+
+            self.blocks += 1;
+            self.block.clear();
+        }
+    }
+
+    /// The next limb of the digest, or `None` with the error of the context set if there is
+    /// no digest to read.
+    fn finalize(&mut self) -> Option<u64> {
+        if let Some(state) = self.state.take() {
+            if self.blocks == 0 || !self.block.is_empty() {
+                self.error = Some(
+                    "poseidon_finalize is called on a message that is not padded to whole blocks"
+                        .to_owned(),
+                );
+
+                return None;
+            }
+
+            self.digest = decompose(&state[0]).to_vec();
+        }
+
+        if self.digest.is_empty() {
+            self.error =
+                Some("poseidon_finalize is called without a digest in progress".to_owned());
+
+            return None;
+        }
+
+        Some(self.digest.remove(0))
+    }
+}
+
+impl ForeignContext for Context {
+    fn take_error(&mut self) -> Option<String> {
+        self.error.take()
+    }
+}
+
+pub fn register_poseidon_foreign(env: &mut HostEnv) {
+    let poseidon_new = Rc::new(
+        |context: &mut dyn ForeignContext, args: wasmi::RuntimeArgs| {
+            let context = context.downcast_mut::<Context>().unwrap();
+
+            let length: i64 = args.nth(0);
+            context.new_digest(length as u64);
+
+            None
+        },
+    );
+
+    let poseidon_push = Rc::new(
+        |context: &mut dyn ForeignContext, args: wasmi::RuntimeArgs| {
+            let context = context.downcast_mut::<Context>().unwrap();
+
+            let value: i64 = args.nth(0);
+            context.push(value as u64);
+
+            None
+        },
+    );
+
+    let poseidon_finalize = Rc::new(
+        |context: &mut dyn ForeignContext, _args: wasmi::RuntimeArgs| {
+            let context = context.downcast_mut::<Context>().unwrap();
+
+            context
+                .finalize()
+                .map(|value| wasmi::RuntimeValue::I64(value as i64))
+        },
+    );
+
+    env.internal_env
+        .register_plugin(HostPlugin::Poseidon, Box::new(Context::new()));
+
+    env.internal_env.register_function(
+        "poseidon_new",
+        specs::host_function::Signature {
+            params: vec![ValueType::I64],
+            return_types: vec![],
+        },
+        HostPlugin::Poseidon,
+        Op::New as usize,
+        poseidon_new,
+    );
+
+    env.internal_env.register_function(
+        "poseidon_push",
+        specs::host_function::Signature {
+            params: vec![ValueType::I64],
+            return_types: vec![],
+        },
+        HostPlugin::Poseidon,
+        Op::Push as usize,
+        poseidon_push,
+    );
+
+    env.internal_env.register_function(
+        "poseidon_finalize",
+        specs::host_function::Signature {
+            params: vec![],
+            return_types: vec![ValueType::I64],
+        },
+        HostPlugin::Poseidon,
+        Op::Finalize as usize,
+        poseidon_finalize,
+    );
+}
@@ -0,0 +1,169 @@
+#[cfg(test)]
+mod tests {
+    use crate::circuits::poseidon::primitives::p128pow5t9::RATE;
+    use crate::circuits::poseidon::primitives::p128pow5t9::WIDTH;
+    use crate::circuits::poseidon::primitives::ConstantLength;
+    use crate::circuits::poseidon::primitives::Hash;
+    use crate::circuits::poseidon::primitives::P128Pow5T9;
+    use crate::foreign::poseidon_helper::decompose;
+    use crate::foreign::poseidon_helper::runtime::register_poseidon_foreign;
+    use crate::foreign::require_helper::register_require_foreign;
+    use crate::foreign::wasm_input_helper::runtime::register_wasm_input_foreign;
+    use crate::runtime::host::host_env::HostEnv;
+    use crate::test::test_circuit_with_env;
+    use halo2_proofs::arithmetic::Field;
+    use halo2_proofs::pairing::bn256::Fr;
+
+    /// Hashes `messages` messages given as private inputs, the length of each one, the
+    /// number of its padded limbs and the limbs, and requires each digest to be the next 4
+    /// public inputs.
+    fn guest(messages: usize) -> String {
+        format!(
+            r#"
+        (module
+            (import "env" "wasm_input" (func $wasm_input (param i32) (result i64)))
+            (import "env" "require" (func $require (param i32)))
+            (import "env" "poseidon_new" (func $poseidon_new (param i64)))
+            (import "env" "poseidon_push" (func $poseidon_push (param i64)))
+            (import "env" "poseidon_finalize" (func $poseidon_finalize (result i64)))
+            (export "main" (func $main))
+            (func $digest (; 5 ;)
+                (local $limbs i64)
+                (call $poseidon_new (call $wasm_input (i32.const 0)))
+                (local.set $limbs (call $wasm_input (i32.const 0)))
+                (block
+                    (loop
+                        (br_if 1 (i64.eqz (local.get $limbs)))
+                        (call $poseidon_push (call $wasm_input (i32.const 0)))
+                        (local.set $limbs (i64.sub (local.get $limbs) (i64.const 1)))
+                        (br 0)
+                    )
+                )
+                (call $require (i64.eq (call $poseidon_finalize) (call $wasm_input (i32.const 1))))
+                (call $require (i64.eq (call $poseidon_finalize) (call $wasm_input (i32.const 1))))
+                (call $require (i64.eq (call $poseidon_finalize) (call $wasm_input (i32.const 1))))
+                (call $require (i64.eq (call $poseidon_finalize) (call $wasm_input (i32.const 1))))
+            )
+            (func $main (; 6 ;)
+                (local $messages i32)
+                (local.set $messages (i32.const {}))
+                (block
+                    (loop
+                        (br_if 1 (i32.eqz (local.get $messages)))
+                        (call $digest)
+                        (local.set $messages (i32.sub (local.get $messages) (i32.const 1)))
+                        (br 0)
+                    )
+                )
+            )
+        )
+    "#,
+            messages
+        )
+    }
+
+    fn digest<const L: usize>(message: [Fr; L]) -> (Vec<Fr>, Fr) {
+        let digest =
+            Hash::<Fr, P128Pow5T9<Fr>, ConstantLength<L>, WIDTH, RATE>::init().hash(message);
+
+        (message.to_vec(), digest)
+    }
+
+    /// The limbs of the message padded with zeros to whole blocks.
+    fn padded_limbs(message: &[Fr]) -> Vec<u64> {
+        let mut padded = message.to_vec();
+        padded.resize((padded.len() + RATE - 1) / RATE * RATE, Fr::zero());
+
+        padded.iter().flat_map(decompose::<Fr>).collect()
+    }
+
+    fn test_poseidon_plugin(messages: &[(Vec<Fr>, Fr)]) {
+        let mut private_inputs = vec![];
+        let mut public_inputs = vec![];
+        for (message, digest) in messages {
+            let limbs = padded_limbs(message);
+
+            private_inputs.push(message.len() as u64);
+            private_inputs.push(limbs.len() as u64);
+            private_inputs.extend(limbs);
+            public_inputs.extend(decompose(digest));
+        }
+
+        let wasm = wabt::wat2wasm(&guest(messages.len())).expect("failed to parse wat");
+
+        let mut env = HostEnv::new();
+        let wasm_runtime_io = register_wasm_input_foreign(&mut env, public_inputs, private_inputs);
+        register_require_foreign(&mut env);
+        register_poseidon_foreign(&mut env);
+        env.finalize();
+
+        test_circuit_with_env(env, wasm_runtime_io, wasm, "main").unwrap();
+    }
+
+    #[test]
+    fn test_poseidon_plugin_single_element() {
+        test_poseidon_plugin(&[digest([Fr::from(1)])]);
+    }
+
+    #[test]
+    fn test_poseidon_plugin_full_block() {
+        // Elements of up to 4 limbs, the largest one first.
+        test_poseidon_plugin(&[digest([
+            -Fr::one(),
+            Fr::from(0),
+            Fr::from(u64::MAX),
+            -Fr::from(u64::MAX),
+            Fr::from(2),
+            -Fr::from(3),
+            Fr::from(4),
+            -Fr::from(5),
+        ])]);
+    }
+
+    #[test]
+    fn test_poseidon_plugin_consecutive_digests() {
+        let mut message = [Fr::zero(); 20];
+        for (i, element) in message.iter_mut().enumerate() {
+            *element = Fr::from(i as u64) - Fr::from(10);
+        }
+
+        test_poseidon_plugin(&[
+            digest(message),
+            digest([Fr::from(7), Fr::from(11)]),
+            digest([Fr::from(1)]),
+        ]);
+    }
+
+    /// Misuses of the plugin trap the execution instead of panicking the host.
+    #[test]
+    fn test_poseidon_plugin_misuse() {
+        for body in [
+            "(call $poseidon_push (i64.const 0))",
+            "(drop (call $poseidon_finalize))",
+            "(call $poseidon_new (i64.const 1)) (call $poseidon_new (i64.const 1))",
+            "(call $poseidon_new (i64.const 1)) (call $poseidon_push (i64.const 0)) (drop (call $poseidon_finalize))",
+        ] {
+            let wasm = wabt::wat2wasm(format!(
+                r#"
+            (module
+                (import "env" "poseidon_new" (func $poseidon_new (param i64)))
+                (import "env" "poseidon_push" (func $poseidon_push (param i64)))
+                (import "env" "poseidon_finalize" (func $poseidon_finalize (result i64)))
+                (func (export "main")
+                    {}
+                )
+            )
+            "#,
+                body
+            ))
+            .expect("failed to parse wat");
+
+            let mut env = HostEnv::new();
+            let wasm_runtime_io = register_wasm_input_foreign(&mut env, vec![], vec![]);
+            register_poseidon_foreign(&mut env);
+            env.finalize();
+
+            assert!(test_circuit_with_env(env, wasm_runtime_io, wasm, "main").is_err());
+        }
+    }
+}
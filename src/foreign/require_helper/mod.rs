@@ -35,7 +35,7 @@ pub fn register_require_foreign(env: &mut HostEnv) {
         "require",
         specs::host_function::Signature {
             params: vec![ValueType::I32],
-            return_type: None,
+            return_types: vec![],
         },
         HostPlugin::Require,
         0,
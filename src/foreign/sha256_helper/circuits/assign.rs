@@ -0,0 +1,347 @@
+use halo2_proofs::arithmetic::FieldExt;
+use halo2_proofs::circuit::Layouter;
+use halo2_proofs::circuit::Region;
+use halo2_proofs::plonk::Advice;
+use halo2_proofs::plonk::Column;
+use halo2_proofs::plonk::Error;
+use specs::etable::EventTableEntry;
+use specs::step::StepInfo;
+
+use super::encode_call;
+use super::Sha256HelperTableConfig;
+use super::BLOCK_ROWS;
+use super::STATE_ROWS;
+use super::WORD_BITS;
+use crate::foreign::sha256_helper::digest_words;
+use crate::foreign::sha256_helper::Compression;
+use crate::foreign::sha256_helper::Op;
+use crate::foreign::sha256_helper::FINALIZE_CALLS;
+use crate::foreign::sha256_helper::IV;
+use crate::foreign::sha256_helper::ROUND_CONSTANTS;
+
+struct Slot {
+    is_new: bool,
+    is_final: bool,
+    state: [u32; 8],
+    block: [u32; 16],
+}
+
+/// The rows of an entry of the table, a compressed block.
+pub const ENTRY_ROWS: usize = BLOCK_ROWS;
+
+/// The blocks the calls of `entries` compress.
+pub(crate) fn table_entries(entries: &[EventTableEntry]) -> usize {
+    slots(entries).len()
+}
+
+/// The slots of the calls of `entries`, the steps of the plugin in the order of execution.
+fn slots(entries: &[EventTableEntry]) -> Vec<Slot> {
+    let mut slots = vec![];
+
+    let mut state = IV;
+    let mut is_new = false;
+    let mut block = vec![];
+    let mut finalize_calls = 0;
+
+    for entry in entries {
+        match &entry.step_info {
+            StepInfo::CallHost {
+                op_index_in_plugin,
+                args,
+                ..
+            } => match Op::from_index(*op_index_in_plugin) {
+                Op::New => {
+                    state = IV;
+                    is_new = true;
+                }
+                Op::Push => {
+                    block.push((args[0] >> 32) as u32);
+                    block.push(args[0] as u32);
+
+                    if block.len() == 16 {
+                        let block: [u32; 16] = std::mem::take(&mut block).try_into().unwrap();
+
+                        slots.push(Slot {
+                            is_new,
+                            is_final: false,
+                            state,
+                            block,
+                        });
+
+                        state = Compression::new(&state, &block).output(&state);
+                        is_new = false;
+                    }
+                }
+                Op::Finalize => {
+                    if finalize_calls == 0 {
+                        slots.push(Slot {
+                            is_new: false,
+                            is_final: true,
+                            state,
+                            block: [0; 16],
+                        });
+                    }
+
+                    finalize_calls = (finalize_calls + 1) % FINALIZE_CALLS;
+                }
+            },
+            _ => unreachable!(),
+        }
+    }
+
+    // The calls of the last block may follow the last step, they are left out of the
+    // lookups of the event table.
+    if is_new || !block.is_empty() {
+        block.resize(16, 0);
+
+        slots.push(Slot {
+            is_new,
+            is_final: false,
+            state,
+            block: block.try_into().unwrap(),
+        });
+    }
+
+    slots
+}
+
+pub struct Sha256HelperTableChip<F: FieldExt> {
+    config: Sha256HelperTableConfig<F>,
+    max_available_rows: usize,
+}
+
+impl<F: FieldExt> Sha256HelperTableChip<F> {
+    pub fn new(config: Sha256HelperTableConfig<F>, max_available_rows: usize) -> Self {
+        Self {
+            config,
+            max_available_rows,
+        }
+    }
+
+    fn assign_word(
+        &self,
+        region: &mut Region<'_, F>,
+        columns: &[Column<Advice>],
+        offset: usize,
+        value: u64,
+    ) -> Result<(), Error> {
+        for (i, column) in columns.iter().enumerate() {
+            region.assign_advice(
+                || "sha256 helper bit",
+                *column,
+                offset,
+                || Ok(F::from((value >> i) & 1)),
+            )?;
+        }
+
+        Ok(())
+    }
+
+    fn assign_fixed(&self, region: &mut Region<'_, F>, slot: usize) -> Result<(), Error> {
+        let mut assign = |name, column, row: usize, value: u64| {
+            region.assign_fixed(
+                || format!("sha256 helper {}", name),
+                column,
+                slot * BLOCK_ROWS + row,
+                || Ok(F::from(value)),
+            )
+        };
+
+        for row in 0..BLOCK_ROWS {
+            assign("sel", self.config.sel, row, 1)?;
+        }
+
+        assign("sel slot", self.config.sel_slot, 0, 1)?;
+
+        for row in 0..STATE_ROWS {
+            assign("sel state", self.config.sel_state, row, 1)?;
+            assign("iv a", self.config.iv_a, row, IV[3 - row] as u64)?;
+            assign("iv e", self.config.iv_e, row, IV[7 - row] as u64)?;
+
+            if slot > 0 {
+                assign("sel chain", self.config.sel_chain, row, 1)?;
+            }
+        }
+
+        for (round, constant) in ROUND_CONSTANTS.iter().enumerate() {
+            let row = STATE_ROWS + round;
+
+            assign(
+                "round constant",
+                self.config.round_constant,
+                row,
+                *constant as u64,
+            )?;
+
+            if round >= 16 {
+                assign("sel schedule", self.config.sel_schedule, row, 1)?;
+            } else if round % 2 == 1 {
+                assign("sel push", self.config.sel_push, row, 1)?;
+            }
+        }
+
+        for (i, sel) in self.config.sel_finalize.iter().enumerate() {
+            assign("sel finalize", *sel, 1 + i, 1)?;
+        }
+
+        Ok(())
+    }
+
+    /// Assigns `slot` as the slot `index` following `previous`, returns the number of the
+    /// calls up to the end of the slot.
+    fn assign_slot(
+        &self,
+        region: &mut Region<'_, F>,
+        index: usize,
+        slot: &Slot,
+        previous: Option<&Compression>,
+        mut call_index: u64,
+    ) -> Result<(Compression, u64), Error> {
+        let compression = Compression::new(&slot.state, &slot.block);
+        let digest = digest_words(&slot.state);
+
+        for row in 0..BLOCK_ROWS {
+            let offset = index * BLOCK_ROWS + row;
+
+            let mut assign_advice = |name, column, value: F| {
+                region.assign_advice(
+                    || format!("sha256 helper {}", name),
+                    column,
+                    offset,
+                    || Ok(value),
+                )
+            };
+
+            assign_advice("enable", self.config.enable, F::one())?;
+            assign_advice("is new", self.config.is_new, F::from(slot.is_new as u64))?;
+            assign_advice(
+                "is final",
+                self.config.is_final,
+                F::from(slot.is_final as u64),
+            )?;
+
+            let (carry_a, carry_e) = if row < STATE_ROWS {
+                match previous {
+                    Some(previous) if !slot.is_new => (
+                        (previous.a[row] as u64 + previous.a[BLOCK_ROWS - STATE_ROWS + row] as u64)
+                            >> 32,
+                        (previous.e[row] as u64 + previous.e[BLOCK_ROWS - STATE_ROWS + row] as u64)
+                            >> 32,
+                    ),
+                    _ => (0, 0),
+                }
+            } else {
+                let round = row - STATE_ROWS;
+
+                self.assign_word(region, &self.config.w, offset, compression.w[round] as u64)?;
+                self.assign_word(
+                    region,
+                    &self.config.carry_w,
+                    offset,
+                    compression.carry_w[round],
+                )?;
+
+                (compression.carry_a[round], compression.carry_e[round])
+            };
+
+            self.assign_word(region, &self.config.a, offset, compression.a[row] as u64)?;
+            self.assign_word(region, &self.config.e, offset, compression.e[row] as u64)?;
+            self.assign_word(region, &self.config.carry_a, offset, carry_a)?;
+            self.assign_word(region, &self.config.carry_e, offset, carry_e)?;
+
+            let call = if row == 0 && slot.is_new {
+                Some((Op::New, 0))
+            } else if (1..=FINALIZE_CALLS).contains(&row) && slot.is_final {
+                Some((Op::Finalize, digest[row - 1]))
+            } else if row >= STATE_ROWS
+                && row < STATE_ROWS + 16
+                && (row - STATE_ROWS) % 2 == 1
+                && !slot.is_final
+            {
+                let round = row - STATE_ROWS;
+
+                Some((
+                    Op::Push,
+                    ((compression.w[round - 1] as u64) << WORD_BITS) + compression.w[round] as u64,
+                ))
+            } else {
+                None
+            };
+
+            region.assign_advice(
+                || "sha256 helper call index",
+                self.config.call_index,
+                offset,
+                || Ok(F::from(call_index)),
+            )?;
+
+            if let Some((op, value)) = call {
+                region.assign_advice(
+                    || "sha256 helper call enable",
+                    self.config.call_enable,
+                    offset,
+                    || Ok(F::one()),
+                )?;
+                region.assign_advice(
+                    || "sha256 helper lookup index",
+                    self.config.lookup_index,
+                    offset,
+                    || Ok(F::from(call_index)),
+                )?;
+                region.assign_advice(
+                    || "sha256 helper call encoded",
+                    self.config.call_encoded,
+                    offset,
+                    || Ok(encode_call(op, value)),
+                )?;
+
+                call_index += 1;
+            }
+        }
+
+        Ok((compression, call_index))
+    }
+
+    /// Assigns the calls of `entries`, the steps of the plugin in the event table.
+    pub fn assign(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        entries: &[EventTableEntry],
+    ) -> Result<(), Error> {
+        let capability = self.max_available_rows / ENTRY_ROWS;
+        let slots = slots(entries);
+        if slots.len() > capability {
+            return Err(Error::Synthesis);
+        }
+
+        layouter.assign_region(
+            || "sha256 helper table",
+            |mut region| {
+                for slot in 0..capability {
+                    self.assign_fixed(&mut region, slot)?;
+                }
+
+                let mut previous = None;
+                let mut call_index = 0;
+                for (index, slot) in slots.iter().enumerate() {
+                    let (compression, next_call_index) =
+                        self.assign_slot(&mut region, index, slot, previous.as_ref(), call_index)?;
+
+                    previous = Some(compression);
+                    call_index = next_call_index;
+                }
+
+                for offset in slots.len() * BLOCK_ROWS..capability * BLOCK_ROWS {
+                    region.assign_advice(
+                        || "sha256 helper call index",
+                        self.config.call_index,
+                        offset,
+                        || Ok(F::from(call_index)),
+                    )?;
+                }
+
+                Ok(())
+            },
+        )
+    }
+}
@@ -0,0 +1,382 @@
+use halo2_proofs::arithmetic::FieldExt;
+use halo2_proofs::plonk::Advice;
+use halo2_proofs::plonk::Column;
+use halo2_proofs::plonk::ConstraintSystem;
+use halo2_proofs::plonk::Expression;
+use halo2_proofs::plonk::VirtualCells;
+use num_bigint::BigUint;
+
+use crate::circuits::utils::bn_to_field;
+use crate::constant_from;
+use crate::constant_from_bn;
+use crate::curr;
+use crate::fixed_curr;
+use crate::foreign::sha256_helper::Op;
+use crate::foreign::sha256_helper::FINALIZE_CALLS;
+use crate::foreign::ForeignTableConfig;
+use crate::nextn;
+
+use super::Sha256HelperTableConfig;
+use super::BLOCK_ROWS;
+use super::STATE_ROWS;
+use super::WORD_BITS;
+
+fn bits<F: FieldExt>(
+    meta: &mut VirtualCells<'_, F>,
+    columns: &[Column<Advice>],
+    rotation: i32,
+) -> Vec<Expression<F>> {
+    columns
+        .iter()
+        .map(|column| nextn!(meta, *column, rotation))
+        .collect()
+}
+
+/// The value of little-endian `bits`.
+fn compose<F: FieldExt>(bits: &[Expression<F>]) -> Expression<F> {
+    bits.iter()
+        .enumerate()
+        .fold(constant_from!(0), |acc, (i, bit)| {
+            acc + bit.clone() * constant_from!(1u64 << i)
+        })
+}
+
+fn rotr<F: FieldExt>(bits: &[Expression<F>], n: usize) -> Vec<Expression<F>> {
+    (0..WORD_BITS)
+        .map(|i| bits[(i + n) % WORD_BITS].clone())
+        .collect()
+}
+
+fn shr<F: FieldExt>(bits: &[Expression<F>], n: usize) -> Vec<Expression<F>> {
+    (0..WORD_BITS)
+        .map(|i| {
+            if i + n < WORD_BITS {
+                bits[i + n].clone()
+            } else {
+                constant_from!(0)
+            }
+        })
+        .collect()
+}
+
+fn xor<F: FieldExt>(x: Expression<F>, y: Expression<F>) -> Expression<F> {
+    x.clone() + y.clone() - constant_from!(2) * x * y
+}
+
+/// The value of `x ^ y ^ z`.
+fn xor3<F: FieldExt>(
+    x: Vec<Expression<F>>,
+    y: Vec<Expression<F>>,
+    z: Vec<Expression<F>>,
+) -> Expression<F> {
+    compose(
+        &x.into_iter()
+            .zip(y.into_iter())
+            .zip(z.into_iter())
+            .map(|((x, y), z)| xor(xor(x, y), z))
+            .collect::<Vec<_>>(),
+    )
+}
+
+fn big_sigma0<F: FieldExt>(x: &[Expression<F>]) -> Expression<F> {
+    xor3(rotr(x, 2), rotr(x, 13), rotr(x, 22))
+}
+
+fn big_sigma1<F: FieldExt>(x: &[Expression<F>]) -> Expression<F> {
+    xor3(rotr(x, 6), rotr(x, 11), rotr(x, 25))
+}
+
+fn small_sigma0<F: FieldExt>(x: &[Expression<F>]) -> Expression<F> {
+    xor3(rotr(x, 7), rotr(x, 18), shr(x, 3))
+}
+
+fn small_sigma1<F: FieldExt>(x: &[Expression<F>]) -> Expression<F> {
+    xor3(rotr(x, 17), rotr(x, 19), shr(x, 10))
+}
+
+fn ch<F: FieldExt>(e: &[Expression<F>], f: &[Expression<F>], g: &[Expression<F>]) -> Expression<F> {
+    compose(
+        &(0..WORD_BITS)
+            .map(|i| {
+                e[i].clone() * f[i].clone() + (constant_from!(1) - e[i].clone()) * g[i].clone()
+            })
+            .collect::<Vec<_>>(),
+    )
+}
+
+fn maj<F: FieldExt>(
+    a: &[Expression<F>],
+    b: &[Expression<F>],
+    c: &[Expression<F>],
+) -> Expression<F> {
+    compose(
+        &(0..WORD_BITS)
+            .map(|i| {
+                a[i].clone() * b[i].clone()
+                    + a[i].clone() * c[i].clone()
+                    + b[i].clone() * c[i].clone()
+                    - constant_from!(2) * a[i].clone() * b[i].clone() * c[i].clone()
+            })
+            .collect::<Vec<_>>(),
+    )
+}
+
+fn tag<F: FieldExt>(op: Op) -> Expression<F> {
+    constant_from_bn!(&(BigUint::from(op.tag()) << 64))
+}
+
+impl<F: FieldExt> Sha256HelperTableConfig<F> {
+    pub fn configure(meta: &mut ConstraintSystem<F>) -> Self {
+        let sel = meta.fixed_column();
+        let sel_slot = meta.fixed_column();
+        let sel_state = meta.fixed_column();
+        let sel_chain = meta.fixed_column();
+        let sel_schedule = meta.fixed_column();
+        let sel_push = meta.fixed_column();
+        let sel_finalize = [(); FINALIZE_CALLS].map(|_| meta.fixed_column());
+        let round_constant = meta.fixed_column();
+        let iv_a = meta.fixed_column();
+        let iv_e = meta.fixed_column();
+
+        let enable = meta.advice_column();
+        let is_new = meta.advice_column();
+        let is_final = meta.advice_column();
+
+        let a = [(); WORD_BITS].map(|_| meta.advice_column());
+        let e = [(); WORD_BITS].map(|_| meta.advice_column());
+        let w = [(); WORD_BITS].map(|_| meta.advice_column());
+        let carry_a = [(); 3].map(|_| meta.advice_column());
+        let carry_e = [(); 3].map(|_| meta.advice_column());
+        let carry_w = [(); 2].map(|_| meta.advice_column());
+
+        let call_enable = meta.advice_column();
+        let call_index = meta.advice_column();
+        let lookup_index = meta.advice_column();
+        let call_encoded = meta.advice_column();
+
+        let word_modulus = || constant_from!(1u64 << WORD_BITS);
+
+        meta.create_gate("sha256 helper: bits", |meta| {
+            a.iter()
+                .chain(e.iter())
+                .chain(w.iter())
+                .chain(carry_a.iter())
+                .chain(carry_e.iter())
+                .chain(carry_w.iter())
+                .map(|bit| curr!(meta, *bit) * (constant_from!(1) - curr!(meta, *bit)))
+                .map(|expr| expr * fixed_curr!(meta, sel))
+                .collect::<Vec<_>>()
+        });
+
+        meta.create_gate("sha256 helper: slot", |meta| {
+            let enable_curr = curr!(meta, enable);
+            let is_new_curr = curr!(meta, is_new);
+            let is_final_curr = curr!(meta, is_final);
+            let sel_chain = fixed_curr!(meta, sel_chain);
+
+            vec![
+                enable_curr.clone() * (constant_from!(1) - enable_curr.clone()),
+                is_new_curr.clone() * (constant_from!(1) - is_new_curr.clone()),
+                is_final_curr.clone() * (constant_from!(1) - is_final_curr.clone()),
+                is_new_curr.clone() * is_final_curr.clone(),
+                (is_new_curr.clone() + is_final_curr) * (constant_from!(1) - enable_curr.clone()),
+                // The enabled slots precede the others.
+                sel_chain.clone()
+                    * (constant_from!(1) - nextn!(meta, enable, -(BLOCK_ROWS as i32)))
+                    * enable_curr.clone(),
+                // A digest continues after a block.
+                sel_chain.clone()
+                    * enable_curr.clone()
+                    * (constant_from!(1) - is_new_curr.clone())
+                    * nextn!(meta, is_final, -(BLOCK_ROWS as i32)),
+                (constant_from!(1) - sel_chain) * enable_curr * (constant_from!(1) - is_new_curr),
+            ]
+            .into_iter()
+            .map(|expr| expr * fixed_curr!(meta, sel_slot))
+            .collect::<Vec<_>>()
+        });
+
+        meta.create_gate("sha256 helper: slot flags", |meta| {
+            vec![
+                curr!(meta, enable) - nextn!(meta, enable, -1),
+                curr!(meta, is_new) - nextn!(meta, is_new, -1),
+                curr!(meta, is_final) - nextn!(meta, is_final, -1),
+            ]
+            .into_iter()
+            .map(|expr| expr * (fixed_curr!(meta, sel) - fixed_curr!(meta, sel_slot)))
+            .collect::<Vec<_>>()
+        });
+
+        meta.create_gate("sha256 helper: state", |meta| {
+            let is_new = curr!(meta, is_new);
+            let is_chained = fixed_curr!(meta, sel_chain)
+                * curr!(meta, enable)
+                * (constant_from!(1) - is_new.clone());
+
+            let state_a = compose(&bits(meta, &a, 0));
+            let state_e = compose(&bits(meta, &e, 0));
+
+            // The state following a block is the sum of the state the block starts from and
+            // of the working variables of its last 4 rounds.
+            let chained_a = compose(&bits(meta, &a, -(BLOCK_ROWS as i32)))
+                + compose(&bits(meta, &a, -(STATE_ROWS as i32)));
+            let chained_e = compose(&bits(meta, &e, -(BLOCK_ROWS as i32)))
+                + compose(&bits(meta, &e, -(STATE_ROWS as i32)));
+
+            vec![
+                is_new.clone() * (state_a.clone() - fixed_curr!(meta, iv_a)),
+                is_new * (state_e.clone() - fixed_curr!(meta, iv_e)),
+                is_chained.clone()
+                    * (state_a + compose(&bits(meta, &carry_a, 0)) * word_modulus() - chained_a),
+                is_chained
+                    * (state_e + compose(&bits(meta, &carry_e, 0)) * word_modulus() - chained_e),
+            ]
+            .into_iter()
+            .map(|expr| expr * fixed_curr!(meta, sel_state))
+            .collect::<Vec<_>>()
+        });
+
+        meta.create_gate("sha256 helper: round", |meta| {
+            let is_block = curr!(meta, enable) - curr!(meta, is_final);
+
+            let va = bits(meta, &a, -1);
+            let vb = bits(meta, &a, -2);
+            let vc = bits(meta, &a, -3);
+            let vd = compose(&bits(meta, &a, -4));
+            let ve = bits(meta, &e, -1);
+            let vf = bits(meta, &e, -2);
+            let vg = bits(meta, &e, -3);
+            let vh = compose(&bits(meta, &e, -4));
+
+            let t1 = vh
+                + big_sigma1(&ve)
+                + ch(&ve, &vf, &vg)
+                + fixed_curr!(meta, round_constant)
+                + compose(&bits(meta, &w, 0));
+            let t2 = big_sigma0(&va) + maj(&va, &vb, &vc);
+
+            vec![
+                compose(&bits(meta, &a, 0)) + compose(&bits(meta, &carry_a, 0)) * word_modulus()
+                    - (t1.clone() + t2),
+                compose(&bits(meta, &e, 0)) + compose(&bits(meta, &carry_e, 0)) * word_modulus()
+                    - (vd + t1),
+            ]
+            .into_iter()
+            .map(|expr| {
+                expr * is_block.clone() * (fixed_curr!(meta, sel) - fixed_curr!(meta, sel_state))
+            })
+            .collect::<Vec<_>>()
+        });
+
+        meta.create_gate("sha256 helper: message schedule", |meta| {
+            let is_block = curr!(meta, enable) - curr!(meta, is_final);
+
+            let sum = small_sigma1(&bits(meta, &w, -2))
+                + compose(&bits(meta, &w, -7))
+                + small_sigma0(&bits(meta, &w, -15))
+                + compose(&bits(meta, &w, -16));
+
+            vec![
+                (compose(&bits(meta, &w, 0)) + compose(&bits(meta, &carry_w, 0)) * word_modulus()
+                    - sum)
+                    * is_block
+                    * fixed_curr!(meta, sel_schedule),
+            ]
+        });
+
+        meta.create_gate("sha256 helper: calls", |meta| {
+            let enable = curr!(meta, enable);
+            let is_new = curr!(meta, is_new);
+            let is_final = curr!(meta, is_final);
+            let is_block = enable - is_final.clone();
+
+            let is_new_call = fixed_curr!(meta, sel_slot) * is_new;
+            let is_push_call = fixed_curr!(meta, sel_push) * is_block;
+            let is_finalize_call = sel_finalize
+                .iter()
+                .map(|sel| fixed_curr!(meta, *sel) * is_final.clone())
+                .collect::<Vec<_>>();
+
+            let push = compose(&bits(meta, &w, -1)) * word_modulus() + compose(&bits(meta, &w, 0));
+            // The digest is the state of the slot: a, b, c, d are the words a of its rows
+            // 3, 2, 1, 0, and so are e, f, g, h of the words e.
+            let digest = [
+                compose(&bits(meta, &a, 2)) * word_modulus() + compose(&bits(meta, &a, 1)),
+                compose(&bits(meta, &a, -1)) * word_modulus() + compose(&bits(meta, &a, -2)),
+                compose(&bits(meta, &e, 0)) * word_modulus() + compose(&bits(meta, &e, -1)),
+                compose(&bits(meta, &e, -3)) * word_modulus() + compose(&bits(meta, &e, -4)),
+            ];
+
+            let call_enable_expr = is_finalize_call.iter().fold(
+                is_new_call.clone() + is_push_call.clone(),
+                |acc, is_finalize_call| acc + is_finalize_call.clone(),
+            );
+            let call_encoded_expr = is_finalize_call.into_iter().zip(digest).fold(
+                is_new_call * tag(Op::New) + is_push_call * (tag(Op::Push) + push),
+                |acc, (is_finalize_call, word)| acc + is_finalize_call * (tag(Op::Finalize) + word),
+            );
+
+            let is_first_row =
+                fixed_curr!(meta, sel_slot) * (constant_from!(1) - fixed_curr!(meta, sel_chain));
+
+            vec![
+                curr!(meta, call_enable) - call_enable_expr,
+                curr!(meta, call_encoded) - call_encoded_expr,
+                curr!(meta, lookup_index) - curr!(meta, call_enable) * curr!(meta, call_index),
+                is_first_row.clone() * curr!(meta, call_index),
+                (constant_from!(1) - is_first_row)
+                    * (curr!(meta, call_index)
+                        - nextn!(meta, call_index, -1)
+                        - nextn!(meta, call_enable, -1)),
+            ]
+            .into_iter()
+            .map(|expr| expr * fixed_curr!(meta, sel))
+            .collect::<Vec<_>>()
+        });
+
+        Sha256HelperTableConfig {
+            sel,
+            sel_slot,
+            sel_state,
+            sel_chain,
+            sel_schedule,
+            sel_push,
+            sel_finalize,
+            round_constant,
+            iv_a,
+            iv_e,
+            enable,
+            is_new,
+            is_final,
+            a,
+            e,
+            w,
+            carry_a,
+            carry_e,
+            carry_w,
+            call_enable,
+            call_index,
+            lookup_index,
+            call_encoded,
+            _mark: std::marker::PhantomData,
+        }
+    }
+}
+
+impl<F: FieldExt> ForeignTableConfig<F> for Sha256HelperTableConfig<F> {
+    fn configure_in_table(
+        &self,
+        meta: &mut ConstraintSystem<F>,
+        key: &'static str,
+        expr: &dyn Fn(&mut VirtualCells<'_, F>) -> Vec<Expression<F>>,
+    ) {
+        meta.lookup_any(key, |meta| {
+            let mut exprs = expr(meta);
+
+            vec![
+                (exprs.remove(0), curr!(meta, self.lookup_index)),
+                (exprs.remove(0), curr!(meta, self.call_encoded)),
+            ]
+        });
+    }
+}
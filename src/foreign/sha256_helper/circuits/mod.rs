@@ -0,0 +1,74 @@
+//! The SHA-256 table.
+//!
+//! The table is a sequence of slots of `BLOCK_ROWS` rows. A slot holds the state a block
+//! starts from in its first `STATE_ROWS` rows, and the rounds of the compression of the
+//! block in the following ones, one round per row: the words `a` and `e` of each row and
+//! the message schedule `w` are decomposed into bits, so that the functions of a round are
+//! expressions of the bits of the 4 previous rows.
+//!
+//! A slot either starts a digest, with the IV as its state, or continues the digest of the
+//! previous slot, with the state following the previous block. The calls of the guest are
+//! laid out in the slots: `sha256_new` on the first row of the slot starting a digest, a
+//! push on every other round of the first 16 rounds, as the pair of message words of the
+//! round and the previous one, and the calls of `sha256_finalize` on the slot following
+//! the last block, whose state is the digest and whose rounds are not constrained. Calls
+//! are numbered in the order of the table, the event table looks up each call with its
+//! number among the calls of the plugin, so the calls of the table are exactly the calls
+//! of the execution.
+
+use std::marker::PhantomData;
+
+use halo2_proofs::arithmetic::FieldExt;
+use halo2_proofs::plonk::Advice;
+use halo2_proofs::plonk::Column;
+use halo2_proofs::plonk::Fixed;
+
+use super::FINALIZE_CALLS;
+pub(crate) use crate::foreign::stream_helper::encode_call;
+
+pub mod assign;
+pub mod config;
+
+pub const SHA256_FOREIGN_TABLE_KEY: &'static str = "sha256-helper-table";
+
+const STATE_ROWS: usize = 4;
+const BLOCK_ROWS: usize = STATE_ROWS + super::ROUNDS;
+const WORD_BITS: usize = 32;
+
+#[derive(Clone)]
+pub struct Sha256HelperTableConfig<F: FieldExt> {
+    /// Enables the rows of the slots.
+    sel: Column<Fixed>,
+    /// Enables the first row of each slot.
+    sel_slot: Column<Fixed>,
+    /// Enables the rows of the state of each slot.
+    sel_state: Column<Fixed>,
+    /// Enables the rows of the state of each slot but the first one.
+    sel_chain: Column<Fixed>,
+    /// Enables the rounds whose message word is computed from the previous ones.
+    sel_schedule: Column<Fixed>,
+    sel_push: Column<Fixed>,
+    sel_finalize: [Column<Fixed>; FINALIZE_CALLS],
+    round_constant: Column<Fixed>,
+    iv_a: Column<Fixed>,
+    iv_e: Column<Fixed>,
+
+    enable: Column<Advice>,
+    is_new: Column<Advice>,
+    is_final: Column<Advice>,
+
+    a: [Column<Advice>; WORD_BITS],
+    e: [Column<Advice>; WORD_BITS],
+    w: [Column<Advice>; WORD_BITS],
+    carry_a: [Column<Advice>; 3],
+    carry_e: [Column<Advice>; 3],
+    carry_w: [Column<Advice>; 2],
+
+    call_enable: Column<Advice>,
+    /// The number of the calls preceding the row.
+    call_index: Column<Advice>,
+    lookup_index: Column<Advice>,
+    call_encoded: Column<Advice>,
+
+    _mark: PhantomData<F>,
+}
@@ -0,0 +1,24 @@
+use specs::host_function::HostPlugin;
+
+use super::circuits::SHA256_FOREIGN_TABLE_KEY;
+
+use crate::foreign::stream_helper::ETableStreamHelperTableConfigBuilder;
+use crate::foreign::stream_helper::StreamPlugin;
+
+pub(crate) struct Sha256Plugin;
+
+impl StreamPlugin for Sha256Plugin {
+    const PLUGIN: HostPlugin = HostPlugin::Sha256;
+    const TABLE_KEY: &'static str = SHA256_FOREIGN_TABLE_KEY;
+    const NEW_TAKES_VALUE: bool = false;
+
+    const STACK_READ: &'static str = "sha256 stack read";
+    const STACK_WRITE: &'static str = "sha256 stack write";
+    const SEL: &'static str = "sha256_helper: sel";
+    const NEW_VALUE: &'static str = "sha256_helper: new takes no value";
+    const LOOKUP_AUX: &'static str = "sha256_helper: lookup aux";
+    const LOOKUP: &'static str = "lookup sha256 table";
+}
+
+pub(crate) type ETableSha256HelperTableConfigBuilder =
+    ETableStreamHelperTableConfigBuilder<Sha256Plugin>;
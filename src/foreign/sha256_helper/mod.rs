@@ -0,0 +1,177 @@
+//! SHA-256 digests computed by the host.
+//!
+//! A guest hashes a message padded as FIPS 180-4 specifies: it starts a digest with
+//! `sha256_new`, pushes the padded message 8 bytes at a time, as big-endian words, with
+//! `sha256_push`, then reads the digest 8 bytes at a time with 4 calls of
+//! `sha256_finalize`. A digest costs a few host calls, the event table looks each of them
+//! up in the SHA-256 table, which proves the compression of the blocks, see `circuits`.
+
+use specs::etable::EventTableEntry;
+use specs::host_function::HostPlugin;
+use specs::step::StepInfo;
+
+pub mod circuits;
+pub mod etable_op_configure;
+pub mod runtime;
+pub mod test;
+
+pub(crate) use crate::foreign::stream_helper::Op;
+
+/// Pushes of a block of 512 bits.
+pub(crate) const PUSHES_PER_BLOCK: usize = 8;
+/// Calls of `sha256_finalize` reading a digest of 256 bits.
+pub(crate) const FINALIZE_CALLS: usize = 4;
+/// Rounds of the compression of a block.
+pub(crate) const ROUNDS: usize = 64;
+
+pub(crate) const IV: [u32; 8] = [
+    0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+];
+
+pub(crate) const ROUND_CONSTANTS: [u32; ROUNDS] = [
+    0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+    0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+    0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+    0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+    0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+    0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+    0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+    0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+];
+
+/// The compression of a block step by step, as the table lays it out.
+///
+/// `a` and `e` start with the working variables `d, c, b, a` and `h, g, f, e` of the state
+/// the block starts from, followed by the new `a` and `e` of each round, so that the
+/// variables of a round are the 4 values preceding it. `w` is the message schedule. The
+/// carries are the ones of the additions modulo 2^32 of each round and of the schedule.
+pub(crate) struct Compression {
+    pub(crate) a: Vec<u32>,
+    pub(crate) e: Vec<u32>,
+    pub(crate) w: Vec<u32>,
+    pub(crate) carry_a: Vec<u64>,
+    pub(crate) carry_e: Vec<u64>,
+    pub(crate) carry_w: Vec<u64>,
+}
+
+fn big_sigma0(x: u32) -> u32 {
+    x.rotate_right(2) ^ x.rotate_right(13) ^ x.rotate_right(22)
+}
+
+fn big_sigma1(x: u32) -> u32 {
+    x.rotate_right(6) ^ x.rotate_right(11) ^ x.rotate_right(25)
+}
+
+fn small_sigma0(x: u32) -> u32 {
+    x.rotate_right(7) ^ x.rotate_right(18) ^ (x >> 3)
+}
+
+fn small_sigma1(x: u32) -> u32 {
+    x.rotate_right(17) ^ x.rotate_right(19) ^ (x >> 10)
+}
+
+impl Compression {
+    pub(crate) fn new(state: &[u32; 8], block: &[u32; 16]) -> Self {
+        let mut w = block.to_vec();
+        let mut carry_w = vec![0; 16];
+        for t in 16..ROUNDS {
+            let sum = small_sigma1(w[t - 2]) as u64
+                + w[t - 7] as u64
+                + small_sigma0(w[t - 15]) as u64
+                + w[t - 16] as u64;
+
+            w.push(sum as u32);
+            carry_w.push(sum >> 32);
+        }
+
+        let mut a = vec![state[3], state[2], state[1], state[0]];
+        let mut e = vec![state[7], state[6], state[5], state[4]];
+        let mut carry_a = vec![];
+        let mut carry_e = vec![];
+        for t in 0..ROUNDS {
+            let (va, vb, vc, vd) = (a[t + 3], a[t + 2], a[t + 1], a[t]);
+            let (ve, vf, vg, vh) = (e[t + 3], e[t + 2], e[t + 1], e[t]);
+
+            let t1 = vh as u64
+                + big_sigma1(ve) as u64
+                + ((ve & vf) ^ (!ve & vg)) as u64
+                + ROUND_CONSTANTS[t] as u64
+                + w[t] as u64;
+            let t2 = big_sigma0(va) as u64 + ((va & vb) ^ (va & vc) ^ (vb & vc)) as u64;
+
+            a.push((t1 + t2) as u32);
+            e.push((vd as u64 + t1) as u32);
+            carry_a.push((t1 + t2) >> 32);
+            carry_e.push((vd as u64 + t1) >> 32);
+        }
+
+        Compression {
+            a,
+            e,
+            w,
+            carry_a,
+            carry_e,
+            carry_w,
+        }
+    }
+
+    /// The state following the block.
+    pub(crate) fn output(&self, state: &[u32; 8]) -> [u32; 8] {
+        let a = &self.a[ROUNDS..];
+        let e = &self.e[ROUNDS..];
+
+        [
+            state[0].wrapping_add(a[3]),
+            state[1].wrapping_add(a[2]),
+            state[2].wrapping_add(a[1]),
+            state[3].wrapping_add(a[0]),
+            state[4].wrapping_add(e[3]),
+            state[5].wrapping_add(e[2]),
+            state[6].wrapping_add(e[1]),
+            state[7].wrapping_add(e[0]),
+        ]
+    }
+}
+
+/// The words `sha256_finalize` returns for the digest `state`.
+pub(crate) fn digest_words(state: &[u32; 8]) -> Vec<u64> {
+    state
+        .chunks(2)
+        .map(|pair| ((pair[0] as u64) << 32) + pair[1] as u64)
+        .collect()
+}
+
+/// Whether a digest is in progress before each step of `entries` and after the last one.
+///
+/// The table of a circuit proves the digests started by its own steps, so an execution is
+/// not split into segments while a digest is in progress.
+pub(crate) fn digest_in_progress(entries: &[EventTableEntry]) -> Vec<bool> {
+    let mut in_progress = false;
+    let mut finalize_calls = 0;
+
+    let mut status = vec![false];
+    for entry in entries {
+        if let StepInfo::CallHost {
+            plugin: HostPlugin::Sha256,
+            op_index_in_plugin,
+            ..
+        } = &entry.step_info
+        {
+            match Op::from_index(*op_index_in_plugin) {
+                Op::New => in_progress = true,
+                Op::Push => (),
+                Op::Finalize => {
+                    finalize_calls += 1;
+                    if finalize_calls == FINALIZE_CALLS {
+                        finalize_calls = 0;
+                        in_progress = false;
+                    }
+                }
+            }
+        }
+
+        status.push(in_progress);
+    }
+
+    status
+}
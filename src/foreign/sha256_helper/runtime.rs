@@ -0,0 +1,163 @@
+use std::rc::Rc;
+
+use specs::host_function::HostPlugin;
+use specs::types::ValueType;
+
+use crate::runtime::host::host_env::HostEnv;
+use crate::runtime::host::ForeignContext;
+
+use super::digest_words;
+use super::Compression;
+use super::Op;
+use super::IV;
+use super::PUSHES_PER_BLOCK;
+
+#[derive(Default)]
+struct Context {
+    /// The state of the digest in progress, until it is finalized.
+    state: Option<[u32; 8]>,
+    blocks: usize,
+    block: Vec<u32>,
+    /// The words of the finalized digest left to read.
+    digest: Vec<u64>,
+    error: Option<String>,
+}
+
+impl Context {
+    fn is_in_progress(&self) -> bool {
+        self.state.is_some() || !self.digest.is_empty()
+    }
+
+    fn new_digest(&mut self) {
+        if self.is_in_progress() {
+            self.error =
+                Some("sha256_new is called while the previous digest is in progress".to_owned());
+
+            return;
+        }
+
+        self.state = Some(IV);
+        self.blocks = 0;
+    }
+
+    fn push(&mut self, value: u64) {
+        let state = match self.state.as_mut() {
+            Some(state) => state,
+            None => {
+                self.error = Some("sha256_push is called without a digest in progress".to_owned());
+
+                return;
+            }
+        };
+
+        self.block.push((value >> 32) as u32);
+        self.block.push(value as u32);
+
+        if self.block.len() == PUSHES_PER_BLOCK * 2 {
+            let block: [u32; 16] = self.block.clone().try_into().unwrap();
+            *state = Compression::new(state, &block).output(state);
+
+            self.blocks += 1;
+            self.block.clear();
+        }
+    }
+
+    /// The next word of the digest, or `None` with the error of the context set if there is
+    /// no digest to read.
+    fn finalize(&mut self) -> Option<u64> {
+        if let Some(state) = self.state.take() {
+            if self.blocks == 0 || !self.block.is_empty() {
+                self.error = Some(
+                    "sha256_finalize is called on a message that is not padded to whole blocks"
+                        .to_owned(),
+                );
+
+                return None;
+            }
+
+            self.digest = digest_words(&state);
+        }
+
+        if self.digest.is_empty() {
+            self.error = Some("sha256_finalize is called without a digest in progress".to_owned());
+
+            return None;
+        }
+
+        Some(self.digest.remove(0))
+    }
+}
+
+impl ForeignContext for Context {
+    fn take_error(&mut self) -> Option<String> {
+        self.error.take()
+    }
+}
+
+pub fn register_sha256_foreign(env: &mut HostEnv) {
+    let sha256_new = Rc::new(
+        |context: &mut dyn ForeignContext, _args: wasmi::RuntimeArgs| {
+            let context = context.downcast_mut::<Context>().unwrap();
+            context.new_digest();
+
+            None
+        },
+    );
+
+    let sha256_push = Rc::new(
+        |context: &mut dyn ForeignContext, args: wasmi::RuntimeArgs| {
+            let context = context.downcast_mut::<Context>().unwrap();
+
+            let value: i64 = args.nth(0);
+            context.push(value as u64);
+
+            None
+        },
+    );
+
+    let sha256_finalize = Rc::new(
+        |context: &mut dyn ForeignContext, _args: wasmi::RuntimeArgs| {
+            let context = context.downcast_mut::<Context>().unwrap();
+
+            context
+                .finalize()
+                .map(|value| wasmi::RuntimeValue::I64(value as i64))
+        },
+    );
+
+    env.internal_env
+        .register_plugin(HostPlugin::Sha256, Box::new(Context::default()));
+
+    env.internal_env.register_function(
+        "sha256_new",
+        specs::host_function::Signature {
+            params: vec![],
+            return_types: vec![],
+        },
+        HostPlugin::Sha256,
+        Op::New as usize,
+        sha256_new,
+    );
+
+    env.internal_env.register_function(
+        "sha256_push",
+        specs::host_function::Signature {
+            params: vec![ValueType::I64],
+            return_types: vec![],
+        },
+        HostPlugin::Sha256,
+        Op::Push as usize,
+        sha256_push,
+    );
+
+    env.internal_env.register_function(
+        "sha256_finalize",
+        specs::host_function::Signature {
+            params: vec![],
+            return_types: vec![ValueType::I64],
+        },
+        HostPlugin::Sha256,
+        Op::Finalize as usize,
+        sha256_finalize,
+    );
+}
@@ -0,0 +1,150 @@
+#[cfg(test)]
+mod tests {
+    use sha2::Digest;
+    use sha2::Sha256;
+
+    use crate::foreign::require_helper::register_require_foreign;
+    use crate::foreign::sha256_helper::runtime::register_sha256_foreign;
+    use crate::foreign::wasm_input_helper::runtime::register_wasm_input_foreign;
+    use crate::runtime::host::host_env::HostEnv;
+    use crate::test::test_circuit_with_env;
+
+    /// Hashes `messages` messages given as private inputs, the number of words of each one
+    /// followed by the words, and requires each digest to be the next 4 public inputs.
+    fn guest(messages: usize) -> String {
+        format!(
+            r#"
+        (module
+            (import "env" "wasm_input" (func $wasm_input (param i32) (result i64)))
+            (import "env" "require" (func $require (param i32)))
+            (import "env" "sha256_new" (func $sha256_new))
+            (import "env" "sha256_push" (func $sha256_push (param i64)))
+            (import "env" "sha256_finalize" (func $sha256_finalize (result i64)))
+            (export "main" (func $main))
+            (func $digest (; 5 ;)
+                (local $words i64)
+                (call $sha256_new)
+                (local.set $words (call $wasm_input (i32.const 0)))
+                (block
+                    (loop
+                        (br_if 1 (i64.eqz (local.get $words)))
+                        (call $sha256_push (call $wasm_input (i32.const 0)))
+                        (local.set $words (i64.sub (local.get $words) (i64.const 1)))
+                        (br 0)
+                    )
+                )
+                (call $require (i64.eq (call $sha256_finalize) (call $wasm_input (i32.const 1))))
+                (call $require (i64.eq (call $sha256_finalize) (call $wasm_input (i32.const 1))))
+                (call $require (i64.eq (call $sha256_finalize) (call $wasm_input (i32.const 1))))
+                (call $require (i64.eq (call $sha256_finalize) (call $wasm_input (i32.const 1))))
+            )
+            (func $main (; 6 ;)
+                (local $messages i32)
+                (local.set $messages (i32.const {}))
+                (block
+                    (loop
+                        (br_if 1 (i32.eqz (local.get $messages)))
+                        (call $digest)
+                        (local.set $messages (i32.sub (local.get $messages) (i32.const 1)))
+                        (br 0)
+                    )
+                )
+            )
+        )
+    "#,
+            messages
+        )
+    }
+
+    /// The message padded as FIPS 180-4 specifies, 8 bytes per word.
+    fn padded_words(message: &[u8]) -> Vec<u64> {
+        let mut padded = message.to_vec();
+        padded.push(0x80);
+        while padded.len() % 64 != 56 {
+            padded.push(0);
+        }
+        padded.extend_from_slice(&((message.len() as u64) * 8).to_be_bytes());
+
+        padded
+            .chunks(8)
+            .map(|chunk| u64::from_be_bytes(chunk.try_into().unwrap()))
+            .collect()
+    }
+
+    fn digest_words(message: &[u8]) -> Vec<u64> {
+        Sha256::digest(message)
+            .chunks(8)
+            .map(|chunk| u64::from_be_bytes(chunk.try_into().unwrap()))
+            .collect()
+    }
+
+    fn test_sha256(messages: &[&[u8]]) {
+        let mut private_inputs = vec![];
+        let mut public_inputs = vec![];
+        for message in messages {
+            let words = padded_words(message);
+
+            private_inputs.push(words.len() as u64);
+            private_inputs.extend(words);
+            public_inputs.extend(digest_words(message));
+        }
+
+        let wasm = wabt::wat2wasm(&guest(messages.len())).expect("failed to parse wat");
+
+        let mut env = HostEnv::new();
+        let wasm_runtime_io = register_wasm_input_foreign(&mut env, public_inputs, private_inputs);
+        register_require_foreign(&mut env);
+        register_sha256_foreign(&mut env);
+        env.finalize();
+
+        test_circuit_with_env(env, wasm_runtime_io, wasm, "main").unwrap();
+    }
+
+    #[test]
+    fn test_sha256_one_block() {
+        test_sha256(&[b"abc"]);
+    }
+
+    #[test]
+    fn test_sha256_two_blocks() {
+        test_sha256(&[b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq"]);
+    }
+
+    #[test]
+    fn test_sha256_consecutive_digests() {
+        test_sha256(&[b"", b"abc", &[0x5a; 100]]);
+    }
+
+    /// Misuses of the plugin trap the execution instead of panicking the host.
+    #[test]
+    fn test_sha256_plugin_misuse() {
+        for body in [
+            "(call $sha256_push (i64.const 0))",
+            "(drop (call $sha256_finalize))",
+            "(call $sha256_new) (call $sha256_new)",
+            "(call $sha256_new) (call $sha256_push (i64.const 0)) (drop (call $sha256_finalize))",
+        ] {
+            let wasm = wabt::wat2wasm(format!(
+                r#"
+            (module
+                (import "env" "sha256_new" (func $sha256_new))
+                (import "env" "sha256_push" (func $sha256_push (param i64)))
+                (import "env" "sha256_finalize" (func $sha256_finalize (result i64)))
+                (func (export "main")
+                    {}
+                )
+            )
+            "#,
+                body
+            ))
+            .expect("failed to parse wat");
+
+            let mut env = HostEnv::new();
+            let wasm_runtime_io = register_wasm_input_foreign(&mut env, vec![], vec![]);
+            register_sha256_foreign(&mut env);
+            env.finalize();
+
+            assert!(test_circuit_with_env(env, wasm_runtime_io, wasm, "main").is_err());
+        }
+    }
+}
@@ -0,0 +1,323 @@
+//! The calls of the plugins streaming values to the host: a guest starts a computation
+//! with `new`, pushes its input a value of 64 bits at a time with `push`, then reads its
+//! output a value at a time with `finalize`.
+//!
+//! The event table configuration of these calls is shared, a plugin only tells its table
+//! and whether its `new` takes a value, see `StreamPlugin`.
+
+use std::marker::PhantomData;
+
+use halo2_proofs::arithmetic::FieldExt;
+use halo2_proofs::plonk::Error;
+use halo2_proofs::plonk::Expression;
+use halo2_proofs::plonk::VirtualCells;
+use num_bigint::BigUint;
+use specs::etable::EventTableEntry;
+use specs::host_function::HostPlugin;
+use specs::itable::OpcodeClass;
+use specs::itable::OPCODE_CLASS_SHIFT;
+use specs::mtable::LocationType;
+use specs::step::StepInfo;
+
+use crate::circuits::cell::AllocatedBitCell;
+use crate::circuits::cell::AllocatedU64Cell;
+use crate::circuits::cell::AllocatedUnlimitedCell;
+use crate::circuits::cell::CellExpression;
+use crate::circuits::etable::allocator::AllocatedMemoryTableLookupReadCell;
+use crate::circuits::etable::allocator::AllocatedMemoryTableLookupWriteCell;
+use crate::circuits::etable::allocator::EventTableCellAllocator;
+use crate::circuits::etable::constraint_builder::ConstraintBuilder;
+use crate::circuits::etable::EventTableCommonConfig;
+use crate::circuits::etable::EventTableOpcodeConfig;
+use crate::circuits::utils::bn_to_field;
+use crate::circuits::utils::step_status::StepStatus;
+use crate::circuits::utils::table_entry::EventTableEntryWithMemoryInfo;
+use crate::circuits::utils::Context;
+use crate::constant_from;
+use crate::constant_from_bn;
+use crate::foreign::etable_op_configure::ForeignCallLookupConfig;
+use crate::foreign::EventTableForeignCallConfigBuilder;
+use crate::foreign::InternalHostPluginBuilder;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Op {
+    New = 0,
+    Push = 1,
+    Finalize = 2,
+}
+
+impl Op {
+    pub(crate) fn from_index(op_index_in_plugin: usize) -> Self {
+        match op_index_in_plugin {
+            0 => Op::New,
+            1 => Op::Push,
+            2 => Op::Finalize,
+            _ => unreachable!(),
+        }
+    }
+
+    /// The tag of the op in the lookup of a call, tags start from 1 so that a call never
+    /// looks up the empty rows of the table.
+    pub(crate) fn tag(self) -> u64 {
+        self as u64 + 1
+    }
+}
+
+/// The encoding of a call in the lookup of the event table.
+pub(crate) fn encode_call<F: FieldExt>(op: Op, value: u64) -> F {
+    bn_to_field(&((BigUint::from(op.tag()) << 64) + value))
+}
+
+/// A plugin whose calls are `new`, `push` and `finalize`.
+pub(crate) trait StreamPlugin: 'static {
+    const PLUGIN: HostPlugin;
+    const TABLE_KEY: &'static str;
+    /// Whether `new` takes a value, e.g. the length of the message, otherwise its value is 0.
+    const NEW_TAKES_VALUE: bool;
+
+    const STACK_READ: &'static str;
+    const STACK_WRITE: &'static str;
+    const SEL: &'static str;
+    /// The constraint of the value of `new` if it takes none.
+    const NEW_VALUE: &'static str = "stream_helper: new takes no value";
+    const LOOKUP_AUX: &'static str;
+    const LOOKUP: &'static str;
+}
+
+pub(crate) struct ETableStreamHelperTableConfig<F: FieldExt, P: StreamPlugin> {
+    plugin_index: usize,
+    is_new_op: AllocatedBitCell<F>,
+    is_push_op: AllocatedBitCell<F>,
+    is_finalize_op: AllocatedBitCell<F>,
+
+    value: AllocatedU64Cell<F>,
+
+    lookup_call: ForeignCallLookupConfig<F>,
+
+    lookup_read_stack: AllocatedMemoryTableLookupReadCell<F>,
+    lookup_write_stack: AllocatedMemoryTableLookupWriteCell<F>,
+
+    _mark: PhantomData<P>,
+}
+
+pub(crate) struct ETableStreamHelperTableConfigBuilder<P: StreamPlugin> {
+    index: usize,
+    _mark: PhantomData<P>,
+}
+
+impl<P: StreamPlugin> InternalHostPluginBuilder for ETableStreamHelperTableConfigBuilder<P> {
+    fn new(index: usize) -> Self {
+        Self {
+            index,
+            _mark: PhantomData,
+        }
+    }
+}
+
+impl<F: FieldExt, P: StreamPlugin> EventTableForeignCallConfigBuilder<F>
+    for ETableStreamHelperTableConfigBuilder<P>
+{
+    fn configure(
+        self,
+        common_config: &EventTableCommonConfig<F>,
+        allocator: &mut EventTableCellAllocator<F>,
+        constraint_builder: &mut ConstraintBuilder<F>,
+        lookup_cells: &mut (impl Iterator<Item = AllocatedUnlimitedCell<F>> + Clone),
+    ) -> Box<dyn EventTableOpcodeConfig<F>> {
+        let eid = common_config.eid_cell;
+        let sp = common_config.sp_cell;
+
+        let is_new_op = allocator.alloc_bit_cell();
+        let is_push_op = allocator.alloc_bit_cell();
+        let is_finalize_op = allocator.alloc_bit_cell();
+
+        let value = allocator.alloc_u64_cell();
+
+        // The ops taking a value pop it from the stack.
+        let takes_value = move |meta: &mut VirtualCells<'_, F>| {
+            if P::NEW_TAKES_VALUE {
+                is_new_op.expr(meta) + is_push_op.expr(meta)
+            } else {
+                is_push_op.expr(meta)
+            }
+        };
+
+        let lookup_read_stack = allocator.alloc_memory_table_lookup_read_cell(
+            P::STACK_READ,
+            constraint_builder,
+            eid,
+            move |____| constant_from!(LocationType::Stack as u64),
+            move |meta| sp.expr(meta) + constant_from!(1),
+            move |____| constant_from!(0),
+            move |meta| value.u64_cell.expr(meta),
+            takes_value,
+        );
+        let lookup_write_stack = allocator.alloc_memory_table_lookup_write_cell(
+            P::STACK_WRITE,
+            constraint_builder,
+            eid,
+            move |____| constant_from!(LocationType::Stack as u64),
+            move |meta| sp.expr(meta),
+            move |____| constant_from!(0),
+            move |meta| value.u64_cell.expr(meta),
+            move |meta| is_finalize_op.expr(meta),
+        );
+
+        constraint_builder.push(
+            P::SEL,
+            Box::new(move |meta| {
+                vec![
+                    is_new_op.expr(meta) + is_push_op.expr(meta) + is_finalize_op.expr(meta)
+                        - constant_from!(1),
+                ]
+            }),
+        );
+
+        if !P::NEW_TAKES_VALUE {
+            constraint_builder.push(
+                P::NEW_VALUE,
+                Box::new(move |meta| vec![is_new_op.expr(meta) * value.expr(meta)]),
+            );
+        }
+
+        let lookup_call = ForeignCallLookupConfig::configure(
+            P::PLUGIN,
+            P::TABLE_KEY,
+            (P::LOOKUP_AUX, P::LOOKUP),
+            common_config,
+            constraint_builder,
+            lookup_cells,
+            move |meta| {
+                let tag = |op: Op| constant_from_bn!(&(BigUint::from(op.tag()) << 64));
+
+                is_new_op.expr(meta) * tag(Op::New)
+                    + is_push_op.expr(meta) * tag(Op::Push)
+                    + is_finalize_op.expr(meta) * tag(Op::Finalize)
+                    + value.expr(meta)
+            },
+        );
+
+        Box::new(ETableStreamHelperTableConfig::<F, P> {
+            plugin_index: self.index,
+            is_new_op,
+            is_push_op,
+            is_finalize_op,
+            value,
+            lookup_call,
+            lookup_read_stack,
+            lookup_write_stack,
+            _mark: PhantomData,
+        })
+    }
+}
+
+impl<F: FieldExt, P: StreamPlugin> EventTableOpcodeConfig<F>
+    for ETableStreamHelperTableConfig<F, P>
+{
+    fn opcode(&self, meta: &mut VirtualCells<'_, F>) -> Expression<F> {
+        constant_from_bn!(
+            &(BigUint::from(OpcodeClass::ForeignPluginStart as u64 + self.plugin_index as u64)
+                << OPCODE_CLASS_SHIFT)
+        ) + self.is_push_op.expr(meta) * constant_from!(Op::Push as u64)
+            + self.is_finalize_op.expr(meta) * constant_from!(Op::Finalize as u64)
+    }
+
+    fn assign(
+        &self,
+        ctx: &mut Context<'_, F>,
+        step: &StepStatus,
+        entry: &EventTableEntryWithMemoryInfo,
+    ) -> Result<(), Error> {
+        match &entry.eentry.step_info {
+            StepInfo::CallHost {
+                args,
+                ret_vals,
+                op_index_in_plugin,
+                ..
+            } => {
+                let op = Op::from_index(*op_index_in_plugin);
+
+                let value = match op {
+                    Op::New if !P::NEW_TAKES_VALUE => {
+                        self.is_new_op.assign_bool(ctx, true)?;
+
+                        0
+                    }
+                    Op::New | Op::Push => {
+                        let value = args[0];
+
+                        if op == Op::New {
+                            self.is_new_op.assign_bool(ctx, true)?;
+                        } else {
+                            self.is_push_op.assign_bool(ctx, true)?;
+                        }
+                        self.lookup_read_stack.assign(
+                            ctx,
+                            entry.memory_rw_entires[0].start_eid,
+                            step.current.eid,
+                            entry.memory_rw_entires[0].end_eid,
+                            step.current.sp + 1,
+                            LocationType::Stack,
+                            false,
+                            value,
+                        )?;
+
+                        value
+                    }
+                    Op::Finalize => {
+                        let value = ret_vals[0];
+
+                        self.is_finalize_op.assign_bool(ctx, true)?;
+                        self.lookup_write_stack.assign(
+                            ctx,
+                            step.current.eid,
+                            entry.memory_rw_entires[0].end_eid,
+                            step.current.sp,
+                            LocationType::Stack,
+                            false,
+                            value,
+                        )?;
+
+                        value
+                    }
+                };
+
+                self.value.assign(ctx, value)?;
+                self.lookup_call.assign(ctx, step, encode_call(op, value))?;
+
+                Ok(())
+            }
+
+            _ => unreachable!(),
+        }
+    }
+
+    fn sp_diff(&self, meta: &mut VirtualCells<'_, F>) -> Option<Expression<F>> {
+        let pops = if P::NEW_TAKES_VALUE {
+            self.is_new_op.expr(meta) + self.is_push_op.expr(meta)
+        } else {
+            self.is_push_op.expr(meta)
+        };
+
+        Some(pops - self.is_finalize_op.expr(meta))
+    }
+
+    fn mops(&self, meta: &mut VirtualCells<'_, F>) -> Option<Expression<F>> {
+        Some(self.is_finalize_op.expr(meta))
+    }
+
+    fn memory_writing_ops(&self, entry: &EventTableEntry) -> u32 {
+        match &entry.step_info {
+            StepInfo::CallHost {
+                plugin,
+                op_index_in_plugin,
+                ..
+            } => {
+                assert_eq!(*plugin, P::PLUGIN);
+
+                (Op::from_index(*op_index_in_plugin) == Op::Finalize) as u32
+            }
+            _ => unreachable!(),
+        }
+    }
+}
@@ -12,6 +12,7 @@ use specs::mtable::VarType;
 use specs::step::StepInfo;
 
 use super::circuits::WASM_INPUT_FOREIGN_TABLE_KEY;
+use super::is_public_input_or_output;
 
 use crate::circuits::cell::AllocatedBitCell;
 use crate::circuits::cell::AllocatedU64Cell;
@@ -183,21 +184,22 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for ETableWasmInputHelperTableConfig
         match &entry.eentry.step_info {
             StepInfo::CallHost {
                 args,
-                ret_val,
+                ret_vals,
                 signature,
                 op_index_in_plugin,
                 ..
             } => {
                 if *op_index_in_plugin == Op::WasmInput as usize {
                     let arg_type: VarType = (*signature.params.get(0).unwrap()).into();
-                    let ret_type: VarType = signature.return_type.unwrap().into();
+                    let ret_type: VarType = (*signature.return_types.get(0).unwrap()).into();
 
                     assert_eq!(args.len(), 1);
+                    assert_eq!(ret_vals.len(), 1);
                     assert_eq!(arg_type, VarType::I32);
                     assert_eq!(ret_type, VarType::I64);
 
                     let is_public = *args.get(0).unwrap() == 1;
-                    let value = ret_val.unwrap();
+                    let value = *ret_vals.first().unwrap();
 
                     self.is_wasm_input_op.assign_bool(ctx, true)?;
                     self.is_public.assign_bool(ctx, is_public)?;
@@ -229,7 +231,7 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for ETableWasmInputHelperTableConfig
                         step.current.sp + 1,
                         LocationType::Stack,
                         false,
-                        ret_val.unwrap(),
+                        value,
                     )?;
                 } else {
                     let arg_type: VarType = (*signature.params.get(0).unwrap()).into();
@@ -299,16 +301,10 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for ETableWasmInputHelperTableConfig
 
     fn is_host_public_input(&self, entry: &EventTableEntry) -> bool {
         match &entry.step_info {
-            StepInfo::CallHost {
-                plugin,
-                args,
-                op_index_in_plugin,
-                ..
-            } => {
+            StepInfo::CallHost { plugin, .. } => {
                 assert_eq!(*plugin, HostPlugin::HostInput);
 
-                *op_index_in_plugin == Op::WasmInput as usize && args[0] != 0
-                    || *op_index_in_plugin == Op::WasmOutput as usize
+                is_public_input_or_output(&entry.step_info)
             }
             _ => unreachable!(),
         }
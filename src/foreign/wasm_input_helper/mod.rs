@@ -1,3 +1,6 @@
+use specs::host_function::HostPlugin;
+use specs::step::StepInfo;
+
 pub mod circuits;
 pub mod etable_op_configure;
 pub mod runtime;
@@ -7,3 +10,20 @@ enum Op {
     WasmInput = 0,
     WasmOutput = 1,
 }
+
+/// Whether the step reads a public input or writes an output, both of them are instances
+/// of the proof.
+pub(crate) fn is_public_input_or_output(step_info: &StepInfo) -> bool {
+    match step_info {
+        StepInfo::CallHost {
+            plugin: HostPlugin::HostInput,
+            args,
+            op_index_in_plugin,
+            ..
+        } => {
+            *op_index_in_plugin == Op::WasmInput as usize && args[0] != 0
+                || *op_index_in_plugin == Op::WasmOutput as usize
+        }
+        _ => false,
+    }
+}
@@ -2,6 +2,7 @@ use std::cell::RefCell;
 use std::rc::Rc;
 
 use specs::host_function::HostPlugin;
+use specs::kvtable::KvTable;
 use specs::types::ValueType;
 
 use crate::runtime::host::host_env::HostEnv;
@@ -109,7 +110,7 @@ pub fn register_wasm_input_foreign(
         "wasm_input",
         specs::host_function::Signature {
             params: vec![ValueType::I32],
-            return_type: Some(ValueType::I64),
+            return_types: vec![ValueType::I64],
         },
         HostPlugin::HostInput,
         Op::WasmInput as usize,
@@ -120,7 +121,7 @@ pub fn register_wasm_input_foreign(
         "wasm_output",
         specs::host_function::Signature {
             params: vec![ValueType::I64],
-            return_type: None,
+            return_types: vec![],
         },
         HostPlugin::HostInput,
         Op::WasmOutput as usize,
@@ -130,5 +131,6 @@ pub fn register_wasm_input_foreign(
     WasmRuntimeIO {
         public_inputs_and_outputs,
         outputs,
+        kvtable: Rc::new(RefCell::new(KvTable::default())),
     }
 }
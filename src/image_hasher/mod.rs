@@ -6,14 +6,16 @@ use specs::encode::image_table::ImageTableEncoder;
 use specs::imtable::InitMemoryTable;
 use specs::itable::InstructionTable;
 use specs::jtable::StaticFrameEntry;
-use specs::mtable::LocationType;
+use specs::jtable::INHERITED_FRAME_TABLE_ENTRIES;
+use specs::jtable::STATIC_FRAME_ENTRY_NUMBER;
 use specs::CompilationTable;
 
-use crate::circuits::checksum::poseidon::primitives::ConstantLength;
-use crate::circuits::checksum::poseidon::primitives::Hash;
-use crate::circuits::checksum::poseidon::primitives::P128Pow5T9;
 use crate::circuits::checksum::L;
 use crate::circuits::config::max_image_table_rows;
+use crate::circuits::config::max_init_memory_table_rows;
+use crate::circuits::poseidon::primitives::ConstantLength;
+use crate::circuits::poseidon::primitives::Hash;
+use crate::circuits::poseidon::primitives::P128Pow5T9;
 use crate::circuits::utils::bn_to_field;
 
 pub trait ImageHasher {
@@ -57,21 +59,20 @@ impl ImageHasher for CompilationTable {
         }
 
         fn msg_of_init_memory_table<F: FieldExt>(init_memory_table: &InitMemoryTable) -> Vec<F> {
-            let heap_entries = init_memory_table.filter(LocationType::Heap);
-            let global_entries = init_memory_table.filter(LocationType::Global);
-
             let mut cells = vec![];
 
             cells.push(bn_to_field(
                 &ImageTableEncoder::InitMemory.encode(BigUint::from(0u64)),
             ));
 
-            for v in heap_entries.into_iter().chain(global_entries.into_iter()) {
+            for v in init_memory_table.entries() {
                 cells.push(bn_to_field::<F>(
                     &ImageTableEncoder::InitMemory.encode(v.encode()),
                 ));
             }
 
+            cells.resize(max_init_memory_table_rows() as usize, F::zero());
+
             cells
         }
 
@@ -79,13 +80,11 @@ impl ImageHasher for CompilationTable {
             instruction_table: &InstructionTable,
             br_table: &BrTable,
             elem_table: &ElemTable,
-            init_memory_table: &InitMemoryTable,
         ) -> Vec<F> {
             let mut cells = vec![];
 
             cells.append(&mut msg_of_instruction_table(instruction_table));
             cells.append(&mut msg_of_br_table(br_table, elem_table));
-            cells.append(&mut msg_of_init_memory_table(init_memory_table));
 
             for _ in cells.len()..(max_image_table_rows() as usize) {
                 cells.push(F::zero());
@@ -97,29 +96,27 @@ impl ImageHasher for CompilationTable {
         fn msg_of_static_frame_table<F: FieldExt>(
             static_frame_table: &Vec<StaticFrameEntry>,
         ) -> Vec<F> {
-            let mut cells = static_frame_table
-                .into_iter()
-                .map(|entry| vec![F::one(), bn_to_field(&entry.encode())])
-                .collect::<Vec<Vec<_>>>();
-
-            cells.resize(
-                2,
-                vec![
-                    F::zero(),
-                    bn_to_field(
-                        &StaticFrameEntry {
-                            frame_id: 0,
-                            next_frame_id: 0,
-                            callee_fid: 0,
-                            fid: 0,
-                            iid: 0,
-                        }
-                        .encode(),
-                    ),
-                ],
-            );
-
-            cells.concat()
+            let (inherited_entries, root_entries): (Vec<_>, Vec<_>) = static_frame_table
+                .iter()
+                .partition(|entry| entry.is_inherited());
+
+            let mut cells = vec![];
+
+            // The padding entries are disabled and encode to zero.
+            for (entries, capability) in [
+                (root_entries, STATIC_FRAME_ENTRY_NUMBER),
+                (inherited_entries, INHERITED_FRAME_TABLE_ENTRIES),
+            ] {
+                let mut entries = entries
+                    .into_iter()
+                    .map(|entry| vec![F::one(), bn_to_field(&entry.encode())])
+                    .collect::<Vec<Vec<_>>>();
+                entries.resize(capability, vec![F::zero(), F::zero()]);
+
+                cells.append(&mut entries.concat());
+            }
+
+            cells
         }
 
         let mut cells: Vec<F> = vec![];
@@ -128,10 +125,16 @@ impl ImageHasher for CompilationTable {
             &self.itable,
             &self.itable.create_brtable(),
             &self.elem_table,
-            &self.imtable,
         ));
-
-        cells.push(F::from(self.fid_of_entry as u64));
+        cells.append(&mut msg_of_init_memory_table(&self.imtable));
+        cells.append(
+            &mut self
+                .initialization_state
+                .plain()
+                .into_iter()
+                .map(|v| F::from(*v as u64))
+                .collect(),
+        );
         cells.append(&mut msg_of_static_frame_table(&self.static_jtable));
 
         let poseidon_hasher = Hash::<F, P128Pow5T9<F>, ConstantLength<L>, 9, 8>::init();
@@ -1,11 +1,11 @@
 #![deny(dead_code)]
 #![deny(unused_variables)]
 #![deny(unused_imports)]
-#![feature(thread_local)]
 
 pub mod circuits;
 pub mod cli;
 pub mod foreign;
+pub mod prover;
 pub mod runtime;
 pub mod traits;
 
@@ -0,0 +1,367 @@
+//! Aggregation of any number of executions of an image into one proof.
+//!
+//! An aggregate circuit verifies a fixed number of proofs, `max_proofs`, its keys depend
+//! on it. Executions are aggregated by groups of `max_proofs`, then the aggregates are
+//! aggregated in turn, layer by layer, until a single aggregate is left. The root is proved
+//! with the sha256 transcript to be verified on EVM.
+//!
+//! The last group of a layer is padded with dummies: the proof of the empty execution of
+//! the image, whose instances are `ZkWasmProver::dummy_instances`, or the aggregate of
+//! dummies only on the upper layers. The first `n_proofs` executions of the layout are the
+//! real ones, a consumer of the aggregate instances ignores the proofs of the dummy
+//! instances. The empty execution does not terminate, no execution of the image has the
+//! instances of the dummy.
+//!
+//! Proofs of different images of the same K are aggregated by `aggregate_images`, the
+//! aggregate circuit then verifies each proof with the verifying key of its image. The
+//! identity of the image of each proof is exposed by the aggregate instances: with the
+//! checksum feature, the image hash is the first instance of each proof, which the
+//! aggregate instances commit to, and the verifying keys are constants of the aggregate
+//! circuit either way.
+//!
+//! The aggregation goes through `halo2aggregator-s`, which keeps the keys and proofs of
+//! every aggregate in the output directory, the params of both K must be there already.
+
+use std::fs;
+use std::path::Path;
+
+use halo2_proofs::pairing::bn256::Bn256;
+use halo2_proofs::pairing::bn256::Fr;
+use halo2_proofs::pairing::bn256::G1Affine;
+use halo2_proofs::plonk::Circuit;
+use halo2_proofs::plonk::VerifyingKey;
+use halo2_proofs::poly::commitment::Params;
+use halo2_proofs::poly::commitment::ParamsVerifier;
+use halo2aggregator_s::circuit_verifier::build_aggregate_verify_circuit;
+use halo2aggregator_s::circuit_verifier::circuit::AggregatorCircuit;
+use halo2aggregator_s::circuits::utils::run_circuit_unsafe_full_pass;
+use halo2aggregator_s::circuits::utils::TranscriptHash;
+use serde::Deserialize;
+use serde::Serialize;
+
+use super::catch_panic;
+use super::execution_instances;
+use super::Proof;
+use super::ProverError;
+use super::Result;
+use super::Transcript;
+use super::Witness;
+use super::ZkWasmProver;
+
+pub const AGGREGATE_PREFIX: &'static str = "aggregate-circuit";
+
+/// The shape of the aggregation of `n_proofs` executions.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AggregateLayout {
+    pub n_proofs: usize,
+    /// Number of proofs verified by each aggregate circuit.
+    pub max_proofs: usize,
+    /// Number of aggregates of each layer, from the one of the executions to the root.
+    pub layers: Vec<usize>,
+    /// Hex encoded fingerprints of the images of the executions, in their order.
+    #[serde(default)]
+    pub images: Vec<String>,
+}
+
+impl AggregateLayout {
+    pub fn new(n_proofs: usize, max_proofs: usize) -> Result<Self> {
+        // Aggregates of a single proof never reduce a layer.
+        if n_proofs == 0 || max_proofs == 0 || (max_proofs == 1 && n_proofs > 1) {
+            return Err(ProverError::Aggregation(format!(
+                "cannot aggregate {} proofs by groups of {}",
+                n_proofs, max_proofs
+            )));
+        }
+
+        let mut layers = vec![(n_proofs + max_proofs - 1) / max_proofs];
+        while *layers.last().unwrap() > 1 {
+            let nodes = *layers.last().unwrap();
+            layers.push((nodes + max_proofs - 1) / max_proofs);
+        }
+
+        Ok(AggregateLayout {
+            n_proofs,
+            max_proofs,
+            layers,
+            images: vec![],
+        })
+    }
+
+    /// The size of the instances of the root proof.
+    pub fn instances_size(&self) -> usize {
+        6 + 3 * self.max_proofs
+    }
+}
+
+/// Pads `items` to `size` with copies of `dummy`.
+pub fn pad<T: Clone>(mut items: Vec<T>, size: usize, dummy: T) -> Vec<T> {
+    if items.len() < size {
+        items.resize(size, dummy);
+    }
+
+    items
+}
+
+/// An aggregate circuit with its instances.
+type Node = (AggregatorCircuit<G1Affine>, Vec<Fr>);
+
+/// Nodes of a layer verify the same circuits, the verifying key of the first node is
+/// copied to the others so that it is not generated again.
+fn reuse_vkey(dir: &Path, from_prefix: &str, to_prefix: &str) -> Result<()> {
+    let from = dir.join(format!("{}.0.vkey.data", from_prefix));
+    let to = dir.join(format!("{}.0.vkey.data", to_prefix));
+
+    if from.exists() && !to.exists() {
+        fs::copy(from, to)?;
+    }
+
+    Ok(())
+}
+
+fn aggregate_node<C: Circuit<Fr>>(
+    dir: &Path,
+    prefix: &str,
+    k: u32,
+    circuits: Vec<C>,
+    instances: Vec<Vec<Vec<Fr>>>,
+) -> Result<Node> {
+    catch_panic(|| {
+        run_circuit_unsafe_full_pass::<Bn256, _>(
+            dir,
+            prefix,
+            k,
+            circuits,
+            instances,
+            TranscriptHash::Poseidon,
+            vec![],
+            true,
+        )
+        .ok_or_else(|| ProverError::Aggregation(format!("failed to aggregate {}", prefix)))
+    })
+}
+
+/// Proves the root aggregate with the sha256 transcript, as the proof of `AGGREGATE_PREFIX`.
+pub(super) fn prove_root(
+    dir: &Path,
+    aggregate_k: u32,
+    root: AggregatorCircuit<G1Affine>,
+    instances: Vec<Fr>,
+) -> Result<()> {
+    catch_panic(|| {
+        run_circuit_unsafe_full_pass::<Bn256, _>(
+            dir,
+            AGGREGATE_PREFIX,
+            aggregate_k,
+            vec![root],
+            vec![vec![instances]],
+            TranscriptHash::Sha,
+            vec![],
+            true,
+        );
+
+        Ok(())
+    })
+}
+
+/// A proof to be aggregated with the proofs of other images.
+pub struct AggregateTarget<'a> {
+    pub prover: &'a ZkWasmProver,
+    pub vkey: &'a VerifyingKey<G1Affine>,
+    /// A proof of the poseidon transcript, which the aggregate circuit verifies.
+    pub proof: Proof,
+    /// The instances of the proof as given to `ZkWasmProver::verify`.
+    pub instances: Vec<Fr>,
+}
+
+/// Aggregates proofs of different images into the proof of `AGGREGATE_PREFIX`, `params`
+/// are the params of the K of the images.
+pub fn aggregate_images(
+    dir: &Path,
+    params: &Params<G1Affine>,
+    aggregate_k: u32,
+    targets: &[AggregateTarget],
+) -> Result<AggregateLayout> {
+    let mut layout = AggregateLayout::new(targets.len(), targets.len())?;
+
+    let mut vkeys = vec![];
+    let mut instances = vec![];
+    let mut proofs = vec![];
+    for target in targets {
+        if target.proof.transcript != Transcript::Poseidon {
+            return Err(ProverError::UnsupportedTranscript(target.proof.transcript));
+        }
+
+        // Reports the failing proof instead of an unsatisfied aggregate circuit.
+        target
+            .prover
+            .verify(params, target.vkey, &target.instances, &target.proof)?;
+
+        vkeys.push(target.vkey);
+        instances.push(vec![target.prover.circuit_instances(&target.instances)]);
+        proofs.push(target.proof.bytes.clone());
+        layout
+            .images
+            .push(hex::encode(target.prover.image_fingerprint()));
+    }
+
+    let params_verifier: ParamsVerifier<Bn256> = params
+        .verifier(instances.iter().map(|i| i[0].len()).max().unwrap())
+        .map_err(|err| ProverError::Params(format!("{:?}", err)))?;
+
+    let (root, root_instances) = catch_panic(|| {
+        Ok(build_aggregate_verify_circuit::<Bn256>(
+            &params_verifier,
+            &vkeys[..],
+            instances.iter().collect(),
+            proofs,
+            TranscriptHash::Poseidon,
+            vec![],
+        ))
+    })?;
+
+    prove_root(dir, aggregate_k, root, root_instances)?;
+
+    Ok(layout)
+}
+
+/// Aggregates `group`, nodes of the layer below `layer`, into a node of `layer`.
+fn aggregate_layer_node(
+    dir: &Path,
+    node_prefix: &str,
+    layer: usize,
+    aggregate_k: u32,
+    group: Vec<Node>,
+) -> Result<Node> {
+    reuse_vkey(
+        dir,
+        &format!("{}.layer{}.{}", AGGREGATE_PREFIX, layer, 0),
+        node_prefix,
+    )?;
+
+    let (circuits, instances) = group
+        .into_iter()
+        .map(|(circuit, instances)| (circuit, vec![instances]))
+        .unzip();
+
+    aggregate_node(dir, node_prefix, aggregate_k, circuits, instances)
+}
+
+impl ZkWasmProver {
+    /// The witness of the empty execution of the image, which pads the last group of
+    /// executions of an aggregation.
+    pub fn dummy_witness(&self) -> Witness {
+        let circuit = self.circuit_without_witness();
+        let instances = execution_instances(&circuit.tables, &[]);
+
+        Witness {
+            circuit,
+            instances,
+            public_inputs_and_outputs: vec![],
+            outputs: vec![],
+            result: None,
+            trap: None,
+        }
+    }
+
+    /// The instances of the padding proofs of an aggregation, as given to `verify`.
+    pub fn dummy_instances(&self) -> Vec<Fr> {
+        self.dummy_witness().instances
+    }
+
+    /// Aggregates the witnesses of `group` into a node of the first layer.
+    fn aggregate_leaf(
+        &self,
+        dir: &Path,
+        prefix: &str,
+        node_prefix: &str,
+        group: Vec<Witness>,
+    ) -> Result<Node> {
+        reuse_vkey(dir, prefix, node_prefix)?;
+
+        let instances = group
+            .iter()
+            .map(|witness| vec![self.circuit_instances(&witness.instances)])
+            .collect();
+        let circuits = group.into_iter().map(|witness| witness.circuit).collect();
+
+        aggregate_node(dir, node_prefix, self.k, circuits, instances)
+    }
+
+    /// Aggregates the executions of `witnesses` into the proof of `AGGREGATE_PREFIX`,
+    /// `prefix` is the one of the verifying key of the image in `dir`.
+    pub fn aggregate(
+        &self,
+        dir: &Path,
+        prefix: &str,
+        aggregate_k: u32,
+        max_proofs: usize,
+        witnesses: Vec<Witness>,
+    ) -> Result<AggregateLayout> {
+        let mut layout = AggregateLayout::new(witnesses.len(), max_proofs)?;
+        layout.images = vec![hex::encode(self.image_fingerprint()); witnesses.len()];
+
+        let dummy = self.dummy_witness();
+        // The nodes of each layer aggregating dummies only, which pad the layer above.
+        let mut dummy_nodes: Vec<Node> = vec![];
+
+        let mut nodes = vec![];
+        for (node, group) in witnesses.chunks(max_proofs).enumerate() {
+            nodes.push(self.aggregate_leaf(
+                dir,
+                prefix,
+                &format!("{}.leaf{}", prefix, node),
+                pad(group.to_vec(), max_proofs, dummy.clone()),
+            )?);
+        }
+
+        for layer in 1..layout.layers.len() {
+            if nodes.len() % max_proofs != 0 {
+                while dummy_nodes.len() < layer {
+                    let below = dummy_nodes.len();
+
+                    let dummy_node = if below == 0 {
+                        self.aggregate_leaf(
+                            dir,
+                            prefix,
+                            &format!("{}.leaf.dummy", prefix),
+                            vec![dummy.clone(); max_proofs],
+                        )?
+                    } else {
+                        aggregate_layer_node(
+                            dir,
+                            &format!("{}.layer{}.dummy", AGGREGATE_PREFIX, below),
+                            below,
+                            aggregate_k,
+                            vec![dummy_nodes[below - 1].clone(); max_proofs],
+                        )?
+                    };
+
+                    dummy_nodes.push(dummy_node);
+                }
+            }
+
+            let mut next = vec![];
+            for (node, group) in nodes.chunks(max_proofs).enumerate() {
+                let group = match dummy_nodes.get(layer - 1) {
+                    Some(dummy_node) => pad(group.to_vec(), max_proofs, dummy_node.clone()),
+                    None => group.to_vec(),
+                };
+
+                next.push(aggregate_layer_node(
+                    dir,
+                    &format!("{}.layer{}.{}", AGGREGATE_PREFIX, layer, node),
+                    layer,
+                    aggregate_k,
+                    group,
+                )?);
+            }
+
+            nodes = next;
+        }
+
+        let (root, root_instances) = nodes.pop().unwrap();
+        prove_root(dir, aggregate_k, root, root_instances)?;
+
+        Ok(layout)
+    }
+}
@@ -0,0 +1,246 @@
+//! Proof bundles: a proof with everything needed to verify it but the params and the key.
+//!
+//! A bundle is a JSON document holding the proof, its instances and transcript, along with
+//! the description of the circuit it was made for, i.e. K and the circuit configuration of
+//! the image, so that it is verified without the image. The image is identified by the md5
+//! of its binary, and by its hash with the checksum feature, and the verifying key by its
+//! fingerprint, which is checked before verifying. The image hash and the public inputs and
+//! outputs are checked against the instances too, so that a bundle cannot claim an image or
+//! an io other than the ones it is proved for.
+
+use std::io::Read;
+use std::io::Write;
+
+use halo2_proofs::pairing::bn256::Bn256;
+use halo2_proofs::pairing::bn256::Fr;
+use halo2_proofs::pairing::bn256::G1Affine;
+use halo2_proofs::pairing::group::ff::PrimeField;
+use halo2_proofs::plonk::VerifyingKey;
+use halo2_proofs::poly::commitment::Params;
+use halo2_proofs::poly::commitment::ParamsVerifier;
+use serde::Deserialize;
+use serde::Serialize;
+use sha2::Digest;
+use sha2::Sha256;
+
+use crate::circuits::config::CircuitConfigure;
+use crate::circuits::TestCircuit;
+
+use super::verify_proof_with_transcript;
+use super::Proof;
+use super::ProverError;
+use super::Result;
+use super::Transcript;
+use super::Witness;
+use super::ZkWasmProver;
+
+/// Bumped whenever the layout of the bundle changes.
+pub const PROOF_BUNDLE_FORMAT_VERSION: u32 = 2;
+
+/// The instances of the KV roots before and after the execution, followed by the one of
+/// whether the execution terminates.
+const STATE_INSTANCES: usize = 3;
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProofBundle {
+    pub format_version: u32,
+    /// The version of the crate the circuit comes from.
+    pub circuit_version: String,
+    pub k: u32,
+    pub circuit_configure: CircuitConfigure,
+    pub transcript: Transcript,
+    pub image_md5: String,
+    /// The image hash, with the checksum feature.
+    pub image_hash: Option<String>,
+    pub entry: String,
+    pub expose_result: bool,
+    /// The public inputs and outputs of the execution, with the trap code if it traps.
+    pub public_inputs_and_outputs: Vec<u64>,
+    /// Hex encoded SHA-256 digest of the verifying key.
+    pub vkey_fingerprint: String,
+    /// Hex encoded instances of the circuit, i.e. the image hash and the post image hash
+    /// with the checksum feature, the KV roots, then the public inputs and outputs.
+    pub instances: Vec<String>,
+    /// Hex encoded transcript of the proof.
+    pub proof: String,
+}
+
+/// The SHA-256 digest of the verifying key as written by `VerifyingKey::write`.
+pub fn vkey_fingerprint(vk: &VerifyingKey<G1Affine>) -> Result<[u8; 32]> {
+    let mut buf = vec![];
+    vk.write(&mut buf)?;
+
+    Ok(Sha256::digest(&buf).into())
+}
+
+fn decode_hex(field: &str, value: &str) -> Result<Vec<u8>> {
+    hex::decode(value)
+        .map_err(|err| ProverError::MalformedProof(format!("invalid {}: {}", field, err)))
+}
+
+fn decode_fr(field: &str, value: &str) -> Result<Fr> {
+    let mut repr = <Fr as PrimeField>::Repr::default();
+    let bytes = decode_hex(field, value)?;
+    if bytes.len() != repr.as_ref().len() {
+        return Err(ProverError::MalformedProof(format!(
+            "{} {} is not {} bytes",
+            field,
+            value,
+            repr.as_ref().len()
+        )));
+    }
+    repr.as_mut().copy_from_slice(&bytes);
+
+    Option::from(Fr::from_repr(repr))
+        .ok_or_else(|| ProverError::MalformedProof(format!("{} {} is not in Fr", field, value)))
+}
+
+impl ProofBundle {
+    pub fn write<W: Write>(&self, writer: W) -> Result<()> {
+        serde_json::to_writer_pretty(writer, self).map_err(|err| ProverError::Io(err.into()))
+    }
+
+    pub fn read<R: Read>(reader: R) -> Result<Self> {
+        let bundle: ProofBundle = serde_json::from_reader(reader)
+            .map_err(|err| ProverError::MalformedProof(err.to_string()))?;
+
+        if bundle.format_version != PROOF_BUNDLE_FORMAT_VERSION {
+            return Err(ProverError::MalformedProof(format!(
+                "proof bundle format version {} is not supported, expected {}",
+                bundle.format_version, PROOF_BUNDLE_FORMAT_VERSION
+            )));
+        }
+
+        Ok(bundle)
+    }
+
+    pub fn read_vkey<R: Read>(
+        &self,
+        params: &Params<G1Affine>,
+        mut reader: R,
+    ) -> Result<VerifyingKey<G1Affine>> {
+        Ok(VerifyingKey::read::<_, TestCircuit<Fr>>(
+            &mut reader,
+            params,
+            self.circuit_configure.clone(),
+        )?)
+    }
+
+    pub fn instances(&self) -> Result<Vec<Fr>> {
+        self.instances
+            .iter()
+            .map(|instance| decode_fr("instance", instance))
+            .collect()
+    }
+
+    /// Checks the instances are the ones of the image and the io of a terminated execution
+    /// of the bundle, the post image hash and the KV roots are only checked by the proof.
+    fn check_instances(&self, instances: &[Fr]) -> Result<()> {
+        #[allow(unused_mut)]
+        let mut leading = STATE_INSTANCES;
+
+        #[cfg(feature = "checksum")]
+        {
+            let image_hash = self.image_hash.as_ref().ok_or_else(|| {
+                ProverError::MalformedProof("the bundle has no image hash".to_owned())
+            })?;
+
+            if instances.first() != Some(&decode_fr("image hash", image_hash)?) {
+                return Err(ProverError::MalformedProof(
+                    "the instances are not the ones of the image of the bundle".to_owned(),
+                ));
+            }
+
+            // The image hash and the post image hash.
+            leading += 2;
+        }
+
+        let public_inputs_and_outputs = self
+            .public_inputs_and_outputs
+            .iter()
+            .map(|v| Fr::from(*v))
+            .collect::<Vec<_>>();
+
+        // A prefix of the execution, e.g. a segment, is not the execution of the bundle.
+        if instances.get(leading - 1) != Some(&Fr::from(1)) {
+            return Err(ProverError::MalformedProof(
+                "the instances are not the ones of a terminated execution".to_owned(),
+            ));
+        }
+
+        if instances.len() < leading || instances[leading..] != public_inputs_and_outputs[..] {
+            return Err(ProverError::MalformedProof(
+                "the instances are not the ones of the public inputs and outputs of the bundle"
+                    .to_owned(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Verifies the proof of the bundle, `vk` must be the key the bundle was made with.
+    pub fn verify(&self, params: &Params<G1Affine>, vk: &VerifyingKey<G1Affine>) -> Result<()> {
+        if params.k != self.k {
+            return Err(ProverError::Params(format!(
+                "params of K {} are given to a bundle of K {}",
+                params.k, self.k
+            )));
+        }
+
+        if hex::encode(vkey_fingerprint(vk)?) != self.vkey_fingerprint {
+            return Err(ProverError::KeyMismatch(
+                "the verifying key is not the one of the bundle".to_owned(),
+            ));
+        }
+
+        let instances = self.instances()?;
+        self.check_instances(&instances)?;
+
+        let proof = Proof {
+            transcript: self.transcript,
+            bytes: decode_hex("proof", &self.proof)?,
+        };
+
+        let params_verifier: ParamsVerifier<Bn256> = params
+            .verifier(instances.len())
+            .map_err(|err| ProverError::Params(format!("{:?}", err)))?;
+
+        verify_proof_with_transcript(&params_verifier, vk, &[&[&instances]], &proof)
+            .map_err(ProverError::Verification)
+    }
+}
+
+impl ZkWasmProver {
+    /// Bundles `proof` of the execution of `witness` with the circuit it was made for.
+    pub fn bundle(
+        &self,
+        vk: &VerifyingKey<G1Affine>,
+        witness: &Witness,
+        proof: &Proof,
+    ) -> Result<ProofBundle> {
+        #[cfg(feature = "checksum")]
+        let image_hash = Some(hex::encode(self.image_hash().to_repr()));
+        #[cfg(not(feature = "checksum"))]
+        let image_hash = None;
+
+        Ok(ProofBundle {
+            format_version: PROOF_BUNDLE_FORMAT_VERSION,
+            circuit_version: env!("CARGO_PKG_VERSION").to_owned(),
+            k: self.k,
+            circuit_configure: self.circuit_configure(),
+            transcript: proof.transcript,
+            image_md5: format!("{:X}", md5::compute(&self.wasm)),
+            image_hash,
+            entry: self.entry.name.clone(),
+            expose_result: self.entry.expose_result,
+            public_inputs_and_outputs: witness.public_inputs_and_outputs.clone(),
+            vkey_fingerprint: hex::encode(vkey_fingerprint(vk)?),
+            instances: self
+                .circuit_instances(&witness.instances)
+                .iter()
+                .map(|instance| hex::encode(instance.to_repr()))
+                .collect(),
+            proof: hex::encode(&proof.bytes),
+        })
+    }
+}
@@ -0,0 +1,561 @@
+//! Setup, witness generation, proving and verification of an image in memory.
+//!
+//! `ZkWasmProver` is bound to an image, its entry and K. Params, keys, proofs and
+//! instances are taken and returned as values, nothing is read from or written to disk,
+//! and every failure is reported as a `ProverError`.
+//!
+//! The instances of a proof are the public instances of the execution, i.e. the post
+//! image hash with the checksum feature, the roots of the KV store before and after the
+//! execution if the image calls it, then the public inputs and outputs. With the
+//! checksum feature, the image hash is prepended to them by `prove` and `verify`, so that
+//! the image a proof is verified against is the one of the prover.
+//!
+//! Executions read the KV store of the prover, an empty store in memory unless one is
+//! given by `with_kv_store`, and leave it as it is: the nodes an execution writes are
+//! committed to the store by `commit_kv_store` once its proof is created.
+//!
+//! With the checksum feature, an execution too long for the circuit is proved as segments
+//! by `generate_segments`, `prove_segments` and `verify_segments`.
+//!
+//! A prover is `Send` and `Sync`, so that a service shares one among its workers. A panic
+//! while generating keys, witnesses or proofs is reported as a `ProverError` instead of
+//! unwinding into the worker.
+
+use std::any::Any;
+use std::fmt;
+use std::io;
+use std::io::Read;
+use std::panic;
+use std::panic::AssertUnwindSafe;
+use std::sync::Arc;
+use std::sync::Mutex;
+
+use anyhow::anyhow;
+
+use halo2_proofs::pairing::bn256::Bn256;
+use halo2_proofs::pairing::bn256::Fr;
+use halo2_proofs::pairing::bn256::G1Affine;
+use halo2_proofs::plonk::keygen_pk;
+use halo2_proofs::plonk::keygen_vk;
+use halo2_proofs::plonk::ProvingKey;
+use halo2_proofs::plonk::VerifyingKey;
+use halo2_proofs::poly::commitment::Params;
+use halo2_proofs::poly::commitment::ParamsVerifier;
+use specs::step::TrapCode;
+use specs::CompilationTable;
+use specs::ExecutionTable;
+use specs::Tables;
+use wasmi::ImportsBuilder;
+use wasmi::RuntimeValue;
+
+use crate::circuits::config::CircuitConfigure;
+use crate::circuits::config::MIN_K;
+use crate::circuits::TestCircuit;
+use crate::foreign::babyjubjub_helper::runtime::register_babyjubjub_foreign;
+use crate::foreign::keccak_helper::runtime::register_keccak_foreign;
+use crate::foreign::kv_helper;
+use crate::foreign::kv_helper::kv_instances;
+use crate::foreign::kv_helper::runtime::register_kv_foreign;
+use crate::foreign::kv_helper::store::KvStore;
+use crate::foreign::kv_helper::store::MemoryKvStore;
+use crate::foreign::kv_helper::store::SharedKvStore;
+use crate::foreign::log_helper::register_log_foreign;
+use crate::foreign::poseidon_helper::runtime::register_poseidon_foreign;
+use crate::foreign::require_helper::register_require_foreign;
+use crate::foreign::sha256_helper::runtime::register_sha256_foreign;
+use crate::foreign::wasm_input_helper::runtime::register_wasm_input_foreign;
+#[cfg(feature = "checksum")]
+use crate::image_hasher::ImageHasher;
+use crate::runtime::budget::estimate_k;
+use crate::runtime::budget::CircuitSizeEstimate;
+use crate::runtime::budget::ExecutionBudget;
+use crate::runtime::entry::EntryPoint;
+use crate::runtime::entry::ENTRY_WRAPPER;
+use crate::runtime::host::host_env::HostEnv;
+use crate::runtime::wasmi_interpreter::Execution;
+use crate::runtime::wasmi_interpreter::WasmRuntimeIO;
+use crate::runtime::ExecutionResult;
+use crate::runtime::WasmInterpreter;
+
+mod aggregate;
+mod bundle;
+mod params;
+mod proof;
+mod proving_key;
+#[cfg(feature = "checksum")]
+mod segment;
+mod solidity;
+
+pub use aggregate::aggregate_images;
+pub use aggregate::pad;
+pub use aggregate::AggregateLayout;
+pub use aggregate::AggregateTarget;
+pub use aggregate::AGGREGATE_PREFIX;
+pub use bundle::vkey_fingerprint;
+pub use bundle::ProofBundle;
+pub use bundle::PROOF_BUNDLE_FORMAT_VERSION;
+pub use params::downsize_params;
+pub use params::import_params;
+pub use proof::Proof;
+pub use proof::Transcript;
+pub use proof::PROOF_FORMAT_VERSION;
+pub use proving_key::PROVING_KEY_FORMAT_VERSION;
+pub use solidity::SolidityCalldata;
+
+pub(crate) use proof::create_proof_with_transcript;
+pub(crate) use proof::verify_proof_with_transcript;
+
+#[derive(Debug)]
+pub enum ProverError {
+    /// K is below `MIN_K`.
+    InvalidK(u32),
+    /// The image cannot be loaded or compiled.
+    Image(anyhow::Error),
+    /// The execution failed, e.g. it trapped in a host function or exceeded the capacity
+    /// of the circuit, in which case the error is a `CapacityExceeded`.
+    Execution(anyhow::Error),
+    /// The params do not fit the circuit or the instances.
+    Params(String),
+    /// A persisted key does not belong to the circuit of the prover.
+    KeyMismatch(String),
+    /// A persisted proof cannot be read, e.g. its transcript is unknown.
+    MalformedProof(String),
+    /// The proof is made with a transcript the verifier cannot check, e.g. on EVM.
+    UnsupportedTranscript(Transcript),
+    /// A segment does not start where its previous segment stops.
+    UnlinkedSegments(String),
+    Aggregation(String),
+    Io(io::Error),
+    Keygen(halo2_proofs::plonk::Error),
+    Proof(halo2_proofs::plonk::Error),
+    Verification(halo2_proofs::plonk::Error),
+    /// The prover panicked, e.g. on a circuit it failed to synthesize.
+    Panicked(String),
+}
+
+impl fmt::Display for ProverError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProverError::InvalidK(k) => write!(f, "K {} is less than the minimum {}", k, MIN_K),
+            ProverError::Image(err) => write!(f, "failed to load the image: {}", err),
+            ProverError::Execution(err) => write!(f, "execution failed: {}", err),
+            ProverError::Params(err) => write!(f, "invalid params: {}", err),
+            ProverError::KeyMismatch(err) => write!(f, "key mismatch: {}", err),
+            ProverError::MalformedProof(err) => write!(f, "malformed proof: {}", err),
+            ProverError::UnlinkedSegments(err) => write!(f, "unlinked segments: {}", err),
+            ProverError::Aggregation(err) => write!(f, "aggregation failed: {}", err),
+            ProverError::UnsupportedTranscript(transcript) => {
+                write!(f, "proofs of {} transcript are not supported", transcript)
+            }
+            ProverError::Io(err) => write!(f, "{}", err),
+            ProverError::Keygen(err) => write!(f, "key generation failed: {:?}", err),
+            ProverError::Proof(err) => write!(f, "proof generation failed: {:?}", err),
+            ProverError::Verification(err) => write!(f, "verification failed: {:?}", err),
+            ProverError::Panicked(err) => write!(f, "prover panicked: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for ProverError {}
+
+impl From<io::Error> for ProverError {
+    fn from(err: io::Error) -> Self {
+        ProverError::Io(err)
+    }
+}
+
+pub type Result<T> = std::result::Result<T, ProverError>;
+
+fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    payload
+        .downcast_ref::<&str>()
+        .map(|message| message.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown panic".to_owned())
+}
+
+/// Runs `f`, a panic is reported as `ProverError::Panicked` instead of unwinding into the
+/// caller.
+pub(crate) fn catch_panic<T>(f: impl FnOnce() -> Result<T>) -> Result<T> {
+    panic::catch_unwind(AssertUnwindSafe(f))
+        .unwrap_or_else(|payload| Err(ProverError::Panicked(panic_message(payload))))
+}
+
+/// The circuit of an execution with its public instances.
+#[derive(Clone)]
+pub struct Witness {
+    pub circuit: TestCircuit<Fr>,
+    pub instances: Vec<Fr>,
+    /// The public inputs and outputs of the execution, with the trap code if it traps, which
+    /// are the trailing instances.
+    pub public_inputs_and_outputs: Vec<u64>,
+    /// The public outputs of the execution, without the inputs.
+    pub outputs: Vec<u64>,
+    pub result: Option<RuntimeValue>,
+    pub trap: Option<TrapCode>,
+}
+
+impl Witness {
+    fn new(k: u32, execution_result: ExecutionResult<RuntimeValue>) -> Self {
+        let instances = execution_instances(
+            &execution_result.tables,
+            &execution_result.public_inputs_and_outputs,
+        );
+
+        Witness {
+            circuit: TestCircuit::new(k, execution_result.tables),
+            instances,
+            public_inputs_and_outputs: execution_result.public_inputs_and_outputs,
+            outputs: execution_result.outputs,
+            result: execution_result.result,
+            trap: execution_result.trap,
+        }
+    }
+}
+
+/// The public instances of an execution of `tables`, without the image hash.
+fn execution_instances(tables: &Tables, public_inputs_and_outputs: &[u64]) -> Vec<Fr> {
+    let mut instances: Vec<Fr> = vec![];
+
+    #[cfg(feature = "checksum")]
+    instances.push(tables.post_image_table.hash());
+
+    instances.append(&mut kv_instances(tables));
+    instances.push(Fr::from(tables.is_terminated() as u64));
+
+    instances.append(
+        &mut public_inputs_and_outputs
+            .iter()
+            .map(|v| (*v).into())
+            .collect(),
+    );
+
+    instances
+}
+
+pub struct ZkWasmProver {
+    k: u32,
+    wasm: Vec<u8>,
+    entry: EntryPoint,
+    compilation_tables: CompilationTable,
+    kv_store: SharedKvStore,
+}
+
+impl ZkWasmProver {
+    /// Compiles `entry` of `wasm` for a circuit of size `k`.
+    pub fn new(k: u32, wasm: Vec<u8>, entry: EntryPoint) -> Result<Self> {
+        if k < MIN_K {
+            return Err(ProverError::InvalidK(k));
+        }
+
+        let compilation_tables = {
+            let compiler = WasmInterpreter::new(k);
+            let module = compiler
+                .load_entry(&wasm, &entry)
+                .map_err(ProverError::Image)?;
+
+            let (env, _) = host_env(vec![], vec![], empty_kv_store());
+            let imports = ImportsBuilder::new().with_resolver("env", &env);
+
+            compiler
+                .compile(
+                    &module,
+                    &imports,
+                    &env.function_description_table(),
+                    ENTRY_WRAPPER,
+                )
+                .map_err(ProverError::Image)?
+                .tables
+        };
+
+        Ok(ZkWasmProver {
+            k,
+            wasm,
+            entry,
+            compilation_tables,
+            kv_store: empty_kv_store(),
+        })
+    }
+
+    /// Executes the image on the KV store `kv_store` instead of an empty one.
+    pub fn with_kv_store(mut self, kv_store: Box<dyn KvStore>) -> Self {
+        self.kv_store = Arc::new(Mutex::new(kv_store));
+
+        self
+    }
+
+    pub fn k(&self) -> u32 {
+        self.k
+    }
+
+    pub fn compilation_tables(&self) -> &CompilationTable {
+        &self.compilation_tables
+    }
+
+    #[cfg(feature = "checksum")]
+    pub fn image_hash(&self) -> Fr {
+        self.compilation_tables.hash()
+    }
+
+    /// Params from an insecure setup, which are only suitable for testing.
+    pub fn unsafe_params(&self) -> Params<G1Affine> {
+        Params::<G1Affine>::unsafe_setup::<Bn256>(self.k)
+    }
+
+    /// The configuration of the circuit of the image.
+    pub fn circuit_configure(&self) -> CircuitConfigure {
+        CircuitConfigure::new(self.k, &self.compilation_tables)
+    }
+
+    /// The circuit of the image, which is all the keys depend on.
+    pub fn circuit_without_witness(&self) -> TestCircuit<Fr> {
+        TestCircuit::new(
+            self.k,
+            Tables {
+                // An empty execution leaves the image unchanged.
+                post_image_table: self.compilation_tables.clone(),
+                compilation_tables: self.compilation_tables.clone(),
+                execution_tables: ExecutionTable::default(),
+            },
+        )
+    }
+
+    fn check_params(&self, params: &Params<G1Affine>) -> Result<()> {
+        if params.k != self.k {
+            return Err(ProverError::Params(format!(
+                "params of K {} are given to a circuit of K {}",
+                params.k, self.k
+            )));
+        }
+
+        Ok(())
+    }
+
+    pub fn keygen_vk(&self, params: &Params<G1Affine>) -> Result<VerifyingKey<G1Affine>> {
+        self.check_params(params)?;
+
+        catch_panic(|| {
+            let circuit = self.circuit_without_witness();
+
+            keygen_vk(params, &circuit).map_err(ProverError::Keygen)
+        })
+    }
+
+    pub fn keygen_pk(
+        &self,
+        params: &Params<G1Affine>,
+        vk: VerifyingKey<G1Affine>,
+    ) -> Result<ProvingKey<G1Affine>> {
+        self.check_params(params)?;
+
+        catch_panic(|| {
+            let circuit = self.circuit_without_witness();
+
+            keygen_pk(params, vk, &circuit).map_err(ProverError::Keygen)
+        })
+    }
+
+    /// Reads a verifying key written with `VerifyingKey::write`, whose constraint system is
+    /// the one of the circuit of the image.
+    pub fn read_vkey<R: Read>(
+        &self,
+        params: &Params<G1Affine>,
+        mut reader: R,
+    ) -> Result<VerifyingKey<G1Affine>> {
+        Ok(VerifyingKey::read::<_, TestCircuit<Fr>>(
+            &mut reader,
+            params,
+            self.circuit_configure(),
+        )?)
+    }
+
+    /// Generates the proving key, the verifying key is the one of the proving key.
+    pub fn setup(&self, params: &Params<G1Affine>) -> Result<ProvingKey<G1Affine>> {
+        let vk = self.keygen_vk(params)?;
+
+        self.keygen_pk(params, vk)
+    }
+
+    fn execute(
+        &self,
+        compiler: WasmInterpreter,
+        public_inputs: Vec<u64>,
+        private_inputs: Vec<u64>,
+    ) -> Result<ExecutionResult<RuntimeValue>> {
+        let module = compiler
+            .load_entry(&self.wasm, &self.entry)
+            .map_err(ProverError::Image)?;
+
+        let (mut env, wasm_runtime_io) =
+            host_env(public_inputs, private_inputs, self.kv_store.clone());
+        let imports = ImportsBuilder::new().with_resolver("env", &env);
+
+        let compiled_module = compiler
+            .compile(
+                &module,
+                &imports,
+                &env.function_description_table(),
+                ENTRY_WRAPPER,
+            )
+            .map_err(ProverError::Image)?;
+
+        // Host functions panic on invalid inputs, e.g. an unsatisfied `require`, the panic
+        // is reported as an execution error instead of unwinding into the caller.
+        panic::catch_unwind(AssertUnwindSafe(|| {
+            compiled_module.run(&mut env, wasm_runtime_io)
+        }))
+        .unwrap_or_else(|payload| Err(anyhow!(panic_message(payload))))
+        .map_err(ProverError::Execution)
+    }
+
+    /// Executes the image and returns the circuit of the execution.
+    pub fn generate_witness(
+        &self,
+        public_inputs: Vec<u64>,
+        private_inputs: Vec<u64>,
+    ) -> Result<Witness> {
+        let execution_result =
+            self.execute(WasmInterpreter::new(self.k), public_inputs, private_inputs)?;
+
+        catch_panic(|| Ok(Witness::new(self.k, execution_result)))
+    }
+
+    /// Executes the image without bounding it by the circuit and estimates the smallest K
+    /// it can be proved with.
+    pub fn estimate_k(
+        &self,
+        public_inputs: Vec<u64>,
+        private_inputs: Vec<u64>,
+    ) -> Result<CircuitSizeEstimate> {
+        let execution_result = self.execute(
+            WasmInterpreter::with_budget(self.k, ExecutionBudget::unbounded()),
+            public_inputs,
+            private_inputs,
+        )?;
+
+        Ok(estimate_k(&execution_result.tables))
+    }
+
+    /// The instances the circuit is proved with, i.e. `instances` preceded by the image
+    /// hash with the checksum feature.
+    pub fn circuit_instances(&self, instances: &[Fr]) -> Vec<Fr> {
+        #[allow(unused_mut)]
+        let mut circuit_instances = vec![];
+
+        #[cfg(feature = "checksum")]
+        circuit_instances.push(self.image_hash());
+
+        circuit_instances.extend_from_slice(instances);
+
+        circuit_instances
+    }
+
+    pub fn prove(
+        &self,
+        params: &Params<G1Affine>,
+        pk: &ProvingKey<G1Affine>,
+        witness: &Witness,
+        transcript: Transcript,
+    ) -> Result<Proof> {
+        self.prove_with_instances(
+            params,
+            pk,
+            witness,
+            &self.circuit_instances(&witness.instances),
+            transcript,
+        )
+    }
+
+    /// Proves the circuit of `witness` with the instances of the circuit `instances`.
+    fn prove_with_instances(
+        &self,
+        params: &Params<G1Affine>,
+        pk: &ProvingKey<G1Affine>,
+        witness: &Witness,
+        instances: &[Fr],
+        transcript: Transcript,
+    ) -> Result<Proof> {
+        self.check_params(params)?;
+
+        catch_panic(|| {
+            create_proof_with_transcript(
+                transcript,
+                params,
+                pk,
+                &[witness.circuit.clone()],
+                &[&[instances]],
+            )
+            .map_err(ProverError::Proof)
+        })
+    }
+
+    /// Commits the nodes written by the execution of `witness` to the KV store, once it is
+    /// proven. The store must not have been committed to since the execution.
+    pub fn commit_kv_store(&self, witness: &Witness) -> Result<()> {
+        let mut kv_store = self
+            .kv_store
+            .lock()
+            .map_err(|_| ProverError::Panicked("the kv store is poisoned".to_owned()))?;
+
+        kv_helper::commit(
+            kv_store.as_mut(),
+            &witness.circuit.tables.execution_tables.kvtable,
+        )?;
+
+        Ok(())
+    }
+
+    /// Verifies `proof` with the transcript it was made with.
+    pub fn verify(
+        &self,
+        params: &Params<G1Affine>,
+        vk: &VerifyingKey<G1Affine>,
+        instances: &[Fr],
+        proof: &Proof,
+    ) -> Result<()> {
+        self.verify_with_instances(params, vk, &self.circuit_instances(instances), proof)
+    }
+
+    /// Verifies `proof` against the instances of the circuit `instances`.
+    fn verify_with_instances(
+        &self,
+        params: &Params<G1Affine>,
+        vk: &VerifyingKey<G1Affine>,
+        instances: &[Fr],
+        proof: &Proof,
+    ) -> Result<()> {
+        self.check_params(params)?;
+
+        let params_verifier: ParamsVerifier<Bn256> = params
+            .verifier(instances.len())
+            .map_err(|err| ProverError::Params(format!("{:?}", err)))?;
+
+        catch_panic(|| {
+            verify_proof_with_transcript(&params_verifier, vk, &[&[instances]], proof)
+                .map_err(ProverError::Verification)
+        })
+    }
+}
+
+fn empty_kv_store() -> SharedKvStore {
+    Arc::new(Mutex::new(Box::new(MemoryKvStore::default())))
+}
+
+fn host_env(
+    public_inputs: Vec<u64>,
+    private_inputs: Vec<u64>,
+    kv_store: SharedKvStore,
+) -> (HostEnv, WasmRuntimeIO) {
+    let mut env = HostEnv::new();
+    let wasm_runtime_io = register_wasm_input_foreign(&mut env, public_inputs, private_inputs);
+    register_require_foreign(&mut env);
+    register_sha256_foreign(&mut env);
+    register_keccak_foreign(&mut env);
+    register_poseidon_foreign(&mut env);
+    register_kv_foreign(&mut env, kv_store, &wasm_runtime_io);
+    register_babyjubjub_foreign(&mut env);
+    register_log_foreign(&mut env);
+    env.finalize();
+
+    (env, wasm_runtime_io)
+}
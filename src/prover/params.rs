@@ -0,0 +1,223 @@
+//! Params imported from a trusted setup ceremony.
+//!
+//! Params made by `Params::unsafe_setup` are derived from a secret known to whoever ran
+//! it, they are only fit for tests. Production params are imported from a powers-of-tau
+//! ceremony instead, either in the halo2 format (e.g. converted from the perpetual
+//! powers-of-tau) or as a snarkjs `.ptau` file, and downsized to the K of the circuit.
+
+use std::io::Read;
+use std::io::Seek;
+use std::io::SeekFrom;
+
+use halo2_proofs::arithmetic::best_fft;
+use halo2_proofs::arithmetic::parallelize;
+use halo2_proofs::arithmetic::CurveAffine;
+use halo2_proofs::pairing::bn256::Fq;
+use halo2_proofs::pairing::bn256::Fq2;
+use halo2_proofs::pairing::bn256::Fr;
+use halo2_proofs::pairing::bn256::G1Affine;
+use halo2_proofs::pairing::bn256::G2Affine;
+use halo2_proofs::pairing::bn256::G1;
+use halo2_proofs::pairing::group::ff::Field;
+use halo2_proofs::pairing::group::ff::PrimeField;
+use halo2_proofs::pairing::group::prime::PrimeCurveAffine;
+use halo2_proofs::pairing::group::Curve;
+use halo2_proofs::pairing::group::GroupEncoding;
+use halo2_proofs::poly::commitment::Params;
+use halo2_proofs::poly::EvaluationDomain;
+
+use super::ProverError;
+use super::Result;
+
+const PTAU_MAGIC: &[u8; 4] = b"ptau";
+const PTAU_HEADER_SECTION: u32 = 1;
+const PTAU_TAU_G1_SECTION: u32 = 2;
+const PTAU_TAU_G2_SECTION: u32 = 3;
+
+/// Downsizes params to K, the powers of tau of the smaller params are a prefix of the
+/// larger ones, only the Lagrange basis has to be recomputed.
+pub fn downsize_params(params: &Params<G1Affine>, k: u32) -> Result<Params<G1Affine>> {
+    if k > params.k {
+        return Err(ProverError::Params(format!(
+            "params of K {} cannot be downsized to K {}",
+            params.k, k
+        )));
+    }
+
+    if k == params.k {
+        return Ok(params.clone());
+    }
+
+    let g = params.g[..1 << k].to_vec();
+    let g_lagrange = lagrange_basis(&g, k);
+
+    Ok(Params {
+        k,
+        n: 1 << k,
+        g,
+        g_lagrange,
+        additional_data: params.additional_data.clone(),
+    })
+}
+
+/// Imports the params of K from a ceremony file, either params in the halo2 format or a
+/// snarkjs `.ptau` file, which is told by its magic.
+pub fn import_params<R: Read + Seek>(mut reader: R, k: u32) -> Result<Params<G1Affine>> {
+    let mut magic = [0u8; 4];
+    reader.read_exact(&mut magic)?;
+    reader.seek(SeekFrom::Start(0))?;
+
+    if &magic == PTAU_MAGIC {
+        read_ptau(reader, k)
+    } else {
+        let params = Params::<G1Affine>::read(&mut reader)?;
+
+        downsize_params(&params, k)
+    }
+}
+
+/// The commitments to the Lagrange polynomials of the domain of size 2^K, i.e. the
+/// inverse FFT of the powers of tau.
+fn lagrange_basis(g: &[G1Affine], k: u32) -> Vec<G1Affine> {
+    let mut g_projective = g.iter().map(|p| p.to_curve()).collect::<Vec<G1>>();
+
+    let omega_inv = EvaluationDomain::<Fr>::new(1, k).get_omega_inv();
+    best_fft(&mut g_projective, omega_inv, k);
+
+    let n_inv = Fr::from(g.len() as u64).invert().unwrap();
+    parallelize(&mut g_projective, |points, _| {
+        for point in points.iter_mut() {
+            *point = *point * n_inv;
+        }
+    });
+
+    let mut g_lagrange = vec![G1Affine::identity(); g.len()];
+    G1::batch_normalize(&g_projective, &mut g_lagrange);
+
+    g_lagrange
+}
+
+fn read_u32<R: Read>(reader: &mut R) -> Result<u32> {
+    let mut bytes = [0u8; 4];
+    reader.read_exact(&mut bytes)?;
+
+    Ok(u32::from_le_bytes(bytes))
+}
+
+fn read_u64<R: Read>(reader: &mut R) -> Result<u64> {
+    let mut bytes = [0u8; 8];
+    reader.read_exact(&mut bytes)?;
+
+    Ok(u64::from_le_bytes(bytes))
+}
+
+/// snarkjs stores the coordinates in Montgomery form, `r_inv` is the inverse of the
+/// Montgomery constant 2^256.
+fn read_fq<R: Read>(reader: &mut R, r_inv: &Fq) -> Result<Fq> {
+    let mut repr = <Fq as PrimeField>::Repr::default();
+    reader.read_exact(repr.as_mut())?;
+
+    Option::<Fq>::from(Fq::from_repr(repr))
+        .map(|montgomery| montgomery * r_inv)
+        .ok_or_else(|| ProverError::Params("invalid base field element in ptau".to_owned()))
+}
+
+fn read_g1<R: Read>(reader: &mut R, r_inv: &Fq) -> Result<G1Affine> {
+    let x = read_fq(reader, r_inv)?;
+    let y = read_fq(reader, r_inv)?;
+
+    Option::from(G1Affine::from_xy(x, y))
+        .ok_or_else(|| ProverError::Params("invalid G1 point in ptau".to_owned()))
+}
+
+fn read_g2<R: Read>(reader: &mut R, r_inv: &Fq) -> Result<G2Affine> {
+    let x = Fq2 {
+        c0: read_fq(reader, r_inv)?,
+        c1: read_fq(reader, r_inv)?,
+    };
+    let y = Fq2 {
+        c0: read_fq(reader, r_inv)?,
+        c1: read_fq(reader, r_inv)?,
+    };
+
+    Option::from(G2Affine::from_xy(x, y))
+        .ok_or_else(|| ProverError::Params("invalid G2 point in ptau".to_owned()))
+}
+
+/// Reads the first 2^K powers of tau of a snarkjs `.ptau` file. The sections of the file
+/// are located first, since snarkjs does not guarantee their order.
+fn read_ptau<R: Read + Seek>(mut reader: R, k: u32) -> Result<Params<G1Affine>> {
+    reader.seek(SeekFrom::Start(PTAU_MAGIC.len() as u64))?;
+
+    let _version = read_u32(&mut reader)?;
+    let n_sections = read_u32(&mut reader)?;
+
+    let mut sections = vec![];
+    for _ in 0..n_sections {
+        let section = read_u32(&mut reader)?;
+        let size = read_u64(&mut reader)?;
+        let offset = reader.stream_position()?;
+
+        sections.push((section, offset));
+        reader.seek(SeekFrom::Current(size as i64))?;
+    }
+
+    let seek_section = |reader: &mut R, section: u32| -> Result<()> {
+        let (_, offset) = sections
+            .iter()
+            .find(|(id, _)| *id == section)
+            .ok_or_else(|| ProverError::Params(format!("ptau has no section {}", section)))?;
+        reader.seek(SeekFrom::Start(*offset))?;
+
+        Ok(())
+    };
+
+    seek_section(&mut reader, PTAU_HEADER_SECTION)?;
+    let n8 = read_u32(&mut reader)?;
+    if n8 as usize != <Fq as PrimeField>::Repr::default().as_ref().len() {
+        return Err(ProverError::Params(format!(
+            "ptau of {} bytes field elements is not over bn254",
+            n8
+        )));
+    }
+    reader.seek(SeekFrom::Current(n8 as i64))?;
+    let power = read_u32(&mut reader)?;
+    if k > power {
+        return Err(ProverError::Params(format!(
+            "ptau of power {} cannot provide params of K {}",
+            power, k
+        )));
+    }
+
+    let r_inv = Fq::from(2).pow_vartime(&[256]).invert().unwrap();
+
+    seek_section(&mut reader, PTAU_TAU_G1_SECTION)?;
+    let g = (0..1usize << k)
+        .map(|_| read_g1(&mut reader, &r_inv))
+        .collect::<Result<Vec<_>>>()?;
+
+    seek_section(&mut reader, PTAU_TAU_G2_SECTION)?;
+    let g2 = read_g2(&mut reader, &r_inv)?;
+    let s_g2 = read_g2(&mut reader, &r_inv)?;
+
+    if g[0] != G1Affine::generator() || g2 != G2Affine::generator() {
+        return Err(ProverError::Params(
+            "the powers of tau of ptau do not start from the generators".to_owned(),
+        ));
+    }
+
+    let g_lagrange = lagrange_basis(&g, k);
+
+    // The verifier reads [1]_2 and [tau]_2 from the additional data, as laid out by
+    // `Params::unsafe_setup`.
+    let mut additional_data = Vec::from(g2.to_bytes().as_ref());
+    additional_data.extend(s_g2.to_bytes().as_ref());
+
+    Ok(Params {
+        k,
+        n: 1 << k,
+        g,
+        g_lagrange,
+        additional_data,
+    })
+}
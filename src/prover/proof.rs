@@ -0,0 +1,206 @@
+//! Proofs along with the transcript they were made with.
+//!
+//! A proof is only verified with the transcript it was made with, so the transcript is
+//! recorded in the header of a persisted proof and `verify` picks it by itself.
+
+use std::fmt;
+use std::io::Read;
+use std::io::Write;
+use std::str::FromStr;
+
+use halo2_proofs::pairing::bn256::Bn256;
+use halo2_proofs::pairing::bn256::Fr;
+use halo2_proofs::pairing::bn256::G1Affine;
+use halo2_proofs::plonk::create_proof;
+use halo2_proofs::plonk::verify_proof;
+use halo2_proofs::plonk::Error;
+use halo2_proofs::plonk::ProvingKey;
+use halo2_proofs::plonk::SingleVerifier;
+use halo2_proofs::plonk::VerifyingKey;
+use halo2_proofs::poly::commitment::Params;
+use halo2_proofs::poly::commitment::ParamsVerifier;
+use halo2_proofs::transcript::Blake2bRead;
+use halo2_proofs::transcript::Blake2bWrite;
+use halo2_proofs::transcript::Challenge255;
+use halo2aggregator_s::transcript::poseidon::PoseidonRead;
+use halo2aggregator_s::transcript::poseidon::PoseidonWrite;
+use halo2aggregator_s::transcript::sha256::ShaRead;
+use halo2aggregator_s::transcript::sha256::ShaWrite;
+use rand::rngs::OsRng;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::circuits::TestCircuit;
+
+use super::ProverError;
+use super::Result;
+
+const PROOF_MAGIC: &[u8; 4] = b"ZKPF";
+/// Bumped whenever the layout of the header changes.
+pub const PROOF_FORMAT_VERSION: u32 = 1;
+
+/// The hash the Fiat-Shamir transcript of a proof is built on. Poseidon is cheap to verify
+/// in a circuit, e.g. by the aggregator, and SHA-256 is cheap to verify on EVM.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Transcript {
+    Blake2b,
+    Poseidon,
+    Sha256,
+}
+
+impl Transcript {
+    fn tag(&self) -> u8 {
+        match self {
+            Transcript::Blake2b => 0,
+            Transcript::Poseidon => 1,
+            Transcript::Sha256 => 2,
+        }
+    }
+
+    fn from_tag(tag: u8) -> Result<Self> {
+        match tag {
+            0 => Ok(Transcript::Blake2b),
+            1 => Ok(Transcript::Poseidon),
+            2 => Ok(Transcript::Sha256),
+            _ => Err(ProverError::MalformedProof(format!(
+                "unknown transcript {}",
+                tag
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for Transcript {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Transcript::Blake2b => write!(f, "blake2b"),
+            Transcript::Poseidon => write!(f, "poseidon"),
+            Transcript::Sha256 => write!(f, "sha256"),
+        }
+    }
+}
+
+impl FromStr for Transcript {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "blake2b" => Ok(Transcript::Blake2b),
+            "poseidon" => Ok(Transcript::Poseidon),
+            "sha256" => Ok(Transcript::Sha256),
+            _ => Err(format!(
+                "unknown transcript {}, expected blake2b, poseidon or sha256",
+                s
+            )),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Proof {
+    pub transcript: Transcript,
+    /// The transcript of the proof, as read by the verifiers of `transcript`.
+    pub bytes: Vec<u8>,
+}
+
+impl Proof {
+    pub fn write<W: Write>(&self, mut writer: W) -> Result<()> {
+        writer.write_all(PROOF_MAGIC)?;
+        writer.write_all(&PROOF_FORMAT_VERSION.to_le_bytes())?;
+        writer.write_all(&[self.transcript.tag()])?;
+        writer.write_all(&self.bytes)?;
+        writer.flush()?;
+
+        Ok(())
+    }
+
+    pub fn read<R: Read>(mut reader: R) -> Result<Self> {
+        let mut magic = [0u8; 4];
+        reader.read_exact(&mut magic)?;
+        if &magic != PROOF_MAGIC {
+            return Err(ProverError::MalformedProof("not a zkwasm proof".to_owned()));
+        }
+
+        let mut word = [0u8; 4];
+        reader.read_exact(&mut word)?;
+        let version = u32::from_le_bytes(word);
+        if version != PROOF_FORMAT_VERSION {
+            return Err(ProverError::MalformedProof(format!(
+                "proof format version {} is not supported, expected {}",
+                version, PROOF_FORMAT_VERSION
+            )));
+        }
+
+        let mut tag = [0u8; 1];
+        reader.read_exact(&mut tag)?;
+        let transcript = Transcript::from_tag(tag[0])?;
+
+        let mut bytes = vec![];
+        reader.read_to_end(&mut bytes)?;
+
+        Ok(Proof { transcript, bytes })
+    }
+}
+
+/// Proves `circuits` with the transcript of `transcript`.
+pub(crate) fn create_proof_with_transcript(
+    transcript: Transcript,
+    params: &Params<G1Affine>,
+    pk: &ProvingKey<G1Affine>,
+    circuits: &[TestCircuit<Fr>],
+    instances: &[&[&[Fr]]],
+) -> std::result::Result<Proof, Error> {
+    let bytes = match transcript {
+        Transcript::Blake2b => {
+            let mut writer = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
+            create_proof(params, pk, circuits, instances, OsRng, &mut writer)?;
+            writer.finalize()
+        }
+        Transcript::Poseidon => {
+            let mut writer = PoseidonWrite::init(vec![]);
+            create_proof(params, pk, circuits, instances, OsRng, &mut writer)?;
+            writer.finalize()
+        }
+        Transcript::Sha256 => {
+            let mut writer = ShaWrite::<_, _, Challenge255<_>, sha2::Sha256>::init(vec![]);
+            create_proof(params, pk, circuits, instances, OsRng, &mut writer)?;
+            writer.finalize()
+        }
+    };
+
+    Ok(Proof { transcript, bytes })
+}
+
+pub(crate) fn verify_proof_with_transcript(
+    params_verifier: &ParamsVerifier<Bn256>,
+    vk: &VerifyingKey<G1Affine>,
+    instances: &[&[&[Fr]]],
+    proof: &Proof,
+) -> std::result::Result<(), Error> {
+    let strategy = SingleVerifier::new(params_verifier);
+
+    match proof.transcript {
+        Transcript::Blake2b => verify_proof(
+            params_verifier,
+            vk,
+            strategy,
+            instances,
+            &mut Blake2bRead::<_, _, Challenge255<_>>::init(&proof.bytes[..]),
+        ),
+        Transcript::Poseidon => verify_proof(
+            params_verifier,
+            vk,
+            strategy,
+            instances,
+            &mut PoseidonRead::init(&proof.bytes[..]),
+        ),
+        Transcript::Sha256 => verify_proof(
+            params_verifier,
+            vk,
+            strategy,
+            instances,
+            &mut ShaRead::<_, _, _, sha2::Sha256>::init(&proof.bytes[..]),
+        ),
+    }
+}
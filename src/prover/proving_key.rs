@@ -0,0 +1,107 @@
+//! Proving keys persisted along with the fingerprint of the circuit they were generated for.
+//!
+//! A proving key is written after a header holding K and the fingerprint of the image, a
+//! key is only read back by the prover of the same image and K, so that a stale key is
+//! reported instead of producing invalid proofs.
+
+use std::io::Read;
+use std::io::Write;
+
+use halo2_proofs::pairing::bn256::Fr;
+use halo2_proofs::pairing::bn256::G1Affine;
+use halo2_proofs::plonk::ProvingKey;
+use halo2_proofs::poly::commitment::Params;
+use sha2::Digest;
+use sha2::Sha256;
+
+use crate::circuits::TestCircuit;
+
+use super::ProverError;
+use super::Result;
+use super::ZkWasmProver;
+
+const PROVING_KEY_MAGIC: &[u8; 4] = b"ZKPK";
+/// Bumped whenever the layout of the header changes.
+pub const PROVING_KEY_FORMAT_VERSION: u32 = 1;
+
+impl ZkWasmProver {
+    /// The fingerprint of the circuit of the image, i.e. the SHA-256 digest of its
+    /// compilation tables and of the version of the crate, which the keys depend on.
+    pub fn image_fingerprint(&self) -> [u8; 32] {
+        let mut hasher = Sha256::new();
+
+        hasher.update(env!("CARGO_PKG_VERSION").as_bytes());
+        hasher.update(
+            serde_json::to_vec(&self.compilation_tables)
+                .expect("compilation tables are serializable"),
+        );
+
+        hasher.finalize().into()
+    }
+
+    pub fn write_proving_key<W: Write>(
+        &self,
+        pk: &ProvingKey<G1Affine>,
+        mut writer: W,
+    ) -> Result<()> {
+        writer.write_all(PROVING_KEY_MAGIC)?;
+        writer.write_all(&PROVING_KEY_FORMAT_VERSION.to_le_bytes())?;
+        writer.write_all(&self.k.to_le_bytes())?;
+        writer.write_all(&self.image_fingerprint())?;
+
+        pk.write(&mut writer)?;
+        writer.flush()?;
+
+        Ok(())
+    }
+
+    /// Reads a proving key written by `write_proving_key`, it fails with `KeyMismatch` if
+    /// the key was generated for another image or K.
+    pub fn read_proving_key<R: Read>(
+        &self,
+        params: &Params<G1Affine>,
+        mut reader: R,
+    ) -> Result<ProvingKey<G1Affine>> {
+        self.check_params(params)?;
+
+        let mut magic = [0u8; 4];
+        reader.read_exact(&mut magic)?;
+        if &magic != PROVING_KEY_MAGIC {
+            return Err(ProverError::KeyMismatch(
+                "not a zkwasm proving key".to_owned(),
+            ));
+        }
+
+        let mut word = [0u8; 4];
+        reader.read_exact(&mut word)?;
+        let version = u32::from_le_bytes(word);
+        if version != PROVING_KEY_FORMAT_VERSION {
+            return Err(ProverError::KeyMismatch(format!(
+                "proving key format version {} is not supported, expected {}",
+                version, PROVING_KEY_FORMAT_VERSION
+            )));
+        }
+
+        reader.read_exact(&mut word)?;
+        let k = u32::from_le_bytes(word);
+        if k != self.k {
+            return Err(ProverError::KeyMismatch(format!(
+                "the proving key is generated with K {} but K is {}",
+                k, self.k
+            )));
+        }
+
+        let mut fingerprint = [0u8; 32];
+        reader.read_exact(&mut fingerprint)?;
+        if fingerprint != self.image_fingerprint() {
+            return Err(ProverError::KeyMismatch(
+                "the proving key is generated for another image".to_owned(),
+            ));
+        }
+
+        let pk =
+            ProvingKey::read::<_, TestCircuit<Fr>>(&mut reader, params, self.circuit_configure())?;
+
+        Ok(pk)
+    }
+}
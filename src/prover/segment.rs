@@ -0,0 +1,285 @@
+//! Segmented proving: an execution too long for the circuit is proved as a sequence of
+//! segments, each one by its own proof of the circuit of the prover.
+//!
+//! The instances of a segment are the ones of its circuit: the hash of the image it starts
+//! from, then the public instances of its execution, among which whether it terminates the
+//! execution. The verifier checks the first segment starts from the image of the prover
+//! and each following one from the image its previous segment leaves behind, that the KV
+//! store of a segment is the one its previous segment leaves behind, and that the last
+//! segment terminates the execution. Segments proved out of order, with one missing in
+//! between, or a prefix of the execution are rejected.
+//!
+//! `aggregate_segments` proves the same in a single aggregate: the aggregate circuit
+//! constrains the instances of adjacent segments to be linked, and commits to the
+//! instances of the first and the last segments, which its verifier checks.
+//!
+//! The execution is split once it is traced, as the tracer only stops at host calls: the
+//! whole trace of the execution is held in memory while the segments are built.
+
+use std::path::Path;
+
+use halo2_proofs::pairing::bn256::Bn256;
+use halo2_proofs::pairing::bn256::Fr;
+use halo2_proofs::pairing::bn256::G1Affine;
+use halo2_proofs::plonk::ProvingKey;
+use halo2_proofs::plonk::VerifyingKey;
+use halo2_proofs::poly::commitment::Params;
+use halo2_proofs::poly::commitment::ParamsVerifier;
+use halo2aggregator_s::circuit_verifier::build_aggregate_verify_circuit;
+use halo2aggregator_s::circuits::utils::TranscriptHash;
+
+use super::aggregate::prove_root;
+use super::aggregate::AggregateLayout;
+use super::catch_panic;
+use super::execution_instances;
+use super::Proof;
+use super::ProverError;
+use super::Result;
+use super::Transcript;
+use super::Witness;
+use super::ZkWasmProver;
+use crate::circuits::TestCircuit;
+use crate::runtime::budget::ExecutionBudget;
+use crate::runtime::segment::is_linked;
+use crate::runtime::segment::split_execution;
+use crate::runtime::WasmInterpreter;
+
+/// The indexes of the instances of a segment: the hashes of the images it starts from and
+/// leaves behind, the roots of the KV store before and after it, then whether it
+/// terminates the execution.
+const PRE_IMAGE_INSTANCE: usize = 0;
+const POST_IMAGE_INSTANCE: usize = 1;
+const KV_OLD_ROOT_INSTANCE: usize = 2;
+const KV_NEW_ROOT_INSTANCE: usize = 3;
+const TERMINATED_INSTANCE: usize = 4;
+
+/// The instances of adjacent segments constrained to be equal by the aggregate circuit, as
+/// `[proof, instance, proof, instance]`: each segment starts from the image and the KV
+/// store its previous segment leaves behind.
+fn segment_links(n_segments: usize) -> Vec<[usize; 4]> {
+    (1..n_segments)
+        .flat_map(|next| {
+            [
+                [next - 1, POST_IMAGE_INSTANCE, next, PRE_IMAGE_INSTANCE],
+                [next - 1, KV_NEW_ROOT_INSTANCE, next, KV_OLD_ROOT_INSTANCE],
+            ]
+        })
+        .collect()
+}
+
+impl ZkWasmProver {
+    /// The most steps a segment can have in the circuit of the prover.
+    pub fn segment_size(&self) -> usize {
+        ExecutionBudget::for_circuit(self.k).steps
+    }
+
+    /// Executes the image without bounding it by the circuit and splits the execution into
+    /// segments of at most `segment_size` steps.
+    ///
+    /// The instances of each witness are the instances of its circuit, i.e. they start with
+    /// the hash of the image of the segment. The outputs, the result and the trap of the
+    /// execution are the ones of the last witness.
+    pub fn generate_segments(
+        &self,
+        public_inputs: Vec<u64>,
+        private_inputs: Vec<u64>,
+        segment_size: usize,
+    ) -> Result<Vec<Witness>> {
+        let execution_result = self.execute(
+            WasmInterpreter::with_budget(self.k, ExecutionBudget::unbounded()),
+            public_inputs,
+            private_inputs,
+        )?;
+
+        catch_panic(|| {
+            let segments = split_execution(
+                &execution_result.tables,
+                &execution_result.public_inputs_and_outputs,
+                segment_size,
+            )
+            .map_err(ProverError::Execution)?;
+
+            let last = segments.len() - 1;
+            let mut witnesses = vec![];
+
+            for (index, segment) in segments.into_iter().enumerate() {
+                ExecutionBudget::for_circuit(self.k)
+                    .check(
+                        &segment.tables.compilation_tables,
+                        &segment.tables.execution_tables,
+                    )
+                    .map_err(|err| ProverError::Execution(err.into()))?;
+
+                let mut instances = vec![segment.tables.compilation_tables.hash()];
+                instances.append(&mut execution_instances(
+                    &segment.tables,
+                    &segment.public_inputs_and_outputs,
+                ));
+
+                witnesses.push(Witness {
+                    circuit: TestCircuit::new(self.k, segment.tables),
+                    instances,
+                    public_inputs_and_outputs: segment.public_inputs_and_outputs,
+                    outputs: vec![],
+                    result: None,
+                    trap: None,
+                });
+
+                if index == last {
+                    let witness = witnesses.last_mut().unwrap();
+
+                    witness.outputs = execution_result.outputs.clone();
+                    witness.result = execution_result.result;
+                    witness.trap = execution_result.trap;
+                }
+            }
+
+            Ok(witnesses)
+        })
+    }
+
+    /// Proves each segment of `witnesses` in order.
+    pub fn prove_segments(
+        &self,
+        params: &Params<G1Affine>,
+        pk: &ProvingKey<G1Affine>,
+        witnesses: &[Witness],
+        transcript: Transcript,
+    ) -> Result<Vec<Proof>> {
+        witnesses
+            .iter()
+            .map(|witness| {
+                self.prove_with_instances(params, pk, witness, &witness.instances, transcript)
+            })
+            .collect()
+    }
+
+    /// Checks the segments given in order by their instances are the whole execution of
+    /// the image: the first one starts from the image, each following one from the image
+    /// and the KV store its previous one leaves behind, and the last one terminates.
+    fn check_segments(&self, instances: &[Vec<Fr>]) -> Result<()> {
+        if instances
+            .iter()
+            .any(|instances| instances.len() <= TERMINATED_INSTANCE)
+        {
+            return Err(ProverError::UnlinkedSegments(
+                "the instances of a segment are truncated".to_owned(),
+            ));
+        }
+
+        match instances.first() {
+            None => {
+                return Err(ProverError::UnlinkedSegments(
+                    "no segment is given".to_owned(),
+                ))
+            }
+            Some(first) if first[PRE_IMAGE_INSTANCE] != self.image_hash() => {
+                return Err(ProverError::UnlinkedSegments(
+                    "the first segment does not start from the image".to_owned(),
+                ))
+            }
+            Some(_) => (),
+        }
+
+        if !is_linked(instances) {
+            return Err(ProverError::UnlinkedSegments(
+                "a segment does not start from the image its previous segment leaves behind"
+                    .to_owned(),
+            ));
+        }
+
+        let is_kv_linked = instances
+            .windows(2)
+            .all(|pair| pair[0][KV_NEW_ROOT_INSTANCE] == pair[1][KV_OLD_ROOT_INSTANCE]);
+
+        if !is_kv_linked {
+            return Err(ProverError::UnlinkedSegments(
+                "a segment does not start from the KV store its previous segment leaves behind"
+                    .to_owned(),
+            ));
+        }
+
+        if instances.last().unwrap()[TERMINATED_INSTANCE] != Fr::from(1) {
+            return Err(ProverError::UnlinkedSegments(
+                "the last segment does not terminate the execution".to_owned(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Verifies the proofs of the segments of an execution, given in order with their
+    /// instances, and that each segment starts where its previous one stops.
+    pub fn verify_segments(
+        &self,
+        params: &Params<G1Affine>,
+        vk: &VerifyingKey<G1Affine>,
+        segments: &[(Vec<Fr>, Proof)],
+    ) -> Result<()> {
+        self.check_segments(
+            &segments
+                .iter()
+                .map(|(instances, _)| instances.clone())
+                .collect::<Vec<_>>(),
+        )?;
+
+        for (instances, proof) in segments {
+            self.verify_with_instances(params, vk, instances, proof)?;
+        }
+
+        Ok(())
+    }
+
+    /// Aggregates the proofs of the segments of an execution, given in order with their
+    /// instances, into the proof of `AGGREGATE_PREFIX`. The proofs must be of the poseidon
+    /// transcript, `params` are the params of the K of the prover.
+    ///
+    /// The aggregate circuit verifies every segment and links each one to its previous one,
+    /// a verifier of the aggregate proof only checks the first segment starts from the
+    /// image and the last one terminates, by the instances the aggregate commits to.
+    pub fn aggregate_segments(
+        &self,
+        dir: &Path,
+        params: &Params<G1Affine>,
+        vk: &VerifyingKey<G1Affine>,
+        aggregate_k: u32,
+        segments: &[(Vec<Fr>, Proof)],
+    ) -> Result<AggregateLayout> {
+        let mut layout = AggregateLayout::new(segments.len(), segments.len())?;
+        layout.images = vec![hex::encode(self.image_fingerprint()); segments.len()];
+
+        // Reports the unlinked or failing segment instead of an unsatisfied aggregate
+        // circuit.
+        self.verify_segments(params, vk, segments)?;
+
+        let mut instances = vec![];
+        let mut proofs = vec![];
+        for (segment_instances, proof) in segments {
+            if proof.transcript != Transcript::Poseidon {
+                return Err(ProverError::UnsupportedTranscript(proof.transcript));
+            }
+
+            instances.push(vec![segment_instances.clone()]);
+            proofs.push(proof.bytes.clone());
+        }
+
+        let params_verifier: ParamsVerifier<Bn256> = params
+            .verifier(instances.iter().map(|i| i[0].len()).max().unwrap())
+            .map_err(|err| ProverError::Params(format!("{:?}", err)))?;
+
+        let (root, root_instances) = catch_panic(|| {
+            Ok(build_aggregate_verify_circuit::<Bn256>(
+                &params_verifier,
+                &vec![vk; segments.len()][..],
+                instances.iter().collect(),
+                proofs,
+                TranscriptHash::Poseidon,
+                segment_links(segments.len()),
+            ))
+        })?;
+
+        prove_root(dir, aggregate_k, root, root_instances)?;
+
+        Ok(layout)
+    }
+}
@@ -0,0 +1,133 @@
+//! Solidity verifiers of single proofs.
+//!
+//! The verifier is rendered by `halo2aggregator-s` from the templates of its aggregator
+//! contract, with the circuit of the image in place of the aggregator circuit. A contract
+//! hashes its transcript with the `SHA256` precompile, so only proofs made with the
+//! `Sha256` transcript can be checked on EVM.
+
+use std::path::Path;
+
+use halo2_proofs::pairing::bn256::Bn256;
+use halo2_proofs::pairing::bn256::Fr;
+use halo2_proofs::pairing::bn256::G1Affine;
+use halo2_proofs::pairing::group::ff::PrimeField;
+use halo2_proofs::plonk::VerifyingKey;
+use halo2_proofs::poly::commitment::Params;
+use halo2_proofs::poly::commitment::ParamsVerifier;
+use halo2aggregator_s::solidity_verifier::codegen::solidity_aux_gen;
+use halo2aggregator_s::solidity_verifier::solidity_render;
+use serde::Serialize;
+
+use super::Proof;
+use super::ProverError;
+use super::Result;
+use super::Transcript;
+use super::ZkWasmProver;
+
+/// The arguments of the `verify` call of the contract, as hex encoded 256-bit words.
+#[derive(Serialize)]
+pub struct SolidityCalldata {
+    pub proof: Vec<String>,
+    pub instances: Vec<String>,
+}
+
+fn hex_word(le_bytes: &[u8]) -> String {
+    let mut be_bytes = le_bytes.to_vec();
+    be_bytes.reverse();
+
+    format!("0x{:0>64}", hex::encode(be_bytes))
+}
+
+impl ZkWasmProver {
+    fn solidity_params_verifier(
+        &self,
+        params: &Params<G1Affine>,
+        instances: &[Fr],
+        proof: &Proof,
+    ) -> Result<ParamsVerifier<Bn256>> {
+        self.check_params(params)?;
+
+        if proof.transcript != Transcript::Sha256 {
+            return Err(ProverError::UnsupportedTranscript(proof.transcript));
+        }
+
+        params
+            .verifier(self.circuit_instances(instances).len())
+            .map_err(|err| ProverError::Params(format!("{:?}", err)))
+    }
+
+    /// Renders the contracts verifying the proofs of the image from the templates of
+    /// `template_dir` to `contract_dir`. `instances` and `proof` are a sample the rendering
+    /// is checked against.
+    pub fn render_solidity_verifier(
+        &self,
+        params: &Params<G1Affine>,
+        vk: &VerifyingKey<G1Affine>,
+        instances: &[Fr],
+        proof: &Proof,
+        template_dir: &Path,
+        contract_dir: &Path,
+    ) -> Result<()> {
+        let params_verifier = self.solidity_params_verifier(params, instances, proof)?;
+
+        solidity_render(
+            &format!("{}/*", template_dir.display()),
+            &contract_dir.display().to_string(),
+            vec![(
+                "AggregatorConfig.sol.tera".to_owned(),
+                "AggregatorConfig.sol".to_owned(),
+            )],
+            "AggregatorVerifierStepStart.sol.tera",
+            "AggregatorVerifierStepEnd.sol.tera",
+            |i| format!("AggregatorVerifierStep{}.sol", i + 1),
+            // Without aggregation, the contract checks no target circuit.
+            &params_verifier,
+            &params_verifier,
+            vk,
+            &self.circuit_instances(instances),
+            proof.bytes.clone(),
+        );
+
+        Ok(())
+    }
+
+    /// Writes the auxiliary data the contract is called with along with the proof to
+    /// `aux_path`.
+    pub fn solidity_aux(
+        &self,
+        params: &Params<G1Affine>,
+        vk: &VerifyingKey<G1Affine>,
+        instances: &[Fr],
+        proof: &Proof,
+        aux_path: &Path,
+    ) -> Result<()> {
+        let params_verifier = self.solidity_params_verifier(params, instances, proof)?;
+
+        solidity_aux_gen(
+            &params_verifier,
+            vk,
+            &self.circuit_instances(instances),
+            proof.bytes.clone(),
+            &aux_path.to_path_buf(),
+        );
+
+        Ok(())
+    }
+
+    /// The proof and the instances, including the image hash with the checksum feature, as
+    /// they are passed to the contract.
+    pub fn solidity_calldata(&self, instances: &[Fr], proof: &Proof) -> Result<SolidityCalldata> {
+        if proof.transcript != Transcript::Sha256 {
+            return Err(ProverError::UnsupportedTranscript(proof.transcript));
+        }
+
+        Ok(SolidityCalldata {
+            proof: proof.bytes.chunks(32).map(hex_word).collect(),
+            instances: self
+                .circuit_instances(instances)
+                .iter()
+                .map(|instance| hex_word(instance.to_repr().as_ref()))
+                .collect(),
+        })
+    }
+}
@@ -0,0 +1,449 @@
+//! The capacity of the circuit an execution is proved in.
+//!
+//! Every table of the circuit, the tables of the plugins included, takes a fixed number of
+//! rows per entry out of the rows available for `k`. An execution is checked against the
+//! capacity of each table as it is traced, see `BudgetMonitor`, and once it is traced, so
+//! that an oversized trace is reported before any circuit is synthesized, or against the
+//! rows of each K to estimate the smallest K it fits in.
+
+use std::collections::HashSet;
+use std::fmt;
+
+use specs::etable::EventTable;
+use specs::host_function::HostPlugin;
+use specs::itable::UnaryOp;
+use specs::jtable::JumpTable;
+use specs::jtable::INHERITED_FRAME_TABLE_ENTRIES;
+use specs::jtable::STATIC_FRAME_ENTRY_NUMBER;
+use specs::mtable::AccessType;
+use specs::mtable::LocationType;
+use specs::step::StepInfo;
+use specs::CompilationTable;
+use specs::ExecutionTable;
+use specs::Tables;
+
+use crate::circuits::bit_table::STEP_SIZE as BIT_TABLE_ENTRY_ROWS;
+#[cfg(feature = "checksum")]
+use crate::circuits::config::max_image_table_rows;
+#[cfg(feature = "checksum")]
+use crate::circuits::config::max_init_memory_table_rows;
+use crate::circuits::config::MIN_K;
+use crate::circuits::etable::EVENT_TABLE_ENTRY_ROWS;
+use crate::circuits::jtable::JtableOffset;
+use crate::circuits::mtable::MEMORY_TABLE_ENTRY_ROWS;
+use crate::circuits::test_circuit::available_rows;
+use crate::circuits::test_circuit::blinding_factors;
+use crate::circuits::test_circuit::max_available_rows;
+use crate::foreign::babyjubjub_helper::circuits::assign as babyjubjub_table;
+use crate::foreign::keccak_helper::circuits::assign as keccak_table;
+use crate::foreign::kv_helper::circuits::assign as kv_table;
+use crate::foreign::poseidon_helper::circuits::assign as poseidon_table;
+use crate::foreign::sha256_helper::circuits::assign as sha256_table;
+use crate::runtime::memory_event_of_step;
+
+// The widest range table column not scaled with K is the u16 column.
+const RANGE_TABLE_ROWS: usize = 1 << 16;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CircuitTable {
+    EventTable,
+    MemoryTable,
+    JumpTable,
+    ExternalHostCallTable,
+    BitTable,
+    Sha256Table,
+    KeccakTable,
+    PoseidonTable,
+    KvTable,
+    BabyJubjubTable,
+    ImageTable,
+    RangeTable,
+}
+
+impl fmt::Display for CircuitTable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CircuitTable::EventTable => write!(f, "etable"),
+            CircuitTable::MemoryTable => write!(f, "mtable"),
+            CircuitTable::JumpTable => write!(f, "jtable"),
+            CircuitTable::ExternalHostCallTable => write!(f, "external host call table"),
+            CircuitTable::BitTable => write!(f, "bit table"),
+            CircuitTable::Sha256Table => write!(f, "sha256 helper table"),
+            CircuitTable::KeccakTable => write!(f, "keccak helper table"),
+            CircuitTable::PoseidonTable => write!(f, "poseidon helper table"),
+            CircuitTable::KvTable => write!(f, "kv helper table"),
+            CircuitTable::BabyJubjubTable => write!(f, "babyjubjub helper table"),
+            CircuitTable::ImageTable => write!(f, "image table"),
+            CircuitTable::RangeTable => write!(f, "range table"),
+        }
+    }
+}
+
+/// The execution needs `required` entries in `table` but only `capacity` fit.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CapacityExceeded {
+    pub table: CircuitTable,
+    pub required: usize,
+    pub capacity: usize,
+}
+
+impl CapacityExceeded {
+    pub fn overflow(&self) -> usize {
+        self.required - self.capacity
+    }
+}
+
+impl fmt::Display for CapacityExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "capacity exceeded: the {} requires {} entries but only {} fit, {} over",
+            self.table,
+            self.required,
+            self.capacity,
+            self.overflow()
+        )
+    }
+}
+
+impl std::error::Error for CapacityExceeded {}
+
+// An execution exceeding the budget of a table other than the etable traps at the host call
+// observing it.
+impl wasmi::HostError for CapacityExceeded {}
+
+/// The maximal number of entries of each table.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ExecutionBudget {
+    /// Steps in the etable.
+    pub steps: usize,
+    /// Memory writing entries, i.e. the init and write accesses of the mtable.
+    pub memory_ops: usize,
+    /// Frames in the jtable, static frames included.
+    pub frames: usize,
+    pub external_host_calls: usize,
+    pub bit_ops: usize,
+    /// Blocks compressed by the sha256 plugin.
+    pub sha256_blocks: usize,
+    /// Blocks absorbed by the keccak plugin.
+    pub keccak_blocks: usize,
+    /// Blocks absorbed by the poseidon plugin.
+    pub poseidon_blocks: usize,
+    pub kv_accesses: usize,
+    /// Terms of the sums of the babyjubjub plugin.
+    pub babyjubjub_terms: usize,
+}
+
+impl ExecutionBudget {
+    /// The budget of an execution proved with `k`.
+    pub fn for_circuit(k: u32) -> Self {
+        let rows = max_available_rows(k);
+
+        ExecutionBudget {
+            steps: rows / EVENT_TABLE_ENTRY_ROWS as usize,
+            // The mtable must end strictly before the available rows.
+            memory_ops: (rows - 1) / MEMORY_TABLE_ENTRY_ROWS as usize,
+            // The last entry is taken by the terminate row.
+            frames: rows / JtableOffset::JtableOffsetMax as usize - 1,
+            // The first row is taken by the header.
+            external_host_calls: rows - 1,
+            bit_ops: rows / BIT_TABLE_ENTRY_ROWS,
+            sha256_blocks: rows / sha256_table::ENTRY_ROWS,
+            keccak_blocks: rows / keccak_table::ENTRY_ROWS,
+            poseidon_blocks: rows / poseidon_table::ENTRY_ROWS,
+            kv_accesses: rows / kv_table::ENTRY_ROWS,
+            babyjubjub_terms: rows / babyjubjub_table::ENTRY_ROWS,
+        }
+    }
+
+    /// A budget no execution exceeds, to trace an execution whatever its size is.
+    pub fn unbounded() -> Self {
+        ExecutionBudget {
+            steps: usize::MAX,
+            memory_ops: usize::MAX,
+            frames: usize::MAX,
+            external_host_calls: usize::MAX,
+            bit_ops: usize::MAX,
+            sha256_blocks: usize::MAX,
+            keccak_blocks: usize::MAX,
+            poseidon_blocks: usize::MAX,
+            kv_accesses: usize::MAX,
+            babyjubjub_terms: usize::MAX,
+        }
+    }
+
+    /// The capacities of the tables, in the order of `traced_entries`.
+    fn capacities(&self) -> [usize; 10] {
+        [
+            self.steps,
+            self.memory_ops,
+            self.frames,
+            self.external_host_calls,
+            self.bit_ops,
+            self.sha256_blocks,
+            self.keccak_blocks,
+            self.poseidon_blocks,
+            self.kv_accesses,
+            self.babyjubjub_terms,
+        ]
+    }
+
+    pub fn check(
+        &self,
+        compilation_tables: &CompilationTable,
+        execution_tables: &ExecutionTable,
+    ) -> Result<(), CapacityExceeded> {
+        check_entries(
+            traced_entries(compilation_tables, execution_tables),
+            self.capacities(),
+        )
+    }
+
+    /// A monitor of the executions of `compilation_tables` against the budget.
+    pub fn monitor(&self, compilation_tables: &CompilationTable) -> BudgetMonitor {
+        BudgetMonitor {
+            capacities: self.capacities(),
+            static_frames: static_frames(compilation_tables),
+            observed: 0,
+            memory_writes: 0,
+            initialized: HashSet::new(),
+            external_host_calls: 0,
+            bit_ops: 0,
+        }
+    }
+}
+
+fn check_entries<const N: usize>(
+    entries: [(CircuitTable, usize); N],
+    capacities: [usize; 10],
+) -> Result<(), CapacityExceeded> {
+    for ((table, required), capacity) in entries.into_iter().zip(capacities) {
+        if required > capacity {
+            return Err(CapacityExceeded {
+                table,
+                required,
+                capacity,
+            });
+        }
+    }
+
+    Ok(())
+}
+
+/// Counts the entries of the tables as an execution is traced, so that an execution
+/// exceeding its budget is aborted as soon as it is observed instead of once it is traced.
+///
+/// The steps are bounded at each step by the step limit of the etable of the tracer, see
+/// `EventTable::set_step_limit`. The other tables are observed at each host call, where the
+/// runtime regains control. The entries of the plugin tables depend on the calls that
+/// follow, they are only checked once the execution is traced.
+pub struct BudgetMonitor {
+    capacities: [usize; 10],
+    static_frames: usize,
+    /// The steps counted so far.
+    observed: usize,
+    memory_writes: usize,
+    /// The heap and global locations accessed so far, each initialized in the mtable.
+    initialized: HashSet<(LocationType, u32)>,
+    external_host_calls: usize,
+    bit_ops: usize,
+}
+
+impl BudgetMonitor {
+    /// Counts the steps of `etable` traced since the last observation, then checks the
+    /// entries of the tables traced so far against the budget.
+    pub fn observe(
+        &mut self,
+        etable: &EventTable,
+        jtable: &JumpTable,
+    ) -> Result<(), CapacityExceeded> {
+        for entry in &etable.entries()[self.observed..] {
+            for mentry in memory_event_of_step(entry, &mut 1) {
+                if mentry.atype != AccessType::Read {
+                    self.memory_writes += 1;
+                }
+
+                if mentry.ltype == LocationType::Heap || mentry.ltype == LocationType::Global {
+                    self.initialized.insert((mentry.ltype, mentry.offset));
+                }
+            }
+
+            if let StepInfo::ExternalHostCall { .. } = entry.step_info {
+                self.external_host_calls += 1;
+            }
+
+            if is_bit_table_op(&entry.step_info) {
+                self.bit_ops += 1;
+            }
+        }
+        self.observed = etable.entries().len();
+
+        check_entries(
+            [
+                (CircuitTable::EventTable, self.observed),
+                (
+                    CircuitTable::MemoryTable,
+                    self.memory_writes + self.initialized.len(),
+                ),
+                (
+                    CircuitTable::JumpTable,
+                    self.static_frames + jtable.entries().len(),
+                ),
+                (
+                    CircuitTable::ExternalHostCallTable,
+                    self.external_host_calls,
+                ),
+                (CircuitTable::BitTable, self.bit_ops),
+            ],
+            self.capacities,
+        )
+    }
+}
+
+/// The frames of the jtable preceding the frames of the execution.
+fn static_frames(compilation_tables: &CompilationTable) -> usize {
+    if cfg!(feature = "checksum") {
+        // The static frames are padded to be committed by the image checksum.
+        STATIC_FRAME_ENTRY_NUMBER + INHERITED_FRAME_TABLE_ENTRIES
+    } else {
+        compilation_tables.static_jtable.len()
+    }
+}
+
+/// The entries of the tables filled by the execution, in the order of the fields of
+/// `ExecutionBudget`.
+fn traced_entries(
+    compilation_tables: &CompilationTable,
+    execution_tables: &ExecutionTable,
+) -> [(CircuitTable, usize); 10] {
+    let etable = &execution_tables.etable;
+
+    [
+        (CircuitTable::EventTable, etable.entries().len()),
+        (
+            CircuitTable::MemoryTable,
+            execution_tables
+                .mtable
+                .entries()
+                .iter()
+                .filter(|entry| entry.atype != AccessType::Read)
+                .count(),
+        ),
+        (
+            CircuitTable::JumpTable,
+            static_frames(compilation_tables) + execution_tables.jtable.entries().len(),
+        ),
+        (
+            CircuitTable::ExternalHostCallTable,
+            etable.filter_external_host_call_table().entries().len(),
+        ),
+        (
+            CircuitTable::BitTable,
+            etable
+                .entries()
+                .iter()
+                .filter(|entry| is_bit_table_op(&entry.step_info))
+                .count(),
+        ),
+        (
+            CircuitTable::Sha256Table,
+            sha256_table::table_entries(&etable.filter_foreign_entries(HostPlugin::Sha256)),
+        ),
+        (
+            CircuitTable::KeccakTable,
+            keccak_table::table_entries(&etable.filter_foreign_entries(HostPlugin::Keccak256)),
+        ),
+        (
+            CircuitTable::PoseidonTable,
+            poseidon_table::table_entries(&etable.filter_foreign_entries(HostPlugin::Poseidon)),
+        ),
+        (
+            CircuitTable::KvTable,
+            kv_table::table_entries(&etable.filter_foreign_entries(HostPlugin::MerkleKv)),
+        ),
+        (
+            CircuitTable::BabyJubjubTable,
+            babyjubjub_table::table_entries(&etable.filter_foreign_entries(HostPlugin::BabyJubjub)),
+        ),
+    ]
+}
+
+/// The smallest K an execution fits in, with the rows each table takes.
+#[derive(Clone, Debug)]
+pub struct CircuitSizeEstimate {
+    pub k: u32,
+    pub rows: Vec<(CircuitTable, usize)>,
+}
+
+impl fmt::Display for CircuitSizeEstimate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "k: {}", self.k)?;
+        for (table, rows) in self.rows.iter() {
+            writeln!(f, "  {}: {} rows", table, rows)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Estimates the smallest K the execution of `tables` can be proved with. The execution
+/// should be traced with an unbounded budget.
+pub fn estimate_k(tables: &Tables) -> CircuitSizeEstimate {
+    let compilation_tables = &tables.compilation_tables;
+
+    // With the checksum, the image table is padded to be hashed.
+    #[cfg(feature = "checksum")]
+    let image_rows = (max_image_table_rows() + max_init_memory_table_rows()) as usize;
+    // The instruction, br/elem and init memory sections each start with a header.
+    #[cfg(not(feature = "checksum"))]
+    let image_rows = 3
+        + compilation_tables.itable.entries().len()
+        + compilation_tables.itable.create_brtable().entries().len()
+        + compilation_tables.elem_table.entries().len()
+        + compilation_tables.imtable.entries().len();
+
+    let mut rows = traced_entries(compilation_tables, &tables.execution_tables)
+        .into_iter()
+        .map(|(table, entries)| {
+            let rows = match table {
+                CircuitTable::EventTable => entries * EVENT_TABLE_ENTRY_ROWS as usize,
+                CircuitTable::MemoryTable => entries * MEMORY_TABLE_ENTRY_ROWS as usize + 1,
+                CircuitTable::JumpTable => (entries + 1) * JtableOffset::JtableOffsetMax as usize,
+                CircuitTable::ExternalHostCallTable => entries + 1,
+                CircuitTable::BitTable => entries * BIT_TABLE_ENTRY_ROWS,
+                CircuitTable::Sha256Table => entries * sha256_table::ENTRY_ROWS,
+                CircuitTable::KeccakTable => entries * keccak_table::ENTRY_ROWS,
+                CircuitTable::PoseidonTable => entries * poseidon_table::ENTRY_ROWS,
+                CircuitTable::KvTable => entries * kv_table::ENTRY_ROWS,
+                CircuitTable::BabyJubjubTable => entries * babyjubjub_table::ENTRY_ROWS,
+                CircuitTable::ImageTable | CircuitTable::RangeTable => unreachable!(),
+            };
+
+            (table, rows)
+        })
+        .collect::<Vec<_>>();
+    rows.push((CircuitTable::ImageTable, image_rows));
+    rows.push((CircuitTable::RangeTable, RANGE_TABLE_ROWS));
+
+    let required = rows.iter().map(|(_, rows)| *rows).max().unwrap();
+    let blinding_factors = blinding_factors();
+
+    let mut k = MIN_K;
+    while available_rows(k, blinding_factors) < required {
+        k += 1;
+    }
+
+    CircuitSizeEstimate { k, rows }
+}
+
+fn is_bit_table_op(step_info: &StepInfo) -> bool {
+    matches!(
+        step_info,
+        StepInfo::I32BinBitOp { .. }
+            | StepInfo::I64BinBitOp { .. }
+            | StepInfo::UnaryOp {
+                class: UnaryOp::Popcnt,
+                ..
+            }
+    )
+}
@@ -0,0 +1,316 @@
+//! Invokes the entry function of an image with arguments.
+//!
+//! The execution always starts from an export without parameters, so the entry is wrapped
+//! into the export `ENTRY_WRAPPER`. The wrapper reads each parameter of the entry as a
+//! public input through `wasm_input`, calls the entry and, with `expose_result`, writes each
+//! of its results as a public output through `wasm_output`. It only depends on the
+//! signature of the entry, the arguments themselves are bound by the instance.
+
+use anyhow::anyhow;
+use anyhow::Result;
+use parity_wasm::elements::ExportEntry;
+use parity_wasm::elements::External;
+use parity_wasm::elements::Func;
+use parity_wasm::elements::FuncBody;
+use parity_wasm::elements::FunctionType;
+use parity_wasm::elements::ImportCountType;
+use parity_wasm::elements::ImportEntry;
+use parity_wasm::elements::ImportSection;
+use parity_wasm::elements::Instruction;
+use parity_wasm::elements::Instructions;
+use parity_wasm::elements::Internal;
+use parity_wasm::elements::Local;
+use parity_wasm::elements::Module;
+use parity_wasm::elements::Section;
+use parity_wasm::elements::Type;
+use parity_wasm::elements::TypeSection;
+use parity_wasm::elements::ValueType;
+use wasmi::RuntimeValue;
+
+pub const ENTRY_WRAPPER: &str = "zkwasm_entry";
+
+#[derive(Clone, Debug)]
+pub struct EntryPoint {
+    pub name: String,
+    /// Whether the results of the entry are appended to the public outputs.
+    pub expose_result: bool,
+}
+
+impl EntryPoint {
+    pub fn new(name: &str, expose_result: bool) -> Self {
+        EntryPoint {
+            name: name.to_owned(),
+            expose_result,
+        }
+    }
+
+    /// The public inputs binding `args` to the parameters of the entry of `wasm`, the float
+    /// arguments are bound by their bit patterns.
+    pub fn encode_args(&self, wasm: &[u8], args: &[RuntimeValue]) -> Result<Vec<u64>> {
+        let module = Module::from_bytes(wasm)?;
+        let ty = function_type(&module, export_index(&module, &self.name)?)?;
+
+        if ty.params().len() != args.len() {
+            return Err(anyhow!(
+                "{} takes {} arguments but {} were given",
+                self.name,
+                ty.params().len(),
+                args.len()
+            ));
+        }
+
+        ty.params()
+            .iter()
+            .zip(args)
+            .map(|(param, arg)| match (param, arg) {
+                (ValueType::I32, RuntimeValue::I32(v)) => Ok(*v as u32 as u64),
+                (ValueType::I64, RuntimeValue::I64(v)) => Ok(*v as u64),
+                (ValueType::F32, RuntimeValue::F32(v)) => Ok(v.to_bits() as u64),
+                (ValueType::F64, RuntimeValue::F64(v)) => Ok(v.to_bits()),
+                (param, arg) => Err(anyhow!(
+                    "{} expects an argument of {} but {:?} was given",
+                    self.name,
+                    param,
+                    arg
+                )),
+            })
+            .collect()
+    }
+}
+
+fn export_index(module: &Module, name: &str) -> Result<u32> {
+    module
+        .export_section()
+        .and_then(|section| {
+            section
+                .entries()
+                .iter()
+                .find_map(|entry| match entry.internal() {
+                    Internal::Function(idx) if entry.field() == name => Some(*idx),
+                    _ => None,
+                })
+        })
+        .ok_or_else(|| anyhow!("function {} is not exported", name))
+}
+
+fn function_type(module: &Module, idx: u32) -> Result<FunctionType> {
+    let imported = module.import_section().map_or(vec![], |section| {
+        section
+            .entries()
+            .iter()
+            .filter_map(|entry| match entry.external() {
+                External::Function(type_ref) => Some(*type_ref),
+                _ => None,
+            })
+            .collect()
+    });
+
+    let type_ref = match imported.get(idx as usize) {
+        Some(type_ref) => *type_ref,
+        None => module
+            .function_section()
+            .and_then(|section| section.entries().get(idx as usize - imported.len()))
+            .ok_or_else(|| anyhow!("function {} does not exist", idx))?
+            .type_ref(),
+    };
+
+    let Type::Function(ty) = module
+        .type_section()
+        .and_then(|section| section.types().get(type_ref as usize))
+        .ok_or_else(|| anyhow!("type {} does not exist", type_ref))?;
+
+    Ok(ty.clone())
+}
+
+fn type_ref_of(module: &mut Module, ty: FunctionType) -> u32 {
+    if module.type_section().is_none() {
+        module
+            .insert_section(Section::Type(TypeSection::with_types(vec![])))
+            .unwrap();
+    }
+
+    let types = module.type_section_mut().unwrap().types_mut();
+    match types.iter().position(|Type::Function(t)| *t == ty) {
+        Some(type_ref) => type_ref as u32,
+        None => {
+            types.push(Type::Function(ty));
+            types.len() as u32 - 1
+        }
+    }
+}
+
+/// The function index of the host function `env.name`, which is imported if the module
+/// does not import it yet. Importing a function shifts the indices of the functions
+/// defined by the module, they are relocated. An existing import must be of type `ty`.
+fn import_host_function(module: &mut Module, name: &str, ty: FunctionType) -> Result<u32> {
+    let imported = module.import_count(ImportCountType::Function) as u32;
+
+    if let Some(section) = module.import_section() {
+        let existing = section
+            .entries()
+            .iter()
+            .filter(|entry| matches!(entry.external(), External::Function(_)))
+            .position(|entry| entry.module() == "env" && entry.field() == name);
+
+        if let Some(idx) = existing {
+            let existing_ty = function_type(module, idx as u32)?;
+            if existing_ty != ty {
+                return Err(anyhow!(
+                    "env.{} is imported as {:?} but {:?} is expected",
+                    name,
+                    existing_ty,
+                    ty
+                ));
+            }
+
+            return Ok(idx as u32);
+        }
+    }
+
+    let type_ref = type_ref_of(module, ty);
+
+    if module.import_section().is_none() {
+        module
+            .insert_section(Section::Import(ImportSection::with_entries(vec![])))
+            .unwrap();
+    }
+
+    // The appended import follows the imported functions and precedes the defined ones.
+    module
+        .import_section_mut()
+        .unwrap()
+        .entries_mut()
+        .push(ImportEntry::new(
+            "env".to_owned(),
+            name.to_owned(),
+            External::Function(type_ref),
+        ));
+
+    let relocate = |idx: &mut u32| {
+        if *idx >= imported {
+            *idx += 1;
+        }
+    };
+
+    if let Some(section) = module.code_section_mut() {
+        for body in section.bodies_mut() {
+            for instruction in body.code_mut().elements_mut() {
+                if let Instruction::Call(idx) = instruction {
+                    relocate(idx);
+                }
+            }
+        }
+    }
+    if let Some(section) = module.elements_section_mut() {
+        for segment in section.entries_mut() {
+            for idx in segment.members_mut() {
+                relocate(idx);
+            }
+        }
+    }
+    if let Some(section) = module.export_section_mut() {
+        for entry in section.entries_mut() {
+            if let Internal::Function(idx) = entry.internal_mut() {
+                relocate(idx);
+            }
+        }
+    }
+    if let Some(mut idx) = module.start_section() {
+        relocate(&mut idx);
+        module.set_start_section(idx);
+    }
+
+    Ok(imported)
+}
+
+/// Appends the export `ENTRY_WRAPPER` invoking `entry`, see the module documentation. The
+/// float types of the module must be lowered already.
+pub fn wrap_entry(mut module: Module, entry: &EntryPoint) -> Result<Module> {
+    if export_index(&module, ENTRY_WRAPPER).is_ok() {
+        return Err(anyhow!("{} is already exported", ENTRY_WRAPPER));
+    }
+
+    let ty = function_type(&module, export_index(&module, &entry.name)?)?;
+
+    let wasm_input = if ty.params().is_empty() {
+        None
+    } else {
+        Some(import_host_function(
+            &mut module,
+            "wasm_input",
+            FunctionType::new(vec![ValueType::I32], vec![ValueType::I64]),
+        )?)
+    };
+    let wasm_output = if entry.expose_result && !ty.results().is_empty() {
+        Some(import_host_function(
+            &mut module,
+            "wasm_output",
+            FunctionType::new(vec![ValueType::I64], vec![]),
+        )?)
+    } else {
+        None
+    };
+
+    // Imports may have shifted the index of the entry.
+    let entry_idx = export_index(&module, &entry.name)?;
+
+    let mut code = vec![];
+    for param in ty.params() {
+        code.push(Instruction::I32Const(1));
+        code.push(Instruction::Call(wasm_input.unwrap()));
+        if *param == ValueType::I32 {
+            code.push(Instruction::I32WrapI64);
+        }
+    }
+    code.push(Instruction::Call(entry_idx));
+
+    let mut locals = vec![];
+    if let Some(wasm_output) = wasm_output {
+        for (local, result) in ty.results().iter().enumerate().rev() {
+            code.push(Instruction::SetLocal(local as u32));
+            locals.push(Local::new(1, *result));
+        }
+        locals.reverse();
+
+        for (local, result) in ty.results().iter().enumerate() {
+            code.push(Instruction::GetLocal(local as u32));
+            if *result == ValueType::I32 {
+                code.push(Instruction::I64ExtendUI32);
+            }
+            code.push(Instruction::Call(wasm_output));
+        }
+
+        // The wrapper returns the results of the entry as well.
+        for local in 0..ty.results().len() {
+            code.push(Instruction::GetLocal(local as u32));
+        }
+    }
+    code.push(Instruction::End);
+
+    let type_ref = type_ref_of(
+        &mut module,
+        FunctionType::new(vec![], ty.results().to_vec()),
+    );
+    let wrapper_idx = module.functions_space() as u32;
+
+    module
+        .function_section_mut()
+        .ok_or_else(|| anyhow!("module without function section"))?
+        .entries_mut()
+        .push(Func::new(type_ref));
+    module
+        .code_section_mut()
+        .ok_or_else(|| anyhow!("module without code section"))?
+        .bodies_mut()
+        .push(FuncBody::new(locals, Instructions::new(code)));
+    module
+        .export_section_mut()
+        .unwrap()
+        .entries_mut()
+        .push(ExportEntry::new(
+            ENTRY_WRAPPER.to_owned(),
+            Internal::Function(wrapper_idx),
+        ));
+
+    Ok(module)
+}
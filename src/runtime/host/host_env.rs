@@ -8,7 +8,9 @@ use wasmi::ModuleImportResolver;
 use wasmi::RuntimeArgs;
 use wasmi::RuntimeValue;
 use wasmi::Trap;
+use wasmi::TrapKind;
 
+use crate::runtime::host::HostCallError;
 use crate::runtime::host::HostFunctionExecutionEnv;
 
 use super::external_circuit_plugin::ExternalCircuitEnv;
@@ -147,7 +149,12 @@ impl Externals for HostEnv {
                 let mut ctx = (*ctx).borrow_mut();
                 let ctx = ctx.as_mut();
 
-                Ok((function.execution_env.cb)(ctx, args))
+                let ret = (function.execution_env.cb)(ctx, args);
+
+                match ctx.take_error() {
+                    Some(err) => Err(Trap::new(TrapKind::Host(Box::new(HostCallError(err))))),
+                    None => Ok(ret),
+                }
             }
             None => unreachable!(),
         }
@@ -3,7 +3,9 @@ use downcast_rs::Downcast;
 use specs::external_host_call_table::ExternalHostCallSignature;
 use specs::host_function::HostFunctionDesc;
 use std::cell::RefCell;
+use std::fmt;
 use std::rc::Rc;
+use wasmi::HostError;
 use wasmi::RuntimeArgs;
 use wasmi::RuntimeValue;
 use wasmi::Signature;
@@ -48,9 +50,27 @@ impl MatchForeignOpSignature for ExternalHostCallSignature {
 /// }
 /// impl ForeignContext for Context {}
 /// ```
-pub trait ForeignContext: Downcast {}
+pub trait ForeignContext: Downcast {
+    /// Takes the error of an invalid call of the guest, e.g. with an argument out of the
+    /// domain of the function, which traps the execution instead of panicking the host.
+    fn take_error(&mut self) -> Option<String> {
+        None
+    }
+}
 impl_downcast!(ForeignContext);
 
+/// The trap of an invalid call of a host function.
+#[derive(Debug)]
+pub struct HostCallError(pub String);
+
+impl fmt::Display for HostCallError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid host call: {}", self.0)
+    }
+}
+
+impl HostError for HostCallError {}
+
 pub struct ForeignPlugin {
     ctx: Rc<RefCell<Box<dyn ForeignContext>>>,
 }
@@ -1,24 +1,39 @@
 use std::cell::RefCell;
 use std::rc::Rc;
 
+use specs::etable::EventTable;
 use specs::etable::EventTableEntry;
 use specs::external_host_call_table::ExternalHostCallSignature;
+use specs::jtable::JumpTable;
+use specs::jtable::StaticFrameEntry;
 use specs::mtable::AccessType;
 use specs::mtable::LocationType;
 use specs::mtable::MemoryTableEntry;
 use specs::mtable::VarType;
+use specs::step::bulk_memory_chunk;
+use specs::step::bulk_memory_copy_read_address;
 use specs::step::StepInfo;
+use specs::step::TrapCode;
+use specs::types::ValueType;
 use specs::CompilationTable;
 use specs::Tables;
 
+use self::budget::ExecutionBudget;
 use self::wasmi_interpreter::WasmiRuntime;
 
+pub mod budget;
+pub mod entry;
 pub mod host;
+pub mod segment;
+pub mod soft_float;
 pub mod wasmi_interpreter;
 
 pub struct CompiledImage<I, T> {
     pub entry: String,
     pub tables: CompilationTable,
+    /// The execution fails with `CapacityExceeded` once traced beyond the budget, it is
+    /// aborted at the first host call beyond it.
+    pub budget: ExecutionBudget,
     pub instance: I,
     pub tracer: Rc<RefCell<T>>,
 }
@@ -29,6 +44,8 @@ pub struct ExecutionResult<R> {
     pub result: Option<R>,
     pub public_inputs_and_outputs: Vec<u64>,
     pub outputs: Vec<u64>,
+    /// Set when the execution trapped, the trap code is also the last public output.
+    pub trap: Option<TrapCode>,
 }
 
 // TODO: use feature
@@ -43,193 +60,69 @@ pub fn memory_event_of_step(event: &EventTableEntry, emid: &mut u32) -> Vec<Memo
             drop,
             keep,
             keep_values,
+            keep_index,
             ..
-        } => {
-            assert_eq!(keep.len(), keep_values.len());
-            assert!(keep.len() <= 1);
-
-            let mut sp = sp_before_execution + 1;
-            let mut ops = vec![];
-
-            {
-                for i in 0..keep.len() {
-                    ops.push(MemoryTableEntry {
-                        eid,
-                        emid: *emid,
-                        offset: sp,
-                        ltype: LocationType::Stack,
-                        atype: AccessType::Read,
-                        vtype: keep[i].into(),
-                        is_mutable: true,
-                        value: keep_values[i],
-                    });
-
-                    sp = sp + 1;
-                    *emid = (*emid).checked_add(1).unwrap();
-                }
-            }
-
-            sp += drop;
-            sp -= 1;
-
-            {
-                for i in 0..keep.len() {
-                    ops.push(MemoryTableEntry {
-                        eid,
-                        emid: *emid,
-                        offset: sp,
-                        ltype: LocationType::Stack,
-                        atype: AccessType::Write,
-                        vtype: keep[i].into(),
-                        is_mutable: true,
-                        value: keep_values[i],
-                    });
-
-                    sp = sp - 1;
-                    *emid = (*emid).checked_add(1).unwrap();
-                }
-            }
-
-            ops
         }
+        | StepInfo::Return {
+            drop,
+            keep,
+            keep_values,
+            keep_index,
+            ..
+        } => mem_op_of_keep_value(
+            sp_before_execution,
+            eid,
+            emid,
+            *drop,
+            keep,
+            keep_values,
+            *keep_index,
+        ),
         StepInfo::BrIfEqz {
             condition,
             drop,
             keep,
             keep_values,
+            keep_index,
             ..
-        } => {
-            assert_eq!(keep.len(), keep_values.len());
-            assert!(keep.len() <= 1);
-
-            let mut sp = sp_before_execution + 1;
-
-            let mut ops = vec![MemoryTableEntry {
-                eid,
-                emid: *emid,
-                offset: sp,
-                ltype: LocationType::Stack,
-                atype: AccessType::Read,
-                vtype: VarType::I32,
-                is_mutable: true,
-                value: *condition as u32 as u64,
-            }];
-
-            sp = sp + 1;
-            *emid = (*emid).checked_add(1).unwrap();
-
-            if *condition != 0 {
-                return ops;
-            }
-
-            {
-                for i in 0..keep.len() {
-                    ops.push(MemoryTableEntry {
-                        eid,
-                        emid: *emid,
-                        offset: sp,
-                        ltype: LocationType::Stack,
-                        atype: AccessType::Read,
-                        vtype: keep[i].into(),
-                        is_mutable: true,
-                        value: keep_values[i],
-                    });
-
-                    sp = sp + 1;
-                    *emid = (*emid).checked_add(1).unwrap();
-                }
-            }
-
-            sp += drop;
-            sp -= 1;
-
-            {
-                for i in 0..keep.len() {
-                    ops.push(MemoryTableEntry {
-                        eid,
-                        emid: *emid,
-                        offset: sp,
-                        ltype: LocationType::Stack,
-                        atype: AccessType::Write,
-                        vtype: keep[i].into(),
-                        is_mutable: true,
-                        value: keep_values[i],
-                    });
-
-                    sp = sp - 1;
-                    *emid = (*emid).checked_add(1).unwrap();
-                }
-            }
-
-            ops
         }
-        StepInfo::BrIfNez {
+        | StepInfo::BrIfNez {
             condition,
             drop,
             keep,
             keep_values,
+            keep_index,
             ..
         } => {
-            assert_eq!(keep.len(), keep_values.len());
-            assert!(keep.len() <= 1);
-
-            let mut sp = sp_before_execution + 1;
-
-            let mut ops = vec![MemoryTableEntry {
-                eid,
-                emid: *emid,
-                offset: sp,
-                ltype: LocationType::Stack,
-                atype: AccessType::Read,
-                vtype: VarType::I32,
-                is_mutable: true,
-                value: *condition as u32 as u64,
-            }];
-
-            sp = sp + 1;
-            *emid = (*emid).checked_add(1).unwrap();
-
-            if *condition == 0 {
-                return ops;
-            }
-
-            {
-                for i in 0..keep.len() {
-                    ops.push(MemoryTableEntry {
-                        eid,
-                        emid: *emid,
-                        offset: sp,
-                        ltype: LocationType::Stack,
-                        atype: AccessType::Read,
-                        vtype: keep[i].into(),
-                        is_mutable: true,
-                        value: keep_values[i],
-                    });
-
-                    sp = sp + 1;
-                    *emid = (*emid).checked_add(1).unwrap();
-                }
-            }
-
-            sp += drop;
-            sp -= 1;
+            let is_taken = match &event.step_info {
+                StepInfo::BrIfEqz { .. } => *condition == 0,
+                _ => *condition != 0,
+            };
 
-            {
-                for i in 0..keep.len() {
-                    ops.push(MemoryTableEntry {
-                        eid,
-                        emid: *emid,
-                        offset: sp,
-                        ltype: LocationType::Stack,
-                        atype: AccessType::Write,
-                        vtype: keep[i].into(),
-                        is_mutable: true,
-                        value: keep_values[i],
-                    });
+            let mut ops = if *keep_index == 0 {
+                mem_op_from_stack_only_step(
+                    sp_before_execution,
+                    eid,
+                    emid,
+                    VarType::I32,
+                    VarType::I32,
+                    &[*condition as u32 as u64],
+                    &[],
+                )
+            } else {
+                vec![]
+            };
 
-                    sp = sp - 1;
-                    *emid = (*emid).checked_add(1).unwrap();
-                }
+            if is_taken {
+                ops.append(&mut mem_op_of_keep_value(
+                    sp_before_execution + 1,
+                    eid,
+                    emid,
+                    *drop,
+                    keep,
+                    keep_values,
+                    *keep_index,
+                ));
             }
 
             ops
@@ -239,119 +132,32 @@ pub fn memory_event_of_step(event: &EventTableEntry, emid: &mut u32) -> Vec<Memo
             drop,
             keep,
             keep_values,
+            keep_index,
             ..
         } => {
-            assert_eq!(keep.len(), keep_values.len());
-            assert!(keep.len() <= 1);
-
-            let mut sp = sp_before_execution + 1;
+            let mut ops = if *keep_index == 0 {
+                mem_op_from_stack_only_step(
+                    sp_before_execution,
+                    eid,
+                    emid,
+                    VarType::I32,
+                    VarType::I32,
+                    &[*index as u32 as u64],
+                    &[],
+                )
+            } else {
+                vec![]
+            };
 
-            let mut ops = vec![MemoryTableEntry {
+            ops.append(&mut mem_op_of_keep_value(
+                sp_before_execution + 1,
                 eid,
-                emid: *emid,
-                offset: sp,
-                ltype: LocationType::Stack,
-                atype: AccessType::Read,
-                vtype: VarType::I32,
-                is_mutable: true,
-                value: *index as u32 as u64,
-            }];
-
-            sp = sp + 1;
-            *emid = (*emid).checked_add(1).unwrap();
-
-            {
-                for i in 0..keep.len() {
-                    ops.push(MemoryTableEntry {
-                        eid,
-                        emid: *emid,
-                        offset: sp,
-                        ltype: LocationType::Stack,
-                        atype: AccessType::Read,
-                        vtype: keep[i].into(),
-                        is_mutable: true,
-                        value: keep_values[i],
-                    });
-
-                    sp = sp + 1;
-                    *emid = (*emid).checked_add(1).unwrap();
-                }
-            }
-
-            sp += drop;
-            sp -= 1;
-
-            {
-                for i in 0..keep.len() {
-                    ops.push(MemoryTableEntry {
-                        eid,
-                        emid: *emid,
-                        offset: sp,
-                        ltype: LocationType::Stack,
-                        atype: AccessType::Write,
-                        vtype: keep[i].into(),
-                        is_mutable: true,
-                        value: keep_values[i],
-                    });
-
-                    sp = sp - 1;
-                    *emid = (*emid).checked_add(1).unwrap();
-                }
-            }
-
-            ops
-        }
-        StepInfo::Return {
-            drop,
-            keep,
-            drop_values,
-            keep_values,
-        } => {
-            assert_eq!(*drop as usize, drop_values.len());
-            assert_eq!(keep.len(), keep_values.len());
-            assert!(keep.len() <= 1);
-
-            let mut sp = sp_before_execution + 1;
-            let mut ops = vec![];
-
-            {
-                for i in 0..keep.len() {
-                    ops.push(MemoryTableEntry {
-                        eid,
-                        emid: *emid,
-                        offset: sp,
-                        ltype: LocationType::Stack,
-                        atype: AccessType::Read,
-                        vtype: keep[i].into(),
-                        is_mutable: true,
-                        value: keep_values[i],
-                    });
-
-                    sp = sp + 1;
-                    *emid = (*emid).checked_add(1).unwrap();
-                }
-            }
-
-            sp += drop;
-            sp -= 1;
-
-            {
-                for i in 0..keep.len() {
-                    ops.push(MemoryTableEntry {
-                        eid,
-                        emid: *emid,
-                        offset: sp,
-                        ltype: LocationType::Stack,
-                        atype: AccessType::Write,
-                        vtype: keep[i].into(),
-                        is_mutable: true,
-                        value: keep_values[i],
-                    });
-
-                    sp = sp - 1;
-                    *emid = (*emid).checked_add(1).unwrap();
-                }
-            }
+                emid,
+                *drop,
+                keep,
+                keep_values,
+                *keep_index,
+            ));
 
             ops
         }
@@ -439,7 +245,7 @@ pub fn memory_event_of_step(event: &EventTableEntry, emid: &mut u32) -> Vec<Memo
         }
         StepInfo::CallHost {
             args,
-            ret_val,
+            ret_vals,
             signature,
             ..
         } => {
@@ -463,18 +269,21 @@ pub fn memory_event_of_step(event: &EventTableEntry, emid: &mut u32) -> Vec<Memo
 
             sp = sp + args.len() as u32;
 
-            if let Some(ty) = signature.return_type {
+            assert_eq!(signature.return_types.len(), ret_vals.len());
+
+            for (ty, val) in signature.return_types.iter().zip(ret_vals.iter()) {
                 mops.push(MemoryTableEntry {
                     eid,
                     emid: *emid,
                     offset: sp,
                     ltype: LocationType::Stack,
                     atype: AccessType::Write,
-                    vtype: ty.into(),
+                    vtype: (*ty).into(),
                     is_mutable: true,
-                    value: ret_val.unwrap(),
+                    value: *val,
                 });
 
+                sp = sp - 1;
                 *emid = (*emid).checked_add(1).unwrap();
             }
 
@@ -875,6 +684,93 @@ pub fn memory_event_of_step(event: &EventTableEntry, emid: &mut u32) -> Vec<Memo
             &[*grow_size as u32 as u64],
             &[*result as u32 as u64],
         ),
+        StepInfo::MemoryFill {
+            dst,
+            value,
+            size,
+            is_first,
+            pre_block_value,
+            updated_block_value,
+        } => {
+            let mut ops = if *is_first {
+                mem_op_from_stack_only_step(
+                    sp_before_execution,
+                    eid,
+                    emid,
+                    VarType::I32,
+                    VarType::I32,
+                    &[*size as u64, *value as u64, *dst as u64],
+                    &[],
+                )
+            } else {
+                vec![]
+            };
+
+            if *size > 0 {
+                let (chunk_dst, _, _) = bulk_memory_chunk(*dst, *dst, *size, *is_first, false);
+
+                ops.append(&mut mem_op_of_heap_block_update(
+                    eid,
+                    emid,
+                    chunk_dst / 8,
+                    *pre_block_value,
+                    *updated_block_value,
+                ));
+            }
+
+            ops
+        }
+        StepInfo::MemoryCopy {
+            dst,
+            src,
+            size,
+            is_first,
+            is_backward,
+            src_block_value,
+            pre_block_value,
+            updated_block_value,
+        } => {
+            let mut ops = if *is_first {
+                mem_op_from_stack_only_step(
+                    sp_before_execution,
+                    eid,
+                    emid,
+                    VarType::I32,
+                    VarType::I32,
+                    &[*size as u64, *src as u64, *dst as u64],
+                    &[],
+                )
+            } else {
+                vec![]
+            };
+
+            if *size > 0 {
+                let (chunk_dst, chunk_src, len) =
+                    bulk_memory_chunk(*dst, *src, *size, *is_first, *is_backward);
+
+                ops.push(MemoryTableEntry {
+                    eid,
+                    emid: *emid,
+                    offset: bulk_memory_copy_read_address(chunk_src, len, *is_backward) / 8,
+                    ltype: LocationType::Heap,
+                    atype: AccessType::Read,
+                    vtype: VarType::I64,
+                    is_mutable: true,
+                    value: *src_block_value,
+                });
+                *emid = (*emid).checked_add(1).unwrap();
+
+                ops.append(&mut mem_op_of_heap_block_update(
+                    eid,
+                    emid,
+                    chunk_dst / 8,
+                    *pre_block_value,
+                    *updated_block_value,
+                ));
+            }
+
+            ops
+        }
 
         StepInfo::I32Const { value } => mem_op_from_stack_only_step(
             sp_before_execution,
@@ -999,7 +895,227 @@ pub fn memory_event_of_step(event: &EventTableEntry, emid: &mut u32) -> Vec<Memo
             &[*value as u32 as u64],
             &[*result as u64],
         ),
+        StepInfo::I32SignExtendI8 { value, result }
+        | StepInfo::I32SignExtendI16 { value, result } => mem_op_from_stack_only_step(
+            sp_before_execution,
+            eid,
+            emid,
+            VarType::I32,
+            VarType::I32,
+            &[*value as u32 as u64],
+            &[*result as u32 as u64],
+        ),
+        StepInfo::I64SignExtendI8 { value, result }
+        | StepInfo::I64SignExtendI16 { value, result }
+        | StepInfo::I64SignExtendI32 { value, result } => mem_op_from_stack_only_step(
+            sp_before_execution,
+            eid,
+            emid,
+            VarType::I64,
+            VarType::I64,
+            &[*value as u64],
+            &[*result as u64],
+        ),
+
+        StepInfo::Trap {
+            code,
+            address,
+            size,
+            is_source,
+            frame_index,
+            ..
+        } => {
+            // Only the trapping frame reads the operands witnessing the trap.
+            if *frame_index != 0 {
+                return vec![];
+            }
+
+            code.witness(&event.inst.opcode, *address, *size, *is_source)
+                .into_iter()
+                .map(|(depth, vtype, value)| {
+                    let read = MemoryTableEntry {
+                        eid,
+                        emid: *emid,
+                        offset: sp_before_execution + depth,
+                        ltype: LocationType::Stack,
+                        atype: AccessType::Read,
+                        vtype,
+                        is_mutable: true,
+                        value,
+                    };
+                    *emid = (*emid).checked_add(1).unwrap();
+
+                    read
+                })
+                .collect()
+        }
+    }
+}
+
+/// Expands the trap step recorded by the tracer into one step per open frame, innermost
+/// first. Each step leaves its frame like a return does, so that every call in the jump
+/// table is still closed by exactly one lookup, and the frame register follows the frames
+/// being left.
+pub fn unwind_trap(
+    etable: &mut EventTable,
+    jtable: &JumpTable,
+    static_jtable: &[StaticFrameEntry],
+) {
+    let trap = etable.entries_mut().pop().unwrap();
+
+    let (code, address, size, is_source) = match trap.step_info {
+        StepInfo::Trap {
+            code,
+            address,
+            size,
+            is_source,
+            ..
+        } => (code, address, size, is_source),
+        _ => unreachable!(),
+    };
+
+    // (frame_id, callee_fid, caller_frame_id, caller_fid, caller_iid)
+    let mut frames = vec![];
+    let mut frame_id = trap.last_jump_eid;
+    let mut fid = trap.inst.fid;
+
+    while frame_id != 0 {
+        let entry = jtable
+            .entries()
+            .iter()
+            .find(|entry| entry.eid == frame_id)
+            .unwrap();
+
+        frames.push((
+            frame_id,
+            fid,
+            entry.last_jump_eid,
+            entry.inst.fid,
+            entry.inst.iid,
+        ));
+
+        frame_id = entry.last_jump_eid;
+        fid = entry.inst.fid;
+    }
+
+    let position = static_jtable
+        .iter()
+        .position(|entry| entry.callee_fid == fid)
+        .unwrap();
+    for entry in &static_jtable[position..] {
+        frames.push((
+            entry.frame_id,
+            entry.callee_fid,
+            entry.next_frame_id,
+            entry.fid,
+            entry.iid,
+        ));
     }
+
+    let frame_count = frames.len() as u32;
+    for (frame_index, (frame_id, callee_fid, caller_frame_id, caller_fid, caller_iid)) in
+        frames.into_iter().enumerate()
+    {
+        etable.entries_mut().push(EventTableEntry {
+            eid: trap.eid + frame_index as u32,
+            last_jump_eid: frame_id,
+            step_info: StepInfo::Trap {
+                code,
+                address,
+                size,
+                is_source,
+                frame_index: frame_index as u32,
+                frame_count,
+                frame_id,
+                callee_fid,
+                caller_frame_id,
+                caller_fid,
+                caller_iid,
+            },
+            ..trap.clone()
+        });
+    }
+}
+
+/// Moves the kept value `keep[keep_index]` over the dropped values, the values are counted
+/// from `sp + 1`, the deepest one is at `sp + keep.len()`.
+fn mem_op_of_keep_value(
+    sp: u32,
+    eid: u32,
+    emid: &mut u32,
+    drop: u32,
+    keep: &[ValueType],
+    keep_values: &[u64],
+    keep_index: u32,
+) -> Vec<MemoryTableEntry> {
+    assert_eq!(keep.len(), keep_values.len());
+
+    if keep.is_empty() {
+        return vec![];
+    }
+
+    let index = keep_index as usize;
+    let offset = (keep.len() - index) as u32;
+
+    let read = MemoryTableEntry {
+        eid,
+        emid: *emid,
+        offset: sp + offset,
+        ltype: LocationType::Stack,
+        atype: AccessType::Read,
+        vtype: keep[index].into(),
+        is_mutable: true,
+        value: keep_values[index],
+    };
+    *emid = (*emid).checked_add(1).unwrap();
+
+    let write = MemoryTableEntry {
+        eid,
+        emid: *emid,
+        offset: sp + drop + offset,
+        ltype: LocationType::Stack,
+        atype: AccessType::Write,
+        vtype: keep[index].into(),
+        is_mutable: true,
+        value: keep_values[index],
+    };
+    *emid = (*emid).checked_add(1).unwrap();
+
+    vec![read, write]
+}
+
+fn mem_op_of_heap_block_update(
+    eid: u32,
+    emid: &mut u32,
+    block_offset: u32,
+    pre_block_value: u64,
+    updated_block_value: u64,
+) -> Vec<MemoryTableEntry> {
+    let read = MemoryTableEntry {
+        eid,
+        emid: *emid,
+        offset: block_offset,
+        ltype: LocationType::Heap,
+        atype: AccessType::Read,
+        vtype: VarType::I64,
+        is_mutable: true,
+        value: pre_block_value,
+    };
+    *emid = (*emid).checked_add(1).unwrap();
+
+    let write = MemoryTableEntry {
+        eid,
+        emid: *emid,
+        offset: block_offset,
+        ltype: LocationType::Heap,
+        atype: AccessType::Write,
+        vtype: VarType::I64,
+        is_mutable: true,
+        value: updated_block_value,
+    };
+    *emid = (*emid).checked_add(1).unwrap();
+
+    vec![read, write]
 }
 
 pub(crate) fn mem_op_from_stack_only_step(
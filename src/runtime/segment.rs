@@ -0,0 +1,393 @@
+//! Continuations: a long execution is proved as a sequence of segments.
+//!
+//! A segment starts from the image its previous segment leaves behind: the memory, the
+//! registers and the frames still open. The frames inherited from the previous segment
+//! are relabeled to `1..=depth`, the steps of the segment follow them, so the eids of a
+//! segment stay small whatever the length of the execution is.
+//!
+//! With the `checksum` feature, the proof of a segment publishes the hashes of the image
+//! it starts from and of the image it leaves behind. Adjacent segments are linked when
+//! the latter equals the former of the next segment.
+
+use std::collections::HashMap;
+
+use anyhow::anyhow;
+use anyhow::Result;
+use specs::etable::EventTable;
+use specs::etable::EventTableEntry;
+use specs::itable::Opcode;
+use specs::jtable::JumpTable;
+use specs::jtable::JumpTableEntry;
+use specs::jtable::StaticFrameEntry;
+use specs::jtable::INHERITED_FRAME_TABLE_ENTRIES;
+use specs::mtable::MTable;
+use specs::state::InitializationState;
+use specs::step::StepInfo;
+use specs::CompilationTable;
+use specs::ExecutionTable;
+use specs::Tables;
+
+use super::memory_event_of_step;
+use crate::foreign::babyjubjub_helper::sum_in_progress as babyjubjub_in_progress;
+use crate::foreign::keccak_helper::digest_in_progress as keccak_in_progress;
+use crate::foreign::kv_helper::is_kv_step;
+use crate::foreign::poseidon_helper::digest_in_progress as poseidon_in_progress;
+use crate::foreign::sha256_helper::digest_in_progress as sha256_in_progress;
+use crate::foreign::wasm_input_helper::is_public_input_or_output;
+
+pub struct Segment {
+    pub tables: Tables,
+    pub public_inputs_and_outputs: Vec<u64>,
+}
+
+/// The registers once the last step of a whole execution is executed. A trapped execution
+/// stops at the trapping instruction, otherwise the entry function has returned.
+pub fn terminate_state(etable: &EventTable) -> InitializationState<u32> {
+    let last_entry = etable.entries().last().unwrap();
+
+    if let StepInfo::Trap { .. } = last_entry.step_info {
+        InitializationState {
+            eid: last_entry.eid + 1,
+            fid: last_entry.inst.fid,
+            iid: last_entry.inst.iid,
+            frame_id: 0,
+            sp: last_entry.sp,
+
+            initial_memory_pages: last_entry.allocated_memory_pages,
+        }
+    } else {
+        InitializationState {
+            eid: last_entry.eid + 1,
+            fid: 0,
+            iid: 0,
+            frame_id: 0,
+            sp: last_entry.sp
+                + if let Opcode::Return { drop, .. } = &last_entry.inst.opcode {
+                    *drop
+                } else {
+                    unreachable!()
+                },
+
+            initial_memory_pages: last_entry.allocated_memory_pages,
+        }
+    }
+}
+
+/// The image left behind by `execution_tables` started from `compilation_tables`. It is
+/// the compilation table of the next segment, the frames left open are relabeled to
+/// `1..=depth` as its inherited frames.
+pub fn post_image_table(
+    compilation_tables: &CompilationTable,
+    execution_tables: &ExecutionTable,
+    terminate_state: &InitializationState<u32>,
+) -> CompilationTable {
+    let live_frames = execution_tables.live_frames(&compilation_tables.static_jtable);
+    let depth = live_frames.len() as u32;
+
+    let static_jtable = compilation_tables
+        .static_jtable
+        .iter()
+        .filter(|entry| !entry.is_inherited())
+        .cloned()
+        .chain(
+            live_frames
+                .into_iter()
+                .enumerate()
+                .map(|(index, frame)| StaticFrameEntry {
+                    frame_id: index as u32 + 1,
+                    next_frame_id: index as u32,
+                    ..frame
+                }),
+        )
+        .collect();
+
+    CompilationTable {
+        itable: compilation_tables.itable.clone(),
+        imtable: compilation_tables.imtable.update(&execution_tables.mtable),
+        elem_table: compilation_tables.elem_table.clone(),
+        configure_table: compilation_tables.configure_table.clone(),
+        static_jtable,
+        initialization_state: InitializationState {
+            eid: depth + 1,
+            frame_id: depth,
+            ..terminate_state.clone()
+        },
+    }
+}
+
+/// A segment can only start at the first step of an instruction, the steps of an
+/// instruction are constrained together.
+fn is_first_step(entry: &EventTableEntry) -> bool {
+    match &entry.step_info {
+        StepInfo::Br { keep_index, .. }
+        | StepInfo::BrIfEqz { keep_index, .. }
+        | StepInfo::BrIfNez { keep_index, .. }
+        | StepInfo::BrTable { keep_index, .. }
+        | StepInfo::Return { keep_index, .. } => *keep_index == 0,
+        StepInfo::MemoryFill { is_first, .. } | StepInfo::MemoryCopy { is_first, .. } => *is_first,
+        StepInfo::Trap { frame_index, .. } => *frame_index == 0,
+        _ => true,
+    }
+}
+
+fn is_public_step(entry: &EventTableEntry) -> bool {
+    match &entry.step_info {
+        // The trap code is the last output of a trapped execution.
+        StepInfo::Trap { frame_index, .. } => *frame_index == 0,
+        step_info => is_public_input_or_output(step_info),
+    }
+}
+
+/// Relabels the frames and steps of the segment starting at `start_eid`, `inherited_frames`
+/// are the original ids of the frames labeled `1..=depth`.
+struct Relabel<'a> {
+    start_eid: u32,
+    inherited_frames: &'a Vec<u32>,
+}
+
+impl<'a> Relabel<'a> {
+    fn depth(&self) -> u32 {
+        self.inherited_frames.len() as u32
+    }
+
+    fn label(&self, id: u32) -> u32 {
+        if id == 0 {
+            0
+        } else if id >= self.start_eid {
+            id - self.start_eid + self.depth() + 1
+        } else {
+            self.inherited_frames
+                .iter()
+                .position(|frame_id| *frame_id == id)
+                .unwrap() as u32
+                + 1
+        }
+    }
+
+    fn unlabel(&self, label: u32) -> u32 {
+        if label <= self.depth() {
+            self.inherited_frames[label as usize - 1]
+        } else {
+            label + self.start_eid - self.depth() - 1
+        }
+    }
+
+    fn event_table_entry(&self, entry: &EventTableEntry) -> EventTableEntry {
+        let step_info = match &entry.step_info {
+            StepInfo::Trap {
+                code,
+                address,
+                size,
+                is_source,
+                frame_index,
+                frame_count,
+                frame_id,
+                callee_fid,
+                caller_frame_id,
+                caller_fid,
+                caller_iid,
+            } => StepInfo::Trap {
+                code: *code,
+                address: *address,
+                size: *size,
+                is_source: *is_source,
+                frame_index: *frame_index,
+                frame_count: *frame_count,
+                frame_id: self.label(*frame_id),
+                callee_fid: *callee_fid,
+                caller_frame_id: self.label(*caller_frame_id),
+                caller_fid: *caller_fid,
+                caller_iid: *caller_iid,
+            },
+            step_info => step_info.clone(),
+        };
+
+        EventTableEntry {
+            eid: self.label(entry.eid),
+            last_jump_eid: self.label(entry.last_jump_eid),
+            step_info,
+            ..entry.clone()
+        }
+    }
+
+    fn jump_table_entry(&self, entry: &JumpTableEntry) -> JumpTableEntry {
+        JumpTableEntry {
+            eid: self.label(entry.eid),
+            last_jump_eid: self.label(entry.last_jump_eid),
+            ..entry.clone()
+        }
+    }
+}
+
+/// Splits a whole execution into segments of at most `segment_size` steps.
+///
+/// A segment ends before the first step of an instruction, with at most
+/// `INHERITED_FRAME_TABLE_ENTRIES` frames open, no SHA-256, Keccak-256 or Poseidon digest
+/// and no Baby Jubjub sum in progress.
+pub fn split_execution(
+    tables: &Tables,
+    public_inputs_and_outputs: &Vec<u64>,
+    segment_size: usize,
+) -> Result<Vec<Segment>> {
+    let entries = tables.execution_tables.etable.entries();
+    let jtable = tables
+        .execution_tables
+        .jtable
+        .entries()
+        .iter()
+        .map(|entry| (entry.eid, entry))
+        .collect::<HashMap<_, _>>();
+
+    let depth_of = |mut frame_id: u32| {
+        let mut depth = 0;
+        while frame_id != 0 {
+            frame_id = jtable.get(&frame_id).unwrap().last_jump_eid;
+            depth += 1;
+        }
+        depth
+    };
+    let sha256_in_progress = sha256_in_progress(entries);
+    let keccak_in_progress = keccak_in_progress(entries);
+    let poseidon_in_progress = poseidon_in_progress(entries);
+    let babyjubjub_in_progress = babyjubjub_in_progress(entries);
+
+    let mut bounds = vec![0];
+    while *bounds.last().unwrap() < entries.len() {
+        let start = *bounds.last().unwrap();
+        let mut end = (start + segment_size).min(entries.len());
+
+        while end < entries.len()
+            && !(is_first_step(&entries[end])
+                && !sha256_in_progress[end]
+                && !keccak_in_progress[end]
+                && !poseidon_in_progress[end]
+                && !babyjubjub_in_progress[end]
+                && depth_of(entries[end].last_jump_eid) <= INHERITED_FRAME_TABLE_ENTRIES)
+        {
+            end -= 1;
+        }
+
+        if end == start {
+            return Err(anyhow!(
+                "failed to split the execution at step {} into segments of {} steps",
+                entries[start].eid,
+                segment_size
+            ));
+        }
+
+        bounds.push(end);
+    }
+
+    let mut segments = vec![];
+    let mut compilation_tables = tables.compilation_tables.clone();
+    let mut inherited_frames = vec![];
+    let mut io_offset = 0;
+    let mut kv_offset = 0;
+
+    for (index, range) in bounds.windows(2).enumerate() {
+        let (start, end) = (range[0], range[1]);
+        let is_last = index == bounds.len() - 2;
+
+        let relabel = Relabel {
+            start_eid: entries[start].eid,
+            inherited_frames: &inherited_frames,
+        };
+
+        let etable = EventTable::new(
+            entries[start..end]
+                .iter()
+                .map(|entry| relabel.event_table_entry(entry))
+                .collect(),
+        );
+
+        let mut jtable = JumpTable::default();
+        for entry in tables.execution_tables.jtable.entries() {
+            if entry.eid >= entries[start].eid && (is_last || entry.eid < entries[end].eid) {
+                jtable.push(relabel.jump_table_entry(entry));
+            }
+        }
+
+        let mtable = MTable::new(
+            etable
+                .entries()
+                .iter()
+                .map(|entry| memory_event_of_step(entry, &mut 1))
+                .collect::<Vec<Vec<_>>>()
+                .concat(),
+            &compilation_tables.imtable,
+        );
+
+        let kv_count = entries[start..end]
+            .iter()
+            .filter(|entry| is_kv_step(entry))
+            .count();
+        let kvtable = tables
+            .execution_tables
+            .kvtable
+            .slice(kv_offset, kv_offset + kv_count);
+
+        let execution_tables = ExecutionTable {
+            etable,
+            mtable,
+            jtable,
+            kvtable,
+        };
+
+        let terminate_state = if is_last {
+            terminate_state(&execution_tables.etable)
+        } else {
+            let next_entry = &entries[end];
+
+            InitializationState {
+                eid: relabel.label(next_entry.eid),
+                fid: next_entry.inst.fid,
+                iid: next_entry.inst.iid,
+                frame_id: relabel.label(next_entry.last_jump_eid),
+                sp: next_entry.sp,
+
+                initial_memory_pages: next_entry.allocated_memory_pages,
+            }
+        };
+
+        let post_image_table =
+            post_image_table(&compilation_tables, &execution_tables, &terminate_state);
+
+        let next_inherited_frames = execution_tables
+            .live_frames(&compilation_tables.static_jtable)
+            .iter()
+            .map(|frame| relabel.unlabel(frame.frame_id))
+            .collect::<Vec<_>>();
+
+        let io_count = if is_last {
+            public_inputs_and_outputs.len() - io_offset
+        } else {
+            entries[start..end]
+                .iter()
+                .filter(|entry| is_public_step(entry))
+                .count()
+        };
+
+        segments.push(Segment {
+            tables: Tables {
+                compilation_tables,
+                execution_tables,
+                post_image_table: post_image_table.clone(),
+            },
+            public_inputs_and_outputs: public_inputs_and_outputs[io_offset..io_offset + io_count]
+                .to_vec(),
+        });
+
+        compilation_tables = post_image_table;
+        inherited_frames = next_inherited_frames;
+        io_offset += io_count;
+        kv_offset += kv_count;
+    }
+
+    Ok(segments)
+}
+
+/// Whether each segment starts from the image its previous segment leaves behind, the
+/// instances of a segment start with the hashes of its pre and post images.
+pub fn is_linked<F: PartialEq>(instances: &[Vec<F>]) -> bool {
+    instances.windows(2).all(|pair| pair[0][1] == pair[1][0])
+}
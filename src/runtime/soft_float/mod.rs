@@ -0,0 +1,328 @@
+//! Lowers the float instructions of an image into integer code.
+//!
+//! The circuits only prove integer instructions, so every f32/f64 value is carried as its
+//! i32/i64 bit pattern and each float operation becomes a call to a routine of
+//! `soft_float.wat`. The routines used by the image are appended to its functions and are
+//! then traced and proven like the rest of the code.
+
+use std::collections::BTreeSet;
+use std::collections::HashMap;
+
+use anyhow::anyhow;
+use anyhow::Result;
+use parity_wasm::elements::BlockType;
+use parity_wasm::elements::External;
+use parity_wasm::elements::Func;
+use parity_wasm::elements::FuncBody;
+use parity_wasm::elements::GlobalType;
+use parity_wasm::elements::Instruction;
+use parity_wasm::elements::Instructions;
+use parity_wasm::elements::Internal;
+use parity_wasm::elements::Local;
+use parity_wasm::elements::Module;
+use parity_wasm::elements::Type;
+use parity_wasm::elements::ValueType;
+
+const SOFT_FLOAT_ROUTINES: &str = include_str!("soft_float.wat");
+
+enum Lowered {
+    Instruction(Instruction),
+    Routine(&'static str),
+}
+
+fn lower_value_type(value_type: ValueType) -> ValueType {
+    match value_type {
+        ValueType::F32 => ValueType::I32,
+        ValueType::F64 => ValueType::I64,
+        _ => value_type,
+    }
+}
+
+fn lower_block_type(block_type: BlockType) -> BlockType {
+    match block_type {
+        BlockType::Value(value_type) => BlockType::Value(lower_value_type(value_type)),
+        // The types of the module are lowered in place, a block of several params or results
+        // keeps the index of its type.
+        BlockType::TypeIndex(_) | BlockType::NoResult => block_type,
+    }
+}
+
+fn lower_instruction(instruction: Instruction) -> Vec<Lowered> {
+    use Lowered::Routine;
+
+    let lowered = |instructions: Vec<Instruction>| {
+        instructions
+            .into_iter()
+            .map(Lowered::Instruction)
+            .collect::<Vec<_>>()
+    };
+
+    match instruction {
+        Instruction::Block(ty) => lowered(vec![Instruction::Block(lower_block_type(ty))]),
+        Instruction::Loop(ty) => lowered(vec![Instruction::Loop(lower_block_type(ty))]),
+        Instruction::If(ty) => lowered(vec![Instruction::If(lower_block_type(ty))]),
+
+        Instruction::F32Const(v) => lowered(vec![Instruction::I32Const(v as i32)]),
+        Instruction::F64Const(v) => lowered(vec![Instruction::I64Const(v as i64)]),
+
+        Instruction::F32Load(align, offset) => lowered(vec![Instruction::I32Load(align, offset)]),
+        Instruction::F64Load(align, offset) => lowered(vec![Instruction::I64Load(align, offset)]),
+        Instruction::F32Store(align, offset) => lowered(vec![Instruction::I32Store(align, offset)]),
+        Instruction::F64Store(align, offset) => lowered(vec![Instruction::I64Store(align, offset)]),
+
+        Instruction::I32ReinterpretF32
+        | Instruction::I64ReinterpretF64
+        | Instruction::F32ReinterpretI32
+        | Instruction::F64ReinterpretI64 => vec![],
+
+        Instruction::F32Abs => lowered(vec![Instruction::I32Const(i32::MAX), Instruction::I32And]),
+        Instruction::F32Neg => lowered(vec![Instruction::I32Const(i32::MIN), Instruction::I32Xor]),
+        Instruction::F64Abs => lowered(vec![Instruction::I64Const(i64::MAX), Instruction::I64And]),
+        Instruction::F64Neg => lowered(vec![Instruction::I64Const(i64::MIN), Instruction::I64Xor]),
+
+        Instruction::F32Ne => vec![Routine("f32_eq"), Lowered::Instruction(Instruction::I32Eqz)],
+        Instruction::F64Ne => vec![Routine("f64_eq"), Lowered::Instruction(Instruction::I32Eqz)],
+
+        Instruction::F32Eq => vec![Routine("f32_eq")],
+        Instruction::F32Lt => vec![Routine("f32_lt")],
+        Instruction::F32Gt => vec![Routine("f32_gt")],
+        Instruction::F32Le => vec![Routine("f32_le")],
+        Instruction::F32Ge => vec![Routine("f32_ge")],
+        Instruction::F64Eq => vec![Routine("f64_eq")],
+        Instruction::F64Lt => vec![Routine("f64_lt")],
+        Instruction::F64Gt => vec![Routine("f64_gt")],
+        Instruction::F64Le => vec![Routine("f64_le")],
+        Instruction::F64Ge => vec![Routine("f64_ge")],
+
+        Instruction::F32Ceil => vec![Routine("f32_ceil")],
+        Instruction::F32Floor => vec![Routine("f32_floor")],
+        Instruction::F32Trunc => vec![Routine("f32_trunc")],
+        Instruction::F32Nearest => vec![Routine("f32_nearest")],
+        Instruction::F32Sqrt => vec![Routine("f32_sqrt")],
+        Instruction::F32Add => vec![Routine("f32_add")],
+        Instruction::F32Sub => vec![Routine("f32_sub")],
+        Instruction::F32Mul => vec![Routine("f32_mul")],
+        Instruction::F32Div => vec![Routine("f32_div")],
+        Instruction::F32Min => vec![Routine("f32_min")],
+        Instruction::F32Max => vec![Routine("f32_max")],
+        Instruction::F32Copysign => vec![Routine("f32_copysign")],
+        Instruction::F64Ceil => vec![Routine("f64_ceil")],
+        Instruction::F64Floor => vec![Routine("f64_floor")],
+        Instruction::F64Trunc => vec![Routine("f64_trunc")],
+        Instruction::F64Nearest => vec![Routine("f64_nearest")],
+        Instruction::F64Sqrt => vec![Routine("f64_sqrt")],
+        Instruction::F64Add => vec![Routine("f64_add")],
+        Instruction::F64Sub => vec![Routine("f64_sub")],
+        Instruction::F64Mul => vec![Routine("f64_mul")],
+        Instruction::F64Div => vec![Routine("f64_div")],
+        Instruction::F64Min => vec![Routine("f64_min")],
+        Instruction::F64Max => vec![Routine("f64_max")],
+        Instruction::F64Copysign => vec![Routine("f64_copysign")],
+
+        Instruction::I32TruncSF32 => vec![Routine("i32_trunc_f32_s")],
+        Instruction::I32TruncUF32 => vec![Routine("i32_trunc_f32_u")],
+        Instruction::I32TruncSF64 => vec![Routine("i32_trunc_f64_s")],
+        Instruction::I32TruncUF64 => vec![Routine("i32_trunc_f64_u")],
+        Instruction::I64TruncSF32 => vec![Routine("i64_trunc_f32_s")],
+        Instruction::I64TruncUF32 => vec![Routine("i64_trunc_f32_u")],
+        Instruction::I64TruncSF64 => vec![Routine("i64_trunc_f64_s")],
+        Instruction::I64TruncUF64 => vec![Routine("i64_trunc_f64_u")],
+        Instruction::F32ConvertSI32 => vec![Routine("f32_convert_i32_s")],
+        Instruction::F32ConvertUI32 => vec![Routine("f32_convert_i32_u")],
+        Instruction::F32ConvertSI64 => vec![Routine("f32_convert_i64_s")],
+        Instruction::F32ConvertUI64 => vec![Routine("f32_convert_i64_u")],
+        Instruction::F32DemoteF64 => vec![Routine("f32_demote_f64")],
+        Instruction::F64ConvertSI32 => vec![Routine("f64_convert_i32_s")],
+        Instruction::F64ConvertUI32 => vec![Routine("f64_convert_i32_u")],
+        Instruction::F64ConvertSI64 => vec![Routine("f64_convert_i64_s")],
+        Instruction::F64ConvertUI64 => vec![Routine("f64_convert_i64_u")],
+        Instruction::F64PromoteF32 => vec![Routine("f64_promote_f32")],
+
+        instruction => vec![Lowered::Instruction(instruction)],
+    }
+}
+
+fn lower_types(module: &mut Module) {
+    if let Some(section) = module.type_section_mut() {
+        for Type::Function(ty) in section.types_mut() {
+            for param in ty.params_mut() {
+                *param = lower_value_type(*param);
+            }
+            for result in ty.results_mut() {
+                *result = lower_value_type(*result);
+            }
+        }
+    }
+
+    if let Some(section) = module.import_section_mut() {
+        for entry in section.entries_mut() {
+            if let External::Global(ty) = entry.external_mut() {
+                *ty = GlobalType::new(lower_value_type(ty.content_type()), ty.is_mutable());
+            }
+        }
+    }
+
+    if let Some(section) = module.global_section_mut() {
+        for entry in section.entries_mut() {
+            let ty = entry.global_type();
+            *entry.global_type_mut() =
+                GlobalType::new(lower_value_type(ty.content_type()), ty.is_mutable());
+
+            for instruction in entry.init_expr_mut().code_mut() {
+                match instruction {
+                    Instruction::F32Const(v) => *instruction = Instruction::I32Const(*v as i32),
+                    Instruction::F64Const(v) => *instruction = Instruction::I64Const(*v as i64),
+                    _ => (),
+                }
+            }
+        }
+    }
+}
+
+/// Rewrites the float types and instructions of `module` into integer ones, appending the
+/// soft-float routines it needs. A module without float operations is returned unchanged.
+pub fn lower_float(mut module: Module) -> Result<Module> {
+    lower_types(&mut module);
+
+    let bodies = match module.code_section_mut() {
+        Some(section) => section.bodies_mut(),
+        None => return Ok(module),
+    };
+
+    let mut lowered_bodies = vec![];
+    let mut names = BTreeSet::new();
+    for body in bodies.iter_mut() {
+        for local in body.locals_mut() {
+            *local = Local::new(local.count(), lower_value_type(local.value_type()));
+        }
+
+        let code = std::mem::take(body.code_mut().elements_mut())
+            .into_iter()
+            .flat_map(lower_instruction)
+            .collect::<Vec<_>>();
+
+        for lowered in &code {
+            if let Lowered::Routine(name) = lowered {
+                names.insert(*name);
+            }
+        }
+
+        lowered_bodies.push(code);
+    }
+
+    let routine_index = if names.is_empty() {
+        HashMap::new()
+    } else {
+        inject_routines(&mut module, &names)?
+    };
+
+    for (body, code) in module
+        .code_section_mut()
+        .unwrap()
+        .bodies_mut()
+        .iter_mut()
+        .zip(lowered_bodies)
+    {
+        *body.code_mut().elements_mut() = code
+            .into_iter()
+            .map(|lowered| match lowered {
+                Lowered::Instruction(instruction) => instruction,
+                Lowered::Routine(name) => Instruction::Call(routine_index[name]),
+            })
+            .collect();
+    }
+
+    Ok(module)
+}
+
+/// Appends the routines `names` and their callees after the functions of `module`, keeping
+/// their relative order, and returns the function index of each routine in `names`.
+fn inject_routines(
+    module: &mut Module,
+    names: &BTreeSet<&'static str>,
+) -> Result<HashMap<&'static str, u32>> {
+    let routines = Module::from_bytes(wabt::wat2wasm(SOFT_FLOAT_ROUTINES)?)?;
+    let exports = routines.export_section().unwrap().entries();
+    let types = routines.type_section().unwrap().types();
+    let functions = routines.function_section().unwrap().entries();
+    let bodies = routines.code_section().unwrap().bodies();
+
+    let exported = names
+        .iter()
+        .map(|name| {
+            exports
+                .iter()
+                .find_map(|entry| match entry.internal() {
+                    Internal::Function(idx) if entry.field() == *name => Some((*name, *idx)),
+                    _ => None,
+                })
+                .ok_or_else(|| anyhow!("soft-float routine {} is missing", name))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut closure = BTreeSet::new();
+    let mut pending = exported.iter().map(|(_, idx)| *idx).collect::<Vec<_>>();
+    while let Some(idx) = pending.pop() {
+        if closure.insert(idx) {
+            for instruction in bodies[idx as usize].code().elements() {
+                if let Instruction::Call(callee) = instruction {
+                    pending.push(*callee);
+                }
+            }
+        }
+    }
+
+    let base = module.functions_space() as u32;
+    let relocated = closure
+        .iter()
+        .enumerate()
+        .map(|(offset, idx)| (*idx, base + offset as u32))
+        .collect::<HashMap<_, _>>();
+
+    for idx in closure {
+        let Type::Function(ty) = &types[functions[idx as usize].type_ref() as usize];
+
+        let module_types = module
+            .type_section_mut()
+            .ok_or_else(|| anyhow!("module calling soft-float routines has no type section"))?
+            .types_mut();
+        let type_ref = match module_types.iter().position(|Type::Function(t)| t == ty) {
+            Some(type_ref) => type_ref,
+            None => {
+                module_types.push(Type::Function(ty.clone()));
+                module_types.len() - 1
+            }
+        };
+
+        module
+            .function_section_mut()
+            .ok_or_else(|| anyhow!("module calling soft-float routines has no function section"))?
+            .entries_mut()
+            .push(Func::new(type_ref as u32));
+
+        let body = &bodies[idx as usize];
+        let code = body
+            .code()
+            .elements()
+            .iter()
+            .map(|instruction| match instruction {
+                Instruction::Call(callee) => Instruction::Call(relocated[callee]),
+                instruction => instruction.clone(),
+            })
+            .collect();
+
+        module
+            .code_section_mut()
+            .unwrap()
+            .bodies_mut()
+            .push(FuncBody::new(
+                body.locals().to_vec(),
+                Instructions::new(code),
+            ));
+    }
+
+    Ok(exported
+        .into_iter()
+        .map(|(name, idx)| (name, relocated[&idx]))
+        .collect())
+}
@@ -0,0 +1,890 @@
+;; Integer implementations of the wasm float instructions, injected into images by the
+;; float lowering pass. f32 and f64 values are carried as their i32 and i64 bit patterns.
+;;
+;; The f64 routines follow Berkeley SoftFloat 3 with round-to-nearest-even. The f32
+;; arithmetic is computed in f64 then rounded once more, which is exact since the f64
+;; significand is wider than twice the f32 one plus two bits.
+
+(module
+  ;; a >> dist, the bits shifted out are or-ed into the lowest bit; dist must be non-zero
+  (func $shift_right_jam64 (param $a i64) (param $dist i64) (result i64)
+    (if (result i64) (i64.lt_u (local.get $dist) (i64.const 63))
+      (then
+        (i64.or
+          (i64.shr_u (local.get $a) (local.get $dist))
+          (i64.extend_i32_u
+            (i64.ne
+              (i64.shl (local.get $a) (i64.sub (i64.const 64) (local.get $dist)))
+              (i64.const 0)))))
+      (else
+        (i64.extend_i32_u (i64.ne (local.get $a) (i64.const 0))))))
+
+  (func $f64_is_nan (param $a i64) (result i32)
+    (i64.gt_u
+      (i64.and (local.get $a) (i64.const 0x7fffffffffffffff))
+      (i64.const 0x7ff0000000000000)))
+
+  (func $f64_propagate_nan (param $a i64) (param $b i64) (result i64)
+    (i64.or
+      (select (local.get $a) (local.get $b) (call $f64_is_nan (local.get $a)))
+      (i64.const 0x0008000000000000)))
+
+  ;; infinity for a non-zero magnitude, the default NaN otherwise
+  (func $f64_inf_or_nan (param $sign i64) (param $mag i64) (result i64)
+    (select
+      (i64.or (i64.shl (local.get $sign) (i64.const 63)) (i64.const 0x7ff0000000000000))
+      (i64.const 0x7ff8000000000000)
+      (i64.ne (local.get $mag) (i64.const 0))))
+
+  ;; sig has its leading bit at bit 62 and 10 rounding bits, exp is the biased exponent minus one
+  (func $f64_round_pack (param $sign i64) (param $exp i64) (param $sig i64) (result i64)
+    (local $round_bits i64)
+    (local.set $round_bits (i64.and (local.get $sig) (i64.const 0x3ff)))
+    (if (i64.ge_u (local.get $exp) (i64.const 0x7fd))
+      (then
+        (if (i64.lt_s (local.get $exp) (i64.const 0))
+          (then
+            (local.set $sig
+              (call $shift_right_jam64 (local.get $sig) (i64.sub (i64.const 0) (local.get $exp))))
+            (local.set $exp (i64.const 0))
+            (local.set $round_bits (i64.and (local.get $sig) (i64.const 0x3ff))))
+          (else
+            (if (i32.or
+                  (i64.gt_s (local.get $exp) (i64.const 0x7fd))
+                  (i64.ge_u
+                    (i64.add (local.get $sig) (i64.const 0x200))
+                    (i64.const 0x8000000000000000)))
+              (then
+                (return
+                  (i64.or
+                    (i64.shl (local.get $sign) (i64.const 63))
+                    (i64.const 0x7ff0000000000000)))))))))
+    (local.set $sig (i64.shr_u (i64.add (local.get $sig) (i64.const 0x200)) (i64.const 10)))
+    (if (i64.eq (local.get $round_bits) (i64.const 0x200))
+      (then (local.set $sig (i64.and (local.get $sig) (i64.const -2)))))
+    (if (i64.eqz (local.get $sig))
+      (then (local.set $exp (i64.const 0))))
+    (i64.add
+      (i64.add
+        (i64.shl (local.get $sign) (i64.const 63))
+        (i64.shl (local.get $exp) (i64.const 52)))
+      (local.get $sig)))
+
+  (func $f64_norm_round_pack (param $sign i64) (param $exp i64) (param $sig i64) (result i64)
+    (local $shift i64)
+    (local.set $shift (i64.sub (i64.clz (local.get $sig)) (i64.const 1)))
+    (local.set $exp (i64.sub (local.get $exp) (local.get $shift)))
+    (if (result i64)
+      (i32.and
+        (i64.ge_s (local.get $shift) (i64.const 10))
+        (i64.lt_u (local.get $exp) (i64.const 0x7fd)))
+      (then
+        (i64.add
+          (i64.add
+            (i64.shl (local.get $sign) (i64.const 63))
+            (i64.shl
+              (select (local.get $exp) (i64.const 0) (i64.ne (local.get $sig) (i64.const 0)))
+              (i64.const 52)))
+          (i64.shl (local.get $sig) (i64.sub (local.get $shift) (i64.const 10)))))
+      (else
+        (call $f64_round_pack
+          (local.get $sign)
+          (local.get $exp)
+          (i64.shl (local.get $sig) (local.get $shift))))))
+
+  (func $f64_add_mags (param $a i64) (param $b i64) (param $sign i64) (result i64)
+    (local $exp_a i64) (local $sig_a i64) (local $exp_b i64) (local $sig_b i64)
+    (local $exp_diff i64) (local $exp_z i64) (local $sig_z i64)
+    (local.set $exp_a (i64.and (i64.shr_u (local.get $a) (i64.const 52)) (i64.const 0x7ff)))
+    (local.set $sig_a (i64.and (local.get $a) (i64.const 0xfffffffffffff)))
+    (local.set $exp_b (i64.and (i64.shr_u (local.get $b) (i64.const 52)) (i64.const 0x7ff)))
+    (local.set $sig_b (i64.and (local.get $b) (i64.const 0xfffffffffffff)))
+    (local.set $exp_diff (i64.sub (local.get $exp_a) (local.get $exp_b)))
+    (if (i64.eqz (local.get $exp_diff))
+      (then
+        (if (i64.eqz (local.get $exp_a))
+          (then (return (i64.add (local.get $a) (local.get $sig_b)))))
+        (if (i64.eq (local.get $exp_a) (i64.const 0x7ff))
+          (then
+            (if (i64.ne (i64.or (local.get $sig_a) (local.get $sig_b)) (i64.const 0))
+              (then (return (call $f64_propagate_nan (local.get $a) (local.get $b)))))
+            (return (local.get $a))))
+        (local.set $exp_z (local.get $exp_a))
+        (local.set $sig_z
+          (i64.shl
+            (i64.add
+              (i64.add (i64.const 0x20000000000000) (local.get $sig_a))
+              (local.get $sig_b))
+            (i64.const 9))))
+      (else
+        (local.set $sig_a (i64.shl (local.get $sig_a) (i64.const 9)))
+        (local.set $sig_b (i64.shl (local.get $sig_b) (i64.const 9)))
+        (if (i64.lt_s (local.get $exp_diff) (i64.const 0))
+          (then
+            (if (i64.eq (local.get $exp_b) (i64.const 0x7ff))
+              (then
+                (if (i64.ne (local.get $sig_b) (i64.const 0))
+                  (then (return (call $f64_propagate_nan (local.get $a) (local.get $b)))))
+                (return
+                  (i64.or
+                    (i64.shl (local.get $sign) (i64.const 63))
+                    (i64.const 0x7ff0000000000000)))))
+            (local.set $exp_z (local.get $exp_b))
+            (local.set $sig_a
+              (select
+                (i64.add (local.get $sig_a) (i64.const 0x2000000000000000))
+                (i64.shl (local.get $sig_a) (i64.const 1))
+                (i64.ne (local.get $exp_a) (i64.const 0))))
+            (local.set $sig_a
+              (call $shift_right_jam64
+                (local.get $sig_a)
+                (i64.sub (i64.const 0) (local.get $exp_diff)))))
+          (else
+            (if (i64.eq (local.get $exp_a) (i64.const 0x7ff))
+              (then
+                (if (i64.ne (local.get $sig_a) (i64.const 0))
+                  (then (return (call $f64_propagate_nan (local.get $a) (local.get $b)))))
+                (return (local.get $a))))
+            (local.set $exp_z (local.get $exp_a))
+            (local.set $sig_b
+              (select
+                (i64.add (local.get $sig_b) (i64.const 0x2000000000000000))
+                (i64.shl (local.get $sig_b) (i64.const 1))
+                (i64.ne (local.get $exp_b) (i64.const 0))))
+            (local.set $sig_b
+              (call $shift_right_jam64 (local.get $sig_b) (local.get $exp_diff)))))
+        (local.set $sig_z
+          (i64.add
+            (i64.add (i64.const 0x2000000000000000) (local.get $sig_a))
+            (local.get $sig_b)))
+        (if (i64.lt_u (local.get $sig_z) (i64.const 0x4000000000000000))
+          (then
+            (local.set $exp_z (i64.sub (local.get $exp_z) (i64.const 1)))
+            (local.set $sig_z (i64.shl (local.get $sig_z) (i64.const 1)))))))
+    (call $f64_round_pack (local.get $sign) (local.get $exp_z) (local.get $sig_z)))
+
+  (func $f64_sub_mags (param $a i64) (param $b i64) (param $sign i64) (result i64)
+    (local $exp_a i64) (local $sig_a i64) (local $exp_b i64) (local $sig_b i64)
+    (local $exp_diff i64) (local $exp_z i64) (local $sig_z i64) (local $shift i64)
+    (local.set $exp_a (i64.and (i64.shr_u (local.get $a) (i64.const 52)) (i64.const 0x7ff)))
+    (local.set $sig_a (i64.and (local.get $a) (i64.const 0xfffffffffffff)))
+    (local.set $exp_b (i64.and (i64.shr_u (local.get $b) (i64.const 52)) (i64.const 0x7ff)))
+    (local.set $sig_b (i64.and (local.get $b) (i64.const 0xfffffffffffff)))
+    (local.set $exp_diff (i64.sub (local.get $exp_a) (local.get $exp_b)))
+    (if (i64.eqz (local.get $exp_diff))
+      (then
+        (if (i64.eq (local.get $exp_a) (i64.const 0x7ff))
+          (then
+            (if (i64.ne (i64.or (local.get $sig_a) (local.get $sig_b)) (i64.const 0))
+              (then (return (call $f64_propagate_nan (local.get $a) (local.get $b)))))
+            (return (i64.const 0x7ff8000000000000))))
+        (local.set $sig_z (i64.sub (local.get $sig_a) (local.get $sig_b)))
+        (if (i64.eqz (local.get $sig_z))
+          (then (return (i64.const 0))))
+        (if (i64.ne (local.get $exp_a) (i64.const 0))
+          (then (local.set $exp_a (i64.sub (local.get $exp_a) (i64.const 1)))))
+        (if (i64.lt_s (local.get $sig_z) (i64.const 0))
+          (then
+            (local.set $sign (i64.xor (local.get $sign) (i64.const 1)))
+            (local.set $sig_z (i64.sub (i64.const 0) (local.get $sig_z)))))
+        (local.set $shift (i64.sub (i64.clz (local.get $sig_z)) (i64.const 11)))
+        (local.set $exp_z (i64.sub (local.get $exp_a) (local.get $shift)))
+        (if (i64.lt_s (local.get $exp_z) (i64.const 0))
+          (then
+            (local.set $shift (local.get $exp_a))
+            (local.set $exp_z (i64.const 0))))
+        (return
+          (i64.add
+            (i64.add
+              (i64.shl (local.get $sign) (i64.const 63))
+              (i64.shl (local.get $exp_z) (i64.const 52)))
+            (i64.shl (local.get $sig_z) (local.get $shift))))))
+    (local.set $sig_a (i64.shl (local.get $sig_a) (i64.const 10)))
+    (local.set $sig_b (i64.shl (local.get $sig_b) (i64.const 10)))
+    (if (i64.lt_s (local.get $exp_diff) (i64.const 0))
+      (then
+        (local.set $sign (i64.xor (local.get $sign) (i64.const 1)))
+        (if (i64.eq (local.get $exp_b) (i64.const 0x7ff))
+          (then
+            (if (i64.ne (local.get $sig_b) (i64.const 0))
+              (then (return (call $f64_propagate_nan (local.get $a) (local.get $b)))))
+            (return
+              (i64.or
+                (i64.shl (local.get $sign) (i64.const 63))
+                (i64.const 0x7ff0000000000000)))))
+        (local.set $sig_a
+          (i64.add
+            (local.get $sig_a)
+            (select
+              (i64.const 0x4000000000000000)
+              (local.get $sig_a)
+              (i64.ne (local.get $exp_a) (i64.const 0)))))
+        (local.set $sig_a
+          (call $shift_right_jam64 (local.get $sig_a) (i64.sub (i64.const 0) (local.get $exp_diff))))
+        (local.set $sig_b (i64.or (local.get $sig_b) (i64.const 0x4000000000000000)))
+        (local.set $exp_z (local.get $exp_b))
+        (local.set $sig_z (i64.sub (local.get $sig_b) (local.get $sig_a))))
+      (else
+        (if (i64.eq (local.get $exp_a) (i64.const 0x7ff))
+          (then
+            (if (i64.ne (local.get $sig_a) (i64.const 0))
+              (then (return (call $f64_propagate_nan (local.get $a) (local.get $b)))))
+            (return (local.get $a))))
+        (local.set $sig_b
+          (i64.add
+            (local.get $sig_b)
+            (select
+              (i64.const 0x4000000000000000)
+              (local.get $sig_b)
+              (i64.ne (local.get $exp_b) (i64.const 0)))))
+        (local.set $sig_b (call $shift_right_jam64 (local.get $sig_b) (local.get $exp_diff)))
+        (local.set $sig_a (i64.or (local.get $sig_a) (i64.const 0x4000000000000000)))
+        (local.set $exp_z (local.get $exp_a))
+        (local.set $sig_z (i64.sub (local.get $sig_a) (local.get $sig_b)))))
+    (call $f64_norm_round_pack
+      (local.get $sign)
+      (i64.sub (local.get $exp_z) (i64.const 1))
+      (local.get $sig_z)))
+
+  (func $f64_add (export "f64_add") (param $a i64) (param $b i64) (result i64)
+    (local $sign_a i64)
+    (local.set $sign_a (i64.shr_u (local.get $a) (i64.const 63)))
+    (if (result i64) (i64.eq (local.get $sign_a) (i64.shr_u (local.get $b) (i64.const 63)))
+      (then (call $f64_add_mags (local.get $a) (local.get $b) (local.get $sign_a)))
+      (else (call $f64_sub_mags (local.get $a) (local.get $b) (local.get $sign_a)))))
+
+  (func $f64_sub (export "f64_sub") (param $a i64) (param $b i64) (result i64)
+    (call $f64_add
+      (local.get $a)
+      (i64.xor (local.get $b) (i64.const 0x8000000000000000))))
+
+  (func $f64_mul (export "f64_mul") (param $a i64) (param $b i64) (result i64)
+    (local $sign i64) (local $exp_a i64) (local $sig_a i64) (local $exp_b i64) (local $sig_b i64)
+    (local $exp_z i64) (local $sig_z i64) (local $shift i64)
+    (local $a_lo i64) (local $a_hi i64) (local $b_lo i64) (local $b_hi i64)
+    (local $lo i64) (local $mid i64) (local $mid_sum i64) (local $hi i64)
+    (local.set $sign (i64.shr_u (i64.xor (local.get $a) (local.get $b)) (i64.const 63)))
+    (local.set $exp_a (i64.and (i64.shr_u (local.get $a) (i64.const 52)) (i64.const 0x7ff)))
+    (local.set $sig_a (i64.and (local.get $a) (i64.const 0xfffffffffffff)))
+    (local.set $exp_b (i64.and (i64.shr_u (local.get $b) (i64.const 52)) (i64.const 0x7ff)))
+    (local.set $sig_b (i64.and (local.get $b) (i64.const 0xfffffffffffff)))
+    (if (i64.eq (local.get $exp_a) (i64.const 0x7ff))
+      (then
+        (if (i32.or (i64.ne (local.get $sig_a) (i64.const 0)) (call $f64_is_nan (local.get $b)))
+          (then (return (call $f64_propagate_nan (local.get $a) (local.get $b)))))
+        (return
+          (call $f64_inf_or_nan (local.get $sign) (i64.or (local.get $exp_b) (local.get $sig_b))))))
+    (if (i64.eq (local.get $exp_b) (i64.const 0x7ff))
+      (then
+        (if (i64.ne (local.get $sig_b) (i64.const 0))
+          (then (return (call $f64_propagate_nan (local.get $a) (local.get $b)))))
+        (return
+          (call $f64_inf_or_nan (local.get $sign) (i64.or (local.get $exp_a) (local.get $sig_a))))))
+    (if (i64.eqz (local.get $exp_a))
+      (then
+        (if (i64.eqz (local.get $sig_a))
+          (then (return (i64.shl (local.get $sign) (i64.const 63)))))
+        (local.set $shift (i64.sub (i64.clz (local.get $sig_a)) (i64.const 11)))
+        (local.set $exp_a (i64.sub (i64.const 1) (local.get $shift)))
+        (local.set $sig_a (i64.shl (local.get $sig_a) (local.get $shift)))))
+    (if (i64.eqz (local.get $exp_b))
+      (then
+        (if (i64.eqz (local.get $sig_b))
+          (then (return (i64.shl (local.get $sign) (i64.const 63)))))
+        (local.set $shift (i64.sub (i64.clz (local.get $sig_b)) (i64.const 11)))
+        (local.set $exp_b (i64.sub (i64.const 1) (local.get $shift)))
+        (local.set $sig_b (i64.shl (local.get $sig_b) (local.get $shift)))))
+    (local.set $exp_z (i64.sub (i64.add (local.get $exp_a) (local.get $exp_b)) (i64.const 0x3ff)))
+    (local.set $sig_a
+      (i64.shl (i64.or (local.get $sig_a) (i64.const 0x10000000000000)) (i64.const 10)))
+    (local.set $sig_b
+      (i64.shl (i64.or (local.get $sig_b) (i64.const 0x10000000000000)) (i64.const 11)))
+    ;; the high 64 bits of the 128 bits product, from 32 bits limbs
+    (local.set $a_lo (i64.and (local.get $sig_a) (i64.const 0xffffffff)))
+    (local.set $a_hi (i64.shr_u (local.get $sig_a) (i64.const 32)))
+    (local.set $b_lo (i64.and (local.get $sig_b) (i64.const 0xffffffff)))
+    (local.set $b_hi (i64.shr_u (local.get $sig_b) (i64.const 32)))
+    (local.set $lo (i64.mul (local.get $a_lo) (local.get $b_lo)))
+    (local.set $mid (i64.mul (local.get $a_hi) (local.get $b_lo)))
+    (local.set $mid_sum (i64.add (local.get $mid) (i64.mul (local.get $a_lo) (local.get $b_hi))))
+    (local.set $hi
+      (i64.add
+        (i64.mul (local.get $a_hi) (local.get $b_hi))
+        (i64.shl
+          (i64.extend_i32_u (i64.lt_u (local.get $mid_sum) (local.get $mid)))
+          (i64.const 32))))
+    (local.set $mid (i64.shl (local.get $mid_sum) (i64.const 32)))
+    (local.set $lo (i64.add (local.get $lo) (local.get $mid)))
+    (local.set $hi
+      (i64.add
+        (i64.add (local.get $hi) (i64.shr_u (local.get $mid_sum) (i64.const 32)))
+        (i64.extend_i32_u (i64.lt_u (local.get $lo) (local.get $mid)))))
+    (local.set $sig_z
+      (i64.or (local.get $hi) (i64.extend_i32_u (i64.ne (local.get $lo) (i64.const 0)))))
+    (if (i64.lt_u (local.get $sig_z) (i64.const 0x4000000000000000))
+      (then
+        (local.set $exp_z (i64.sub (local.get $exp_z) (i64.const 1)))
+        (local.set $sig_z (i64.shl (local.get $sig_z) (i64.const 1)))))
+    (call $f64_round_pack (local.get $sign) (local.get $exp_z) (local.get $sig_z)))
+
+  (func $f64_div (export "f64_div") (param $a i64) (param $b i64) (result i64)
+    (local $sign i64) (local $exp_a i64) (local $sig_a i64) (local $exp_b i64) (local $sig_b i64)
+    (local $exp_z i64) (local $shift i64) (local $quotient i64) (local $rem i64) (local $i i32)
+    (local.set $sign (i64.shr_u (i64.xor (local.get $a) (local.get $b)) (i64.const 63)))
+    (local.set $exp_a (i64.and (i64.shr_u (local.get $a) (i64.const 52)) (i64.const 0x7ff)))
+    (local.set $sig_a (i64.and (local.get $a) (i64.const 0xfffffffffffff)))
+    (local.set $exp_b (i64.and (i64.shr_u (local.get $b) (i64.const 52)) (i64.const 0x7ff)))
+    (local.set $sig_b (i64.and (local.get $b) (i64.const 0xfffffffffffff)))
+    (if (i64.eq (local.get $exp_a) (i64.const 0x7ff))
+      (then
+        (if (i64.ne (local.get $sig_a) (i64.const 0))
+          (then (return (call $f64_propagate_nan (local.get $a) (local.get $b)))))
+        (if (i64.eq (local.get $exp_b) (i64.const 0x7ff))
+          (then
+            (if (i64.ne (local.get $sig_b) (i64.const 0))
+              (then (return (call $f64_propagate_nan (local.get $a) (local.get $b)))))
+            (return (i64.const 0x7ff8000000000000))))
+        (return
+          (i64.or (i64.shl (local.get $sign) (i64.const 63)) (i64.const 0x7ff0000000000000)))))
+    (if (i64.eq (local.get $exp_b) (i64.const 0x7ff))
+      (then
+        (if (i64.ne (local.get $sig_b) (i64.const 0))
+          (then (return (call $f64_propagate_nan (local.get $a) (local.get $b)))))
+        (return (i64.shl (local.get $sign) (i64.const 63)))))
+    (if (i64.eqz (local.get $exp_b))
+      (then
+        (if (i64.eqz (local.get $sig_b))
+          (then
+            (return
+              (call $f64_inf_or_nan
+                (local.get $sign)
+                (i64.or (local.get $exp_a) (local.get $sig_a))))))
+        (local.set $shift (i64.sub (i64.clz (local.get $sig_b)) (i64.const 11)))
+        (local.set $exp_b (i64.sub (i64.const 1) (local.get $shift)))
+        (local.set $sig_b (i64.shl (local.get $sig_b) (local.get $shift)))))
+    (if (i64.eqz (local.get $exp_a))
+      (then
+        (if (i64.eqz (local.get $sig_a))
+          (then (return (i64.shl (local.get $sign) (i64.const 63)))))
+        (local.set $shift (i64.sub (i64.clz (local.get $sig_a)) (i64.const 11)))
+        (local.set $exp_a (i64.sub (i64.const 1) (local.get $shift)))
+        (local.set $sig_a (i64.shl (local.get $sig_a) (local.get $shift)))))
+    (local.set $exp_z (i64.add (i64.sub (local.get $exp_a) (local.get $exp_b)) (i64.const 0x3fe)))
+    (local.set $sig_a (i64.or (local.get $sig_a) (i64.const 0x10000000000000)))
+    (local.set $sig_b (i64.or (local.get $sig_b) (i64.const 0x10000000000000)))
+    (if (i64.lt_u (local.get $sig_a) (local.get $sig_b))
+      (then
+        (local.set $exp_z (i64.sub (local.get $exp_z) (i64.const 1)))
+        (local.set $sig_a (i64.shl (local.get $sig_a) (i64.const 1)))))
+    ;; restoring division, one quotient bit per iteration
+    (local.set $quotient (i64.const 1))
+    (local.set $rem (i64.sub (local.get $sig_a) (local.get $sig_b)))
+    (local.set $i (i32.const 62))
+    (loop $bits
+      (local.set $rem (i64.shl (local.get $rem) (i64.const 1)))
+      (local.set $quotient (i64.shl (local.get $quotient) (i64.const 1)))
+      (if (i64.ge_u (local.get $rem) (local.get $sig_b))
+        (then
+          (local.set $rem (i64.sub (local.get $rem) (local.get $sig_b)))
+          (local.set $quotient (i64.or (local.get $quotient) (i64.const 1)))))
+      (local.set $i (i32.sub (local.get $i) (i32.const 1)))
+      (br_if $bits (local.get $i)))
+    (call $f64_round_pack
+      (local.get $sign)
+      (local.get $exp_z)
+      (i64.or
+        (local.get $quotient)
+        (i64.extend_i32_u (i64.ne (local.get $rem) (i64.const 0))))))
+
+  (func $f64_sqrt (export "f64_sqrt") (param $a i64) (result i64)
+    (local $exp_a i64) (local $sig_a i64) (local $exp_z i64) (local $shift i64)
+    (local $rem i64) (local $root i64) (local $trial i64) (local $i i64)
+    (local.set $exp_a (i64.and (i64.shr_u (local.get $a) (i64.const 52)) (i64.const 0x7ff)))
+    (local.set $sig_a (i64.and (local.get $a) (i64.const 0xfffffffffffff)))
+    (if (i64.eq (local.get $exp_a) (i64.const 0x7ff))
+      (then
+        (if (i64.ne (local.get $sig_a) (i64.const 0))
+          (then (return (call $f64_propagate_nan (local.get $a) (local.get $a)))))
+        (if (i64.ge_s (local.get $a) (i64.const 0))
+          (then (return (local.get $a))))
+        (return (i64.const 0x7ff8000000000000))))
+    (if (i64.lt_s (local.get $a) (i64.const 0))
+      (then
+        (if (i64.eqz (i64.or (local.get $exp_a) (local.get $sig_a)))
+          (then (return (local.get $a))))
+        (return (i64.const 0x7ff8000000000000))))
+    (if (i64.eqz (local.get $exp_a))
+      (then
+        (if (i64.eqz (local.get $sig_a))
+          (then (return (local.get $a))))
+        (local.set $shift (i64.sub (i64.clz (local.get $sig_a)) (i64.const 11)))
+        (local.set $exp_a (i64.sub (i64.const 1) (local.get $shift)))
+        (local.set $sig_a (i64.shl (local.get $sig_a) (local.get $shift)))))
+    (local.set $exp_z
+      (i64.add
+        (i64.shr_s (i64.sub (local.get $exp_a) (i64.const 0x3ff)) (i64.const 1))
+        (i64.const 0x3fe)))
+    ;; the radicand is doubled when the unbiased exponent is odd
+    (local.set $sig_a
+      (i64.shl
+        (i64.or (local.get $sig_a) (i64.const 0x10000000000000))
+        (i64.xor (i64.and (local.get $exp_a) (i64.const 1)) (i64.const 1))))
+    ;; digit by digit square root of sig_a << 56, one root bit per iteration
+    (local.set $i (i64.const 54))
+    (loop $bits
+      (local.set $rem
+        (i64.or
+          (i64.shl (local.get $rem) (i64.const 2))
+          (select
+            (i64.and
+              (i64.shr_u
+                (local.get $sig_a)
+                (i64.sub (i64.shl (local.get $i) (i64.const 1)) (i64.const 56)))
+              (i64.const 3))
+            (i64.const 0)
+            (i64.ge_s (local.get $i) (i64.const 28)))))
+      (local.set $trial (i64.or (i64.shl (local.get $root) (i64.const 2)) (i64.const 1)))
+      (local.set $root (i64.shl (local.get $root) (i64.const 1)))
+      (if (i64.ge_u (local.get $rem) (local.get $trial))
+        (then
+          (local.set $rem (i64.sub (local.get $rem) (local.get $trial)))
+          (local.set $root (i64.or (local.get $root) (i64.const 1)))))
+      (local.set $i (i64.sub (local.get $i) (i64.const 1)))
+      (br_if $bits (i64.ge_s (local.get $i) (i64.const 0))))
+    (call $f64_round_pack
+      (i64.const 0)
+      (local.get $exp_z)
+      (i64.or
+        (i64.shl (local.get $root) (i64.const 8))
+        (i64.extend_i32_u (i64.ne (local.get $rem) (i64.const 0))))))
+
+  (func $f64_eq (export "f64_eq") (param $a i64) (param $b i64) (result i32)
+    (if (i32.or (call $f64_is_nan (local.get $a)) (call $f64_is_nan (local.get $b)))
+      (then (return (i32.const 0))))
+    (i32.or
+      (i64.eq (local.get $a) (local.get $b))
+      (i64.eqz (i64.shl (i64.or (local.get $a) (local.get $b)) (i64.const 1)))))
+
+  (func $f64_lt (export "f64_lt") (param $a i64) (param $b i64) (result i32)
+    (local $sign_a i32)
+    (if (i32.or (call $f64_is_nan (local.get $a)) (call $f64_is_nan (local.get $b)))
+      (then (return (i32.const 0))))
+    (local.set $sign_a (i32.wrap_i64 (i64.shr_u (local.get $a) (i64.const 63))))
+    (if (result i32) (i64.lt_s (i64.xor (local.get $a) (local.get $b)) (i64.const 0))
+      (then
+        (i32.and
+          (local.get $sign_a)
+          (i64.ne (i64.shl (i64.or (local.get $a) (local.get $b)) (i64.const 1)) (i64.const 0))))
+      (else
+        (i32.and
+          (i64.ne (local.get $a) (local.get $b))
+          (i32.xor (local.get $sign_a) (i64.lt_u (local.get $a) (local.get $b)))))))
+
+  (func $f64_le (export "f64_le") (param $a i64) (param $b i64) (result i32)
+    (local $sign_a i32)
+    (if (i32.or (call $f64_is_nan (local.get $a)) (call $f64_is_nan (local.get $b)))
+      (then (return (i32.const 0))))
+    (local.set $sign_a (i32.wrap_i64 (i64.shr_u (local.get $a) (i64.const 63))))
+    (if (result i32) (i64.lt_s (i64.xor (local.get $a) (local.get $b)) (i64.const 0))
+      (then
+        (i32.or
+          (local.get $sign_a)
+          (i64.eqz (i64.shl (i64.or (local.get $a) (local.get $b)) (i64.const 1)))))
+      (else
+        (i32.or
+          (i64.eq (local.get $a) (local.get $b))
+          (i32.xor (local.get $sign_a) (i64.lt_u (local.get $a) (local.get $b)))))))
+
+  (func $f64_gt (export "f64_gt") (param $a i64) (param $b i64) (result i32)
+    (call $f64_lt (local.get $b) (local.get $a)))
+
+  (func $f64_ge (export "f64_ge") (param $a i64) (param $b i64) (result i32)
+    (call $f64_le (local.get $b) (local.get $a)))
+
+  (func $f64_min (export "f64_min") (param $a i64) (param $b i64) (result i64)
+    (if (i32.or (call $f64_is_nan (local.get $a)) (call $f64_is_nan (local.get $b)))
+      (then (return (i64.const 0x7ff8000000000000))))
+    (if (call $f64_lt (local.get $a) (local.get $b))
+      (then (return (local.get $a))))
+    (if (call $f64_lt (local.get $b) (local.get $a))
+      (then (return (local.get $b))))
+    ;; min(-0, +0) is -0
+    (i64.or (local.get $a) (local.get $b)))
+
+  (func $f64_max (export "f64_max") (param $a i64) (param $b i64) (result i64)
+    (if (i32.or (call $f64_is_nan (local.get $a)) (call $f64_is_nan (local.get $b)))
+      (then (return (i64.const 0x7ff8000000000000))))
+    (if (call $f64_lt (local.get $b) (local.get $a))
+      (then (return (local.get $a))))
+    (if (call $f64_lt (local.get $a) (local.get $b))
+      (then (return (local.get $b))))
+    ;; max(-0, +0) is +0
+    (i64.and (local.get $a) (local.get $b)))
+
+  (func $f64_copysign (export "f64_copysign") (param $a i64) (param $b i64) (result i64)
+    (i64.or
+      (i64.and (local.get $a) (i64.const 0x7fffffffffffffff))
+      (i64.and (local.get $b) (i64.const 0x8000000000000000))))
+
+  ;; mode 0 rounds to nearest even, 1 towards -inf, 2 towards +inf and 3 towards zero
+  (func $f64_round_to_int (param $a i64) (param $mode i32) (result i64)
+    (local $exp i64) (local $z i64) (local $last_bit i64) (local $round_mask i64)
+    (local.set $exp (i64.and (i64.shr_u (local.get $a) (i64.const 52)) (i64.const 0x7ff)))
+    (if (i64.le_u (local.get $exp) (i64.const 0x3fe))
+      (then
+        (if (i64.eqz (i64.shl (local.get $a) (i64.const 1)))
+          (then (return (local.get $a))))
+        (local.set $z (i64.and (local.get $a) (i64.const 0x8000000000000000)))
+        (if (i32.eqz (local.get $mode))
+          (then
+            (if (i32.and
+                  (i64.eq (local.get $exp) (i64.const 0x3fe))
+                  (i64.ne (i64.and (local.get $a) (i64.const 0xfffffffffffff)) (i64.const 0)))
+              (then (return (i64.or (local.get $z) (i64.const 0x3ff0000000000000)))))))
+        (if (i32.and (i32.eq (local.get $mode) (i32.const 1)) (i64.ne (local.get $z) (i64.const 0)))
+          (then (return (i64.const 0xbff0000000000000))))
+        (if (i32.and (i32.eq (local.get $mode) (i32.const 2)) (i64.eqz (local.get $z)))
+          (then (return (i64.const 0x3ff0000000000000))))
+        (return (local.get $z))))
+    (if (i64.ge_u (local.get $exp) (i64.const 0x433))
+      (then
+        (if (call $f64_is_nan (local.get $a))
+          (then (return (i64.or (local.get $a) (i64.const 0x0008000000000000)))))
+        (return (local.get $a))))
+    (local.set $z (local.get $a))
+    (local.set $last_bit (i64.shl (i64.const 1) (i64.sub (i64.const 0x433) (local.get $exp))))
+    (local.set $round_mask (i64.sub (local.get $last_bit) (i64.const 1)))
+    (if (i32.eqz (local.get $mode))
+      (then
+        (local.set $z (i64.add (local.get $z) (i64.shr_u (local.get $last_bit) (i64.const 1))))
+        (if (i64.eqz (i64.and (local.get $z) (local.get $round_mask)))
+          (then
+            (local.set $z
+              (i64.and (local.get $z) (i64.xor (local.get $last_bit) (i64.const -1))))))))
+    ;; rounding away from zero, floor of a negative value or ceil of a positive one
+    (if (i32.or
+          (i32.and (i32.eq (local.get $mode) (i32.const 1)) (i64.lt_s (local.get $z) (i64.const 0)))
+          (i32.and (i32.eq (local.get $mode) (i32.const 2)) (i64.ge_s (local.get $z) (i64.const 0))))
+      (then (local.set $z (i64.add (local.get $z) (local.get $round_mask)))))
+    (i64.and (local.get $z) (i64.xor (local.get $round_mask) (i64.const -1))))
+
+  (func $f64_nearest (export "f64_nearest") (param $a i64) (result i64)
+    (call $f64_round_to_int (local.get $a) (i32.const 0)))
+
+  (func $f64_floor (export "f64_floor") (param $a i64) (result i64)
+    (call $f64_round_to_int (local.get $a) (i32.const 1)))
+
+  (func $f64_ceil (export "f64_ceil") (param $a i64) (result i64)
+    (call $f64_round_to_int (local.get $a) (i32.const 2)))
+
+  (func $f64_trunc (export "f64_trunc") (param $a i64) (result i64)
+    (call $f64_round_to_int (local.get $a) (i32.const 3)))
+
+  (func $f64_promote_f32 (export "f64_promote_f32") (param $a i32) (result i64)
+    (local $sign i64) (local $exp i64) (local $frac i64) (local $shift i64)
+    (local.set $sign
+      (i64.shl (i64.extend_i32_u (i32.shr_u (local.get $a) (i32.const 31))) (i64.const 63)))
+    (local.set $exp
+      (i64.extend_i32_u (i32.and (i32.shr_u (local.get $a) (i32.const 23)) (i32.const 0xff))))
+    (local.set $frac (i64.extend_i32_u (i32.and (local.get $a) (i32.const 0x7fffff))))
+    (if (i64.eq (local.get $exp) (i64.const 0xff))
+      (then
+        (return
+          (i64.or
+            (i64.or (local.get $sign) (i64.const 0x7ff0000000000000))
+            (select
+              (i64.or (i64.const 0x0008000000000000) (i64.shl (local.get $frac) (i64.const 29)))
+              (i64.const 0)
+              (i64.ne (local.get $frac) (i64.const 0)))))))
+    (if (i64.eqz (local.get $exp))
+      (then
+        (if (i64.eqz (local.get $frac))
+          (then (return (local.get $sign))))
+        ;; the leading bit moves to the hidden bit, which is added to the exponent below
+        (local.set $shift (i64.sub (i64.clz (local.get $frac)) (i64.const 40)))
+        (local.set $exp (i64.sub (i64.const 0) (local.get $shift)))
+        (local.set $frac (i64.shl (local.get $frac) (local.get $shift)))))
+    (i64.add
+      (i64.add
+        (local.get $sign)
+        (i64.shl (i64.add (local.get $exp) (i64.const 0x380)) (i64.const 52)))
+      (i64.shl (local.get $frac) (i64.const 29))))
+
+  ;; sig has its leading bit at bit 30 and 7 rounding bits, exp is the biased exponent minus one
+  (func $f32_round_pack (param $sign i64) (param $exp i64) (param $sig i64) (result i32)
+    (local $round_bits i64)
+    (local.set $round_bits (i64.and (local.get $sig) (i64.const 0x7f)))
+    (if (i64.ge_u (local.get $exp) (i64.const 0xfd))
+      (then
+        (if (i64.lt_s (local.get $exp) (i64.const 0))
+          (then
+            (local.set $sig
+              (call $shift_right_jam64 (local.get $sig) (i64.sub (i64.const 0) (local.get $exp))))
+            (local.set $exp (i64.const 0))
+            (local.set $round_bits (i64.and (local.get $sig) (i64.const 0x7f))))
+          (else
+            (if (i32.or
+                  (i64.gt_s (local.get $exp) (i64.const 0xfd))
+                  (i64.ge_u (i64.add (local.get $sig) (i64.const 0x40)) (i64.const 0x80000000)))
+              (then
+                (return
+                  (i32.wrap_i64
+                    (i64.or
+                      (i64.shl (local.get $sign) (i64.const 31))
+                      (i64.const 0x7f800000))))))))))
+    (local.set $sig (i64.shr_u (i64.add (local.get $sig) (i64.const 0x40)) (i64.const 7)))
+    (if (i64.eq (local.get $round_bits) (i64.const 0x40))
+      (then (local.set $sig (i64.and (local.get $sig) (i64.const -2)))))
+    (if (i64.eqz (local.get $sig))
+      (then (local.set $exp (i64.const 0))))
+    (i32.wrap_i64
+      (i64.add
+        (i64.add
+          (i64.shl (local.get $sign) (i64.const 31))
+          (i64.shl (local.get $exp) (i64.const 23)))
+        (local.get $sig))))
+
+  (func $f32_demote_f64 (export "f32_demote_f64") (param $a i64) (result i32)
+    (local $sign i64) (local $exp i64) (local $frac i64)
+    (local.set $sign (i64.shr_u (local.get $a) (i64.const 63)))
+    (local.set $exp (i64.and (i64.shr_u (local.get $a) (i64.const 52)) (i64.const 0x7ff)))
+    (local.set $frac (i64.and (local.get $a) (i64.const 0xfffffffffffff)))
+    (if (i64.eq (local.get $exp) (i64.const 0x7ff))
+      (then
+        (return
+          (i32.wrap_i64
+            (i64.or
+              (i64.or (i64.shl (local.get $sign) (i64.const 31)) (i64.const 0x7f800000))
+              (select
+                (i64.or (i64.const 0x400000) (i64.shr_u (local.get $frac) (i64.const 29)))
+                (i64.const 0)
+                (i64.ne (local.get $frac) (i64.const 0))))))))
+    (local.set $frac
+      (i64.or
+        (i64.shr_u (local.get $frac) (i64.const 22))
+        (i64.extend_i32_u
+          (i64.ne (i64.and (local.get $frac) (i64.const 0x3fffff)) (i64.const 0)))))
+    (if (i64.eqz (i64.or (local.get $exp) (local.get $frac)))
+      (then (return (i32.wrap_i64 (i64.shl (local.get $sign) (i64.const 31))))))
+    (call $f32_round_pack
+      (local.get $sign)
+      (i64.sub (local.get $exp) (i64.const 0x381))
+      (i64.or (local.get $frac) (i64.const 0x40000000))))
+
+  ;; the f64 nearest to the unsigned magnitude mag
+  (func $f64_from_mag (param $sign i64) (param $mag i64) (result i64)
+    (if (i64.eqz (local.get $mag))
+      (then (return (i64.const 0))))
+    (if (i64.lt_s (local.get $mag) (i64.const 0))
+      (then
+        (return
+          (call $f64_round_pack
+            (local.get $sign)
+            (i64.const 0x43d)
+            (i64.or
+              (i64.shr_u (local.get $mag) (i64.const 1))
+              (i64.and (local.get $mag) (i64.const 1)))))))
+    (call $f64_norm_round_pack (local.get $sign) (i64.const 0x43c) (local.get $mag)))
+
+  ;; the f32 nearest to the unsigned magnitude mag
+  (func $f32_from_mag (param $sign i64) (param $mag i64) (result i32)
+    (local $shift i64)
+    (if (i64.eqz (local.get $mag))
+      (then (return (i32.const 0))))
+    (local.set $shift (i64.sub (i64.clz (local.get $mag)) (i64.const 40)))
+    (if (i64.ge_s (local.get $shift) (i64.const 0))
+      (then
+        (return
+          (i32.wrap_i64
+            (i64.add
+              (i64.add
+                (i64.shl (local.get $sign) (i64.const 31))
+                (i64.shl (i64.sub (i64.const 0x95) (local.get $shift)) (i64.const 23)))
+              (i64.shl (local.get $mag) (local.get $shift)))))))
+    (local.set $shift (i64.add (local.get $shift) (i64.const 7)))
+    (call $f32_round_pack
+      (local.get $sign)
+      (i64.sub (i64.const 0x9c) (local.get $shift))
+      (select
+        (call $shift_right_jam64 (local.get $mag) (i64.sub (i64.const 0) (local.get $shift)))
+        (i64.shl (local.get $mag) (local.get $shift))
+        (i64.lt_s (local.get $shift) (i64.const 0)))))
+
+  (func $f64_convert_i64_s (export "f64_convert_i64_s") (param $a i64) (result i64)
+    (call $f64_from_mag
+      (i64.shr_u (local.get $a) (i64.const 63))
+      (select
+        (i64.sub (i64.const 0) (local.get $a))
+        (local.get $a)
+        (i64.lt_s (local.get $a) (i64.const 0)))))
+
+  (func $f64_convert_i64_u (export "f64_convert_i64_u") (param $a i64) (result i64)
+    (call $f64_from_mag (i64.const 0) (local.get $a)))
+
+  (func $f64_convert_i32_s (export "f64_convert_i32_s") (param $a i32) (result i64)
+    (call $f64_convert_i64_s (i64.extend_i32_s (local.get $a))))
+
+  (func $f64_convert_i32_u (export "f64_convert_i32_u") (param $a i32) (result i64)
+    (call $f64_from_mag (i64.const 0) (i64.extend_i32_u (local.get $a))))
+
+  (func $f32_convert_i64_s (export "f32_convert_i64_s") (param $a i64) (result i32)
+    (call $f32_from_mag
+      (i64.shr_u (local.get $a) (i64.const 63))
+      (select
+        (i64.sub (i64.const 0) (local.get $a))
+        (local.get $a)
+        (i64.lt_s (local.get $a) (i64.const 0)))))
+
+  (func $f32_convert_i64_u (export "f32_convert_i64_u") (param $a i64) (result i32)
+    (call $f32_from_mag (i64.const 0) (local.get $a)))
+
+  (func $f32_convert_i32_s (export "f32_convert_i32_s") (param $a i32) (result i32)
+    (call $f32_convert_i64_s (i64.extend_i32_s (local.get $a))))
+
+  (func $f32_convert_i32_u (export "f32_convert_i32_u") (param $a i32) (result i32)
+    (call $f32_from_mag (i64.const 0) (i64.extend_i32_u (local.get $a))))
+
+  ;; the unbiased exponent of a, traps if a is NaN
+  (func $f64_trunc_exp (param $a i64) (result i64)
+    (if (call $f64_is_nan (local.get $a))
+      (then (unreachable)))
+    (i64.sub
+      (i64.and (i64.shr_u (local.get $a) (i64.const 52)) (i64.const 0x7ff))
+      (i64.const 0x3ff)))
+
+  ;; the integer part of |a|, for an unbiased exponent in [0, 63]
+  (func $f64_int_mag (param $a i64) (param $exp i64) (result i64)
+    (local $sig i64)
+    (local.set $sig
+      (i64.or (i64.and (local.get $a) (i64.const 0xfffffffffffff)) (i64.const 0x10000000000000)))
+    (select
+      (i64.shl (local.get $sig) (i64.sub (local.get $exp) (i64.const 52)))
+      (i64.shr_u (local.get $sig) (i64.sub (i64.const 52) (local.get $exp)))
+      (i64.ge_s (local.get $exp) (i64.const 52))))
+
+  (func $i64_trunc_f64_s (export "i64_trunc_f64_s") (param $a i64) (result i64)
+    (local $exp i64) (local $mag i64)
+    (local.set $exp (call $f64_trunc_exp (local.get $a)))
+    (if (i64.lt_s (local.get $exp) (i64.const 0))
+      (then (return (i64.const 0))))
+    (if (i64.ge_s (local.get $exp) (i64.const 63))
+      (then
+        (if (i64.eq (local.get $a) (i64.const 0xc3e0000000000000))
+          (then (return (i64.const 0x8000000000000000))))
+        (unreachable)))
+    (local.set $mag (call $f64_int_mag (local.get $a) (local.get $exp)))
+    (select
+      (i64.sub (i64.const 0) (local.get $mag))
+      (local.get $mag)
+      (i64.lt_s (local.get $a) (i64.const 0))))
+
+  (func $i64_trunc_f64_u (export "i64_trunc_f64_u") (param $a i64) (result i64)
+    (local $exp i64)
+    (local.set $exp (call $f64_trunc_exp (local.get $a)))
+    (if (i64.lt_s (local.get $exp) (i64.const 0))
+      (then (return (i64.const 0))))
+    (if (i32.or
+          (i64.lt_s (local.get $a) (i64.const 0))
+          (i64.ge_s (local.get $exp) (i64.const 64)))
+      (then (unreachable)))
+    (call $f64_int_mag (local.get $a) (local.get $exp)))
+
+  (func $i32_trunc_f64_s (export "i32_trunc_f64_s") (param $a i64) (result i32)
+    (local $exp i64) (local $mag i64)
+    (local.set $exp (call $f64_trunc_exp (local.get $a)))
+    (if (i64.lt_s (local.get $exp) (i64.const 0))
+      (then (return (i32.const 0))))
+    (if (i64.ge_s (local.get $exp) (i64.const 32))
+      (then (unreachable)))
+    (local.set $mag (call $f64_int_mag (local.get $a) (local.get $exp)))
+    (if (i64.lt_s (local.get $a) (i64.const 0))
+      (then
+        (if (i64.gt_u (local.get $mag) (i64.const 0x80000000))
+          (then (unreachable)))
+        (return (i32.wrap_i64 (i64.sub (i64.const 0) (local.get $mag))))))
+    (if (i64.ge_u (local.get $mag) (i64.const 0x80000000))
+      (then (unreachable)))
+    (i32.wrap_i64 (local.get $mag)))
+
+  (func $i32_trunc_f64_u (export "i32_trunc_f64_u") (param $a i64) (result i32)
+    (local $exp i64)
+    (local.set $exp (call $f64_trunc_exp (local.get $a)))
+    (if (i64.lt_s (local.get $exp) (i64.const 0))
+      (then (return (i32.const 0))))
+    (if (i32.or
+          (i64.lt_s (local.get $a) (i64.const 0))
+          (i64.ge_s (local.get $exp) (i64.const 32)))
+      (then (unreachable)))
+    (i32.wrap_i64 (call $f64_int_mag (local.get $a) (local.get $exp))))
+
+  (func (export "i64_trunc_f32_s") (param $a i32) (result i64)
+    (call $i64_trunc_f64_s (call $f64_promote_f32 (local.get $a))))
+
+  (func (export "i64_trunc_f32_u") (param $a i32) (result i64)
+    (call $i64_trunc_f64_u (call $f64_promote_f32 (local.get $a))))
+
+  (func (export "i32_trunc_f32_s") (param $a i32) (result i32)
+    (call $i32_trunc_f64_s (call $f64_promote_f32 (local.get $a))))
+
+  (func (export "i32_trunc_f32_u") (param $a i32) (result i32)
+    (call $i32_trunc_f64_u (call $f64_promote_f32 (local.get $a))))
+
+  (func (export "f32_add") (param $a i32) (param $b i32) (result i32)
+    (call $f32_demote_f64
+      (call $f64_add (call $f64_promote_f32 (local.get $a)) (call $f64_promote_f32 (local.get $b)))))
+
+  (func (export "f32_sub") (param $a i32) (param $b i32) (result i32)
+    (call $f32_demote_f64
+      (call $f64_sub (call $f64_promote_f32 (local.get $a)) (call $f64_promote_f32 (local.get $b)))))
+
+  (func (export "f32_mul") (param $a i32) (param $b i32) (result i32)
+    (call $f32_demote_f64
+      (call $f64_mul (call $f64_promote_f32 (local.get $a)) (call $f64_promote_f32 (local.get $b)))))
+
+  (func (export "f32_div") (param $a i32) (param $b i32) (result i32)
+    (call $f32_demote_f64
+      (call $f64_div (call $f64_promote_f32 (local.get $a)) (call $f64_promote_f32 (local.get $b)))))
+
+  (func (export "f32_min") (param $a i32) (param $b i32) (result i32)
+    (call $f32_demote_f64
+      (call $f64_min (call $f64_promote_f32 (local.get $a)) (call $f64_promote_f32 (local.get $b)))))
+
+  (func (export "f32_max") (param $a i32) (param $b i32) (result i32)
+    (call $f32_demote_f64
+      (call $f64_max (call $f64_promote_f32 (local.get $a)) (call $f64_promote_f32 (local.get $b)))))
+
+  (func (export "f32_copysign") (param $a i32) (param $b i32) (result i32)
+    (i32.or
+      (i32.and (local.get $a) (i32.const 0x7fffffff))
+      (i32.and (local.get $b) (i32.const 0x80000000))))
+
+  (func (export "f32_sqrt") (param $a i32) (result i32)
+    (call $f32_demote_f64 (call $f64_sqrt (call $f64_promote_f32 (local.get $a)))))
+
+  (func (export "f32_nearest") (param $a i32) (result i32)
+    (call $f32_demote_f64 (call $f64_nearest (call $f64_promote_f32 (local.get $a)))))
+
+  (func (export "f32_floor") (param $a i32) (result i32)
+    (call $f32_demote_f64 (call $f64_floor (call $f64_promote_f32 (local.get $a)))))
+
+  (func (export "f32_ceil") (param $a i32) (result i32)
+    (call $f32_demote_f64 (call $f64_ceil (call $f64_promote_f32 (local.get $a)))))
+
+  (func (export "f32_trunc") (param $a i32) (result i32)
+    (call $f32_demote_f64 (call $f64_trunc (call $f64_promote_f32 (local.get $a)))))
+
+  (func (export "f32_eq") (param $a i32) (param $b i32) (result i32)
+    (call $f64_eq (call $f64_promote_f32 (local.get $a)) (call $f64_promote_f32 (local.get $b))))
+
+  (func (export "f32_lt") (param $a i32) (param $b i32) (result i32)
+    (call $f64_lt (call $f64_promote_f32 (local.get $a)) (call $f64_promote_f32 (local.get $b))))
+
+  (func (export "f32_le") (param $a i32) (param $b i32) (result i32)
+    (call $f64_le (call $f64_promote_f32 (local.get $a)) (call $f64_promote_f32 (local.get $b))))
+
+  (func (export "f32_gt") (param $a i32) (param $b i32) (result i32)
+    (call $f64_lt (call $f64_promote_f32 (local.get $b)) (call $f64_promote_f32 (local.get $a))))
+
+  (func (export "f32_ge") (param $a i32) (param $b i32) (result i32)
+    (call $f64_le (call $f64_promote_f32 (local.get $b)) (call $f64_promote_f32 (local.get $a))))
+)
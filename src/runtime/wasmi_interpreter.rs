@@ -1,20 +1,41 @@
 use std::cell::RefCell;
 use std::collections::HashMap;
+use std::panic;
+use std::panic::AssertUnwindSafe;
 use std::rc::Rc;
 
-use crate::circuits::config::zkwasm_k;
+use crate::runtime::budget::BudgetMonitor;
+use crate::runtime::budget::CapacityExceeded;
+use crate::runtime::budget::CircuitTable;
+use crate::runtime::budget::ExecutionBudget;
+use crate::runtime::entry::wrap_entry;
+use crate::runtime::entry::EntryPoint;
 use crate::runtime::memory_event_of_step;
+use crate::runtime::segment::post_image_table;
+use crate::runtime::segment::terminate_state;
+use crate::runtime::soft_float::lower_float;
+use crate::runtime::unwind_trap;
+use anyhow::anyhow;
 use anyhow::Result;
+use specs::etable::EventTableEntry;
+use specs::etable::StepLimitExceeded;
 use specs::host_function::HostFunctionDesc;
 use specs::jtable::StaticFrameEntry;
+use specs::kvtable::KvTable;
 use specs::mtable::MTable;
+use specs::state::InitializationState;
+use specs::step::StepInfo;
 use specs::CompilationTable;
 use specs::ExecutionTable;
 use specs::Tables;
 use wasmi::Externals;
 use wasmi::ImportResolver;
 use wasmi::ModuleInstance;
+use wasmi::RuntimeArgs;
 use wasmi::RuntimeValue;
+use wasmi::Trap;
+use wasmi::TrapKind;
+use wasmi::DEFAULT_VALUE_STACK_LIMIT;
 
 use super::CompiledImage;
 use super::ExecutionResult;
@@ -22,6 +43,8 @@ use super::ExecutionResult;
 pub struct WasmRuntimeIO {
     pub public_inputs_and_outputs: Rc<RefCell<Vec<u64>>>,
     pub outputs: Rc<RefCell<Vec<u64>>>,
+    /// The accesses of the KV store, recorded by the KV plugin if it is registered.
+    pub kvtable: Rc<RefCell<KvTable>>,
 }
 
 impl WasmRuntimeIO {
@@ -29,10 +52,73 @@ impl WasmRuntimeIO {
         Self {
             public_inputs_and_outputs: Rc::new(RefCell::new(vec![])),
             outputs: Rc::new(RefCell::new(vec![])),
+            kvtable: Rc::new(RefCell::new(KvTable::default())),
         }
     }
 }
 
+/// The externals of an execution, which is checked against its budget at each host call.
+struct MonitoredExternals<'a, E> {
+    externals: &'a mut E,
+    tracer: Rc<RefCell<wasmi::tracer::Tracer>>,
+    monitor: BudgetMonitor,
+}
+
+impl<'a, E: Externals> Externals for MonitoredExternals<'a, E> {
+    fn invoke_index(
+        &mut self,
+        index: usize,
+        args: RuntimeArgs,
+    ) -> Result<Option<RuntimeValue>, Trap> {
+        {
+            let tracer = self.tracer.borrow();
+
+            self.monitor
+                .observe(&tracer.etable, &tracer.jtable)
+                .map_err(|err| Trap::new(TrapKind::Host(Box::new(err))))?;
+        }
+
+        self.externals.invoke_index(index, args)
+    }
+}
+
+/// The error of a failed execution, the budget exceeded by the execution if it trapped on
+/// it.
+fn execution_error(err: wasmi::Error) -> anyhow::Error {
+    match err
+        .as_host_error()
+        .and_then(|err| err.downcast_ref::<CapacityExceeded>())
+    {
+        Some(exceeded) => exceeded.clone().into(),
+        None => match &err {
+            wasmi::Error::Trap(trap) if !trap.kind().is_host() => {
+                anyhow!(
+                    "the execution traps on {:?}, which can not be proved",
+                    trap.kind()
+                )
+            }
+            _ => err.into(),
+        },
+    }
+}
+
+/// Runs `invoke`, an execution exceeding the step limit of the etable of its tracer is
+/// aborted with the `CapacityExceeded` of the etable, see `EventTable::set_step_limit`.
+fn catch_step_limit<R>(invoke: impl FnOnce() -> Result<R>) -> Result<R> {
+    match panic::catch_unwind(AssertUnwindSafe(invoke)) {
+        Ok(result) => result,
+        Err(payload) => match payload.downcast::<StepLimitExceeded>() {
+            Ok(exceeded) => Err(CapacityExceeded {
+                table: CircuitTable::EventTable,
+                required: exceeded.limit + 1,
+                capacity: exceeded.limit,
+            }
+            .into()),
+            Err(payload) => panic::resume_unwind(payload),
+        },
+    }
+}
+
 pub trait Execution<R> {
     fn run<E: Externals>(
         self,
@@ -49,13 +135,66 @@ impl Execution<RuntimeValue>
         externals: &mut E,
         wasm_io: WasmRuntimeIO,
     ) -> Result<ExecutionResult<RuntimeValue>> {
-        let instance = self
-            .instance
-            .run_start_tracer(externals, self.tracer.clone())
-            .unwrap();
+        let mut externals = MonitoredExternals {
+            externals,
+            tracer: self.tracer.clone(),
+            monitor: self.budget.monitor(&self.tables),
+        };
+
+        // The steps are bounded by the tracer, which aborts a computation exceeding the
+        // budget whether it calls the host or not.
+        self.tracer
+            .borrow_mut()
+            .etable
+            .set_step_limit(Some(self.budget.steps));
+
+        let invocation = catch_step_limit(|| {
+            let instance = self
+                .instance
+                .run_start_tracer(&mut externals, self.tracer.clone())
+                .map_err(|trap| execution_error(trap.into()))?;
+
+            Ok(instance.invoke_export_trace(&self.entry, &[], &mut externals, self.tracer.clone()))
+        });
+
+        self.tracer.borrow_mut().etable.set_step_limit(None);
+
+        let (result, trap) = match invocation? {
+            Ok(result) => (result, None),
+            Err(err) => {
+                let mut tracer = self.tracer.borrow_mut();
+
+                // Only the traps recorded by the tracer can be proved, other errors are fatal.
+                let code = match tracer.etable.entries().last() {
+                    Some(EventTableEntry {
+                        step_info: StepInfo::Trap { code, .. },
+                        inst,
+                        ..
+                    }) => {
+                        if !code.is_proved_for(&inst.opcode) {
+                            return Err(anyhow!(
+                                "the execution traps on {:?} at {:?}, which can not be proved",
+                                code,
+                                inst.opcode
+                            ));
+                        }
 
-        let result =
-            instance.invoke_export_trace(&self.entry, &[], externals, self.tracer.clone())?;
+                        *code
+                    }
+                    _ => return Err(execution_error(err)),
+                };
+
+                let jtable = tracer.jtable.clone();
+                unwind_trap(&mut tracer.etable, &jtable, &self.tables.static_jtable);
+
+                wasm_io
+                    .public_inputs_and_outputs
+                    .borrow_mut()
+                    .push(code as u64);
+
+                (None, Some(code))
+            }
+        };
 
         let execution_tables = {
             let tracer = self.tracer.borrow();
@@ -76,26 +215,69 @@ impl Execution<RuntimeValue>
                 etable: tracer.etable.clone(),
                 mtable,
                 jtable: tracer.jtable.clone(),
+                kvtable: wasm_io.kvtable.borrow().clone(),
             }
         };
 
+        self.budget.check(&self.tables, &execution_tables)?;
+
+        let post_image_table = post_image_table(
+            &self.tables,
+            &execution_tables,
+            &terminate_state(&execution_tables.etable),
+        );
+
         Ok(ExecutionResult {
             tables: Tables {
                 compilation_tables: self.tables.clone(),
                 execution_tables,
+                post_image_table,
             },
             result,
             public_inputs_and_outputs: wasm_io.public_inputs_and_outputs.borrow().clone(),
             outputs: wasm_io.public_inputs_and_outputs.borrow().clone(),
+            trap,
         })
     }
 }
 
-pub struct WasmiRuntime;
+pub struct WasmiRuntime {
+    k: u32,
+    budget: Option<ExecutionBudget>,
+}
 
 impl WasmiRuntime {
-    pub fn new() -> Self {
-        WasmiRuntime
+    /// A runtime compiling images for the circuit of `k`, their executions are bounded by
+    /// the capacity of the circuit.
+    pub fn new(k: u32) -> Self {
+        WasmiRuntime { k, budget: None }
+    }
+
+    /// Bounds the executions of the compiled images by `budget` instead of the capacity
+    /// of the circuit of `k`.
+    pub fn with_budget(k: u32, budget: ExecutionBudget) -> Self {
+        WasmiRuntime {
+            k,
+            budget: Some(budget),
+        }
+    }
+
+    /// Loads a wasm binary, lowering its float instructions into soft-float routines first.
+    /// The lowering happens here since `compile` only borrows the module.
+    pub fn load(&self, wasm: &[u8]) -> Result<wasmi::Module> {
+        let module = parity_wasm::elements::Module::from_bytes(wasm)?;
+        let module = lower_float(module)?;
+
+        Ok(wasmi::Module::from_parity_wasm_module(module)?)
+    }
+
+    /// Like `load`, then wraps `entry` into the export `ENTRY_WRAPPER` taking its arguments
+    /// as public inputs, the image is then compiled with `ENTRY_WRAPPER` as its entry.
+    pub fn load_entry(&self, wasm: &[u8], entry: &EntryPoint) -> Result<wasmi::Module> {
+        let module = parity_wasm::elements::Module::from_bytes(wasm)?;
+        let module = wrap_entry(lower_float(module)?, entry)?;
+
+        Ok(wasmi::Module::from_parity_wasm_module(module)?)
     }
 
     pub fn compile<'a, I: ImportResolver>(
@@ -108,8 +290,7 @@ impl WasmiRuntime {
         let tracer = wasmi::tracer::Tracer::new(host_plugin_lookup.clone());
         let tracer = Rc::new(RefCell::new(tracer));
 
-        let instance = ModuleInstance::new(&module, imports, Some(tracer.clone()))
-            .expect("failed to instantiate wasm module");
+        let instance = ModuleInstance::new(&module, imports, Some(tracer.clone()))?;
 
         let fid_of_entry = {
             let idx_of_entry = instance.lookup_function_by_name(tracer.clone(), entry);
@@ -148,21 +329,38 @@ impl WasmiRuntime {
         };
 
         let itable = tracer.borrow().itable.clone();
-        let imtable = tracer.borrow().imtable.finalized(zkwasm_k());
+        let imtable = tracer.borrow().imtable.finalized(self.k);
         let elem_table = tracer.borrow().elem_table.clone();
         let configure_table = tracer.borrow().configure_table.clone();
         let static_jtable = tracer.borrow().static_jtable_entries.clone();
+        let initialization_state = InitializationState {
+            eid: 1,
+            fid: fid_of_entry,
+            iid: 0,
+            frame_id: 0,
+            sp: DEFAULT_VALUE_STACK_LIMIT as u32 - 1,
+
+            initial_memory_pages: configure_table.init_memory_pages,
+        };
+
+        let tables = CompilationTable {
+            itable,
+            imtable,
+            elem_table,
+            configure_table,
+            static_jtable,
+            initialization_state,
+        };
+
+        let budget = self
+            .budget
+            .clone()
+            .unwrap_or_else(|| ExecutionBudget::for_circuit(self.k));
 
         Ok(CompiledImage {
             entry: entry.to_owned(),
-            tables: CompilationTable {
-                itable,
-                imtable,
-                elem_table,
-                configure_table,
-                static_jtable,
-                fid_of_entry,
-            },
+            tables,
+            budget,
             instance,
             tracer,
         })
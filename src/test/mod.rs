@@ -1,6 +1,7 @@
-use crate::circuits::config::zkwasm_k;
+use crate::circuits::config::MIN_K;
 use crate::circuits::utils::table_entry::MemoryWritingTable;
 use crate::circuits::TestCircuit;
+use crate::foreign::kv_helper::kv_instances;
 use crate::profile::Profiler;
 use crate::runtime::host::host_env::HostEnv;
 use crate::runtime::wasmi_interpreter::Execution;
@@ -15,6 +16,7 @@ use anyhow::Result;
 use halo2_proofs::arithmetic::FieldExt;
 use halo2_proofs::dev::MockProver;
 use halo2_proofs::pairing::bn256::Fr;
+use specs::Tables;
 use wasmi::ImportsBuilder;
 use wasmi::RuntimeValue;
 
@@ -22,66 +24,83 @@ use wasmi::RuntimeValue;
 mod test_wasm_instructions;
 
 mod spec;
+mod test_aggregate;
 mod test_binary_search;
+mod test_budget;
+mod test_configure;
+mod test_continuation;
+mod test_entry_args;
 mod test_fibonacci;
+mod test_input_manifest;
+mod test_params;
+mod test_prover;
 mod test_rlp;
 mod test_rlp_simple;
 mod test_start;
+mod test_trace;
 
 #[cfg(feature = "checksum")]
 mod test_uniform_verifier;
 
-/// Create circuit with trace and run mock test.
-fn test_circuit_mock<F: FieldExt>(
-    execution_result: ExecutionResult<wasmi::RuntimeValue>,
-) -> Result<()> {
-    let instance = {
-        let mut v: Vec<F> = vec![];
+/// The instances of the proof of `tables`.
+fn instances_of<F: FieldExt>(tables: &Tables, public_inputs_and_outputs: &Vec<u64>) -> Vec<F> {
+    let mut v: Vec<F> = vec![];
 
-        #[cfg(feature = "checksum")]
-        v.push(execution_result.tables.compilation_tables.hash());
+    #[cfg(feature = "checksum")]
+    v.push(tables.compilation_tables.hash());
+    #[cfg(feature = "checksum")]
+    v.push(tables.post_image_table.hash());
 
-        v.append(
-            &mut execution_result
-                .public_inputs_and_outputs
-                .iter()
-                .map(|v| (*v).into())
-                .collect(),
-        );
+    v.append(&mut kv_instances(tables));
+    v.push(F::from(tables.is_terminated() as u64));
 
-        v
-    };
+    v.append(
+        &mut public_inputs_and_outputs
+            .iter()
+            .map(|v| (*v).into())
+            .collect(),
+    );
+
+    v
+}
+
+/// Create circuit of `k` with trace and run mock test.
+pub fn test_circuit_mock<F: FieldExt>(
+    k: u32,
+    execution_result: ExecutionResult<wasmi::RuntimeValue>,
+) -> Result<()> {
+    let instance = instances_of(
+        &execution_result.tables,
+        &execution_result.public_inputs_and_outputs,
+    );
 
     execution_result.tables.write_json(None);
-    let memory_writing_table: MemoryWritingTable = execution_result
-        .tables
-        .execution_tables
-        .mtable
-        .clone()
-        .into();
+    let memory_writing_table =
+        MemoryWritingTable::new(k, execution_result.tables.execution_tables.mtable.clone());
     memory_writing_table.write_json(None);
 
     execution_result.tables.profile_tables();
 
-    let circuit = TestCircuit::new(execution_result.tables);
-    let prover = MockProver::run(zkwasm_k(), &circuit, vec![instance])?;
+    let circuit = TestCircuit::new(k, execution_result.tables);
+    let prover = MockProver::run(k, &circuit, vec![instance])?;
     assert_eq!(prover.verify(), Ok(()));
 
     Ok(())
 }
 
-/// Run function and generate trace.
-fn compile_then_execute_wasm(
+/// Run function and generate trace for the circuit of `k`.
+pub fn compile_then_execute_wasm(
+    k: u32,
     mut env: HostEnv,
     wasm_runtime_io: WasmRuntimeIO,
     wasm: Vec<u8>,
     function_name: &str,
 ) -> Result<ExecutionResult<RuntimeValue>> {
-    let module = wasmi::Module::from_buffer(&wasm).expect("failed to load wasm");
+    let compiler = WasmInterpreter::new(k);
+    let module = compiler.load(&wasm).expect("failed to load wasm");
 
     let imports = ImportsBuilder::new().with_resolver("env", &env);
 
-    let compiler = WasmInterpreter::new();
     let compiled_module = compiler
         .compile(
             &module,
@@ -103,8 +122,8 @@ pub fn test_circuit_with_env(
     wasm: Vec<u8>,
     function_name: &str,
 ) -> Result<ExecutionResult<RuntimeValue>> {
-    let trace = compile_then_execute_wasm(env, wasm_runtime_io, wasm, function_name)?;
-    test_circuit_mock::<Fr>(trace.clone())?;
+    let trace = compile_then_execute_wasm(MIN_K, env, wasm_runtime_io, wasm, function_name)?;
+    test_circuit_mock::<Fr>(MIN_K, trace.clone())?;
 
     Ok(trace)
 }
@@ -112,7 +131,14 @@ pub fn test_circuit_with_env(
 /// Run test function and generate trace, then test circuit with mock prover. Only tests should
 /// use this function.
 fn test_circuit_noexternal(textual_repr: &str) -> Result<()> {
-    let wasm = wabt::wat2wasm(&textual_repr).expect("failed to parse wat");
+    let wasm = {
+        let mut features = wabt::Features::new();
+        features.enable_sign_extension();
+        features.enable_bulk_memory();
+        features.enable_multi_value();
+
+        wabt::wat2wasm_with_features(&textual_repr, features).expect("failed to parse wat")
+    };
 
     let mut env = HostEnv::new();
     env.finalize();
@@ -0,0 +1,128 @@
+mod tests {
+    use std::fs;
+
+    use crate::circuits::config::MIN_K;
+    use crate::cli::targets::read_target_manifest;
+    use crate::prover::pad;
+    use crate::prover::AggregateLayout;
+    use crate::prover::ProverError;
+    use crate::prover::ZkWasmProver;
+    use crate::runtime::entry::EntryPoint;
+    use halo2_proofs::dev::MockProver;
+
+    #[test]
+    fn test_aggregate_layout() {
+        let layout = AggregateLayout::new(1, 4).unwrap();
+        assert_eq!(layout.layers, vec![1]);
+        assert_eq!(layout.instances_size(), 18);
+
+        assert_eq!(AggregateLayout::new(4, 4).unwrap().layers, vec![1]);
+        assert_eq!(AggregateLayout::new(5, 4).unwrap().layers, vec![2, 1]);
+        assert_eq!(
+            AggregateLayout::new(1000, 4).unwrap().layers,
+            vec![250, 63, 16, 4, 1]
+        );
+        assert_eq!(AggregateLayout::new(1, 1).unwrap().layers, vec![1]);
+
+        assert!(matches!(
+            AggregateLayout::new(0, 4),
+            Err(ProverError::Aggregation(_))
+        ));
+        assert!(matches!(
+            AggregateLayout::new(4, 0),
+            Err(ProverError::Aggregation(_))
+        ));
+        assert!(matches!(
+            AggregateLayout::new(3, 1),
+            Err(ProverError::Aggregation(_))
+        ));
+    }
+
+    #[test]
+    fn test_pad() {
+        assert_eq!(pad(vec![1, 2, 3], 5, 0), vec![1, 2, 3, 0, 0]);
+        assert_eq!(pad(vec![1, 2, 3], 3, 0), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn test_dummy_witness() {
+        let textual_repr = r#"
+        (module
+            (import "env" "wasm_input" (func $wasm_input (param i32) (result i64)))
+            (func (export "test")
+              (drop (call $wasm_input (i32.const 1))))
+        )
+        "#;
+
+        let wasm = wabt::wat2wasm(textual_repr).expect("failed to parse wat");
+        let prover = ZkWasmProver::new(MIN_K, wasm, EntryPoint::new("test", false)).unwrap();
+
+        // Any execution of the image reads a public input, so its instances are not the
+        // ones of the empty execution.
+        let dummy = prover.dummy_witness();
+        assert_eq!(dummy.instances, prover.dummy_instances());
+        assert_ne!(
+            dummy.instances,
+            prover.generate_witness(vec![1], vec![]).unwrap().instances
+        );
+
+        let mock = MockProver::run(
+            MIN_K,
+            &dummy.circuit,
+            vec![prover.circuit_instances(&dummy.instances)],
+        )
+        .unwrap();
+        assert_eq!(mock.verify(), Ok(()));
+    }
+
+    #[test]
+    fn test_target_manifest() {
+        let dir = std::env::temp_dir().join(format!("zkwasm-targets-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+
+        fs::write(
+            dir.join("targets.toml"),
+            r#"
+            [[targets]]
+            wasm = "matcher.wasm"
+            function = "zkmain"
+            output = "matcher"
+
+            [[targets]]
+            wasm = "settlement.wasm"
+            function = "settle"
+            expose_result = true
+            output = "settlement"
+            proof = "proofs/settlement.data"
+            "#,
+        )
+        .unwrap();
+
+        let targets = read_target_manifest(&dir.join("targets.toml"), "zkwasm").unwrap();
+        assert_eq!(targets.len(), 2);
+
+        assert_eq!(targets[0].wasm, dir.join("matcher.wasm"));
+        assert!(!targets[0].expose_result);
+        assert_eq!(
+            targets[0].vkey,
+            dir.join("matcher").join("zkwasm.0.vkey.data")
+        );
+        assert_eq!(
+            targets[0].proof,
+            dir.join("matcher").join("zkwasm.0.transcript.data")
+        );
+
+        assert_eq!(targets[1].function, "settle");
+        assert!(targets[1].expose_result);
+        assert_eq!(targets[1].proof, dir.join("proofs/settlement.data"));
+        assert_eq!(
+            targets[1].instance,
+            dir.join("settlement").join("zkwasm.0.instance.data")
+        );
+
+        fs::write(dir.join("empty.json"), r#"{ "targets": [] }"#).unwrap();
+        assert!(read_target_manifest(&dir.join("empty.json"), "zkwasm").is_err());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
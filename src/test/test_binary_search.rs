@@ -1,3 +1,4 @@
+use crate::circuits::config::MIN_K;
 use crate::foreign::wasm_input_helper::runtime::register_wasm_input_foreign;
 use crate::runtime::host::host_env::HostEnv;
 use crate::runtime::ExecutionResult;
@@ -16,7 +17,7 @@ fn build_test() -> Result<ExecutionResult<wasmi::RuntimeValue>> {
     let wasm_runtime_io = register_wasm_input_foreign(&mut env, public_inputs, vec![]);
     env.finalize();
 
-    compile_then_execute_wasm(env, wasm_runtime_io, wasm, "bsearch")
+    compile_then_execute_wasm(MIN_K, env, wasm_runtime_io, wasm, "bsearch")
 }
 
 mod tests {
@@ -29,7 +30,7 @@ mod tests {
     fn test_binary_search_mock() {
         let trace = build_test().unwrap();
 
-        test_circuit_mock::<Fp>(trace).unwrap();
+        test_circuit_mock::<Fp>(MIN_K, trace).unwrap();
     }
 
     #[test]
@@ -37,6 +38,7 @@ mod tests {
         let execution_result = build_test().unwrap();
 
         let builder = ZkWasmCircuitBuilder {
+            k: MIN_K,
             tables: execution_result.tables,
             public_inputs_and_outputs: execution_result.public_inputs_and_outputs,
         };
@@ -0,0 +1,235 @@
+mod tests {
+    use std::sync::Arc;
+    use std::sync::Mutex;
+
+    use crate::circuits::config::MIN_K;
+    use crate::foreign::kv_helper::runtime::register_kv_foreign;
+    use crate::foreign::kv_helper::store::MemoryKvStore;
+    use crate::foreign::require_helper::register_require_foreign;
+    use crate::runtime::budget::estimate_k;
+    use crate::runtime::budget::CapacityExceeded;
+    use crate::runtime::budget::CircuitTable;
+    use crate::runtime::budget::ExecutionBudget;
+    use crate::runtime::host::host_env::HostEnv;
+    use crate::runtime::wasmi_interpreter::Execution;
+    use crate::runtime::wasmi_interpreter::WasmRuntimeIO;
+    use crate::runtime::ExecutionResult;
+    use crate::runtime::WasmInterpreter;
+    use wasmi::ImportsBuilder;
+    use wasmi::RuntimeValue;
+
+    const TEXTUAL_REPR: &str = r#"
+        (module
+            (func (export "test") (result i32)
+              (local i32)
+              i32.const 100
+              local.set 0
+              loop
+                local.get 0
+                i32.const 1
+                i32.sub
+                local.tee 0
+                br_if 0
+              end
+              local.get 0)
+        )
+    "#;
+
+    fn run_with_budget(
+        budget: Option<ExecutionBudget>,
+    ) -> anyhow::Result<ExecutionResult<RuntimeValue>> {
+        let wasm = wabt::wat2wasm(TEXTUAL_REPR).expect("failed to parse wat");
+
+        let mut env = HostEnv::new();
+        env.finalize();
+
+        let compiler = match budget {
+            Some(budget) => WasmInterpreter::with_budget(MIN_K, budget),
+            None => WasmInterpreter::new(MIN_K),
+        };
+        let module = compiler.load(&wasm)?;
+        let imports = ImportsBuilder::new().with_resolver("env", &env);
+        let compiled_module =
+            compiler.compile(&module, &imports, &env.function_description_table(), "test")?;
+
+        compiled_module.run(&mut env, WasmRuntimeIO::empty())
+    }
+
+    #[test]
+    fn test_budget_default_fits() {
+        run_with_budget(None).unwrap();
+    }
+
+    #[test]
+    fn test_budget_steps_exceeded() {
+        let budget = ExecutionBudget {
+            steps: 100,
+            ..ExecutionBudget::for_circuit(MIN_K)
+        };
+
+        let err = run_with_budget(Some(budget)).unwrap_err();
+        let err = err.downcast_ref::<CapacityExceeded>().unwrap();
+
+        assert_eq!(err.table, CircuitTable::EventTable);
+        assert_eq!(err.capacity, 100);
+        assert_eq!(err.overflow(), err.required - 100);
+        assert!(err.required > 100);
+    }
+
+    #[test]
+    fn test_budget_estimate_k() {
+        let execution_result = run_with_budget(Some(ExecutionBudget::unbounded())).unwrap();
+        let estimate = estimate_k(&execution_result.tables);
+
+        assert_eq!(estimate.k, MIN_K);
+        assert!(estimate.rows.contains(&(
+            CircuitTable::EventTable,
+            execution_result
+                .tables
+                .execution_tables
+                .etable
+                .entries()
+                .len()
+                * 4
+        )));
+    }
+
+    #[test]
+    fn test_budget_plugin_table_exceeded() {
+        let wasm = wabt::wat2wasm(
+            r#"
+            (module
+                (import "env" "kv_set" (func $kv_set (param i64 i64)))
+                (func (export "test")
+                  (call $kv_set (i64.const 1) (i64.const 10))
+                  (call $kv_set (i64.const 2) (i64.const 20)))
+            )
+            "#,
+        )
+        .expect("failed to parse wat");
+
+        let mut env = HostEnv::new();
+        let wasm_runtime_io = WasmRuntimeIO::empty();
+        register_kv_foreign(
+            &mut env,
+            Arc::new(Mutex::new(Box::new(MemoryKvStore::default()))),
+            &wasm_runtime_io,
+        );
+        env.finalize();
+
+        let compiler = WasmInterpreter::with_budget(
+            MIN_K,
+            ExecutionBudget {
+                kv_accesses: 1,
+                ..ExecutionBudget::for_circuit(MIN_K)
+            },
+        );
+        let module = compiler.load(&wasm).unwrap();
+        let imports = ImportsBuilder::new().with_resolver("env", &env);
+        let compiled_module = compiler
+            .compile(&module, &imports, &env.function_description_table(), "test")
+            .unwrap();
+
+        let err = compiled_module.run(&mut env, wasm_runtime_io).unwrap_err();
+        let err = err.downcast_ref::<CapacityExceeded>().unwrap();
+
+        assert_eq!(err.table, CircuitTable::KvTable);
+        assert_eq!(err.required, 2);
+        assert_eq!(err.capacity, 1);
+    }
+
+    #[test]
+    fn test_budget_aborts_at_host_call() {
+        let wasm = wabt::wat2wasm(
+            r#"
+            (module
+                (import "env" "require" (func $require (param i32)))
+                (func (export "test")
+                  (local i32)
+                  i32.const 10000
+                  local.set 0
+                  loop
+                    i32.const 1
+                    call $require
+                    local.get 0
+                    i32.const 1
+                    i32.sub
+                    local.tee 0
+                    br_if 0
+                  end)
+            )
+            "#,
+        )
+        .expect("failed to parse wat");
+
+        let mut env = HostEnv::new();
+        register_require_foreign(&mut env);
+        env.finalize();
+
+        let compiler = WasmInterpreter::with_budget(
+            MIN_K,
+            ExecutionBudget {
+                steps: 100,
+                ..ExecutionBudget::for_circuit(MIN_K)
+            },
+        );
+        let module = compiler.load(&wasm).unwrap();
+        let imports = ImportsBuilder::new().with_resolver("env", &env);
+        let compiled_module = compiler
+            .compile(&module, &imports, &env.function_description_table(), "test")
+            .unwrap();
+        let tracer = compiled_module.tracer.clone();
+
+        let err = compiled_module
+            .run(&mut env, WasmRuntimeIO::empty())
+            .unwrap_err();
+        let err = err.downcast_ref::<CapacityExceeded>().unwrap();
+        assert_eq!(err.table, CircuitTable::EventTable);
+
+        // The execution is aborted within an iteration of the loop beyond the budget.
+        assert!(tracer.borrow().etable.entries().len() < 100 + 16);
+    }
+
+    #[test]
+    fn test_budget_aborts_without_host_call() {
+        // The loop never ends, nor does it call the host.
+        let wasm = wabt::wat2wasm(
+            r#"
+            (module
+                (func (export "test")
+                  loop
+                    br 0
+                  end)
+            )
+            "#,
+        )
+        .expect("failed to parse wat");
+
+        let mut env = HostEnv::new();
+        env.finalize();
+
+        let compiler = WasmInterpreter::with_budget(
+            MIN_K,
+            ExecutionBudget {
+                steps: 100,
+                ..ExecutionBudget::for_circuit(MIN_K)
+            },
+        );
+        let module = compiler.load(&wasm).unwrap();
+        let imports = ImportsBuilder::new().with_resolver("env", &env);
+        let compiled_module = compiler
+            .compile(&module, &imports, &env.function_description_table(), "test")
+            .unwrap();
+        let tracer = compiled_module.tracer.clone();
+
+        let err = compiled_module
+            .run(&mut env, WasmRuntimeIO::empty())
+            .unwrap_err();
+        let err = err.downcast_ref::<CapacityExceeded>().unwrap();
+
+        assert_eq!(err.table, CircuitTable::EventTable);
+        assert_eq!(err.required, 101);
+        assert_eq!(err.capacity, 100);
+        assert_eq!(tracer.borrow().etable.entries().len(), 100);
+    }
+}
@@ -0,0 +1,40 @@
+mod tests {
+    use crate::circuits::config::CircuitConfigure;
+    use crate::circuits::config::MIN_K;
+    use crate::circuits::test_circuit::blinding_factors;
+    use crate::circuits::TestCircuit;
+    use crate::prover::ZkWasmProver;
+    use crate::runtime::entry::EntryPoint;
+    use halo2_proofs::pairing::bn256::Fr;
+    use halo2_proofs::plonk::Circuit;
+    use halo2_proofs::plonk::ConstraintSystem;
+
+    /// The cells of the event table suffice for every op and every plugin, the circuit
+    /// configures all of them.
+    #[test]
+    fn test_configure_with_every_plugin() {
+        let mut meta = ConstraintSystem::<Fr>::default();
+        TestCircuit::<Fr>::configure(&mut meta);
+    }
+
+    /// The circuit of an image configures its ops only, without the table of any plugin.
+    #[test]
+    fn test_configure_with_opcode_selector() {
+        let wasm = wabt::wat2wasm(
+            "(module (func (export \"test\") (param i64) (result i64) local.get 0))",
+        )
+        .expect("failed to parse wat");
+        let prover = ZkWasmProver::new(MIN_K, wasm, EntryPoint::new("test", true)).unwrap();
+
+        let mut every_op = ConstraintSystem::<Fr>::default();
+        TestCircuit::<Fr>::configure_with_params(&mut every_op, CircuitConfigure::default());
+
+        let mut image_ops = ConstraintSystem::<Fr>::default();
+        TestCircuit::<Fr>::configure_with_params(&mut image_ops, prover.circuit_configure());
+
+        assert!(image_ops.num_advice_columns() < every_op.num_advice_columns());
+        assert!(image_ops.gates().len() < every_op.gates().len());
+        assert!(image_ops.blinding_factors() <= blinding_factors());
+        assert_eq!(every_op.blinding_factors(), blinding_factors());
+    }
+}
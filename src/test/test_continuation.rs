@@ -0,0 +1,96 @@
+mod tests {
+    use crate::circuits::config::MIN_K;
+    use crate::circuits::TestCircuit;
+    use crate::runtime::host::host_env::HostEnv;
+    use crate::runtime::segment::is_linked;
+    use crate::runtime::segment::split_execution;
+    use crate::runtime::wasmi_interpreter::WasmRuntimeIO;
+    use anyhow::Result;
+    use halo2_proofs::dev::MockProver;
+    use halo2_proofs::pairing::bn256::Fr;
+    use wasmi::RuntimeValue;
+
+    use crate::test::compile_then_execute_wasm;
+    use crate::test::instances_of;
+
+    /// Proves the execution as segments of `segment_size` steps, returns the number of segments.
+    fn test_segments(textual_repr: &str, segment_size: usize) -> Result<usize> {
+        let wasm = wabt::wat2wasm(textual_repr).expect("failed to parse wat");
+
+        let mut env = HostEnv::new();
+        env.finalize();
+
+        let execution_result =
+            compile_then_execute_wasm(MIN_K, env, WasmRuntimeIO::empty(), wasm, "test")?;
+        assert_eq!(execution_result.result, Some(RuntimeValue::I32(55)));
+
+        let segments = split_execution(
+            &execution_result.tables,
+            &execution_result.public_inputs_and_outputs,
+            segment_size,
+        )?;
+
+        let mut instances = vec![];
+        for (index, segment) in segments.iter().enumerate() {
+            // Only the last segment terminates the execution.
+            assert_eq!(segment.tables.is_terminated(), index == segments.len() - 1);
+
+            let instance = instances_of::<Fr>(&segment.tables, &segment.public_inputs_and_outputs);
+
+            let circuit = TestCircuit::new(MIN_K, segment.tables.clone());
+            let prover = MockProver::run(MIN_K, &circuit, vec![instance.clone()])?;
+            assert_eq!(prover.verify(), Ok(()));
+
+            instances.push(instance);
+        }
+
+        if cfg!(feature = "checksum") {
+            assert!(is_linked(&instances));
+        }
+
+        Ok(segments.len())
+    }
+
+    #[test]
+    fn test_continuation_fibonacci() {
+        let textual_repr = r#"
+        (module
+            (global $calls (mut i32) (i32.const 0))
+            (memory (;0;) 1 1)
+
+            (func $fib (param i32) (result i32)
+              global.get $calls
+              i32.const 1
+              i32.add
+              global.set $calls
+
+              local.get 0
+              i32.const 2
+              i32.lt_u
+              if (result i32)
+                local.get 0
+              else
+                local.get 0
+                i32.const 1
+                i32.sub
+                call $fib
+                local.get 0
+                i32.const 2
+                i32.sub
+                call $fib
+                i32.add
+              end)
+
+            (func (export "test") (result i32)
+              i32.const 0
+              i32.const 10
+              call $fib
+              i32.store
+              i32.const 0
+              i32.load)
+        )
+        "#;
+
+        assert!(test_segments(textual_repr, 256).unwrap() > 1);
+    }
+}
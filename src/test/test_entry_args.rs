@@ -0,0 +1,104 @@
+mod tests {
+    use crate::circuits::config::MIN_K;
+    use crate::foreign::wasm_input_helper::runtime::register_wasm_input_foreign;
+    use crate::runtime::entry::EntryPoint;
+    use crate::runtime::entry::ENTRY_WRAPPER;
+    use crate::runtime::host::host_env::HostEnv;
+    use crate::runtime::wasmi_interpreter::Execution;
+    use crate::runtime::WasmInterpreter;
+    use halo2_proofs::pairing::bn256::Fr;
+    use wasmi::ImportsBuilder;
+    use wasmi::RuntimeValue;
+
+    use crate::test::test_circuit_mock;
+
+    #[test]
+    fn test_entry_args_and_result() {
+        // The table and the call to $double are relocated once wasm_input is imported.
+        let textual_repr = r#"
+        (module
+            (table 1 funcref)
+            (elem (i32.const 0) $double)
+
+            (func $double (param i64) (result i64)
+              local.get 0
+              local.get 0
+              i64.add)
+
+            (func (export "test") (param i32 i64) (result i64)
+              local.get 0
+              i64.extend_i32_u
+              local.get 1
+              i32.const 0
+              call_indirect (param i64) (result i64)
+              i64.add)
+        )
+        "#;
+
+        let wasm = wabt::wat2wasm(textual_repr).expect("failed to parse wat");
+        let entry = EntryPoint::new("test", true);
+
+        let public_inputs = entry
+            .encode_args(&wasm, &[RuntimeValue::I32(3), RuntimeValue::I64(4)])
+            .unwrap();
+        assert_eq!(public_inputs, vec![3, 4]);
+
+        let mut env = HostEnv::new();
+        let wasm_runtime_io = register_wasm_input_foreign(&mut env, public_inputs, vec![]);
+        env.finalize();
+
+        let compiler = WasmInterpreter::new(MIN_K);
+        let module = compiler.load_entry(&wasm, &entry).unwrap();
+        let imports = ImportsBuilder::new().with_resolver("env", &env);
+        let execution_result = compiler
+            .compile(
+                &module,
+                &imports,
+                &env.function_description_table(),
+                ENTRY_WRAPPER,
+            )
+            .unwrap()
+            .run(&mut env, wasm_runtime_io)
+            .unwrap();
+
+        assert_eq!(execution_result.result, Some(RuntimeValue::I64(11)));
+        assert_eq!(execution_result.public_inputs_and_outputs, vec![3, 4, 11]);
+
+        test_circuit_mock::<Fr>(MIN_K, execution_result).unwrap();
+    }
+
+    #[test]
+    fn test_entry_args_mismatch() {
+        let textual_repr = r#"
+        (module
+            (func (export "test") (param i32))
+        )
+        "#;
+
+        let wasm = wabt::wat2wasm(textual_repr).expect("failed to parse wat");
+        let entry = EntryPoint::new("test", false);
+
+        assert!(entry.encode_args(&wasm, &[]).is_err());
+        assert!(entry.encode_args(&wasm, &[RuntimeValue::I64(1)]).is_err());
+    }
+
+    #[test]
+    fn test_entry_host_import_mismatch() {
+        // The wrapper reads the parameter with wasm_input of (param i32) (result i64).
+        let textual_repr = r#"
+        (module
+            (import "env" "wasm_input" (func $wasm_input (param i64) (result i64)))
+
+            (func (export "test") (param i64) (result i64)
+              local.get 0
+              call $wasm_input)
+        )
+        "#;
+
+        let wasm = wabt::wat2wasm(textual_repr).expect("failed to parse wat");
+        let entry = EntryPoint::new("test", false);
+
+        let compiler = WasmInterpreter::new(MIN_K);
+        assert!(compiler.load_entry(&wasm, &entry).is_err());
+    }
+}
@@ -1,3 +1,4 @@
+use crate::circuits::config::MIN_K;
 use crate::runtime::host::host_env::HostEnv;
 use crate::runtime::wasmi_interpreter::WasmRuntimeIO;
 use crate::runtime::ExecutionResult;
@@ -65,7 +66,7 @@ fn build_test() -> Result<(ExecutionResult<RuntimeValue>, i32)> {
     let mut env = HostEnv::new();
     env.finalize();
 
-    let trace = compile_then_execute_wasm(env, WasmRuntimeIO::empty(), wasm, "zkmain")?;
+    let trace = compile_then_execute_wasm(MIN_K, env, WasmRuntimeIO::empty(), wasm, "zkmain")?;
 
     Ok((trace, 55))
 }
@@ -82,7 +83,7 @@ mod tests {
 
         assert_eq!(trace.result.unwrap(), RuntimeValue::I32(expected_value));
 
-        test_circuit_mock::<Fp>(trace).unwrap();
+        test_circuit_mock::<Fp>(MIN_K, trace).unwrap();
     }
 
     #[test]
@@ -95,6 +96,7 @@ mod tests {
         );
 
         let builder = ZkWasmCircuitBuilder {
+            k: MIN_K,
             tables: execution_result.tables,
             public_inputs_and_outputs: execution_result.public_inputs_and_outputs,
         };
@@ -0,0 +1,71 @@
+mod tests {
+    use std::fs;
+
+    use crate::cli::inputs::read_input_manifest;
+    use crate::cli::inputs::select_input_set;
+
+    #[test]
+    fn test_input_manifest() {
+        let dir = std::env::temp_dir().join(format!("zkwasm-inputs-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+
+        fs::write(dir.join("witness.bin"), (1u8..=10).collect::<Vec<_>>()).unwrap();
+        fs::write(
+            dir.join("inputs.toml"),
+            r#"
+            [[sets]]
+            name = "first"
+            public = [
+                { type = "i32", value = -1 },
+                { type = "u8", value = 255 },
+                { type = "u64", value = "0xffffffffffffffff" },
+            ]
+            private = [{ type = "file", value = "witness.bin" }]
+
+            [[sets]]
+            name = "second"
+            public = [{ type = "utf8", value = "zkwasm" }, { type = "bytes", value = "0x0102" }]
+            "#,
+        )
+        .unwrap();
+
+        let sets = read_input_manifest(&dir.join("inputs.toml")).unwrap();
+        assert_eq!(sets.len(), 2);
+
+        assert_eq!(sets[0].public_inputs, vec![0xffffffff, 255, u64::MAX]);
+        assert_eq!(sets[0].private_inputs, vec![0x0807060504030201, 0x0a09]);
+
+        let second = select_input_set(sets, Some(&"second".to_owned())).unwrap();
+        assert_eq!(
+            second.public_inputs,
+            vec![u64::from_le_bytes(*b"zkwasm\0\0"), 1, 2]
+        );
+
+        fs::write(
+            dir.join("inputs.json"),
+            r#"{ "public": [{ "type": "u8", "value": 256 }] }"#,
+        )
+        .unwrap();
+        assert!(read_input_manifest(&dir.join("inputs.json")).is_err());
+
+        fs::write(
+            dir.join("inputs.json"),
+            r#"{ "public": [{ "type": "i64", "value": -9223372036854775808 }, { "type": "i64", "value": 9223372036854775807 }] }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            read_input_manifest(&dir.join("inputs.json")).unwrap()[0].public_inputs,
+            vec![i64::MIN as u64, i64::MAX as u64]
+        );
+
+        // i64::MAX + 1
+        fs::write(
+            dir.join("inputs.json"),
+            r#"{ "public": [{ "type": "i64", "value": 9223372036854775808 }] }"#,
+        )
+        .unwrap();
+        assert!(read_input_manifest(&dir.join("inputs.json")).is_err());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,55 @@
+mod tests {
+    use crate::circuits::config::MIN_K;
+    use crate::prover::downsize_params;
+    use crate::prover::import_params;
+    use crate::prover::ProverError;
+    use crate::prover::Transcript;
+    use crate::prover::ZkWasmProver;
+    use crate::runtime::entry::EntryPoint;
+    use halo2_proofs::pairing::bn256::Bn256;
+    use halo2_proofs::pairing::bn256::G1Affine;
+    use halo2_proofs::poly::commitment::Params;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_downsized_params() {
+        let wasm = wabt::wat2wasm(
+            r#"
+            (module
+                (func (export "test") (param i64) (result i64)
+                  local.get 0
+                  i64.const 1
+                  i64.add)
+            )
+            "#,
+        )
+        .expect("failed to parse wat");
+        let prover = ZkWasmProver::new(MIN_K, wasm, EntryPoint::new("test", true)).unwrap();
+
+        // Params of a larger K stand for the params of a ceremony.
+        let ceremony = Params::<G1Affine>::unsafe_setup::<Bn256>(MIN_K + 1);
+        let mut buf = vec![];
+        ceremony.write(&mut buf).unwrap();
+
+        let params = import_params(Cursor::new(&buf), MIN_K).unwrap();
+        assert_eq!(params.k, MIN_K);
+        assert_eq!(params.g[..], ceremony.g[..1 << MIN_K]);
+
+        assert!(matches!(
+            downsize_params(&params, MIN_K + 1),
+            Err(ProverError::Params(_))
+        ));
+
+        // The recomputed Lagrange basis must commit to the same polynomials as the
+        // monomial one for the proof to verify.
+        let pk = prover.setup(&params).unwrap();
+        let witness = prover.generate_witness(vec![41], vec![]).unwrap();
+        let proof = prover
+            .prove(&params, &pk, &witness, Transcript::Poseidon)
+            .unwrap();
+
+        prover
+            .verify(&params, pk.get_vk(), &witness.instances, &proof)
+            .unwrap();
+    }
+}
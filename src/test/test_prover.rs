@@ -0,0 +1,405 @@
+mod tests {
+    use crate::circuits::config::MIN_K;
+    use crate::prover::catch_panic;
+    use crate::prover::Proof;
+    use crate::prover::ProofBundle;
+    use crate::prover::ProverError;
+    use crate::prover::Transcript;
+    use crate::prover::ZkWasmProver;
+    use crate::runtime::entry::EntryPoint;
+    use halo2_proofs::dev::MockProver;
+    use halo2_proofs::pairing::bn256::Fr;
+    use halo2_proofs::pairing::group::ff::PrimeField;
+    use std::sync::Arc;
+    use std::thread;
+    use wasmi::RuntimeValue;
+
+    fn build_prover() -> ZkWasmProver {
+        let textual_repr = r#"
+        (module
+            (func (export "test") (param i64 i64) (result i64)
+              local.get 0
+              local.get 1
+              i64.mul)
+        )
+        "#;
+
+        let wasm = wabt::wat2wasm(textual_repr).expect("failed to parse wat");
+
+        ZkWasmProver::new(MIN_K, wasm, EntryPoint::new("test", true)).unwrap()
+    }
+
+    #[test]
+    fn test_prover_round_trip() {
+        let prover = build_prover();
+
+        let witness = prover.generate_witness(vec![6, 7], vec![]).unwrap();
+        assert_eq!(witness.result, Some(RuntimeValue::I64(42)));
+        assert_eq!(witness.outputs, vec![42]);
+
+        let params = prover.unsafe_params();
+        let pk = {
+            let mut buf = vec![];
+            prover
+                .write_proving_key(&prover.setup(&params).unwrap(), &mut buf)
+                .unwrap();
+
+            // The key is only read back for the same image.
+            let other = ZkWasmProver::new(
+                MIN_K,
+                wabt::wat2wasm("(module (func (export \"test\")))").unwrap(),
+                EntryPoint::new("test", false),
+            )
+            .unwrap();
+            assert!(matches!(
+                other.read_proving_key(&params, &buf[..]),
+                Err(ProverError::KeyMismatch(_))
+            ));
+
+            prover.read_proving_key(&params, &buf[..]).unwrap()
+        };
+        let proof = prover
+            .prove(&params, &pk, &witness, Transcript::Poseidon)
+            .unwrap();
+
+        prover
+            .verify(&params, pk.get_vk(), &witness.instances, &proof)
+            .unwrap();
+
+        let mut forged = witness.instances.clone();
+        *forged.last_mut().unwrap() = Fr::from(43);
+        assert!(matches!(
+            prover.verify(&params, pk.get_vk(), &forged, &proof),
+            Err(ProverError::Verification(_))
+        ));
+    }
+
+    #[test]
+    fn test_prover_transcripts() {
+        let prover = build_prover();
+
+        let witness = prover.generate_witness(vec![6, 7], vec![]).unwrap();
+        let params = prover.unsafe_params();
+        let pk = prover.setup(&params).unwrap();
+
+        for transcript in [
+            Transcript::Blake2b,
+            Transcript::Poseidon,
+            Transcript::Sha256,
+        ] {
+            let mut buf = vec![];
+            prover
+                .prove(&params, &pk, &witness, transcript)
+                .unwrap()
+                .write(&mut buf)
+                .unwrap();
+
+            // The transcript is picked from the persisted proof.
+            let proof = Proof::read(&buf[..]).unwrap();
+            assert_eq!(proof.transcript, transcript);
+            prover
+                .verify(&params, pk.get_vk(), &witness.instances, &proof)
+                .unwrap();
+
+            let other = Proof {
+                transcript: if transcript == Transcript::Poseidon {
+                    Transcript::Sha256
+                } else {
+                    Transcript::Poseidon
+                },
+                bytes: proof.bytes.clone(),
+            };
+            assert!(matches!(
+                prover.verify(&params, pk.get_vk(), &witness.instances, &other),
+                Err(ProverError::Verification(_))
+            ));
+        }
+
+        assert!(matches!(
+            Proof::read(&b"ZKPF\x01\x00\x00\x00\x09"[..]),
+            Err(ProverError::MalformedProof(_))
+        ));
+    }
+
+    #[test]
+    fn test_proof_bundle() {
+        let prover = build_prover();
+
+        let witness = prover.generate_witness(vec![6, 7], vec![]).unwrap();
+        let params = prover.unsafe_params();
+        let pk = prover.setup(&params).unwrap();
+        let proof = prover
+            .prove(&params, &pk, &witness, Transcript::Poseidon)
+            .unwrap();
+
+        let mut buf = vec![];
+        prover
+            .bundle(pk.get_vk(), &witness, &proof)
+            .unwrap()
+            .write(&mut buf)
+            .unwrap();
+
+        // The bundle is verified without the image.
+        drop(prover);
+        let bundle = ProofBundle::read(&buf[..]).unwrap();
+        assert_eq!(bundle.k, MIN_K);
+        assert_eq!(bundle.entry, "test");
+
+        let vk = {
+            let mut vk_buf = vec![];
+            pk.get_vk().write(&mut vk_buf).unwrap();
+            bundle.read_vkey(&params, &vk_buf[..]).unwrap()
+        };
+        assert_eq!(bundle.public_inputs_and_outputs, vec![6, 7, 42]);
+        bundle.verify(&params, &vk).unwrap();
+
+        // The KV root after the execution, which precedes the termination flag and the
+        // public inputs and outputs.
+        let mut forged = bundle.clone();
+        let kv_root = forged.instances.len() - bundle.public_inputs_and_outputs.len() - 2;
+        forged.instances[kv_root] = hex::encode(Fr::from(43).to_repr());
+        assert!(matches!(
+            forged.verify(&params, &vk),
+            Err(ProverError::Verification(_))
+        ));
+
+        // The proof of a prefix of the execution is not the proof of the bundle.
+        let mut prefix = bundle.clone();
+        prefix.instances[kv_root + 1] = hex::encode(Fr::from(0).to_repr());
+        assert!(matches!(
+            prefix.verify(&params, &vk),
+            Err(ProverError::MalformedProof(_))
+        ));
+
+        let mut forged_output = bundle.clone();
+        *forged_output.instances.last_mut().unwrap() = hex::encode(Fr::from(43).to_repr());
+        assert!(matches!(
+            forged_output.verify(&params, &vk),
+            Err(ProverError::MalformedProof(_))
+        ));
+
+        let mut other_io = bundle.clone();
+        other_io.public_inputs_and_outputs = vec![6, 7, 43];
+        assert!(matches!(
+            other_io.verify(&params, &vk),
+            Err(ProverError::MalformedProof(_))
+        ));
+
+        #[cfg(feature = "checksum")]
+        {
+            let mut other_image = bundle.clone();
+            other_image.image_hash = Some(hex::encode(Fr::from(43).to_repr()));
+            assert!(matches!(
+                other_image.verify(&params, &vk),
+                Err(ProverError::MalformedProof(_))
+            ));
+        }
+
+        let mut other_key = bundle.clone();
+        other_key.vkey_fingerprint = hex::encode([0u8; 32]);
+        assert!(matches!(
+            other_key.verify(&params, &vk),
+            Err(ProverError::KeyMismatch(_))
+        ));
+    }
+
+    #[test]
+    fn test_solidity_calldata() {
+        let prover = build_prover();
+
+        let mut bytes = vec![0u8; 64];
+        bytes[0] = 1;
+        let proof = Proof {
+            transcript: Transcript::Sha256,
+            bytes,
+        };
+
+        let calldata = prover.solidity_calldata(&[Fr::from(42)], &proof).unwrap();
+
+        // The words are big-endian.
+        assert_eq!(calldata.proof.len(), 2);
+        assert_eq!(calldata.proof[0], format!("0x{:0>64}", "1"));
+        assert_eq!(
+            calldata.instances.last().unwrap(),
+            &format!("0x{:0>64}", "2a")
+        );
+
+        assert!(matches!(
+            prover.solidity_calldata(
+                &[Fr::from(42)],
+                &Proof {
+                    transcript: Transcript::Poseidon,
+                    bytes: proof.bytes,
+                }
+            ),
+            Err(ProverError::UnsupportedTranscript(Transcript::Poseidon))
+        ));
+    }
+
+    #[test]
+    fn test_prover_errors() {
+        let wasm = wabt::wat2wasm("(module)").expect("failed to parse wat");
+        assert!(matches!(
+            ZkWasmProver::new(MIN_K - 1, wasm.clone(), EntryPoint::new("test", false)),
+            Err(ProverError::InvalidK(_))
+        ));
+        assert!(matches!(
+            ZkWasmProver::new(MIN_K, wasm, EntryPoint::new("test", false)),
+            Err(ProverError::Image(_))
+        ));
+
+        // The entry reads two public inputs but none is given, wasm_input panics.
+        let prover = build_prover();
+        assert!(matches!(
+            prover.generate_witness(vec![], vec![]),
+            Err(ProverError::Execution(_))
+        ));
+    }
+
+    #[test]
+    fn test_provers_coexist() {
+        // Two images are proved with different K at once, each circuit carries its own
+        // configuration.
+        let images = [
+            (
+                MIN_K,
+                "(module (func (export \"test\") (param i64) (result i64) local.get 0))",
+            ),
+            (
+                MIN_K + 1,
+                r#"
+                (module
+                    (memory 1 2)
+                    (func (export "test") (param i64) (result i64)
+                      i32.const 0
+                      local.get 0
+                      i64.store
+                      i32.const 0
+                      i64.load
+                      i64.popcnt))
+                "#,
+            ),
+        ];
+
+        let handles = images.map(|(k, textual_repr)| {
+            thread::spawn(move || {
+                let wasm = wabt::wat2wasm(textual_repr).expect("failed to parse wat");
+                let prover = ZkWasmProver::new(k, wasm, EntryPoint::new("test", true)).unwrap();
+
+                let witness = prover.generate_witness(vec![7], vec![]).unwrap();
+                let instances = prover.circuit_instances(&witness.instances);
+
+                let mock = MockProver::run(k, &witness.circuit, vec![instances]).unwrap();
+                assert_eq!(mock.verify(), Ok(()));
+
+                witness.result
+            })
+        });
+
+        let [identity, popcnt] = handles.map(|handle| handle.join().unwrap());
+        assert_eq!(identity, Some(RuntimeValue::I64(7)));
+        assert_eq!(popcnt, Some(RuntimeValue::I64(3)));
+    }
+
+    #[test]
+    fn test_prover_shared_across_threads() {
+        let prover = Arc::new(build_prover());
+
+        let handles = [(6, 7), (8, 9)].map(|(a, b)| {
+            let prover = prover.clone();
+            thread::spawn(move || prover.generate_witness(vec![a, b], vec![]).unwrap().result)
+        });
+
+        let results = handles.map(|handle| handle.join().unwrap());
+        assert_eq!(
+            results,
+            [Some(RuntimeValue::I64(42)), Some(RuntimeValue::I64(72))]
+        );
+    }
+
+    #[test]
+    fn test_prover_panic_as_error() {
+        let result: Result<(), ProverError> = catch_panic(|| panic!("synthesis failed"));
+        assert!(matches!(
+            result,
+            Err(ProverError::Panicked(message)) if message == "synthesis failed"
+        ));
+    }
+
+    #[cfg(feature = "checksum")]
+    #[test]
+    fn test_prover_segments() {
+        let textual_repr = r#"
+        (module
+            (func $sum (param i64) (result i64)
+              (local $acc i64)
+              (block
+                (loop
+                  (br_if 1 (i64.eqz (local.get 0)))
+                  (local.set $acc (i64.add (local.get $acc) (local.get 0)))
+                  (local.set 0 (i64.sub (local.get 0) (i64.const 1)))
+                  (br 0)))
+              local.get $acc)
+
+            (func (export "test") (param i64) (result i64)
+              local.get 0
+              call $sum)
+        )
+        "#;
+
+        let wasm = wabt::wat2wasm(textual_repr).expect("failed to parse wat");
+        let prover = ZkWasmProver::new(MIN_K, wasm, EntryPoint::new("test", true)).unwrap();
+
+        let witnesses = prover.generate_segments(vec![100], vec![], 256).unwrap();
+        assert!(witnesses.len() > 2);
+        assert_eq!(
+            witnesses.last().unwrap().result,
+            Some(RuntimeValue::I64(5050))
+        );
+        assert_eq!(witnesses.last().unwrap().outputs, vec![5050]);
+
+        let params = prover.unsafe_params();
+        let pk = prover.setup(&params).unwrap();
+        let proofs = prover
+            .prove_segments(&params, &pk, &witnesses, Transcript::Poseidon)
+            .unwrap();
+
+        let segments = witnesses
+            .iter()
+            .map(|witness| witness.instances.clone())
+            .zip(proofs)
+            .collect::<Vec<_>>();
+        prover
+            .verify_segments(&params, pk.get_vk(), &segments)
+            .unwrap();
+
+        // Only the last segment terminates the execution.
+        let terminated = witnesses
+            .iter()
+            .map(|witness| witness.instances[4])
+            .collect::<Vec<_>>();
+        let mut expected = vec![Fr::from(0); witnesses.len() - 1];
+        expected.push(Fr::from(1));
+        assert_eq!(terminated, expected);
+
+        // Each proof is valid on its own, but a prefix of the execution, or a segment out
+        // of order or missing in between is not linked.
+        let mut swapped = segments.clone();
+        swapped.swap(1, 2);
+        let mut skipped = segments.clone();
+        skipped.remove(1);
+
+        for unlinked in [
+            &segments[..2],
+            &segments[1..],
+            &swapped[..],
+            &skipped[..],
+            &segments[..0],
+        ] {
+            assert!(matches!(
+                prover.verify_segments(&params, pk.get_vk(), unlinked),
+                Err(ProverError::UnlinkedSegments(_))
+            ));
+        }
+    }
+}
@@ -0,0 +1,73 @@
+mod tests {
+    use crate::circuits::config::MIN_K;
+    use crate::runtime::host::host_env::HostEnv;
+    use crate::runtime::wasmi_interpreter::WasmRuntimeIO;
+    use crate::test::compile_then_execute_wasm;
+    use crate::test::test_circuit_mock;
+    use halo2_proofs::pairing::bn256::Fr;
+    use specs::Tables;
+    use specs::TRACE_FORMAT_VERSION;
+
+    fn execute() -> crate::runtime::ExecutionResult<wasmi::RuntimeValue> {
+        let textual_repr = r#"
+        (module
+            (memory (;0;) 1 1)
+
+            (func (export "test") (result i32)
+              (local i32)
+              i32.const 10
+              local.set 0
+              loop
+                i32.const 0
+                i32.const 0
+                i32.load
+                local.get 0
+                i32.add
+                i32.store
+                local.get 0
+                i32.const 1
+                i32.sub
+                local.tee 0
+                br_if 0
+              end
+              i32.const 0
+              i32.load)
+        )
+        "#;
+
+        let wasm = wabt::wat2wasm(textual_repr).expect("failed to parse wat");
+
+        let mut env = HostEnv::new();
+        env.finalize();
+
+        compile_then_execute_wasm(MIN_K, env, WasmRuntimeIO::empty(), wasm, "test").unwrap()
+    }
+
+    #[test]
+    fn test_trace_roundtrip() {
+        let mut execution_result = execute();
+
+        let mut trace = vec![];
+        execution_result.tables.write(&mut trace).unwrap();
+
+        let tables = Tables::read(&trace[..]).unwrap();
+        assert_eq!(
+            serde_json::to_string(&tables).unwrap(),
+            serde_json::to_string(&execution_result.tables).unwrap()
+        );
+
+        // The loaded trace is proved without executing the image again.
+        execution_result.tables = tables;
+        test_circuit_mock::<Fr>(MIN_K, execution_result).unwrap();
+    }
+
+    #[test]
+    fn test_trace_version_mismatch() {
+        let mut trace = vec![];
+        execute().tables.write(&mut trace).unwrap();
+
+        trace[4..8].copy_from_slice(&(TRACE_FORMAT_VERSION + 1).to_le_bytes());
+
+        assert!(Tables::read(&trace[..]).is_err());
+    }
+}
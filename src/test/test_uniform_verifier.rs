@@ -32,6 +32,7 @@ fn setup_uniform_verifier() -> Result<(Params<G1Affine>, ProvingKey<G1Affine>)>
     let execution_result = test_circuit_with_env(env, WasmRuntimeIO::empty(), wasm, "zkmain")?;
 
     let builder = ZkWasmCircuitBuilder {
+        k: K,
         tables: execution_result.tables,
         public_inputs_and_outputs: execution_result.public_inputs_and_outputs,
     };
@@ -119,6 +120,7 @@ mod tests {
 
     use super::*;
     use crate::circuits::ZkWasmCircuitBuilder;
+    use crate::foreign::kv_helper::kv_instances;
     use crate::image_hasher::ImageHasher;
 
     #[test]
@@ -133,9 +135,13 @@ mod tests {
         );
 
         let image_hash: Fr = execution_result.tables.compilation_tables.hash();
-        let instances = vec![image_hash];
+        let post_image_hash: Fr = execution_result.tables.post_image_table.hash();
+        let mut instances = vec![image_hash, post_image_hash];
+        instances.append(&mut kv_instances(&execution_result.tables));
+        instances.push(Fr::from(execution_result.tables.is_terminated() as u64));
 
         let builder = ZkWasmCircuitBuilder {
+            k: K,
             tables: execution_result.tables,
             public_inputs_and_outputs: execution_result.public_inputs_and_outputs,
         };
@@ -143,10 +149,12 @@ mod tests {
         let proof = {
             let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
 
+            let circuit = builder.build_circuit();
+
             create_proof(
                 &params,
                 &uniform_verifier_pk,
-                &[builder.build_circuit()],
+                &[circuit.clone()],
                 &[&[&instances]],
                 OsRng,
                 &mut transcript,
@@ -157,7 +165,7 @@ mod tests {
         };
 
         {
-            let public_inputs_size = 1;
+            let public_inputs_size = instances.len();
 
             let params_verifier: ParamsVerifier<Bn256> =
                 params.verifier(public_inputs_size).unwrap();
@@ -10,12 +10,15 @@ mod op_call_host;
 mod op_call_indirect;
 mod op_const;
 mod op_conversion;
+mod op_float;
 mod op_global_get;
 mod op_global_set;
 mod op_load;
 mod op_local_get;
 mod op_local_set;
 mod op_local_tee;
+mod op_memory_copy;
+mod op_memory_fill;
 mod op_memory_grow;
 mod op_memory_size;
 mod op_rel;
@@ -24,3 +27,4 @@ mod op_select;
 mod op_store;
 mod op_test;
 mod op_unary;
+mod op_unreachable;
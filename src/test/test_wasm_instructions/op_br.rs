@@ -30,3 +30,26 @@ fn test_br_drop_ok() {
 
     test_circuit_noexternal(textual_repr).unwrap();
 }
+
+#[test]
+fn test_br_multi_value_ok() {
+    let textual_repr = r#"
+        (module
+            (func (export "test")
+              (block (result i32 i64 i32)
+                (i32.const 0)
+                (i64.const 1)
+                (i32.const 2)
+                (i64.const 3)
+                (i32.const 4)
+                br 0
+              )
+              drop
+              drop
+              drop
+            )
+           )
+        "#;
+
+    test_circuit_noexternal(textual_repr).unwrap();
+}
@@ -17,3 +17,23 @@ fn test_br_if_eqz_ok() {
 
     test_circuit_noexternal(textual_repr).unwrap();
 }
+
+#[test]
+fn test_br_if_eqz_multi_value_ok() {
+    let textual_repr = r#"
+        (module
+            (func (export "test")
+              (i64.const 1)
+              (i32.const 2)
+              (if (param i64 i32) (result i32 i64) (i32.const 0)
+                (then drop drop (i32.const 3) (i64.const 4))
+                (else drop drop (i32.const 5) (i64.const 6))
+              )
+              drop
+              drop
+            )
+           )
+        "#;
+
+    test_circuit_noexternal(textual_repr).unwrap();
+}
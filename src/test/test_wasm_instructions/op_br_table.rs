@@ -115,3 +115,29 @@ fn test_br_table_oob_2() {
 
     test_circuit_noexternal(textual_repr).unwrap();
 }
+
+#[test]
+fn test_br_table_multi_value_ok() {
+    let textual_repr = r#"
+        (module
+            (func (export "test") (result i64)
+              (block (result i32 i64)
+                (block (result i32 i64)
+                  (i32.const 0)
+                  (i32.const 1)
+                  (i64.const 2)
+                  (br_table 0 1 (i32.const 1))
+                )
+                drop
+                drop
+                (i32.const 3)
+                (i64.const 4)
+              )
+              drop
+              (i64.extend_i32_u)
+            )
+           )
+        "#;
+
+    test_circuit_noexternal(textual_repr).unwrap();
+}
@@ -65,3 +65,138 @@ fn test_i64_extend_i32_s_ok() {
 
     test_circuit_noexternal(textual_repr).unwrap()
 }
+
+#[test]
+fn test_i32_extend8_s_ok() {
+    let textual_repr = r#"
+        (module
+            (func (export "test")
+                (i32.const 0)
+                (i32.extend8_s)
+                (drop)
+
+                (i32.const 0x7f)
+                (i32.extend8_s)
+                (drop)
+
+                (i32.const 0x80)
+                (i32.extend8_s)
+                (drop)
+
+                (i32.const 0xffffff01)
+                (i32.extend8_s)
+                (drop)
+            )
+        )
+        "#;
+
+    test_circuit_noexternal(textual_repr).unwrap()
+}
+
+#[test]
+fn test_i32_extend16_s_ok() {
+    let textual_repr = r#"
+        (module
+            (func (export "test")
+                (i32.const 0)
+                (i32.extend16_s)
+                (drop)
+
+                (i32.const 0x7fff)
+                (i32.extend16_s)
+                (drop)
+
+                (i32.const 0x8000)
+                (i32.extend16_s)
+                (drop)
+
+                (i32.const 0x12348765)
+                (i32.extend16_s)
+                (drop)
+            )
+        )
+        "#;
+
+    test_circuit_noexternal(textual_repr).unwrap()
+}
+
+#[test]
+fn test_i64_extend8_s_ok() {
+    let textual_repr = r#"
+        (module
+            (func (export "test")
+                (i64.const 0)
+                (i64.extend8_s)
+                (drop)
+
+                (i64.const 0x7f)
+                (i64.extend8_s)
+                (drop)
+
+                (i64.const 0x80)
+                (i64.extend8_s)
+                (drop)
+
+                (i64.const 0xffffffffffffff01)
+                (i64.extend8_s)
+                (drop)
+            )
+        )
+        "#;
+
+    test_circuit_noexternal(textual_repr).unwrap()
+}
+
+#[test]
+fn test_i64_extend16_s_ok() {
+    let textual_repr = r#"
+        (module
+            (func (export "test")
+                (i64.const 0)
+                (i64.extend16_s)
+                (drop)
+
+                (i64.const 0x7fff)
+                (i64.extend16_s)
+                (drop)
+
+                (i64.const 0x8000)
+                (i64.extend16_s)
+                (drop)
+
+                (i64.const 0x1234567812348765)
+                (i64.extend16_s)
+                (drop)
+            )
+        )
+        "#;
+
+    test_circuit_noexternal(textual_repr).unwrap()
+}
+
+#[test]
+fn test_i64_extend32_s_ok() {
+    let textual_repr = r#"
+        (module
+            (func (export "test")
+                (i64.const 0)
+                (i64.extend32_s)
+                (drop)
+
+                (i64.const 0x7fffffff)
+                (i64.extend32_s)
+                (drop)
+
+                (i64.const 0x80000000)
+                (i64.extend32_s)
+                (drop)
+
+                (i64.const 0x1234567887654321)
+                (i64.extend32_s)
+                (drop)
+            )
+        )
+        "#;
+
+    test_circuit_noexternal(textual_repr).unwrap()
+}
@@ -0,0 +1,407 @@
+use specs::step::TrapCode;
+use wasmi::RuntimeValue;
+
+use crate::foreign::wasm_input_helper::runtime::register_wasm_input_foreign;
+use crate::runtime::host::host_env::HostEnv;
+use crate::runtime::ExecutionResult;
+use crate::test::test_circuit_with_env;
+
+/// Proves `body`, which outputs values with `$wasm_output`, with the mock prover.
+fn run(body: &str) -> ExecutionResult<RuntimeValue> {
+    let textual_repr = format!(
+        r#"
+        (module
+            (import "env" "wasm_output" (func $wasm_output (param i64)))
+            (memory 1)
+            (global $g (mut f32) (f32.const 0.5))
+            (func (export "test")
+              {}
+            )
+        )
+    "#,
+        body
+    );
+
+    let wasm = {
+        let mut features = wabt::Features::new();
+        features.enable_sign_extension();
+        features.enable_bulk_memory();
+        features.enable_multi_value();
+
+        wabt::wat2wasm_with_features(&textual_repr, features).expect("failed to parse wat")
+    };
+
+    let mut env = HostEnv::new();
+    let wasm_runtime_io = register_wasm_input_foreign(&mut env, vec![], vec![]);
+    env.finalize();
+
+    test_circuit_with_env(env, wasm_runtime_io, wasm, "test").unwrap()
+}
+
+/// Proves the expressions of `cases` and checks each one outputs the bits paired with it.
+fn check(cases: &[(String, u64)]) {
+    let body = cases
+        .iter()
+        .map(|(expr, _)| format!("(call $wasm_output {})", expr))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let execution_result = run(&body);
+
+    assert_eq!(execution_result.trap, None);
+    assert_eq!(execution_result.outputs.len(), cases.len());
+
+    for ((expr, expected), output) in cases.iter().zip(execution_result.outputs) {
+        assert_eq!(
+            output, *expected,
+            "{} outputs {:#x} instead of {:#x}",
+            expr, output, expected
+        );
+    }
+}
+
+fn f64_bits(bits: u64) -> String {
+    format!("(f64.reinterpret_i64 (i64.const {}))", bits as i64)
+}
+
+fn f64_const(v: f64) -> String {
+    f64_bits(v.to_bits())
+}
+
+fn f32_bits(bits: u32) -> String {
+    format!("(f32.reinterpret_i32 (i32.const {}))", bits as i32)
+}
+
+fn f32_const(v: f32) -> String {
+    f32_bits(v.to_bits())
+}
+
+/// The case of an f64 expression expected to be `bits`.
+fn f64_case(expr: String, bits: u64) -> (String, u64) {
+    (format!("(i64.reinterpret_f64 {})", expr), bits)
+}
+
+/// The case of an f32 expression expected to be `bits`.
+fn f32_case(expr: String, bits: u32) -> (String, u64) {
+    (
+        format!("(i64.extend_i32_u (i32.reinterpret_f32 {}))", expr),
+        bits as u64,
+    )
+}
+
+fn i32_case(expr: String, value: i32) -> (String, u64) {
+    (format!("(i64.extend_i32_u {})", expr), value as u32 as u64)
+}
+
+fn i64_case(expr: String, value: i64) -> (String, u64) {
+    (expr, value as u64)
+}
+
+fn f64_binary(op: &str, a: f64, b: f64) -> String {
+    format!("(f64.{} {} {})", op, f64_const(a), f64_const(b))
+}
+
+fn f32_binary(op: &str, a: f32, b: f32) -> String {
+    format!("(f32.{} {} {})", op, f32_const(a), f32_const(b))
+}
+
+#[test]
+fn test_f64_arithmetic() {
+    let value = -(((1.5f64 + -0.25) * 3.0 / 7.0).sqrt()) - 0.1;
+
+    check(&[
+        f64_case(
+            r#"
+              (f64.const 1.5)
+              (f64.const -0.25)
+              (f64.add)
+              (f64.const 3)
+              (f64.mul)
+              (f64.const 7)
+              (f64.div)
+              (f64.sqrt)
+              (f64.neg)
+              (f64.const 0.1)
+              (f64.sub)"#
+                .to_owned(),
+            value.to_bits(),
+        ),
+        f64_case(
+            format!("(f64.floor {})", f64_const(value)),
+            value.floor().to_bits(),
+        ),
+        i32_case(
+            format!("(f64.ne (f64.floor {}) (f64.const 2))", f64_const(value)),
+            (value.floor() != 2.0) as i32,
+        ),
+        f64_case(f64_binary("add", 0.1, 0.2), (0.1f64 + 0.2).to_bits()),
+        f64_case(f64_binary("sub", 1.0, 1.0), (1.0f64 - 1.0).to_bits()),
+        f64_case(f64_binary("mul", 1e308, 10.0), (1e308f64 * 10.0).to_bits()),
+        f64_case(
+            f64_binary("mul", 1e-308, 1e-10),
+            (1e-308f64 * 1e-10).to_bits(),
+        ),
+        f64_case(f64_binary("div", 1.0, 3.0), (1.0f64 / 3.0).to_bits()),
+        f64_case(f64_binary("div", -1.0, 0.0), (-1.0f64 / 0.0).to_bits()),
+        f64_case(
+            format!("(f64.sqrt {})", f64_const(2.0)),
+            2.0f64.sqrt().to_bits(),
+        ),
+        f64_case(
+            format!("(f64.sqrt {})", f64_const(-0.0)),
+            (-0.0f64).sqrt().to_bits(),
+        ),
+    ])
+}
+
+#[test]
+fn test_f32_arithmetic() {
+    check(&[
+        f32_case(f32_binary("add", 0.1, 0.2), (0.1f32 + 0.2).to_bits()),
+        f32_case(
+            f32_binary("add", 16777216.0, 1.0),
+            (16777216.0f32 + 1.0).to_bits(),
+        ),
+        f32_case(
+            f32_binary("mul", 1.0000001, 1.0000001),
+            (1.0000001f32 * 1.0000001).to_bits(),
+        ),
+        f32_case(f32_binary("mul", 3e38, 2.0), (3e38f32 * 2.0).to_bits()),
+        f32_case(f32_binary("div", 1.0, 3.0), (1.0f32 / 3.0).to_bits()),
+        f32_case(f32_binary("div", 1e-38, 1e3), (1e-38f32 / 1e3).to_bits()),
+        f32_case(
+            format!("(f32.sqrt {})", f32_const(2.0)),
+            2.0f32.sqrt().to_bits(),
+        ),
+    ])
+}
+
+#[test]
+fn test_f32_in_memory_and_globals() {
+    check(&[
+        f32_case(
+            r#"
+              (f32.store (i32.const 0) (f32.const -3.75))
+              (global.set $g
+                (f32.max
+                  (f32.load (i32.const 0))
+                  (f32.abs (global.get $g))))
+              (global.get $g)"#
+                .to_owned(),
+            (-3.75f32).max(0.5f32.abs()).to_bits(),
+        ),
+        i32_case("(f32.lt (global.get $g) (f32.const 1))".to_owned(), 1),
+    ])
+}
+
+/// The float params and results of a block of a type index are lowered with its type.
+#[test]
+fn test_float_multi_value_block() {
+    check(&[
+        f64_case(
+            "(f64.sub (block (result f64 f64) (f64.const 1.5) (f64.const 0.25)))".to_owned(),
+            (1.5f64 - 0.25f64).to_bits(),
+        ),
+        f32_case(
+            "(f32.const 1.5) (f32.const 4) (block (param f32 f32) (result f32) f32.mul)".to_owned(),
+            (1.5f32 * 4f32).to_bits(),
+        ),
+    ])
+}
+
+/// A NaN operand is quieted and keeps its sign and payload, the first one is propagated
+/// if both are NaN. An invalid operation produces the positive canonical NaN.
+#[test]
+fn test_nan() {
+    let snan = 0x7ff0000000000001u64;
+    let negative_nan = 0xfff8000000000abcu64;
+
+    check(&[
+        f64_case(
+            format!("(f64.add {} {})", f64_bits(snan), f64_const(1.0)),
+            0x7ff8000000000001,
+        ),
+        f64_case(
+            format!("(f64.sub {} {})", f64_const(1.0), f64_bits(negative_nan)),
+            negative_nan,
+        ),
+        f64_case(
+            format!(
+                "(f64.mul {} {})",
+                f64_bits(0x7ff800000000000a),
+                f64_bits(0xfff800000000000b)
+            ),
+            0x7ff800000000000a,
+        ),
+        f64_case(
+            format!("(f64.sqrt {})", f64_bits(negative_nan)),
+            negative_nan,
+        ),
+        f64_case(f64_binary("div", 0.0, 0.0), 0x7ff8000000000000),
+        f64_case(
+            f64_binary("sub", f64::INFINITY, f64::INFINITY),
+            0x7ff8000000000000,
+        ),
+        f64_case(
+            format!("(f64.sqrt {})", f64_const(-1.0)),
+            0x7ff8000000000000,
+        ),
+        f64_case(
+            format!("(f64.neg {})", f64_bits(0x7ff8000000000123)),
+            (-f64::from_bits(0x7ff8000000000123)).to_bits(),
+        ),
+        f64_case(
+            format!("(f64.abs {})", f64_bits(negative_nan)),
+            f64::from_bits(negative_nan).abs().to_bits(),
+        ),
+        f64_case(
+            format!("(f64.copysign {} {})", f64_bits(snan), f64_const(-1.0)),
+            f64::from_bits(snan).copysign(-1.0).to_bits(),
+        ),
+        f64_case(
+            format!("(f64.promote_f32 {})", f32_bits(0xff800001)),
+            0xfff8000020000000,
+        ),
+        f32_case(
+            format!("(f32.demote_f64 {})", f64_bits(0xfff0000000000001)),
+            0xffc00000,
+        ),
+        f32_case(
+            format!("(f32.add {} {})", f32_bits(0x7f800001), f32_const(1.0)),
+            0x7fc00001,
+        ),
+        f32_case(
+            format!("(f32.neg {})", f32_bits(0x7fc00123)),
+            (-f32::from_bits(0x7fc00123)).to_bits(),
+        ),
+        f32_case(f32_binary("div", 0.0, 0.0), 0x7fc00000),
+        i32_case(format!("(f64.eq {} {})", f64_bits(snan), f64_bits(snan)), 0),
+        i32_case(format!("(f64.ne {} {})", f64_bits(snan), f64_bits(snan)), 1),
+    ])
+}
+
+/// min and max order -0 below +0, and are the positive canonical NaN if an operand is NaN.
+#[test]
+fn test_min_max() {
+    check(&[
+        f64_case(f64_binary("min", -0.0, 0.0), (-0.0f64).to_bits()),
+        f64_case(f64_binary("min", 0.0, -0.0), (-0.0f64).to_bits()),
+        f64_case(f64_binary("max", -0.0, 0.0), 0.0f64.to_bits()),
+        f64_case(f64_binary("max", 0.0, -0.0), 0.0f64.to_bits()),
+        f64_case(f64_binary("min", 1.0, 2.0), 1.0f64.min(2.0).to_bits()),
+        f64_case(f64_binary("max", -1.0, -2.0), (-1.0f64).max(-2.0).to_bits()),
+        f64_case(
+            format!(
+                "(f64.min {} {})",
+                f64_bits(0xfff8000000000abc),
+                f64_const(1.0)
+            ),
+            0x7ff8000000000000,
+        ),
+        f64_case(
+            format!(
+                "(f64.max {} {})",
+                f64_const(1.0),
+                f64_bits(0x7ff0000000000001)
+            ),
+            0x7ff8000000000000,
+        ),
+        f32_case(f32_binary("min", -0.0, 0.0), (-0.0f32).to_bits()),
+        f32_case(f32_binary("max", 0.0, -0.0), 0.0f32.to_bits()),
+        f32_case(
+            format!("(f32.min {} {})", f32_bits(0x7fc00001), f32_const(1.0)),
+            0x7fc00000,
+        ),
+    ])
+}
+
+/// nearest rounds ties to even, demote rounds to nearest even and saturates to infinity.
+#[test]
+fn test_rounding() {
+    let f64_unary = |op: &str, v: f64| format!("(f64.{} {})", op, f64_const(v));
+    let f32_unary = |op: &str, v: f32| format!("(f32.{} {})", op, f32_const(v));
+    let demote = |v: f64| {
+        f32_case(
+            format!("(f32.demote_f64 {})", f64_const(v)),
+            (v as f32).to_bits(),
+        )
+    };
+
+    check(&[
+        f64_case(f64_unary("nearest", 2.5), 2.0f64.to_bits()),
+        f64_case(f64_unary("nearest", 3.5), 4.0f64.to_bits()),
+        f64_case(f64_unary("nearest", -2.5), (-2.0f64).to_bits()),
+        f64_case(f64_unary("nearest", -0.5), (-0.0f64).to_bits()),
+        f64_case(f64_unary("nearest", 0.49999999999999994), 0.0f64.to_bits()),
+        f64_case(
+            f64_unary("nearest", 4503599627370497.0),
+            4503599627370497.0f64.to_bits(),
+        ),
+        f32_case(f32_unary("nearest", 2.5), 2.0f32.to_bits()),
+        f32_case(f32_unary("nearest", -1.5), (-2.0f32).to_bits()),
+        f64_case(f64_unary("floor", -0.5), (-0.5f64).floor().to_bits()),
+        f64_case(f64_unary("floor", -0.0), (-0.0f64).floor().to_bits()),
+        f64_case(f64_unary("ceil", -0.5), (-0.5f64).ceil().to_bits()),
+        f64_case(f64_unary("trunc", -0.7), (-0.7f64).trunc().to_bits()),
+        f32_case(f32_unary("ceil", 1.1), 1.1f32.ceil().to_bits()),
+        // 1 + 2^-24 is halfway between 1 and the next f32, and ties to 1.
+        demote(1.0 + f64::powi(2.0, -24)),
+        demote(1.0 + 3.0 * f64::powi(2.0, -24)),
+        demote(1.0 + f64::powi(2.0, -24) + f64::powi(2.0, -52)),
+        demote(f64::MAX),
+        demote(-1e-46),
+        demote(1e-40),
+    ])
+}
+
+#[test]
+fn test_float_conversion() {
+    check(&[
+        i64_case(
+            r#"
+              (i64.const -9007199254740993)
+              (f64.convert_i64_s)
+              (f32.demote_f64)
+              (f64.promote_f32)
+              (i64.trunc_f64_s)"#
+                .to_owned(),
+            -9007199254740993i64 as f64 as f32 as f64 as i64,
+        ),
+        i64_case(
+            r#"
+              (i32.const -1)
+              (f32.convert_i32_u)
+              (f32.nearest)
+              (i64.trunc_f32_u)"#
+                .to_owned(),
+            u32::MAX as f32 as i64,
+        ),
+        i64_case(
+            "(i64.reinterpret_f64 (f64.const 1e300))".to_owned(),
+            1e300f64.to_bits() as i64,
+        ),
+        f32_case(
+            "(f32.convert_i64_u (i64.const -1))".to_owned(),
+            (u64::MAX as f32).to_bits(),
+        ),
+        f64_case(
+            "(f64.convert_i32_s (i32.const -2147483648))".to_owned(),
+            (i32::MIN as f64).to_bits(),
+        ),
+        i32_case(
+            format!("(i32.trunc_f64_s {})", f64_const(-1.9)),
+            -1.9f64 as i32,
+        ),
+    ])
+}
+
+#[test]
+fn test_trap_float_truncation_of_nan() {
+    let execution_result = run(r#"
+              (f32.const nan)
+              (i32.trunc_f32_s)
+              (drop)"#);
+
+    assert_eq!(execution_result.trap, Some(TrapCode::Unreachable));
+    assert_eq!(execution_result.outputs, vec![TrapCode::Unreachable as u64]);
+}
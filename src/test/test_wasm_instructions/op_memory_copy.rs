@@ -0,0 +1,114 @@
+use crate::test::test_circuit_noexternal;
+
+#[test]
+fn test_memory_copy_aligned() {
+    let textual_repr = r#"
+        (module
+            (memory $0 1)
+            (data (i32.const 0) "\01\02\03\04\05\06\07\08\09\0a\0b\0c\0d\0e\0f\10")
+            (func (export "test")
+                (memory.copy (i32.const 64) (i32.const 0) (i32.const 16))
+                (drop (i64.load offset=72 (i32.const 0)))
+            )
+        )
+    "#;
+
+    test_circuit_noexternal(textual_repr).unwrap()
+}
+
+#[test]
+fn test_memory_copy_unaligned() {
+    let textual_repr = r#"
+        (module
+            (memory $0 1)
+            (data (i32.const 0) "\01\02\03\04\05\06\07\08\09\0a\0b\0c\0d\0e\0f\10\11\12\13\14")
+            (func (export "test")
+                (memory.copy (i32.const 67) (i32.const 1) (i32.const 18))
+                (drop (i64.load offset=64 (i32.const 0)))
+            )
+        )
+    "#;
+
+    test_circuit_noexternal(textual_repr).unwrap()
+}
+
+#[test]
+fn test_memory_copy_misaligned_source_forward() {
+    let textual_repr = r#"
+        (module
+            (memory $0 1)
+            (data (i32.const 0) "\01\02\03\04\05\06\07\08\09\0a\0b\0c\0d\0e\0f\10\11\12\13\14\15\16\17\18\19\1a\1b\1c\1d\1e\1f\20")
+            (func (export "test")
+                (memory.copy (i32.const 64) (i32.const 3) (i32.const 24))
+                (drop (i64.load offset=80 (i32.const 0)))
+            )
+        )
+    "#;
+
+    test_circuit_noexternal(textual_repr).unwrap()
+}
+
+#[test]
+fn test_memory_copy_misaligned_source_backward() {
+    let textual_repr = r#"
+        (module
+            (memory $0 1)
+            (data (i32.const 0) "\01\02\03\04\05\06\07\08\09\0a\0b\0c\0d\0e\0f\10\11\12\13\14\15\16\17\18\19\1a\1b\1c\1d\1e\1f\20")
+            (func (export "test")
+                (memory.copy (i32.const 69) (i32.const 2) (i32.const 27))
+                (drop (i64.load offset=72 (i32.const 0)))
+            )
+        )
+    "#;
+
+    test_circuit_noexternal(textual_repr).unwrap()
+}
+
+#[test]
+fn test_memory_copy_overlap_forward() {
+    let textual_repr = r#"
+        (module
+            (memory $0 1)
+            (data (i32.const 0) "\01\02\03\04\05\06\07\08\09\0a\0b\0c\0d\0e\0f\10\11\12\13\14")
+            (func (export "test")
+                (memory.copy (i32.const 0) (i32.const 3) (i32.const 17))
+                (memory.copy (i32.const 0) (i32.const 8) (i32.const 12))
+                (drop (i64.load offset=0 (i32.const 0)))
+            )
+        )
+    "#;
+
+    test_circuit_noexternal(textual_repr).unwrap()
+}
+
+#[test]
+fn test_memory_copy_overlap_backward() {
+    let textual_repr = r#"
+        (module
+            (memory $0 1)
+            (data (i32.const 0) "\01\02\03\04\05\06\07\08\09\0a\0b\0c\0d\0e\0f\10\11\12\13\14")
+            (func (export "test")
+                (memory.copy (i32.const 3) (i32.const 0) (i32.const 17))
+                (memory.copy (i32.const 8) (i32.const 0) (i32.const 12))
+                (drop (i64.load offset=8 (i32.const 0)))
+            )
+        )
+    "#;
+
+    test_circuit_noexternal(textual_repr).unwrap()
+}
+
+#[test]
+fn test_memory_copy_zero_length() {
+    let textual_repr = r#"
+        (module
+            (memory $0 1)
+            (func (export "test")
+                (memory.copy (i32.const 65536) (i32.const 0) (i32.const 0))
+                (memory.copy (i32.const 5) (i32.const 5) (i32.const 0))
+            )
+        )
+    "#;
+
+    test_circuit_noexternal(textual_repr).unwrap()
+}
@@ -0,0 +1,48 @@
+use crate::test::test_circuit_noexternal;
+
+#[test]
+fn test_memory_fill_aligned() {
+    let textual_repr = r#"
+        (module
+            (memory $0 1)
+            (func (export "test")
+                (memory.fill (i32.const 0) (i32.const 0xab) (i32.const 16))
+                (drop (i64.load offset=8 (i32.const 0)))
+            )
+        )
+    "#;
+
+    test_circuit_noexternal(textual_repr).unwrap()
+}
+
+#[test]
+fn test_memory_fill_unaligned() {
+    let textual_repr = r#"
+        (module
+            (memory $0 1)
+            (data (i32.const 0) "\01\02\03\04\05\06\07\08\09\0a\0b\0c\0d\0e\0f\10\11\12\13\14")
+            (func (export "test")
+                (memory.fill (i32.const 3) (i32.const 0x1ff) (i32.const 14))
+                (drop (i32.load offset=0 (i32.const 0)))
+                (drop (i64.load offset=12 (i32.const 0)))
+            )
+        )
+    "#;
+
+    test_circuit_noexternal(textual_repr).unwrap()
+}
+
+#[test]
+fn test_memory_fill_zero_length() {
+    let textual_repr = r#"
+        (module
+            (memory $0 1)
+            (func (export "test")
+                (memory.fill (i32.const 65536) (i32.const 1) (i32.const 0))
+                (memory.fill (i32.const 5) (i32.const 1) (i32.const 0))
+            )
+        )
+    "#;
+
+    test_circuit_noexternal(textual_repr).unwrap()
+}
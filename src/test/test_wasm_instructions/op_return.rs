@@ -42,3 +42,31 @@ fn test_return_with_keep_ok() {
 
     test_circuit_noexternal(textual_repr).unwrap();
 }
+
+#[test]
+fn test_return_multi_value_ok() {
+    let textual_repr = r#"
+            (module
+                (func $pair (param i32) (result i32 i64)
+                  (block
+                    (local.get 0)
+                    (i64.const 1)
+                    return
+                  )
+                  (i32.const 2)
+                  (i64.const 3)
+                )
+
+                (func (export "test") (result i64)
+                  (call $pair (i32.const 0))
+                  drop
+                  drop
+                  (call $pair (i32.const 4))
+                  (drop)
+                  (i64.extend_i32_u)
+                )
+               )
+            "#;
+
+    test_circuit_noexternal(textual_repr).unwrap();
+}
@@ -0,0 +1,163 @@
+use crate::test::test_circuit_noexternal;
+
+#[test]
+fn test_unreachable() {
+    let textual_repr = r#"
+        (module
+            (func (export "test")
+              (unreachable)
+            )
+        )
+    "#;
+
+    test_circuit_noexternal(textual_repr).unwrap()
+}
+
+#[test]
+fn test_unreachable_in_nested_call() {
+    let textual_repr = r#"
+        (module
+            (func $inner
+              (unreachable)
+            )
+
+            (func $middle
+              (call $inner)
+            )
+
+            (func (export "test")
+              (call $middle)
+            )
+        )
+    "#;
+
+    test_circuit_noexternal(textual_repr).unwrap()
+}
+
+#[test]
+fn test_trap_division_by_zero() {
+    let textual_repr = r#"
+        (module
+            (func (export "test")
+              (drop (i32.div_u (i32.const 1) (i32.const 0)))
+            )
+        )
+    "#;
+
+    test_circuit_noexternal(textual_repr).unwrap();
+
+    let textual_repr = r#"
+        (module
+            (func (export "test")
+              (drop (i64.rem_s (i64.const 1) (i64.const 0)))
+            )
+        )
+    "#;
+
+    test_circuit_noexternal(textual_repr).unwrap()
+}
+
+#[test]
+fn test_trap_load_out_of_bounds() {
+    let textual_repr = r#"
+        (module
+            (memory $0 1)
+            (func (export "test")
+              (drop (i64.load offset=4 (i32.const 65530)))
+            )
+        )
+    "#;
+
+    test_circuit_noexternal(textual_repr).unwrap()
+}
+
+#[test]
+fn test_trap_store_out_of_bounds() {
+    let textual_repr = r#"
+        (module
+            (memory $0 1)
+            (func (export "test")
+              (i32.store8 (i32.const 65536) (i32.const 1))
+            )
+        )
+    "#;
+
+    test_circuit_noexternal(textual_repr).unwrap()
+}
+
+#[test]
+fn test_trap_integer_overflow() {
+    let textual_repr = r#"
+        (module
+            (func (export "test")
+              (drop (i32.div_s (i32.const 0x80000000) (i32.const -1)))
+            )
+        )
+    "#;
+
+    test_circuit_noexternal(textual_repr).unwrap();
+
+    let textual_repr = r#"
+        (module
+            (func (export "test")
+              (drop (i64.div_s (i64.const 0x8000000000000000) (i64.const -1)))
+            )
+        )
+    "#;
+
+    test_circuit_noexternal(textual_repr).unwrap()
+}
+
+#[test]
+fn test_trap_memory_fill_out_of_bounds() {
+    let textual_repr = r#"
+        (module
+            (memory $0 1)
+            (func (export "test")
+              (memory.fill (i32.const 65530) (i32.const 1) (i32.const 7))
+            )
+        )
+    "#;
+
+    test_circuit_noexternal(textual_repr).unwrap()
+}
+
+#[test]
+fn test_trap_memory_copy_out_of_bounds() {
+    let textual_repr = r#"
+        (module
+            (memory $0 1)
+            (func (export "test")
+              (memory.copy (i32.const 0) (i32.const 65535) (i32.const 2))
+            )
+        )
+    "#;
+
+    test_circuit_noexternal(textual_repr).unwrap();
+
+    let textual_repr = r#"
+        (module
+            (memory $0 1)
+            (func (export "test")
+              (memory.copy (i32.const 65537) (i32.const 0) (i32.const 0))
+            )
+        )
+    "#;
+
+    test_circuit_noexternal(textual_repr).unwrap()
+}
+
+#[test]
+fn test_trap_call_indirect_is_rejected() {
+    let textual_repr = r#"
+        (module
+            (type $t (func))
+            (table 1 funcref)
+            (func (export "test")
+              (call_indirect (type $t) (i32.const 1))
+            )
+        )
+    "#;
+
+    assert!(test_circuit_noexternal(textual_repr).is_err())
+}